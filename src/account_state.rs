@@ -0,0 +1,178 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use casper_types::{account::AccountHash, crypto::AsymmetricType, PublicKey};
+use serde_json::Value;
+
+use super::{balance, Error};
+
+/// The length of a hex-encoded block hash.
+const BLOCK_HASH_HEX_LENGTH: usize = 64;
+
+/// A block identified by its hash or height, after which an account's state is read.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BlockIdentifier {
+    /// The hex-encoded block hash.
+    Hash(String),
+    Height(u64),
+}
+
+impl FromStr for BlockIdentifier {
+    type Err = Error;
+
+    /// Parses a block height, e.g. `1234`, or a hex-encoded block hash.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Ok(height) = value.parse::<u64>() {
+            return Ok(BlockIdentifier::Height(height));
+        }
+        if value.len() == BLOCK_HASH_HEX_LENGTH
+            && value.chars().all(|char| char.is_ascii_hexdigit())
+        {
+            return Ok(BlockIdentifier::Hash(value.to_lowercase()));
+        }
+        Err(Error::ParseBlockIdentifier {
+            value: value.to_string(),
+        })
+    }
+}
+
+impl Display for BlockIdentifier {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            BlockIdentifier::Hash(hash) => write!(formatter, "{}", hash),
+            BlockIdentifier::Height(height) => write!(formatter, "{}", height),
+        }
+    }
+}
+
+/// An account's associated keys and action thresholds, as held in global state.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AccountState {
+    /// The associated keys and their weights.
+    pub associated_keys: Vec<(AccountHash, u8)>,
+    pub key_management_threshold: u8,
+    pub deployment_threshold: u8,
+}
+
+impl AccountState {
+    /// Parses the JSON representation of an account, as returned by `query-global-state`.
+    fn from_json(account: &Value) -> Result<Self, String> {
+        let weight = |value: &Value, what: &str| {
+            value
+                .as_u64()
+                .filter(|weight| *weight <= u64::from(u8::MAX))
+                .map(|weight| weight as u8)
+                .ok_or_else(|| format!("the account has no valid {}", what))
+        };
+
+        let associated_keys = account["associated_keys"]
+            .as_array()
+            .ok_or("the account has no associated keys")?
+            .iter()
+            .map(|key| {
+                let account_hash = key["account_hash"]
+                    .as_str()
+                    .and_then(|account_hash| AccountHash::from_formatted_str(account_hash).ok())
+                    .ok_or("the account has an associated key with no valid account hash")?;
+                Ok((
+                    account_hash,
+                    weight(&key["weight"], "associated key weight")?,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let thresholds = &account["action_thresholds"];
+        Ok(AccountState {
+            associated_keys,
+            key_management_threshold: weight(
+                &thresholds["key_management"],
+                "key-management threshold",
+            )?,
+            deployment_threshold: weight(&thresholds["deployment"], "deployment threshold")?,
+        })
+    }
+
+    /// Returns the weight of the given associated key, or `None` if it isn't one.
+    pub fn weight_of(&self, account_hash: &AccountHash) -> Option<u8> {
+        self.associated_keys
+            .iter()
+            .find(|(associated_key, _)| associated_key == account_hash)
+            .map(|(_, weight)| *weight)
+    }
+
+    /// Returns the combined weight of those of `signers` which are associated keys.
+    pub fn signing_weight(&self, signers: &[AccountHash]) -> u32 {
+        self.associated_keys
+            .iter()
+            .filter(|(account_hash, _)| signers.contains(account_hash))
+            .map(|(_, weight)| u32::from(*weight))
+            .sum()
+    }
+
+    /// Returns `true` if a deploy signed by `signers` would meet the deployment threshold.
+    pub fn can_deploy(&self, signers: &[AccountHash]) -> bool {
+        self.signing_weight(signers) >= u32::from(self.deployment_threshold)
+    }
+
+    /// Returns `true` if a deploy signed by `signers` could change the associated keys or action
+    /// thresholds, which needs it to meet both thresholds.
+    pub fn can_manage_keys(&self, signers: &[AccountHash]) -> bool {
+        self.can_deploy(signers)
+            && self.signing_weight(signers) >= u32::from(self.key_management_threshold)
+    }
+}
+
+impl Display for AccountState {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        writeln!(formatter, "Associated keys:")?;
+        for (account_hash, weight) in &self.associated_keys {
+            writeln!(
+                formatter,
+                "  {} weight {}",
+                account_hash.to_formatted_string(),
+                weight
+            )?;
+        }
+        writeln!(
+            formatter,
+            "Key management threshold: {}",
+            self.key_management_threshold
+        )?;
+        write!(
+            formatter,
+            "Deployment threshold: {}",
+            self.deployment_threshold
+        )
+    }
+}
+
+/// Returns the associated keys and action thresholds of the given account in the latest global
+/// state, as reported by the node at `node_address`.
+pub(super) fn fetch(node_address: &str, account: &PublicKey) -> Result<AccountState, String> {
+    fetch_at(node_address, &account.to_hex(), None)
+}
+
+/// Returns the associated keys and action thresholds of the account with the given hex-encoded
+/// public key or formatted account hash, in the global state after `block`, or the latest if
+/// `None`.
+pub(super) fn fetch_at(
+    node_address: &str,
+    account: &str,
+    block: Option<&BlockIdentifier>,
+) -> Result<AccountState, String> {
+    let account = account.trim();
+    if PublicKey::from_hex(account).is_err() && AccountHash::from_formatted_str(account).is_err() {
+        return Err(format!(
+            "'{}' is neither a hex-encoded public key nor a formatted account hash",
+            account
+        ));
+    }
+    let block = block.map(BlockIdentifier::to_string);
+    AccountState::from_json(&balance::query_account(
+        node_address,
+        account,
+        block.as_deref(),
+    )?)
+}
@@ -0,0 +1,127 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use casper_types::{account::AccountHash, crypto::AsymmetricType, PublicKey};
+use serde_json::{json, Value};
+
+/// The name of the file holding the address book, under the tool's config dir.
+const FILE_NAME: &str = "address_book.json";
+
+/// A known signer's key, saved so it can be added to other configurations without re-entering it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AddressBookEntry {
+    /// The name the signer is known by, e.g. `Alice (Ledger)`.
+    pub label: String,
+    /// The formatted account hash added as an associated key.
+    pub account_hash: String,
+    /// The hex-encoded public key, if the key was saved as one.
+    pub public_key: Option<String>,
+}
+
+impl AddressBookEntry {
+    /// Returns an entry for `key`, given as a hex-encoded public key or a formatted account hash.
+    pub(super) fn new(label: &str, key: &str) -> Result<Self, String> {
+        let label = label.trim();
+        if label.is_empty() || label.contains('\n') {
+            return Err("the label must not be empty or span several lines".to_string());
+        }
+        let key = key.trim();
+        let (account_hash, public_key) = match AccountHash::from_formatted_str(key) {
+            Ok(account_hash) => (account_hash, None),
+            Err(_) => {
+                let public_key = PublicKey::from_hex(key).map_err(|error| {
+                    format!(
+                        "'{}' is neither a hex-encoded public key nor a formatted account hash: {}",
+                        key, error
+                    )
+                })?;
+                (public_key.to_account_hash(), Some(public_key.to_hex()))
+            }
+        };
+        Ok(AddressBookEntry {
+            label: label.to_string(),
+            account_hash: account_hash.to_formatted_string(),
+            public_key,
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "label": self.label,
+            "account_hash": self.account_hash,
+            "public_key": self.public_key,
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let label = value["label"]
+            .as_str()
+            .ok_or("an entry has no 'label' string")?;
+        let account_hash = value["account_hash"]
+            .as_str()
+            .ok_or_else(|| format!("entry '{}' has no 'account_hash' string", label))?;
+        let entry = match value["public_key"].as_str() {
+            Some(public_key) => AddressBookEntry::new(label, public_key)?,
+            None => AddressBookEntry::new(label, account_hash)?,
+        };
+        if entry.account_hash != account_hash.trim() {
+            return Err(format!(
+                "the public key of entry '{}' doesn't match its account hash",
+                label
+            ));
+        }
+        Ok(entry)
+    }
+}
+
+/// Returns the path of the address book, under the user's config dir, e.g.
+/// `~/.config/casper-multisig-tool/address_book.json` on Linux.
+pub(super) fn file() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join("casper-multisig-tool").join(FILE_NAME))
+}
+
+/// Reads the entries in `file`, or returns none if it doesn't exist yet.
+pub(super) fn load(file: &Path) -> Result<Vec<AddressBookEntry>, String> {
+    if !file.exists() {
+        return Ok(vec![]);
+    }
+    let contents = fs::read_to_string(file).map_err(|error| error.to_string())?;
+    let address_book =
+        serde_json::from_str::<Value>(&contents).map_err(|error| error.to_string())?;
+    address_book["entries"]
+        .as_array()
+        .ok_or("it has no 'entries' list")?
+        .iter()
+        .map(AddressBookEntry::from_json)
+        .collect()
+}
+
+/// Writes the entries to `file`, creating its dir if needed.
+pub(super) fn save(file: &Path, entries: &[AddressBookEntry]) -> Result<(), String> {
+    let address_book = json!({
+        "entries": entries.iter().map(AddressBookEntry::to_json).collect::<Vec<_>>(),
+    });
+    let contents =
+        serde_json::to_string_pretty(&address_book).map_err(|error| error.to_string())?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    fs::write(file, contents).map_err(|error| error.to_string())
+}
+
+/// Adds `entry`, replacing any for the same account hash, and keeps the entries sorted by label.
+///
+/// A public key already known for the account hash is kept if `entry` only has the account hash.
+pub(super) fn insert(entries: &mut Vec<AddressBookEntry>, mut entry: AddressBookEntry) {
+    if let Some(index) = entries
+        .iter()
+        .position(|existing| existing.account_hash == entry.account_hash)
+    {
+        let existing = entries.remove(index);
+        entry.public_key = entry.public_key.or(existing.public_key);
+    }
+    entries.push(entry);
+    entries.sort_by_key(|entry| entry.label.to_lowercase());
+}
@@ -0,0 +1,130 @@
+use std::fmt::{self, Display, Formatter};
+
+use casper_node::types::Deploy;
+use casper_types::account::AccountHash;
+
+use super::{account_state::AccountState, deploy};
+
+/// How far a deploy's valid approvals go towards meeting its account's deployment threshold.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ApprovalProgress {
+    /// The combined weight of the associated keys which have signed.
+    pub collected_weight: u32,
+    pub deployment_threshold: u8,
+    /// The associated keys which have signed, with their weights.
+    pub signed: Vec<(AccountHash, u8)>,
+    /// The associated keys which haven't signed yet, with their weights, heaviest first.
+    pub unsigned: Vec<(AccountHash, u8)>,
+    /// The signers of valid approvals who aren't associated keys of the account, which would cause
+    /// the node to reject the deploy.
+    pub unassociated: Vec<AccountHash>,
+}
+
+impl ApprovalProgress {
+    /// Tallies the deploy's approvals which are valid signatures of its hash against `account`.
+    pub(super) fn new(deploy: &Deploy, account: &AccountState) -> Self {
+        let signers = deploy
+            .approvals()
+            .iter()
+            .filter(|approval| {
+                deploy::verify_approval(deploy, approval.signer(), approval.signature()).is_ok()
+            })
+            .map(|approval| approval.signer().to_account_hash())
+            .collect::<Vec<_>>();
+
+        let (signed, mut unsigned): (Vec<_>, Vec<_>) = account
+            .associated_keys
+            .iter()
+            .copied()
+            .partition(|(account_hash, _)| signers.contains(account_hash));
+        unsigned.sort_by(|(_, weight1), (_, weight2)| weight2.cmp(weight1));
+        let unassociated = signers
+            .into_iter()
+            .filter(|signer| account.weight_of(signer).is_none())
+            .collect();
+
+        ApprovalProgress {
+            collected_weight: signed.iter().map(|(_, weight)| u32::from(*weight)).sum(),
+            deployment_threshold: account.deployment_threshold,
+            signed,
+            unsigned,
+            unassociated,
+        }
+    }
+
+    /// Returns `true` if the collected weight meets the deployment threshold.
+    pub fn is_complete(&self) -> bool {
+        self.remaining_weight() == 0
+    }
+
+    /// Returns the further weight needed to meet the deployment threshold.
+    pub fn remaining_weight(&self) -> u32 {
+        u32::from(self.deployment_threshold).saturating_sub(self.collected_weight)
+    }
+
+    /// Returns the unsigned associated keys which could each complete the approvals alone.
+    pub fn completing_signers(&self) -> Vec<(AccountHash, u8)> {
+        self.unsigned
+            .iter()
+            .copied()
+            .filter(|(_, weight)| u32::from(*weight) >= self.remaining_weight())
+            .collect()
+    }
+}
+
+impl Display for ApprovalProgress {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let list = |keys: &[(AccountHash, u8)]| {
+            keys.iter()
+                .map(|(account_hash, weight)| {
+                    format!("{} (weight {})", account_hash.to_formatted_string(), weight)
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        write!(
+            formatter,
+            "Collected weight {} of the deployment threshold {}",
+            self.collected_weight, self.deployment_threshold
+        )?;
+        if self.is_complete() {
+            write!(formatter, ": the deployment threshold is met")?;
+        } else {
+            let completing_signers = self.completing_signers();
+            let available_weight: u32 = self
+                .unsigned
+                .iter()
+                .map(|(_, weight)| u32::from(*weight))
+                .sum();
+            if !completing_signers.is_empty() {
+                write!(
+                    formatter,
+                    "\nAny one of these signers could complete it: {}",
+                    list(&completing_signers)
+                )?;
+            } else if available_weight >= self.remaining_weight() {
+                write!(
+                    formatter,
+                    "\n{} more weight is needed from these signers: {}",
+                    self.remaining_weight(),
+                    list(&self.unsigned)
+                )?;
+            } else {
+                write!(
+                    formatter,
+                    "\nThe remaining signers' weight of {} can't complete it",
+                    available_weight
+                )?;
+            }
+        }
+        for signer in &self.unassociated {
+            write!(
+                formatter,
+                "\nWARNING: {} isn't an associated key, so the node will reject the deploy",
+                signer.to_formatted_string()
+            )?;
+        }
+        Ok(())
+    }
+}
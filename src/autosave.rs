@@ -0,0 +1,105 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use super::key_configuration::KeyConfiguration;
+
+/// The name of the file holding the autosaved configurations, under the tool's config dir.
+const FILE_NAME: &str = "autosave.json";
+
+/// The version of the autosave file format written.
+const FORMAT_VERSION: u64 = 1;
+
+/// A configuration tab as it was when last autosaved, so it can be restored if the tool was closed
+/// by accident.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AutosavedTab {
+    /// The tab's label.
+    pub name: String,
+    pub configuration: KeyConfiguration,
+    /// The root dir of the project which will hold the smart contract, or empty if not chosen yet.
+    pub project_path: PathBuf,
+    /// The smart contract's name, or empty if not chosen yet.
+    pub contract_name: String,
+}
+
+impl AutosavedTab {
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "configuration": self.configuration.to_json(),
+            "project_path": self.project_path.display().to_string(),
+            "contract_name": self.contract_name,
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let name = value["name"].as_str().ok_or("a tab has no 'name' string")?;
+        let configuration = KeyConfiguration::from_json(&value["configuration"])
+            .map_err(|error| format!("tab '{}': {}", name, error))?;
+        Ok(AutosavedTab {
+            name: name.to_string(),
+            configuration,
+            project_path: PathBuf::from(value["project_path"].as_str().unwrap_or_default()),
+            contract_name: value["contract_name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+}
+
+/// Returns the path of the autosave file, under the user's config dir, e.g.
+/// `~/.config/casper-multisig-tool/autosave.json` on Linux.
+pub(super) fn file() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join("casper-multisig-tool").join(FILE_NAME))
+}
+
+/// Reads the tabs in `file`, or returns none if it doesn't exist.
+pub(super) fn load(file: &Path) -> Result<Vec<AutosavedTab>, String> {
+    if !file.exists() {
+        return Ok(vec![]);
+    }
+    let contents = fs::read_to_string(file).map_err(|error| error.to_string())?;
+    let autosave = serde_json::from_str::<Value>(&contents).map_err(|error| error.to_string())?;
+    if autosave["version"].as_u64() != Some(FORMAT_VERSION) {
+        return Err(format!(
+            "only version {} autosaves are supported",
+            FORMAT_VERSION
+        ));
+    }
+    autosave["tabs"]
+        .as_array()
+        .ok_or("it has no 'tabs' list")?
+        .iter()
+        .map(AutosavedTab::from_json)
+        .collect()
+}
+
+/// Writes the tabs to `file`, creating its dir if needed.
+///
+/// The file is replaced in one step, so it's never left half-written if the tool is killed.
+pub(super) fn save(file: &Path, tabs: &[AutosavedTab]) -> Result<(), String> {
+    let autosave = json!({
+        "version": FORMAT_VERSION,
+        "tabs": tabs.iter().map(AutosavedTab::to_json).collect::<Vec<_>>(),
+    });
+    let contents = serde_json::to_string_pretty(&autosave).map_err(|error| error.to_string())?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    let temp_file = file.with_extension("json.tmp");
+    fs::write(&temp_file, contents).map_err(|error| error.to_string())?;
+    fs::rename(&temp_file, file).map_err(|error| error.to_string())
+}
+
+/// Removes `file` if it exists.
+pub(super) fn discard(file: &Path) -> Result<(), String> {
+    if !file.exists() {
+        return Ok(());
+    }
+    fs::remove_file(file).map_err(|error| error.to_string())
+}
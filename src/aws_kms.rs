@@ -0,0 +1,125 @@
+//! Signing with secp256k1 keys held in AWS KMS, via the AWS CLI.
+//!
+//! The CLI takes its credentials and default region from its usual config files and environment
+//! variables, e.g. `AWS_PROFILE`.
+
+use std::{env, fs, process, process::Command};
+
+use casper_types::{crypto::AsymmetricType, PublicKey, Signature};
+use k256::{ecdsa::VerifyingKey, pkcs8::FromPublicKey};
+use serde_json::Value;
+
+use super::remote_signer::RemoteSigner;
+
+/// The KMS key spec of secp256k1 keys, the only kind KMS holds which Casper supports.
+const KEY_SPEC: &str = "ECC_SECG_P256K1";
+
+/// The signing algorithm matching Casper's secp256k1 signatures, which sign the message's SHA-256
+/// digest.
+const SIGNING_ALGORITHM: &str = "ECDSA_SHA_256";
+
+/// A secp256k1 key held in AWS KMS.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AwsKmsSigner {
+    /// The key's ID, ARN, alias name or alias ARN.
+    pub key_id: String,
+    /// The region holding the key, or `None` to use the AWS CLI's default.
+    pub region: Option<String>,
+}
+
+impl AwsKmsSigner {
+    pub fn new(key_id: &str, region: Option<&str>) -> Self {
+        AwsKmsSigner {
+            key_id: key_id.trim().to_string(),
+            region: region
+                .map(str::trim)
+                .filter(|region| !region.is_empty())
+                .map(str::to_string),
+        }
+    }
+
+    /// Runs `aws kms <subcommand>` against the key with the given further args, returning the
+    /// parsed JSON output.
+    fn run(&self, subcommand: &str, args: &[&str]) -> Result<Value, String> {
+        let mut command = Command::new("aws");
+        command
+            .args(&["kms", subcommand, "--key-id", &self.key_id])
+            .args(args)
+            .args(&["--output", "json"]);
+        if let Some(region) = &self.region {
+            command.args(&["--region", region]);
+        }
+        let output = command
+            .output()
+            .map_err(|error| format!("failed to run the AWS CLI: {}", error))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        serde_json::from_slice(&output.stdout)
+            .map_err(|error| format!("failed to parse the AWS CLI's output: {}", error))
+    }
+}
+
+impl RemoteSigner for AwsKmsSigner {
+    fn description(&self) -> String {
+        format!("AWS KMS key {}", self.key_id)
+    }
+
+    fn public_key(&self) -> Result<PublicKey, String> {
+        let response = self.run("get-public-key", &[])?;
+        let key_spec = response["KeySpec"]
+            .as_str()
+            .or_else(|| response["CustomerMasterKeySpec"].as_str())
+            .unwrap_or_default();
+        if key_spec != KEY_SPEC {
+            return Err(format!(
+                "the key is {} rather than {}, which is the only kind supported",
+                key_spec, KEY_SPEC
+            ));
+        }
+        let der = base64_field(&response, "PublicKey")?;
+        let verifying_key = VerifyingKey::from_public_key_der(&der)
+            .map_err(|error| format!("failed to parse the public key: {}", error))?;
+        PublicKey::secp256k1_from_bytes(verifying_key.to_bytes().as_slice())
+            .map_err(|error| format!("invalid public key: {}", error))
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, String> {
+        // The CLI only reads binary args from files.
+        let message_file = env::temp_dir()
+            .join("casper-multisig-tool")
+            .join(format!("kms-message-{}.bin", process::id()));
+        fs::create_dir_all(message_file.parent().unwrap()).map_err(|error| error.to_string())?;
+        fs::write(&message_file, message).map_err(|error| error.to_string())?;
+        let response = self.run(
+            "sign",
+            &[
+                "--message",
+                &format!("fileb://{}", message_file.display()),
+                "--message-type",
+                "RAW",
+                "--signing-algorithm",
+                SIGNING_ALGORITHM,
+            ],
+        );
+        let _ = fs::remove_file(&message_file);
+
+        let der = base64_field(&response?, "Signature")?;
+        let mut signature = k256::ecdsa::Signature::from_asn1(&der)
+            .map_err(|error| format!("failed to parse the signature: {}", error))?;
+        // KMS may return a high-S signature, which the node's verification rejects.
+        signature
+            .normalize_s()
+            .map_err(|error| format!("invalid signature: {}", error))?;
+        Signature::secp256k1_from_bytes(signature.as_ref())
+            .map_err(|error| format!("invalid signature: {}", error))
+    }
+}
+
+/// Decodes the base64-encoded string field `name` of the AWS CLI's output.
+fn base64_field(response: &Value, name: &str) -> Result<Vec<u8>, String> {
+    response[name]
+        .as_str()
+        .and_then(|value| base64::decode(value).ok())
+        .ok_or_else(|| format!("the AWS CLI's output has no valid {}", name))
+}
@@ -0,0 +1,105 @@
+use casper_node::types::Deploy;
+use casper_types::{crypto::AsymmetricType, system::standard_payment::ARG_AMOUNT, PublicKey, U512};
+
+use serde_json::Value;
+
+use super::rpc;
+
+/// The stored value variants of an account which hold its main purse: `Account` on Casper 1.x
+/// networks and `AddressableEntity` on 2.x networks.
+const ACCOUNT_VARIANTS: [&str; 2] = ["Account", "AddressableEntity"];
+
+/// Returns the amount passed to the deploy's payment code, if it has an `amount` arg.
+pub(super) fn payment_amount(deploy: &Deploy) -> Option<U512> {
+    deploy
+        .payment()
+        .args()
+        .get(ARG_AMOUNT)?
+        .clone()
+        .into_t::<U512>()
+        .ok()
+}
+
+/// Returns the balance in motes of the main purse of the given account, as reported by the node.
+///
+/// Uses `casper-client` to get the latest state root hash, read the account's main purse, and
+/// then read the purse's balance.
+pub(super) fn main_purse_balance(node_address: &str, account: &PublicKey) -> Result<U512, String> {
+    let state_root_hash = state_root_hash(node_address, None)?;
+    let main_purse = query_account_at(node_address, &state_root_hash, &account.to_hex())?
+        ["main_purse"]
+        .as_str()
+        .ok_or("the account has no main purse")?
+        .to_string();
+
+    let balance = rpc::call(
+        node_address,
+        &[
+            "get-balance",
+            "--state-root-hash",
+            &state_root_hash,
+            "--purse-uref",
+            &main_purse,
+        ],
+    )?;
+    balance["balance_value"]
+        .as_str()
+        .and_then(|value| U512::from_dec_str(value).ok())
+        .ok_or_else(|| "no balance in the node's response".to_string())
+}
+
+/// Returns the JSON representation of the given account in the global state after the given
+/// block, or the latest if `None`, holding e.g. its `associated_keys`, `action_thresholds` and
+/// `main_purse`.
+///
+/// `account` is the hex-encoded public key or formatted account hash of the account, and `block`
+/// the hex-encoded hash or the height of a block.
+pub(super) fn query_account(
+    node_address: &str,
+    account: &str,
+    block: Option<&str>,
+) -> Result<Value, String> {
+    query_account_at(
+        node_address,
+        &state_root_hash(node_address, block)?,
+        account,
+    )
+}
+
+fn query_account_at(
+    node_address: &str,
+    state_root_hash: &str,
+    account: &str,
+) -> Result<Value, String> {
+    let mut result = rpc::call(
+        node_address,
+        &[
+            "query-global-state",
+            "--state-root-hash",
+            state_root_hash,
+            "--key",
+            account,
+        ],
+    )?;
+    let stored_value = &mut result["stored_value"];
+    ACCOUNT_VARIANTS
+        .iter()
+        .find(|variant| stored_value.get(**variant).is_some())
+        .map(|variant| stored_value[*variant].take())
+        .ok_or_else(|| "the key doesn't hold an account".to_string())
+}
+
+/// Returns the state root hash after the given block, or the node's latest if `None`.
+fn state_root_hash(node_address: &str, block: Option<&str>) -> Result<String, String> {
+    let result = match block {
+        Some(block) => rpc::call(
+            node_address,
+            &["get-state-root-hash", "--block-identifier", block],
+        )?,
+        None => rpc::call(node_address, &["get-state-root-hash"])?,
+    };
+    result["state_root_hash"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "no state root hash in the node's response".to_string())
+}
@@ -0,0 +1,113 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The package name of the skeleton contract used to pre-warm the shared target dir.
+pub(super) const PREWARM_PACKAGE_NAME: &str = "prewarm";
+
+/// The source of the skeleton contract used to pre-warm the shared target dir.
+pub(super) const PREWARM_MAIN_RS: &str = r#"#![no_main]
+
+#[no_mangle]
+pub extern "C" fn call() {}
+"#;
+
+/// Where cargo writes the build output of the generated projects.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TargetDirLocation {
+    /// Each project's own `target` dir.
+    Project,
+    /// A target dir shared between all generated projects, e.g. under the user's cache dir.
+    Shared { path: PathBuf },
+    /// A target dir shared between all generated projects under the system's temp dir, so that
+    /// the OS eventually cleans it up.
+    Temp,
+}
+
+impl TargetDirLocation {
+    /// Returns the target dir shared between all generated projects, or `None` if each project is
+    /// built in its own `target` dir.
+    pub(super) fn shared_target_dir(&self) -> Option<PathBuf> {
+        match self {
+            TargetDirLocation::Project => None,
+            TargetDirLocation::Shared { path } => Some(path.clone()),
+            TargetDirLocation::Temp => {
+                Some(env::temp_dir().join("casper-multisig-tool").join("target"))
+            }
+        }
+    }
+}
+
+impl Default for TargetDirLocation {
+    /// A target dir shared under the user's cache dir if there is one, else each project's own.
+    fn default() -> Self {
+        match default_shared_target_dir() {
+            Some(path) => TargetDirLocation::Shared { path },
+            None => TargetDirLocation::Project,
+        }
+    }
+}
+
+/// Returns the default shared cargo target dir, under the user's cache dir.
+pub(super) fn default_shared_target_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|cache_dir| cache_dir.join("casper-multisig-tool").join("target"))
+}
+
+/// Removes the shared target dir at `path`, if it exists.
+///
+/// As the path is user-configured, it is only removed if it looks like a cargo target dir, i.e.
+/// it contains the `CACHEDIR.TAG` or `.rustc_info.json` cargo writes there.
+///
+/// Returns `Ok(true)` if it was removed, or an error message.
+pub(super) fn remove_shared_target_dir(path: &Path) -> Result<bool, String> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let is_target_dir = ["CACHEDIR.TAG", ".rustc_info.json"]
+        .iter()
+        .any(|file| path.join(file).is_file());
+    if !is_target_dir {
+        return Err("it doesn't look like a cargo target dir".to_string());
+    }
+    fs::remove_dir_all(path).map_err(|error| error.to_string())?;
+    Ok(true)
+}
+
+/// Returns the dir of the skeleton project used to pre-warm the given shared target dir.
+pub(super) fn prewarm_project_dir(shared_target_dir: &Path) -> PathBuf {
+    shared_target_dir.join("prewarm-project")
+}
+
+/// Returns `true` if `sccache` can be run.
+pub(super) fn sccache_available() -> bool {
+    Command::new("sccache")
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Returns the path of the contract's Wasm as built into the given target dir.
+pub(super) fn wasm_path_in(target_dir: &Path, contract_name: &str) -> PathBuf {
+    target_dir
+        .join("wasm32-unknown-unknown")
+        .join("release")
+        .join(format!("{}.wasm", contract_name))
+}
+
+/// Configures `command`, which runs cargo on the host, to use the given shared target dir and
+/// optionally sccache.
+pub(super) fn apply_to_host_command(
+    command: &mut Command,
+    shared_target_dir: Option<&Path>,
+    use_sccache: bool,
+) {
+    if let Some(target_dir) = shared_target_dir {
+        command.env("CARGO_TARGET_DIR", target_dir);
+    }
+    if use_sccache {
+        command.env("RUSTC_WRAPPER", "sccache");
+    }
+}
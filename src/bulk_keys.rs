@@ -0,0 +1,76 @@
+use casper_types::crypto::AsymmetricType;
+
+use super::pasted_key::PastedKey;
+
+/// One key entered in the bulk-add dialog.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BulkKey {
+    /// The line number of the key in the entered text, starting from 1.
+    pub line: usize,
+    /// The label given before the key, if any.
+    pub label: Option<String>,
+    /// The formatted account hash of the key.
+    pub account_hash: String,
+    /// The hex-encoded public key, if the key was entered as one.
+    pub public_key: Option<String>,
+    /// The weight given after the key, if any.
+    pub weight: Option<u8>,
+}
+
+/// Parses `text` as one key per line, each either on its own or as `label,key` or
+/// `label,key,weight`.
+///
+/// Blank lines and lines starting with `#` are skipped.  Each other line yields a `BulkKey` or an
+/// error with its line number, including if it repeats the key of an earlier line.
+pub(super) fn parse(text: &str) -> Vec<Result<BulkKey, (usize, String)>> {
+    let mut results: Vec<Result<BulkKey, (usize, String)>> = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let result = parse_line(line_number, line).and_then(|bulk_key| {
+            let earlier = results
+                .iter()
+                .flatten()
+                .find(|earlier| earlier.account_hash == bulk_key.account_hash);
+            match earlier {
+                Some(earlier) => Err(format!("the same key as line {}", earlier.line)),
+                None => Ok(bulk_key),
+            }
+        });
+        results.push(result.map_err(|error| (line_number, error)));
+    }
+    results
+}
+
+fn parse_line(line_number: usize, line: &str) -> Result<BulkKey, String> {
+    let mut fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+    // A label may itself contain commas, so the key and weight are taken from the end.
+    let weight = if fields.len() >= 3 {
+        let weight = fields.pop().unwrap_or_default();
+        Some(
+            weight
+                .parse::<u8>()
+                .map_err(|_| format!("the weight '{}' isn't a number from 0 to 255", weight))?,
+        )
+    } else {
+        None
+    };
+    let key = fields.pop().unwrap_or_default();
+    let label = Some(fields.join(",").trim().to_string()).filter(|label| !label.is_empty());
+
+    let pasted_key = PastedKey::parse(key)?;
+    let public_key = match &pasted_key {
+        PastedKey::PublicKey(public_key) => Some(public_key.to_hex()),
+        PastedKey::AccountHash(_) => None,
+    };
+    Ok(BulkKey {
+        line: line_number,
+        label,
+        account_hash: pasted_key.account_hash().to_formatted_string(),
+        public_key,
+        weight,
+    })
+}
@@ -0,0 +1,73 @@
+use std::{
+    env, fs, io,
+    path::Path,
+    process::{self, Command},
+};
+
+/// The directory holding the contract crate in a project scaffolded by cargo-casper, relative to
+/// the project dir.
+pub(super) const CONTRACT_DIR: &str = "contract";
+
+/// Returns `true` if `cargo casper` can be run.
+pub(super) fn available() -> bool {
+    Command::new("cargo")
+        .args(&["casper", "--version"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Scaffolds the project at `project_dir` with `cargo casper`, unless its contract crate already
+/// exists.
+///
+/// cargo-casper refuses to write into an existing directory, so the template is generated in a
+/// temporary directory and its files copied across, leaving any already in the project untouched.
+/// The generated files then replace the template's contract source, manifests, tests and Makefile.
+pub(super) fn scaffold(project_dir: &Path) -> Result<(), String> {
+    if project_dir.join(CONTRACT_DIR).join("Cargo.toml").exists() {
+        return Ok(());
+    }
+
+    let scaffold_dir = env::temp_dir()
+        .join("casper-multisig-tool")
+        .join(format!("scaffold-{}", process::id()));
+    let _ = fs::remove_dir_all(&scaffold_dir);
+    fs::create_dir_all(&scaffold_dir).map_err(|error| error.to_string())?;
+
+    let project_name = project_dir
+        .file_name()
+        .ok_or_else(|| format!("{} has no file name", project_dir.display()))?;
+    let output = Command::new("cargo")
+        .arg("casper")
+        .arg(project_name)
+        .current_dir(&scaffold_dir)
+        .output()
+        .map_err(|error| format!("failed to run cargo casper: {}", error))?;
+    let result = if output.status.success() {
+        copy_missing(&scaffold_dir.join(project_name), project_dir)
+            .map_err(|error| format!("failed to copy the template: {}", error))
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    };
+
+    let _ = fs::remove_dir_all(&scaffold_dir);
+    result
+}
+
+/// Recursively copies the files under `source` to `destination`, skipping any which already exist
+/// there, and any git repository.
+fn copy_missing(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let destination_path = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            if entry.file_name() != ".git" {
+                copy_missing(&entry.path(), &destination_path)?;
+            }
+        } else if !destination_path.exists() {
+            fs::copy(entry.path(), &destination_path)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+/// The first line of every generated CI workflow, used to recognise them.
+pub(super) const HEADER: &str = "# Generated by the Casper Multisig Account Creation Tool.";
+
+/// The file listing the Blake2b hashes of the stripped Wasm files, uploaded with them.
+const HASHES_FILE: &str = "wasm-hashes.txt";
+
+/// The CI service for which a workflow building the contract is generated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CiProvider {
+    /// A GitHub Actions workflow in `.github/workflows`.
+    GitHubActions,
+    /// A GitLab CI pipeline in `.gitlab-ci.yml`.
+    GitLabCi,
+}
+
+impl CiProvider {
+    /// All the providers.
+    pub(super) const ALL: [CiProvider; 2] = [CiProvider::GitHubActions, CiProvider::GitLabCi];
+
+    /// Returns the path of the workflow file relative to the project dir.
+    pub(super) fn workflow_path(&self) -> PathBuf {
+        match self {
+            CiProvider::GitHubActions => PathBuf::from(".github")
+                .join("workflows")
+                .join("build-contract.yml"),
+            CiProvider::GitLabCi => PathBuf::from(".gitlab-ci.yml"),
+        }
+    }
+}
+
+impl Display for CiProvider {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            CiProvider::GitHubActions => write!(formatter, "GitHub Actions"),
+            CiProvider::GitLabCi => write!(formatter, "GitLab CI"),
+        }
+    }
+}
+
+/// The settings for a generated CI workflow.
+pub(super) struct WorkflowConfig<'a> {
+    /// The path of the contract crate's dir relative to the project dir, followed by a `/`, or an
+    /// empty string if they're the same.
+    pub(super) contract_dir_prefix: &'a str,
+    pub(super) toolchain: &'a str,
+    /// The extra args passed to `cargo build`.
+    pub(super) build_args: &'a [&'a str],
+    /// The paths of the compiled Wasm files relative to the contract crate's dir.
+    pub(super) wasm_paths: Vec<String>,
+    pub(super) run_wasm_opt: bool,
+    /// The name under which the stripped Wasm files and their hashes are uploaded.
+    pub(super) artifact_name: &'a str,
+}
+
+impl<'a> WorkflowConfig<'a> {
+    /// Returns the commands which build, shrink, strip and hash the contract, run from the contract
+    /// crate's dir.
+    fn commands(&self) -> Vec<String> {
+        let mut commands = vec![];
+        let build_args = self
+            .build_args
+            .iter()
+            .map(|arg| format!(" {}", arg))
+            .collect::<String>();
+        if self.build_args.contains(&"--offline") {
+            commands.push("cargo fetch --locked".to_string());
+        }
+        commands.push(format!("cargo build --release{}", build_args));
+        if self.run_wasm_opt {
+            for wasm_path in &self.wasm_paths {
+                commands.push(format!("wasm-opt -Oz -o {0} {0}", wasm_path));
+            }
+        }
+        commands.push(format!("wasm-strip {}", self.wasm_paths.join(" ")));
+        commands.push(format!(
+            "b2sum --length 256 {} | tee {}",
+            self.wasm_paths.join(" "),
+            HASHES_FILE
+        ));
+        commands
+    }
+
+    /// Returns the paths of the uploaded files relative to the project dir.
+    fn artifact_paths(&self) -> Vec<String> {
+        self.wasm_paths
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(HASHES_FILE))
+            .map(|path| format!("{}{}", self.contract_dir_prefix, path))
+            .collect()
+    }
+
+    /// Returns the Debian packages providing the tools run after the build.
+    fn packages(&self) -> &'static str {
+        if self.run_wasm_opt {
+            "wabt binaryen"
+        } else {
+            "wabt"
+        }
+    }
+}
+
+/// Returns the contents of the workflow file for the given provider.
+pub(super) fn workflow_contents(provider: CiProvider, config: &WorkflowConfig) -> String {
+    match provider {
+        CiProvider::GitHubActions => github_actions_contents(config),
+        CiProvider::GitLabCi => gitlab_ci_contents(config),
+    }
+}
+
+fn github_actions_contents(config: &WorkflowConfig) -> String {
+    let working_directory = match config.contract_dir_prefix.strip_suffix('/') {
+        Some(contract_dir) => format!(
+            "    defaults:\n      run:\n        working-directory: {}\n",
+            contract_dir
+        ),
+        None => String::new(),
+    };
+    let steps = config
+        .commands()
+        .iter()
+        .map(|command| format!("          {}\n", command))
+        .collect::<String>();
+    let artifact_paths = config
+        .artifact_paths()
+        .iter()
+        .map(|path| format!("            {}\n", path))
+        .collect::<String>();
+
+    format!(
+        r#"{header}
+#
+# Builds, strips and hashes the session Wasm, and uploads it with its hashes so that every
+# signer can check the artifact they sign was built from this commit.
+
+name: Build contract
+
+on:
+  push:
+  pull_request:
+  workflow_dispatch:
+
+jobs:
+  build:
+    runs-on: ubuntu-latest
+{working_directory}    steps:
+      - uses: actions/checkout@v4
+      - name: Install tools
+        run: |
+          rustup toolchain install {toolchain} --profile minimal
+          rustup target add wasm32-unknown-unknown --toolchain {toolchain}
+          sudo apt-get update
+          sudo apt-get install --yes {packages}
+      - name: Build
+        run: |
+{steps}      - uses: actions/upload-artifact@v4
+        with:
+          name: {artifact_name}
+          path: |
+{artifact_paths}"#,
+        header = HEADER,
+        working_directory = working_directory,
+        toolchain = config.toolchain,
+        packages = config.packages(),
+        steps = steps,
+        artifact_name = config.artifact_name,
+        artifact_paths = artifact_paths,
+    )
+}
+
+fn gitlab_ci_contents(config: &WorkflowConfig) -> String {
+    let mut commands = config.commands();
+    if let Some(contract_dir) = config.contract_dir_prefix.strip_suffix('/') {
+        commands.insert(0, format!("cd {}", contract_dir));
+    }
+    let script = commands
+        .iter()
+        .map(|command| format!("    - {}\n", command))
+        .collect::<String>();
+    let artifact_paths = config
+        .artifact_paths()
+        .iter()
+        .map(|path| format!("      - {}\n", path))
+        .collect::<String>();
+
+    format!(
+        r#"{header}
+#
+# Builds, strips and hashes the session Wasm, and keeps it with its hashes as a job artifact so
+# that every signer can check the artifact they sign was built from this commit.
+
+build-contract:
+  image: rust:latest
+  before_script:
+    - rustup toolchain install {toolchain} --profile minimal
+    - rustup target add wasm32-unknown-unknown --toolchain {toolchain}
+    - apt-get update
+    - apt-get install --yes {packages}
+  script:
+{script}  artifacts:
+    name: {artifact_name}
+    paths:
+{artifact_paths}"#,
+        header = HEADER,
+        toolchain = config.toolchain,
+        packages = config.packages(),
+        script = script,
+        artifact_name = config.artifact_name,
+        artifact_paths = artifact_paths,
+    )
+}
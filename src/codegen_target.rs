@@ -0,0 +1,277 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::{
+    dependencies::{self, DependencySource},
+    smart_contract::braced_list,
+    Error,
+};
+
+/// The prefix of the `use` declaration for `casper_types` in the generated session code.
+const TYPES_USE_PREFIX: &str = "use casper_types::";
+
+/// The version of the Casper network the generated session code targets.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CodegenTarget {
+    /// Casper 1.x, where the associated keys and action thresholds belong to the account.
+    #[default]
+    Casper1,
+    /// Casper 2.0 (Condor), where accounts are addressable entities and the associated-key and
+    /// action-threshold types are those of `casper_types::addressable_entity`.
+    ///
+    /// The generated project has no test crate, as the test support crate's API differs entirely.
+    Casper2,
+}
+
+impl CodegenTarget {
+    /// Returns the default version requirement for `casper-contract` and `casper-types`.
+    pub fn default_dependency(&self) -> DependencySource {
+        match self {
+            CodegenTarget::Casper1 => DependencySource::default(),
+            CodegenTarget::Casper2 => DependencySource::Version("5".to_string()),
+        }
+    }
+
+    /// Returns the default toolchain.
+    ///
+    /// For Casper 2.0 this is the last stable release before the `wasm32-unknown-unknown` target
+    /// enabled Wasm features which the execution engine rejects.
+    pub fn default_toolchain(&self) -> &'static str {
+        match self {
+            CodegenTarget::Casper1 => dependencies::DEFAULT_TOOLCHAIN,
+            CodegenTarget::Casper2 => "1.77.2",
+        }
+    }
+
+    /// Returns `Ok` if the SDK crates can be used with this target, else `Err`.
+    ///
+    /// Only version requirements are checked; git and path dependencies are assumed to match.
+    pub(super) fn validate_dependencies(
+        &self,
+        casper_contract: &DependencySource,
+        casper_types: &DependencySource,
+    ) -> Result<(), Error> {
+        for (crate_name, source) in &[
+            ("casper-contract", casper_contract),
+            ("casper-types", casper_types),
+        ] {
+            let major = match source.major_version() {
+                Some(major) => major,
+                None => continue,
+            };
+            let compatible = match self {
+                CodegenTarget::Casper1 => major < 5,
+                CodegenTarget::Casper2 => major >= 5,
+            };
+            if !compatible {
+                return Err(Error::IncompatibleDependencies {
+                    inner: format!("{} {} can't be used to target {}", crate_name, source, self),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `source`, generated for Casper 1.x, adapted for this target.
+    ///
+    /// The host functions managing associated keys and action thresholds are unchanged in
+    /// Casper 2.0, but take the `addressable_entity` types, so only the `casper_types` imports
+    /// differ.
+    pub(super) fn adapt_source(&self, source: &str) -> String {
+        match self {
+            CodegenTarget::Casper1 => source.to_string(),
+            CodegenTarget::Casper2 => rewrite_types_imports(source),
+        }
+    }
+}
+
+impl Display for CodegenTarget {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            CodegenTarget::Casper1 => write!(formatter, "Casper 1.x"),
+            CodegenTarget::Casper2 => write!(formatter, "Casper 2.0 (addressable entities)"),
+        }
+    }
+}
+
+/// Rewrites the `casper_types` imports of `source` to take everything other than `AccountHash`
+/// from `addressable_entity` rather than `account`, laid out as rustfmt would.
+fn rewrite_types_imports(source: &str) -> String {
+    let start = match source.find(TYPES_USE_PREFIX) {
+        Some(start) => start,
+        None => return source.to_string(),
+    };
+    let end = start + source[start..].find(';').unwrap_or(source.len() - start);
+    let tree = source[start + TYPES_USE_PREFIX.len()..end]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    let mut account_items = vec![];
+    let mut entity_items = vec![];
+    let mut other_items = vec![];
+    for item in split_list(strip_braces(&tree)) {
+        match item.strip_prefix("account::") {
+            Some(account_tree) => {
+                for account_item in split_list(strip_braces(account_tree)) {
+                    if account_item == "AccountHash" {
+                        account_items.push(account_item);
+                    } else {
+                        entity_items.push(account_item);
+                    }
+                }
+            }
+            None => other_items.push(item),
+        }
+    }
+
+    let modules = [
+        ("account::", account_items),
+        ("addressable_entity::", entity_items),
+    ];
+    let modules = modules
+        .iter()
+        .filter(|(_, items)| !items.is_empty())
+        .collect::<Vec<_>>();
+
+    let imports = if let ([(module, items)], true) = (modules.as_slice(), other_items.is_empty()) {
+        let prefix = format!("{}{}", TYPES_USE_PREFIX, module);
+        let list = match items.as_slice() {
+            [item] => item.to_string(),
+            _ => braced_list(items, &prefix, 0),
+        };
+        format!("{}{};", prefix, list)
+    } else if modules.iter().any(|(_, items)| items.len() > 1) {
+        // rustfmt puts each path on its own line when any of them has a nested list.
+        let mut lines = String::new();
+        for (module, items) in &modules {
+            let list = match items.as_slice() {
+                [item] => item.to_string(),
+                _ => braced_list(items, module, 4),
+            };
+            lines = format!("{}    {}{},\n", lines, module, list);
+        }
+        if !other_items.is_empty() {
+            lines = format!("{}    {},\n", lines, other_items.join(", "));
+        }
+        format!("{}{{\n{}}};", TYPES_USE_PREFIX, lines)
+    } else {
+        let mut entries = modules
+            .iter()
+            .map(|(module, items)| format!("{}{}", module, items[0]))
+            .collect::<Vec<_>>();
+        entries.extend(other_items.iter().map(|item| item.to_string()));
+        let entries = entries.iter().map(String::as_str).collect::<Vec<_>>();
+        format!(
+            "{}{};",
+            TYPES_USE_PREFIX,
+            braced_list(&entries, TYPES_USE_PREFIX, 0)
+        )
+    };
+    format!("{}{}{}", &source[..start], imports, &source[end + 1..])
+}
+
+/// Returns `tree` without its enclosing braces, if it has them.
+fn strip_braces(tree: &str) -> &str {
+    tree.strip_prefix('{')
+        .and_then(|tree| tree.strip_suffix('}'))
+        .unwrap_or(tree)
+}
+
+/// Splits a comma-separated use list at its top level, ignoring any trailing comma.
+fn split_list(list: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut item_start = 0;
+    for (index, c) in list.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&list[item_start..index]);
+                item_start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    items.push(&list[item_start..]);
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_braces_removes_only_enclosing_braces() {
+        assert_eq!(strip_braces("{AccountHash,Weight}"), "AccountHash,Weight");
+        assert_eq!(strip_braces("AccountHash"), "AccountHash");
+        assert_eq!(strip_braces("{AccountHash"), "{AccountHash");
+    }
+
+    #[test]
+    fn split_list_splits_at_top_level_only() {
+        assert_eq!(
+            split_list("account::{AccountHash,Weight},runtime_args,RuntimeArgs,"),
+            vec![
+                "account::{AccountHash,Weight}",
+                "runtime_args",
+                "RuntimeArgs"
+            ]
+        );
+        assert_eq!(split_list("AccountHash"), vec!["AccountHash"]);
+        assert!(split_list("").is_empty());
+    }
+
+    #[test]
+    fn rewrite_types_imports_to_single_module() {
+        let source = "use casper_contract::contract_api::runtime;\n\
+            use casper_types::account::{ActionType, Weight};\n\
+            \n\
+            fn call() {}\n";
+        assert_eq!(
+            rewrite_types_imports(source),
+            "use casper_contract::contract_api::runtime;\n\
+            use casper_types::addressable_entity::{ActionType, Weight};\n\
+            \n\
+            fn call() {}\n"
+        );
+
+        let source = "use casper_types::account::Weight;\n";
+        assert_eq!(
+            rewrite_types_imports(source),
+            "use casper_types::addressable_entity::Weight;\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_types_imports_to_nested_list() {
+        let source = "use casper_types::{\n    \
+            account::{AccountHash, ActionType, Weight},\n    \
+            runtime_args, RuntimeArgs,\n\
+            };\n";
+        assert_eq!(
+            rewrite_types_imports(source),
+            "use casper_types::{\n    \
+            account::AccountHash,\n    \
+            addressable_entity::{ActionType, Weight},\n    \
+            runtime_args, RuntimeArgs,\n\
+            };\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_types_imports_to_flat_entries_mixed_with_other_items() {
+        let source = "use casper_types::{account::{AccountHash, Weight}, RuntimeArgs};\n";
+        assert_eq!(
+            rewrite_types_imports(source),
+            "use casper_types::{account::AccountHash, addressable_entity::Weight, RuntimeArgs};\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_types_imports_leaves_source_without_them_unchanged() {
+        let source = "use casper_contract::contract_api::runtime;\n";
+        assert_eq!(rewrite_types_imports(source), source);
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::File,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use super::Error;
+
+/// The name of the Dockerfile written to the project when compiling inside Docker.
+pub(super) const DOCKERFILE: &str = "Dockerfile.build";
+
+/// The pinned base image of the generated Dockerfile.
+const BASE_IMAGE: &str = "rust:1.49.0-slim-buster";
+
+/// The path at which the project dir is mounted inside the container.
+const CONTAINER_PROJECT_DIR: &str = "/project";
+
+/// The path at which a shared cargo target dir is mounted inside the container.
+const CONTAINER_SHARED_TARGET_DIR: &str = "/shared-target";
+
+/// The `CARGO_HOME` used inside the container.  It lives under the mounted `target` dir so that
+/// downloaded crates persist between runs of the otherwise ephemeral containers.
+const CONTAINER_CARGO_HOME: &str = "/project/target/docker-cargo-home";
+
+/// How the generated contract is compiled.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum CompileBackend {
+    /// Compile using the toolchain installed on the host.
+    #[default]
+    Host,
+    /// Compile inside a Docker container via `docker run`.
+    ///
+    /// If `image` is `None`, an image is built from the project's generated Dockerfile, tagged
+    /// with the toolchain, and reused by later builds.
+    Docker { image: Option<String> },
+}
+
+impl CompileBackend {
+    pub(super) fn validate(&self) -> Result<(), Error> {
+        if let CompileBackend::Docker { image: Some(image) } = self {
+            if image.is_empty() || image.contains(char::is_whitespace) {
+                return Err(Error::InvalidDockerImage {
+                    image: image.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for CompileBackend {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            CompileBackend::Host => write!(formatter, "host toolchain"),
+            CompileBackend::Docker { image: Some(image) } => {
+                write!(formatter, "docker image {}", image)
+            }
+            CompileBackend::Docker { image: None } => {
+                write!(formatter, "docker image built from {}", DOCKERFILE)
+            }
+        }
+    }
+}
+
+/// Returns the contents of the Dockerfile for an image able to compile the contract with the
+/// given toolchain.
+pub(super) fn dockerfile_contents(toolchain: &str) -> String {
+    format!(
+        r#"# Generated by the Casper Multisig Account Creation Tool.
+#
+# Build with:
+#   docker build --tag {tag} - < {dockerfile}
+
+FROM {base_image}
+
+RUN rustup toolchain install {toolchain} --profile minimal \
+    && rustup target add wasm32-unknown-unknown --toolchain {toolchain} \
+    && chmod -R a+w $RUSTUP_HOME $CARGO_HOME
+"#,
+        tag = default_image_tag(toolchain),
+        dockerfile = DOCKERFILE,
+        base_image = BASE_IMAGE,
+        toolchain = toolchain,
+    )
+}
+
+/// Returns the tag of the image built from the generated Dockerfile for the given toolchain.
+pub(super) fn default_image_tag(toolchain: &str) -> String {
+    format!("casper-multisig-tool-build:{}", toolchain)
+}
+
+/// Returns `true` if the given image exists locally.
+pub(super) fn image_exists(image: &str) -> bool {
+    Command::new("docker")
+        .args(&["image", "inspect", image])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Returns a command which builds the image from the project's Dockerfile, passed via stdin so no
+/// build context is sent.
+pub(super) fn build_image_command(project_dir: &Path, image: &str) -> Result<Command, String> {
+    let dockerfile_path = project_dir.join(DOCKERFILE);
+    let dockerfile = File::open(&dockerfile_path)
+        .map_err(|error| format!("failed to open {}: {}", dockerfile_path.display(), error))?;
+    let mut command = Command::new("docker");
+    command
+        .args(&["build", "--tag", image, "-"])
+        .stdin(dockerfile);
+    Ok(command)
+}
+
+/// Returns a command which runs `cargo` with the given args and `env` vars inside the image, with
+/// the project dir mounted as the working dir.
+///
+/// If `shared_target_dir` is provided, it is mounted and used as the cargo target dir.
+pub(super) fn cargo_command(
+    project_dir: &Path,
+    image: &str,
+    shared_target_dir: Option<&Path>,
+    env: &[(&str, &str)],
+    cargo_args: &[&str],
+) -> Command {
+    let mut command = Command::new("docker");
+    command
+        .args(&["run", "--rm", "--volume"])
+        .arg(format!(
+            "{}:{}",
+            project_dir.display(),
+            CONTAINER_PROJECT_DIR
+        ))
+        .args(&["--workdir", CONTAINER_PROJECT_DIR])
+        .arg("--env")
+        .arg(format!("CARGO_HOME={}", CONTAINER_CARGO_HOME));
+    if let Some(target_dir) = shared_target_dir {
+        command
+            .arg("--volume")
+            .arg(format!(
+                "{}:{}",
+                target_dir.display(),
+                CONTAINER_SHARED_TARGET_DIR
+            ))
+            .arg("--env")
+            .arg(format!("CARGO_TARGET_DIR={}", CONTAINER_SHARED_TARGET_DIR));
+    }
+    for (name, value) in env {
+        command.arg("--env").arg(format!("{}={}", name, value));
+    }
+    // Run as the host user so the build output isn't owned by root.
+    if let Some(user) = host_user() {
+        command.args(&["--user", &user]);
+    }
+    command.arg(image).arg("cargo").args(cargo_args);
+    command
+}
+
+/// Returns the host user as `<uid>:<gid>`, or `None` if unavailable (e.g. on Windows).
+fn host_user() -> Option<String> {
+    let id = |flag: &str| -> Option<String> {
+        let output = Command::new("id").arg(flag).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    Some(format!("{}:{}", id("-u")?, id("-g")?))
+}
@@ -0,0 +1,65 @@
+/// The stream a command run to compile the contract wrote a line of output to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputStream {
+    Stdout,
+    /// Where cargo writes its statuses and the compiler's diagnostics.
+    Stderr,
+}
+
+/// The level of a diagnostic in the compile output.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+}
+
+/// Returns the level of the diagnostic each line of `lines` belongs to, or `None` for lines which
+/// aren't part of one, e.g. cargo's "Compiling" statuses.
+///
+/// A diagnostic starts with a line prefixed by its level, e.g. `error[E0425]: cannot find value`
+/// or `warning: unused import`, and runs until a blank line, a cargo status or the next diagnostic.
+pub(super) fn diagnostic_levels<S: AsRef<str>>(lines: &[S]) -> Vec<Option<DiagnosticLevel>> {
+    let mut current = None;
+    lines
+        .iter()
+        .map(|line| {
+            let line = line.as_ref();
+            current = match header_level(line) {
+                Some(level) => Some(level),
+                None if line.trim().is_empty() || is_status(line) => None,
+                None => current,
+            };
+            current
+        })
+        .collect()
+}
+
+/// Returns the level of a diagnostic's first line, e.g. `error[E0425]: ...`, or `None` if `line`
+/// doesn't start one.
+///
+/// The prefix's case is ignored, so that the tool's own "Error: " and "Warning: " lines count too.
+fn header_level(line: &str) -> Option<DiagnosticLevel> {
+    let prefix = line.split([':', '[']).next().unwrap_or_default();
+    if prefix.len() == line.len() {
+        return None;
+    }
+    match prefix.to_ascii_lowercase().as_str() {
+        "error" => Some(DiagnosticLevel::Error),
+        "warning" => Some(DiagnosticLevel::Warning),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `line` is a cargo status, e.g. `   Compiling casper-types v1.2.0`.
+///
+/// Cargo right-aligns its statuses, so they're indented and capitalised, unlike the continuation
+/// lines of a diagnostic such as `  --> src/main.rs:3:5` or `3 |     let a = b;`.
+fn is_status(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.len() < line.len()
+        && trimmed.starts_with(|char: char| char.is_ascii_uppercase())
+        && trimmed
+            .split(' ')
+            .next()
+            .is_some_and(|status| status.chars().all(|char| char.is_ascii_alphabetic()))
+}
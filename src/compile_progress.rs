@@ -0,0 +1,113 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::{compile_output::OutputStream, CompileEvent};
+
+/// The env vars which make cargo show its progress bar although its output isn't a terminal, so
+/// that the number of crates compiled so far can be reported.
+///
+/// Versions of cargo without these settings ignore them, leaving only the phase to be reported.
+pub(super) const CARGO_PROGRESS_ENV: [(&str, &str); 2] = [
+    ("CARGO_TERM_PROGRESS_WHEN", "always"),
+    ("CARGO_TERM_PROGRESS_WIDTH", "100"),
+];
+
+/// The statuses cargo prints while updating the index and downloading the dependencies.
+const FETCHING_STATUSES: [&str; 5] = ["Updating", "Locking", "Adding", "Downloading", "Downloaded"];
+
+/// A phase of generating and compiling the contract, reported by [`CompileEvent::Progress`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompilePhase {
+    /// Writing the project's files.
+    GeneratingFiles,
+    /// Updating the registry index and downloading the dependencies.
+    FetchingDependencies,
+    /// Compiling the contract and its dependencies, with the numbers of crates compiled so far and
+    /// in total, once cargo has shown them.
+    Compiling { crates: Option<(usize, usize)> },
+    /// Shrinking the compiled Wasm with `wasm-opt` or `wasm-strip`.
+    Stripping,
+}
+
+impl CompilePhase {
+    /// All the phases, in the order they happen.  Not every compilation goes through them all.
+    pub const ALL: [CompilePhase; 4] = [
+        CompilePhase::GeneratingFiles,
+        CompilePhase::FetchingDependencies,
+        CompilePhase::Compiling { crates: None },
+        CompilePhase::Stripping,
+    ];
+
+    /// Returns the position of the phase in [`CompilePhase::ALL`].
+    pub fn index(&self) -> usize {
+        match self {
+            CompilePhase::GeneratingFiles => 0,
+            CompilePhase::FetchingDependencies => 1,
+            CompilePhase::Compiling { .. } => 2,
+            CompilePhase::Stripping => 3,
+        }
+    }
+}
+
+impl Display for CompilePhase {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            CompilePhase::GeneratingFiles => write!(formatter, "Generating files"),
+            CompilePhase::FetchingDependencies => write!(formatter, "Fetching dependencies"),
+            CompilePhase::Compiling {
+                crates: Some((compiled, total)),
+            } => write!(formatter, "Compiling {}/{} crates", compiled, total),
+            CompilePhase::Compiling { crates: None } => write!(formatter, "Compiling"),
+            CompilePhase::Stripping => write!(formatter, "Stripping the Wasm"),
+        }
+    }
+}
+
+/// Returns the events for a line of cargo's output, written to `stream`.
+///
+/// A progress bar only updates the phase, as it's redrawn many times a second.  A status such as
+/// "Downloaded" or "Compiling" updates the phase and is shown as output too.
+pub(super) fn output_events(line: String, stream: OutputStream) -> Vec<CompileEvent> {
+    if let Some(phase) = progress_bar_phase(&line) {
+        return vec![CompileEvent::Progress(phase)];
+    }
+    let phase = status_phase(&line);
+    let output = CompileEvent::CommandOutput { line, stream };
+    match phase {
+        Some(phase) => vec![CompileEvent::Progress(phase), output],
+        None => vec![output],
+    }
+}
+
+/// Returns the phase shown by a progress bar, e.g. `    Building [=====>   ] 12/80: casper-types`,
+/// or `None` if `line` isn't one.
+fn progress_bar_phase(line: &str) -> Option<CompilePhase> {
+    let (name, rest) = line.trim_start().split_once(' ')?;
+    if name.is_empty() || !name.chars().all(|char| char.is_ascii_alphabetic()) {
+        return None;
+    }
+    let rest = rest.trim_start().strip_prefix('[')?;
+    let (_bar, stats) = rest.split_once(']')?;
+    if name != "Building" {
+        return Some(CompilePhase::FetchingDependencies);
+    }
+    // The stats are followed by the names of the crates being compiled, e.g. " 12/80: a, b".
+    let stats = stats.split(':').next().unwrap_or_default().trim();
+    let crates = stats
+        .split_once('/')
+        .and_then(|(compiled, total)| Some((compiled.parse().ok()?, total.parse().ok()?)));
+    Some(CompilePhase::Compiling { crates })
+}
+
+/// Returns the phase shown by a status line, e.g. `   Compiling casper-types v1.2.0`, or `None`
+/// if `line` isn't one.
+fn status_phase(line: &str) -> Option<CompilePhase> {
+    // Cargo right-aligns its statuses, so they're indented unless the status is long.
+    let status = line.trim_start().split(' ').next()?;
+    if FETCHING_STATUSES.contains(&status) {
+        Some(CompilePhase::FetchingDependencies)
+    } else if status == "Compiling" {
+        Some(CompilePhase::Compiling { crates: None })
+    } else {
+        None
+    }
+}
@@ -0,0 +1,369 @@
+use std::fmt::{self, Display, Formatter};
+
+use casper_types::account::AccountHash;
+
+use super::Error;
+
+/// What the generated contract does to the account.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum ContractMode {
+    /// Convert a newly-created account into a multisig account with the configured associated
+    /// keys and action thresholds.
+    #[default]
+    Setup,
+    /// Only remove the given associated keys, leaving the action thresholds and all other keys
+    /// untouched.
+    ///
+    /// The account hashes are in their formatted form, i.e. prefixed with `account-hash-`.
+    RemoveKeys { account_hashes: Vec<String> },
+    /// Only set the given action thresholds, leaving the associated keys and any threshold which
+    /// is `None` untouched.
+    UpdateThresholds {
+        key_management: Option<u8>,
+        deployment: Option<u8>,
+    },
+    /// Return a multisig account to the control of a single key: set both action thresholds to 1,
+    /// set the recovery key's weight to 1 (adding it if needed) and remove every other configured
+    /// associated key.
+    ///
+    /// Intended to be generated and archived alongside the setup contract before the setup is
+    /// sent, so keys which are already absent are skipped rather than reverting the deploy.
+    RevertToSingleKey { recovery_key: String },
+}
+
+impl ContractMode {
+    pub(super) fn validate(&self) -> Result<(), Error> {
+        match self {
+            ContractMode::Setup => (),
+            ContractMode::RemoveKeys { account_hashes } => {
+                if account_hashes.is_empty() {
+                    return Err(make_error("at least one key to remove must be provided"));
+                }
+                let parsed = parse_account_hashes(account_hashes)?;
+                for (index, account_hash) in parsed.iter().enumerate() {
+                    if parsed[..index].contains(account_hash) {
+                        return Err(make_error(&format!(
+                            "{} is listed more than once",
+                            account_hash.to_formatted_string()
+                        )));
+                    }
+                }
+            }
+            ContractMode::UpdateThresholds {
+                key_management,
+                deployment,
+            } => {
+                if key_management.is_none() && deployment.is_none() {
+                    return Err(make_error("at least one threshold to set must be provided"));
+                }
+                if *key_management == Some(0) || *deployment == Some(0) {
+                    return Err(make_error("thresholds must be at least 1"));
+                }
+                if let (Some(key_management), Some(deployment)) = (key_management, deployment) {
+                    if key_management < deployment {
+                        return Err(make_error(
+                            "the key-management threshold must not be lower than the \
+                            deploy-execution threshold",
+                        ));
+                    }
+                }
+            }
+            ContractMode::RevertToSingleKey { recovery_key } => {
+                parse_account_hash(recovery_key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the source of the session code for this mode, or `None` for [`ContractMode::Setup`]
+    /// whose source depends on the rest of the configuration.
+    ///
+    /// `configured_keys` are the associated keys of the multisig account, main key first.
+    pub(super) fn main_rs_contents(
+        &self,
+        configured_keys: &[AccountHash],
+    ) -> Result<Option<String>, Error> {
+        let contents = match self {
+            ContractMode::Setup => return Ok(None),
+            ContractMode::RemoveKeys { account_hashes } => {
+                remove_keys_main_rs_contents(&parse_account_hashes(account_hashes)?)
+            }
+            ContractMode::UpdateThresholds {
+                key_management,
+                deployment,
+            } => update_thresholds_main_rs_contents(*key_management, *deployment),
+            ContractMode::RevertToSingleKey { recovery_key } => {
+                let recovery_key = parse_account_hash(recovery_key)?;
+                revert_to_single_key_main_rs_contents(
+                    recovery_key,
+                    &keys_removed_on_revert(recovery_key, configured_keys),
+                )
+            }
+        };
+        Ok(Some(contents))
+    }
+}
+
+impl Display for ContractMode {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            ContractMode::Setup => write!(formatter, "multisig account setup"),
+            ContractMode::RemoveKeys { account_hashes } => {
+                write!(formatter, "removal of {} key(s)", account_hashes.len())
+            }
+            ContractMode::UpdateThresholds { .. } => write!(formatter, "action threshold update"),
+            ContractMode::RevertToSingleKey { .. } => write!(formatter, "single-key recovery"),
+        }
+    }
+}
+
+fn make_error(inner: &str) -> Error {
+    Error::InvalidContractMode {
+        inner: inner.to_string(),
+    }
+}
+
+/// Returns the configured associated keys which the single-key recovery contract removes, i.e.
+/// all but the recovery key.
+pub(super) fn keys_removed_on_revert(
+    recovery_key: AccountHash,
+    configured_keys: &[AccountHash],
+) -> Vec<AccountHash> {
+    configured_keys
+        .iter()
+        .filter(|account_hash| **account_hash != recovery_key)
+        .copied()
+        .collect()
+}
+
+/// Parses the given formatted account hashes.
+fn parse_account_hashes(account_hashes: &[String]) -> Result<Vec<AccountHash>, Error> {
+    account_hashes
+        .iter()
+        .map(|account_hash| parse_account_hash(account_hash))
+        .collect()
+}
+
+fn parse_account_hash(account_hash: &str) -> Result<AccountHash, Error> {
+    AccountHash::from_formatted_str(account_hash.trim()).map_err(|error| {
+        make_error(&format!(
+            "failed to parse '{}' as a formatted account hash: {}",
+            account_hash, error
+        ))
+    })
+}
+
+/// Returns the source of a contract which removes each of the given associated keys and makes no
+/// other changes.
+fn remove_keys_main_rs_contents(account_hashes: &[AccountHash]) -> String {
+    let mut contents = r#"#![cfg_attr(
+    not(target_arch = "wasm32"),
+    crate_type = "target arch should be wasm32"
+)]
+#![no_main]
+
+use casper_contract::{contract_api::account, unwrap_or_revert::UnwrapOrRevert};
+use casper_types::account::AccountHash;
+
+"#
+    .to_string();
+
+    for (index, account_hash) in account_hashes.iter().enumerate() {
+        contents = format!(
+            r#"{contents}// {hex_hash}
+#[rustfmt::skip]
+const REMOVED_KEY_{index}_HASH: AccountHash = AccountHash::new({hash:?});
+
+"#,
+            contents = contents,
+            hex_hash = account_hash.to_formatted_string(),
+            index = index,
+            hash = account_hash.value(),
+        );
+    }
+
+    let statements = (0..account_hashes.len())
+        .map(|index| {
+            format!(
+                r#"    // Remove associated key {index}.
+    account::remove_associated_key(REMOVED_KEY_{index}_HASH).unwrap_or_revert();
+"#,
+                index = index
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        r#"{}#[no_mangle]
+pub extern "C" fn call() {{
+{}}}
+"#,
+        contents,
+        statements.join("\n")
+    )
+}
+
+/// Returns the source of a contract which sets the given action thresholds and makes no other
+/// changes.
+fn update_thresholds_main_rs_contents(
+    key_management: Option<u8>,
+    deployment: Option<u8>,
+) -> String {
+    let imports = if key_management.is_some() && deployment.is_some() {
+        r#"use casper_contract::{
+    contract_api::{account, runtime},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::account::{ActionType, SetThresholdFailure, Weight};
+"#
+    } else {
+        r#"use casper_contract::{contract_api::account, unwrap_or_revert::UnwrapOrRevert};
+use casper_types::account::{ActionType, Weight};
+"#
+    };
+    let mut contents = format!(
+        r#"#![cfg_attr(
+    not(target_arch = "wasm32"),
+    crate_type = "target arch should be wasm32"
+)]
+#![no_main]
+
+{}
+"#,
+        imports
+    );
+    if let Some(weight) = key_management {
+        contents = format!(
+            "{}const KEY_MANAGEMENT_WEIGHT: u8 = {};\n",
+            contents, weight
+        );
+    }
+    if let Some(weight) = deployment {
+        contents = format!("{}const DEPLOYMENT_WEIGHT: u8 = {};\n", contents, weight);
+    }
+
+    let set_key_management = r#"account::set_action_threshold(
+        ActionType::KeyManagement,
+        Weight::new(KEY_MANAGEMENT_WEIGHT),
+    )"#;
+    let set_deployment =
+        "account::set_action_threshold(ActionType::Deployment, Weight::new(DEPLOYMENT_WEIGHT))";
+    let body = match (key_management, deployment) {
+        (Some(_), Some(_)) => r#"    // Set the key-management threshold first, unless it would be lower than the current
+    // deploy-execution threshold, in which case set the deploy-execution threshold first.
+    let key_management_weight = Weight::new(KEY_MANAGEMENT_WEIGHT);
+    let deployment_weight = Weight::new(DEPLOYMENT_WEIGHT);
+    match account::set_action_threshold(ActionType::KeyManagement, key_management_weight) {
+        Ok(()) => account::set_action_threshold(ActionType::Deployment, deployment_weight)
+            .unwrap_or_revert(),
+        Err(SetThresholdFailure::DeploymentThreshold) => {
+            account::set_action_threshold(ActionType::Deployment, deployment_weight)
+                .unwrap_or_revert();
+            account::set_action_threshold(ActionType::KeyManagement, key_management_weight)
+                .unwrap_or_revert();
+        }
+        Err(error) => runtime::revert(error),
+    }
+"#
+        .to_string(),
+        (Some(_), None) => format!(
+            "    // Set the key-management threshold.\n    {}\n    .unwrap_or_revert();\n",
+            set_key_management
+        ),
+        (None, _) => format!(
+            "    // Set the deploy-execution threshold.\n    {}\n        .unwrap_or_revert();\n",
+            set_deployment
+        ),
+    };
+
+    format!(
+        r#"{}
+#[no_mangle]
+pub extern "C" fn call() {{
+{}}}
+"#,
+        contents, body
+    )
+}
+
+/// Returns the source of a contract which returns the account to the control of `recovery_key`
+/// alone, removing each of `removed_keys` which is still associated.
+fn revert_to_single_key_main_rs_contents(
+    recovery_key: AccountHash,
+    removed_keys: &[AccountHash],
+) -> String {
+    let mut contents = format!(
+        r#"#![cfg_attr(
+    not(target_arch = "wasm32"),
+    crate_type = "target arch should be wasm32"
+)]
+#![no_main]
+
+use casper_contract::{{
+    contract_api::{{account, runtime}},
+    unwrap_or_revert::UnwrapOrRevert,
+}};
+use casper_types::account::{{AccountHash, ActionType, RemoveKeyFailure, UpdateKeyFailure, Weight}};
+
+// {hex_hash}
+#[rustfmt::skip]
+const RECOVERY_KEY_HASH: AccountHash = AccountHash::new({hash:?});
+
+"#,
+        hex_hash = recovery_key.to_formatted_string(),
+        hash = recovery_key.value(),
+    );
+
+    for (index, account_hash) in removed_keys.iter().enumerate() {
+        contents = format!(
+            r#"{contents}// {hex_hash}
+#[rustfmt::skip]
+const REMOVED_KEY_{index}_HASH: AccountHash = AccountHash::new({hash:?});
+
+"#,
+            contents = contents,
+            hex_hash = account_hash.to_formatted_string(),
+            index = index,
+            hash = account_hash.value(),
+        );
+    }
+
+    let removed_key_hashes = (0..removed_keys.len())
+        .map(|index| format!("REMOVED_KEY_{}_HASH", index))
+        .collect::<Vec<_>>();
+    contents = format!(
+        "{}const REMOVED_KEY_HASHES: [AccountHash; {}] = [{}];\n\n",
+        contents,
+        removed_keys.len(),
+        removed_key_hashes.join(", ")
+    );
+
+    format!(
+        r#"{}#[no_mangle]
+pub extern "C" fn call() {{
+    // Lower the deploy-execution threshold first, as the key-management threshold must not be
+    // lower than it.
+    account::set_action_threshold(ActionType::Deployment, Weight::new(1)).unwrap_or_revert();
+    account::set_action_threshold(ActionType::KeyManagement, Weight::new(1)).unwrap_or_revert();
+
+    // Give the recovery key a weight of 1, adding it if it isn't associated.
+    match account::update_associated_key(RECOVERY_KEY_HASH, Weight::new(1)) {{
+        Ok(()) => (),
+        Err(UpdateKeyFailure::MissingKey) => {{
+            account::add_associated_key(RECOVERY_KEY_HASH, Weight::new(1)).unwrap_or_revert()
+        }}
+        Err(error) => runtime::revert(error),
+    }}
+
+    // Remove every other key, skipping those which have already been removed.
+    for account_hash in REMOVED_KEY_HASHES.iter() {{
+        match account::remove_associated_key(*account_hash) {{
+            Ok(()) | Err(RemoveKeyFailure::MissingKey) => (),
+            Err(error) => runtime::revert(error),
+        }}
+    }}
+}}
+"#,
+        contents
+    )
+}
@@ -0,0 +1,112 @@
+use super::Error;
+
+/// Names which Cargo rejects or which would clash with the generated project's own dependencies.
+const RESERVED_NAMES: &[&str] = &[
+    "test",
+    "std",
+    "core",
+    "alloc",
+    "proc_macro",
+    "proc-macro",
+    "casper-contract",
+    "casper_contract",
+    "casper-types",
+    "casper_types",
+];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Returns `name` converted to a valid Cargo package name, or an error if it can't sensibly be.
+///
+/// Any character other than an ASCII letter, digit, `_` or `-` is replaced by `_`.  Names which
+/// are empty, have no letters or digits, start with a digit, or are reserved are rejected.
+pub(super) fn sanitize(name: &str) -> Result<String, Error> {
+    let make_error = |inner: &str| Error::InvalidContractName {
+        name: name.to_string(),
+        inner: inner.to_string(),
+    };
+
+    let sanitized = name
+        .trim()
+        .chars()
+        .map(|char| {
+            if char.is_ascii_alphanumeric() || char == '_' || char == '-' {
+                char
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    if sanitized.is_empty() {
+        return Err(make_error("must not be empty"));
+    }
+    if !sanitized.chars().any(|char| char.is_ascii_alphanumeric()) {
+        return Err(make_error(
+            "must contain at least one ASCII letter or digit",
+        ));
+    }
+    if sanitized.starts_with(|char: char| char.is_ascii_digit()) {
+        return Err(make_error("must not start with a digit"));
+    }
+    if RUST_KEYWORDS.contains(&sanitized.as_str()) {
+        return Err(make_error("must not be a Rust keyword"));
+    }
+    if RESERVED_NAMES.contains(&sanitized.to_lowercase().as_str()) {
+        return Err(make_error("is reserved"));
+    }
+
+    Ok(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the reason `name` is rejected.
+    fn rejection(name: &str) -> String {
+        match sanitize(name) {
+            Err(Error::InvalidContractName { inner, .. }) => inner,
+            result => panic!("expected '{}' to be rejected, got {:?}", name, result),
+        }
+    }
+
+    #[test]
+    fn replaces_invalid_chars() {
+        assert_eq!(sanitize(" my contract.v2 ").unwrap(), "my_contract_v2");
+        assert_eq!(sanitize("multisig-setup").unwrap(), "multisig-setup");
+    }
+
+    #[test]
+    fn rejects_keyword() {
+        assert_eq!(rejection("fn"), "must not be a Rust keyword");
+        // Only an exact match is a keyword.
+        assert_eq!(sanitize("fn_setup").unwrap(), "fn_setup");
+    }
+
+    #[test]
+    fn rejects_reserved_crate_name() {
+        assert_eq!(rejection("casper-types"), "is reserved");
+        assert_eq!(rejection("Casper_Contract"), "is reserved");
+    }
+
+    #[test]
+    fn rejects_leading_digit() {
+        assert_eq!(rejection("2of3"), "must not start with a digit");
+    }
+
+    #[test]
+    fn rejects_name_sanitized_to_nothing() {
+        assert_eq!(rejection("  "), "must not be empty");
+        assert_eq!(
+            rejection("!!"),
+            "must contain at least one ASCII letter or digit"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::{self, Display, Formatter};
+
+use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_node::{crypto::hash, types::Deploy};
+use casper_types::U512;
+
+/// The heuristic's fixed cost in motes, covering the standard payment code and the preprocessing
+/// of the session Wasm.
+const HEURISTIC_BASE_COST: u64 = 1_000_000_000;
+
+/// The heuristic's cost in motes per KiB of session Wasm, covering its instrumentation and the
+/// code executed.
+const HEURISTIC_COST_PER_KIB: u64 = 20_000_000;
+
+/// The heuristic's cost in motes per configured associated key, covering the host functions which
+/// add, update or remove it.
+const HEURISTIC_COST_PER_KEY: u64 = 100_000_000;
+
+/// The margin added to a cost measured by speculative execution, as global state may change
+/// before the deploy is executed.
+const SPECULATIVE_MARGIN_PERCENT: u64 = 20;
+
+/// The margin added to a heuristic cost, which is only a rough guide.
+const HEURISTIC_MARGIN_PERCENT: u64 = 100;
+
+/// Suggested payments are rounded up to a multiple of this many motes, i.e. 0.1 CSPR.
+const PAYMENT_GRANULARITY: u64 = 100_000_000;
+
+/// How a deploy's cost was estimated.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EstimateMethod {
+    /// By executing the deploy on a node's speculative execution server.
+    SpeculativeExecution,
+    /// From the size of the session Wasm and the number of associated keys.
+    Heuristic,
+}
+
+impl Display for EstimateMethod {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            EstimateMethod::SpeculativeExecution => write!(formatter, "speculative execution"),
+            EstimateMethod::Heuristic => write!(
+                formatter,
+                "a rough estimate from the Wasm's size and number of keys"
+            ),
+        }
+    }
+}
+
+/// An estimate of what a deploy of the session Wasm will cost, and the payment amount suggested to
+/// cover it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CostEstimate {
+    /// The estimated cost in motes.
+    pub cost: U512,
+    /// The suggested payment amount in motes: the cost plus a margin, rounded up.
+    pub suggested_payment: U512,
+    pub method: EstimateMethod,
+}
+
+impl CostEstimate {
+    /// Returns an estimate based on the cost measured by speculatively executing the deploy.
+    pub(super) fn from_speculative_cost(cost: U512) -> Self {
+        CostEstimate {
+            cost,
+            suggested_payment: with_margin(cost, SPECULATIVE_MARGIN_PERCENT),
+            method: EstimateMethod::SpeculativeExecution,
+        }
+    }
+
+    /// Returns a heuristic estimate for session Wasm of the given size in bytes which configures
+    /// the given number of associated keys.
+    pub(super) fn heuristic(wasm_size: u64, key_count: usize) -> Self {
+        let cost = U512::from(HEURISTIC_BASE_COST)
+            + U512::from(HEURISTIC_COST_PER_KIB) * U512::from(wasm_size.div_ceil(1024))
+            + U512::from(HEURISTIC_COST_PER_KEY) * U512::from(key_count);
+        CostEstimate {
+            cost,
+            suggested_payment: with_margin(cost, HEURISTIC_MARGIN_PERCENT),
+            method: EstimateMethod::Heuristic,
+        }
+    }
+}
+
+/// Returns the hex-encoded hash of the deploy's session Wasm, used to match speculative estimates
+/// to the Wasm they were measured for, or `None` if the session isn't module bytes.
+pub(super) fn session_wasm_hash(deploy: &Deploy) -> Option<String> {
+    match deploy.session() {
+        ExecutableDeployItem::ModuleBytes { module_bytes, .. } => {
+            Some(wasm_hash(module_bytes.as_slice()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the hex-encoded hash of the given Wasm.
+pub(super) fn wasm_hash(wasm_bytes: &[u8]) -> String {
+    format!("{:x}", hash::hash(wasm_bytes))
+}
+
+/// Returns `cost` plus the given percentage, rounded up to a multiple of `PAYMENT_GRANULARITY`.
+fn with_margin(cost: U512, margin_percent: u64) -> U512 {
+    let granularity = U512::from(PAYMENT_GRANULARITY);
+    let with_margin = cost * U512::from(100 + margin_percent) / U512::from(100);
+    (with_margin + granularity - 1) / granularity * granularity
+}
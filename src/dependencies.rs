@@ -0,0 +1,206 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use super::Error;
+
+const GIT_PREFIX: &str = "git+";
+const PATH_PREFIX: &str = "path+";
+
+/// The toolchain written to the generated project's `rust-toolchain` file by default.
+pub(super) const DEFAULT_TOOLCHAIN: &str = "nightly-2020-12-16";
+
+/// Where a dependency of the generated contract is taken from.
+///
+/// Parses from and displays as:
+///   * a version requirement, e.g. `1` or `=1.2.0`
+///   * `git+<url>#<revision>`
+///   * `path+<local directory>`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DependencySource {
+    /// A version requirement for the crate as published on crates.io.
+    Version(String),
+    /// A revision of a git repository containing the crate.
+    Git { url: String, rev: String },
+    /// A local directory containing the crate.
+    Path(PathBuf),
+}
+
+impl DependencySource {
+    /// Returns the value to be used for this dependency in a Cargo.toml `[dependencies]` table.
+    pub(super) fn to_manifest_value(&self) -> String {
+        match self {
+            DependencySource::Version(version) => format!("{:?}", version),
+            DependencySource::Git { url, rev } => format!("{{ git = {:?}, rev = {:?} }}", url, rev),
+            DependencySource::Path(path) => {
+                format!("{{ path = {:?} }}", path.display().to_string())
+            }
+        }
+    }
+
+    /// Returns the major version if this is a version requirement.
+    pub(super) fn major_version(&self) -> Option<u64> {
+        match self {
+            DependencySource::Version(version) => parse_version_requirement(version),
+            DependencySource::Git { .. } | DependencySource::Path(_) => None,
+        }
+    }
+
+    pub(super) fn validate(&self, crate_name: &str) -> Result<(), Error> {
+        let make_error = |inner: String| Error::InvalidDependency {
+            crate_name: crate_name.to_string(),
+            inner,
+        };
+
+        match self {
+            DependencySource::Version(version) => {
+                if parse_version_requirement(version).is_none() {
+                    return Err(make_error(format!(
+                        "'{}' is not a valid version requirement",
+                        version
+                    )));
+                }
+            }
+            DependencySource::Git { url, rev } => {
+                if url.is_empty() {
+                    return Err(make_error("the git URL must not be empty".to_string()));
+                }
+                if rev.is_empty() {
+                    return Err(make_error("the git revision must not be empty".to_string()));
+                }
+            }
+            DependencySource::Path(path) => {
+                if !path.join("Cargo.toml").is_file() {
+                    return Err(make_error(format!(
+                        "{} does not contain a Cargo.toml",
+                        path.display()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for DependencySource {
+    fn default() -> Self {
+        DependencySource::Version("1".to_string())
+    }
+}
+
+impl Display for DependencySource {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            DependencySource::Version(version) => write!(formatter, "{}", version),
+            DependencySource::Git { url, rev } => {
+                write!(formatter, "{}{}#{}", GIT_PREFIX, url, rev)
+            }
+            DependencySource::Path(path) => write!(formatter, "{}{}", PATH_PREFIX, path.display()),
+        }
+    }
+}
+
+impl FromStr for DependencySource {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(git) = value.strip_prefix(GIT_PREFIX) {
+            let mut parts = git.rsplitn(2, '#');
+            let rev = parts.next().unwrap_or_default();
+            let url = parts.next().ok_or_else(|| Error::ParseDependencySource {
+                value: value.to_string(),
+                inner: "expected git+<url>#<revision>".to_string(),
+            })?;
+            return Ok(DependencySource::Git {
+                url: url.to_string(),
+                rev: rev.to_string(),
+            });
+        }
+
+        if let Some(path) = value.strip_prefix(PATH_PREFIX) {
+            return Ok(DependencySource::Path(PathBuf::from(path)));
+        }
+
+        if parse_version_requirement(value).is_none() {
+            return Err(Error::ParseDependencySource {
+                value: value.to_string(),
+                inner: "expected a version requirement, git+<url>#<revision> or path+<directory>"
+                    .to_string(),
+            });
+        }
+        Ok(DependencySource::Version(value.to_string()))
+    }
+}
+
+/// Parses a simple version requirement like `1`, `^1.2` or `=1.2.0`, returning its major version.
+fn parse_version_requirement(requirement: &str) -> Option<u64> {
+    let version = requirement.trim_start_matches(&['=', '^', '~'][..]);
+    let parts = version.split('.').collect::<Vec<_>>();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+    let mut numbers = parts.iter().map(|part| part.parse::<u64>());
+    let major = numbers.next()?.ok()?;
+    if numbers.any(|number| number.is_err()) {
+        return None;
+    }
+    Some(major)
+}
+
+/// Returns `Ok` if the given dependency sources and toolchain can be used together to build the
+/// contract, else `Err`.
+pub(super) fn validate(
+    casper_contract: &DependencySource,
+    casper_types: &DependencySource,
+    toolchain: &str,
+) -> Result<(), Error> {
+    casper_contract.validate("casper-contract")?;
+    casper_types.validate("casper-types")?;
+
+    if toolchain.is_empty() || toolchain.contains(char::is_whitespace) {
+        return Err(Error::InvalidToolchain {
+            toolchain: toolchain.to_string(),
+            inner: "must be a rustup toolchain name like nightly-2020-12-16".to_string(),
+        });
+    }
+
+    let contract_major = casper_contract.major_version();
+    let types_major = casper_types.major_version();
+    if let (Some(contract_major), Some(types_major)) = (contract_major, types_major) {
+        // The two crates are released in lockstep; mixing major versions leaves the contract with
+        // two incompatible copies of casper-types.
+        if contract_major != types_major {
+            return Err(Error::IncompatibleDependencies {
+                inner: format!(
+                    "casper-contract {} and casper-types {} are from different SDK releases",
+                    casper_contract, casper_types
+                ),
+            });
+        }
+    }
+
+    // The 1.x SDK crates use unstable features and hence need a nightly toolchain.
+    let requires_nightly = contract_major == Some(1) || types_major == Some(1);
+    if requires_nightly && !toolchain.starts_with("nightly") {
+        return Err(Error::InvalidToolchain {
+            toolchain: toolchain.to_string(),
+            inner: "version 1 of casper-contract and casper-types requires a nightly toolchain"
+                .to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns `path` made absolute relative to the current directory if it is relative.
+pub(super) fn absolute_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    std::env::current_dir()
+        .map(|current_dir| current_dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
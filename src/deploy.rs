@@ -0,0 +1,419 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_node::{
+    crypto,
+    types::{Approval, Deploy, TimeDiff, Timestamp},
+};
+use serde_json::Value;
+
+use casper_types::{
+    bytesrepr::{Bytes, ToBytes},
+    crypto::AsymmetricType,
+    runtime_args,
+    system::standard_payment::ARG_AMOUNT,
+    PublicKey, RuntimeArgs, Signature, U512,
+};
+
+use super::{keystore, prebuilt_session, Error};
+
+/// The field of a deploy's JSON representation which holds its approvals.
+const APPROVALS_FIELD: &str = "approvals";
+
+/// The field of a deploy's JSON representation which holds its hash.
+const HASH_FIELD: &str = "hash";
+
+/// The time-to-live used by casper-client unless overridden.
+const DEFAULT_TTL: TimeDiff = TimeDiff::from_seconds(30 * 60);
+
+/// The largest time-to-live accepted by the network, as set by `deploys.max_ttl` in its
+/// chainspec.
+const MAX_TTL: TimeDiff = TimeDiff::from_seconds(24 * 60 * 60);
+
+/// The largest serialized payment or session runtime args accepted by the network, as set by
+/// `deploys.payment_args_max_length` and `deploys.session_args_max_length` in its chainspec.
+const MAX_ARGS_LENGTH: usize = 1024;
+
+/// The settings from which a deploy of the session Wasm is built.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeployParams {
+    /// The name of the network the deploy is sent to, e.g. `casper` or `casper-test`.
+    pub chain_name: String,
+    /// The amount in motes passed to the standard payment code.
+    pub payment_amount: U512,
+    /// How long after its timestamp the deploy may be executed.
+    pub ttl: TimeDiff,
+    /// The number of motes paid per unit of gas.
+    pub gas_price: u64,
+    /// The path of the secret key file of the account the deploy is sent from.
+    ///
+    /// The deploy's header names the account derived from it, and it provides the deploy's first
+    /// approval.
+    pub secret_key: PathBuf,
+    /// The runtime args passed to the session code.
+    pub session_args: RuntimeArgs,
+}
+
+impl DeployParams {
+    /// Returns params with casper-client's default TTL and gas price, and no session args.
+    pub fn new(chain_name: &str, payment_amount: U512, secret_key: PathBuf) -> Self {
+        DeployParams {
+            chain_name: chain_name.trim().to_string(),
+            payment_amount,
+            ttl: DEFAULT_TTL,
+            gas_price: 1,
+            secret_key,
+            session_args: RuntimeArgs::new(),
+        }
+    }
+
+    /// Returns `Ok` if the header and runtime args would be accepted by the network, else `Err`.
+    fn validate(&self, payment_args: &RuntimeArgs) -> Result<(), Error> {
+        let make_error = |inner: String| Error::BuildDeploy { inner };
+
+        if self.chain_name.is_empty() {
+            return Err(make_error("the chain name must not be empty".to_string()));
+        }
+        if self.payment_amount.is_zero() {
+            return Err(make_error(
+                "the payment amount must not be zero".to_string(),
+            ));
+        }
+        if self.ttl.millis() == 0 || self.ttl > MAX_TTL {
+            return Err(make_error(format!(
+                "the time-to-live must be non-zero and at most {}",
+                MAX_TTL
+            )));
+        }
+        if self.gas_price == 0 {
+            return Err(make_error("the gas price must not be zero".to_string()));
+        }
+        for (name, args) in &[("payment", payment_args), ("session", &self.session_args)] {
+            let length = args.serialized_length();
+            if length > MAX_ARGS_LENGTH {
+                return Err(make_error(format!(
+                    "the serialized {} args are {} bytes, exceeding the limit of {}",
+                    name, length, MAX_ARGS_LENGTH
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns a deploy of the given session Wasm, paid for with the standard payment code and signed
+/// by `params.secret_key`.
+pub(super) fn build(
+    wasm_file: &str,
+    params: &DeployParams,
+    max_deploy_size: u32,
+) -> Result<Deploy, Error> {
+    let make_error = |inner: String| Error::BuildDeploy { inner };
+
+    let payment_args = runtime_args! { ARG_AMOUNT => params.payment_amount };
+    params.validate(&payment_args)?;
+
+    let module_bytes = fs::read(wasm_file).map_err(|error| Error::ReadWasmFile {
+        file: wasm_file.to_string(),
+        inner: error.to_string(),
+    })?;
+    prebuilt_session::validate_wasm(wasm_file, &module_bytes, None)?;
+
+    let secret_key = keystore::read_secret_key(&params.secret_key).map_err(|error| {
+        make_error(format!(
+            "failed to read {} as a secret key: {}",
+            params.secret_key.display(),
+            error
+        ))
+    })?;
+
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: payment_args,
+    };
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::from(module_bytes),
+        args: params.session_args.clone(),
+    };
+    let mut deploy = Deploy::new(
+        Timestamp::now(),
+        params.ttl,
+        params.gas_price,
+        vec![],
+        params.chain_name.clone(),
+        payment,
+        session,
+        &secret_key,
+    );
+
+    deploy
+        .is_valid_size(max_deploy_size)
+        .map_err(|error| make_error(error.to_string()))?;
+    deploy
+        .is_valid()
+        .map_err(|error| make_error(error.to_string()))?;
+    Ok(deploy)
+}
+
+/// Returns a copy of `deploy` with a fresh timestamp, so a new time-to-live, signed only by the
+/// secret key of its account in `secret_key_file`.
+///
+/// The copy has a different hash, so none of the original's approvals are valid for it.
+pub(super) fn rebuild(deploy: &Deploy, secret_key_file: &str) -> Result<Deploy, Error> {
+    let make_error = |inner: String| Error::BuildDeploy { inner };
+
+    let secret_key = keystore::read_secret_key(Path::new(secret_key_file)).map_err(|error| {
+        make_error(format!(
+            "failed to read {} as a secret key: {}",
+            secret_key_file, error
+        ))
+    })?;
+    if PublicKey::from(&secret_key) != *deploy.header().account() {
+        return Err(make_error(format!(
+            "{} isn't the secret key of the deploy's account {}",
+            secret_key_file,
+            deploy.header().account().to_hex()
+        )));
+    }
+
+    let header = deploy.header();
+    let mut rebuilt = Deploy::new(
+        Timestamp::now(),
+        header.ttl(),
+        header.gas_price(),
+        header.dependencies().clone(),
+        header.chain_name().to_string(),
+        deploy.payment().clone(),
+        deploy.session().clone(),
+        &secret_key,
+    );
+    rebuilt
+        .is_valid()
+        .map_err(|error| make_error(error.to_string()))?;
+    Ok(rebuilt)
+}
+
+/// Returns how long until the deploy expires, or `None` if it already has.
+pub(super) fn time_to_expiry(deploy: &Deploy) -> Option<Duration> {
+    deploy
+        .header()
+        .expires()
+        .millis()
+        .checked_sub(Timestamp::now().millis())
+        .filter(|millis| *millis > 0)
+        .map(Duration::from_millis)
+}
+
+/// Signs `deploy` with the secret key in the given file, appending and returning the approval.
+///
+/// Returns an error if the key has already signed the deploy, since a duplicate approval only
+/// makes the deploy larger.
+pub(super) fn sign(deploy: &mut Deploy, secret_key_file: &str) -> Result<Approval, Error> {
+    let make_error = |inner: String| Error::SignDeploy {
+        file: secret_key_file.to_string(),
+        inner,
+    };
+
+    let secret_key = keystore::read_secret_key(Path::new(secret_key_file))
+        .map_err(|error| make_error(format!("failed to read secret key: {}", error)))?;
+    let signer = PublicKey::from(&secret_key);
+    if deploy
+        .approvals()
+        .iter()
+        .any(|approval| *approval.signer() == signer)
+    {
+        return Err(make_error(
+            "the deploy is already signed by this key".to_string(),
+        ));
+    }
+
+    deploy.sign(&secret_key);
+    deploy
+        .approvals()
+        .last()
+        .cloned()
+        .ok_or_else(|| make_error("no approval was added".to_string()))
+}
+
+/// Writes `deploy` as JSON to `path`, in the form accepted by `casper-client send-deploy`.
+pub(super) fn save(deploy: &Deploy, path: &Path) -> Result<(), Error> {
+    let make_error = |inner: String| Error::SaveDeploy {
+        file: path.display().to_string(),
+        inner,
+    };
+
+    let json =
+        serde_json::to_string_pretty(deploy).map_err(|error| make_error(error.to_string()))?;
+    fs::write(path, json).map_err(|error| make_error(error.to_string()))
+}
+
+/// Writes `deploy` without its approvals as JSON to `path`, for other signers to sign with
+/// `casper-client sign-deploy`.
+pub(super) fn save_unsigned(deploy: &Deploy, path: &Path) -> Result<(), Error> {
+    let unsigned = with_approvals(deploy, &[]).map_err(|inner| Error::SaveDeploy {
+        file: path.display().to_string(),
+        inner,
+    })?;
+    save(&unsigned, path)
+}
+
+/// Reads a deploy from the JSON file at `path`, checking its hashes and approvals.
+pub(super) fn load(path: &Path) -> Result<Deploy, Error> {
+    let make_error = |inner: String| Error::LoadDeploy {
+        file: path.display().to_string(),
+        inner,
+    };
+
+    let json = fs::read_to_string(path).map_err(|error| make_error(error.to_string()))?;
+    let mut deploy =
+        serde_json::from_str::<Deploy>(&json).map_err(|error| make_error(error.to_string()))?;
+    deploy
+        .is_valid()
+        .map_err(|error| make_error(error.to_string()))?;
+    Ok(deploy)
+}
+
+/// The outcome of checking one approval from a co-signer against the deploy.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ApprovalCheck {
+    /// The key the approval claims to be from.
+    pub signer: PublicKey,
+    /// Why the signature isn't valid over the deploy hash, or `None` if it is.
+    pub error: Option<String>,
+}
+
+/// Returns an error unless `signature` is `signer`'s signature of the deploy hash.
+pub(super) fn verify_approval(
+    deploy: &Deploy,
+    signer: &PublicKey,
+    signature: &Signature,
+) -> Result<(), String> {
+    crypto::verify(deploy.id(), signature, signer)
+        .map_err(|error| format!("not a valid signature of deploy {}: {}", deploy.id(), error))
+}
+
+/// Checks each approval in the JSON file at `path` against the deploy hash, without adding any.
+///
+/// Returns an error if the file can't be read, or holds a copy of a different deploy.
+pub(super) fn check_approvals(deploy: &Deploy, path: &Path) -> Result<Vec<ApprovalCheck>, Error> {
+    let approvals = read_approvals(deploy, path).map_err(|inner| Error::ImportApprovals {
+        file: path.display().to_string(),
+        inner,
+    })?;
+    Ok(approvals
+        .into_iter()
+        .map(|approval| ApprovalCheck {
+            error: verify_approval(deploy, approval.signer(), approval.signature()).err(),
+            signer: approval.signer().clone(),
+        })
+        .collect())
+}
+
+/// Adds the approvals in the JSON file at `path` to `deploy`, returning those which weren't
+/// already present.
+///
+/// The file may hold a copy of the deploy signed by other parties, e.g. the output of
+/// `casper-client sign-deploy`, a single approval or a list of approvals.  Every approval's
+/// signature is checked against the deploy hash before any are added.
+pub(super) fn import_approvals(deploy: &mut Deploy, path: &Path) -> Result<Vec<Approval>, Error> {
+    let make_error = |inner: String| Error::ImportApprovals {
+        file: path.display().to_string(),
+        inner,
+    };
+
+    let approvals = read_approvals(deploy, path).map_err(make_error)?;
+    let mut merged = deploy.approvals().to_vec();
+    let mut added = vec![];
+    for approval in approvals {
+        verify_approval(deploy, approval.signer(), approval.signature()).map_err(|error| {
+            make_error(format!(
+                "the approval by {} is {}",
+                approval.signer().to_hex(),
+                error
+            ))
+        })?;
+        if merged
+            .iter()
+            .all(|existing| existing.signer() != approval.signer())
+        {
+            merged.push(approval.clone());
+            added.push(approval);
+        }
+    }
+
+    if !added.is_empty() {
+        *deploy = with_approvals(deploy, &merged).map_err(make_error)?;
+    }
+    Ok(added)
+}
+
+/// Reads the approvals in the JSON file at `path`, which may hold a signed copy of `deploy`, a
+/// single approval or a list of approvals.
+///
+/// Returns an error if the file holds a copy of a different deploy, whose approvals are therefore
+/// signatures of the wrong deploy hash.
+fn read_approvals(deploy: &Deploy, path: &Path) -> Result<Vec<Approval>, String> {
+    let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let json = serde_json::from_str::<Value>(&contents).map_err(|error| error.to_string())?;
+    if json.get(HASH_FIELD).is_some() {
+        let signed_copy =
+            serde_json::from_value::<Deploy>(json).map_err(|error| error.to_string())?;
+        if signed_copy.id() != deploy.id() {
+            return Err(format!(
+                "it holds deploy {} rather than {}, so it was signed for a different deploy",
+                signed_copy.id(),
+                deploy.id()
+            ));
+        }
+        Ok(signed_copy.approvals().to_vec())
+    } else if json.is_array() {
+        serde_json::from_value::<Vec<Approval>>(json).map_err(|error| error.to_string())
+    } else {
+        Ok(vec![
+            serde_json::from_value::<Approval>(json).map_err(|error| error.to_string())?
+        ])
+    }
+}
+
+/// Appends `signer`'s `signature` of the deploy hash to the approvals of `deploy`, returning the
+/// approval.
+///
+/// Returns an error if the signature is invalid or `signer` has already signed the deploy.
+pub(super) fn add_signature(
+    deploy: &mut Deploy,
+    signer: PublicKey,
+    signature: Signature,
+) -> Result<Approval, String> {
+    verify_approval(deploy, &signer, &signature)?;
+    if deploy
+        .approvals()
+        .iter()
+        .any(|approval| *approval.signer() == signer)
+    {
+        return Err("the deploy is already signed by this key".to_string());
+    }
+
+    let approval = serde_json::from_value::<Approval>(serde_json::json!({
+        "signer": signer,
+        "signature": signature,
+    }))
+    .map_err(|error| error.to_string())?;
+    let mut approvals = deploy.approvals().to_vec();
+    approvals.push(approval.clone());
+    *deploy = with_approvals(deploy, &approvals)?;
+    Ok(approval)
+}
+
+/// Returns a copy of `deploy` with its approvals replaced by `approvals`.
+///
+/// `Deploy` only allows approvals to be added by signing with a secret key, so this goes via its
+/// JSON representation.
+fn with_approvals(deploy: &Deploy, approvals: &[Approval]) -> Result<Deploy, String> {
+    let mut json = serde_json::to_value(deploy).map_err(|error| error.to_string())?;
+    json[APPROVALS_FIELD] = serde_json::to_value(approvals).map_err(|error| error.to_string())?;
+    serde_json::from_value(json).map_err(|error| error.to_string())
+}
@@ -0,0 +1,101 @@
+use std::fmt::{self, Display, Formatter};
+
+use casper_node::types::Deploy;
+use casper_types::{crypto::AsymmetricType, RuntimeArgs, U512};
+
+use super::{balance, cost_estimate};
+
+/// The decoded contents of a deploy, compared against a local build of the configuration.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeployInspection {
+    pub deploy_hash: String,
+    /// The hex-encoded public key of the account the deploy is sent from.
+    pub account: String,
+    pub chain_name: String,
+    pub timestamp: String,
+    pub ttl: String,
+    /// The amount passed to the payment code, if it has an `amount` arg.
+    pub payment_amount: Option<U512>,
+    /// The hex-encoded public keys of the deploy's signers.
+    pub signers: Vec<String>,
+    /// The hex-encoded Blake2b hash of the session module bytes, or `None` if the session isn't
+    /// module bytes.
+    pub session_wasm_hash: Option<String>,
+    /// The hex-encoded Blake2b hash of the locally built session Wasm.
+    pub local_wasm_hash: String,
+    /// Whether the session args are those the configuration calls for.
+    pub session_args_match: bool,
+}
+
+impl DeployInspection {
+    /// Decodes `deploy` and compares it with `local_wasm`, built from the configuration, and the
+    /// session args the configuration calls for.
+    pub(super) fn new(deploy: &Deploy, local_wasm: &[u8], expected_args: &RuntimeArgs) -> Self {
+        let header = deploy.header();
+        DeployInspection {
+            deploy_hash: deploy.id().to_string(),
+            account: header.account().to_hex(),
+            chain_name: header.chain_name().to_string(),
+            timestamp: header.timestamp().to_string(),
+            ttl: header.ttl().to_string(),
+            payment_amount: balance::payment_amount(deploy),
+            signers: deploy
+                .approvals()
+                .iter()
+                .map(|approval| approval.signer().to_hex())
+                .collect(),
+            session_wasm_hash: cost_estimate::session_wasm_hash(deploy),
+            local_wasm_hash: cost_estimate::wasm_hash(local_wasm),
+            session_args_match: deploy.session().args() == expected_args,
+        }
+    }
+
+    /// Returns `true` if the deploy's session Wasm and args are those of the local build.
+    pub fn matches(&self) -> bool {
+        self.session_wasm_hash.as_ref() == Some(&self.local_wasm_hash) && self.session_args_match
+    }
+}
+
+impl Display for DeployInspection {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        writeln!(formatter, "Deploy: {}", self.deploy_hash)?;
+        writeln!(formatter, "Account: {}", self.account)?;
+        writeln!(formatter, "Chain name: {}", self.chain_name)?;
+        writeln!(formatter, "Timestamp: {}", self.timestamp)?;
+        writeln!(formatter, "TTL: {}", self.ttl)?;
+        match &self.payment_amount {
+            Some(amount) => writeln!(formatter, "Payment amount: {} motes", amount)?,
+            None => writeln!(formatter, "Payment amount: unknown")?,
+        }
+        if self.signers.is_empty() {
+            writeln!(formatter, "Signed by: nobody yet")?;
+        }
+        for signer in &self.signers {
+            writeln!(formatter, "Signed by: {}", signer)?;
+        }
+        writeln!(
+            formatter,
+            "Session Wasm hash: {}",
+            self.session_wasm_hash
+                .as_deref()
+                .unwrap_or("none, the session isn't module bytes")
+        )?;
+        writeln!(formatter, "Local build hash: {}", self.local_wasm_hash)?;
+
+        if self.matches() {
+            write!(
+                formatter,
+                "MATCH: the session Wasm and args are those of the local build of the configuration"
+            )
+        } else {
+            let mut mismatches = vec![];
+            if self.session_wasm_hash.as_ref() != Some(&self.local_wasm_hash) {
+                mismatches.push("the session Wasm differs from the local build");
+            }
+            if !self.session_args_match {
+                mismatches.push("the session args differ from those of the configuration");
+            }
+            write!(formatter, "MISMATCH: {}", mismatches.join("; "))
+        }
+    }
+}
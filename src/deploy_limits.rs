@@ -0,0 +1,41 @@
+use std::{fs, path::Path};
+
+/// The largest deploy, in bytes, accepted by mainnet, as set by `deploys.max_deploy_size` in its
+/// chainspec.
+pub(super) const DEFAULT_MAX_DEPLOY_SIZE: u32 = 1_048_576;
+
+/// A generous estimate of the size of everything in a deploy other than the session Wasm: the
+/// header, payment, runtime args and approvals.
+const DEPLOY_OVERHEAD: u64 = 8 * 1024;
+
+/// The session Wasm size above which a warning is given that the deploy is unusually expensive.
+///
+/// A multisig setup contract is typically a few tens of KiB, or less after `wasm-opt`.  Larger
+/// modules usually contain debug info or unused code, and cost more to send and execute.
+const LARGE_SESSION_WASM_SIZE: u64 = 256 * 1024;
+
+/// Returns a warning about the size of the session Wasm at `wasm_path` in relation to the given
+/// max deploy size, or `None` if its size is unremarkable or can't be read.
+pub(super) fn size_warning(wasm_path: &Path, max_deploy_size: u32) -> Option<String> {
+    let wasm_size = fs::metadata(wasm_path).ok()?.len();
+    let max_deploy_size = u64::from(max_deploy_size);
+
+    if wasm_size + DEPLOY_OVERHEAD > max_deploy_size {
+        return Some(format!(
+            "{} is {} bytes, so the deploy would exceed the network's max deploy size of {} bytes \
+            and be rejected",
+            wasm_path.display(),
+            wasm_size,
+            max_deploy_size
+        ));
+    }
+    if wasm_size > LARGE_SESSION_WASM_SIZE {
+        return Some(format!(
+            "{} is {} bytes, which is unusually large for session code.  The deploy will need a \
+            higher payment amount than usual; consider enabling wasm-opt to shrink it",
+            wasm_path.display(),
+            wasm_size
+        ));
+    }
+    None
+}
@@ -0,0 +1,350 @@
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use casper_node::types::{Deploy, Timestamp};
+use casper_types::{crypto::AsymmetricType, ApiError};
+use serde_json::Value;
+
+use super::{balance, event_stream::Listener, rpc, DeployEvent};
+
+/// How long to wait between queries of the deploy's execution results.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long to wait between queries while also listening to the node's event stream, which
+/// normally reports the result first.
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The outcome of executing a deploy, as reported by `info_get_deploy`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct ExecutionResult {
+    pub(super) block_hash: String,
+    pub(super) cost: String,
+    /// The error if execution failed, e.g. `ApiError::DuplicateKey [22]`.
+    pub(super) error_message: Option<String>,
+}
+
+impl ExecutionResult {
+    /// Returns the final event reporting this result.
+    fn into_event(self) -> DeployEvent {
+        match self.error_message {
+            None => DeployEvent::Succeeded {
+                block_hash: self.block_hash,
+                cost: self.cost,
+            },
+            Some(error_message) => DeployEvent::Failed {
+                block_hash: self.block_hash,
+                cost: self.cost,
+                cause: failure_cause(&error_message),
+                error_message,
+            },
+        }
+    }
+}
+
+/// Sends the deploy saved in `deploy_file` to the given node using `casper-client send-deploy`,
+/// then polls `casper-client get-deploy` until it has been executed or has expired.
+///
+/// If `event_stream_url` is given, the node's event stream is also watched so that the result is
+/// reported as soon as the deploy is processed, and polling only continues as a fallback.
+///
+/// The progress is streamed through the returned channel, with a [`DeployEvent`] other than
+/// [`DeployEvent::Output`] sent last.
+pub(super) fn send(
+    deploy: Deploy,
+    deploy_file: &Path,
+    node_address: &str,
+    event_stream_url: Option<&str>,
+) -> Receiver<DeployEvent> {
+    let deploy_file = deploy_file.to_path_buf();
+    let node_address = node_address.trim().to_string();
+    let event_stream_url = event_stream_url.map(|url| url.trim().to_string());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        if let Err(reason) = check_balance(&deploy, &node_address, &sender) {
+            let _ = sender.send(DeployEvent::NotExecuted { reason });
+            return;
+        }
+
+        // Subscribe before sending, so that the event can't be missed.
+        let deploy_hash = format!("{:x}", deploy.id().inner());
+        let listener = event_stream_url.and_then(|url| match Listener::start(&url, &deploy_hash) {
+            Ok(listener) => {
+                let _ = sender.send(DeployEvent::Output(format!(
+                    "Listening to the event stream at {}",
+                    url
+                )));
+                Some(listener)
+            }
+            Err(error) => {
+                let _ = sender.send(DeployEvent::Output(format!(
+                    "Failed to listen to the event stream, so polling instead: {}",
+                    error
+                )));
+                None
+            }
+        });
+
+        let final_event = match submit(&deploy_file, &node_address, &sender) {
+            Ok(()) => poll(&deploy, &node_address, listener, &sender),
+            Err(reason) => DeployEvent::NotExecuted { reason },
+        };
+        let _ = sender.send(final_event);
+    });
+    receiver
+}
+
+/// Returns a human-readable cause of the given execution error, if it's one the generated session
+/// code can raise.
+///
+/// The session code reverts with the host's error when changing the associated keys or thresholds
+/// fails, which the node reports as e.g. `ApiError::DuplicateKey [22]`.
+pub(super) fn failure_cause(error_message: &str) -> Option<String> {
+    if error_message.contains("Out of gas") {
+        return Some(
+            "the payment amount didn't cover the cost of executing the deploy; rebuild it with a \
+            larger payment amount"
+                .to_string(),
+        );
+    }
+
+    let code = error_message
+        .trim_end()
+        .strip_suffix(']')
+        .and_then(|rest| rest.rsplit('[').next())
+        .and_then(|code| code.parse::<u32>().ok())?;
+    let cause = match ApiError::from(code) {
+        ApiError::DuplicateKey => "a key being added is already an associated key of the account",
+        ApiError::MaxKeysLimit => "the account already has the maximum number of associated keys",
+        ApiError::PermissionDenied => {
+            "the deploy's approvals don't meet the account's key-management threshold"
+        }
+        ApiError::MissingKey => "a key being updated or removed isn't an associated key",
+        ApiError::ThresholdViolation => {
+            "updating or removing a key would leave the keys' total weight below a threshold"
+        }
+        ApiError::KeyManagementThreshold => {
+            "the key-management threshold can't be lower than the deployment threshold"
+        }
+        ApiError::DeploymentThreshold => {
+            "the deployment threshold can't be higher than the key-management threshold"
+        }
+        ApiError::InsufficientTotalWeight => {
+            "a threshold is higher than the total weight of the associated keys"
+        }
+        _ => return None,
+    };
+    Some(cause.to_string())
+}
+
+/// Returns an error if the paying account's main purse can't cover the deploy's payment amount,
+/// since the deploy would then fail without executing the session code.
+///
+/// If the balance can't be queried, e.g. as the node doesn't allow it, a warning is output and the
+/// deploy is sent regardless.
+fn check_balance(
+    deploy: &Deploy,
+    node_address: &str,
+    sender: &Sender<DeployEvent>,
+) -> Result<(), String> {
+    let payment_amount = match balance::payment_amount(deploy) {
+        Some(amount) => amount,
+        None => return Ok(()),
+    };
+    let account = deploy.header().account();
+    match balance::main_purse_balance(node_address, account) {
+        Ok(balance) if balance < payment_amount => Err(format!(
+            "the paying account {} holds {} motes, less than the payment amount of {} motes",
+            account.to_hex(),
+            balance,
+            payment_amount
+        )),
+        Ok(balance) => {
+            let _ = sender.send(DeployEvent::Output(format!(
+                "The paying account holds {} motes, enough for the payment amount of {} motes",
+                balance, payment_amount
+            )));
+            Ok(())
+        }
+        Err(error) => {
+            let _ = sender.send(DeployEvent::Output(format!(
+                "Warning: failed to check the paying account's balance: {}",
+                error
+            )));
+            Ok(())
+        }
+    }
+}
+
+/// Runs `casper-client send-deploy`, sending progress as [`DeployEvent::Output`]s.
+///
+/// Resending a deploy is harmless, as nodes ignore one they already hold, so it's sent to the next
+/// of several nodes if one fails.
+fn submit(
+    deploy_file: &Path,
+    node_address: &str,
+    sender: &Sender<DeployEvent>,
+) -> Result<(), String> {
+    let deploy_file = deploy_file.display().to_string();
+    let _ = sender.send(DeployEvent::Output(format!(
+        "Sending the deploy in {} to {}",
+        deploy_file, node_address
+    )));
+    let result = rpc::call_reporting(
+        node_address,
+        &["send-deploy", "--input", &deploy_file],
+        |line| {
+            let _ = sender.send(DeployEvent::Output(line));
+        },
+    )
+    .map_err(|error| {
+        format!(
+            "the node didn't accept the deploy from {}: {}",
+            deploy_file, error
+        )
+    })?;
+    let _ = sender.send(DeployEvent::Output(format!(
+        "The node accepted deploy {}",
+        json_to_string(&result["deploy_hash"])
+    )));
+    Ok(())
+}
+
+/// Queries the node for the deploy's execution results until they arrive, are reported by
+/// `listener`, or the deploy expires, and returns the final event.
+fn poll(
+    deploy: &Deploy,
+    node_address: &str,
+    mut listener: Option<Listener>,
+    sender: &Sender<DeployEvent>,
+) -> DeployEvent {
+    let deploy_hash = format!("{:x}", deploy.id().inner());
+    let _ = sender.send(DeployEvent::Output(String::new()));
+    let _ = sender.send(DeployEvent::Output(format!(
+        "Waiting for deploy {} to be executed",
+        deploy_hash
+    )));
+
+    loop {
+        match query(&deploy_hash, node_address) {
+            Ok(Some(result)) => return result.into_event(),
+            Ok(None) => (),
+            // The node may not have gossiped the deploy yet, or be briefly unavailable.
+            Err(error) => {
+                let _ = sender.send(DeployEvent::Output(format!("Query failed: {}", error)));
+            }
+        }
+        if deploy.header().expired(Timestamp::now()) {
+            return DeployEvent::NotExecuted {
+                reason: format!(
+                    "the deploy expired at {} without being executed",
+                    deploy.header().expires()
+                ),
+            };
+        }
+
+        match listener
+            .as_ref()
+            .map(|listener| listener.wait(STREAM_POLL_INTERVAL))
+        {
+            Some(Ok(result)) => return result.into_event(),
+            Some(Err(RecvTimeoutError::Timeout)) => (),
+            Some(Err(RecvTimeoutError::Disconnected)) => {
+                let _ = sender.send(DeployEvent::Output(
+                    "The event stream closed, so polling instead".to_string(),
+                ));
+                listener = None;
+            }
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+/// Polls the node until the deploy with the given hex-encoded hash has been executed, returning
+/// its result, or an error if it isn't executed within `timeout`.
+pub(super) fn wait_for_result(
+    deploy_hash: &str,
+    node_address: &str,
+    timeout: Duration,
+) -> Result<ExecutionResult, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        // As in `poll`, query errors are expected until the node has seen the deploy.
+        if let Ok(Some(result)) = query(deploy_hash, node_address) {
+            return Ok(result);
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "deploy {} wasn't executed within {} seconds",
+                deploy_hash,
+                timeout.as_secs()
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Runs `casper-client get-deploy`, returning the execution result if the deploy has been executed.
+fn query(deploy_hash: &str, node_address: &str) -> Result<Option<ExecutionResult>, String> {
+    let result = rpc::call(node_address, &["get-deploy", deploy_hash])?;
+    Ok(parse_execution_result(&result))
+}
+
+/// Returns the execution result from the `result` of an `info_get_deploy` response, if present.
+///
+/// Casper 1.x nodes return a list of `execution_results`, while 2.x nodes return a single
+/// `execution_info` whose result is versioned.
+fn parse_execution_result(result: &Value) -> Option<ExecutionResult> {
+    if let Some(execution_results) = result["execution_results"].as_array() {
+        let first = execution_results.first()?;
+        return parse_v1_result(&first["block_hash"], &first["result"]);
+    }
+
+    let execution_info = &result["execution_info"];
+    parse_versioned_result(
+        &execution_info["block_hash"],
+        &execution_info["execution_result"],
+    )
+}
+
+/// Parses an execution result, which 2.x nodes wrap in a `Version1` or `Version2` variant.
+pub(super) fn parse_versioned_result(
+    block_hash: &Value,
+    execution_result: &Value,
+) -> Option<ExecutionResult> {
+    if let Some(version2) = execution_result.get("Version2") {
+        return Some(ExecutionResult {
+            block_hash: block_hash.as_str()?.to_string(),
+            cost: json_to_string(&version2["cost"]),
+            error_message: version2["error_message"].as_str().map(str::to_string),
+        });
+    }
+    match execution_result.get("Version1") {
+        Some(version1) => parse_v1_result(block_hash, version1),
+        None => parse_v1_result(block_hash, execution_result),
+    }
+}
+
+/// Parses an execution result holding either a `Success` or a `Failure`.
+fn parse_v1_result(block_hash: &Value, result: &Value) -> Option<ExecutionResult> {
+    let (outcome, error_message) = match result.get("Failure") {
+        Some(failure) => (failure, Some(json_to_string(&failure["error_message"]))),
+        None => (result.get("Success")?, None),
+    };
+    Some(ExecutionResult {
+        block_hash: block_hash.as_str()?.to_string(),
+        cost: json_to_string(&outcome["cost"]),
+        error_message,
+    })
+}
+
+/// Returns the contents of a JSON string, or the JSON representation of any other value.
+fn json_to_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use casper_types::U512;
+use serde_json::Value;
+
+use super::{deploy_status, rpc, Error};
+
+/// The default port of a node's JSON-RPC server.
+const RPC_PORT: &str = ":7777";
+
+/// The default port of a node's speculative execution server, which is disabled unless the node
+/// operator enables it.
+const SPECULATIVE_EXEC_PORT: &str = ":7778";
+
+/// The prefix of the formatted account hashes used as global state keys.
+const ACCOUNT_KEY_PREFIX: &str = "account-hash-";
+
+/// The predicted outcome of executing a deploy against the current global state without committing
+/// it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DryRun {
+    /// The block whose global state the deploy was executed against.
+    pub block_hash: String,
+    /// The cost in motes of executing the deploy.
+    pub cost: String,
+    /// The error if execution failed, e.g. `ApiError::DuplicateKey [22]`.
+    pub error_message: Option<String>,
+    /// The cause of `error_message` if it's one the session code is known to raise.
+    pub cause: Option<String>,
+    /// The changes the deploy would make to accounts, as `<account hash>: <transform>` entries.
+    pub account_effects: Vec<String>,
+    /// The payment amount in motes suggested for the deploy, i.e. its cost plus a margin, if it
+    /// would succeed.
+    pub suggested_payment: Option<U512>,
+}
+
+/// Returns the address of the speculative execution server of the node at the given JSON-RPC
+/// address, assuming it uses the default ports.
+pub(super) fn default_address(node_address: &str) -> Option<String> {
+    let host = rpc::primary_node_address(node_address)
+        .trim_end_matches('/')
+        .strip_suffix(RPC_PORT)?;
+    Some(format!("{}{}", host, SPECULATIVE_EXEC_PORT))
+}
+
+/// Executes the deploy saved in `deploy_file` on the given node's speculative execution server
+/// using `casper-client send-deploy --speculative-exec`.
+pub(super) fn run(deploy_file: &Path, node_address: &str) -> Result<DryRun, Error> {
+    let make_error = |inner: String| Error::DryRun {
+        node_address: node_address.trim().to_string(),
+        inner,
+    };
+
+    let deploy_file = deploy_file.display().to_string();
+    let result = rpc::call(
+        node_address,
+        &[
+            "send-deploy",
+            "--input",
+            &deploy_file,
+            // Without a value, the deploy is executed against the latest block.
+            "--speculative-exec",
+        ],
+    )
+    .map_err(make_error)?;
+
+    let execution_result = &result["execution_result"];
+    let outcome = deploy_status::parse_versioned_result(&result["block_hash"], execution_result)
+        .ok_or_else(|| make_error("the response holds no execution result".to_string()))?;
+    let cause = outcome
+        .error_message
+        .as_deref()
+        .and_then(deploy_status::failure_cause);
+    Ok(DryRun {
+        block_hash: outcome.block_hash,
+        cost: outcome.cost,
+        error_message: outcome.error_message,
+        cause,
+        account_effects: account_effects(execution_result),
+        suggested_payment: None,
+    })
+}
+
+/// Returns the non-trivial transforms of account keys in the given execution result's effects.
+fn account_effects(execution_result: &Value) -> Vec<String> {
+    let result = execution_result.get("Version1").unwrap_or(execution_result);
+    let outcome = result
+        .get("Success")
+        .or_else(|| result.get("Failure"))
+        .unwrap_or(&Value::Null);
+    outcome["effect"]["transforms"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|entry| {
+            let key = entry["key"].as_str()?;
+            let transform = match &entry["transform"] {
+                Value::String(name) => name.clone(),
+                Value::Object(variant) => variant.keys().next()?.clone(),
+                _ => return None,
+            };
+            if !key.starts_with(ACCOUNT_KEY_PREFIX) || transform == "Identity" {
+                return None;
+            }
+            Some(format!("{}: {}", key, transform))
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use serde_json::Value;
+
+use super::{
+    deploy_status::{self, ExecutionResult},
+    rpc,
+};
+
+/// The default port of a node's JSON-RPC server.
+const RPC_PORT: &str = ":7777";
+
+/// The default port and path of a node's main event stream, which carries `DeployProcessed`
+/// events.
+const EVENT_STREAM_PORT_AND_PATH: &str = ":9999/events/main";
+
+/// The prefix of the lines of a server-sent event which hold its JSON data.
+const DATA_PREFIX: &str = "data:";
+
+/// Returns the URL of the main event stream of the node at the given JSON-RPC address, assuming it
+/// uses the default ports, e.g. `http://1.2.3.4:9999/events/main` for `http://1.2.3.4:7777`.
+///
+/// Casper 2.x nodes serve all events at `/events` instead.
+pub(super) fn default_url(node_address: &str) -> Option<String> {
+    let host = rpc::primary_node_address(node_address)
+        .trim_end_matches('/')
+        .strip_suffix(RPC_PORT)?;
+    Some(format!("{}{}", host, EVENT_STREAM_PORT_AND_PATH))
+}
+
+/// A subscription to a node's event stream, watching for a single deploy to be processed.
+///
+/// The stream is read using `curl`, which is killed when the listener is dropped.
+pub(super) struct Listener {
+    curl: Child,
+    results: Receiver<ExecutionResult>,
+}
+
+impl Listener {
+    /// Subscribes to the event stream at `url` and starts watching for the deploy with the given
+    /// hex-encoded hash.
+    pub(super) fn start(url: &str, deploy_hash: &str) -> Result<Self, String> {
+        // The connection settings are passed on stdin to keep any API keys out of the args.
+        let mut curl = Command::new("curl")
+            .args(&["--silent", "--no-buffer", "--fail"])
+            .args(["--config", "-"])
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("failed to run curl: {}", error))?;
+        // Dropping stdin once written closes it, so curl reads the whole config.
+        if let Err(error) = curl
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(rpc::curl_config().as_bytes())
+        {
+            let _ = curl.kill();
+            let _ = curl.wait();
+            return Err(format!(
+                "failed to send the connection settings to curl: {}",
+                error
+            ));
+        }
+        let stdout = curl.stdout.take().unwrap();
+
+        let deploy_hash = deploy_hash.to_string();
+        let (sender, results) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if let Some(result) = line
+                    .strip_prefix(DATA_PREFIX)
+                    .and_then(|data| processed_deploy(data, &deploy_hash))
+                {
+                    let _ = sender.send(result);
+                    break;
+                }
+            }
+        });
+
+        Ok(Listener { curl, results })
+    }
+
+    /// Waits up to `timeout` for the deploy to be processed, returning its execution result.
+    ///
+    /// Returns `Err(RecvTimeoutError::Disconnected)` once the stream has closed.
+    pub(super) fn wait(&self, timeout: Duration) -> Result<ExecutionResult, RecvTimeoutError> {
+        self.results.recv_timeout(timeout)
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = self.curl.kill();
+        let _ = self.curl.wait();
+    }
+}
+
+/// Returns the execution result held by the given event data if it reports that the deploy with
+/// the given hash was processed.
+///
+/// Casper 1.x nodes emit `DeployProcessed` events, while 2.x nodes emit `TransactionProcessed`
+/// events which identify deploys by a `Deploy` variant of the transaction hash.
+fn processed_deploy(data: &str, deploy_hash: &str) -> Option<ExecutionResult> {
+    let event = serde_json::from_str::<Value>(data.trim()).ok()?;
+    let (processed, hash) = match event.get("DeployProcessed") {
+        Some(processed) => (processed, &processed["deploy_hash"]),
+        None => {
+            let processed = event.get("TransactionProcessed")?;
+            (processed, &processed["transaction_hash"]["Deploy"])
+        }
+    };
+    if hash.as_str()? != deploy_hash {
+        return None;
+    }
+    deploy_status::parse_versioned_result(&processed["block_hash"], &processed["execution_result"])
+}
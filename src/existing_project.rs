@@ -0,0 +1,144 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use super::{
+    key_configuration::KeyConfiguration,
+    prebuilt_session::{
+        ARG_ACCOUNT_PREFIX, ARG_DEPLOYMENT_THRESHOLD, ARG_KEY_COUNT, ARG_KEY_MANAGEMENT_THRESHOLD,
+        ARG_REMOVE_MAIN_ACCOUNT, ARG_WEIGHT_PREFIX,
+    },
+    provenance::{self, Provenance},
+    smart_contract::SESSION_ARGS_FILE,
+};
+
+/// The statement with which the setup session code removes the main account's key.
+const REMOVE_MAIN_KEY_STATEMENT: &str = "remove_associated_key(MAIN_ACCOUNT_HASH)";
+
+/// Returns the configuration the project at `project_dir` was generated from, so it can be
+/// adjusted and regenerated.
+///
+/// The keys and their labels come from the provenance header of `src/main.rs`, which is verified
+/// first.  The weights and thresholds come from the constants baked into the session code, or
+/// from `session_args.txt` if the project uses the generic session contract.
+pub(super) fn read_configuration(project_dir: &Path) -> Result<KeyConfiguration, String> {
+    if !project_dir.is_dir() {
+        return Err("no such folder".to_string());
+    }
+    let provenance = provenance::verify_project(project_dir).map_err(|error| error.to_string())?;
+
+    let session_args_path = provenance::contract_dir(project_dir).join(SESSION_ARGS_FILE);
+    let mut configuration = if session_args_path.exists() {
+        let session_args =
+            fs::read_to_string(&session_args_path).map_err(|error| error.to_string())?;
+        from_session_args(&session_args)?
+    } else {
+        let mut sources = vec![];
+        for path in provenance::session_source_paths(project_dir) {
+            sources.push(fs::read_to_string(&path).map_err(|error| error.to_string())?);
+        }
+        from_constants(&provenance, &sources)?
+    };
+
+    configuration.key_labels = provenance
+        .keys
+        .into_iter()
+        .filter_map(|(account_hash, label)| Some((account_hash, label?)))
+        .collect();
+    Ok(configuration)
+}
+
+/// Returns the configuration baked into the setup session code `sources` as constants, e.g.
+/// `const ACCOUNT_1_WEIGHT: u8 = 2;`, with the keys in the order `provenance` lists them.
+fn from_constants(provenance: &Provenance, sources: &[String]) -> Result<KeyConfiguration, String> {
+    let mut constants = HashMap::new();
+    for line in sources.iter().flat_map(|source| source.lines()) {
+        let parsed = line
+            .strip_prefix("const ")
+            .and_then(|line| line.strip_suffix(';'))
+            .and_then(|line| line.split_once(": u8 = "));
+        if let Some((name, value)) = parsed {
+            let value = value
+                .parse::<u8>()
+                .map_err(|_| format!("{} is not a weight from 0 to 255", name))?;
+            constants.insert(name.to_string(), value);
+        }
+    }
+    let constant = |name: &str| {
+        constants.get(name).copied().ok_or_else(|| {
+            format!(
+                "the session code has no {} constant, so the project wasn't generated to set \
+                up the account",
+                name
+            )
+        })
+    };
+
+    let mut weights = vec![constant("MAIN_ACCOUNT_WEIGHT")?];
+    while let Some(weight) = constants.get(&format!("ACCOUNT_{}_WEIGHT", weights.len())) {
+        weights.push(*weight);
+    }
+    if weights.len() > provenance.keys.len() {
+        return Err(format!(
+            "the session code adds {} keys but the provenance header lists {}",
+            weights.len(),
+            provenance.keys.len()
+        ));
+    }
+
+    Ok(KeyConfiguration {
+        associated_keys: provenance
+            .keys
+            .iter()
+            .map(|(account_hash, _)| account_hash.clone())
+            .zip(weights)
+            .collect(),
+        primary_key_should_be_deleted: sources
+            .iter()
+            .any(|source| source.contains(REMOVE_MAIN_KEY_STATEMENT)),
+        key_management_weight: constant("KEY_MANAGEMENT_WEIGHT")?,
+        deployment_weight: constant("DEPLOYMENT_WEIGHT")?,
+        key_labels: vec![],
+    })
+}
+
+/// Returns the configuration passed to the generic session contract by `session_args`, the
+/// casper-client `--session-arg` values one per line, e.g. `weight_0:u8='1'`.
+fn from_session_args(session_args: &str) -> Result<KeyConfiguration, String> {
+    let mut args = HashMap::new();
+    for line in session_args.lines().filter(|line| !line.trim().is_empty()) {
+        let (name, typed_value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("malformed session arg '{}'", line))?;
+        let value = typed_value
+            .split_once('=')
+            .map(|(_, value)| value.trim_matches('\''))
+            .ok_or_else(|| format!("malformed session arg '{}'", line))?;
+        args.insert(name.to_string(), value.to_string());
+    }
+    let arg = |name: &str| {
+        args.get(name)
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} has no '{}' arg", SESSION_ARGS_FILE, name))
+    };
+    let weight = |name: &str| {
+        arg(name)?
+            .parse::<u8>()
+            .map_err(|_| format!("'{}' is not a weight from 0 to 255", name))
+    };
+
+    let key_count = weight(ARG_KEY_COUNT)?;
+    let associated_keys = (0..key_count)
+        .map(|index| {
+            Ok((
+                arg(&format!("{}{}", ARG_ACCOUNT_PREFIX, index))?.to_string(),
+                weight(&format!("{}{}", ARG_WEIGHT_PREFIX, index))?,
+            ))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(KeyConfiguration {
+        associated_keys,
+        primary_key_should_be_deleted: arg(ARG_REMOVE_MAIN_ACCOUNT)? == "true",
+        key_management_weight: weight(ARG_KEY_MANAGEMENT_THRESHOLD)?,
+        deployment_weight: weight(ARG_DEPLOYMENT_THRESHOLD)?,
+        key_labels: vec![],
+    })
+}
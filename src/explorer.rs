@@ -0,0 +1,30 @@
+use casper_node::types::Deploy;
+use casper_types::account::AccountHash;
+
+/// The prefix of a formatted account hash, omitted from explorer URLs.
+const ACCOUNT_HASH_PREFIX: &str = "account-hash-";
+
+/// The block explorer for Casper mainnet.
+pub(super) const MAINNET_EXPLORER_URL: &str = "https://cspr.live";
+
+/// The block explorer for the official Casper testnet.
+pub(super) const TESTNET_EXPLORER_URL: &str = "https://testnet.cspr.live";
+
+/// Returns the URL of the given account's page on the block explorer at `explorer_url`.
+pub(super) fn account_url(explorer_url: &str, account_hash: &AccountHash) -> String {
+    let formatted = account_hash.to_formatted_string();
+    format!(
+        "{}/account/{}",
+        explorer_url.trim().trim_end_matches('/'),
+        formatted.trim_start_matches(ACCOUNT_HASH_PREFIX)
+    )
+}
+
+/// Returns the URL of the given deploy's page on the block explorer at `explorer_url`.
+pub(super) fn deploy_url(explorer_url: &str, deploy: &Deploy) -> String {
+    format!(
+        "{}/deploy/{:x}",
+        explorer_url.trim().trim_end_matches('/'),
+        deploy.id().inner()
+    )
+}
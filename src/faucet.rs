@@ -0,0 +1,44 @@
+use std::process::Command;
+
+use casper_types::{crypto::AsymmetricType, PublicKey};
+
+use super::Error;
+
+/// The chain name of the official Casper testnet.
+pub(super) const TESTNET_CHAIN_NAME: &str = "casper-test";
+
+/// The official testnet's faucet, which sends test CSPR to an account once the user has proven
+/// they're human, so it can't be driven without a browser.
+pub(super) const TESTNET_FAUCET_URL: &str = "https://testnet.cspr.live/tools/faucet";
+
+/// Opens the testnet faucet in the default browser, returning the hex-encoded form of the given
+/// public key, which is what the faucet asks for.
+pub(super) fn open_testnet_faucet(public_key: &str) -> Result<String, Error> {
+    let public_key = PublicKey::from_hex(public_key.trim()).map_err(|error| Error::Faucet {
+        inner: format!("failed to parse public key: {}", error),
+    })?;
+    open_in_browser(TESTNET_FAUCET_URL).map_err(|inner| Error::Faucet { inner })?;
+    Ok(public_key.to_hex())
+}
+
+/// Opens `url` in the default browser.
+pub(super) fn open_in_browser(url: &str) -> Result<(), String> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty arg is the title of the window `start` would otherwise take from the URL.
+        command.args(&["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command
+        .arg(url)
+        .status()
+        .map_err(|error| format!("failed to open {}: {}", url, error))?;
+    if !status.success() {
+        return Err(format!("failed to open {}: {}", url, status));
+    }
+    Ok(())
+}
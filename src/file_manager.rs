@@ -0,0 +1,24 @@
+use std::{path::Path, process::Command};
+
+/// Opens `dir` in the system's file manager.
+pub(super) fn open_dir(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err("no such folder".to_string());
+    }
+    let mut command = if cfg!(windows) {
+        Command::new("explorer")
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command
+        .arg(dir)
+        .status()
+        .map_err(|error| error.to_string())?;
+    // Explorer exits with a failure status even once it has opened the folder.
+    if !status.success() && !cfg!(windows) {
+        return Err(status.to_string());
+    }
+    Ok(())
+}
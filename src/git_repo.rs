@@ -0,0 +1,71 @@
+use std::{path::Path, process::Command};
+
+use super::{write_if_changed, Error};
+
+const GITIGNORE_CONTENTS: &str = "target/\n";
+
+/// The identity used for the initial commit if the user hasn't configured one for git.
+const FALLBACK_USER_NAME: &str = "casper-multisig-tool";
+const FALLBACK_USER_EMAIL: &str = "casper-multisig-tool@localhost";
+
+/// Initializes a git repository in `project_dir` (or reinitializes an existing one), writes a
+/// `.gitignore` and commits all the project's files with the given message.
+pub(super) fn init_and_commit(project_dir: &Path, commit_message: &str) -> Result<(), Error> {
+    write_if_changed(project_dir.join(".gitignore"), GITIGNORE_CONTENTS).unwrap();
+
+    run_git(project_dir, &["init", "--quiet"])?;
+    run_git(project_dir, &["add", "--all"])?;
+
+    let mut commit_args = vec![];
+    if !has_configured_identity(project_dir) {
+        commit_args.push("-c".to_string());
+        commit_args.push(format!("user.name={}", FALLBACK_USER_NAME));
+        commit_args.push("-c".to_string());
+        commit_args.push(format!("user.email={}", FALLBACK_USER_EMAIL));
+    }
+    // Allow empty commits so that regenerating an unchanged project still records the config.
+    commit_args.extend(
+        [
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "--message",
+            commit_message,
+        ]
+        .iter()
+        .map(|arg| arg.to_string()),
+    );
+    run_git(project_dir, &commit_args)
+}
+
+fn has_configured_identity(project_dir: &Path) -> bool {
+    ["user.name", "user.email"].iter().all(|key| {
+        Command::new("git")
+            .args(&["config", key])
+            .current_dir(project_dir)
+            .output()
+            .map(|output| output.status.success() && !output.stdout.is_empty())
+            .unwrap_or(false)
+    })
+}
+
+fn run_git<S: AsRef<str>>(project_dir: &Path, args: &[S]) -> Result<(), Error> {
+    let args = args.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    let output = Command::new("git")
+        .args(&args)
+        .current_dir(project_dir)
+        .output()
+        .map_err(|error| Error::GitRepo {
+            inner: format!("failed to run git: {}.  Is git installed?", error),
+        })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(Error::GitRepo {
+        inner: format!(
+            "'git {}' failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    })
+}
@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Display, Formatter},
+};
+
+use once_cell::sync::Lazy;
+
+/// The Spanish catalog, in gettext's PO format with each message's English text as its id.
+const SPANISH_CATALOG: &str = include_str!("../locales/es.po");
+
+static SPANISH: Lazy<HashMap<String, String>> = Lazy::new(|| parse_catalog(SPANISH_CATALOG));
+
+/// The language the UI is shown in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Language {
+    English,
+    Spanish,
+    /// Follows the OS's locale where it's one of the other languages, and is English otherwise.
+    #[default]
+    System,
+}
+
+impl Language {
+    /// All the languages.
+    pub const ALL: [Language; 3] = [Language::English, Language::Spanish, Language::System];
+
+    /// Returns the language the UI is translated into, detecting the OS's locale for `System`.
+    pub(super) fn resolve(&self) -> Language {
+        match self {
+            Language::System => system_language(),
+            language => *language,
+        }
+    }
+
+    /// Returns the name the language is stored under in the preferences file, which for a
+    /// translated language is also the name of its catalog under `locales`.
+    pub(super) fn id(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+            Language::System => "system",
+        }
+    }
+
+    /// Returns the translation of `text`, or `None` if the language has none for it.
+    pub(super) fn translate(&self, text: &str) -> Option<&'static str> {
+        let catalog = match self.resolve() {
+            Language::Spanish => &SPANISH,
+            Language::English | Language::System => return None,
+        };
+        catalog.get(text).map(String::as_str)
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        // Named in their own language, so they can be found whichever one is shown.
+        match self {
+            Language::English => write!(formatter, "English"),
+            Language::Spanish => write!(formatter, "Español"),
+            Language::System => write!(formatter, "System"),
+        }
+    }
+}
+
+/// Returns `template` with each `{}` in turn replaced by the next of `args`.
+pub(super) fn fill(template: &str, args: &[&str]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}").peekable();
+    while let Some(part) = parts.next() {
+        filled.push_str(part);
+        if parts.peek().is_some() {
+            filled.push_str(args.next().copied().unwrap_or("{}"));
+        }
+    }
+    filled
+}
+
+/// Returns the language of the OS's locale, e.g. `es_ES.UTF-8`, taken from the first of
+/// `LC_ALL`, `LC_MESSAGES` and `LANG` which is set, or English if none is or it isn't translated.
+fn system_language() -> Language {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    let code = locale
+        .split(['_', '.', '-', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    Language::ALL
+        .iter()
+        .copied()
+        .find(|language| *language != Language::System && language.id() == code)
+        .unwrap_or(Language::English)
+}
+
+/// Parses a PO catalog into its translations keyed by their English text.
+///
+/// Only `msgid` and `msgstr` entries are read, each of which may continue over several quoted
+/// lines.  Untranslated entries, i.e. those with an empty `msgstr`, and fuzzy ones are skipped so
+/// that the English is shown instead.
+fn parse_catalog(catalog: &str) -> HashMap<String, String> {
+    let mut translations = HashMap::new();
+    let mut msgid = String::new();
+    let mut msgstr = String::new();
+    let mut in_msgstr = false;
+    let mut fuzzy = false;
+    let mut finish_entry = |msgid: &mut String, msgstr: &mut String, fuzzy: &mut bool| {
+        if !msgid.is_empty() && !msgstr.is_empty() && !*fuzzy {
+            translations.insert(msgid.clone(), msgstr.clone());
+        }
+        msgid.clear();
+        msgstr.clear();
+        *fuzzy = false;
+    };
+
+    for line in catalog.lines().map(str::trim) {
+        // A comment or `msgid` after a `msgstr` starts the next entry.
+        let starts_entry = line.starts_with('#') || line.starts_with("msgid ");
+        if starts_entry && in_msgstr {
+            finish_entry(&mut msgid, &mut msgstr, &mut fuzzy);
+            in_msgstr = false;
+        }
+        if let Some(quoted) = line.strip_prefix("msgid ") {
+            msgid = unquote(quoted);
+        } else if let Some(quoted) = line.strip_prefix("msgstr ") {
+            msgstr = unquote(quoted);
+            in_msgstr = true;
+        } else if line.starts_with('"') {
+            if in_msgstr {
+                msgstr.push_str(&unquote(line));
+            } else {
+                msgid.push_str(&unquote(line));
+            }
+        } else if line.starts_with("#,") && line.contains("fuzzy") {
+            fuzzy = true;
+        }
+    }
+    finish_entry(&mut msgid, &mut msgstr, &mut fuzzy);
+    translations
+}
+
+/// Returns the contents of a quoted PO string with its escapes, e.g. `\"` and `\n`, replaced.
+fn unquote(quoted: &str) -> String {
+    let inner = quoted
+        .trim()
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or_default();
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unquoted.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unquoted.push('\n'),
+            Some('t') => unquoted.push('\t'),
+            Some(escaped) => unquoted.push(escaped),
+            None => (),
+        }
+    }
+    unquoted
+}
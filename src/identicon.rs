@@ -0,0 +1,124 @@
+use casper_types::account::AccountHash;
+
+/// The number of cells along each side of the grid.
+const GRID_SIZE: usize = 5;
+
+/// The number of columns chosen freely; the rest mirror them, so the identicon is symmetric.
+const DISTINCT_COLUMNS: usize = GRID_SIZE.div_ceil(2);
+
+/// The width of the blank margin around the grid, in cells.
+const MARGIN: usize = 1;
+
+/// The colour of the cells which aren't filled in.
+const BACKGROUND: [u8; 3] = [0xf0, 0xf0, 0xf0];
+
+/// A small symmetric pattern derived from an account hash, so that keys can be told apart at a
+/// glance rather than by comparing their hex.
+///
+/// Like GitHub's identicons, it's a 5x5 grid of cells mirrored about its middle column, filled in
+/// a single colour.  Both the pattern and the colour are taken from the account hash's bytes,
+/// which are already a Blake2b digest.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Identicon {
+    /// The RGB colour of the filled-in cells.
+    pub color: [u8; 3],
+    /// Whether each cell is filled in, by row then column.
+    pub cells: [[bool; GRID_SIZE]; GRID_SIZE],
+}
+
+impl Identicon {
+    pub(super) fn new(account_hash: &AccountHash) -> Self {
+        let bytes = account_hash.value();
+        let mut cells = [[false; GRID_SIZE]; GRID_SIZE];
+        for (row, row_cells) in cells.iter_mut().enumerate() {
+            for column in 0..DISTINCT_COLUMNS {
+                let filled = bytes[row * DISTINCT_COLUMNS + column].is_multiple_of(2);
+                row_cells[column] = filled;
+                row_cells[GRID_SIZE - 1 - column] = filled;
+            }
+        }
+        let hue = u16::from_be_bytes([bytes[30], bytes[31]]) % 360;
+        Identicon {
+            color: hsl_to_rgb(f64::from(hue), 0.65, 0.45),
+            cells,
+        }
+    }
+
+    /// Returns the width and height in pixels of the identicon drawn with `cell_size` pixel cells,
+    /// including its margin.
+    pub const fn size(cell_size: usize) -> usize {
+        (GRID_SIZE + 2 * MARGIN) * cell_size
+    }
+
+    /// Returns the identicon as RGB pixel data, row by row, with `cell_size` pixel cells.
+    pub fn to_rgb(&self, cell_size: usize) -> Vec<u8> {
+        let size = Self::size(cell_size);
+        let mut pixels = Vec::with_capacity(size * size * 3);
+        for y in 0..size {
+            for x in 0..size {
+                let row = (y / cell_size).checked_sub(MARGIN);
+                let column = (x / cell_size).checked_sub(MARGIN);
+                let filled = match (row, column) {
+                    (Some(row), Some(column)) if row < GRID_SIZE && column < GRID_SIZE => {
+                        self.cells[row][column]
+                    }
+                    _ => false,
+                };
+                pixels.extend_from_slice(if filled { &self.color } else { &BACKGROUND });
+            }
+        }
+        pixels
+    }
+
+    /// Returns the identicon as a standalone SVG document `size` pixels square, e.g. to embed in
+    /// an HTML report.
+    pub fn to_svg(&self, size: usize) -> String {
+        let view_size = GRID_SIZE + 2 * MARGIN;
+        let fill = format!(
+            "#{:02x}{:02x}{:02x}",
+            self.color[0], self.color[1], self.color[2]
+        );
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" \
+            viewBox=\"0 0 {view} {view}\" shape-rendering=\"crispEdges\">\
+            <rect width=\"{view}\" height=\"{view}\" fill=\"#{:02x}{:02x}{:02x}\"/>",
+            BACKGROUND[0],
+            BACKGROUND[1],
+            BACKGROUND[2],
+            size = size,
+            view = view_size
+        );
+        for (row, row_cells) in self.cells.iter().enumerate() {
+            for (column, filled) in row_cells.iter().enumerate() {
+                if *filled {
+                    svg.push_str(&format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>",
+                        column + MARGIN,
+                        row + MARGIN,
+                        fill
+                    ));
+                }
+            }
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+/// Converts a colour from HSL, with the hue in degrees and the others from 0 to 1, to RGB.
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue / 60.0;
+    let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (red, green, blue) = match sector as u8 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let lightness_offset = lightness - chroma / 2.0;
+    let channel = |value: f64| ((value + lightness_offset) * 255.0).round() as u8;
+    [channel(red), channel(green), channel(blue)]
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use casper_node::crypto::AsymmetricKeyExt;
+use casper_types::{account::AccountHash, crypto::AsymmetricType, PublicKey, SecretKey, U512};
+
+use super::{keystore, rpc, Error};
+
+/// The minimum amount in motes which the network accepts for a native transfer.
+pub(super) const MIN_TRANSFER_AMOUNT: u64 = 2_500_000_000;
+
+/// The payment amount in motes for a native transfer.
+const TRANSFER_PAYMENT_AMOUNT: &str = "10000";
+
+/// The values substituted for the node specific options of a transfer command.
+pub(super) struct TransferPlaceholders {
+    pub(super) node_address: &'static str,
+    pub(super) chain_name: &'static str,
+    pub(super) transfer_id: &'static str,
+}
+
+pub(super) const EXAMPLE_PLACEHOLDERS: TransferPlaceholders = TransferPlaceholders {
+    node_address: "<NODE_ADDRESS>",
+    chain_name: "<CHAIN_NAME>",
+    transfer_id: "<TRANSFER_ID>",
+};
+
+pub(super) const MAKEFILE_PLACEHOLDERS: TransferPlaceholders = TransferPlaceholders {
+    node_address: "$(NODE)",
+    chain_name: "$(CHAIN_NAME)",
+    transfer_id: "$(TRANSFER_ID)",
+};
+
+/// A native transfer which funds the main account before the setup deploy is sent, since the
+/// setup deploy's payment is taken from the main account's purse.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct InitialTransfer {
+    pub(super) amount: U512,
+    pub(super) source_secret_key: PathBuf,
+    pub(super) target_public_key: PublicKey,
+}
+
+impl InitialTransfer {
+    /// Parses the transfer's amount in motes, the path of the source account's secret key file
+    /// and the hex-encoded public key of the main account.
+    pub(super) fn new(
+        amount: &str,
+        source_secret_key: &str,
+        target_public_key: &str,
+    ) -> Result<Self, Error> {
+        let make_error = |inner: String| Error::InvalidInitialTransfer { inner };
+
+        let amount = U512::from_dec_str(amount.trim())
+            .map_err(|error| make_error(format!("failed to parse amount: {:?}", error)))?;
+        if amount < U512::from(MIN_TRANSFER_AMOUNT) {
+            return Err(make_error(format!(
+                "the amount must be at least {} motes",
+                MIN_TRANSFER_AMOUNT
+            )));
+        }
+        if keystore::is_keystore(Path::new(source_secret_key)) {
+            return Err(make_error(format!(
+                "{} is an encrypted keystore, which casper-client can't read, so the source \
+                account's secret key must be PEM-encoded",
+                source_secret_key
+            )));
+        }
+        SecretKey::from_file(source_secret_key).map_err(|error| {
+            make_error(format!(
+                "failed to read {} as a secret key: {}",
+                source_secret_key, error
+            ))
+        })?;
+        // The path is used from within the project dir, so it must not be relative.
+        let source_secret_key = fs::canonicalize(source_secret_key)
+            .unwrap_or_else(|_| PathBuf::from(source_secret_key));
+        let target_public_key = PublicKey::from_hex(target_public_key.trim())
+            .map_err(|error| make_error(format!("failed to parse target public key: {}", error)))?;
+
+        Ok(InitialTransfer {
+            amount,
+            source_secret_key,
+            target_public_key,
+        })
+    }
+
+    /// Returns `Ok` if the transfer funds the given main account, else `Err`.
+    pub(super) fn validate_target(&self, main_account_hash: AccountHash) -> Result<(), Error> {
+        if self.target_public_key.to_account_hash() != main_account_hash {
+            return Err(Error::InvalidInitialTransfer {
+                inner: format!(
+                    "the target public key doesn't match the main account {}",
+                    main_account_hash.to_formatted_string()
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the casper-client command for sending the transfer, split into one entry per option.
+    pub(super) fn transfer_args(&self, placeholders: &TransferPlaceholders) -> Vec<String> {
+        vec![
+            "casper-client transfer".to_string(),
+            format!("--node-address {}", placeholders.node_address),
+            format!("--chain-name {}", placeholders.chain_name),
+            format!("--secret-key {}", self.source_secret_key.display()),
+            format!("--amount {}", self.amount),
+            format!("--target-account {}", self.target_public_key.to_hex()),
+            format!("--transfer-id {}", placeholders.transfer_id),
+            format!("--payment-amount {}", TRANSFER_PAYMENT_AMOUNT),
+        ]
+    }
+
+    /// Returns a command which sends the transfer to the given node.
+    ///
+    /// The transfer ID is the current Unix time in milliseconds.
+    pub(super) fn command(&self, node_address: &str, chain_name: &str) -> Command {
+        let transfer_id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+        let mut command = Command::new("casper-client");
+        command
+            .arg("transfer")
+            .args(&["--node-address", node_address])
+            .args(&["--chain-name", chain_name])
+            .arg("--secret-key")
+            .arg(&self.source_secret_key)
+            .args(&["--amount", &self.amount.to_string()])
+            .args(&["--target-account", &self.target_public_key.to_hex()])
+            .args(&["--transfer-id", &transfer_id.to_string()])
+            .args(&["--payment-amount", TRANSFER_PAYMENT_AMOUNT]);
+        rpc::apply_connection_settings(&mut command);
+        command
+    }
+}
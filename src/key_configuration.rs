@@ -0,0 +1,152 @@
+use std::{fs, path::Path};
+
+use casper_types::account::AccountHash;
+use serde_json::{json, Value};
+
+/// The version of the configuration file format written.
+const FORMAT_VERSION: u64 = 1;
+
+/// The associated keys, weights, action thresholds and key labels being configured, which can be
+/// saved to and opened from a JSON file.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct KeyConfiguration {
+    /// The associated keys as `(formatted account hash, weight)` pairs, the main account's first.
+    pub associated_keys: Vec<(String, u8)>,
+    /// Whether the main account's key is removed once the others have been added.
+    pub primary_key_should_be_deleted: bool,
+    pub key_management_weight: u8,
+    pub deployment_weight: u8,
+    /// The labels of account hashes as `(formatted account hash, label)` pairs.
+    pub key_labels: Vec<(String, String)>,
+}
+
+impl KeyConfiguration {
+    /// Returns an error if an account hash is malformed or a key is listed twice.
+    fn validate(&self) -> Result<(), String> {
+        let mut account_hashes = vec![];
+        for (formatted_account_hash, _) in &self.associated_keys {
+            let account_hash = AccountHash::from_formatted_str(formatted_account_hash.trim())
+                .map_err(|error| {
+                    format!(
+                        "failed to parse account hash '{}': {}",
+                        formatted_account_hash, error
+                    )
+                })?;
+            if account_hashes.contains(&account_hash) {
+                return Err(format!(
+                    "{} is listed more than once",
+                    formatted_account_hash
+                ));
+            }
+            account_hashes.push(account_hash);
+        }
+        Ok(())
+    }
+
+    pub(super) fn to_json(&self) -> Value {
+        json!({
+            "version": FORMAT_VERSION,
+            "associated_keys": self
+                .associated_keys
+                .iter()
+                .map(|(account_hash, weight)| json!({
+                    "account_hash": account_hash,
+                    "weight": weight,
+                }))
+                .collect::<Vec<_>>(),
+            "primary_key_should_be_deleted": self.primary_key_should_be_deleted,
+            "action_thresholds": {
+                "key_management": self.key_management_weight,
+                "deployment": self.deployment_weight,
+            },
+            "key_labels": self
+                .key_labels
+                .iter()
+                .map(|(account_hash, label)| json!({
+                    "account_hash": account_hash,
+                    "label": label,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    pub(super) fn from_json(value: &Value) -> Result<Self, String> {
+        if value["version"].as_u64() != Some(FORMAT_VERSION) {
+            return Err(format!(
+                "only version {} configurations are supported",
+                FORMAT_VERSION
+            ));
+        }
+        let weight = |value: &Value, what: &str| {
+            value
+                .as_u64()
+                .filter(|weight| *weight <= u64::from(u8::MAX))
+                .map(|weight| weight as u8)
+                .ok_or_else(|| format!("{} must be a weight from 0 to 255", what))
+        };
+        let string = |value: &Value, what: &str| {
+            value
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("{} must be a string", what))
+        };
+        let entries = |name: &str| {
+            value[name]
+                .as_array()
+                .cloned()
+                .ok_or_else(|| format!("{} must be a list", name))
+        };
+
+        let associated_keys = entries("associated_keys")?
+            .iter()
+            .map(|key| {
+                Ok((
+                    string(&key["account_hash"], "an associated key's account_hash")?,
+                    weight(&key["weight"], "an associated key's weight")?,
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+        let key_labels = match value.get("key_labels") {
+            Some(_) => entries("key_labels")?
+                .iter()
+                .map(|entry| {
+                    Ok((
+                        string(&entry["account_hash"], "a key label's account_hash")?,
+                        string(&entry["label"], "a key label's label")?,
+                    ))
+                })
+                .collect::<Result<_, String>>()?,
+            None => vec![],
+        };
+        let thresholds = &value["action_thresholds"];
+        let configuration = KeyConfiguration {
+            associated_keys,
+            primary_key_should_be_deleted: value["primary_key_should_be_deleted"]
+                .as_bool()
+                .unwrap_or_default(),
+            key_management_weight: weight(
+                &thresholds["key_management"],
+                "the key management threshold",
+            )?,
+            deployment_weight: weight(&thresholds["deployment"], "the deployment threshold")?,
+            key_labels,
+        };
+        configuration.validate()?;
+        Ok(configuration)
+    }
+}
+
+/// Writes `configuration` to the JSON file at `path`.
+pub(super) fn save(configuration: &KeyConfiguration, path: &Path) -> Result<(), String> {
+    configuration.validate()?;
+    let contents = serde_json::to_string_pretty(&configuration.to_json())
+        .map_err(|error| error.to_string())?;
+    fs::write(path, contents).map_err(|error| error.to_string())
+}
+
+/// Reads the configuration in the JSON file at `path`.
+pub(super) fn open(path: &Path) -> Result<KeyConfiguration, String> {
+    let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let value: Value = serde_json::from_str(&contents).map_err(|error| error.to_string())?;
+    KeyConfiguration::from_json(&value)
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use casper_node::crypto::AsymmetricKeyExt;
+use casper_types::{crypto::AsymmetricType, PublicKey, SecretKey};
+use ctr::cipher::{NewCipher, StreamCipher};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// The secret keys of the keystores unlocked this session, by canonical path, so the passphrase is
+/// only asked for once.
+static UNLOCKED: Lazy<Mutex<HashMap<PathBuf, SecretKey>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The version of the Web3 Secret Storage format supported.
+const KEYSTORE_VERSION: u64 = 3;
+
+/// The only supported cipher.
+const CIPHER: &str = "aes-128-ctr";
+
+/// The only supported key derivation function.
+const KDF: &str = "scrypt";
+
+/// The length of the derived key.  The first half is the AES key, and the second authenticates
+/// the ciphertext.
+const DERIVED_KEY_LENGTH: usize = 32;
+
+/// Returns `true` if the file at `path` is a JSON keystore rather than, e.g., a PEM file.
+pub(super) fn is_keystore(path: &Path) -> bool {
+    read(path).is_ok_and(|keystore| keystore["crypto"].is_object())
+}
+
+/// Returns `true` if the keystore at `path` has been unlocked this session.
+pub(super) fn is_unlocked(path: &Path) -> bool {
+    UNLOCKED.lock().unwrap().contains_key(&canonical(path))
+}
+
+/// Returns the public key of the keystore at `path`: that of its secret key if it's been
+/// unlocked, or else the one recorded in its `public_key` field, which needs no passphrase.
+pub(super) fn public_key(path: &Path) -> Result<PublicKey, String> {
+    if let Some(secret_key) = UNLOCKED.lock().unwrap().get(&canonical(path)) {
+        return Ok(PublicKey::from(secret_key));
+    }
+    let keystore = read(path)?;
+    let public_key = keystore["public_key"]
+        .as_str()
+        .ok_or("the keystore records no public key, so must be unlocked to derive it")?;
+    PublicKey::from_hex(public_key)
+        .map_err(|error| format!("failed to parse the recorded public key: {}", error))
+}
+
+/// Decrypts the keystore at `path` with `passphrase`, keeping its secret key for the rest of the
+/// session, and returns its public key.
+pub(super) fn unlock(path: &Path, passphrase: &str) -> Result<PublicKey, String> {
+    let secret_key = decrypt(&read(path)?, passphrase)?;
+    let public_key = PublicKey::from(&secret_key);
+    UNLOCKED.lock().unwrap().insert(canonical(path), secret_key);
+    Ok(public_key)
+}
+
+/// Reads the secret key in the file at `path`, which is either PEM-encoded or a keystore which has
+/// been unlocked.
+pub(super) fn read_secret_key(path: &Path) -> Result<SecretKey, String> {
+    if !is_keystore(path) {
+        return SecretKey::from_file(path).map_err(|error| error.to_string());
+    }
+    UNLOCKED
+        .lock()
+        .unwrap()
+        .get(&canonical(path))
+        .cloned()
+        .ok_or_else(|| "it's an encrypted keystore which hasn't been unlocked".to_string())
+}
+
+/// Returns the secret key held in `keystore`, a Web3 Secret Storage v3 document, decrypted with
+/// `passphrase`.
+///
+/// As the key's algorithm isn't part of that format, the keystore must also have either a
+/// `public_key` field holding the hex-encoded public key, which is checked against the decrypted
+/// key, or a `key_algorithm` field of `ed25519` or `secp256k1`.
+fn decrypt(keystore: &Value, passphrase: &str) -> Result<SecretKey, String> {
+    if keystore["version"].as_u64() != Some(KEYSTORE_VERSION) {
+        return Err(format!(
+            "only version {} keystores are supported",
+            KEYSTORE_VERSION
+        ));
+    }
+    let crypto = &keystore["crypto"];
+    if crypto["cipher"].as_str() != Some(CIPHER) {
+        return Err(format!("only the {} cipher is supported", CIPHER));
+    }
+    if crypto["kdf"].as_str() != Some(KDF) {
+        return Err(format!(
+            "only the {} key derivation function is supported",
+            KDF
+        ));
+    }
+    let derived_key = derive_key(&crypto["kdfparams"], passphrase)?;
+    decrypt_with_derived_key(keystore, &derived_key)
+}
+
+/// Returns the key derived from `passphrase` by scrypt with `kdf_params`.
+fn derive_key(kdf_params: &Value, passphrase: &str) -> Result<[u8; DERIVED_KEY_LENGTH], String> {
+    let number = |name: &str| {
+        kdf_params[name]
+            .as_u64()
+            .ok_or_else(|| format!("the keystore has no valid scrypt {}", name))
+    };
+    let n = number("n")?;
+    if n < 2 || !n.is_power_of_two() {
+        return Err("the scrypt n must be a power of two greater than one".to_string());
+    }
+    if number("dklen")? != DERIVED_KEY_LENGTH as u64 {
+        return Err(format!(
+            "the derived key must be {} bytes long",
+            DERIVED_KEY_LENGTH
+        ));
+    }
+    let r = u32::try_from(number("r")?).map_err(|error| error.to_string())?;
+    let p = u32::try_from(number("p")?).map_err(|error| error.to_string())?;
+    let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p)
+        .map_err(|error| format!("invalid scrypt parameters: {}", error))?;
+    let salt = hex_field(&kdf_params["salt"], "salt")?;
+
+    let mut derived_key = [0; DERIVED_KEY_LENGTH];
+    scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut derived_key)
+        .map_err(|error| error.to_string())?;
+    Ok(derived_key)
+}
+
+/// Returns the secret key held in `keystore`, decrypted with `derived_key`, once the MAC has shown
+/// it's the right key.
+fn decrypt_with_derived_key(keystore: &Value, derived_key: &[u8]) -> Result<SecretKey, String> {
+    let crypto = &keystore["crypto"];
+    let iv = hex_field(&crypto["cipherparams"]["iv"], "IV")?;
+    let ciphertext = hex_field(&crypto["ciphertext"], "ciphertext")?;
+    let mac = hex_field(&crypto["mac"], "MAC")?;
+
+    let mut hasher = Keccak256::new();
+    hasher.update(&derived_key[16..]);
+    hasher.update(&ciphertext);
+    if hasher.finalize().as_slice() != mac.as_slice() {
+        return Err("the passphrase is wrong, or the keystore is corrupt".to_string());
+    }
+
+    let mut secret_bytes = ciphertext;
+    Aes128Ctr::new_from_slices(&derived_key[..16], &iv)
+        .map_err(|_| "the IV must be 16 bytes long".to_string())?
+        .apply_keystream(&mut secret_bytes);
+
+    let recorded_public_key = match keystore["public_key"].as_str() {
+        Some(public_key) => Some(
+            PublicKey::from_hex(public_key)
+                .map_err(|error| format!("failed to parse the recorded public key: {}", error))?,
+        ),
+        None => None,
+    };
+    let secret_key =
+        match (&recorded_public_key, keystore["key_algorithm"].as_str()) {
+            (Some(PublicKey::Ed25519(_)), _) | (None, Some("ed25519")) => {
+                SecretKey::ed25519_from_bytes(&secret_bytes)
+            }
+            (Some(PublicKey::Secp256k1(_)), _) | (None, Some("secp256k1")) => {
+                SecretKey::secp256k1_from_bytes(&secret_bytes)
+            }
+            _ => return Err(
+                "the keystore must have a public_key, or a key_algorithm of ed25519 or secp256k1"
+                    .to_string(),
+            ),
+        }
+        .map_err(|error| format!("the decrypted secret key is invalid: {}", error))?;
+    if recorded_public_key.is_some_and(|public_key| public_key != PublicKey::from(&secret_key)) {
+        return Err("the decrypted secret key doesn't match the recorded public key".to_string());
+    }
+    Ok(secret_key)
+}
+
+/// Reads and parses the JSON file at `path`.
+fn read(path: &Path) -> Result<Value, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|error| format!("failed to read {}: {}", path.display(), error))?;
+    serde_json::from_str(&contents)
+        .map_err(|error| format!("failed to parse {}: {}", path.display(), error))
+}
+
+/// Decodes the hex-encoded string `value`, named `what` in any error.
+fn hex_field(value: &Value, what: &str) -> Result<Vec<u8>, String> {
+    value
+        .as_str()
+        .and_then(|hex| hex::decode(hex).ok())
+        .ok_or_else(|| format!("the keystore has no valid {}", what))
+}
+
+/// Returns the canonical form of `path`, so a keystore is found whichever path it's chosen by.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The secret key of the Web3 Secret Storage Definition's test vectors.
+    const TEST_SECRET_KEY: &str =
+        "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    /// The scrypt test vector of the Web3 Secret Storage Definition, with the `key_algorithm`
+    /// field added, as the format doesn't record it.
+    fn test_vector() -> Value {
+        serde_json::json!({
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": {
+                    "iv": "83dbcc02d8ccb40e466191a123791e0e"
+                },
+                "ciphertext": "d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c",
+                "kdf": "scrypt",
+                "kdfparams": {
+                    "dklen": 32,
+                    "n": 262144,
+                    "r": 1,
+                    "p": 8,
+                    "salt": "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"
+                },
+                "mac": "2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097"
+            },
+            "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+            "version": 3,
+            "key_algorithm": "secp256k1"
+        })
+    }
+
+    fn test_secret_key() -> SecretKey {
+        SecretKey::secp256k1_from_bytes(hex::decode(TEST_SECRET_KEY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn decrypts_test_vector() {
+        // The vector's scrypt parameters break RFC 7914's limit on n for an r of 1, so the scrypt
+        // crate won't derive its key.  The derived key given alongside the vector is used instead.
+        let derived_key =
+            hex::decode("fac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd")
+                .unwrap();
+
+        let secret_key = decrypt_with_derived_key(&test_vector(), &derived_key).unwrap();
+
+        assert_eq!(
+            PublicKey::from(&secret_key),
+            PublicKey::from(&test_secret_key())
+        );
+    }
+
+    #[test]
+    fn derives_rfc_7914_key() {
+        // The second test vector of RFC 7914, whose first 32 bytes are the derived key.
+        let kdf_params = serde_json::json!({
+            "dklen": 32,
+            "n": 1024,
+            "r": 8,
+            "p": 16,
+            "salt": hex::encode("NaCl")
+        });
+
+        let derived_key = derive_key(&kdf_params, "password").unwrap();
+
+        assert_eq!(
+            hex::encode(derived_key),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162"
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_passphrase() {
+        // The vector's secret key encrypted under the RFC 7914 parameters, which scrypt accepts.
+        let mut keystore = test_vector();
+        keystore["crypto"]["kdfparams"] = serde_json::json!({
+            "dklen": 32,
+            "n": 1024,
+            "r": 8,
+            "p": 16,
+            "salt": hex::encode("NaCl")
+        });
+        let derived_key = derive_key(&keystore["crypto"]["kdfparams"], "password").unwrap();
+        let iv = hex::decode("83dbcc02d8ccb40e466191a123791e0e").unwrap();
+        let mut ciphertext = hex::decode(TEST_SECRET_KEY).unwrap();
+        Aes128Ctr::new_from_slices(&derived_key[..16], &iv)
+            .unwrap()
+            .apply_keystream(&mut ciphertext);
+        let mut hasher = Keccak256::new();
+        hasher.update(&derived_key[16..]);
+        hasher.update(&ciphertext);
+        keystore["crypto"]["ciphertext"] = Value::from(hex::encode(&ciphertext));
+        keystore["crypto"]["mac"] = Value::from(hex::encode(hasher.finalize().as_slice()));
+
+        let secret_key = decrypt(&keystore, "password").unwrap();
+        assert_eq!(
+            PublicKey::from(&secret_key),
+            PublicKey::from(&test_secret_key())
+        );
+
+        let error = decrypt(&keystore, "wrong password").unwrap_err();
+        assert_eq!(error, "the passphrase is wrong, or the keystore is corrupt");
+    }
+}
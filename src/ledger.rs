@@ -0,0 +1,268 @@
+//! Signing with the Casper app on a Ledger hardware wallet.
+//!
+//! The app holds secp256k1 keys derived at `m/44'/506'/0'/0/<index>`, and is driven by APDU
+//! commands sent over USB HID.  The transport needs the `ledger` feature; without it, opening the
+//! device fails with an error.
+
+use casper_node::types::{Approval, Deploy};
+use casper_types::{bytesrepr::ToBytes, crypto::AsymmetricType, PublicKey, Signature};
+
+use super::{deploy, Error};
+
+/// Returns the public key at a derivation path.
+const INS_GET_ADDRESS: u8 = 0x01;
+/// Signs a message after the user has reviewed it on the device.
+const INS_SIGN: u8 = 0x02;
+
+/// Returns the public key without showing its address on the device.
+const P1_NO_CONFIRM: u8 = 0x00;
+/// Has the device show the key's address so that the user can confirm it.
+const P1_CONFIRM: u8 = 0x01;
+/// Marks the first chunk of a message to be signed, which holds the derivation path.
+const P1_INIT: u8 = 0x00;
+/// Marks an intermediate chunk of a message to be signed.
+const P1_ADD: u8 = 0x01;
+/// Marks the last chunk of a message to be signed.
+const P1_LAST: u8 = 0x02;
+
+/// The largest message chunk sent in a single APDU.
+const CHUNK_SIZE: usize = 250;
+
+/// The hardened BIP-44 purpose and the coin type registered for Casper.
+const PURPOSE: u32 = 44;
+const COIN_TYPE: u32 = 506;
+const HARDENED: u32 = 0x8000_0000;
+
+/// The length of a compressed secp256k1 public key.
+const PUBLIC_KEY_LENGTH: usize = 33;
+/// The length of the message hash which precedes the signature in a signing response.
+const HASH_LENGTH: usize = 32;
+/// The length of the `r` and `s` values of a signature.
+const SIGNATURE_LENGTH: usize = 64;
+
+/// Returns the public key at `m/44'/506'/0'/0/<account_index>`, after the user has confirmed its
+/// address on the device.
+pub(super) fn public_key(account_index: u32) -> Result<PublicKey, Error> {
+    get_public_key(&Device::open()?, account_index, P1_CONFIRM)
+}
+
+/// Has the user review `deploy` on the device and sign it with the key at
+/// `m/44'/506'/0'/0/<account_index>`, appending and returning the approval.
+pub(super) fn sign(deploy: &mut Deploy, account_index: u32) -> Result<Approval, Error> {
+    let message = deploy
+        .to_bytes()
+        .map_err(|error| make_error(format!("failed to serialize the deploy: {:?}", error)))?;
+
+    let device = Device::open()?;
+    let signer = get_public_key(&device, account_index, P1_NO_CONFIRM)?;
+    device.exchange(INS_SIGN, P1_INIT, &derivation_path(account_index))?;
+    let chunks = message.chunks(CHUNK_SIZE).collect::<Vec<_>>();
+    let mut response = vec![];
+    for (index, chunk) in chunks.iter().enumerate() {
+        let p1 = if index + 1 == chunks.len() {
+            P1_LAST
+        } else {
+            P1_ADD
+        };
+        response = device.exchange(INS_SIGN, p1, chunk)?;
+    }
+
+    let signature_bytes = response
+        .get(HASH_LENGTH..HASH_LENGTH + SIGNATURE_LENGTH)
+        .ok_or_else(|| make_error("the device returned no signature".to_string()))?;
+    let signature = Signature::secp256k1_from_bytes(signature_bytes)
+        .map_err(|error| make_error(format!("invalid signature: {}", error)))?;
+    deploy::add_signature(deploy, signer, signature).map_err(make_error)
+}
+
+fn get_public_key(device: &Device, account_index: u32, p1: u8) -> Result<PublicKey, Error> {
+    let response = device.exchange(INS_GET_ADDRESS, p1, &derivation_path(account_index))?;
+    if response.len() < PUBLIC_KEY_LENGTH {
+        return Err(make_error(format!(
+            "expected a {} byte public key, got {} bytes",
+            PUBLIC_KEY_LENGTH,
+            response.len()
+        )));
+    }
+    PublicKey::secp256k1_from_bytes(&response[..PUBLIC_KEY_LENGTH])
+        .map_err(|error| make_error(format!("invalid public key: {}", error)))
+}
+
+fn make_error(inner: String) -> Error {
+    Error::Ledger { inner }
+}
+
+/// Returns the serialized derivation path of the key at the given index.
+fn derivation_path(account_index: u32) -> Vec<u8> {
+    [
+        PURPOSE | HARDENED,
+        COIN_TYPE | HARDENED,
+        HARDENED,
+        0,
+        account_index,
+    ]
+    .iter()
+    .flat_map(|component| component.to_le_bytes().to_vec())
+    .collect()
+}
+
+#[cfg(feature = "ledger")]
+use transport::Device;
+
+#[cfg(feature = "ledger")]
+mod transport {
+    use hidapi::{HidApi, HidDevice};
+
+    use super::{make_error, Error};
+
+    /// The instruction class of the Casper app.
+    const CLA: u8 = 0x11;
+
+    /// The status word of a successful command.
+    const SW_OK: u16 = 0x9000;
+    /// The status word when the user rejects the request on the device.
+    const SW_REJECTED: u16 = 0x6986;
+    /// The status words when a different app, or none, is open on the device.
+    const SW_APP_NOT_OPEN: [u16; 2] = [0x6e00, 0x6e01];
+
+    const VENDOR_ID: u16 = 0x2c97;
+    const USAGE_PAGE: u16 = 0xffa0;
+
+    /// The HID framing of APDUs: each packet starts with the channel, tag and sequence index, and
+    /// the first also holds the length of the whole APDU.
+    const CHANNEL: u16 = 0x0101;
+    const TAG_APDU: u8 = 0x05;
+    const PACKET_SIZE: usize = 64;
+
+    /// How long to wait for the device to respond, including the time taken by the user to review
+    /// the request.
+    const READ_TIMEOUT_MS: i32 = 5 * 60 * 1000;
+
+    /// A Ledger device connected over USB HID.
+    pub(super) struct Device(HidDevice);
+
+    impl Device {
+        /// Opens the first connected Ledger device.
+        pub(super) fn open() -> Result<Self, Error> {
+            let api = HidApi::new().map_err(|error| make_error(error.to_string()))?;
+            let info = api
+                .device_list()
+                .find(|info| {
+                    info.vendor_id() == VENDOR_ID
+                        && (info.usage_page() == USAGE_PAGE || info.interface_number() == 0)
+                })
+                .ok_or_else(|| make_error("no Ledger device is connected".to_string()))?;
+            let device = info
+                .open_device(&api)
+                .map_err(|error| make_error(format!("failed to open the device: {}", error)))?;
+            Ok(Device(device))
+        }
+
+        /// Sends the command APDU for the given instruction and returns the response data.
+        pub(super) fn exchange(&self, ins: u8, p1: u8, data: &[u8]) -> Result<Vec<u8>, Error> {
+            self.write(&command(ins, p1, data))
+                .map_err(|error| make_error(format!("failed to write to the device: {}", error)))?;
+            let response = self.read().map_err(|error| {
+                make_error(format!("failed to read from the device: {}", error))
+            })?;
+            response_data(response)
+        }
+
+        fn write(&self, apdu: &[u8]) -> Result<(), String> {
+            let mut payload = (apdu.len() as u16).to_be_bytes().to_vec();
+            payload.extend_from_slice(apdu);
+            for (sequence, chunk) in payload.chunks(PACKET_SIZE - 5).enumerate() {
+                // The leading zero is the HID report ID.
+                let mut packet = vec![0];
+                packet.extend_from_slice(&CHANNEL.to_be_bytes());
+                packet.push(TAG_APDU);
+                packet.extend_from_slice(&(sequence as u16).to_be_bytes());
+                packet.extend_from_slice(chunk);
+                packet.resize(PACKET_SIZE + 1, 0);
+                self.0.write(&packet).map_err(|error| error.to_string())?;
+            }
+            Ok(())
+        }
+
+        fn read(&self) -> Result<Vec<u8>, String> {
+            let mut response = vec![];
+            let mut expected_length = None;
+            let mut sequence = 0u16;
+            while expected_length.is_none_or(|length| response.len() < length) {
+                let mut packet = [0; PACKET_SIZE];
+                let read = self
+                    .0
+                    .read_timeout(&mut packet, READ_TIMEOUT_MS)
+                    .map_err(|error| error.to_string())?;
+                if read < 5 {
+                    return Err("timed out waiting for the device".to_string());
+                }
+                if packet[..2] != CHANNEL.to_be_bytes()
+                    || packet[2] != TAG_APDU
+                    || packet[3..5] != sequence.to_be_bytes()
+                {
+                    return Err("unexpected packet".to_string());
+                }
+                let mut data = &packet[5..read];
+                if expected_length.is_none() {
+                    if data.len() < 2 {
+                        return Err("truncated packet".to_string());
+                    }
+                    expected_length = Some(usize::from(u16::from_be_bytes([data[0], data[1]])));
+                    data = &data[2..];
+                }
+                response.extend_from_slice(data);
+                sequence = sequence.wrapping_add(1);
+            }
+            response.truncate(expected_length.unwrap_or_default());
+            Ok(response)
+        }
+    }
+
+    /// Returns the command APDU for the given instruction.
+    fn command(ins: u8, p1: u8, data: &[u8]) -> Vec<u8> {
+        let mut apdu = vec![CLA, ins, p1, 0, data.len() as u8];
+        apdu.extend_from_slice(data);
+        apdu
+    }
+
+    /// Returns the data of a response APDU, or an error unless its status word is success.
+    fn response_data(mut response: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if response.len() < 2 {
+            return Err(make_error(
+                "the device returned a truncated response".to_string(),
+            ));
+        }
+        let status_bytes = response.split_off(response.len() - 2);
+        match u16::from_be_bytes([status_bytes[0], status_bytes[1]]) {
+            SW_OK => Ok(response),
+            SW_REJECTED => Err(make_error(
+                "the request was rejected on the device".to_string(),
+            )),
+            status if SW_APP_NOT_OPEN.contains(&status) => Err(make_error(
+                "the Casper app isn't open on the device".to_string(),
+            )),
+            status => Err(make_error(format!(
+                "the device returned status {:#06x}",
+                status
+            ))),
+        }
+    }
+}
+
+#[cfg(not(feature = "ledger"))]
+struct Device;
+
+#[cfg(not(feature = "ledger"))]
+impl Device {
+    fn open() -> Result<Self, Error> {
+        Err(make_error(
+            "this build doesn't support Ledger devices; rebuild with `--features ledger`"
+                .to_string(),
+        ))
+    }
+
+    fn exchange(&self, _ins: u8, _p1: u8, _data: &[u8]) -> Result<Vec<u8>, Error> {
+        unreachable!("a device can't be opened without the ledger feature")
+    }
+}
@@ -1,29 +1,324 @@
+mod account_state;
+mod address_book;
+mod approval_progress;
+mod autosave;
+mod aws_kms;
+mod balance;
+mod build_cache;
+mod bulk_keys;
+mod cargo_casper;
+mod ci_workflow;
+mod codegen_target;
+mod compile_backend;
+mod compile_output;
+mod compile_progress;
+mod contract_mode;
+mod contract_name;
+mod cost_estimate;
+mod dependencies;
+mod deploy;
+mod deploy_inspection;
+mod deploy_limits;
+mod deploy_status;
+mod dry_run;
+mod event_stream;
+mod existing_project;
+mod explorer;
+mod faucet;
+mod file_manager;
+mod git_repo;
+mod i18n;
+mod identicon;
+mod initial_transfer;
+mod key_configuration;
+mod keystore;
+mod ledger;
+mod local_network;
+mod named_keys;
+mod network;
+mod node_status;
+mod offline_bundle;
+mod pasted_key;
+mod plan;
+mod prebuilt_session;
+mod preferences;
+mod prerequisites;
+mod price_feed;
+mod protocol_profile;
+mod provenance;
+mod qr_code;
+mod qr_scan;
+mod recent;
+mod remote_signer;
+mod reproducible_build;
+mod rpc;
+mod rust_syntax;
+mod scheme_template;
 mod smart_contract;
+mod test_project;
+mod validation;
+mod vanity;
+mod verification;
+mod wallet_export;
+mod yubikey;
 
 use std::{
+    collections::HashMap,
     fmt::{self, Display, Formatter},
-    fs,
-    path::PathBuf,
+    fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
     sync::{mpsc::Receiver, Mutex},
+    time::Duration,
 };
 
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use thiserror::Error;
 
-use casper_node::crypto::AsymmetricKeyExt;
-use casper_types::{account::AccountHash, crypto::AsymmetricType, PublicKey};
+use casper_node::{
+    crypto::AsymmetricKeyExt,
+    types::{Approval, Deploy},
+};
+use casper_types::{
+    account::AccountHash, crypto::AsymmetricType, PublicKey, RuntimeArgs, Signature, U512,
+};
 
+use initial_transfer::InitialTransfer;
+use named_keys::NamedKey;
+use pasted_key::PastedKey;
 use smart_contract::SmartContract;
 
+pub use account_state::{AccountState, BlockIdentifier};
+pub use address_book::AddressBookEntry;
+pub use approval_progress::ApprovalProgress;
+pub use autosave::AutosavedTab;
+pub use aws_kms::AwsKmsSigner;
+pub use build_cache::TargetDirLocation;
+pub use bulk_keys::BulkKey;
+pub use ci_workflow::CiProvider;
+pub use codegen_target::CodegenTarget;
+pub use compile_backend::CompileBackend;
+pub use compile_output::{DiagnosticLevel, OutputStream};
+pub use compile_progress::CompilePhase;
+pub use contract_mode::ContractMode;
+pub use cost_estimate::{CostEstimate, EstimateMethod};
+pub use dependencies::DependencySource;
+pub use deploy::{ApprovalCheck, DeployParams};
+pub use deploy_inspection::DeployInspection;
+pub use dry_run::DryRun;
+pub use i18n::Language;
+pub use identicon::Identicon;
+pub use key_configuration::KeyConfiguration;
+pub use network::NetworkProfile;
+pub use node_status::NodeStatus;
+pub use preferences::{Preferences, Theme};
+pub use prerequisites::Prerequisite;
+pub use price_feed::CsprPrice;
+pub use protocol_profile::ProtocolProfile;
+pub use provenance::Provenance;
+pub use qr_code::QrCode;
+pub use qr_scan::QrScan;
+pub use recent::RecentItem;
+pub use remote_signer::RemoteSigner;
+pub use rpc::ConnectionSettings;
+pub use rust_syntax::RustToken;
+pub use scheme_template::SchemeTemplate;
+pub use validation::{Severity, ValidationIssue};
+pub use vanity::KeyAlgorithm;
+pub use verification::Verification;
+pub use yubikey::YubiKeySigner;
+
+/// An event emitted while generating and compiling the smart contract.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CompileEvent {
+    /// A line of the tool's own output to be shown to the user, e.g. the command about to be run.
+    Output(String),
+    /// A line of output from a command run to compile the contract, e.g. cargo, to be shown to the
+    /// user.
+    CommandOutput { line: String, stream: OutputStream },
+    /// Compilation entered the given phase, or progressed within it.
+    Progress(CompilePhase),
+    /// A problem with the compiled contract which doesn't stop compilation succeeding, but which
+    /// should be brought to the user's attention, e.g. that its deploy would be rejected.
+    Warning(String),
+    /// Compilation succeeded and the session Wasm is at `wasm_path`.  Always the last event.
+    Succeeded { wasm_path: PathBuf },
+    /// The project was written to `project_dir` without being compiled, as source-only mode is
+    /// enabled.  Always the last event.
+    SourceGenerated { project_dir: PathBuf },
+    /// Compilation failed for the given reason.  Always the last event.
+    Failed { reason: String },
+    /// Compilation was still running when the compile timeout expired, so cargo was killed.
+    /// Always the last event.
+    TimedOut { timeout: Duration },
+    /// Compilation was cancelled by [`cancel_compilation`], so cargo was killed.  Always the last
+    /// event.
+    Cancelled,
+}
+
+/// An event emitted while running the generated project's tests.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TestEvent {
+    /// A line of output from `cargo test`.
+    Output(String),
+    /// The tests have finished; `passed` is `true` if they all succeeded.
+    Finished { passed: bool },
+}
+
+/// An event emitted while sending the initial transfer which funds the main account.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TransferEvent {
+    /// A line of output from `casper-client transfer`.
+    Output(String),
+    /// The client has finished; `succeeded` is `true` if it sent the transfer.
+    Finished { succeeded: bool },
+}
+
+/// An event emitted while sending a deploy and waiting for it to be executed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DeployEvent {
+    /// A line of output to be shown to the user.
+    Output(String),
+    /// The deploy executed successfully in the given block, costing `cost` motes.  Always the last
+    /// event.
+    Succeeded { block_hash: String, cost: String },
+    /// The deploy was executed in the given block but failed with `error_message`, costing `cost`
+    /// motes.  `cause` explains the error if it's one the session code is known to raise, e.g. a
+    /// revert with `DuplicateKey` or `ThresholdViolation`.  Always the last event.
+    Failed {
+        block_hash: String,
+        cost: String,
+        error_message: String,
+        cause: Option<String>,
+    },
+    /// The node didn't accept the deploy, or it expired without being executed.  Always the last
+    /// event.
+    NotExecuted { reason: String },
+}
+
+/// An event emitted while rehearsing the setup deploy on a local NCTL network.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum LocalNetworkEvent {
+    /// A line of output to be shown to the user.
+    Output(String),
+    /// The rehearsal has finished; `passed` is `true` if the setup deploy succeeded and left the
+    /// account with the configured associated keys and thresholds.
+    Finished { passed: bool },
+}
+
+/// An event emitted while searching for a key pair with a vanity account hash.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum VanityEvent {
+    /// The number of keys generated so far, and the average rate, sent about once a second.
+    Progress { attempts: u64, keys_per_second: u64 },
+    /// A matching key pair was found and saved in `dir`.  Always the last event.
+    Found {
+        account_hash: String,
+        public_key: String,
+        dir: PathBuf,
+    },
+    /// The search failed or hit its time limit.  Always the last event.
+    Stopped { reason: String },
+}
+
+/// Identifies one of several independent configurations being worked on at once, e.g. in separate
+/// tabs.  Each session has its own keys, thresholds and build options; the network profiles are
+/// shared by all of them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SessionId(u64);
+
+/// The sessions other than the current one, which is held in `SMART_CONTRACT`.
+struct Sessions {
+    current: SessionId,
+    inactive: HashMap<SessionId, SmartContract>,
+    next: u64,
+}
+
 static SMART_CONTRACT: Lazy<Mutex<SmartContract>> =
     Lazy::new(|| Mutex::new(SmartContract::default()));
 
+static PREFERENCES: Lazy<Mutex<Preferences>> = Lazy::new(|| Mutex::new(Preferences::default()));
+
+/// The language the UI is translated into, fixed by the first translation so that the windows
+/// already shown don't mix languages once the preference is changed.
+static LANGUAGE: OnceCell<Language> = OnceCell::new();
+
+static SESSIONS: Lazy<Mutex<Sessions>> = Lazy::new(|| {
+    Mutex::new(Sessions {
+        current: SessionId(0),
+        inactive: HashMap::new(),
+        next: 1,
+    })
+});
+
 #[derive(Error, Debug)]
 pub enum Error {
     ParsePublicKeyFile { file: String, inner: Option<String> },
     ParseHexPublicKey { inner: String },
     ParseAccountHash { inner: String },
+    ParsePastedKey { inner: String },
+    ParseBulkKey { line: usize, inner: String },
+    UnsuitableSchemeTemplate { template: String, inner: String },
     NoKeys,
+    ReadWasmFile { file: String, inner: String },
+    BuildRuntimeArgs { inner: String },
+    ParseDependencySource { value: String, inner: String },
+    InvalidDependency { crate_name: String, inner: String },
+    IncompatibleDependencies { inner: String },
+    InvalidToolchain { toolchain: String, inner: String },
+    GitRepo { inner: String },
+    InvalidDockerImage { image: String },
+    InvalidContractName { name: String, inner: String },
+    InvalidNamedKey { name: String, inner: String },
+    InvalidInitialTransfer { inner: String },
+    UnreachableConfiguration { inner: String },
+    InvalidContractMode { inner: String },
+    InvalidKeyLabel { account_hash: String, inner: String },
+    InvalidProvenance { file: String, inner: String },
+    PinDependencies { inner: String },
+    CleanBuildArtifacts { path: String, inner: String },
+    UnsupportedProtocolVersion { version: String, inner: String },
+    DetectProtocolVersion { node_address: String, inner: String },
+    ScaffoldProject { inner: String },
+    SaveSource { file: String, inner: String },
+    BuildDeploy { inner: String },
+    SignDeploy { file: String, inner: String },
+    SaveDeploy { file: String, inner: String },
+    LoadDeploy { file: String, inner: String },
+    ImportApprovals { file: String, inner: String },
+    InvalidApproval { signer: String, inner: String },
+    Ledger { inner: String },
+    RemoteSigner { signer: String, inner: String },
+    DryRun { node_address: String, inner: String },
+    LocalNetwork { inner: String },
+    Faucet { inner: String },
+    VerifyAccount { node_address: String, inner: String },
+    FetchAccount { node_address: String, inner: String },
+    ParseBlockIdentifier { value: String },
+    NodeStatus { node_address: String, inner: String },
+    ExportBundle { dir: String, inner: String },
+    ImportBundle { dir: String, inner: String },
+    OpenExplorer { url: String, inner: String },
+    OpenFolder { dir: String, inner: String },
+    InvalidNetworkProfile { name: String, inner: String },
+    NetworkConfig { file: String, inner: String },
+    PriceFeed { url: String, inner: String },
+    UnlockKeystore { file: String, inner: String },
+    ImportWalletExport { file: String, inner: String },
+    VanityKey { inner: String },
+    KeyConfiguration { file: String, inner: String },
+    OpenProject { dir: String, inner: String },
+    FixPrerequisite { prerequisite: String, inner: String },
+    AddressBook { file: String, inner: String },
+    Autosave { file: String, inner: String },
+    RecentItems { file: String, inner: String },
+    Preferences { file: String, inner: String },
+    InvalidPreferences { inner: String },
+    InvalidAddressBookEntry { label: String, inner: String },
+    EncodeQrCode { inner: String },
+    ScanQrCode { inner: String },
+    UnknownSession { session: SessionId },
+    CloseCurrentSession,
 }
 
 impl Display for Error {
@@ -50,11 +345,325 @@ impl Display for Error {
                     inner
                 )
             }
+            Error::ParsePastedKey { inner } => {
+                write!(formatter, "failed to recognise the pasted key: {}", inner)
+            }
+            Error::ParseBulkKey { line, inner } => write!(formatter, "line {}: {}", line, inner),
+            Error::UnsuitableSchemeTemplate { template, inner } => {
+                write!(
+                    formatter,
+                    "can't apply the '{}' template: {}",
+                    template, inner
+                )
+            }
             Error::NoKeys => write!(formatter, "at least one key must be provided"),
+            Error::ReadWasmFile { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to read {} as a Wasm module: {}",
+                    file, inner
+                )
+            }
+            Error::BuildRuntimeArgs { inner } => {
+                write!(
+                    formatter,
+                    "failed to build the session runtime args: {}",
+                    inner
+                )
+            }
+            Error::ParseDependencySource { value, inner } => {
+                write!(
+                    formatter,
+                    "failed to parse '{}' as a dependency source: {}",
+                    value, inner
+                )
+            }
+            Error::InvalidDependency { crate_name, inner } => {
+                write!(formatter, "invalid {} dependency: {}", crate_name, inner)
+            }
+            Error::IncompatibleDependencies { inner } => {
+                write!(formatter, "incompatible dependencies: {}", inner)
+            }
+            Error::InvalidToolchain { toolchain, inner } => {
+                write!(formatter, "invalid toolchain '{}': {}", toolchain, inner)
+            }
+            Error::GitRepo { inner } => {
+                write!(formatter, "failed to create git repository: {}", inner)
+            }
+            Error::InvalidDockerImage { image } => {
+                write!(formatter, "invalid docker image name '{}'", image)
+            }
+            Error::InvalidContractName { name, inner } => {
+                write!(formatter, "invalid contract name '{}': {}", name, inner)
+            }
+            Error::InvalidNamedKey { name, inner } => {
+                write!(formatter, "invalid named key '{}': {}", name, inner)
+            }
+            Error::InvalidInitialTransfer { inner } => {
+                write!(formatter, "invalid initial transfer: {}", inner)
+            }
+            Error::UnreachableConfiguration { inner } => {
+                write!(formatter, "the configuration can't be reached: {}", inner)
+            }
+            Error::InvalidContractMode { inner } => {
+                write!(formatter, "invalid contract mode: {}", inner)
+            }
+            Error::InvalidKeyLabel {
+                account_hash,
+                inner,
+            } => {
+                write!(formatter, "invalid label for '{}': {}", account_hash, inner)
+            }
+            Error::InvalidProvenance { file, inner } => {
+                write!(
+                    formatter,
+                    "invalid provenance header in {}: {}",
+                    file, inner
+                )
+            }
+            Error::PinDependencies { inner } => {
+                write!(
+                    formatter,
+                    "failed to pin the dependencies in Cargo.lock: {}",
+                    inner
+                )
+            }
+            Error::CleanBuildArtifacts { path, inner } => {
+                write!(formatter, "failed to remove {}: {}", path, inner)
+            }
+            Error::UnsupportedProtocolVersion { version, inner } => {
+                write!(
+                    formatter,
+                    "unsupported protocol version '{}': {}",
+                    version, inner
+                )
+            }
+            Error::DetectProtocolVersion {
+                node_address,
+                inner,
+            } => {
+                write!(
+                    formatter,
+                    "failed to get the protocol version from {}: {}",
+                    node_address, inner
+                )
+            }
+            Error::ScaffoldProject { inner } => {
+                write!(
+                    formatter,
+                    "failed to scaffold the project with cargo-casper: {}",
+                    inner
+                )
+            }
+            Error::SaveSource { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to save the source to {}: {}",
+                    file, inner
+                )
+            }
+            Error::BuildDeploy { inner } => {
+                write!(formatter, "failed to build the deploy: {}", inner)
+            }
+            Error::SignDeploy { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to sign the deploy with {}: {}",
+                    file, inner
+                )
+            }
+            Error::SaveDeploy { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to save the deploy to {}: {}",
+                    file, inner
+                )
+            }
+            Error::LoadDeploy { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to load a deploy from {}: {}",
+                    file, inner
+                )
+            }
+            Error::ImportApprovals { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to import approvals from {}: {}",
+                    file, inner
+                )
+            }
+            Error::InvalidApproval { signer, inner } => {
+                write!(formatter, "the approval by {} is {}", signer, inner)
+            }
+            Error::Ledger { inner } => write!(formatter, "Ledger device error: {}", inner),
+            Error::RemoteSigner { signer, inner } => {
+                write!(formatter, "failed to sign with {}: {}", signer, inner)
+            }
+            Error::InvalidNetworkProfile { name, inner } => {
+                write!(formatter, "invalid network '{}': {}", name, inner)
+            }
+            Error::NetworkConfig { file, inner } => write!(
+                formatter,
+                "failed to read or write the networks config file {}: {}",
+                file, inner
+            ),
+            Error::UnlockKeystore { file, inner } => {
+                write!(formatter, "failed to unlock keystore {}: {}", file, inner)
+            }
+            Error::AddressBook { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to read or write address book {}: {}",
+                    file, inner
+                )
+            }
+            Error::Autosave { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to read or write autosave {}: {}",
+                    file, inner
+                )
+            }
+            Error::RecentItems { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to read or write recent items {}: {}",
+                    file, inner
+                )
+            }
+            Error::Preferences { file, inner } => {
+                write!(
+                    formatter,
+                    "failed to read or write preferences {}: {}",
+                    file, inner
+                )
+            }
+            Error::InvalidPreferences { inner } => {
+                write!(formatter, "invalid preferences: {}", inner)
+            }
+            Error::InvalidAddressBookEntry { label, inner } => write!(
+                formatter,
+                "invalid address book entry '{}': {}",
+                label, inner
+            ),
+            Error::EncodeQrCode { inner } => {
+                write!(formatter, "failed to encode the QR code: {}", inner)
+            }
+            Error::ScanQrCode { inner } => {
+                write!(formatter, "failed to scan a QR code: {}", inner)
+            }
+            Error::UnknownSession { session } => write!(formatter, "no such session {:?}", session),
+            Error::CloseCurrentSession => {
+                write!(formatter, "the current session can't be closed")
+            }
+            Error::KeyConfiguration { file, inner } => write!(
+                formatter,
+                "failed to read or write the configuration file {}: {}",
+                file, inner
+            ),
+            Error::OpenProject { dir, inner } => {
+                write!(formatter, "failed to open the project {}: {}", dir, inner)
+            }
+            Error::FixPrerequisite {
+                prerequisite,
+                inner,
+            } => write!(formatter, "failed to install {}: {}", prerequisite, inner),
+            Error::VanityKey { inner } => {
+                write!(formatter, "failed to generate a vanity key: {}", inner)
+            }
+            Error::ImportWalletExport { file, inner } => write!(
+                formatter,
+                "failed to import Casper Wallet export {}: {}",
+                file, inner
+            ),
+            Error::PriceFeed { url, inner } => write!(
+                formatter,
+                "failed to fetch the CSPR price from {}: {}",
+                url, inner
+            ),
+            Error::VerifyAccount {
+                node_address,
+                inner,
+            } => write!(
+                formatter,
+                "failed to verify the account via {}: {}",
+                node_address, inner
+            ),
+            Error::FetchAccount {
+                node_address,
+                inner,
+            } => write!(
+                formatter,
+                "failed to fetch the account via {}: {}",
+                node_address, inner
+            ),
+            Error::NodeStatus {
+                node_address,
+                inner,
+            } => write!(
+                formatter,
+                "failed to get the status of {}: {}",
+                node_address, inner
+            ),
+            Error::ParseBlockIdentifier { value } => write!(
+                formatter,
+                "'{}' is neither a block height nor a hex-encoded block hash",
+                value
+            ),
+            Error::ExportBundle { dir, inner } => write!(
+                formatter,
+                "failed to export the offline signing bundle to {}: {}",
+                dir, inner
+            ),
+            Error::ImportBundle { dir, inner } => write!(
+                formatter,
+                "failed to import the offline signing bundle {}: {}",
+                dir, inner
+            ),
+            Error::OpenExplorer { url, inner } => {
+                write!(
+                    formatter,
+                    "failed to open {} in the browser: {}",
+                    url, inner
+                )
+            }
+            Error::OpenFolder { dir, inner } => {
+                write!(
+                    formatter,
+                    "failed to open {} in the file manager: {}",
+                    dir, inner
+                )
+            }
+            Error::Faucet { inner } => write!(formatter, "failed to open the faucet: {}", inner),
+            Error::LocalNetwork { inner } => {
+                write!(formatter, "failed to test on the local network: {}", inner)
+            }
+            Error::DryRun {
+                node_address,
+                inner,
+            } => write!(
+                formatter,
+                "failed to execute the deploy speculatively on {}: {}",
+                node_address, inner
+            ),
         }
     }
 }
 
+/// Writes `contents` to the file at `path` unless it already holds exactly those contents.
+///
+/// Leaving unchanged files untouched preserves their modification times, so cargo can reuse the
+/// previous build of a regenerated project rather than rebuilding it from scratch.
+fn write_if_changed<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let path = path.as_ref();
+    let contents = contents.as_ref();
+    if fs::read(path).ok().as_deref() == Some(contents) {
+        return Ok(());
+    }
+    fs::write(path, contents)
+}
+
 fn make_parse_file_error<T: ToString>(path: &str, error: T) -> Error {
     Error::ParsePublicKeyFile {
         file: path.to_string(),
@@ -64,8 +673,15 @@ fn make_parse_file_error<T: ToString>(path: &str, error: T) -> Error {
 
 /// Returns the hex-encoded account hash derived from the public key contained in the provided file.
 ///
-/// The file must be a hex-encoded or PEM-encoded public key as is produced by the casper-client.
+/// The file must be a hex-encoded or PEM-encoded public key as is produced by the casper-client,
+/// or an encrypted keystore which records its public key or has been unlocked.
 pub fn get_account_hash_from_file(path: &str) -> Result<String, Error> {
+    if keystore::is_keystore(Path::new(path)) {
+        return keystore::public_key(Path::new(path))
+            .map(|public_key| public_key.to_account_hash().to_formatted_string())
+            .map_err(|error| make_parse_file_error(path, error));
+    }
+
     match PublicKey::from_file(path) {
         Ok(public_key) => return Ok(public_key.to_account_hash().to_formatted_string()),
         Err(error) => {
@@ -92,6 +708,128 @@ pub fn get_account_hash_from_file(path: &str) -> Result<String, Error> {
     })
 }
 
+/// Returns `true` if the file at `path` is an encrypted JSON keystore rather than a PEM file.
+///
+/// A keystore must be unlocked with [`unlock_keystore`] before it can sign deploys.
+pub fn is_encrypted_keystore(path: &str) -> bool {
+    keystore::is_keystore(Path::new(path))
+}
+
+/// Returns `true` if the keystore at `path` has been unlocked this session.
+pub fn keystore_unlocked(path: &str) -> bool {
+    keystore::is_unlocked(Path::new(path))
+}
+
+/// Decrypts the keystore at `path` with `passphrase` and returns its public key.
+///
+/// The keystore must be a Web3 Secret Storage v3 document using scrypt and AES-128-CTR, with
+/// either a `public_key` field holding the hex-encoded public key or a `key_algorithm` field of
+/// `ed25519` or `secp256k1`.  Its secret key is then held in memory for the rest of the session, so
+/// `path` can be passed wherever a secret key file is taken, except as the initial transfer's
+/// source, which casper-client reads.
+pub fn unlock_keystore(path: &str, passphrase: &str) -> Result<PublicKey, Error> {
+    keystore::unlock(Path::new(path), passphrase).map_err(|inner| Error::UnlockKeystore {
+        file: path.to_string(),
+        inner,
+    })
+}
+
+/// Returns `true` if the file at `path` is a Casper Wallet export: an account list export, or a
+/// secret key downloaded from the wallet.
+pub fn is_wallet_export(path: &str) -> bool {
+    !keystore::is_keystore(Path::new(path)) && wallet_export::is_wallet_export(Path::new(path))
+}
+
+/// Reads the accounts in the Casper Wallet export at `path`, returning each as a `(formatted
+/// account hash, hex-encoded public key, label)` triple.
+///
+/// The accounts' names in the wallet are set as their key labels, replacing any existing labels
+/// of the same keys.  A downloaded secret key is named after its account, e.g.
+/// `Account 1_secret_key.pem`, and is only used to derive the public key.
+pub fn import_wallet_export(path: &str) -> Result<Vec<(String, String, Option<String>)>, Error> {
+    let accounts =
+        wallet_export::read(Path::new(path)).map_err(|inner| Error::ImportWalletExport {
+            file: path.to_string(),
+            inner,
+        })?;
+
+    let mut smart_contract = SMART_CONTRACT.lock().unwrap();
+    let mut imported = vec![];
+    for account in accounts {
+        let account_hash = account.public_key.to_account_hash();
+        if let Some(label) = &account.label {
+            smart_contract
+                .key_labels
+                .retain(|(labelled, _)| *labelled != account_hash);
+            smart_contract
+                .key_labels
+                .push((account_hash, label.clone()));
+        }
+        imported.push((
+            account_hash.to_formatted_string(),
+            account.public_key.to_hex(),
+            account.label,
+        ));
+    }
+    Ok(imported)
+}
+
+/// Returns the longest hex prefix [`generate_vanity_key`] can search for.
+pub fn max_vanity_prefix_length() -> usize {
+    vanity::MAX_PREFIX_LENGTH
+}
+
+/// Returns the most worker threads [`generate_vanity_key`] can use: all but one of the available
+/// cores.
+pub fn max_vanity_threads() -> usize {
+    vanity::max_threads()
+}
+
+/// Returns the longest [`generate_vanity_key`] can search for.
+pub fn max_vanity_duration() -> Duration {
+    vanity::MAX_DURATION
+}
+
+/// Returns the number of key pairs which must be generated on average to find an account hash
+/// starting with `prefix`, which is hex, optionally preceded by `account-hash-`.
+pub fn vanity_expected_attempts(prefix: &str) -> Result<u64, Error> {
+    let prefix = vanity::parse_prefix(prefix).map_err(|inner| Error::VanityKey { inner })?;
+    Ok(vanity::expected_attempts(&prefix))
+}
+
+/// Generates key pairs of the given algorithm until one's account hash starts with `prefix`, then
+/// saves it in `output_dir` as `secret_key.pem`, `public_key.pem` and `public_key_hex`, as
+/// `casper-client keygen` does.
+///
+/// `prefix` is hex, optionally preceded by `account-hash-`, and at most
+/// [`max_vanity_prefix_length`] digits long.  The search runs on `threads` worker threads, capped
+/// at [`max_vanity_threads`], and gives up after `time_limit`, capped at [`max_vanity_duration`].
+///
+/// The progress is streamed through the returned channel, with [`VanityEvent::Found`] or
+/// [`VanityEvent::Stopped`] sent last.  Dropping the receiver stops the search.  Returns an error
+/// if the prefix is invalid, or if `output_dir` already holds any of the key files.
+pub fn generate_vanity_key<P: AsRef<Path>>(
+    prefix: &str,
+    algorithm: KeyAlgorithm,
+    threads: usize,
+    time_limit: Duration,
+    output_dir: P,
+) -> Result<Receiver<VanityEvent>, Error> {
+    let make_error = |inner: String| Error::VanityKey { inner };
+    let prefix = vanity::parse_prefix(prefix).map_err(make_error)?;
+    let output_dir = output_dir.as_ref().to_path_buf();
+    if let Some(existing) = vanity::existing_key_file(&output_dir) {
+        return Err(make_error(format!("{} already exists", existing.display())));
+    }
+    Ok(vanity::search(
+        prefix,
+        algorithm,
+        threads.clamp(1, vanity::max_threads()),
+        time_limit.min(vanity::MAX_DURATION),
+        output_dir,
+    ))
+}
+
 /// Returns the hex-encoded account hash derived from the provided hex-encoded public key.
 ///
 /// The input must be a hex-encoded public key, prefixed with a hex-encoded tag indicating the
@@ -118,6 +856,129 @@ pub fn validate_account_hash(formatted_account_hash: &str) -> Result<(), Error>
     }
 }
 
+/// Returns the formatted account hash of a key pasted as text, along with its hex-encoded public key
+/// if it was pasted as one.
+///
+/// The text can be a hex-encoded public key, a formatted account hash or an account hash as bare
+/// hex.  Mixed-case hex must match the casper-client's checksummed form.
+pub fn parse_pasted_key(text: &str) -> Result<(String, Option<String>), Error> {
+    let pasted_key = PastedKey::parse(text).map_err(|inner| Error::ParsePastedKey { inner })?;
+    let public_key = match &pasted_key {
+        PastedKey::PublicKey(public_key) => Some(public_key.to_hex()),
+        PastedKey::AccountHash(_) => None,
+    };
+    Ok((pasted_key.account_hash().to_formatted_string(), public_key))
+}
+
+/// Parses keys entered one per line, each either on its own or as `label,key` or
+/// `label,key,weight`, where the key is in any form accepted by `parse_pasted_key`.
+///
+/// Blank lines and lines starting with `#` are skipped.  The result of each other line is returned
+/// in order.
+pub fn parse_bulk_keys(text: &str) -> Vec<Result<BulkKey, Error>> {
+    bulk_keys::parse(text)
+        .into_iter()
+        .map(|result| result.map_err(|(line, inner)| Error::ParseBulkKey { line, inner }))
+        .collect()
+}
+
+/// Returns the policy `template` would give the keys of `configuration`, or an error if it needs a
+/// different number of keys.
+pub fn describe_scheme_template(
+    template: SchemeTemplate,
+    configuration: &KeyConfiguration,
+) -> Result<String, Error> {
+    scheme_template::describe(template, configuration).map_err(|inner| {
+        Error::UnsuitableSchemeTemplate {
+            template: template.to_string(),
+            inner,
+        }
+    })
+}
+
+/// Returns `configuration` with its weights and action thresholds set by `template`, or an error if
+/// it needs a different number of keys.
+pub fn apply_scheme_template(
+    template: SchemeTemplate,
+    configuration: &KeyConfiguration,
+) -> Result<KeyConfiguration, Error> {
+    scheme_template::apply(template, configuration).map_err(|inner| {
+        Error::UnsuitableSchemeTemplate {
+            template: template.to_string(),
+            inner,
+        }
+    })
+}
+
+/// Returns the known signers' keys saved in the address book, sorted by label.
+pub fn address_book() -> Result<Vec<AddressBookEntry>, Error> {
+    let file = address_book_file()?;
+    address_book::load(&file).map_err(|inner| Error::AddressBook {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+/// Saves `key`, a hex-encoded public key or formatted account hash, to the address book under
+/// `label`, replacing any entry for the same account hash.
+pub fn save_to_address_book(label: &str, key: &str) -> Result<AddressBookEntry, Error> {
+    let entry =
+        AddressBookEntry::new(label, key).map_err(|inner| Error::InvalidAddressBookEntry {
+            label: label.to_string(),
+            inner,
+        })?;
+    let mut entries = address_book()?;
+    address_book::insert(&mut entries, entry.clone());
+    write_address_book(&entries)?;
+    Ok(entry)
+}
+
+/// Removes the entry for the given formatted account hash from the address book, if there is one.
+pub fn remove_from_address_book(formatted_account_hash: &str) -> Result<(), Error> {
+    let mut entries = address_book()?;
+    entries.retain(|entry| entry.account_hash != formatted_account_hash.trim());
+    write_address_book(&entries)
+}
+
+fn address_book_file() -> Result<PathBuf, Error> {
+    address_book::file().ok_or_else(|| Error::AddressBook {
+        file: "address_book.json".to_string(),
+        inner: "the user's config dir couldn't be found".to_string(),
+    })
+}
+
+fn write_address_book(entries: &[AddressBookEntry]) -> Result<(), Error> {
+    let file = address_book_file()?;
+    address_book::save(&file, entries).map_err(|inner| Error::AddressBook {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+/// Returns the identicon of the provided formatted account hash: a small symmetric pattern which
+/// lets humans tell keys apart, or spot a wrongly pasted hash, at a glance.
+pub fn account_hash_identicon(formatted_account_hash: &str) -> Result<Identicon, Error> {
+    let account_hash =
+        AccountHash::from_formatted_str(formatted_account_hash.trim()).map_err(|error| {
+            Error::ParseAccountHash {
+                inner: error.to_string(),
+            }
+        })?;
+    Ok(Identicon::new(&account_hash))
+}
+
+/// Returns the QR code of `text`, e.g. a formatted account hash, public key or deploy hash, so it
+/// can be scanned by a phone or hardware wallet rather than retyped.
+pub fn qr_code(text: &str) -> Result<QrCode, Error> {
+    QrCode::new(text.trim()).map_err(|inner| Error::EncodeQrCode { inner })
+}
+
+/// Starts scanning the webcam for a QR code, e.g. of a public key shown on a signer's phone or
+/// wallet, using zbarcam.  The scanned text can be parsed as with `parse_pasted_key`.
+pub fn scan_qr_code() -> Result<QrScan, Error> {
+    QrScan::start().map_err(|inner| Error::ScanQrCode { inner })
+}
+
 /// Sets the values which will be written to the smart contract.
 ///
 /// Can be called multiple times before actually generating the contract.
@@ -153,16 +1014,1730 @@ pub fn set_project_path(root_dir: &str) {
     SMART_CONTRACT.lock().unwrap().root_dir = PathBuf::from(root_dir);
 }
 
-/// Sets the smart contract's name.
-pub fn set_contract_name(name: &str) {
-    SMART_CONTRACT.lock().unwrap().contract_name = name.to_string();
+/// Sets the smart contract's name, returning the name actually used.
+///
+/// The name is used as the Cargo package name and project folder, so characters which Cargo
+/// doesn't allow are replaced with `_`.  Returns an error if the name can't be made valid.
+pub fn set_contract_name(name: &str) -> Result<String, Error> {
+    let sanitized = contract_name::sanitize(name)?;
+    SMART_CONTRACT.lock().unwrap().contract_name = sanitized.clone();
+    Ok(sanitized)
 }
 
-pub fn main_rs_contents() -> String {
-    SMART_CONTRACT.lock().unwrap().main_rs_contents()
+/// Returns what the generated contract does to the account.
+pub fn contract_mode() -> ContractMode {
+    SMART_CONTRACT.lock().unwrap().contract_mode.clone()
 }
 
-/// Generates the Rust source for the contract and compiles it to Wasm.
-pub fn generate_smart_contract() -> Result<Receiver<String>, Error> {
-    SMART_CONTRACT.lock().unwrap().create_and_compile()
+/// Sets what the generated contract does to the account.
+///
+/// In any mode other than [`ContractMode::Setup`], the first associated key set via
+/// [`set_associated_keys_and_thresholds`] identifies the account; the other keys, weights and
+/// thresholds are ignored.  No test crate is generated, and neither named keys nor a prebuilt
+/// session Wasm can be used.
+pub fn set_contract_mode(contract_mode: ContractMode) -> Result<(), Error> {
+    contract_mode.validate()?;
+    SMART_CONTRACT.lock().unwrap().contract_mode = contract_mode;
+    Ok(())
+}
+
+/// Returns whether the generated setup contract is idempotent.
+pub fn idempotent_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().idempotent
+}
+
+/// Sets whether the generated setup contract is idempotent.
+///
+/// By default every host call in the session code reverts on failure, so sending the setup deploy
+/// to a partially configured account fails, e.g. with a duplicate key error.  When enabled, keys
+/// already associated are updated rather than added and keys already removed are skipped, so a
+/// retried deploy converges on the configuration instead.  Only affects
+/// [`ContractMode::Setup`].
+pub fn set_idempotent_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().idempotent = enabled;
+}
+
+/// Returns whether the setup contract is the generic one configured via runtime args.
+pub fn runtime_args_template_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().runtime_args_template
+}
+
+/// Sets whether the setup contract is the generic one which reads the keys, weights and
+/// thresholds from its runtime args, rather than having them baked in as constants.
+///
+/// The generated project then compiles [`prebuilt_session_source`], and lists the matching
+/// runtime args in `session_args.txt`.  A single audited build can be reused for any account by
+/// passing the args from [`build_session_runtime_args`] or [`build_casper_client_session_args`].
+/// Only affects [`ContractMode::Setup`].
+pub fn set_runtime_args_template_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().runtime_args_template = enabled;
+}
+
+/// Returns the labels of account hashes as `(formatted account hash, label)` pairs.
+pub fn key_labels() -> Vec<(String, String)> {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .key_labels
+        .iter()
+        .map(|(account_hash, label)| (account_hash.to_formatted_string(), label.clone()))
+        .collect()
+}
+
+/// Sets human-readable labels for account hashes, given as `(formatted account hash, label)`
+/// pairs.
+///
+/// The labels are recorded alongside the keys in the provenance header of the generated sources,
+/// so the owner of each key can be identified later.  They don't affect the session code itself.
+pub fn set_key_labels(key_labels: Vec<(String, String)>) -> Result<(), Error> {
+    let key_labels = provenance::parse_key_labels(&key_labels)?;
+    SMART_CONTRACT.lock().unwrap().key_labels = key_labels;
+    Ok(())
+}
+
+/// Sets the label of a single account hash, leaving the others unchanged, or removes its label if
+/// `label` is blank.
+pub fn set_key_label(formatted_account_hash: &str, label: &str) -> Result<(), Error> {
+    let mut key_labels = key_labels();
+    key_labels.retain(|(labelled, _)| labelled.trim() != formatted_account_hash.trim());
+    if !label.trim().is_empty() {
+        key_labels.push((formatted_account_hash.to_string(), label.to_string()));
+    }
+    set_key_labels(key_labels)
+}
+
+/// Saves the associated keys, weights, action thresholds and key labels to the JSON file at
+/// `path`, so they can be opened again later with [`open_key_configuration`].
+pub fn save_key_configuration<P: AsRef<Path>>(
+    configuration: &KeyConfiguration,
+    path: P,
+) -> Result<(), Error> {
+    key_configuration::save(configuration, path.as_ref()).map_err(|inner| Error::KeyConfiguration {
+        file: path.as_ref().display().to_string(),
+        inner,
+    })
+}
+
+/// Reads the configuration saved by [`save_key_configuration`] in the JSON file at `path`.
+///
+/// Only the file is read: the configuration must then be applied, e.g. its key labels passed to
+/// [`set_key_labels`].
+pub fn open_key_configuration<P: AsRef<Path>>(path: P) -> Result<KeyConfiguration, Error> {
+    key_configuration::open(path.as_ref()).map_err(|inner| Error::KeyConfiguration {
+        file: path.as_ref().display().to_string(),
+        inner,
+    })
+}
+
+/// Reads the configuration a project previously generated by the tool was generated from, given
+/// the project's dir, i.e. the project path joined with the contract name.
+///
+/// The keys and labels are taken from the verified provenance header of its session code, and the
+/// weights and thresholds from the code's constants or its `session_args.txt`.  As with
+/// [`open_key_configuration`], the configuration must then be applied.
+pub fn open_project<P: AsRef<Path>>(dir: P) -> Result<KeyConfiguration, Error> {
+    existing_project::read_configuration(dir.as_ref()).map_err(|inner| Error::OpenProject {
+        dir: dir.as_ref().display().to_string(),
+        inner,
+    })
+}
+
+/// Returns the session which all the other functions act on.
+pub fn current_session() -> SessionId {
+    SESSIONS.lock().unwrap().current
+}
+
+/// Creates a session with the default settings and preferences, without selecting it.
+///
+/// It shares the current session's network profiles and starts with the same network selected,
+/// unless the preferences name one.
+pub fn new_session() -> Result<SessionId, Error> {
+    let smart_contract = SMART_CONTRACT.lock().unwrap();
+    let mut new_smart_contract = SmartContract::default();
+    new_smart_contract.networks = smart_contract.networks.clone();
+    new_smart_contract.select_network(smart_contract.selected_network.as_deref())?;
+    new_smart_contract.price_feed_url = smart_contract.price_feed_url.clone();
+    apply_preferences(&mut new_smart_contract, &PREFERENCES.lock().unwrap())?;
+
+    let mut sessions = SESSIONS.lock().unwrap();
+    let session = SessionId(sessions.next);
+    sessions.next += 1;
+    sessions.inactive.insert(session, new_smart_contract);
+    Ok(session)
+}
+
+/// Makes `session` the current one, so that all the other functions act on it.
+///
+/// Any network profiles added in the previous session are carried over, and the session's own
+/// selected network is connected to.
+pub fn select_session(session: SessionId) -> Result<(), Error> {
+    let mut smart_contract = SMART_CONTRACT.lock().unwrap();
+    let mut sessions = SESSIONS.lock().unwrap();
+    if session == sessions.current {
+        return Ok(());
+    }
+    let mut selected = sessions
+        .inactive
+        .remove(&session)
+        .ok_or(Error::UnknownSession { session })?;
+    selected.networks = smart_contract.networks.clone();
+    let previous = std::mem::replace(&mut *smart_contract, selected);
+    rpc::set_connection(
+        smart_contract
+            .selected_network()
+            .map(|profile| profile.connection.clone())
+            .unwrap_or_default(),
+    );
+    let previous_session = std::mem::replace(&mut sessions.current, session);
+    sessions.inactive.insert(previous_session, previous);
+    Ok(())
+}
+
+/// Discards `session`, which must not be the current one.
+pub fn close_session(session: SessionId) -> Result<(), Error> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    if session == sessions.current {
+        return Err(Error::CloseCurrentSession);
+    }
+    sessions
+        .inactive
+        .remove(&session)
+        .map(|_| ())
+        .ok_or(Error::UnknownSession { session })
+}
+
+/// Returns the path of the file the preferences are persisted in, under the user's config dir,
+/// e.g. `~/.config/casper-multisig-tool/preferences.json` on Linux.
+pub fn preferences_file() -> Option<PathBuf> {
+    preferences::file()
+}
+
+/// Loads the preferences from the preferences file and applies them to the current session.
+///
+/// Should be called after [`load_network_profiles`], so that the preferred network can be
+/// selected.  Until the file is first written, the default preferences apply.
+pub fn load_preferences() -> Result<(), Error> {
+    let file = match preferences::file() {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+    let preferences = preferences::load(&file).map_err(|inner| Error::Preferences {
+        file: file.display().to_string(),
+        inner,
+    })?;
+    apply_preferences(&mut SMART_CONTRACT.lock().unwrap(), &preferences)?;
+    *PREFERENCES.lock().unwrap() = preferences;
+    Ok(())
+}
+
+/// Returns the current preferences.
+pub fn preferences() -> Preferences {
+    PREFERENCES.lock().unwrap().clone()
+}
+
+/// Returns `text` translated into the preferred language, or `text` itself if it has no
+/// translation.
+///
+/// The language is the one preferred when the first text is translated, so a new preference takes
+/// effect after a restart.
+pub fn translate(text: &str) -> &str {
+    LANGUAGE
+        .get_or_init(|| PREFERENCES.lock().unwrap().language.resolve())
+        .translate(text)
+        .unwrap_or(text)
+}
+
+/// Returns `template` translated into the preferred language, with each `{}` in turn replaced by
+/// the next of `args`, e.g. for `"Saved in {}"`.
+pub fn translate_with(template: &str, args: &[&str]) -> String {
+    i18n::fill(translate(template), args)
+}
+
+/// Returns `true` if the preferred theme is dark, or is the system theme and the OS is set to a
+/// dark appearance.
+pub fn dark_theme() -> bool {
+    let theme = PREFERENCES.lock().unwrap().theme;
+    theme.is_dark()
+}
+
+/// Returns `true` if the preferred theme is the high-contrast one.
+pub fn high_contrast_theme() -> bool {
+    PREFERENCES.lock().unwrap().theme == Theme::HighContrast
+}
+
+/// Sets the preferences, applies them to the current session and persists them.
+///
+/// The preferred project dir and contract name are only applied to sessions which have none yet.
+/// Returns an error without changing the preferences if an author is invalid, the contract name
+/// can't be made valid, the network doesn't exist or the toolchain doesn't suit the SDK.
+pub fn set_preferences(mut preferences: Preferences) -> Result<(), Error> {
+    preferences
+        .validate()
+        .map_err(|inner| Error::InvalidPreferences { inner })?;
+    preferences.toolchain = preferences.toolchain.trim().to_string();
+    if !preferences.contract_name.trim().is_empty() {
+        preferences.contract_name = contract_name::sanitize(&preferences.contract_name)?;
+    }
+    apply_preferences(&mut SMART_CONTRACT.lock().unwrap(), &preferences)?;
+
+    if let Some(file) = preferences::file() {
+        preferences::save(&file, &preferences).map_err(|inner| Error::Preferences {
+            file: file.display().to_string(),
+            inner,
+        })?;
+    }
+    *PREFERENCES.lock().unwrap() = preferences;
+    Ok(())
+}
+
+/// Applies `preferences` to `smart_contract`.  The project dir and contract name are only applied
+/// if none are set yet.
+fn apply_preferences(
+    smart_contract: &mut SmartContract,
+    preferences: &Preferences,
+) -> Result<(), Error> {
+    if preferences.network.is_some() {
+        smart_contract.select_network(preferences.network.as_deref())?;
+    }
+    // After the network, as that may apply a protocol profile with a different toolchain.
+    if !preferences.toolchain.is_empty() {
+        dependencies::validate(
+            &smart_contract.casper_contract_dependency,
+            &smart_contract.casper_types_dependency,
+            &preferences.toolchain,
+        )?;
+        smart_contract.toolchain = preferences.toolchain.clone();
+    }
+    if smart_contract.root_dir == PathBuf::default() {
+        smart_contract.root_dir = preferences.project_dir.clone();
+    }
+    if smart_contract.contract_name.is_empty() && !preferences.contract_name.is_empty() {
+        smart_contract.contract_name = contract_name::sanitize(&preferences.contract_name)?;
+    }
+    smart_contract.authors = preferences.authors.clone();
+    smart_contract.run_wasm_strip = preferences.run_wasm_strip;
+    smart_contract.run_wasm_opt = preferences.run_wasm_opt;
+    Ok(())
+}
+
+/// Returns the configuration files and generated projects used recently, most recent first.
+pub fn recent_items() -> Result<Vec<RecentItem>, Error> {
+    let file = recent_items_file()?;
+    recent::load(&file).map_err(|inner| Error::RecentItems {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+/// Records the configuration file at `path` as the most recently saved or opened.
+pub fn add_recent_configuration<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+    add_recent_item(RecentItem::Configuration {
+        file: path.as_ref().to_path_buf(),
+    })
+}
+
+/// Records the current project as the most recently generated, from `configuration`, so it can be
+/// reopened with the same keys, project path and contract name.
+pub fn add_recent_project(configuration: &KeyConfiguration) -> Result<(), Error> {
+    let dir = {
+        let smart_contract = SMART_CONTRACT.lock().unwrap();
+        smart_contract.root_dir.join(&smart_contract.contract_name)
+    };
+    add_recent_item(RecentItem::Project {
+        dir,
+        configuration: configuration.clone(),
+    })
+}
+
+/// Forgets all the recent items.
+pub fn clear_recent_items() -> Result<(), Error> {
+    write_recent_items(&[])
+}
+
+fn add_recent_item(item: RecentItem) -> Result<(), Error> {
+    let mut items = recent_items()?;
+    recent::insert(&mut items, item);
+    write_recent_items(&items)
+}
+
+fn recent_items_file() -> Result<PathBuf, Error> {
+    recent::file().ok_or_else(|| Error::RecentItems {
+        file: "recent.json".to_string(),
+        inner: "the user's config dir couldn't be found".to_string(),
+    })
+}
+
+fn write_recent_items(items: &[RecentItem]) -> Result<(), Error> {
+    let file = recent_items_file()?;
+    recent::save(&file, items).map_err(|inner| Error::RecentItems {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+/// Autosaves the configuration of each of the given sessions, as `(session, tab name,
+/// configuration)`, so they can be restored with [`autosaved_tabs`] if the tool is closed by
+/// accident.
+///
+/// The key labels, project path and contract name are taken from each session rather than the
+/// configuration.  If no session has any associated keys, there's nothing worth restoring, so the
+/// autosave is discarded instead.
+pub fn autosave(tabs: Vec<(SessionId, String, KeyConfiguration)>) -> Result<(), Error> {
+    if tabs
+        .iter()
+        .all(|(_, _, configuration)| configuration.associated_keys.is_empty())
+    {
+        return discard_autosave();
+    }
+
+    let autosaved_tabs = {
+        let smart_contract = SMART_CONTRACT.lock().unwrap();
+        let sessions = SESSIONS.lock().unwrap();
+        tabs.into_iter()
+            .map(|(session, name, mut configuration)| {
+                let session_smart_contract = if session == sessions.current {
+                    &*smart_contract
+                } else {
+                    sessions
+                        .inactive
+                        .get(&session)
+                        .ok_or(Error::UnknownSession { session })?
+                };
+                configuration.key_labels = session_smart_contract
+                    .key_labels
+                    .iter()
+                    .map(|(account_hash, label)| {
+                        (account_hash.to_formatted_string(), label.clone())
+                    })
+                    .collect();
+                Ok(AutosavedTab {
+                    name,
+                    configuration,
+                    project_path: session_smart_contract.root_dir.clone(),
+                    contract_name: session_smart_contract.contract_name.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+    };
+    let file = autosave_file()?;
+    autosave::save(&file, &autosaved_tabs).map_err(|inner| Error::Autosave {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+/// Returns the tabs last saved by [`autosave`], or none if there's no autosave.
+pub fn autosaved_tabs() -> Result<Vec<AutosavedTab>, Error> {
+    let file = autosave_file()?;
+    autosave::load(&file).map_err(|inner| Error::Autosave {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+/// Removes the autosave, e.g. once the user has chosen not to restore it.
+pub fn discard_autosave() -> Result<(), Error> {
+    let file = autosave_file()?;
+    autosave::discard(&file).map_err(|inner| Error::Autosave {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+fn autosave_file() -> Result<PathBuf, Error> {
+    autosave::file().ok_or_else(|| Error::Autosave {
+        file: "autosave.json".to_string(),
+        inner: "the user's config dir couldn't be found".to_string(),
+    })
+}
+
+/// Returns the hex-encoded Blake2b hash of the current configuration, as recorded in the
+/// provenance header of the sources generated from it.
+pub fn current_config_hash() -> Result<String, Error> {
+    SMART_CONTRACT.lock().unwrap().config_hash()
+}
+
+/// Parses and verifies the provenance headers of the session code sources in the generated
+/// project at `project_dir`.
+///
+/// Each source's recorded hash must match its contents, and all of them must have been generated
+/// from the same configuration.  For a project scaffolded by cargo-casper, the sources are those of
+/// its `contract` crate.  Returns the provenance of `src/main.rs`, whose `config_hash` can
+/// be compared with [`current_config_hash`] to check the project matches the current
+/// configuration.
+pub fn verify_provenance<P: AsRef<Path>>(project_dir: P) -> Result<Provenance, Error> {
+    provenance::verify_project(project_dir.as_ref())
+}
+
+/// Returns the named keys stored by the generated contract as `(name, formatted key)` pairs.
+pub fn named_keys() -> Vec<(String, String)> {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .named_keys
+        .iter()
+        .map(|named_key| (named_key.name.clone(), named_key.key.to_formatted_string()))
+        .collect()
+}
+
+/// Sets the named keys to be stored in the account by the generated contract via
+/// `runtime::put_key`.
+///
+/// Each key is given in its formatted form, e.g. `hash-<hex>`, `uref-<hex>-<rights>` or
+/// `account-hash-<hex>`.  Names must be unique.  A URef must already be accessible to the account,
+/// otherwise the deploy will fail with a forged reference error.
+pub fn set_named_keys(named_keys: Vec<(String, String)>) -> Result<(), Error> {
+    let named_keys = named_keys
+        .iter()
+        .map(|(name, key)| NamedKey::new(name, key))
+        .collect::<Result<Vec<_>, _>>()?;
+    named_keys::validate_unique(&named_keys)?;
+    SMART_CONTRACT.lock().unwrap().named_keys = named_keys;
+    Ok(())
+}
+
+/// Returns the initial transfer as `(amount in motes, source secret key path, target public key)`,
+/// if one has been set.
+pub fn initial_transfer() -> Option<(String, String, String)> {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .initial_transfer
+        .as_ref()
+        .map(|initial_transfer| {
+            (
+                initial_transfer.amount.to_string(),
+                initial_transfer.source_secret_key.display().to_string(),
+                initial_transfer.target_public_key.to_hex(),
+            )
+        })
+}
+
+/// Sets the native transfer which funds the main account before the setup deploy is sent, given
+/// as `(amount in motes, source secret key path, target public key)`.
+///
+/// The target must be the hex-encoded public key of the main account.  The transfer is described
+/// in the generated README and Makefile, and can be sent via [`submit_initial_transfer`].  Pass
+/// `None` to not fund the main account.
+pub fn set_initial_transfer(initial_transfer: Option<(&str, &str, &str)>) -> Result<(), Error> {
+    let initial_transfer = initial_transfer
+        .map(|(amount, source_secret_key, target_public_key)| {
+            InitialTransfer::new(amount, source_secret_key, target_public_key)
+        })
+        .transpose()?;
+    SMART_CONTRACT.lock().unwrap().initial_transfer = initial_transfer;
+    Ok(())
+}
+
+/// Sends the initial transfer to the given node using `casper-client`.
+///
+/// The output is streamed through the returned channel, with [`TransferEvent::Finished`] sent
+/// last.  Returns an error if no initial transfer is set or it doesn't fund the main account.
+pub fn submit_initial_transfer(
+    node_address: &str,
+    chain_name: &str,
+) -> Result<Receiver<TransferEvent>, Error> {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .submit_initial_transfer(node_address, chain_name)
+}
+
+pub fn main_rs_contents() -> String {
+    SMART_CONTRACT.lock().unwrap().main_rs_contents()
+}
+
+/// Returns the byte ranges of the Rust `source` to highlight as keywords, comments or literals, in
+/// order, e.g. to show the generated code.
+pub fn highlight_rust(source: &str) -> Vec<(Range<usize>, RustToken)> {
+    rust_syntax::highlight(source)
+}
+
+/// Writes the generated `source` to the file at `path`, e.g. so it can be reviewed before the
+/// project is generated and compiled.
+pub fn save_source<P: AsRef<Path>>(source: &str, path: P) -> Result<(), Error> {
+    fs::write(path.as_ref(), source).map_err(|error| Error::SaveSource {
+        file: path.as_ref().display().to_string(),
+        inner: error.to_string(),
+    })
+}
+
+/// Returns the session code of every deploy needed to reach the configuration, in the order they
+/// must be sent, as `(path relative to the project dir, contents)` pairs.
+///
+/// A newly-created account can't always be converted in a single deploy, since every change needs
+/// the deploy's signers to meet the key-management threshold in force at that point.  In that case
+/// the later deploys must be signed by several of the associated keys.
+pub fn contract_sources() -> Result<Vec<(PathBuf, String)>, Error> {
+    SMART_CONTRACT.lock().unwrap().contract_sources()
+}
+
+/// Returns the Rust source of the generic session contract which reads the keys, weights and
+/// thresholds from its runtime args.
+///
+/// This is the source from which a prebuilt session Wasm should be compiled, adapted for the
+/// current [`CodegenTarget`].
+pub fn prebuilt_session_source() -> String {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .codegen_target
+        .adapt_source(&prebuilt_session::main_rs_contents())
+}
+
+/// Returns the path to the prebuilt session Wasm, if one has been set.
+pub fn prebuilt_session_wasm() -> Option<PathBuf> {
+    SMART_CONTRACT.lock().unwrap().prebuilt_session_wasm.clone()
+}
+
+/// Returns the Blake2b hash the prebuilt session Wasm is pinned to, or `None` if none is set or it's
+/// unverified.
+pub fn prebuilt_session_wasm_hash() -> Option<String> {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .prebuilt_session_wasm_hash
+        .clone()
+}
+
+/// Returns the hex-encoded Blake2b hash of the Wasm module at `path`.
+pub fn wasm_file_hash(path: &str) -> Result<String, Error> {
+    let bytes = fs::read(path).map_err(|error| Error::ReadWasmFile {
+        file: path.to_string(),
+        inner: error.to_string(),
+    })?;
+    Ok(prebuilt_session::wasm_hash(&bytes))
+}
+
+/// Sets the path to a prebuilt session Wasm, compiled from [`prebuilt_session_source`].
+///
+/// The tool doesn't ship a build of that contract, so `expected_hash` should be the Blake2b hash
+/// published with the audited build: a module with any other hash is rejected.  If it's `None`,
+/// the module is accepted but unverified, which the generation output and README point out.
+///
+/// While set, generating the smart contract skips compilation entirely: the prebuilt module is
+/// copied into the project along with the runtime args matching the current configuration.  Pass
+/// `None` to revert to compiling a dedicated contract.
+pub fn set_prebuilt_session_wasm(
+    path: Option<&str>,
+    expected_hash: Option<&str>,
+) -> Result<(), Error> {
+    if let Some(file) = path {
+        let bytes = fs::read(file).map_err(|error| Error::ReadWasmFile {
+            file: file.to_string(),
+            inner: error.to_string(),
+        })?;
+        prebuilt_session::validate_wasm(file, &bytes, expected_hash)?;
+    }
+    let mut smart_contract = SMART_CONTRACT.lock().unwrap();
+    smart_contract.prebuilt_session_wasm = path.map(PathBuf::from);
+    smart_contract.prebuilt_session_wasm_hash = path
+        .and(expected_hash)
+        .map(|hash| hash.trim().to_lowercase());
+    Ok(())
+}
+
+/// Returns the runtime args to be passed to the prebuilt session Wasm for the current
+/// configuration.
+pub fn session_runtime_args() -> Result<RuntimeArgs, Error> {
+    SMART_CONTRACT.lock().unwrap().runtime_args()
+}
+
+/// Returns the runtime args to be passed to the prebuilt session Wasm for the current
+/// configuration, formatted as casper-client `--session-arg` values.
+pub fn casper_client_session_args() -> Vec<String> {
+    SMART_CONTRACT.lock().unwrap().casper_client_session_args()
+}
+
+/// Returns the runtime args to be passed to the generic session contract for the given
+/// configuration, independently of the current one.
+///
+/// `keys` are `(formatted account hash, weight)` pairs, with the main account's key first.
+pub fn build_session_runtime_args(
+    keys: &[(String, u8)],
+    remove_main_account: bool,
+    key_management_weight: u8,
+    deployment_weight: u8,
+) -> Result<RuntimeArgs, Error> {
+    prebuilt_session::runtime_args(
+        &parse_keys(keys)?,
+        remove_main_account,
+        key_management_weight,
+        deployment_weight,
+    )
+}
+
+/// Returns the runtime args to be passed to the generic session contract for the given
+/// configuration, formatted as casper-client `--session-arg` values.
+///
+/// `keys` are `(formatted account hash, weight)` pairs, with the main account's key first.
+pub fn build_casper_client_session_args(
+    keys: &[(String, u8)],
+    remove_main_account: bool,
+    key_management_weight: u8,
+    deployment_weight: u8,
+) -> Result<Vec<String>, Error> {
+    Ok(prebuilt_session::casper_client_session_args(
+        &parse_keys(keys)?,
+        remove_main_account,
+        key_management_weight,
+        deployment_weight,
+    ))
+}
+
+fn parse_keys(keys: &[(String, u8)]) -> Result<Vec<(AccountHash, u8)>, Error> {
+    if keys.is_empty() {
+        return Err(Error::NoKeys);
+    }
+    keys.iter()
+        .map(|(formatted_account_hash, weight)| {
+            AccountHash::from_formatted_str(formatted_account_hash)
+                .map(|account_hash| (account_hash, *weight))
+                .map_err(|error| Error::ParseAccountHash {
+                    inner: error.to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Returns a deploy of the given session Wasm, paid for with the standard payment code.
+///
+/// The deploy is sent from the account of `params.secret_key`, which also signs it.  Further
+/// approvals must be added before sending it if that key alone doesn't meet the account's
+/// deployment threshold.  Returns an error if the network would reject the deploy's header, its
+/// runtime args are too long, or it exceeds the [`max_deploy_size`].
+pub fn build_deploy<P: AsRef<Path>>(wasm: P, params: &DeployParams) -> Result<Deploy, Error> {
+    let max_deploy_size = SMART_CONTRACT.lock().unwrap().max_deploy_size;
+    deploy::build(
+        &wasm.as_ref().display().to_string(),
+        params,
+        max_deploy_size,
+    )
+}
+
+/// Returns a copy of `deploy` with a fresh timestamp, for when it expired, or is about to, before
+/// enough approvals were collected.
+///
+/// The copy is signed only by the secret key of the deploy's account in `secret_key_file`.  It has
+/// a new hash, so every other signer must sign it again: none of the original's approvals carry
+/// over.  Returns an error if the key isn't that of the deploy's account.
+pub fn rebuild_deploy(deploy: &Deploy, secret_key_file: &str) -> Result<Deploy, Error> {
+    deploy::rebuild(deploy, secret_key_file)
+}
+
+/// Returns how long until `deploy` expires, after which the network won't execute it, or `None` if
+/// it already has.
+pub fn time_to_expiry(deploy: &Deploy) -> Option<Duration> {
+    deploy::time_to_expiry(deploy)
+}
+
+/// Signs `deploy` with the secret key in the given PEM file, appending the approval to the deploy
+/// and returning it.
+///
+/// Both Ed25519 and secp256k1 keys are supported.  Returns an error if the key can't be read or
+/// has already signed the deploy.
+pub fn sign_deploy(deploy: &mut Deploy, secret_key_file: &str) -> Result<Approval, Error> {
+    deploy::sign(deploy, secret_key_file)
+}
+
+/// Writes `deploy` as JSON to `path`, in the form accepted by `casper-client send-deploy`.
+pub fn save_deploy<P: AsRef<Path>>(deploy: &Deploy, path: P) -> Result<(), Error> {
+    deploy::save(deploy, path.as_ref())
+}
+
+/// Writes `deploy` without any approvals as JSON to `path`.
+///
+/// This is the file to hand to the other signers, who can each sign it with
+/// `casper-client sign-deploy` and return the output for [`import_approvals`].
+pub fn save_unsigned_deploy<P: AsRef<Path>>(deploy: &Deploy, path: P) -> Result<(), Error> {
+    deploy::save_unsigned(deploy, path.as_ref())
+}
+
+/// Reads a deploy from the JSON file at `path`, e.g. one written by [`save_deploy`].
+///
+/// Returns an error if its hashes don't match its contents or any of its approvals is invalid.
+pub fn load_deploy<P: AsRef<Path>>(path: P) -> Result<Deploy, Error> {
+    deploy::load(path.as_ref())
+}
+
+/// Decodes the deploy in the given JSON file, e.g. one built elsewhere which the user is asked to
+/// sign, and compares its session Wasm with the local build at `wasm` and its session args with
+/// those the configuration calls for.
+///
+/// Returns an error if the file doesn't hold a valid deploy, or the Wasm can't be read.
+pub fn inspect_deploy<P: AsRef<Path>, Q: AsRef<Path>>(
+    deploy_file: P,
+    wasm: Q,
+) -> Result<DeployInspection, Error> {
+    let deploy = deploy::load(deploy_file.as_ref())?;
+    let wasm_bytes = fs::read(wasm.as_ref()).map_err(|error| Error::ReadWasmFile {
+        file: wasm.as_ref().display().to_string(),
+        inner: error.to_string(),
+    })?;
+    let expected_args = SMART_CONTRACT.lock().unwrap().session_args()?;
+    Ok(DeployInspection::new(&deploy, &wasm_bytes, &expected_args))
+}
+
+/// Checks that `signature` is `public_key`'s signature of the hash of `deploy`, e.g. to confirm a
+/// co-signer signed exactly this deploy rather than another.
+pub fn verify_approval(
+    deploy: &Deploy,
+    public_key: &PublicKey,
+    signature: &Signature,
+) -> Result<(), Error> {
+    deploy::verify_approval(deploy, public_key, signature).map_err(|inner| Error::InvalidApproval {
+        signer: public_key.to_hex(),
+        inner,
+    })
+}
+
+/// Checks each approval in the JSON file at `path` against the hash of `deploy`, without adding
+/// any to it.
+///
+/// The file may hold the same forms as for [`import_approvals`].  Returns an error if it can't be
+/// read, or is a signed copy of a different deploy.
+pub fn check_approvals<P: AsRef<Path>>(
+    deploy: &Deploy,
+    path: P,
+) -> Result<Vec<ApprovalCheck>, Error> {
+    deploy::check_approvals(deploy, path.as_ref())
+}
+
+/// Merges the approvals in the JSON file at `path` into `deploy`, returning the ones added.
+///
+/// The file may be a signed copy of the deploy, as written by `casper-client sign-deploy`, or hold
+/// a single approval or a list of them.  Every signature is verified against the deploy hash, and
+/// if any is invalid or the file holds a different deploy, nothing is added.  Approvals by keys
+/// which have already signed the deploy are skipped.
+pub fn import_approvals<P: AsRef<Path>>(
+    deploy: &mut Deploy,
+    path: P,
+) -> Result<Vec<Approval>, Error> {
+    deploy::import_approvals(deploy, path.as_ref())
+}
+
+/// Writes an offline signing bundle for `deploy` of the session Wasm at `wasm` into a new dir under
+/// `parent_dir`, e.g. on removable media, returning the bundle's path.
+///
+/// The bundle holds a copy of the project's sources, the Wasm, the unsigned deploy, a summary of
+/// the configuration and a manifest of the files' Blake2b hashes.  Signers on machines which are
+/// never online sign the unsigned deploy with `casper-client sign-deploy`, writing their signed
+/// copies into the bundle's `approvals` dir, for [`import_offline_bundle`].
+pub fn export_offline_bundle<P: AsRef<Path>, Q: AsRef<Path>>(
+    parent_dir: P,
+    wasm: Q,
+    deploy: &Deploy,
+) -> Result<PathBuf, Error> {
+    let wasm_bytes = fs::read(wasm.as_ref()).map_err(|error| Error::ReadWasmFile {
+        file: wasm.as_ref().display().to_string(),
+        inner: error.to_string(),
+    })?;
+    let (project_dir, summary) = {
+        let smart_contract = SMART_CONTRACT.lock().unwrap();
+        (
+            smart_contract.root_dir.join(&smart_contract.contract_name),
+            smart_contract.bundle_summary(&wasm_bytes),
+        )
+    };
+    let bundle_dir = parent_dir.as_ref().join(format!(
+        "{}-offline-bundle-{}",
+        project_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        &format!("{:x}", deploy.id().inner())[..8]
+    ));
+    offline_bundle::export(&bundle_dir, &project_dir, wasm.as_ref(), deploy, &summary)?;
+    Ok(bundle_dir)
+}
+
+/// Checks the offline signing bundle in `bundle_dir` was exported for `deploy` and that none of
+/// its files have been altered, then merges the approvals signers wrote into its `approvals` dir
+/// into `deploy`, returning those added.
+///
+/// Every approval's signature is checked against the deploy hash before any are added.
+pub fn import_offline_bundle<P: AsRef<Path>>(
+    deploy: &mut Deploy,
+    bundle_dir: P,
+) -> Result<Vec<Approval>, Error> {
+    offline_bundle::import(deploy, bundle_dir.as_ref())
+}
+
+/// Sends the deploy saved in the given JSON file to the given node using `casper-client`, then
+/// polls the node until the deploy has been executed or has expired.
+///
+/// The deploy isn't sent if the paying account's main purse doesn't hold enough to cover its
+/// payment amount.
+///
+/// `node_address` may list several nodes separated by commas.  Requests which fail because a node
+/// can't be reached, times out or responds with a 5xx status are retried against the next, and
+/// then against all of them again with an exponential backoff.  The same applies to the other
+/// functions which query nodes, other than the initial transfer, which only uses the first node.
+///
+/// If `event_stream_url` is given, e.g. from [`default_event_stream_url`], the node's event stream
+/// is also watched using `curl`, so that the result is reported as soon as the deploy is processed
+/// rather than on the next poll.
+///
+/// The output is streamed through the returned channel, with [`DeployEvent::Succeeded`],
+/// [`DeployEvent::Failed`] or [`DeployEvent::NotExecuted`] sent last.  Returns an error if the file
+/// doesn't hold a valid deploy.
+pub fn send_deploy<P: AsRef<Path>>(
+    deploy_file: P,
+    node_address: &str,
+    event_stream_url: Option<&str>,
+) -> Result<Receiver<DeployEvent>, Error> {
+    let deploy = deploy::load(deploy_file.as_ref())?;
+    Ok(deploy_status::send(
+        deploy,
+        deploy_file.as_ref(),
+        node_address,
+        event_stream_url,
+    ))
+}
+
+/// Checks that the account which sent the deploy saved in the given JSON file has the configured
+/// associated keys, weights and action thresholds on the node's network, e.g. once the setup
+/// deploy has executed.
+///
+/// Only the setup contract's outcome can be verified, and where the setup needs several deploys,
+/// the account only matches once the last has executed.  Uses `casper-client`, and blocks until
+/// the node responds.
+pub fn verify_deployed_account<P: AsRef<Path>>(
+    deploy_file: P,
+    node_address: &str,
+) -> Result<Verification, Error> {
+    let make_error = |inner: String| Error::VerifyAccount {
+        node_address: node_address.trim().to_string(),
+        inner,
+    };
+
+    let expected = {
+        let smart_contract = SMART_CONTRACT.lock().unwrap();
+        if smart_contract.contract_mode != ContractMode::Setup {
+            return Err(make_error(
+                "only the setup contract's outcome can be verified".to_string(),
+            ));
+        }
+        smart_contract.expected_account()
+    };
+    let deploy = deploy::load(deploy_file.as_ref())?;
+    let mismatches =
+        verification::verify(node_address.trim(), deploy.header().account(), &expected)
+            .map_err(make_error)?;
+    Ok(Verification::new(mismatches))
+}
+
+/// Returns every problem with the current configuration, errors first, e.g. a threshold which the
+/// keys can't meet, or which a single key meets alone.
+///
+/// Errors prevent the contract being generated, or would lock the account, while warnings flag
+/// configurations which work but are probably unintended.  Returns none if no keys are set.
+pub fn validate_configuration() -> Vec<ValidationIssue> {
+    SMART_CONTRACT.lock().unwrap().validate()
+}
+
+/// Returns the associated keys and action thresholds the main account is left with once the setup
+/// contract has executed, exactly as they will be held in global state.
+///
+/// Returns `None` unless the contract mode is the multisig account setup, as the other modes change
+/// an existing account whose keys before the deploy aren't known.
+pub fn resulting_account() -> Option<AccountState> {
+    let smart_contract = SMART_CONTRACT.lock().unwrap();
+    if smart_contract.contract_mode != ContractMode::Setup {
+        return None;
+    }
+    let expected = smart_contract.expected_account();
+    Some(AccountState {
+        associated_keys: expected.associated_keys,
+        key_management_threshold: expected.key_management_threshold,
+        deployment_threshold: expected.deployment_threshold,
+    })
+}
+
+/// Returns the associated keys and action thresholds of the given account, as reported by the node
+/// at `node_address`.
+pub fn fetch_account_state(node_address: &str, account: &PublicKey) -> Result<AccountState, Error> {
+    account_state::fetch(node_address.trim(), account).map_err(|inner| Error::FetchAccount {
+        node_address: node_address.trim().to_string(),
+        inner,
+    })
+}
+
+/// Returns the associated keys and action thresholds the given account had in the global state
+/// after `block`, or has now if `None`, as reported by the node at `node_address`.
+///
+/// Querying a past block lets e.g. an audit see who could sign for the account at the time of an
+/// incident.  `account` is the account's hex-encoded public key or formatted account hash.  The
+/// node must still hold the global state of the block.
+pub fn query_account_state(
+    node_address: &str,
+    account: &str,
+    block: Option<&BlockIdentifier>,
+) -> Result<AccountState, Error> {
+    account_state::fetch_at(node_address.trim(), account, block).map_err(|inner| {
+        Error::FetchAccount {
+            node_address: node_address.trim().to_string(),
+            inner,
+        }
+    })
+}
+
+/// Returns how far the valid approvals of `deploy` go towards meeting the deployment threshold of
+/// `account`, which should be the state of the deploy's account fetched via
+/// [`fetch_account_state`].
+pub fn approval_progress(deploy: &Deploy, account: &AccountState) -> ApprovalProgress {
+    ApprovalProgress::new(deploy, account)
+}
+
+/// Returns the URL of the main event stream of the node at the given JSON-RPC address, assuming it
+/// uses the default ports, e.g. `http://1.2.3.4:9999/events/main` for `http://1.2.3.4:7777`.
+pub fn default_event_stream_url(node_address: &str) -> Option<String> {
+    event_stream::default_url(node_address)
+}
+
+/// Executes the deploy saved in the given JSON file against the node's current global state
+/// without committing it, returning its predicted cost and effects, or why it would fail.
+///
+/// `node_address` must be that of a node's speculative execution server, e.g. from
+/// [`default_speculative_exec_address`], which node operators have to enable.  Uses
+/// `casper-client`, and blocks until the node responds.
+///
+/// If the deploy would succeed, a payment amount covering its cost is suggested, and the cost is
+/// remembered as the [`estimate_cost`] of its session Wasm.
+pub fn dry_run_deploy<P: AsRef<Path>>(deploy_file: P, node_address: &str) -> Result<DryRun, Error> {
+    let mut dry_run = dry_run::run(deploy_file.as_ref(), node_address)?;
+    if let (None, Ok(cost)) = (&dry_run.error_message, U512::from_dec_str(&dry_run.cost)) {
+        let estimate = CostEstimate::from_speculative_cost(cost);
+        dry_run.suggested_payment = Some(estimate.suggested_payment);
+        let deploy = deploy::load(deploy_file.as_ref())?;
+        if let Some(wasm_hash) = cost_estimate::session_wasm_hash(&deploy) {
+            SMART_CONTRACT.lock().unwrap().speculative_estimate = Some((wasm_hash, estimate));
+        }
+    }
+    Ok(dry_run)
+}
+
+/// Returns an estimate of the cost of a deploy of the given session Wasm, along with a suggested
+/// payment amount.
+///
+/// Uses the cost measured by the last successful [`dry_run_deploy`] of the same Wasm if there is
+/// one, or else a rough estimate from the Wasm's size and the number of associated keys.
+pub fn estimate_cost<P: AsRef<Path>>(wasm: P) -> Result<CostEstimate, Error> {
+    let wasm_bytes = fs::read(wasm.as_ref()).map_err(|error| Error::ReadWasmFile {
+        file: wasm.as_ref().display().to_string(),
+        inner: error.to_string(),
+    })?;
+    let wasm_hash = cost_estimate::wasm_hash(&wasm_bytes);
+    let smart_contract = SMART_CONTRACT.lock().unwrap();
+    match &smart_contract.speculative_estimate {
+        Some((estimated_hash, estimate)) if *estimated_hash == wasm_hash => Ok(estimate.clone()),
+        _ => Ok(CostEstimate::heuristic(
+            wasm_bytes.len() as u64,
+            smart_contract.associated_keys.len(),
+        )),
+    }
+}
+
+/// Returns CoinGecko's endpoint for the CSPR price, used by default.
+pub fn default_price_feed_url() -> &'static str {
+    price_feed::COINGECKO_PRICE_URL
+}
+
+/// Returns the endpoint the CSPR price is fetched from, or `None` if costs aren't shown in fiat.
+pub fn price_feed_url() -> Option<String> {
+    SMART_CONTRACT.lock().unwrap().price_feed_url.clone()
+}
+
+/// Sets the endpoint the CSPR price is fetched from to show costs in US dollars and euros, or
+/// `None` to show them in motes only.
+///
+/// The endpoint must return JSON in the form of CoinGecko's, i.e. `{"casper-network": {"usd": 0.03,
+/// "eur": 0.028}}`, or just the inner object.  Defaults to `None`.
+pub fn set_price_feed_url(url: Option<&str>) {
+    SMART_CONTRACT.lock().unwrap().price_feed_url = url
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string);
+}
+
+/// Returns the current price of CSPR, or `None` if no price feed is set.
+///
+/// Use [`CsprPrice::fiat_value`] to convert an amount of motes.  This runs `curl`, and blocks for
+/// up to 10 seconds until the price feed responds.
+pub fn cspr_price() -> Result<Option<CsprPrice>, Error> {
+    let url = match price_feed_url() {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+    price_feed::fetch(&url)
+        .map(Some)
+        .map_err(|inner| Error::PriceFeed { url, inner })
+}
+
+/// Returns the address of the speculative execution server of the node at the given JSON-RPC
+/// address, assuming it uses the default ports, e.g. `http://1.2.3.4:7778` for
+/// `http://1.2.3.4:7777`.
+pub fn default_speculative_exec_address(node_address: &str) -> Option<String> {
+    dry_run::default_address(node_address)
+}
+
+/// Returns `true` if the given chain name is that of the official Casper testnet.
+pub fn is_testnet(chain_name: &str) -> bool {
+    chain_name.trim() == faucet::TESTNET_CHAIN_NAME
+}
+
+/// Opens the official testnet's faucet in the default browser, so that test CSPR can be requested
+/// for the account with the given public key.
+///
+/// The faucet requires the user to prove they're human, so can't be sent the request directly.
+/// Returns the hex-encoded public key to paste into the faucet, or an error if `public_key` isn't
+/// a valid hex-encoded public key or the browser couldn't be opened.
+pub fn open_testnet_faucet(public_key: &str) -> Result<String, Error> {
+    faucet::open_testnet_faucet(public_key)
+}
+
+/// Returns the path of the file the network profiles are persisted in, under the user's config
+/// dir, e.g. `~/.config/casper-multisig-tool/networks.json` on Linux.
+pub fn network_config_file() -> Option<PathBuf> {
+    network::config_file()
+}
+
+/// Loads the network profiles and the selected network from the networks config file, applying
+/// the selected network's protocol profile and max deploy size.
+///
+/// Until the file is first written, the built-in mainnet, testnet and NCTL profiles are available
+/// and none is selected.
+pub fn load_network_profiles() -> Result<(), Error> {
+    let file = match network::config_file() {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+    let config = match network::load(&file).map_err(|inner| Error::NetworkConfig {
+        file: file.display().to_string(),
+        inner,
+    })? {
+        Some(config) => config,
+        None => return Ok(()),
+    };
+    let mut smart_contract = SMART_CONTRACT.lock().unwrap();
+    smart_contract.networks = config.networks;
+    smart_contract.select_network(config.selected.as_deref())
+}
+
+/// Returns all the network profiles.
+pub fn network_profiles() -> Vec<NetworkProfile> {
+    SMART_CONTRACT.lock().unwrap().networks.clone()
+}
+
+/// Returns the network profile with the given name, if there is one, e.g. for a `--network`
+/// argument.
+pub fn network_profile(name: &str) -> Option<NetworkProfile> {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .networks
+        .iter()
+        .find(|profile| profile.name == name.trim())
+        .cloned()
+}
+
+/// Returns the profile of the selected network, whose node address, event stream URL and chain
+/// name the RPC-facing features default to, if a network is selected.
+pub fn selected_network() -> Option<NetworkProfile> {
+    SMART_CONTRACT.lock().unwrap().selected_network().cloned()
+}
+
+/// Selects the network with the given name, or none, and persists the choice.
+///
+/// The network's max deploy size is applied, as is the protocol profile matching its expected
+/// protocol version, if it has one.
+pub fn select_network(name: Option<&str>) -> Result<(), Error> {
+    let mut smart_contract = SMART_CONTRACT.lock().unwrap();
+    smart_contract.select_network(name)?;
+    save_network_config(&smart_contract)
+}
+
+/// Adds the given network profile, replacing any existing one with the same name, and persists
+/// it.
+pub fn save_network_profile(profile: NetworkProfile) -> Result<(), Error> {
+    let mut smart_contract = SMART_CONTRACT.lock().unwrap();
+    smart_contract.save_network(profile)?;
+    save_network_config(&smart_contract)
+}
+
+fn save_network_config(smart_contract: &SmartContract) -> Result<(), Error> {
+    let file = match network::config_file() {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+    network::save(
+        &file,
+        &smart_contract.networks,
+        smart_contract.selected_network.as_deref(),
+    )
+    .map_err(|inner| Error::NetworkConfig {
+        file: file.display().to_string(),
+        inner,
+    })
+}
+
+/// Returns the URL of the given account's page on the selected network's block explorer, or `None`
+/// if no network with an explorer is selected or `account_hash` isn't a formatted account hash.
+pub fn account_explorer_url(account_hash: &str) -> Option<String> {
+    let account_hash = AccountHash::from_formatted_str(account_hash.trim()).ok()?;
+    let explorer_url = selected_network()?.explorer_url?;
+    Some(explorer::account_url(&explorer_url, &account_hash))
+}
+
+/// Returns the URL of the given deploy's page on the selected network's block explorer, or `None`
+/// if no network with an explorer is selected.
+pub fn deploy_explorer_url(deploy: &Deploy) -> Option<String> {
+    let explorer_url = selected_network()?.explorer_url?;
+    Some(explorer::deploy_url(&explorer_url, deploy))
+}
+
+/// Opens the given explorer URL, e.g. from [`account_explorer_url`], in the default browser.
+pub fn open_in_explorer(url: &str) -> Result<(), Error> {
+    faucet::open_in_browser(url).map_err(|inner| Error::OpenExplorer {
+        url: url.to_string(),
+        inner,
+    })
+}
+
+/// Opens `dir` in the system's file manager, e.g. to show the generated project or the compiled
+/// Wasm.
+pub fn open_folder<P: AsRef<Path>>(dir: P) -> Result<(), Error> {
+    file_manager::open_dir(dir.as_ref()).map_err(|inner| Error::OpenFolder {
+        dir: dir.as_ref().display().to_string(),
+        inner,
+    })
+}
+
+/// Returns the assets dir of the first local network started by NCTL, i.e. `$NCTL/assets/net-1`,
+/// if the `NCTL` environment variable is set.
+pub fn default_nctl_assets_dir() -> Option<PathBuf> {
+    local_network::default_assets_dir()
+}
+
+/// Rehearses the setup deploy of the session Wasm on a local NCTL network, given its assets dir,
+/// e.g. `$NCTL/assets/net-1`.
+///
+/// The main account is funded from the key of the given NCTL user, or the network's faucet if
+/// `funding_user` is 0.  The deploy is then built, signed with the main account's secret key and
+/// sent to the network's first node, and the account's associated keys and thresholds are
+/// compared against the configuration.  Uses `casper-client`.
+///
+/// The output is streamed through the returned channel, with [`LocalNetworkEvent::Finished`] sent
+/// last.  Returns an error unless the contract mode is [`ContractMode::Setup`] and the setup needs
+/// a single deploy, or if `main_secret_key` isn't the main account's key.
+pub fn test_on_local_network<P: AsRef<Path>, Q: AsRef<Path>>(
+    wasm: P,
+    assets_dir: Q,
+    funding_user: u32,
+    main_secret_key: &str,
+) -> Result<Receiver<LocalNetworkEvent>, Error> {
+    let rehearsal = SMART_CONTRACT.lock().unwrap().local_network_rehearsal(
+        wasm.as_ref(),
+        assets_dir.as_ref(),
+        funding_user,
+        main_secret_key,
+    )?;
+    rehearsal.run()
+}
+
+/// Returns `true` if the tool was built with support for Ledger hardware wallets, i.e. with the
+/// `ledger` feature.
+pub fn ledger_supported() -> bool {
+    cfg!(feature = "ledger")
+}
+
+/// Returns `true` if the tool was built with support for YubiKeys, i.e. with the `yubikey`
+/// feature.
+pub fn yubikey_supported() -> bool {
+    cfg!(feature = "yubikey")
+}
+
+/// Returns the hex-encoded public key of a key held by a remote service or device, e.g. a
+/// [`YubiKeySigner`], for adding as an associated key.
+pub fn remote_signer_public_key(signer: &dyn RemoteSigner) -> Result<String, Error> {
+    signer
+        .public_key()
+        .map(|public_key| public_key.to_hex())
+        .map_err(|inner| Error::RemoteSigner {
+            signer: signer.description(),
+            inner,
+        })
+}
+
+/// Returns the hex-encoded public key at `m/44'/506'/0'/0/<account_index>` on a connected Ledger
+/// device running the Casper app.
+///
+/// The device shows the key's address, and the key is only returned once the user confirms it.
+pub fn ledger_public_key(account_index: u32) -> Result<String, Error> {
+    ledger::public_key(account_index).map(|public_key| public_key.to_hex())
+}
+
+/// Has the user review `deploy` on a connected Ledger device running the Casper app, and sign it
+/// with the key at `m/44'/506'/0'/0/<account_index>`.
+///
+/// The approval is checked against the deploy hash, then appended to the deploy and returned.
+pub fn sign_deploy_with_ledger(deploy: &mut Deploy, account_index: u32) -> Result<Approval, Error> {
+    ledger::sign(deploy, account_index)
+}
+
+/// Signs the hash of `deploy` with a key held by a remote service, e.g. an [`AwsKmsSigner`], so
+/// the secret key never leaves the service.
+///
+/// The approval is checked against the deploy hash, then appended to the deploy and returned.
+pub fn sign_deploy_remotely(
+    deploy: &mut Deploy,
+    signer: &dyn RemoteSigner,
+) -> Result<Approval, Error> {
+    remote_signer::sign(deploy, signer)
+}
+
+/// Returns whether `wasm-opt -Oz` will be run on the compiled contract.
+pub fn wasm_opt_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().run_wasm_opt
+}
+
+/// Sets whether `wasm-opt -Oz` will be run on the compiled contract.
+///
+/// Deploy cost is proportional to the size of the module, so this is recommended where `wasm-opt`
+/// (part of binaryen) is installed.
+pub fn set_wasm_opt_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().run_wasm_opt = enabled;
+}
+
+/// Returns `true` if the `wasm-opt` binary can be found.
+pub fn wasm_opt_available() -> bool {
+    smart_contract::wasm_opt_available()
+}
+
+/// Returns whether `wasm-strip` will be run on the compiled contract.
+pub fn wasm_strip_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().run_wasm_strip
+}
+
+/// Sets whether `wasm-strip` will be run on the compiled contract, removing its custom sections
+/// such as debug info.
+pub fn set_wasm_strip_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().run_wasm_strip = enabled;
+}
+
+/// Returns `true` if the `wasm-strip` binary can be found.
+pub fn wasm_strip_available() -> bool {
+    smart_contract::wasm_strip_available()
+}
+
+/// Returns whether the project will be scaffolded with cargo-casper, if it's installed.
+pub fn cargo_casper_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().use_cargo_casper
+}
+
+/// Sets whether the project will be scaffolded with cargo-casper, if it's installed.
+///
+/// The project then has the layout of cargo-casper's template, with the contract crate in a
+/// `contract` directory alongside the test crate and `Makefile`.  The template's files are kept
+/// where they aren't generated by this tool.  If `cargo casper` can't be run, the project is
+/// generated as usual.  Not used with a prebuilt session Wasm.
+pub fn set_cargo_casper_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().use_cargo_casper = enabled;
+}
+
+/// Returns `true` if `cargo casper` can be run.
+pub fn cargo_casper_available() -> bool {
+    cargo_casper::available()
+}
+
+/// Returns the CI service for which a workflow building the contract will be generated, if any.
+pub fn ci_provider() -> Option<CiProvider> {
+    SMART_CONTRACT.lock().unwrap().ci_provider
+}
+
+/// Sets the CI service for which a workflow building the contract will be generated.
+///
+/// The workflow builds the contract with the same toolchain and options as the tool, strips the
+/// Wasm and uploads it with its Blake2b hashes, so that teams committing the generated project get
+/// a reproducible build of the artifact to be signed.  Pass `None` to generate no workflow, which
+/// also removes one generated earlier.  Not used with a prebuilt session Wasm.
+pub fn set_ci_provider(ci_provider: Option<CiProvider>) {
+    SMART_CONTRACT.lock().unwrap().ci_provider = ci_provider;
+}
+
+/// Returns whether a git repository will be initialized in the generated project.
+pub fn git_repo_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().init_git_repo
+}
+
+/// Sets whether a git repository will be initialized in the generated project.
+///
+/// If enabled, a `.gitignore` is written and all generated files are committed with a message
+/// summarizing the configuration.
+pub fn set_git_repo_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().init_git_repo = enabled;
+}
+
+/// Returns whether only the project's source is generated, without compiling it.
+pub fn source_only_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().source_only
+}
+
+/// Sets whether only the project's source is generated, without compiling it.
+///
+/// Useful for auditing the generated code, or for building it elsewhere, e.g. on an air-gapped
+/// machine.  As cargo isn't run, the dependencies aren't pinned in a new `Cargo.lock`, and neither
+/// the build manifest of a reproducible build nor vendored dependencies are produced.
+pub fn set_source_only_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().source_only = enabled;
+}
+
+/// Returns the tools needed to compile the contract with the current options which aren't
+/// installed, e.g. the `wasm32-unknown-unknown` target for the configured toolchain.
+///
+/// Nothing is needed if only the source is generated or a prebuilt session Wasm is used, and the
+/// toolchain isn't needed on the host when compiling in Docker.
+pub fn missing_prerequisites() -> Vec<Prerequisite> {
+    let (host_toolchain, wasm_strip_needed) = {
+        let smart_contract = SMART_CONTRACT.lock().unwrap();
+        if smart_contract.source_only || smart_contract.prebuilt_session_wasm.is_some() {
+            return vec![];
+        }
+        let host_toolchain = if smart_contract.compile_backend == CompileBackend::Host {
+            Some(smart_contract.toolchain.clone())
+        } else {
+            None
+        };
+        (host_toolchain, smart_contract.run_wasm_strip)
+    };
+    prerequisites::missing(host_toolchain.as_deref(), wasm_strip_needed)
+}
+
+/// Installs `prerequisite` by running its [`Prerequisite::fix_command`], blocking until it
+/// finishes, which can take minutes for a toolchain.
+pub fn fix_prerequisite(prerequisite: &Prerequisite) -> Result<(), Error> {
+    prerequisites::fix(prerequisite).map_err(|inner| Error::FixPrerequisite {
+        prerequisite: prerequisite.to_string(),
+        inner,
+    })
+}
+
+/// Returns whether the contract will be built in reproducible-build mode.
+pub fn reproducible_build_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().reproducible_build
+}
+
+/// Sets whether the contract will be built in reproducible-build mode.
+///
+/// In this mode the dependencies pinned in the project's `Cargo.lock` are fetched, the contract is
+/// compiled with `--locked --offline` and a build manifest recording the toolchain and the hashes
+/// of the inputs and output is written to the project.
+pub fn set_reproducible_build_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().reproducible_build = enabled;
+}
+
+/// Returns whether the dependencies will be vendored into the project in reproducible-build mode.
+pub fn vendor_dependencies_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().vendor_dependencies
+}
+
+/// Sets whether the dependencies will be vendored into the project in reproducible-build mode.
+///
+/// Has no effect unless reproducible-build mode is enabled.
+pub fn set_vendor_dependencies_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().vendor_dependencies = enabled;
+}
+
+/// Returns the largest deploy, in bytes, accepted by the target network.
+pub fn max_deploy_size() -> u32 {
+    SMART_CONTRACT.lock().unwrap().max_deploy_size
+}
+
+/// Sets the largest deploy, in bytes, accepted by the target network, i.e. its chainspec's
+/// `deploys.max_deploy_size`.
+///
+/// After compiling, a [`CompileEvent::Warning`] is sent if the session Wasm is too large for its
+/// deploy to be accepted, or is unusually large and hence expensive.  Defaults to mainnet's limit.
+pub fn set_max_deploy_size(max_deploy_size: u32) {
+    SMART_CONTRACT.lock().unwrap().max_deploy_size = max_deploy_size;
+}
+
+/// Returns the compile timeout, or `None` if compilation may run indefinitely.
+pub fn compile_timeout() -> Option<Duration> {
+    SMART_CONTRACT.lock().unwrap().compile_timeout
+}
+
+/// Sets how long the cargo commands run to compile the contract may take in total.
+///
+/// Once it expires, cargo and all its child processes are killed and [`CompileEvent::TimedOut`] is
+/// sent.  With the Docker backend, only the `docker run` client is killed; the container stops
+/// when its build does.  Defaults to 30 minutes; pass `None` to allow compilation to run
+/// indefinitely.
+pub fn set_compile_timeout(timeout: Option<Duration>) {
+    SMART_CONTRACT.lock().unwrap().compile_timeout = timeout;
+}
+
+/// Returns how the contract will be compiled.
+pub fn compile_backend() -> CompileBackend {
+    SMART_CONTRACT.lock().unwrap().compile_backend.clone()
+}
+
+/// Sets how the contract will be compiled.
+pub fn set_compile_backend(compile_backend: CompileBackend) -> Result<(), Error> {
+    compile_backend.validate()?;
+    SMART_CONTRACT.lock().unwrap().compile_backend = compile_backend;
+    Ok(())
+}
+
+/// Returns where cargo writes the build output of the generated projects.
+///
+/// Defaults to [`default_shared_target_dir`] if there is one.
+pub fn target_dir_location() -> TargetDirLocation {
+    SMART_CONTRACT.lock().unwrap().target_dir_location.clone()
+}
+
+/// Returns the cargo target dir shared between all generated projects, if one is used.
+pub fn shared_target_dir() -> Option<PathBuf> {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .target_dir_location
+        .shared_target_dir()
+}
+
+/// Sets where cargo writes the build output of the generated projects.
+///
+/// Sharing a target dir means the contract's dependencies only need to be compiled once, rather
+/// than afresh for every generated project.  A relative [`TargetDirLocation::Shared`] path is
+/// resolved against the current directory.
+pub fn set_target_dir_location(location: TargetDirLocation) {
+    let location = match location {
+        TargetDirLocation::Shared { path } => TargetDirLocation::Shared {
+            path: dependencies::absolute_path(&path),
+        },
+        location => location,
+    };
+    SMART_CONTRACT.lock().unwrap().target_dir_location = location;
+}
+
+/// Returns the suggested shared cargo target dir, under the user's cache dir.
+pub fn default_shared_target_dir() -> Option<PathBuf> {
+    build_cache::default_shared_target_dir()
+}
+
+/// Removes the build output of the generated project and its test crate, and the shared target
+/// dir if one is used, returning the dirs removed.
+///
+/// This includes the compiled contract, so the project must be rebuilt before it can be deployed.
+/// A shared target dir is only removed if it looks like one cargo has written to.  Waits for any
+/// in-progress compilation to finish first.
+pub fn clean_build_artifacts() -> Result<Vec<PathBuf>, Error> {
+    SMART_CONTRACT.lock().unwrap().clean_build_artifacts()
+}
+
+/// Starts compiling a skeleton contract with the current dependencies and toolchain into the
+/// shared target dir in the background.
+///
+/// Once this has finished, generating the real contract only needs to compile the contract itself
+/// rather than all of its dependencies.  Returns `None` if no shared target dir is set or an
+/// identical pre-warm build has already been started; otherwise the build's progress is streamed
+/// through the returned channel.
+pub fn prewarm_dependencies() -> Result<Option<Receiver<CompileEvent>>, Error> {
+    SMART_CONTRACT.lock().unwrap().prewarm()
+}
+
+/// Returns whether `sccache` will be used as the rustc wrapper when compiling on the host.
+pub fn sccache_enabled() -> bool {
+    SMART_CONTRACT.lock().unwrap().use_sccache
+}
+
+/// Sets whether `sccache` will be used as the rustc wrapper when compiling on the host.
+pub fn set_sccache_enabled(enabled: bool) {
+    SMART_CONTRACT.lock().unwrap().use_sccache = enabled;
+}
+
+/// Returns `true` if the `sccache` binary can be found.
+pub fn sccache_available() -> bool {
+    build_cache::sccache_available()
+}
+
+/// Returns the source of the casper-contract dependency of the generated contract.
+pub fn casper_contract_dependency() -> DependencySource {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .casper_contract_dependency
+        .clone()
+}
+
+/// Returns the source of the casper-types dependency of the generated contract.
+pub fn casper_types_dependency() -> DependencySource {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .casper_types_dependency
+        .clone()
+}
+
+/// Returns the source of the casper-engine-test-support dependency of the generated test crate.
+pub fn casper_engine_test_support_dependency() -> DependencySource {
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .casper_engine_test_support_dependency
+        .clone()
+}
+
+/// Sets the source of the casper-engine-test-support dependency of the generated test crate.
+///
+/// Relative paths are resolved against the current directory.
+pub fn set_casper_engine_test_support_dependency(source: DependencySource) -> Result<(), Error> {
+    let source = match source {
+        DependencySource::Path(path) => DependencySource::Path(dependencies::absolute_path(&path)),
+        source => source,
+    };
+    source.validate("casper-engine-test-support")?;
+    SMART_CONTRACT
+        .lock()
+        .unwrap()
+        .casper_engine_test_support_dependency = source;
+    Ok(())
+}
+
+/// Returns the Rust toolchain used to compile the generated contract.
+pub fn toolchain() -> String {
+    SMART_CONTRACT.lock().unwrap().toolchain.clone()
+}
+
+/// Sets the sources of the casper-contract and casper-types dependencies and the Rust toolchain
+/// used to compile the generated contract.
+///
+/// Returns an error without changing the current values if the combination is invalid, e.g. the
+/// two crates come from different SDK releases or a nightly-only SDK is paired with a stable
+/// toolchain.  Relative paths are resolved against the current directory.
+pub fn set_dependencies_and_toolchain(
+    casper_contract: DependencySource,
+    casper_types: DependencySource,
+    toolchain: &str,
+) -> Result<(), Error> {
+    let make_absolute = |source: DependencySource| match source {
+        DependencySource::Path(path) => DependencySource::Path(dependencies::absolute_path(&path)),
+        source => source,
+    };
+    let casper_contract = make_absolute(casper_contract);
+    let casper_types = make_absolute(casper_types);
+    let toolchain = toolchain.trim();
+    dependencies::validate(&casper_contract, &casper_types, toolchain)?;
+
+    let mut smart_contract = SMART_CONTRACT.lock().unwrap();
+    smart_contract.casper_contract_dependency = casper_contract;
+    smart_contract.casper_types_dependency = casper_types;
+    smart_contract.toolchain = toolchain.to_string();
+    Ok(())
+}
+
+/// Returns the protocol profile whose SDK versions, toolchain and code generation target are all
+/// currently in use, if any.
+pub fn protocol_profile() -> Option<ProtocolProfile> {
+    let smart_contract = SMART_CONTRACT.lock().unwrap();
+    ProtocolProfile::ALL
+        .iter()
+        .copied()
+        .find(|profile| profile.is_applied_to(&smart_contract))
+}
+
+/// Sets the SDK versions, toolchain and code generation target to those of the given protocol
+/// profile.
+///
+/// The casper-engine-test-support dependency is left unchanged for profiles which don't generate a
+/// test crate.
+pub fn set_protocol_profile(profile: ProtocolProfile) {
+    profile.apply(&mut SMART_CONTRACT.lock().unwrap());
+}
+
+/// Returns the chain name, protocol version and last block of the node at `node_address`, or of the
+/// first to respond if several are given, via its `info_get_status` JSON-RPC endpoint.
+///
+/// Use [`NodeStatus::chain_name_warning`] to check deploys for the node specify the right chain
+/// name.  This runs `casper-client get-node-status`, and blocks until the node responds.
+pub fn node_status(node_address: &str) -> Result<NodeStatus, Error> {
+    node_status::fetch(node_address).map_err(|inner| Error::NodeStatus {
+        node_address: node_address.trim().to_string(),
+        inner,
+    })
+}
+
+/// Returns the protocol version, e.g. `1.5.2`, of the network the node at `node_address` is on.
+///
+/// Pass the result to [`ProtocolProfile::for_protocol_version`] to find the matching profile.  This
+/// runs `casper-client get-node-status`, so needs `casper-client` to be installed, and blocks until
+/// the node responds.
+pub fn detect_protocol_version(node_address: &str) -> Result<String, Error> {
+    protocol_profile::detect_protocol_version(node_address)
+}
+
+/// Returns the version of the Casper network the session code is generated for.
+pub fn codegen_target() -> CodegenTarget {
+    SMART_CONTRACT.lock().unwrap().codegen_target
+}
+
+/// Sets the version of the Casper network the session code is generated for.
+///
+/// The SDK crates must be from a matching release, e.g. version 5 or later for Casper 2.0; this is
+/// checked when the contract is generated.  [`CodegenTarget::default_dependency`] and
+/// [`CodegenTarget::default_toolchain`] give suitable values.  No test crate is generated when
+/// targeting Casper 2.0.
+pub fn set_codegen_target(codegen_target: CodegenTarget) {
+    SMART_CONTRACT.lock().unwrap().codegen_target = codegen_target;
+}
+
+/// Generates the Rust source for the contract and compiles it to Wasm.
+///
+/// The dependencies are pinned in the project's `Cargo.lock` before it's committed, so that later
+/// rebuilds use the same versions.  This needs `cargo`, and network access unless the project has
+/// been generated before with the same dependencies.
+///
+/// If a prebuilt session Wasm has been set, no compilation happens; that module and its runtime
+/// args are written to the project instead.  In source-only mode, the project is written but not
+/// compiled, and [`CompileEvent::SourceGenerated`] is sent immediately.
+///
+/// The output is streamed through the returned channel, along with [`CompileEvent::Progress`] as
+/// each phase of the compilation is reached, and [`CompileEvent::Succeeded`],
+/// [`CompileEvent::SourceGenerated`] or [`CompileEvent::Failed`] sent last.  The files are written
+/// before this returns, i.e. during [`CompilePhase::GeneratingFiles`].
+pub fn generate_smart_contract() -> Result<Receiver<CompileEvent>, Error> {
+    SMART_CONTRACT.lock().unwrap().create_and_compile()
+}
+
+/// Cancels the compilation started by the last call to [`generate_smart_contract`], if it's still
+/// running.
+///
+/// Cargo and all its child processes are killed, or the next step isn't started, and
+/// [`CompileEvent::Cancelled`] is sent.  As with the compile timeout, only the `docker run` client
+/// is killed with the Docker backend.
+pub fn cancel_compilation() {
+    SMART_CONTRACT.lock().unwrap().cancel_compilation()
+}
+
+/// Returns the level of the error or warning each line of the compile output belongs to, or `None`
+/// for lines outside them, e.g. so that the output can be filtered down to the diagnostics.
+///
+/// Each diagnostic is recognised by the prefix of its first line, such as `error[E0425]:`, and
+/// includes the lines which follow up to a blank line or a cargo status such as "Compiling".
+pub fn diagnostic_levels<S: AsRef<str>>(lines: &[S]) -> Vec<Option<DiagnosticLevel>> {
+    compile_output::diagnostic_levels(lines)
+}
+
+/// Returns whether the generated project has a test crate, which depends on the contract mode
+/// and the code generation target.
+pub fn has_test_crate() -> bool {
+    SMART_CONTRACT.lock().unwrap().has_tests()
+}
+
+/// Runs `cargo test` in the generated project's test crate.
+///
+/// Waits for any in-progress compilation to finish first.  The output is streamed through the
+/// returned channel, with [`TestEvent::Finished`] sent last.
+pub fn run_tests() -> Result<Receiver<TestEvent>, Error> {
+    SMART_CONTRACT.lock().unwrap().run_tests()
 }
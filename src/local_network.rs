@@ -0,0 +1,276 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use casper_types::{crypto::AsymmetricType, PublicKey, RuntimeArgs, U512};
+use serde_json::Value;
+
+use super::{
+    balance,
+    deploy::{self, DeployParams},
+    deploy_status::{self, ExecutionResult},
+    initial_transfer::InitialTransfer,
+    keystore,
+    verification::{self, ExpectedAccount, Verification},
+    DeployEvent, Error, LocalNetworkEvent,
+};
+
+/// The NCTL network used unless another is chosen, relative to the `$NCTL` dir.
+const DEFAULT_NETWORK_DIR: &str = "assets/net-1";
+
+/// The prefix of the name of an NCTL network's assets dir, followed by the network's index.
+const NETWORK_DIR_PREFIX: &str = "net-";
+
+/// The base of the JSON-RPC ports of NCTL nodes: node `n` of network `m` listens on
+/// `11000 + 100 * m + n`.
+const RPC_PORT_BASE: u32 = 11_000;
+
+/// The amount in motes transferred to the main account, enough for several attempts at the setup
+/// deploy.
+const FUNDING_AMOUNT: &str = "100000000000";
+
+/// The payment amount in motes of the setup deploy.
+const PAYMENT_AMOUNT: u64 = 10_000_000_000;
+
+/// How long to wait for the funding transfer to be executed.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(3 * 60);
+
+/// A rehearsal of the setup deploy on a local NCTL network.
+pub(super) struct Rehearsal {
+    /// The network's assets dir, e.g. `$NCTL/assets/net-1`.
+    pub(super) assets_dir: PathBuf,
+    /// The NCTL user whose key funds the main account, or 0 for the network's faucet.
+    pub(super) funding_user: u32,
+    pub(super) main_secret_key: PathBuf,
+    pub(super) wasm: PathBuf,
+    pub(super) session_args: RuntimeArgs,
+    pub(super) max_deploy_size: u32,
+    pub(super) expected: ExpectedAccount,
+}
+
+/// Returns the assets dir of the first network started by NCTL, if the `NCTL` environment variable
+/// is set.
+pub(super) fn default_assets_dir() -> Option<PathBuf> {
+    env::var_os("NCTL").map(|nctl| PathBuf::from(nctl).join(DEFAULT_NETWORK_DIR))
+}
+
+impl Rehearsal {
+    /// Funds the main account from the chosen NCTL key, sends the setup deploy and checks the
+    /// account ends up as configured.
+    ///
+    /// The progress is streamed through the returned channel, with
+    /// [`LocalNetworkEvent::Finished`] sent last.
+    pub(super) fn run(self) -> Result<Receiver<LocalNetworkEvent>, Error> {
+        let make_error = |inner: String| Error::LocalNetwork { inner };
+
+        let network = Network::new(&self.assets_dir).map_err(make_error)?;
+        let funding_key = network.secret_key(self.funding_user).map_err(make_error)?;
+        let main_secret_key =
+            keystore::read_secret_key(&self.main_secret_key).map_err(|error| {
+                make_error(format!(
+                    "failed to read {} as a secret key: {}",
+                    self.main_secret_key.display(),
+                    error
+                ))
+            })?;
+        let main_public_key = PublicKey::from(&main_secret_key);
+        match self.expected.associated_keys.first() {
+            Some((main_account_hash, _))
+                if *main_account_hash == main_public_key.to_account_hash() => {}
+            _ => {
+                return Err(make_error(
+                    "the secret key isn't that of the configured main account".to_string(),
+                ))
+            }
+        }
+        let transfer = InitialTransfer::new(
+            FUNDING_AMOUNT,
+            &funding_key.display().to_string(),
+            &main_public_key.to_hex(),
+        )?;
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let passed = match self.rehearse(&network, &transfer, &main_public_key, &sender) {
+                Ok(passed) => passed,
+                Err(error) => {
+                    send_output(&sender, format!("Error: {}", error));
+                    false
+                }
+            };
+            let _ = sender.send(LocalNetworkEvent::Finished { passed });
+        });
+        Ok(receiver)
+    }
+
+    /// Runs the rehearsal, returning whether the account ends up as configured, or an error if a
+    /// step couldn't be completed.
+    fn rehearse(
+        &self,
+        network: &Network,
+        transfer: &InitialTransfer,
+        main_public_key: &PublicKey,
+        sender: &Sender<LocalNetworkEvent>,
+    ) -> Result<bool, String> {
+        send_output(
+            sender,
+            format!(
+                "Funding the main account {} on {} via {}",
+                main_public_key.to_hex(),
+                network.chain_name,
+                network.node_address
+            ),
+        );
+        fund(network, transfer)?;
+        let balance = balance::main_purse_balance(&network.node_address, main_public_key)?;
+        send_output(sender, format!("The main account holds {} motes", balance));
+
+        let mut params = DeployParams::new(
+            &network.chain_name,
+            U512::from(PAYMENT_AMOUNT),
+            self.main_secret_key.clone(),
+        );
+        params.session_args = self.session_args.clone();
+        let deploy = deploy::build(
+            &self.wasm.display().to_string(),
+            &params,
+            self.max_deploy_size,
+        )
+        .map_err(|error| error.to_string())?;
+        let deploy_file = env::temp_dir()
+            .join("casper-multisig-tool")
+            .join(format!("local-network-deploy-{}.json", process::id()));
+        fs::create_dir_all(deploy_file.parent().unwrap()).map_err(|error| error.to_string())?;
+        deploy::save(&deploy, &deploy_file).map_err(|error| error.to_string())?;
+
+        send_output(sender, String::new());
+        send_output(sender, "Sending the setup deploy".to_string());
+        let events = deploy_status::send(deploy, &deploy_file, &network.node_address, None);
+        let mut executed = false;
+        for event in events {
+            match event {
+                DeployEvent::Output(line) => send_output(sender, line),
+                DeployEvent::Succeeded { cost, .. } => {
+                    send_output(
+                        sender,
+                        format!("The setup deploy succeeded, costing {} motes", cost),
+                    );
+                    executed = true;
+                }
+                DeployEvent::Failed {
+                    error_message,
+                    cause,
+                    ..
+                } => {
+                    send_output(
+                        sender,
+                        format!("The setup deploy failed: {}", error_message),
+                    );
+                    if let Some(cause) = cause {
+                        send_output(sender, format!("Cause: {}", cause));
+                    }
+                }
+                DeployEvent::NotExecuted { reason } => send_output(
+                    sender,
+                    format!("The setup deploy wasn't executed: {}", reason),
+                ),
+            }
+        }
+        let _ = fs::remove_file(&deploy_file);
+        if !executed {
+            return Ok(false);
+        }
+
+        send_output(sender, String::new());
+        send_output(
+            sender,
+            "Checking the account's keys and thresholds".to_string(),
+        );
+        let verification = Verification::new(verification::verify(
+            &network.node_address,
+            main_public_key,
+            &self.expected,
+        )?);
+        send_output(sender, verification.to_string());
+        Ok(verification == Verification::Verified)
+    }
+}
+
+/// The node and chain name of an NCTL network.
+struct Network {
+    assets_dir: PathBuf,
+    node_address: String,
+    chain_name: String,
+}
+
+impl Network {
+    /// Returns the first node of the network with the given assets dir, e.g. `$NCTL/assets/net-1`.
+    fn new(assets_dir: &Path) -> Result<Self, String> {
+        let index = assets_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(NETWORK_DIR_PREFIX))
+            .and_then(|index| index.parse::<u32>().ok())
+            .ok_or_else(|| {
+                format!(
+                    "{} isn't an NCTL network's assets dir, e.g. $NCTL/assets/net-1",
+                    assets_dir.display()
+                )
+            })?;
+        Ok(Network {
+            assets_dir: assets_dir.to_path_buf(),
+            node_address: format!("http://localhost:{}", RPC_PORT_BASE + 100 * index + 1),
+            chain_name: format!("casper-{}{}", NETWORK_DIR_PREFIX, index),
+        })
+    }
+
+    /// Returns the path of the secret key of the given user, or of the faucet for user 0.
+    fn secret_key(&self, user: u32) -> Result<PathBuf, String> {
+        let key_dir = if user == 0 {
+            self.assets_dir.join("faucet")
+        } else {
+            self.assets_dir.join("users").join(format!("user-{}", user))
+        };
+        let secret_key = key_dir.join("secret_key.pem");
+        if !secret_key.is_file() {
+            return Err(format!("{} doesn't exist", secret_key.display()));
+        }
+        Ok(secret_key)
+    }
+}
+
+/// Sends the funding transfer and waits for it to be executed successfully.
+fn fund(network: &Network, transfer: &InitialTransfer) -> Result<(), String> {
+    let output = transfer
+        .command(&network.node_address, &network.chain_name)
+        .output()
+        .map_err(|error| format!("failed to run casper-client: {}", error))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let response = serde_json::from_slice::<Value>(&output.stdout)
+        .map_err(|error| format!("failed to parse the transfer's response: {}", error))?;
+    let deploy_hash = response["result"]["deploy_hash"]
+        .as_str()
+        .ok_or("no deploy hash in the transfer's response")?;
+
+    match deploy_status::wait_for_result(deploy_hash, &network.node_address, TRANSFER_TIMEOUT)? {
+        ExecutionResult {
+            error_message: None,
+            ..
+        } => Ok(()),
+        ExecutionResult {
+            error_message: Some(error_message),
+            ..
+        } => Err(format!("the funding transfer failed: {}", error_message)),
+    }
+}
+
+fn send_output(sender: &Sender<LocalNetworkEvent>, line: String) {
+    let _ = sender.send(LocalNetworkEvent::Output(line));
+}
@@ -1,42 +1,65 @@
 use std::{
-    cmp, collections::HashMap, env, panic, path::PathBuf, thread, thread::JoinHandle,
-    time::Duration,
+    cmp,
+    collections::HashMap,
+    env, panic,
+    path::{Path, PathBuf},
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+    thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
+use once_cell::sync::OnceCell;
+
 use fltk::{
-    app::{self, App, Scheme},
-    button::{Button, CheckButton},
+    app::{self, App, MouseButton, Scheme},
+    browser::{Browser, MultiBrowser},
+    button::{Button, CheckButton, ToggleButton},
     dialog::{self, FileDialog, FileDialogOptions, FileDialogType},
-    enums::{Align, Color, Font, FrameType},
+    enums::{Align, CallbackTrigger, Color, ColorDepth, Event, Font, FrameType, Key, Shortcut},
     frame::Frame,
-    group::{Pack, PackType},
-    image::PngImage,
+    group::{Group, Pack, PackType, Scroll, ScrollType, Tabs},
+    image::{PngImage, RgbImage},
+    input::{Input, MultilineInput},
+    menu::{Choice, MenuBar, MenuFlag, MenuItem},
+    misc::{HelpView, Progress},
     output::Output,
-    prelude::{DisplayExt, GroupExt, InputExt, ValuatorExt, WidgetBase, WidgetExt, WindowExt},
-    text::{TextBuffer, TextDisplay},
+    prelude::{
+        BrowserExt, ButtonExt, DisplayExt, GroupExt, InputExt, MenuExt, ValuatorExt, WidgetBase,
+        WidgetExt, WindowExt,
+    },
+    text::{StyleTableEntry, TextBuffer, TextDisplay},
     valuator::ValueInput,
     window::Window,
 };
 
-use casper_types::account::MAX_ASSOCIATED_KEYS;
+use casper_multisig_tool::{
+    AccountState, AwsKmsSigner, BlockIdentifier, BulkKey, CiProvider, CodegenTarget,
+    CompileBackend, CompileEvent, CompilePhase, ConnectionSettings, ContractMode, DeployEvent,
+    DeployParams, DiagnosticLevel, DryRun, Identicon, KeyAlgorithm, KeyConfiguration, Language,
+    LocalNetworkEvent, NetworkProfile, OutputStream, Preferences, Prerequisite, ProtocolProfile,
+    RecentItem, RustToken, SchemeTemplate, SessionId, Severity, TargetDirLocation, TestEvent,
+    Theme, TransferEvent, VanityEvent, Verification, YubiKeySigner,
+};
+use casper_node::types::Deploy;
+use casper_types::{account::MAX_ASSOCIATED_KEYS, crypto::AsymmetricType, U512};
 
 // TODO:
-//  * key-management threshold max set to total weights of keys, excluding primary if it's to be
-//    deleted.  Need to also handle this in should_be_deleted checkbox callback.
 //  * validate values in lib::set_associated_keys_and_thresholds
-//  * provide lib API for validating and adjusting all input values (keys, weights, thresholds)
 //  * best way to stream stdout/stderr back from lib when running child process?  Provide callback
 //    which appends the data to a buffer?
 //  * use logging rather than println
 //  * remove unwraps
 //  * handle author in Cargo.toml of generated contracts
-//  * handle invalid contract names in `create()`
-//  * create test project to test the contract and execute it
 //  * run wasm-strip if available
 //  * readme with install instructions for dependencies
 //  * tooltips
 //  * help/instructions on main page - mention tooltips on account hash boxes
-//  * add button to run test
 
 fn set_panic_handler() {
     panic::set_hook(Box::new(move |panic_info| {
@@ -54,24 +77,129 @@ fn set_panic_handler() {
 }
 
 const TOOL_NAME: &str = "Casper Multisig Account Creation Tool";
+/// The size the main window opens at, if the screen is large enough.
 const WINDOW_WIDTH: i32 = 1800;
+const WINDOW_HEIGHT: i32 = 1200;
+/// The smallest the main window can be resized to, small enough for a 1366x768 screen.
+const MIN_WINDOW_WIDTH: i32 = 1280;
+const MIN_WINDOW_HEIGHT: i32 = 680;
 const BUTTON_WIDTH: i32 = 300;
 const BUTTON_HEIGHT: i32 = 40;
+/// Narrower than `BUTTON_WIDTH`, so that all the "Add public key" buttons fit in the window.
+const ADD_KEY_BUTTON_WIDTH: i32 = 240;
 const OUTPUT_ROW_HEIGHT: i32 = 40;
+/// The width of an associated key row's widgets other than the account hash and label, along with
+/// their spacing and the "Main account" label beside them.
+const ASSOCIATED_KEY_FIXED_WIDTH: i32 = 910;
+/// The size in pixels of each cell of an account hash's identicon.
+const IDENTICON_CELL_SIZE: usize = 5;
+const IDENTICON_SIZE: i32 = Identicon::size(IDENTICON_CELL_SIZE) as i32;
 const PADDING: i32 = 10;
-const BUTTON_COLOR: u32 = 0xd1d0ce;
+/// The least height of the Rust output kept visible by scrolling the associated keys instead.
+const MIN_RUST_OUTPUT_HEIGHT: i32 = 200;
+/// The width of each button beneath the Rust output, unless the output is too narrow.
+const RUST_OUTPUT_BUTTON_WIDTH: i32 = 220;
+const MENU_BAR_HEIGHT: i32 = 30;
+const TAB_BAR_HEIGHT: i32 = 30;
+/// The height of the menu bar and tab bar above each configuration tab's widgets.
+const HEADER_HEIGHT: i32 = MENU_BAR_HEIGHT + TAB_BAR_HEIGHT;
+/// The most changes to the associated keys and thresholds which can be undone.
+const MAX_UNDO_STEPS: usize = 100;
+const DOCUMENTATION_URL: &str = "https://github.com/Fraser999/casper-multisig-tool#readme";
+/// The in-app guide to associated keys, weights and thresholds, with a worked 2-of-3 example.
+const HELP_GUIDE: &str = include_str!("../help/guide.html");
+/// The style chars selecting the entries of `compile_output_style_table` for the plain output, the
+/// lines written to stderr by the commands run, and the tool's own status lines.
+const PLAIN_STYLE: char = 'A';
+const STDERR_STYLE: char = 'B';
+const STATUS_STYLE: char = 'C';
+const LIGHT_PALETTE: Palette = Palette {
+    background: 0xc0c0c0,
+    input_background: 0xffffff,
+    text: 0x000000,
+    button: 0xd1d0ce,
+    passed: 0xc3fdb8,
+    failed: 0xf5b7b1,
+    code_background: 0xe0e8ee,
+    code_keyword: 0x00008b,
+    code_comment: 0x5f6f5f,
+    code_literal: 0xa0461e,
+    main_key_background: 0xaed6f1,
+    warning_text: 0xff0000,
+    stderr_text: 0x7b2d8b,
+};
+const DARK_PALETTE: Palette = Palette {
+    background: 0x353535,
+    input_background: 0x202020,
+    text: 0xe6e6e6,
+    button: 0x4a4a4a,
+    passed: 0x2e6b34,
+    failed: 0x7b2f2a,
+    code_background: 0x1c232b,
+    code_keyword: 0x82aaff,
+    code_comment: 0x8a9a8a,
+    code_literal: 0xe5a66b,
+    main_key_background: 0x24476a,
+    warning_text: 0xff6b6b,
+    stderr_text: 0xc792ea,
+};
+const HIGH_CONTRAST_PALETTE: Palette = Palette {
+    background: 0x000000,
+    input_background: 0x000000,
+    text: 0xffffff,
+    button: 0x1a1a1a,
+    passed: 0x005f00,
+    failed: 0x8b0000,
+    code_background: 0x0d0d0d,
+    code_keyword: 0x00ffff,
+    code_comment: 0x9aff9a,
+    code_literal: 0xffff00,
+    main_key_background: 0x00006e,
+    warning_text: 0xffff00,
+    stderr_text: 0xff9aff,
+};
+/// The payment amount offered if the deploy's cost can't be estimated, e.g. as the Wasm can't be
+/// read.
+const DEFAULT_PAYMENT_AMOUNT: &str = "10000000000";
+/// How long before a deploy being signed expires the user is warned and offered to rebuild it.
+const EXPIRY_WARNING: Duration = Duration::from_secs(5 * 60);
+/// How often the configurations are autosaved, so they can be restored after an accidental close.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Whether the widgets are coloured from `DARK_PALETTE` rather than `LIGHT_PALETTE`.
+static DARK_THEME: AtomicBool = AtomicBool::new(false);
+/// Whether the widgets are coloured from `HIGH_CONTRAST_PALETTE`, overriding `DARK_THEME`.
+static HIGH_CONTRAST_THEME: AtomicBool = AtomicBool::new(false);
+/// Whether the user has been told an autosave failed, so the next failures aren't shown every
+/// `AUTOSAVE_INTERVAL`.
+static AUTOSAVE_FAILURE_SHOWN: AtomicBool = AtomicBool::new(false);
 
+type IdenticonWidget = Frame;
 type AccountHashWidget = Output;
+type CopyButton = Button;
+type QrCodeButton = Button;
+type LabelWidget = Input;
 type WeightWidget = ValueInput;
 type DeleteButton = Button;
+type ExplorerButton = Button;
+type AddressBookButton = Button;
+type MoveButton = Button;
 type MainKeyShouldBeDeletedWidget = CheckButton;
 
 /// The indices of each widget in the `AssociatedKeyPack` widget.
 #[repr(i32)]
 enum AssociatedKeyPackIndices {
+    Identicon,
     AccountHash,
+    Copy,
+    QrCode,
+    Label,
     Weight,
     Delete,
+    Explorer,
+    AddressBook,
+    MoveUp,
+    MoveDown,
     MainKeyShouldBeDeleted,
 }
 
@@ -79,21 +207,65 @@ enum AssociatedKeyPackIndices {
 #[repr(i32)]
 enum ActionThresholdsPackIndices {
     KeyManagementWeight,
+    KeyManagementHelp,
+    /// Leaves room for the deploy-execution threshold's label, which is drawn to its left.
+    Spacer,
     DeploymentWeight,
+    DeploymentHelp,
+}
+
+/// The indices of each widget in the `Scroll` widget holding the associated keys.
+#[repr(i32)]
+enum AssociatedKeysScrollIndices {
+    MainKeyFrame,
+    MainOutputPack,
 }
 
-/// The indices of each widget in the main window widget.
+/// The indices of each widget in a configuration tab's `Group` widget.
+///
+/// These are also the order the Tab key moves the focus through the widgets, so follow the layout:
+/// top to bottom, then left to right along the thresholds row, then the left column before the
+/// right.
 #[repr(i32)]
-enum WindowIndices {
+enum TabIndices {
     TopFrame,
     AddKeyButtonPack,
     MiddleFrame,
-    MainKeyFrame,
-    MainOutputPack,
+    AssociatedKeysScroll,
     BottomFrame,
     ActionThresholdsPack,
-    RustOutput,
+    BuildOptionsButton,
+    PrebuiltWasmCheckButton,
     GenerateButton,
+    RustOutput,
+    CopyRustButton,
+    SaveRustButton,
+    Issues,
+    ResultingAccount,
+}
+
+/// The colours of the widgets for a theme, as RGB values.
+struct Palette {
+    background: u32,
+    /// The background of inputs, outputs and text displays.
+    input_background: u32,
+    text: u32,
+    button: u32,
+    /// The colour of the "Generate smart contract" button and of successful results.
+    passed: u32,
+    /// The colour of failed results.
+    failed: u32,
+    /// The background of the generated code.
+    code_background: u32,
+    /// The highlighting of the generated code's keywords, comments and literals.
+    code_keyword: u32,
+    code_comment: u32,
+    code_literal: u32,
+    /// The background behind the main key.
+    main_key_background: u32,
+    warning_text: u32,
+    /// The colour of the lines a command run by the tool wrote to its stderr.
+    stderr_text: u32,
 }
 
 /// A wrapper for the horizontal `Pack` widget holding an individual associated key's widgets.
@@ -104,19 +276,59 @@ struct AssociatedKeyPack {
 
 impl AssociatedKeyPack {
     fn new(account_hash_value: &str, tooltip: &str, parent: MainOutputPack) -> Self {
-        let mut account_hash = AccountHashWidget::new(0, 0, 800, 0, None);
+        let mut identicon = IdenticonWidget::new(0, 0, IDENTICON_SIZE, 0, None);
+        identicon.set_tooltip(tr(
+            "The account hash's identicon, to tell keys apart at a glance",
+        ));
+        if let Some(image) = identicon_image(account_hash_value) {
+            identicon.set_image(Some(image));
+        }
+
+        let mut account_hash = AccountHashWidget::new(0, 0, 750, 0, None);
         account_hash.set_value(account_hash_value);
         account_hash.set_tooltip(tooltip);
         account_hash.set_text_font(Font::Courier);
-        account_hash.set_text_size(16);
+        account_hash.set_text_size(text_size());
         account_hash.show();
 
+        let mut copy_button = CopyButton::new(0, 0, 60, 40, tr("Copy"));
+        copy_button.set_color(Color::from_u32(palette().button));
+        copy_button.set_tooltip(tr("Copy the account hash to the clipboard"));
+        let account_hash_value_clone = account_hash_value.to_string();
+        copy_button.set_callback(move |_| copy_to_clipboard(&account_hash_value_clone));
+
+        let mut qr_code_button = QrCodeButton::new(0, 0, 50, 40, "QR");
+        qr_code_button.set_color(Color::from_u32(palette().button));
+        qr_code_button.set_tooltip(tr(
+            "Show the account hash, or the public key it was derived from, as a QR code to scan \
+            with a phone or hardware wallet",
+        ));
+        let account_hash_clone = account_hash.clone();
+        qr_code_button.set_callback(move |_| show_key_qr_code(&account_hash_clone));
+
+        let mut label = LabelWidget::new(0, 0, 200, 0, None);
+        label.set_value(&key_label(account_hash_value).unwrap_or_default());
+        label.set_tooltip(tr(
+            "Who the key belongs to, recorded in the generated sources and README, and saved with \
+            the configuration",
+        ));
+        label.set_text_size(text_size());
+        label.set_trigger(CallbackTrigger::Changed);
+        let account_hash_value_clone = account_hash_value.to_string();
+        label.set_callback(move |label| {
+            if let Err(error) =
+                casper_multisig_tool::set_key_label(&account_hash_value_clone, &label.value())
+            {
+                dialog::alert_default(&error.to_string());
+            }
+        });
+
         let mut weight = WeightWidget::new(100, 0, 50, 0, "");
         weight.set_value(1.0);
         weight.set_align(Align::Top);
-        weight.set_tooltip("The weight of the given key");
+        weight.set_tooltip(tr("The weight of the given key"));
         weight.set_text_font(Font::Courier);
-        weight.set_text_size(16);
+        weight.set_text_size(text_size());
         weight.set_minimum(0.0);
         weight.set_maximum(255.0);
         weight.set_soft(false);
@@ -131,7 +343,7 @@ impl AssociatedKeyPack {
 
         // let mut weight = Counter::new(100, 0, 100, 0, "Weight");
         // weight.set_value(1.0);
-        // weight.set_tooltip("The weight of the given key");
+        // weight.set_tooltip(tr("The weight of the given key"));
         // weight.set_minimum(0.0);
         // weight.set_maximum(255.0);
         // weight.set_type(CounterType::Simple);
@@ -141,16 +353,68 @@ impl AssociatedKeyPack {
 
         // The callback for the delete button will be set in the MainOutputPack, since it needs to
         // remove itself from that parent pack.
-        let mut delete_button = DeleteButton::new(0, 0, 100, 40, "Delete");
-        delete_button.set_color(Color::from_u32(BUTTON_COLOR));
+        let mut delete_button = DeleteButton::new(0, 0, 80, 40, tr("Delete"));
+        delete_button.set_color(Color::from_u32(palette().button));
+        delete_button.set_tooltip(tr("Remove the key from the associated keys"));
+
+        let mut explorer_button = ExplorerButton::new(0, 0, 80, 40, tr("Explorer"));
+        explorer_button.set_color(Color::from_u32(palette().button));
+        explorer_button.set_tooltip(tr(
+            "View the account on the selected network's block explorer",
+        ));
+        let account_hash_value = account_hash_value.to_string();
+        let account_hash_value_clone = account_hash_value.clone();
+        explorer_button.set_callback(move |_| {
+            let url = match casper_multisig_tool::account_explorer_url(&account_hash_value_clone) {
+                Some(url) => url,
+                None => {
+                    dialog::alert_default(tr(
+                        "Select a network with a block explorer under \"Build options\" first",
+                    ));
+                    return;
+                }
+            };
+            if let Err(error) = casper_multisig_tool::open_in_explorer(&url) {
+                dialog::alert_default(&error.to_string());
+            }
+        });
+
+        let mut address_book_button = AddressBookButton::new(0, 0, 80, 40, tr("Save"));
+        address_book_button.set_color(Color::from_u32(palette().button));
+        address_book_button.set_tooltip(tr(
+            "Save the key to the address book, to add it elsewhere later",
+        ));
+        address_book_button.set_callback(move |_| save_to_address_book(&account_hash_value));
+
+        // The callbacks for the move buttons will be set in the MainOutputPack, since they need to
+        // reorder the keys in that parent pack.
+        let mut move_up_button = MoveButton::new(0, 0, 40, 40, "@8->");
+        move_up_button.set_color(Color::from_u32(palette().button));
+        move_up_button.set_tooltip(tr(
+            "Move the key up.  Right-click the key to make it the main key straight away",
+        ));
+        let mut move_down_button = MoveButton::new(0, 0, 40, 40, "@2->");
+        move_down_button.set_color(Color::from_u32(palette().button));
+        move_down_button.set_tooltip(tr("Move the key down"));
 
         let mut pack = Pack::new(PADDING, PADDING, 1400, OUTPUT_ROW_HEIGHT, None);
         pack.end();
-        pack.set_spacing(30);
+        pack.set_spacing(20);
         pack.set_type(PackType::Horizontal);
+        pack.insert(&identicon, AssociatedKeyPackIndices::Identicon as i32);
         pack.insert(&account_hash, AssociatedKeyPackIndices::AccountHash as i32);
+        pack.insert(&copy_button, AssociatedKeyPackIndices::Copy as i32);
+        pack.insert(&qr_code_button, AssociatedKeyPackIndices::QrCode as i32);
+        pack.insert(&label, AssociatedKeyPackIndices::Label as i32);
         pack.insert(&weight, AssociatedKeyPackIndices::Weight as i32);
         pack.insert(&delete_button, AssociatedKeyPackIndices::Delete as i32);
+        pack.insert(&explorer_button, AssociatedKeyPackIndices::Explorer as i32);
+        pack.insert(
+            &address_book_button,
+            AssociatedKeyPackIndices::AddressBook as i32,
+        );
+        pack.insert(&move_up_button, AssociatedKeyPackIndices::MoveUp as i32);
+        pack.insert(&move_down_button, AssociatedKeyPackIndices::MoveDown as i32);
 
         AssociatedKeyPack { pack }
     }
@@ -164,6 +428,15 @@ impl AssociatedKeyPack {
         unsafe { AccountHashWidget::from_widget_ptr(account_hash.as_widget_ptr() as *mut _) }
     }
 
+    /// Returns the label widget.
+    fn label(&self) -> LabelWidget {
+        let label = self
+            .pack
+            .child(AssociatedKeyPackIndices::Label as i32)
+            .unwrap();
+        unsafe { LabelWidget::from_widget_ptr(label.as_widget_ptr() as *mut _) }
+    }
+
     /// Returns the weight widget.
     fn weight(&self) -> WeightWidget {
         let weight = self
@@ -182,6 +455,33 @@ impl AssociatedKeyPack {
         unsafe { DeleteButton::from_widget_ptr(delete_button.as_widget_ptr() as *mut _) }
     }
 
+    /// Returns the move up button widget.
+    fn move_up_button(&self) -> MoveButton {
+        let move_up_button = self
+            .pack
+            .child(AssociatedKeyPackIndices::MoveUp as i32)
+            .unwrap();
+        unsafe { MoveButton::from_widget_ptr(move_up_button.as_widget_ptr() as *mut _) }
+    }
+
+    /// Returns the move down button widget.
+    fn move_down_button(&self) -> MoveButton {
+        let move_down_button = self
+            .pack
+            .child(AssociatedKeyPackIndices::MoveDown as i32)
+            .unwrap();
+        unsafe { MoveButton::from_widget_ptr(move_down_button.as_widget_ptr() as *mut _) }
+    }
+
+    /// Selects the key by giving its account hash the keyboard focus, with the hash highlighted.
+    fn select(&self) {
+        let mut account_hash = self.account_hash();
+        let _ = account_hash.take_focus();
+        let _ = account_hash.set_position(account_hash.value().len() as i32);
+        let _ = account_hash.set_mark(0);
+        account_hash.redraw();
+    }
+
     /// Returns the "main key should be deleted" widget.
     fn main_key_should_be_deleted(&self) -> Option<MainKeyShouldBeDeletedWidget> {
         let should_be_deleted = self
@@ -203,16 +503,17 @@ struct ActionThresholdsPack {
 
 impl ActionThresholdsPack {
     fn new(parent: MainOutputPack) -> Self {
-        let mut key_management_weight = WeightWidget::new(0, 0, 50, 40, "Key-management threshold");
+        let mut key_management_weight =
+            WeightWidget::new(0, 0, 50, 40, tr("Key-management threshold"));
         key_management_weight.set_value(1.0);
         key_management_weight.set_align(Align::Left);
-        key_management_weight.set_tooltip(
+        key_management_weight.set_tooltip(tr(
             "The minimum total weight of signatories required to modify the associated keys.\n\n\
         Cannot exceed the total weights of all keys, excluding the main key if it is set to be \
         deleted after account creation",
-        );
+        ));
         key_management_weight.set_text_font(Font::Courier);
-        key_management_weight.set_text_size(16);
+        key_management_weight.set_text_size(text_size());
         key_management_weight.set_minimum(1.0);
         key_management_weight.set_maximum(255.0);
         key_management_weight.set_bounds(1.0, 255.0);
@@ -220,18 +521,19 @@ impl ActionThresholdsPack {
         key_management_weight.set_step(1.0, 1);
         key_management_weight.show();
 
-        let mut deployment_weight = WeightWidget::new(300, 0, 50, 40, "Deploy-execution threshold");
+        let mut deployment_weight =
+            WeightWidget::new(300, 0, 50, 40, tr("Deploy-execution threshold"));
         deployment_weight.set_value(1.0);
         deployment_weight.set_align(Align::Left);
-        deployment_weight.set_tooltip(
-            "The minimum total weight of signatories required to execute a deploy.\n\nCannot exceed \
-        the key-execution threshold",
-        );
+        deployment_weight.set_tooltip(tr(
+            "The minimum total weight of signatories required to execute a deploy.\n\nCannot \
+            exceed the key-execution threshold",
+        ));
         deployment_weight.set_text_font(Font::Courier);
-        deployment_weight.set_text_size(16);
+        deployment_weight.set_text_size(text_size());
         deployment_weight.set_bounds(1.0, 255.0);
         deployment_weight.set_minimum(1.0);
-        deployment_weight.set_maximum(1.0);
+        deployment_weight.set_maximum(255.0);
         deployment_weight.set_soft(false);
         deployment_weight.set_step(1.0, 1);
         deployment_weight.show();
@@ -244,23 +546,31 @@ impl ActionThresholdsPack {
             parent_clone.redraw_window();
         });
 
-        let mut deployment_weight_clone = deployment_weight.clone();
+        // A deploy-execution threshold above the key-management threshold is left as entered, and
+        // reported in the issues panel, rather than silently lowered.
         key_management_weight.set_callback(move |weight| {
             if weight.value() > weight.maximum() {
                 weight.set_value(weight.maximum());
             }
-            if weight.value() > weight.maximum() {
-                weight.set_value(weight.maximum());
-            }
-            deployment_weight_clone.set_maximum(weight.value());
-            if weight.value() < deployment_weight_clone.value() {
-                deployment_weight_clone.set_value(weight.value());
-            }
             parent.redraw_window();
         });
 
+        let new_help_button = || {
+            let mut help_button = Button::new(0, 0, 40, 40, "?");
+            help_button.set_color(Color::from_u32(palette().button));
+            help_button.set_tooltip(tr(
+                "Explain the action thresholds, with an example of an account needing two of \
+                three signers",
+            ));
+            help_button.set_callback(|_| show_help_window(Some("thresholds")));
+            help_button
+        };
+        let key_management_help = new_help_button();
+        let deployment_help = new_help_button();
+        let spacer = Frame::new(0, 0, 270, 40, None);
+
         let mut pack = Pack::new(250, PADDING + 20, 650, BUTTON_HEIGHT, None);
-        pack.set_spacing(300);
+        pack.set_spacing(10);
         pack.set_type(PackType::Horizontal);
         pack.end();
 
@@ -268,10 +578,19 @@ impl ActionThresholdsPack {
             &key_management_weight,
             ActionThresholdsPackIndices::KeyManagementWeight as i32,
         );
+        pack.insert(
+            &key_management_help,
+            ActionThresholdsPackIndices::KeyManagementHelp as i32,
+        );
+        pack.insert(&spacer, ActionThresholdsPackIndices::Spacer as i32);
         pack.insert(
             &deployment_weight,
             ActionThresholdsPackIndices::DeploymentWeight as i32,
         );
+        pack.insert(
+            &deployment_help,
+            ActionThresholdsPackIndices::DeploymentHelp as i32,
+        );
 
         ActionThresholdsPack { pack }
     }
@@ -295,6 +614,52 @@ impl ActionThresholdsPack {
     }
 }
 
+/// The configurations of the associated keys and thresholds which can be undone or redone.
+///
+/// Key labels aren't part of the history.  A label is stored as each character is typed, so
+/// recording its edits would fill the history one character at a time, and its text field undoes
+/// typing itself.  As labels are kept by account hash, a key restored by undo gets its label back.
+#[derive(Default)]
+struct History {
+    undo: Vec<KeyConfiguration>,
+    redo: Vec<KeyConfiguration>,
+    current: KeyConfiguration,
+    /// Set while a configuration is being applied, so the intermediate states aren't recorded.
+    paused: bool,
+}
+
+impl History {
+    /// Records `configuration` as the current one if it differs, discarding any redoable ones.
+    fn record(&mut self, mut configuration: KeyConfiguration) {
+        configuration.key_labels.clear();
+        if self.paused || configuration == self.current {
+            return;
+        }
+        self.undo
+            .push(std::mem::replace(&mut self.current, configuration));
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Returns the configuration before the current one, if there is one, making it current.
+    fn undo(&mut self) -> Option<KeyConfiguration> {
+        let previous = self.undo.pop()?;
+        self.redo
+            .push(std::mem::replace(&mut self.current, previous.clone()));
+        Some(previous)
+    }
+
+    /// Returns the configuration last undone, if there is one, making it current.
+    fn redo(&mut self) -> Option<KeyConfiguration> {
+        let next = self.redo.pop()?;
+        self.undo
+            .push(std::mem::replace(&mut self.current, next.clone()));
+        Some(next)
+    }
+}
+
 /// A wrapper for the vertical `Pack` widget holding all the individual associated key `Pack`s.
 #[derive(Clone)]
 struct MainOutputPack {
@@ -302,7 +667,14 @@ struct MainOutputPack {
     add_public_key_from_file_button: Button,
     add_public_key_from_hex_button: Button,
     add_account_hash_button: Button,
+    add_device_key_button: Button,
+    add_vanity_key_button: Button,
+    add_address_book_key_button: Button,
     rust_output_buffer: TextBuffer,
+    history: Arc<Mutex<History>>,
+    /// The tooltips indicating the origin of each account hash added this session, so they can be
+    /// restored when a key is re-added by undoing its removal.
+    tooltips: Arc<Mutex<HashMap<String, String>>>,
 }
 
 impl MainOutputPack {
@@ -310,9 +682,12 @@ impl MainOutputPack {
         add_public_key_from_file_button: Button,
         add_public_key_from_hex_button: Button,
         add_account_hash_button: Button,
+        add_device_key_button: Button,
+        add_vanity_key_button: Button,
+        add_address_book_key_button: Button,
         rust_output_buffer: TextBuffer,
     ) -> Self {
-        let mut pack = Pack::new(20, 180, 1460, 0, None);
+        let mut pack = Pack::new(20, 180 + HEADER_HEIGHT, 1460, 0, None);
         pack.set_spacing(10);
         pack.end();
         MainOutputPack {
@@ -320,7 +695,12 @@ impl MainOutputPack {
             add_public_key_from_file_button,
             add_public_key_from_hex_button,
             add_account_hash_button,
+            add_device_key_button,
+            add_vanity_key_button,
+            add_address_book_key_button,
             rust_output_buffer,
+            history: Arc::new(Mutex::new(History::default())),
+            tooltips: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -329,39 +709,98 @@ impl MainOutputPack {
         self.pack.window().unwrap()
     }
 
+    /// Returns the `Group` widget of the configuration tab holding `self`.
+    fn tab(&self) -> Box<dyn GroupExt> {
+        self.scroll().parent().unwrap()
+    }
+
+    /// Returns the `Scroll` widget (holding `self` and the main key frame) widget.
+    fn scroll(&self) -> Scroll {
+        let scroll = self.pack.parent().unwrap();
+        unsafe { Scroll::from_widget_ptr(scroll.as_widget_ptr() as *mut _) }
+    }
+
+    /// Returns the top frame (surrounding the add key buttons) widget.
+    fn top_frame(&self) -> Box<dyn WidgetExt> {
+        self.tab().child(TabIndices::TopFrame as i32).unwrap()
+    }
+
+    /// Returns the horizontal pack widget holding the add key buttons.
+    fn add_key_button_pack(&self) -> Box<dyn GroupExt> {
+        self.tab()
+            .child(TabIndices::AddKeyButtonPack as i32)
+            .unwrap()
+            .as_group()
+            .unwrap()
+    }
+
+    /// Returns the "Scan QR code" button widget, the second last of the add key buttons.
+    fn add_scanned_key_button(&self) -> Box<dyn WidgetExt> {
+        let add_key_button_pack = self.add_key_button_pack();
+        add_key_button_pack
+            .child(add_key_button_pack.children() - 2)
+            .unwrap()
+    }
+
+    /// Returns the "Paste key" button widget, the last of the add key buttons.
+    fn add_pasted_key_button(&self) -> Box<dyn WidgetExt> {
+        let add_key_button_pack = self.add_key_button_pack();
+        add_key_button_pack
+            .child(add_key_button_pack.children() - 1)
+            .unwrap()
+    }
+
     /// Returns the middle frame (surrounding the main output pack) widget.
     fn middle_frame(&self) -> Box<dyn WidgetExt> {
-        self.window()
-            .child(WindowIndices::MiddleFrame as i32)
-            .unwrap()
+        self.tab().child(TabIndices::MiddleFrame as i32).unwrap()
     }
 
     /// Returns the main key frame (highlighting the main key) widget.
     fn main_key_frame(&self) -> Box<dyn WidgetExt> {
-        self.window()
-            .child(WindowIndices::MainKeyFrame as i32)
+        self.scroll()
+            .child(AssociatedKeysScrollIndices::MainKeyFrame as i32)
             .unwrap()
     }
 
     /// Returns the bottom frame (surrounding the action thresholds) widget.
     fn bottom_frame(&self) -> Box<dyn WidgetExt> {
-        self.window()
-            .child(WindowIndices::BottomFrame as i32)
-            .unwrap()
+        self.tab().child(TabIndices::BottomFrame as i32).unwrap()
     }
 
     /// Returns the Rust output TextDisplay widget.
-    fn rust_output_text_display(&self) -> Box<dyn WidgetExt> {
-        self.window()
-            .child(WindowIndices::RustOutput as i32)
-            .unwrap()
+    fn rust_output_text_display(&self) -> TextDisplay {
+        let rust_output = self.tab().child(TabIndices::RustOutput as i32).unwrap();
+        unsafe { TextDisplay::from_widget_ptr(rust_output.as_widget_ptr() as *mut _) }
+    }
+
+    /// Returns the resulting account Browser widget.
+    fn resulting_account_browser(&self) -> Browser {
+        let browser = self
+            .tab()
+            .child(TabIndices::ResultingAccount as i32)
+            .unwrap();
+        unsafe { Browser::from_widget_ptr(browser.as_widget_ptr() as *mut _) }
+    }
+
+    /// Returns the validation issues Browser widget.
+    fn issues_browser(&self) -> Browser {
+        let browser = self.tab().child(TabIndices::Issues as i32).unwrap();
+        unsafe { Browser::from_widget_ptr(browser.as_widget_ptr() as *mut _) }
+    }
+
+    /// Returns the "Copy Rust to clipboard" and "Save main.rs as..." button widgets.
+    fn rust_output_buttons(&self) -> [Box<dyn WidgetExt>; 2] {
+        [
+            self.tab().child(TabIndices::CopyRustButton as i32).unwrap(),
+            self.tab().child(TabIndices::SaveRustButton as i32).unwrap(),
+        ]
     }
 
     /// Returns the action thresholds pack widget.
     fn action_thresholds_pack(&self) -> ActionThresholdsPack {
         let action_thresholds_pack = self
-            .window()
-            .child(WindowIndices::ActionThresholdsPack as i32)
+            .tab()
+            .child(TabIndices::ActionThresholdsPack as i32)
             .unwrap();
         let pack =
             unsafe { Pack::from_widget_ptr(action_thresholds_pack.as_widget_ptr() as *mut _) };
@@ -370,18 +809,43 @@ impl MainOutputPack {
 
     /// Returns the "Generate smart contract" button widget.
     fn generate_smart_contract_button(&self) -> Box<dyn WidgetExt> {
-        self.window()
-            .child(WindowIndices::GenerateButton as i32)
+        self.tab().child(TabIndices::GenerateButton as i32).unwrap()
+    }
+
+    /// Returns the "Use prebuilt session Wasm" checkbox widget.
+    fn prebuilt_wasm_check_button(&self) -> Box<dyn WidgetExt> {
+        self.tab()
+            .child(TabIndices::PrebuiltWasmCheckButton as i32)
+            .unwrap()
+    }
+
+    /// Returns the "Build options" button widget.
+    fn build_options_button(&self) -> Box<dyn WidgetExt> {
+        self.tab()
+            .child(TabIndices::BuildOptionsButton as i32)
             .unwrap()
     }
 
     /// Returns the main key pack (the first child of `self`) widget.
     fn main_key_pack(&self) -> Option<AssociatedKeyPack> {
-        let main_key_pack = self.pack.child(0).and_then(|child| child.as_group())?;
-        let pack = unsafe { Pack::from_widget_ptr(main_key_pack.as_widget_ptr() as *mut _) };
+        self.key_pack(0)
+    }
+
+    /// Returns the associated key pack at `index`, the main key's being at 0.
+    fn key_pack(&self, index: i32) -> Option<AssociatedKeyPack> {
+        let key_pack = self.pack.child(index).and_then(|child| child.as_group())?;
+        let pack = unsafe { Pack::from_widget_ptr(key_pack.as_widget_ptr() as *mut _) };
         Some(AssociatedKeyPack { pack })
     }
 
+    /// Returns the selected key's pack, i.e. the one holding the widget with the keyboard focus.
+    fn selected_key_pack(&self) -> Option<AssociatedKeyPack> {
+        let focus = app::focus()?;
+        (0..self.pack.children())
+            .filter_map(|index| self.key_pack(index))
+            .find(|key_pack| focus.inside(&key_pack.pack))
+    }
+
     /// Adds a new associated key `Pack`.
     fn add_associated_key(&self, account_hash_value: &str, tooltip: &str) {
         // TODO - use lib function to do excessive key count/duplicate key check
@@ -391,18 +855,22 @@ impl MainOutputPack {
             .collect::<HashMap<_, _>>();
 
         if associated_keys.len() >= MAX_ASSOCIATED_KEYS {
-            dialog::alert_default("Already have maximum number of associated keys");
+            dialog::alert_default(tr("Already have maximum number of associated keys"));
             return;
         }
 
         if associated_keys.contains_key(account_hash_value) {
-            dialog::alert_default(&format!(
+            dialog::alert_default(&casper_multisig_tool::translate_with(
                 "{} is already added to associated keys",
-                account_hash_value
+                &[account_hash_value],
             ));
             return;
         }
 
+        self.tooltips
+            .lock()
+            .unwrap()
+            .insert(account_hash_value.to_string(), tooltip.to_string());
         let associated_key_pack = AssociatedKeyPack::new(account_hash_value, tooltip, self.clone());
 
         self.pack.clone().add(&associated_key_pack.pack);
@@ -410,7 +878,61 @@ impl MainOutputPack {
             self.style_main_key();
         }
         self.redraw_window();
+        self.scroll_to_last_key();
 
+        let self_clone = self.clone();
+        associated_key_pack.pack.clone().handle(move |pack, event| {
+            if event != Event::Push {
+                return false;
+            }
+            if app::event_mouse_button() != MouseButton::Right {
+                // Clicking anywhere on the key selects it, unless the click gives a widget of the
+                // key the focus instead.
+                AssociatedKeyPack { pack: pack.clone() }.select();
+                return false;
+            }
+            if self_clone.pack.clone().find(pack) == 0 {
+                return false;
+            }
+            let menu = MenuItem::new(&[tr("Make main key")]);
+            if menu.popup(app::event_x(), app::event_y()).is_some() {
+                self_clone.make_main_key(pack);
+            }
+            true
+        });
+        let self_clone = self.clone();
+        let pack = associated_key_pack.pack.clone();
+        associated_key_pack.move_up_button().set_callback(move |_| {
+            self_clone.move_associated_key(&pack, false);
+        });
+        let self_clone = self.clone();
+        let pack = associated_key_pack.pack.clone();
+        associated_key_pack
+            .move_down_button()
+            .set_callback(move |_| {
+                self_clone.move_associated_key(&pack, true);
+            });
+        // The read-only account hash would swallow these keys, so handles the shortcuts under the
+        // "Keys" menu which act on the selected key itself.
+        let self_clone = self.clone();
+        let pack = associated_key_pack.pack.clone();
+        associated_key_pack.account_hash().handle(move |_, event| {
+            if event != Event::KeyDown {
+                return false;
+            }
+            let key = app::event_key();
+            let state = app::event_state();
+            if key == Key::Delete {
+                self_clone.remove_selected_key();
+            } else if state.contains(Shortcut::Alt) && (key == Key::Up || key == Key::Down) {
+                self_clone.move_associated_key(&pack, key == Key::Down);
+            } else if state.contains(Shortcut::Command) && key == Key::from_char('v') {
+                click(&mut *self_clone.add_pasted_key_button());
+            } else {
+                return false;
+            }
+            true
+        });
         let self_clone = self.clone();
         associated_key_pack.delete_button().set_callback(move |_| {
             self_clone.remove_associated_key(&associated_key_pack.pack);
@@ -422,17 +944,23 @@ impl MainOutputPack {
     fn style_main_key(&self) {
         let mut main_key_pack = self.main_key_pack().unwrap();
 
-        main_key_pack.account_hash().set_tooltip(&format!(
-            "This is the main associated key, used to create the account.\n\n{}",
-            main_key_pack.account_hash().tooltip().unwrap()
-        ));
+        main_key_pack
+            .account_hash()
+            .set_tooltip(&casper_multisig_tool::translate_with(
+                "This is the main associated key, used to create the account.\n\n{}",
+                &[&main_key_pack.account_hash().tooltip().unwrap()],
+            ));
 
         let mut main_key_pack_weight = main_key_pack.weight();
-        main_key_pack_weight.set_label("Weight\n ");
+        main_key_pack_weight.set_label(tr("Weight\n "));
 
         let mut should_be_deleted =
-            MainKeyShouldBeDeletedWidget::new(0, 0, 40, 40, "Should delete\nafter creation\n ");
+            MainKeyShouldBeDeletedWidget::new(0, 0, 40, 40, tr("Should delete\nafter creation\n "));
         should_be_deleted.set_align(Align::TopLeft);
+        should_be_deleted.set_tooltip(tr(
+            "Remove the main key from the account once the session code has run, leaving it \
+            controlled by the other keys",
+        ));
         let self_clone = self.clone();
         should_be_deleted.set_callback(move |widget| {
             if widget.is_checked() {
@@ -453,6 +981,36 @@ impl MainOutputPack {
         self.generate_smart_contract_button().activate();
     }
 
+    /// Undoes `style_main_key` on the current main key `Pack`, before another key becomes the main
+    /// one.
+    fn unstyle_main_key(&self) {
+        let mut main_key_pack = match self.main_key_pack() {
+            Some(main_key_pack) => main_key_pack,
+            None => return,
+        };
+
+        let mut account_hash = main_key_pack.account_hash();
+        if let Some(tooltip) = self.tooltips.lock().unwrap().get(&account_hash.value()) {
+            account_hash.set_tooltip(tooltip);
+        }
+
+        let mut weight = main_key_pack.weight();
+        weight.set_label("");
+        weight.activate();
+
+        if let Some(should_be_deleted) = main_key_pack.main_key_should_be_deleted() {
+            main_key_pack.pack.remove(&should_be_deleted);
+            app::delete_widget(should_be_deleted);
+        }
+    }
+
+    /// Scrolls the associated keys to the bottom, so the last one added is visible.
+    fn scroll_to_last_key(&self) {
+        let mut scroll = self.scroll();
+        let keys_height = (OUTPUT_ROW_HEIGHT + PADDING) * self.pack.children();
+        scroll.scroll_to(0, cmp::max(keys_height - scroll.height(), 0));
+    }
+
     /// Removes an associated key `Pack`.
     fn remove_associated_key(&self, associated_key_pack: &Pack) {
         let removed_index = self.pack.clone().find(associated_key_pack);
@@ -468,74 +1026,571 @@ impl MainOutputPack {
         self.redraw_window();
     }
 
+    /// Removes the selected key, then selects the key which takes its place, or the last one if it
+    /// was last.
+    fn remove_selected_key(&self) {
+        let selected = match self.selected_key_pack() {
+            Some(selected) => selected,
+            None => return,
+        };
+        let index = self.pack.clone().find(&selected.pack);
+        self.remove_associated_key(&selected.pack);
+        if let Some(next) = self.key_pack(cmp::min(index, self.pack.children() - 1)) {
+            next.select();
+        }
+    }
+
+    /// Moves the selected key down if `down` is `true`, or up otherwise.  It stays selected.
+    fn move_selected_key(&self, down: bool) {
+        if let Some(selected) = self.selected_key_pack() {
+            self.move_associated_key(&selected.pack, down);
+        }
+    }
+
+    /// Makes the selected key the main key.
+    fn make_selected_key_main(&self) {
+        if let Some(selected) = self.selected_key_pack() {
+            self.make_main_key(&selected.pack);
+        }
+    }
+
+    /// Shows the QR code of the selected key.
+    fn show_selected_key_qr_code(&self) {
+        if let Some(selected) = self.selected_key_pack() {
+            show_key_qr_code(&selected.account_hash());
+        }
+    }
+
+    /// Swaps an associated key `Pack` with the one below it if `down` is `true`, or above it
+    /// otherwise.  Moving a key to or from the top changes which is the main key.
+    ///
+    /// The "should delete after creation" setting stays with the main key.
+    fn move_associated_key(&self, associated_key_pack: &Pack, down: bool) {
+        let index = self.pack.clone().find(associated_key_pack);
+        let new_index = if down { index + 1 } else { index - 1 };
+        self.reorder_associated_key(associated_key_pack, new_index);
+    }
+
+    /// Moves an associated key `Pack` to the top, making it the main key.
+    ///
+    /// The "should delete after creation" setting stays with the main key.
+    fn make_main_key(&self, associated_key_pack: &Pack) {
+        self.reorder_associated_key(associated_key_pack, 0);
+    }
+
+    /// Moves an associated key `Pack` to `new_index`, restyling the main key if it changes.
+    fn reorder_associated_key(&self, associated_key_pack: &Pack, new_index: i32) {
+        let index = self.pack.clone().find(associated_key_pack);
+        if new_index == index || new_index < 0 || new_index >= self.pack.children() {
+            return;
+        }
+
+        let main_key_changes = index == 0 || new_index == 0;
+        let should_be_deleted = self.main_key_should_be_deleted();
+        if main_key_changes {
+            self.unstyle_main_key();
+        }
+        // The rows are reordered in place rather than rebuilt, as this is called from a callback of
+        // one of the row's own widgets.  `insert` takes the index before the widget is removed from
+        // its old position.
+        let insert_index = if new_index > index {
+            new_index + 1
+        } else {
+            new_index
+        };
+        self.pack.clone().insert(associated_key_pack, insert_index);
+        if main_key_changes {
+            self.style_main_key();
+            if let Some(mut should_be_deleted_widget) = self
+                .main_key_pack()
+                .and_then(|pack| pack.main_key_should_be_deleted())
+            {
+                should_be_deleted_widget.set_checked(should_be_deleted);
+                should_be_deleted_widget.do_callback();
+            }
+        }
+
+        self.redraw_window();
+    }
+
+    /// Adds `bulk_keys` to the associated keys as a single change, with their weights and labels.
+    fn add_bulk_keys(&self, bulk_keys: &[BulkKey]) {
+        let mut configuration = self.key_configuration();
+        let mut tooltips = self.tooltips.lock().unwrap();
+        for bulk_key in bulk_keys {
+            let account_hash = &bulk_key.account_hash;
+            configuration
+                .associated_keys
+                .push((account_hash.clone(), bulk_key.weight.unwrap_or(1)));
+            if let Some(label) = &bulk_key.label {
+                configuration
+                    .key_labels
+                    .retain(|(labelled, _)| labelled != account_hash);
+                configuration
+                    .key_labels
+                    .push((account_hash.clone(), label.clone()));
+            }
+            let tooltip = match &bulk_key.public_key {
+                Some(public_key) => casper_multisig_tool::translate_with(
+                    "Derived from public key {}, added in bulk",
+                    &[public_key],
+                ),
+                None => casper_multisig_tool::translate_with(
+                    "Derived from account hash {}, added in bulk",
+                    &[account_hash],
+                ),
+            };
+            tooltips.insert(account_hash.clone(), tooltip);
+        }
+        drop(tooltips);
+        self.apply_key_configuration(&configuration, "", true);
+    }
+
+    /// Returns the associated keys, weights and thresholds as configured in the window, along with
+    /// the key labels.
+    fn key_configuration(&self) -> KeyConfiguration {
+        let action_thresholds_pack = self.action_thresholds_pack();
+        KeyConfiguration {
+            associated_keys: self.associated_keys(),
+            primary_key_should_be_deleted: self.main_key_should_be_deleted(),
+            key_management_weight: action_thresholds_pack.key_management_weight().value() as u8,
+            deployment_weight: action_thresholds_pack.deployment_weight().value() as u8,
+            key_labels: casper_multisig_tool::key_labels(),
+        }
+    }
+
+    /// Replaces the associated keys, weights and thresholds in the window with those of
+    /// `configuration`.
+    ///
+    /// Keys not added earlier this session are given `tooltip`.  The key labels are only replaced
+    /// if `apply_labels` is `true`.
+    fn apply_key_configuration(
+        &self,
+        configuration: &KeyConfiguration,
+        tooltip: &str,
+        apply_labels: bool,
+    ) {
+        self.history.lock().unwrap().paused = true;
+
+        self.pack.clone().clear();
+        self.main_key_frame().hide();
+        self.generate_smart_contract_button().deactivate();
+        for (account_hash, _) in &configuration.associated_keys {
+            let tooltip = self
+                .tooltips
+                .lock()
+                .unwrap()
+                .get(account_hash)
+                .cloned()
+                .unwrap_or_else(|| tooltip.to_string());
+            self.add_associated_key(account_hash, &tooltip);
+        }
+        for (index, (_, weight)) in configuration.associated_keys.iter().enumerate() {
+            let child_pack = match self
+                .pack
+                .child(index as i32)
+                .and_then(|child| child.as_group())
+            {
+                Some(child_pack) => child_pack,
+                None => break,
+            };
+            let associated_key_pack = AssociatedKeyPack {
+                pack: unsafe { Pack::from_widget_ptr(child_pack.as_widget_ptr() as *mut _) },
+            };
+            associated_key_pack.weight().set_value(f64::from(*weight));
+        }
+        if let Some(mut should_be_deleted) = self
+            .main_key_pack()
+            .and_then(|pack| pack.main_key_should_be_deleted())
+        {
+            should_be_deleted.set_checked(configuration.primary_key_should_be_deleted);
+            should_be_deleted.do_callback();
+        }
+
+        let action_thresholds_pack = self.action_thresholds_pack();
+        action_thresholds_pack
+            .key_management_weight()
+            .set_value(f64::from(configuration.key_management_weight.max(1)));
+        action_thresholds_pack
+            .deployment_weight()
+            .set_value(f64::from(configuration.deployment_weight.max(1)));
+
+        if apply_labels {
+            if let Err(error) =
+                casper_multisig_tool::set_key_labels(configuration.key_labels.clone())
+            {
+                dialog::alert_default(&error.to_string());
+            }
+        }
+
+        self.history.lock().unwrap().paused = false;
+        self.redraw_window();
+    }
+
+    /// Restores the configuration before the last change, if there was one.
+    fn undo(&self) {
+        let previous = self.history.lock().unwrap().undo();
+        if let Some(previous) = previous {
+            self.apply_key_configuration(&previous, "Restored by undo", false);
+        }
+    }
+
+    /// Restores the configuration last undone, if there was one.
+    fn redo(&self) {
+        let next = self.history.lock().unwrap().redo();
+        if let Some(next) = next {
+            self.apply_key_configuration(&next, "Restored by redo", false);
+        }
+    }
+
     /// Redraws the main window.
     fn redraw_window(&self) {
         self.update_smart_contract();
+        self.update_resulting_account();
+        self.update_key_labels();
+        self.update_move_buttons();
+        self.history
+            .lock()
+            .unwrap()
+            .record(self.key_configuration());
 
-        let mut window = self.window();
-        let associated_keys_count = self.pack.children();
-
-        if associated_keys_count as usize >= MAX_ASSOCIATED_KEYS {
+        if self.pack.children() as usize >= MAX_ASSOCIATED_KEYS {
             self.add_public_key_from_file_button.clone().deactivate();
             self.add_public_key_from_hex_button.clone().deactivate();
             self.add_account_hash_button.clone().deactivate();
+            self.add_device_key_button.clone().deactivate();
+            self.add_vanity_key_button.clone().deactivate();
+            self.add_address_book_key_button.clone().deactivate();
         } else {
             self.add_public_key_from_file_button.clone().activate();
             self.add_public_key_from_hex_button.clone().activate();
             self.add_account_hash_button.clone().activate();
+            self.add_vanity_key_button.clone().activate();
+            self.add_address_book_key_button.clone().activate();
+            if casper_multisig_tool::ledger_supported() || casper_multisig_tool::yubikey_supported()
+            {
+                self.add_device_key_button.clone().activate();
+            }
+        }
+
+        self.layout();
+    }
+
+    /// Sizes and positions the tab's widgets to fit the main window, so that it can be resized or
+    /// maximized, and still fit on small screens.
+    fn layout(&self) {
+        let mut window = self.window();
+        let (window_width, window_height) = (window.width(), window.height());
+
+        let mut tab = self.tab();
+        tab.set_size(window_width, window_height - HEADER_HEIGHT);
+        if let Some(mut tabs) = tab.parent() {
+            tabs.set_size(window_width, window_height - MENU_BAR_HEIGHT);
+        }
+
+        // The add key buttons share the width of the top frame, their labels wrapping once narrow.
+        let mut top_frame = self.top_frame();
+        top_frame.set_size(window_width - (2 * PADDING), top_frame.height());
+        let mut add_key_button_pack = self.add_key_button_pack();
+        let add_key_button_count = add_key_button_pack.children();
+        let add_key_button_width = cmp::min(
+            ADD_KEY_BUTTON_WIDTH,
+            (window_width - (4 * PADDING) - (add_key_button_count - 1) * PADDING)
+                / add_key_button_count,
+        );
+        add_key_button_pack.set_size(window_width - (4 * PADDING), BUTTON_HEIGHT);
+        for index in 0..add_key_button_count {
+            if let Some(mut button) = add_key_button_pack.child(index) {
+                button.set_size(add_key_button_width, BUTTON_HEIGHT);
+            }
+        }
+
+        // Beyond the height which leaves room for the thresholds, the buttons and some of the Rust
+        // output, the associated keys are scrolled rather than pushing the rest out of the window.
+        let associated_keys_count = self.pack.children();
+        let keys_height = (OUTPUT_ROW_HEIGHT + PADDING) * associated_keys_count;
+        let max_keys_height = cmp::max(
+            window_height - MIN_RUST_OUTPUT_HEIGHT - BUTTON_HEIGHT - 330 - HEADER_HEIGHT,
+            OUTPUT_ROW_HEIGHT + PADDING,
+        );
+        let visible_keys_height = cmp::min(keys_height, max_keys_height);
+        let mut scroll = self.scroll();
+        scroll.set_size(window_width - 30, visible_keys_height);
+        if scroll.yposition() > keys_height - visible_keys_height {
+            scroll.scroll_to(0, keys_height - visible_keys_height);
+        }
+        let mut main_key_frame = self.main_key_frame();
+        let main_key_frame_width = window_width - 30 - app::scrollbar_size();
+        main_key_frame.set_size(main_key_frame_width, main_key_frame.height());
+
+        // The account hash and label take whatever width the fixed-size widgets of a row leave.
+        let flexible_width = main_key_frame_width - ASSOCIATED_KEY_FIXED_WIDTH;
+        let label_width = (flexible_width * 3 / 10).clamp(100, 200);
+        let account_hash_width = (flexible_width - label_width).clamp(200, 750);
+        for child_pack in self.pack.clone().into_iter() {
+            let child_pack = child_pack.as_group().unwrap();
+            if let Some(mut account_hash) =
+                child_pack.child(AssociatedKeyPackIndices::AccountHash as i32)
+            {
+                account_hash.set_size(account_hash_width, account_hash.height());
+            }
+            if let Some(mut label) = child_pack.child(AssociatedKeyPackIndices::Label as i32) {
+                label.set_size(label_width, label.height());
+            }
         }
 
         let mut middle_frame = self.middle_frame();
-        let middle_frame_height = ((OUTPUT_ROW_HEIGHT + PADDING) * associated_keys_count) + 60;
-        middle_frame.set_size(middle_frame.width(), middle_frame_height);
+        let middle_frame_height = visible_keys_height + 60;
+        middle_frame.set_size(window_width - (2 * PADDING), middle_frame_height);
 
-        self.bottom_frame()
-            .set_pos(self.bottom_frame().x(), middle_frame_height + 150);
+        self.bottom_frame().set_pos(
+            self.bottom_frame().x(),
+            middle_frame_height + 150 + HEADER_HEIGHT,
+        );
         self.action_thresholds_pack().pack.set_pos(
             self.action_thresholds_pack().pack.x(),
-            middle_frame_height + 190,
+            middle_frame_height + 190 + HEADER_HEIGHT,
         );
 
-        self.rust_output_text_display().set_pos(
-            PADDING,
-            self.bottom_frame().y() + self.bottom_frame().height() + PADDING,
+        // The buttons are right-aligned beside the thresholds, narrowing rather than overlapping
+        // them.
+        let bottom_frame_right = self.bottom_frame().x() + self.bottom_frame().width();
+        let button_width = cmp::min(
+            BUTTON_WIDTH,
+            (window_width - bottom_frame_right - (4 * PADDING)) / 3,
         );
+        let buttons_y = middle_frame_height + 200 + HEADER_HEIGHT;
+        let buttons = [
+            self.generate_smart_contract_button(),
+            self.prebuilt_wasm_check_button(),
+            self.build_options_button(),
+        ];
+        for (index, mut button) in (1..).zip(buttons) {
+            button.resize(
+                window_width - index * (PADDING + button_width),
+                buttons_y,
+                button_width,
+                BUTTON_HEIGHT,
+            );
+        }
 
-        self.generate_smart_contract_button().set_pos(
-            self.generate_smart_contract_button().x(),
-            middle_frame_height + 200,
+        // The Rust output fills the rest of the window, beside the issues and resulting account,
+        // with its buttons right-aligned beneath it.
+        let rust_output_y = self.bottom_frame().y() + self.bottom_frame().height() + PADDING;
+        let rust_output_height = window_height - rust_output_y - PADDING;
+        let resulting_account_width = ((window_width - (3 * PADDING)) / 3).clamp(480, 640);
+        let rust_output_width = window_width - (3 * PADDING) - resulting_account_width;
+        let rust_output_text_height = rust_output_height - BUTTON_HEIGHT - PADDING;
+        self.rust_output_text_display().resize(
+            PADDING,
+            rust_output_y,
+            rust_output_width,
+            rust_output_text_height,
         );
-
-        let (_screen_width, screen_height) = app::screen_size();
-        let rust_output_text_display_height =
-            cmp::min(screen_height as i32 - middle_frame_height - 300, 800);
-        self.rust_output_text_display().set_size(
-            self.rust_output_text_display().width(),
-            rust_output_text_display_height,
+        let rust_output_button_width =
+            cmp::min(RUST_OUTPUT_BUTTON_WIDTH, (rust_output_width - PADDING) / 2);
+        let rust_output_buttons_y = rust_output_y + rust_output_text_height + PADDING;
+        let rust_output_buttons = self.rust_output_buttons();
+        let rust_output_button_count = rust_output_buttons.len() as i32;
+        for (index, mut button) in (0..).zip(rust_output_buttons) {
+            button.resize(
+                (2 * PADDING) + rust_output_width
+                    - (rust_output_button_count - index) * (rust_output_button_width + PADDING),
+                rust_output_buttons_y,
+                rust_output_button_width,
+                BUTTON_HEIGHT,
+            );
+        }
+        let issues_height = rust_output_height * 2 / 5;
+        self.issues_browser().resize(
+            window_width - PADDING - resulting_account_width,
+            rust_output_y,
+            resulting_account_width,
+            issues_height,
         );
-
-        window.set_size(
-            window.width(),
-            middle_frame_height + rust_output_text_display_height + 260,
+        self.resulting_account_browser().resize(
+            window_width - PADDING - resulting_account_width,
+            rust_output_y + issues_height + PADDING,
+            resulting_account_width,
+            rust_output_height - issues_height - PADDING,
         );
+        // Listed once the panel is sized, as the issues are wrapped to its width.
+        self.update_issues();
+
         window.redraw();
     }
 
-    /// Returns the associated keys as a map of formatted account hashes to weights.
-    fn associated_keys(&self) -> Vec<(String, u8)> {
-        let mut associated_keys = Vec::new();
-        for child_pack in self.pack.clone().into_iter() {
-            let child_pack = child_pack.as_group().unwrap();
-            let associated_key_pack = AssociatedKeyPack {
-                pack: unsafe { Pack::from_widget_ptr(child_pack.as_widget_ptr() as *mut _) },
-            };
-
-            let account_hash = associated_key_pack.account_hash().value();
-            let weight = associated_key_pack.weight().value() as u8;
-            associated_keys.push((account_hash, weight));
+    /// Shows the associated keys and action thresholds the account is left with once set up, which
+    /// is easier to review than the generated code.
+    fn update_resulting_account(&self) {
+        let mut browser = self.resulting_account_browser();
+        browser.clear();
+        browser.add(&format!("@b{}", tr("Resulting account")));
+        if self.associated_keys().is_empty() {
+            browser.add(&format!(
+                "@i{}",
+                tr("Add keys to see the account they set up")
+            ));
+            return;
         }
-
-        associated_keys
+        let account = match casper_multisig_tool::resulting_account() {
+            Some(account) => account,
+            None => {
+                browser.add(&format!(
+                    "@i{}",
+                    tr("Depends on the account's keys before the deploy,")
+                ));
+                browser.add(&format!(
+                    "@i{}",
+                    tr("as the contract mode changes an existing account")
+                ));
+                return;
+            }
+        };
+        browser.add(&format!(
+            "@u{}\t@u{}\t@u{}",
+            tr("Key"),
+            tr("Account hash"),
+            tr("Weight")
+        ));
+        for (account_hash, weight) in &account.associated_keys {
+            let account_hash = account_hash.to_formatted_string();
+            // The "@." stops a label starting with '@' being taken as formatting.
+            browser.add(&format!(
+                "@.{}\t@.{}\t{}",
+                key_label(&account_hash).unwrap_or_default(),
+                abbreviated_account_hash(&account_hash),
+                weight
+            ));
+        }
+        browser.add("");
+        browser.add(&format!(
+            "{}\t\t{}",
+            tr("Key-management threshold"),
+            account.key_management_threshold
+        ));
+        browser.add(&format!(
+            "{}\t\t{}",
+            tr("Deploy-execution threshold"),
+            account.deployment_threshold
+        ));
+        browser.add(&format!(
+            "{}\t\t{}",
+            tr("Main key"),
+            if self.main_key_should_be_deleted() {
+                tr("Removed")
+            } else {
+                tr("Remains")
+            }
+        ));
+    }
+
+    /// Lists the errors and warnings found by the library's validation of the configuration, and
+    /// only allows the contract to be generated if there are no errors.
+    fn update_issues(&self) {
+        let mut browser = self.issues_browser();
+        browser.clear();
+        if self.associated_keys().is_empty() {
+            browser.add(&format!("@b{}", tr("No issues")));
+            browser.add(&format!("@i{}", tr("Add keys to check the configuration")));
+            return;
+        }
+        let issues = casper_multisig_tool::validate_configuration();
+        let errors = issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .count();
+        let warnings = issues.len() - errors;
+        browser.add(&format!(
+            "@b{}, {}",
+            casper_multisig_tool::translate_with(
+                if errors == 1 { "{} error" } else { "{} errors" },
+                &[&errors.to_string()]
+            ),
+            casper_multisig_tool::translate_with(
+                if warnings == 1 {
+                    "{} warning"
+                } else {
+                    "{} warnings"
+                },
+                &[&warnings.to_string()]
+            )
+        ));
+        // Roughly the most characters of the proportional font which fit across the panel.
+        let max_line_length =
+            (browser.width() - (2 * PADDING) - app::scrollbar_size()) * 9 / (5 * code_text_size());
+        for issue in &issues {
+            let color = match issue.severity {
+                Severity::Error => Color::from_u32(palette().warning_text),
+                Severity::Warning => Color::ForeGround,
+            };
+            for line in wrap_words(&issue.to_string(), max_line_length as usize) {
+                // The "@." stops any '@' in the message being taken as formatting.
+                browser.add(&format!("@C{}@.{}", color.bits(), line));
+            }
+        }
+        if errors == 0 {
+            self.generate_smart_contract_button().activate();
+        } else {
+            self.generate_smart_contract_button().deactivate();
+        }
+    }
+
+    /// Shows the current key labels in the associated keys' label widgets, as they can also be set
+    /// elsewhere, e.g. in the contract options or by opening a configuration.
+    fn update_key_labels(&self) {
+        for child_pack in self.pack.clone().into_iter() {
+            let child_pack = child_pack.as_group().unwrap();
+            let associated_key_pack = AssociatedKeyPack {
+                pack: unsafe { Pack::from_widget_ptr(child_pack.as_widget_ptr() as *mut _) },
+            };
+            let label = key_label(&associated_key_pack.account_hash().value()).unwrap_or_default();
+            let label_widget = associated_key_pack.label();
+            if label_widget.value().trim() != label {
+                label_widget.set_value(&label);
+            }
+        }
+    }
+
+    /// Deactivates the move up button of the first associated key and the move down button of the
+    /// last, activating the rest.
+    fn update_move_buttons(&self) {
+        let last_index = self.pack.children() - 1;
+        for (index, child_pack) in self.pack.clone().into_iter().enumerate() {
+            let child_pack = child_pack.as_group().unwrap();
+            let associated_key_pack = AssociatedKeyPack {
+                pack: unsafe { Pack::from_widget_ptr(child_pack.as_widget_ptr() as *mut _) },
+            };
+            let mut move_up_button = associated_key_pack.move_up_button();
+            let mut move_down_button = associated_key_pack.move_down_button();
+            if index == 0 {
+                move_up_button.deactivate();
+            } else {
+                move_up_button.activate();
+            }
+            if index as i32 == last_index {
+                move_down_button.deactivate();
+            } else {
+                move_down_button.activate();
+            }
+        }
+    }
+
+    /// Returns the associated keys as a map of formatted account hashes to weights.
+    fn associated_keys(&self) -> Vec<(String, u8)> {
+        let mut associated_keys = Vec::new();
+        for child_pack in self.pack.clone().into_iter() {
+            let child_pack = child_pack.as_group().unwrap();
+            let associated_key_pack = AssociatedKeyPack {
+                pack: unsafe { Pack::from_widget_ptr(child_pack.as_widget_ptr() as *mut _) },
+            };
+
+            let account_hash = associated_key_pack.account_hash().value();
+            let weight = associated_key_pack.weight().value() as u8;
+            associated_keys.push((account_hash, weight));
+        }
+
+        associated_keys
     }
 
     fn main_key_should_be_deleted(&self) -> bool {
@@ -562,16 +1617,71 @@ impl MainOutputPack {
                 key_management_weight,
                 deployment_weight,
             ) {
-                dialog::alert_default(&format!("Error setting associated keys: {}", error));
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "Error setting associated keys: {}",
+                    &[&error.to_string()],
+                ));
             }
 
-            casper_multisig_tool::main_rs_contents()
+            match casper_multisig_tool::prebuilt_session_wasm() {
+                Some(wasm_path) => format!(
+                    "// Prebuilt session Wasm: {}\n//\n// Session args:\n{}\n",
+                    wasm_path.display(),
+                    casper_multisig_tool::casper_client_session_args()
+                        .iter()
+                        .map(|arg| format!("//   {}", arg))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                ),
+                None => match casper_multisig_tool::contract_sources() {
+                    // Show the session code of every deploy when more than one is needed.
+                    Ok(sources) if sources.len() > 1 => sources
+                        .iter()
+                        .map(|(path, contents)| format!("// {}\n\n{}", path.display(), contents))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    _ => casper_multisig_tool::main_rs_contents(),
+                },
+            }
         };
 
         self.rust_output_buffer.clone().set_text(&main_rs_contents);
+        self.highlight_rust_output(&main_rs_contents);
+        for mut button in self.rust_output_buttons() {
+            if main_rs_contents.is_empty() {
+                button.deactivate();
+            } else {
+                button.activate();
+            }
+        }
+    }
+
+    /// Highlights the keywords, comments and literals of `contents`, the Rust output's text.
+    fn highlight_rust_output(&self, contents: &str) {
+        // Each byte of the text is styled by the entry of `rust_style_table` its style char selects.
+        let mut styles = vec![b'A'; contents.len()];
+        for (range, token) in casper_multisig_tool::highlight_rust(contents) {
+            let style = match token {
+                RustToken::Keyword => b'B',
+                RustToken::Comment => b'C',
+                RustToken::Literal => b'D',
+            };
+            styles[range].fill(style);
+        }
+        let mut rust_output = self.rust_output_text_display();
+        let mut style_buffer = rust_output
+            .style_buffer()
+            .unwrap_or_else(TextBuffer::default);
+        style_buffer.set_text(&String::from_utf8_lossy(&styles));
+        rust_output.set_highlight_data(style_buffer, rust_style_table());
     }
 
     fn generate_smart_contract(&self) -> Option<JoinHandle<()>> {
+        let missing = casper_multisig_tool::missing_prerequisites();
+        if !missing.is_empty() && !show_prerequisites_window(missing, true) {
+            return None;
+        }
+
         let mut file_dialog = FileDialog::new(FileDialogType::BrowseDir);
         if let Some(start_dir) = get_current_or_default_project_path() {
             let _ =
@@ -579,10 +1689,10 @@ impl MainOutputPack {
         }
         file_dialog.set_option(FileDialogOptions::SaveAsConfirm);
         file_dialog.set_option(FileDialogOptions::NewFolder);
-        file_dialog.set_title(
+        file_dialog.set_title(tr(
             "Choose a folder to save the smart contract.  The folder's name will be used as \
                 the name of the contract.",
-        );
+        ));
         file_dialog.show();
 
         if file_dialog.filename() == PathBuf::default() {
@@ -599,22 +1709,145 @@ impl MainOutputPack {
             .file_name()
             .map(|name| name.to_string_lossy().to_string())
             .unwrap_or_else(get_current_or_default_contract_name);
+        let sanitized_contract_name = match casper_multisig_tool::set_contract_name(&contract_name)
+        {
+            Ok(name) => name,
+            Err(error) => {
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "{}\n\nPlease choose a different folder name.",
+                    &[&error.to_string()],
+                ));
+                return None;
+            }
+        };
         casper_multisig_tool::set_project_path(&project_path);
-        casper_multisig_tool::set_contract_name(&contract_name);
 
         let mut new_window = Window::default()
-            .with_size(1000, 400)
-            .with_label("Generating smart contract");
+            .with_size(1600, 460)
+            .with_label(tr("Generating smart contract"));
         new_window.make_modal(true);
 
-        let mut text_display = TextDisplay::default().with_size(
+        let top_button_width = 100;
+        let open_button_width = 160;
+        let mut progress = Progress::new(
+            PADDING,
+            PADDING,
+            new_window.width() - (7 * PADDING) - (3 * top_button_width) - (2 * open_button_width),
+            BUTTON_HEIGHT,
+            None,
+        );
+        progress.set_color(Color::from_u32(palette().input_background));
+        progress.set_selection_color(Color::from_u32(palette().passed));
+        progress.set_maximum(CompilePhase::ALL.len() as f64);
+        show_compile_phase(&mut progress, CompilePhase::GeneratingFiles);
+
+        let mut open_project_button = Button::new(
+            progress.x() + progress.width() + PADDING,
+            PADDING,
+            open_button_width,
+            BUTTON_HEIGHT,
+            tr("Open project folder"),
+        );
+        open_project_button.set_color(Color::from_u32(palette().button));
+        open_project_button.set_tooltip(tr("Open the generated project in the file manager"));
+        open_project_button.deactivate();
+        open_project_button.set_callback(|_| {
+            let project_dir =
+                casper_multisig_tool::project_path().join(casper_multisig_tool::contract_name());
+            if let Err(error) = casper_multisig_tool::open_folder(&project_dir) {
+                dialog::alert_default(&error.to_string());
+            }
+        });
+
+        let mut open_wasm_folder_button = Button::new(
+            open_project_button.x() + open_button_width + PADDING,
+            PADDING,
+            open_button_width,
+            BUTTON_HEIGHT,
+            tr("Open Wasm location"),
+        );
+        open_wasm_folder_button.set_color(Color::from_u32(palette().button));
+        open_wasm_folder_button.set_tooltip(tr(
+            "Open the folder holding the compiled session Wasm in the file manager",
+        ));
+        open_wasm_folder_button.deactivate();
+
+        let mut errors_toggle = ToggleButton::new(
+            open_wasm_folder_button.x() + open_button_width + PADDING,
+            PADDING,
+            top_button_width,
+            BUTTON_HEIGHT,
+            "Errors",
+        );
+        errors_toggle.set_color(Color::from_u32(palette().button));
+        errors_toggle.set_selection_color(Color::from_u32(palette().failed));
+        errors_toggle.set_tooltip(tr(
+            "Show only the errors in the output, along with any warnings chosen",
+        ));
+
+        let mut warnings_toggle = ToggleButton::new(
+            errors_toggle.x() + top_button_width + PADDING,
+            PADDING,
+            top_button_width,
+            BUTTON_HEIGHT,
+            "Warnings",
+        );
+        warnings_toggle.set_color(Color::from_u32(palette().button));
+        warnings_toggle.set_selection_color(Color::from_u32(palette().warning_text));
+        warnings_toggle.set_tooltip(tr(
+            "Show only the warnings in the output, along with any errors chosen",
+        ));
+
+        let mut cancel_button = Button::new(
+            new_window.width() - PADDING - top_button_width,
+            PADDING,
+            top_button_width,
+            BUTTON_HEIGHT,
+            tr("Cancel"),
+        );
+        cancel_button.set_color(Color::from_u32(palette().button));
+        cancel_button.set_tooltip(tr("Stop compiling, killing cargo"));
+        cancel_button.set_callback(|button| {
+            casper_multisig_tool::cancel_compilation();
+            button.deactivate();
+        });
+
+        let mut text_display = TextDisplay::new(
+            0,
+            BUTTON_HEIGHT + (2 * PADDING),
             new_window.width(),
-            new_window.height() - BUTTON_HEIGHT - (2 * PADDING),
+            new_window.height() - (2 * BUTTON_HEIGHT) - (4 * PADDING),
+            None,
         );
         let buffer = TextBuffer::default();
         text_display.set_buffer(Some(buffer));
         text_display.set_text_font(Font::Courier);
-        text_display.set_text_size(14);
+        text_display.set_text_size(code_text_size());
+        text_display.set_highlight_data(TextBuffer::default(), compile_output_style_table());
+
+        // Shown in place of the whole output while it's filtered to the errors or warnings.
+        let mut filtered_text_display = TextDisplay::new(
+            text_display.x(),
+            text_display.y(),
+            text_display.width(),
+            text_display.height(),
+            None,
+        );
+        filtered_text_display.set_buffer(Some(TextBuffer::default()));
+        filtered_text_display.set_text_font(Font::Courier);
+        filtered_text_display.set_text_size(code_text_size());
+        filtered_text_display.hide();
+
+        let mut compile_output_filter = CompileOutputFilter {
+            errors_toggle: errors_toggle.clone(),
+            warnings_toggle: warnings_toggle.clone(),
+            text_display: text_display.clone(),
+            filtered_text_display,
+        };
+        for toggle in [&mut errors_toggle, &mut warnings_toggle] {
+            let mut compile_output_filter = compile_output_filter.clone();
+            toggle.set_callback(move |_| compile_output_filter.apply());
+        }
 
         let button_width = 100;
         let mut done_button = Button::new(
@@ -622,11 +1855,146 @@ impl MainOutputPack {
             new_window.height() - PADDING - BUTTON_HEIGHT,
             button_width,
             BUTTON_HEIGHT,
-            "Done",
+            tr("Done"),
         );
-        done_button.set_color(Color::from_u32(BUTTON_COLOR));
+        done_button.set_color(Color::from_u32(palette().button));
         done_button.deactivate();
 
+        let mut run_test_button = Button::new(
+            done_button.x() - PADDING - button_width,
+            done_button.y(),
+            button_width,
+            BUTTON_HEIGHT,
+            tr("Run test"),
+        );
+        run_test_button.set_color(Color::from_u32(palette().button));
+        run_test_button.deactivate();
+
+        let mut fund_button = Button::new(
+            run_test_button.x() - PADDING - button_width,
+            done_button.y(),
+            button_width,
+            BUTTON_HEIGHT,
+            tr("Fund account"),
+        );
+        fund_button.set_color(Color::from_u32(palette().button));
+        fund_button.set_tooltip(tr(
+            "Send the initial transfer which funds the main account, or on testnet, request test \
+            CSPR for it from the faucet",
+        ));
+        fund_button.deactivate();
+
+        let mut deploy_button = Button::new(
+            fund_button.x() - PADDING - button_width,
+            done_button.y(),
+            button_width,
+            BUTTON_HEIGHT,
+            tr("Deploy..."),
+        );
+        deploy_button.set_color(Color::from_u32(palette().button));
+        deploy_button.set_tooltip(tr(
+            "Open the deploy screen, to choose the network, payment, time to live and signing keys \
+            for the deploy of the compiled contract, then send it and follow its status",
+        ));
+        deploy_button.deactivate();
+
+        let mut sign_button = Button::new(
+            deploy_button.x() - PADDING - button_width,
+            done_button.y(),
+            button_width,
+            BUTTON_HEIGHT,
+            tr("Sign deploy"),
+        );
+        sign_button.set_color(Color::from_u32(palette().button));
+        sign_button.set_tooltip(tr(
+            "Build a deploy of the compiled contract, or reopen the one saved in the project, and \
+            add approvals by signing it with secret keys or importing other signers' approvals",
+        ));
+        sign_button.deactivate();
+
+        let mut send_button = Button::new(
+            sign_button.x() - PADDING - button_width,
+            done_button.y(),
+            button_width,
+            BUTTON_HEIGHT,
+            tr("Send deploy"),
+        );
+        send_button.set_color(Color::from_u32(palette().button));
+        send_button.set_tooltip(tr(
+            "Send the signed deploy saved in the project to a node, and wait for it to be executed",
+        ));
+        send_button.deactivate();
+
+        let mut dry_run_button = Button::new(
+            send_button.x() - PADDING - button_width,
+            done_button.y(),
+            button_width,
+            BUTTON_HEIGHT,
+            tr("Dry run"),
+        );
+        dry_run_button.set_color(Color::from_u32(palette().button));
+        dry_run_button.set_tooltip(tr(
+            "Execute the signed deploy saved in the project against a node's current state without \
+            committing it, to preview its cost and effects, or why it would fail",
+        ));
+        dry_run_button.deactivate();
+
+        let local_network_button_width = 170;
+        let mut local_network_button = Button::new(
+            dry_run_button.x() - PADDING - local_network_button_width,
+            done_button.y(),
+            local_network_button_width,
+            BUTTON_HEIGHT,
+            tr("Test on local network"),
+        );
+        local_network_button.set_color(Color::from_u32(palette().button));
+        local_network_button.set_tooltip(tr(
+            "Fund the main account on a local NCTL network, send the setup deploy there and check \
+            the account ends up with the configured keys and thresholds",
+        ));
+        local_network_button.deactivate();
+
+        let mut inspect_button = Button::new(
+            local_network_button.x() - PADDING - button_width,
+            done_button.y(),
+            button_width,
+            BUTTON_HEIGHT,
+            tr("Inspect deploy"),
+        );
+        inspect_button.set_color(Color::from_u32(palette().button));
+        inspect_button.set_tooltip(tr(
+            "Decode a deploy built elsewhere and check its session Wasm and args are those of the \
+            compiled contract, before signing it",
+        ));
+        inspect_button.deactivate();
+
+        let copy_wasm_path_button_width = 130;
+        let mut copy_wasm_path_button = Button::new(
+            inspect_button.x() - PADDING - copy_wasm_path_button_width,
+            done_button.y(),
+            copy_wasm_path_button_width,
+            BUTTON_HEIGHT,
+            tr("Copy Wasm path"),
+        );
+        copy_wasm_path_button.set_color(Color::from_u32(palette().button));
+        copy_wasm_path_button.set_tooltip(tr(
+            "Copy the path of the compiled session Wasm to the clipboard",
+        ));
+        copy_wasm_path_button.deactivate();
+
+        // A button only so that a sent deploy's result can be clicked to view it in the explorer.
+        let mut result_pane = Button::new(
+            PADDING,
+            done_button.y(),
+            copy_wasm_path_button.x() - (2 * PADDING),
+            BUTTON_HEIGHT,
+            None,
+        );
+        result_pane.set_frame(FrameType::FlatBox);
+        result_pane.set_down_frame(FrameType::FlatBox);
+        result_pane.set_align(Align::Inside | Align::Left | Align::Clip);
+        result_pane.hide();
+
         new_window.end();
         new_window.show();
 
@@ -635,16 +2003,456 @@ impl MainOutputPack {
             window.hide();
         });
 
-        let receiver = casper_multisig_tool::generate_smart_contract().unwrap();
+        let test_text_display = text_display.clone();
+        let mut test_done_button = done_button.clone();
+        run_test_button.set_callback(move |button| {
+            button.deactivate();
+            button.set_color(Color::from_u32(palette().button));
+            test_done_button.deactivate();
+            let receiver = casper_multisig_tool::run_tests().unwrap();
+
+            let mut button = button.clone();
+            let mut text_display = test_text_display.clone();
+            let mut done_button = test_done_button.clone();
+            thread::spawn(move || {
+                append_line(&mut text_display, "");
+                while let Ok(event) = receiver.recv() {
+                    match event {
+                        TestEvent::Output(line) => append_line(&mut text_display, &line),
+                        TestEvent::Finished { passed } => {
+                            let color = if passed {
+                                palette().passed
+                            } else {
+                                palette().failed
+                            };
+                            button.set_color(Color::from_u32(color));
+                        }
+                    }
+                }
+                button.activate();
+                done_button.activate();
+                button.redraw();
+            });
+        });
+
+        let fund_text_display = text_display.clone();
+        let mut fund_done_button = done_button.clone();
+        fund_button.set_callback(move |button| {
+            let chain_name = match input_chain_name() {
+                Some(value) => value,
+                None => return,
+            };
+            if casper_multisig_tool::is_testnet(&chain_name)
+                && dialog::choice_default(
+                    tr("Request test CSPR for the main account from the testnet faucet?"),
+                    tr("No, send the initial transfer"),
+                    tr("Yes"),
+                    "",
+                ) == 1
+            {
+                request_testnet_funds();
+                return;
+            }
+            let node_address = match input_node_address() {
+                Some(value) => value,
+                None => return,
+            };
+            let receiver =
+                match casper_multisig_tool::submit_initial_transfer(&node_address, &chain_name) {
+                    Ok(receiver) => receiver,
+                    Err(error) => {
+                        dialog::alert_default(error.to_string().as_str());
+                        return;
+                    }
+                };
+            button.deactivate();
+            fund_done_button.deactivate();
+
+            let mut button = button.clone();
+            let mut text_display = fund_text_display.clone();
+            let mut done_button = fund_done_button.clone();
+            thread::spawn(move || {
+                append_line(&mut text_display, "");
+                while let Ok(event) = receiver.recv() {
+                    match event {
+                        TransferEvent::Output(line) => append_line(&mut text_display, &line),
+                        TransferEvent::Finished { succeeded } => {
+                            if succeeded {
+                                append_line(
+                                    &mut text_display,
+                                    "Check the transfer executed successfully before deploying",
+                                );
+                            } else {
+                                // Allow retrying, e.g. against a different node.
+                                button.activate();
+                            }
+                        }
+                    }
+                }
+                done_button.activate();
+            });
+        });
+
+        let wasm_path: Arc<Mutex<Option<PathBuf>>> = Arc::new(Mutex::new(None));
+        let sign_wasm_path = Arc::clone(&wasm_path);
+        let mut sign_text_display = text_display.clone();
+        sign_button.set_callback(move |_| {
+            if let Some(wasm_path) = sign_wasm_path.lock().unwrap().clone() {
+                build_and_sign_deploy(&wasm_path, &mut sign_text_display);
+            }
+        });
+
+        let deploy_wasm_path = Arc::clone(&wasm_path);
+        deploy_button.set_callback(move |_| {
+            if let Some(wasm_path) = deploy_wasm_path.lock().unwrap().clone() {
+                show_deploy_window(wasm_path);
+            }
+        });
+
+        let inspect_wasm_path = Arc::clone(&wasm_path);
+        let mut inspect_text_display = text_display.clone();
+        inspect_button.set_callback(move |_| {
+            let wasm_path = match inspect_wasm_path.lock().unwrap().clone() {
+                Some(wasm_path) => wasm_path,
+                None => return,
+            };
+            let deploy_file = match get_deploy_file() {
+                Some(file) => file,
+                None => return,
+            };
+            match casper_multisig_tool::inspect_deploy(&deploy_file, &wasm_path) {
+                Ok(inspection) => {
+                    append_line(&mut inspect_text_display, "");
+                    append_line(
+                        &mut inspect_text_display,
+                        &casper_multisig_tool::translate_with(
+                            "Inspecting {}",
+                            &[&deploy_file.display().to_string()],
+                        ),
+                    );
+                    for line in inspection.to_string().lines() {
+                        append_line(&mut inspect_text_display, line);
+                    }
+                }
+                Err(error) => dialog::alert_default(&error.to_string()),
+            }
+        });
+
+        let open_wasm_path = Arc::clone(&wasm_path);
+        open_wasm_folder_button.set_callback(move |_| {
+            let wasm_dir = match open_wasm_path.lock().unwrap().as_ref() {
+                Some(wasm_path) => wasm_path.parent().map(Path::to_path_buf),
+                None => return,
+            };
+            if let Some(wasm_dir) = wasm_dir {
+                if let Err(error) = casper_multisig_tool::open_folder(&wasm_dir) {
+                    dialog::alert_default(&error.to_string());
+                }
+            }
+        });
+
+        let copy_wasm_path = Arc::clone(&wasm_path);
+        copy_wasm_path_button.set_callback(move |_| {
+            if let Some(wasm_path) = copy_wasm_path.lock().unwrap().as_ref() {
+                copy_to_clipboard(&wasm_path.display().to_string());
+            }
+        });
+
+        let local_network_wasm_path = Arc::clone(&wasm_path);
+        let local_network_text_display = text_display.clone();
+        let mut local_network_done_button = done_button.clone();
+        local_network_button.set_callback(move |button| {
+            let wasm_path = match local_network_wasm_path.lock().unwrap().clone() {
+                Some(wasm_path) => wasm_path,
+                None => return,
+            };
+            let receiver = match start_local_network_test(&wasm_path) {
+                Some(receiver) => receiver,
+                None => return,
+            };
+            button.deactivate();
+            button.set_color(Color::from_u32(palette().button));
+            local_network_done_button.deactivate();
+
+            let mut button = button.clone();
+            let mut text_display = local_network_text_display.clone();
+            let mut done_button = local_network_done_button.clone();
+            thread::spawn(move || {
+                append_line(&mut text_display, "");
+                while let Ok(event) = receiver.recv() {
+                    match event {
+                        LocalNetworkEvent::Output(line) => append_line(&mut text_display, &line),
+                        LocalNetworkEvent::Finished { passed } => {
+                            let color = if passed {
+                                palette().passed
+                            } else {
+                                palette().failed
+                            };
+                            button.set_color(Color::from_u32(color));
+                        }
+                    }
+                }
+                button.activate();
+                done_button.activate();
+                button.redraw();
+            });
+        });
+
+        let mut dry_run_text_display = text_display.clone();
+        let mut dry_run_result_pane = result_pane.clone();
+        dry_run_button.set_callback(move |_| {
+            let deploy_path = casper_multisig_tool::project_path()
+                .join(casper_multisig_tool::contract_name())
+                .join("deploy.json");
+            if !deploy_path.exists() {
+                dialog::alert_default(tr("Sign the deploy before executing it"));
+                return;
+            }
+            let node_address = match input_node_address() {
+                Some(value) => value,
+                None => return,
+            };
+            let speculative_exec_address = match dialog::input_default(
+                tr("Enter the address of the node's speculative execution server"),
+                &casper_multisig_tool::default_speculative_exec_address(&node_address)
+                    .unwrap_or(node_address),
+            ) {
+                Some(value) => value,
+                None => return,
+            };
+            let dry_run =
+                match casper_multisig_tool::dry_run_deploy(&deploy_path, &speculative_exec_address)
+                {
+                    Ok(dry_run) => dry_run,
+                    Err(error) => {
+                        dialog::alert_default(&error.to_string());
+                        return;
+                    }
+                };
+            show_dry_run(
+                &dry_run,
+                &mut dry_run_text_display,
+                &mut dry_run_result_pane,
+            );
+        });
+
+        let send_text_display = text_display.clone();
+        let mut send_done_button = done_button.clone();
+        send_button.set_callback(move |button| {
+            let deploy_path = casper_multisig_tool::project_path()
+                .join(casper_multisig_tool::contract_name())
+                .join("deploy.json");
+            if !deploy_path.exists() {
+                dialog::alert_default(tr("Sign the deploy before sending it"));
+                return;
+            }
+            if let Ok(deploy) = casper_multisig_tool::load_deploy(&deploy_path) {
+                if casper_multisig_tool::time_to_expiry(&deploy).is_none() {
+                    dialog::alert_default(tr(
+                        "The deploy has expired, so the network won't execute it.  Choose \
+                        \"Sign deploy\" to rebuild it with a fresh timestamp and collect the \
+                        approvals again.",
+                    ));
+                    return;
+                }
+            }
+            let node_address = match input_node_address() {
+                Some(value) => value,
+                None => return,
+            };
+            let chain_name_warning = casper_multisig_tool::load_deploy(&deploy_path)
+                .ok()
+                .and_then(|deploy| {
+                    casper_multisig_tool::node_status(&node_address)
+                        .ok()?
+                        .chain_name_warning(deploy.header().chain_name())
+                });
+            if let Some(warning) = chain_name_warning {
+                if dialog::choice_default(
+                    &casper_multisig_tool::translate_with(
+                        "Warning: {}.\n\nSend the deploy anyway?",
+                        &[&warning],
+                    ),
+                    tr("Cancel"),
+                    tr("Send anyway"),
+                    "",
+                ) != 1
+                {
+                    return;
+                }
+            }
+            let event_stream_url = match dialog::input_default(
+                tr(
+                    "Enter the node's event stream URL to be told as soon as the deploy is \
+                    processed, or leave empty to poll the node instead",
+                ),
+                &default_event_stream_url(&node_address),
+            ) {
+                Some(value) => value,
+                None => return,
+            };
+            let event_stream_url = Some(event_stream_url.trim()).filter(|url| !url.is_empty());
+            let receiver = match casper_multisig_tool::send_deploy(
+                &deploy_path,
+                &node_address,
+                event_stream_url,
+            ) {
+                Ok(receiver) => receiver,
+                Err(error) => {
+                    dialog::alert_default(error.to_string().as_str());
+                    return;
+                }
+            };
+            button.deactivate();
+            send_done_button.deactivate();
+
+            let mut button = button.clone();
+            let mut text_display = send_text_display.clone();
+            let mut done_button = send_done_button.clone();
+            let mut result_pane = result_pane.clone();
+            let explorer_url = casper_multisig_tool::load_deploy(&deploy_path)
+                .ok()
+                .and_then(|deploy| casper_multisig_tool::deploy_explorer_url(&deploy));
+            thread::spawn(move || {
+                if show_deploy_events(
+                    receiver,
+                    &deploy_path,
+                    &node_address,
+                    explorer_url.as_deref(),
+                    &mut text_display,
+                    &mut result_pane,
+                ) {
+                    button.activate();
+                }
+                done_button.activate();
+            });
+        });
+
+        if sanitized_contract_name != contract_name {
+            append_line(
+                &mut text_display,
+                &casper_multisig_tool::translate_with(
+                    "Using '{}' as the contract name, since '{}' isn't a valid Cargo package name",
+                    &[&sanitized_contract_name, &contract_name],
+                ),
+            );
+            append_line(&mut text_display, "");
+        }
+
+        // Writing the project can fail before compilation starts, e.g. if resolving the
+        // dependencies needs crates.io and it can't be reached.
+        let receiver = match casper_multisig_tool::generate_smart_contract() {
+            Ok(receiver) => receiver,
+            Err(error) => {
+                append_line(
+                    &mut text_display,
+                    &casper_multisig_tool::translate_with("Error: {}", &[&error.to_string()]),
+                );
+                finish_compile_progress(
+                    &mut progress,
+                    &casper_multisig_tool::translate_with(
+                        "Failed: {}",
+                        &[&CompilePhase::GeneratingFiles.to_string()],
+                    ),
+                    false,
+                );
+                cancel_button.deactivate();
+                done_button.activate();
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "Failed to generate the smart contract: {}",
+                    &[&error.to_string()],
+                ));
+                return None;
+            }
+        };
+        // Shown in the output rather than a dialog, so as not to interrupt the compilation.
+        if let Err(error) = casper_multisig_tool::add_recent_project(&self.key_configuration()) {
+            append_line(
+                &mut text_display,
+                &casper_multisig_tool::translate_with(
+                    "Warning: couldn't add the project to the recent items: {}",
+                    &[&error.to_string()],
+                ),
+            );
+        }
 
         Some(thread::spawn(move || {
+            let mut failure = None;
+            let mut warnings = vec![];
+            let mut compiled = false;
+            let mut phase = CompilePhase::GeneratingFiles;
             loop {
                 match receiver.recv() {
-                    Ok(line) => {
-                        let mut buffer = text_display.buffer().unwrap();
-                        buffer.append(&format!("{}\n", line));
-                        text_display.set_insert_position(buffer.length());
-                        text_display.scroll(text_display.count_lines(0, buffer.length(), true), 0);
+                    Ok(CompileEvent::Output(line)) => {
+                        append_styled_line(&mut text_display, &line, STATUS_STYLE);
+                        compile_output_filter.refresh();
+                    }
+                    Ok(CompileEvent::CommandOutput { line, stream }) => {
+                        let style = match stream {
+                            OutputStream::Stdout => PLAIN_STYLE,
+                            OutputStream::Stderr => STDERR_STYLE,
+                        };
+                        append_styled_line(&mut text_display, &line, style);
+                        compile_output_filter.refresh();
+                    }
+                    Ok(CompileEvent::Progress(new_phase)) => {
+                        // A "Compiling" status line doesn't say how many crates are compiled, so
+                        // mustn't hide the count shown by the last progress bar.
+                        let keeps_count = new_phase == CompilePhase::Compiling { crates: None }
+                            && matches!(phase, CompilePhase::Compiling { crates: Some(_) });
+                        if !keeps_count && new_phase != phase {
+                            phase = new_phase;
+                            show_compile_phase(&mut progress, phase);
+                        }
+                    }
+                    Ok(CompileEvent::Warning(warning)) => {
+                        append_line(
+                            &mut text_display,
+                            &casper_multisig_tool::translate_with("Warning: {}", &[&warning]),
+                        );
+                        warnings.push(warning);
+                    }
+                    Ok(CompileEvent::Succeeded { wasm_path: path }) => {
+                        *wasm_path.lock().unwrap() = Some(path);
+                        compiled = true;
+                        finish_compile_progress(&mut progress, tr("Compiled"), true);
+                        open_project_button.activate();
+                        open_wasm_folder_button.activate();
+                    }
+                    Ok(CompileEvent::SourceGenerated { .. }) => {
+                        finish_compile_progress(
+                            &mut progress,
+                            tr("Generated without compiling"),
+                            true,
+                        );
+                        open_project_button.activate();
+                    }
+                    Ok(CompileEvent::Failed { reason }) => {
+                        append_line(&mut text_display, "");
+                        append_line(
+                            &mut text_display,
+                            &casper_multisig_tool::translate_with("Error: {}", &[&reason]),
+                        );
+                        finish_compile_progress(
+                            &mut progress,
+                            &casper_multisig_tool::translate_with(
+                                "Failed: {}",
+                                &[&phase.to_string()],
+                            ),
+                            false,
+                        );
+                        failure = Some(reason);
+                    }
+                    Ok(CompileEvent::TimedOut { timeout }) => {
+                        finish_compile_progress(&mut progress, tr("Timed out"), false);
+                        failure = Some(casper_multisig_tool::translate_with(
+                            "compilation timed out after {} minutes.  Increase the compile \
+                            timeout in the build options if the build is just slow.",
+                            &[&(timeout.as_secs() / 60).to_string()],
+                        ));
+                    }
+                    Ok(CompileEvent::Cancelled) => {
+                        finish_compile_progress(&mut progress, tr("Cancelled"), false)
                     }
                     Err(error) => {
                         println!("Stopping RECV: {}", error);
@@ -652,186 +2460,5853 @@ impl MainOutputPack {
                     }
                 }
             }
+            cancel_button.deactivate();
             done_button.activate();
+            match failure {
+                Some(reason) => dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "Failed to generate the smart contract: {}",
+                    &[&reason],
+                )),
+                None => {
+                    if !warnings.is_empty() {
+                        dialog::alert_default(&casper_multisig_tool::translate_with(
+                            "Warning:\n{}",
+                            &[&warnings.join("\n\n")],
+                        ));
+                    }
+                    // The tests need the compiled contract.
+                    if compiled && casper_multisig_tool::has_test_crate() {
+                        run_test_button.activate();
+                    }
+                    // Even without an initial transfer, testnet accounts can be funded from the
+                    // faucet.
+                    fund_button.activate();
+                    if compiled {
+                        deploy_button.activate();
+                        sign_button.activate();
+                        dry_run_button.activate();
+                        send_button.activate();
+                        local_network_button.activate();
+                        inspect_button.activate();
+                        copy_wasm_path_button.activate();
+                    }
+                }
+            }
         }))
     }
 }
 
-/// Returns the account hash as a formatted string and a tooltip indicating the origin of the
-/// account hash, or `None` if the user didn't enter a valid path or cancelled the operation.
-fn get_account_hash_from_public_key_file() -> Option<(String, String)> {
-    let mut file_dialog = FileDialog::new(FileDialogType::BrowseFile);
+/// Returns the identicon of the given formatted account hash as an image, or `None` if it isn't a
+/// valid account hash.
+fn identicon_image(account_hash: &str) -> Option<RgbImage> {
+    let identicon = casper_multisig_tool::account_hash_identicon(account_hash).ok()?;
+    RgbImage::new(
+        &identicon.to_rgb(IDENTICON_CELL_SIZE),
+        IDENTICON_SIZE,
+        IDENTICON_SIZE,
+        ColorDepth::Rgb8,
+    )
+    .ok()
+}
+
+/// Shows the QR code of the key's account hash, along with that of the public key it was derived
+/// from where the key's tooltip names one, e.g. for a key entered as a public key.
+fn show_key_qr_code(account_hash: &AccountHashWidget) {
+    let account_hash_value = account_hash.value();
+    let public_key = account_hash
+        .tooltip()
+        .and_then(|tooltip| public_key_in_tooltip(&tooltip, &account_hash_value));
+    let mut values = vec![(tr("Account hash").to_string(), account_hash_value)];
+    if let Some(public_key) = public_key {
+        values.push((tr("Public key").to_string(), public_key));
+    }
+    show_qr_code_window(values);
+}
+
+/// Returns the hex-encoded public key named in a key's `tooltip`, e.g. "Derived from public key
+/// 01ab...", if it's the one `account_hash` is derived from.
+///
+/// The tooltip may be translated, so any run of hex digits in it is tried rather than the text
+/// after "public key".
+fn public_key_in_tooltip(tooltip: &str, account_hash: &str) -> Option<String> {
+    tooltip
+        .split(|c: char| !c.is_ascii_hexdigit())
+        .filter(|word| word.len() >= 66)
+        .find(|word| {
+            casper_multisig_tool::get_account_hash_from_hex_encoded_public_key(word)
+                .ok()
+                .as_deref()
+                == Some(account_hash)
+        })
+        .map(str::to_string)
+}
+
+/// Shows the guide to associated keys, weights and thresholds, scrolled to `section`, one of the
+/// guide's anchors such as "thresholds", if given.
+///
+/// Unlike the other windows it isn't modal, so it can be read alongside the configuration.
+fn show_help_window(section: Option<&str>) {
+    let width = 900;
+    let height = 800;
+    let mut window = Window::default()
+        .with_size(width, height)
+        .center_screen()
+        .with_label(tr("Multisig guide"));
+
+    let mut help_view = HelpView::new(
+        PADDING,
+        PADDING,
+        width - (2 * PADDING),
+        height - BUTTON_HEIGHT - (3 * PADDING),
+        None,
+    );
+    help_view.set_text_size(text_size());
+    help_view.set_value(HELP_GUIDE);
+    if let Some(section) = section {
+        help_view.set_top_line_string(section);
+    }
+
+    let button_width = 120;
+    let mut contents_button = Button::new(
+        PADDING,
+        height - PADDING - BUTTON_HEIGHT,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Contents"),
+    );
+    contents_button.set_color(Color::from_u32(palette().button));
+    contents_button.set_tooltip(tr("Scroll back to the guide's list of sections"));
+    let mut contents_help_view = help_view.clone();
+    contents_button.set_callback(move |_| contents_help_view.set_top_line(0));
+    let mut close_button = Button::new(
+        width - PADDING - button_width,
+        contents_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Close"),
+    );
+    close_button.set_color(Color::from_u32(palette().button));
+    close_button.set_callback(|button| button.window().unwrap().hide());
+
+    window.end();
+    window.show();
+}
+
+/// Shows the modal window with the QR code of one of `values`, given as their descriptions and
+/// texts, with a choice between them if there are several.
+fn show_qr_code_window(values: Vec<(String, String)>) {
+    let width = 800;
+    let qr_code_size = 400;
+    let mut window = Window::default()
+        .with_size(width, 0)
+        .center_screen()
+        .with_label(tr("QR code"));
+    window.make_modal(true);
+
+    let mut choice = Choice::new(PADDING, PADDING, 300, BUTTON_HEIGHT, None);
+    choice.set_tooltip(tr("Which value the QR code shows"));
+    for (description, _) in &values {
+        choice.add_choice(&escape_menu_label(description));
+    }
+    choice.set_value(0);
+    if values.len() < 2 {
+        choice.hide();
+    }
+    let image_y = if choice.visible() {
+        choice.y() + BUTTON_HEIGHT + PADDING
+    } else {
+        PADDING
+    };
+    let mut image = Frame::new(
+        (width - qr_code_size) / 2,
+        image_y,
+        qr_code_size,
+        qr_code_size,
+        None,
+    );
+    let mut text = Output::new(
+        PADDING,
+        image.y() + qr_code_size + PADDING,
+        width - (2 * PADDING),
+        BUTTON_HEIGHT,
+        None,
+    );
+    text.set_text_font(Font::Courier);
+    text.set_text_size(text_size());
+    text.set_tooltip(tr(
+        "The value the QR code encodes, to check against the scanned one",
+    ));
+
+    let button_width = 100;
+    let mut close_button = Button::new(
+        width - PADDING - button_width,
+        text.y() + BUTTON_HEIGHT + PADDING,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Close"),
+    );
+    close_button.set_color(Color::from_u32(palette().button));
+    close_button.set_callback(|button| button.window().unwrap().hide());
+    let mut copy_button = Button::new(
+        close_button.x() - PADDING - button_width,
+        close_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Copy"),
+    );
+    copy_button.set_color(Color::from_u32(palette().button));
+    copy_button.set_tooltip(tr("Copy the value to the clipboard"));
+    let copy_text = text.clone();
+    copy_button.set_callback(move |_| copy_to_clipboard(&copy_text.value()));
+
+    show_qr_code(&values[0].1, qr_code_size, &mut image, &mut text);
+    choice.set_callback(move |choice| {
+        if let Some((_, value)) = values.get(choice.value().max(0) as usize) {
+            show_qr_code(value, qr_code_size, &mut image, &mut text);
+        }
+    });
+
+    window.end();
+    window.set_size(width, close_button.y() + BUTTON_HEIGHT + PADDING);
+    window.show();
+}
+
+/// Shows the QR code of `value` in `image`, scaled to fit within `size` pixels, and `value` itself
+/// in `text`.
+fn show_qr_code(value: &str, size: i32, image: &mut Frame, text: &mut Output) {
+    text.set_value(value);
+    let qr_code = match casper_multisig_tool::qr_code(value) {
+        Ok(qr_code) => qr_code,
+        Err(error) => {
+            image.set_image(None::<RgbImage>);
+            image.set_label(&error.to_string());
+            image.redraw();
+            return;
+        }
+    };
+    let module_size = cmp::max(1, size as usize / qr_code.size(1));
+    let pixel_size = qr_code.size(module_size) as i32;
+    match RgbImage::new(
+        &qr_code.to_rgb(module_size),
+        pixel_size,
+        pixel_size,
+        ColorDepth::Rgb8,
+    ) {
+        Ok(rgb_image) => {
+            image.set_label("");
+            image.set_image(Some(rgb_image));
+        }
+        Err(error) => image.set_label(&error.to_string()),
+    }
+    image.redraw();
+}
+
+/// The toggles filtering the compile window's output to its errors or warnings, and the displays of
+/// the whole and the filtered output.
+#[derive(Clone)]
+struct CompileOutputFilter {
+    errors_toggle: ToggleButton,
+    warnings_toggle: ToggleButton,
+    text_display: TextDisplay,
+    filtered_text_display: TextDisplay,
+}
+
+impl CompileOutputFilter {
+    /// Shows the output filtered by the toggled levels, or the whole output if neither is toggled.
+    fn apply(&mut self) {
+        if self.levels().is_empty() {
+            self.filtered_text_display.hide();
+            self.text_display.show();
+            return;
+        }
+        self.refresh();
+        self.text_display.hide();
+        self.filtered_text_display.show();
+    }
+
+    /// Refilters the output, e.g. once more has been appended, if it's being filtered.
+    fn refresh(&mut self) {
+        let levels = self.levels();
+        if levels.is_empty() {
+            return;
+        }
+        let text = self.text_display.buffer().unwrap().text();
+        let lines = text.lines().collect::<Vec<_>>();
+        let diagnostic_levels = casper_multisig_tool::diagnostic_levels(&lines);
+        let mut filtered = String::new();
+        let mut last_shown = None;
+        for (index, (line, level)) in lines.iter().zip(diagnostic_levels).enumerate() {
+            if !level.is_some_and(|level| levels.contains(&level)) {
+                continue;
+            }
+            // Diagnostics are kept apart where the lines between them are hidden.
+            if last_shown.is_some_and(|last_shown| last_shown + 1 < index) {
+                filtered.push('\n');
+            }
+            filtered.push_str(line);
+            filtered.push('\n');
+            last_shown = Some(index);
+        }
+        let mut buffer = self.filtered_text_display.buffer().unwrap();
+        buffer.set_text(&filtered);
+        self.filtered_text_display.scroll(
+            self.filtered_text_display
+                .count_lines(0, buffer.length(), true),
+            0,
+        );
+    }
+
+    /// Returns the levels of the diagnostics toggled to be shown.
+    fn levels(&self) -> Vec<DiagnosticLevel> {
+        let mut levels = vec![];
+        if self.errors_toggle.is_toggled() {
+            levels.push(DiagnosticLevel::Error);
+        }
+        if self.warnings_toggle.is_toggled() {
+            levels.push(DiagnosticLevel::Warning);
+        }
+        levels
+    }
+}
+
+/// Shows `phase` in the compile window's progress bar, which fills a section per phase.
+fn show_compile_phase(progress: &mut Progress, phase: CompilePhase) {
+    let within_phase = match phase {
+        CompilePhase::Compiling {
+            crates: Some((compiled, total)),
+        } if total > 0 => compiled.min(total) as f64 / total as f64,
+        _ => 0.0,
+    };
+    progress.set_value(phase.index() as f64 + within_phase);
+    progress.set_label(&casper_multisig_tool::translate_with(
+        "Step {} of {}: {}",
+        &[
+            &(phase.index() + 1).to_string(),
+            &CompilePhase::ALL.len().to_string(),
+            &phase.to_string(),
+        ],
+    ));
+    progress.redraw();
+}
+
+/// Shows in the compile window's progress bar that compilation ended with `outcome`.
+fn finish_compile_progress(progress: &mut Progress, outcome: &str, succeeded: bool) {
+    if succeeded {
+        progress.set_value(progress.maximum());
+    } else {
+        progress.set_selection_color(Color::from_u32(palette().failed));
+    }
+    progress.set_label(outcome);
+    progress.redraw();
+}
+
+/// Puts `text` on the clipboard.
+///
+/// FLTK only copies the selection of a text widget, so `text` is selected in a temporary one.
+fn copy_to_clipboard(text: &str) {
+    let mut output = Output::default();
+    output.set_value(text);
+    let _ = output.set_position(0);
+    let _ = output.set_mark(output.value().len() as i32);
+    if output.copy().is_err() {
+        dialog::alert_default(tr("Failed to copy to the clipboard"));
+    }
+    app::delete_widget(output);
+}
+
+/// Appends `line` to the display's buffer and scrolls to show it.
+fn append_line(text_display: &mut TextDisplay, line: &str) {
+    append_styled_line(text_display, line, PLAIN_STYLE);
+}
+
+/// Appends `line` to the display's buffer in the entry of its style table selected by `style`, if
+/// it has one, and scrolls to show it.
+fn append_styled_line(text_display: &mut TextDisplay, line: &str, style: char) {
+    let mut buffer = text_display.buffer().unwrap();
+    buffer.append(&format!("{}\n", line));
+    // The style buffer must style every byte of the text, including the newline.
+    if let Some(mut style_buffer) = text_display.style_buffer() {
+        style_buffer.append(&style.to_string().repeat(line.len() + 1));
+    }
+    text_display.set_insert_position(buffer.length());
+    text_display.scroll(text_display.count_lines(0, buffer.length(), true), 0);
+}
+
+/// Shows the predicted outcome of a deploy in the output and the results pane.
+fn show_dry_run(dry_run: &DryRun, text_display: &mut TextDisplay, result_pane: &mut Button) {
+    append_line(text_display, "");
+    append_line(
+        text_display,
+        &casper_multisig_tool::translate_with(
+            "Dry run against block {}, costing {} motes",
+            &[&dry_run.block_hash.to_string(), &dry_run.cost.to_string()],
+        ),
+    );
+    for effect in &dry_run.account_effects {
+        append_line(
+            text_display,
+            &casper_multisig_tool::translate_with("  would write {}", &[&effect.to_string()]),
+        );
+    }
+    if let Some(suggested_payment) = dry_run.suggested_payment {
+        append_line(
+            text_display,
+            &casper_multisig_tool::translate_with(
+                "Suggested payment amount: {} motes, which \"Sign deploy\" now defaults to",
+                &[&suggested_payment.to_string()],
+            ),
+        );
+    }
+    match &dry_run.error_message {
+        None => show_deploy_result(
+            result_pane,
+            true,
+            &casper_multisig_tool::translate_with(
+                "Dry run succeeded, costing {} motes",
+                &[&dry_run.cost.to_string()],
+            ),
+            None,
+        ),
+        Some(error_message) => {
+            append_line(
+                text_display,
+                &casper_multisig_tool::translate_with("Would fail: {}", &[error_message]),
+            );
+            if let Some(cause) = &dry_run.cause {
+                append_line(
+                    text_display,
+                    &casper_multisig_tool::translate_with("Cause: {}", &[cause]),
+                );
+            }
+            show_deploy_result(
+                result_pane,
+                false,
+                &casper_multisig_tool::translate_with(
+                    "Dry run failed: {}",
+                    &[dry_run.cause.as_ref().unwrap_or(error_message)],
+                ),
+                None,
+            );
+        }
+    }
+}
+
+/// Shows the events of sending the deploy saved at `deploy_path` to `node_address` as they arrive,
+/// along with its outcome in the results pane, checking the account once a setup deploy executes.
+///
+/// Returns `true` if the deploy wasn't executed, so sending it can be retried.
+fn show_deploy_events(
+    receiver: Receiver<DeployEvent>,
+    deploy_path: &Path,
+    node_address: &str,
+    explorer_url: Option<&str>,
+    text_display: &mut TextDisplay,
+    result_pane: &mut Button,
+) -> bool {
+    append_line(text_display, "");
+    let mut retry = false;
+    while let Ok(event) = receiver.recv() {
+        match event {
+            DeployEvent::Output(line) => append_line(text_display, &line),
+            DeployEvent::Succeeded { block_hash, cost } => {
+                append_line(
+                    text_display,
+                    &casper_multisig_tool::translate_with(
+                        "Executed successfully in block {}, costing {} motes",
+                        &[&block_hash.to_string(), &cost.to_string()],
+                    ),
+                );
+                let summary = casper_multisig_tool::translate_with(
+                    "Deploy succeeded, costing {} motes",
+                    &[&cost.to_string()],
+                );
+                if casper_multisig_tool::contract_mode() == ContractMode::Setup {
+                    verify_account(
+                        deploy_path,
+                        node_address,
+                        &summary,
+                        explorer_url,
+                        text_display,
+                        result_pane,
+                    );
+                } else {
+                    show_deploy_result(result_pane, true, &summary, explorer_url);
+                }
+            }
+            DeployEvent::Failed {
+                block_hash,
+                cost,
+                error_message,
+                cause,
+            } => {
+                append_line(
+                    text_display,
+                    &casper_multisig_tool::translate_with(
+                        "Execution failed in block {}, costing {} motes: {}",
+                        &[&block_hash.to_string(), &cost.to_string(), &error_message],
+                    ),
+                );
+                if let Some(cause) = &cause {
+                    append_line(
+                        text_display,
+                        &casper_multisig_tool::translate_with("Cause: {}", &[cause]),
+                    );
+                }
+                show_deploy_result(
+                    result_pane,
+                    false,
+                    &casper_multisig_tool::translate_with(
+                        "Deploy failed: {}",
+                        &[&cause.unwrap_or(error_message)],
+                    ),
+                    explorer_url,
+                );
+            }
+            DeployEvent::NotExecuted { reason } => {
+                append_line(
+                    text_display,
+                    &casper_multisig_tool::translate_with("Error: {}", &[&reason]),
+                );
+                show_deploy_result(
+                    result_pane,
+                    false,
+                    &casper_multisig_tool::translate_with("Deploy not executed: {}", &[&reason]),
+                    None,
+                );
+                // Allow retrying, e.g. against a different node.
+                retry = true;
+            }
+        }
+    }
+    retry
+}
+
+/// Shows the outcome of sending a deploy in the results pane, coloured by whether it succeeded.
+///
+/// If the deploy is on chain and the selected network has an explorer, the pane opens the deploy's
+/// `explorer_url` when clicked.
+fn show_deploy_result(
+    result_pane: &mut Button,
+    succeeded: bool,
+    summary: &str,
+    explorer_url: Option<&str>,
+) {
+    let color = if succeeded {
+        palette().passed
+    } else {
+        palette().failed
+    };
+    result_pane.set_color(Color::from_u32(color));
+    result_pane.set_label(summary);
+    match explorer_url {
+        Some(url) => {
+            result_pane.set_tooltip(&casper_multisig_tool::translate_with(
+                "{}\n\nClick to view the deploy at {}",
+                &[summary, url],
+            ));
+            let url = url.to_string();
+            result_pane.set_callback(move |_| {
+                if let Err(error) = casper_multisig_tool::open_in_explorer(&url) {
+                    dialog::alert_default(&error.to_string());
+                }
+            });
+        }
+        None => {
+            result_pane.set_tooltip(summary);
+            result_pane.set_callback(|_| ());
+        }
+    }
+    result_pane.show();
+    result_pane.redraw();
+}
+
+/// Checks the account which sent the executed setup deploy against the configuration, and shows
+/// the deploy's `summary` along with the VERIFIED or MISMATCH result.
+fn verify_account(
+    deploy_path: &Path,
+    node_address: &str,
+    summary: &str,
+    explorer_url: Option<&str>,
+    text_display: &mut TextDisplay,
+    result_pane: &mut Button,
+) {
+    append_line(text_display, "");
+    append_line(
+        text_display,
+        tr("Checking the account's keys and thresholds"),
+    );
+    match casper_multisig_tool::verify_deployed_account(deploy_path, node_address) {
+        Ok(verification) => {
+            if let Verification::Mismatch { mismatches } = &verification {
+                for mismatch in mismatches {
+                    append_line(
+                        text_display,
+                        &casper_multisig_tool::translate_with(
+                            "Mismatch: {}",
+                            &[&mismatch.to_string()],
+                        ),
+                    );
+                }
+            }
+            append_line(text_display, &verification.to_string());
+            show_deploy_result(
+                result_pane,
+                verification == Verification::Verified,
+                &format!("{}; {}", summary, verification),
+                explorer_url,
+            );
+        }
+        Err(error) => {
+            append_line(
+                text_display,
+                &casper_multisig_tool::translate_with("Error: {}", &[&error.to_string()]),
+            );
+            show_deploy_result(
+                result_pane,
+                true,
+                &casper_multisig_tool::translate_with(
+                    "{}, but the account couldn't be verified",
+                    &[summary],
+                ),
+                explorer_url,
+            );
+        }
+    }
+}
+
+/// Shows the modal window for choosing the options applied when compiling the smart contract.
+fn show_build_options_window() {
+    let width = 1000;
+    let label_width = 250;
+    let mut window = Window::default()
+        .with_size(width, 0)
+        .with_label(tr("Build options"));
+    window.make_modal(true);
+
+    let mut pack = Pack::new(
+        PADDING + label_width,
+        PADDING,
+        width - label_width - (2 * PADDING),
+        0,
+        None,
+    );
+    pack.set_spacing(PADDING);
+
+    let mut source_only = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Generate the source only, without compiling it"));
+    source_only.set_tooltip(tr(
+        "Writes the full project, but doesn't run cargo, e.g. to audit the source or build it on \
+        another machine.",
+    ));
+    source_only.set_checked(casper_multisig_tool::source_only_enabled());
+    source_only.set_callback(|check_button| {
+        casper_multisig_tool::set_source_only_enabled(check_button.is_checked())
+    });
+
+    let mut wasm_opt = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Optimize the compiled Wasm for size using wasm-opt -Oz"));
+    if !casper_multisig_tool::wasm_opt_available() {
+        wasm_opt.set_label(&casper_multisig_tool::translate_with(
+            "{} (wasm-opt not found)",
+            &[&wasm_opt.label()],
+        ));
+    }
+    wasm_opt.set_tooltip(tr(
+        "Deploy cost is proportional to the size of the module, so optimizing it saves CSPR.\n\n\
+        Requires wasm-opt from binaryen to be installed.",
+    ));
+    wasm_opt.set_checked(casper_multisig_tool::wasm_opt_enabled());
+    wasm_opt.set_callback(|check_button| {
+        casper_multisig_tool::set_wasm_opt_enabled(check_button.is_checked())
+    });
+
+    let mut wasm_strip = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Strip the compiled Wasm using wasm-strip"));
+    if !casper_multisig_tool::wasm_strip_available() {
+        wasm_strip.set_label(&casper_multisig_tool::translate_with(
+            "{} (wasm-strip not found)",
+            &[&wasm_strip.label()],
+        ));
+    }
+    wasm_strip.set_tooltip(tr(
+        "Removes custom sections such as debug info, which the contract doesn't need to run.\n\n\
+        Requires wasm-strip from wabt to be installed.",
+    ));
+    wasm_strip.set_checked(casper_multisig_tool::wasm_strip_enabled());
+    wasm_strip.set_callback(|check_button| {
+        casper_multisig_tool::set_wasm_strip_enabled(check_button.is_checked())
+    });
+
+    let mut reproducible_build = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Reproducible build: build with --locked --offline"));
+    reproducible_build.set_tooltip(tr(
+        "Writes a build manifest recording the toolchain and the hashes of the inputs and compiled \
+        Wasm, so that signers can rebuild the contract and check they get an identical hash.",
+    ));
+    reproducible_build.set_checked(casper_multisig_tool::reproducible_build_enabled());
+
+    let mut vendor_dependencies = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Vendor the dependencies into the project"));
+    vendor_dependencies.set_tooltip(tr(
+        "Copies the source of every dependency into the project's vendor directory so it can be \
+        rebuilt without network access.",
+    ));
+    vendor_dependencies.set_checked(casper_multisig_tool::vendor_dependencies_enabled());
+    if !reproducible_build.is_checked() {
+        vendor_dependencies.deactivate();
+    }
+    vendor_dependencies.set_callback(|check_button| {
+        casper_multisig_tool::set_vendor_dependencies_enabled(check_button.is_checked())
+    });
+
+    let mut vendor_dependencies_clone = vendor_dependencies.clone();
+    reproducible_build.set_callback(move |check_button| {
+        casper_multisig_tool::set_reproducible_build_enabled(check_button.is_checked());
+        if check_button.is_checked() {
+            vendor_dependencies_clone.activate();
+        } else {
+            vendor_dependencies_clone.deactivate();
+        }
+    });
+
+    let mut git_repo = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr(
+            "Initialize a git repository and commit the generated project",
+        ));
+    git_repo.set_tooltip(tr(
+        "Writes a .gitignore and creates an initial commit whose message summarizes the \
+        configuration.\n\nRequires git to be installed.",
+    ));
+    git_repo.set_checked(casper_multisig_tool::git_repo_enabled());
+    git_repo.set_callback(|check_button| {
+        casper_multisig_tool::set_git_repo_enabled(check_button.is_checked())
+    });
+
+    let mut cargo_casper = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Scaffold the project with cargo-casper"));
+    if !casper_multisig_tool::cargo_casper_available() {
+        cargo_casper.set_label(&casper_multisig_tool::translate_with(
+            "{} (cargo-casper not found)",
+            &[&cargo_casper.label()],
+        ));
+    }
+    cargo_casper.set_tooltip(tr(
+        "Gives the project the layout of the official template, with the contract in a contract \
+        directory.  The generated contract source, tests and Makefile replace the template's.",
+    ));
+    cargo_casper.set_checked(casper_multisig_tool::cargo_casper_enabled());
+    cargo_casper.set_callback(|check_button| {
+        casper_multisig_tool::set_cargo_casper_enabled(check_button.is_checked())
+    });
+
+    let mut ci_provider = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("CI workflow"));
+    ci_provider.set_align(Align::Left);
+    ci_provider.add_choice(&format!("{}|GitHub Actions|GitLab CI", tr("None")));
+    ci_provider.set_tooltip(tr(
+        "Generates a workflow which builds, strips and hashes the contract and uploads it as an \
+        artifact, so that teams committing the project get reproducible builds of what they sign.",
+    ));
+    ci_provider.set_value(match casper_multisig_tool::ci_provider() {
+        None => 0,
+        Some(CiProvider::GitHubActions) => 1,
+        Some(CiProvider::GitLabCi) => 2,
+    });
+    ci_provider.set_callback(|choice| {
+        casper_multisig_tool::set_ci_provider(match choice.value() {
+            1 => Some(CiProvider::GitHubActions),
+            2 => Some(CiProvider::GitLabCi),
+            _ => None,
+        })
+    });
+
+    let dependency_tooltip = "A version requirement (e.g. 1 or =1.2.0), a git revision \
+        (git+<url>#<revision>) or a local directory (path+<directory>)";
+    let casper_contract = new_option_input(
+        "casper-contract",
+        &casper_multisig_tool::casper_contract_dependency().to_string(),
+        dependency_tooltip,
+    );
+    let casper_types = new_option_input(
+        "casper-types",
+        &casper_multisig_tool::casper_types_dependency().to_string(),
+        dependency_tooltip,
+    );
+    let casper_engine_test_support = new_option_input(
+        "casper-engine-test-support",
+        &casper_multisig_tool::casper_engine_test_support_dependency().to_string(),
+        dependency_tooltip,
+    );
+    let toolchain = new_option_input(
+        "Rust toolchain",
+        &casper_multisig_tool::toolchain(),
+        "The rustup toolchain written to the project's rust-toolchain file",
+    );
+
+    let mut codegen_target = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Target network"));
+    codegen_target.set_align(Align::Left);
+    codegen_target.add_choice("Casper 1.x|Casper 2.0");
+    codegen_target.set_tooltip(tr(
+        "The Casper version of the network the account is on.  Casper 2.0 needs version 5 or \
+        later of the SDK crates, and no test crate is generated for it.\n\n\
+        Changing this resets the SDK versions and toolchain above to the target's defaults.",
+    ));
+    codegen_target.set_value(match casper_multisig_tool::codegen_target() {
+        CodegenTarget::Casper1 => 0,
+        CodegenTarget::Casper2 => 1,
+    });
+    let casper_contract_clone = casper_contract.clone();
+    let casper_types_clone = casper_types.clone();
+    let toolchain_clone = toolchain.clone();
+    codegen_target.set_callback(move |choice| {
+        let target = choice_codegen_target(choice);
+        let dependency = target.default_dependency().to_string();
+        casper_contract_clone.set_value(&dependency);
+        casper_types_clone.set_value(&dependency);
+        toolchain_clone.set_value(target.default_toolchain());
+    });
+
+    let mut protocol_profile = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Protocol version"));
+    protocol_profile.set_align(Align::Left);
+    protocol_profile.add_choice(&format!(
+        "{}|{}|{}",
+        tr("Custom"),
+        ProtocolProfile::ALL
+            .iter()
+            .map(ProtocolProfile::to_string)
+            .collect::<Vec<_>>()
+            .join("|"),
+        tr("Detect from a node...")
+    ));
+    protocol_profile.set_tooltip(tr(
+        "The protocol version of the network the account is on.  Choosing one sets the SDK \
+        versions, toolchain and target network above to match it.\n\n\
+        Detecting the version needs casper-client to be installed.",
+    ));
+    protocol_profile.set_value(
+        casper_multisig_tool::protocol_profile()
+            .and_then(|profile| {
+                ProtocolProfile::ALL
+                    .iter()
+                    .position(|&item| item == profile)
+            })
+            .map_or(0, |index| index as i32 + 1),
+    );
+    let casper_contract_clone = casper_contract.clone();
+    let casper_types_clone = casper_types.clone();
+    let casper_engine_test_support_clone = casper_engine_test_support.clone();
+    let toolchain_clone = toolchain.clone();
+    let mut codegen_target_clone = codegen_target.clone();
+    protocol_profile.set_callback(move |choice| {
+        let profile = match choice.value() {
+            value if value >= 1 && value as usize <= ProtocolProfile::ALL.len() => {
+                ProtocolProfile::ALL[value as usize - 1]
+            }
+            value if value as usize == ProtocolProfile::ALL.len() + 1 => {
+                match detect_protocol_profile() {
+                    Some(profile) => profile,
+                    None => {
+                        choice.set_value(0);
+                        return;
+                    }
+                }
+            }
+            _ => return,
+        };
+        let index = ProtocolProfile::ALL
+            .iter()
+            .position(|&item| item == profile)
+            .unwrap_or_default();
+        choice.set_value(index as i32 + 1);
+        casper_contract_clone.set_value(&profile.casper_contract_dependency().to_string());
+        casper_types_clone.set_value(&profile.casper_types_dependency().to_string());
+        if let Some(test_support) = profile.casper_engine_test_support_dependency() {
+            casper_engine_test_support_clone.set_value(&test_support.to_string());
+        }
+        toolchain_clone.set_value(profile.toolchain());
+        codegen_target_clone.set_value(match profile.codegen_target() {
+            CodegenTarget::Casper1 => 0,
+            CodegenTarget::Casper2 => 1,
+        });
+    });
+    let compile_timeout = new_option_input(
+        "Compile timeout (minutes)",
+        &casper_multisig_tool::compile_timeout()
+            .map(|timeout| (timeout.as_secs() / 60).to_string())
+            .unwrap_or_default(),
+        "Compilation is stopped if still running after this long.  Leave empty for no timeout.",
+    );
+    let max_deploy_size = new_option_input(
+        "Max deploy size (bytes)",
+        &casper_multisig_tool::max_deploy_size().to_string(),
+        "The target network's max_deploy_size chainspec setting.  A warning is shown if the \
+        compiled contract is too large to be deployed.",
+    );
+
+    let mut network = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Network"));
+    network.set_align(Align::Left);
+    network.add_choice(&network_choices());
+    network.set_tooltip(&casper_multisig_tool::translate_with(
+        "The network the node address, event stream URL and chain name prompts default to.  \
+        Choosing one sets the max deploy size above, and the protocol version too if the network \
+        has an expected one.\n\n\
+        Networks are saved in {}.",
+        &[&casper_multisig_tool::network_config_file()
+            .map(|file| file.display().to_string())
+            .unwrap_or_else(|| tr("memory only, as there's no config dir").to_string())],
+    ));
+    network.set_value(selected_network_index());
+
+    let mut node_status = Frame::default().with_size(0, BUTTON_HEIGHT);
+    node_status.set_align(Align::Left | Align::Inside | Align::Clip);
+    node_status.set_tooltip(tr(
+        "The status of the selected network's node.  Deploys whose chain name doesn't match the \
+        node's are never executed.",
+    ));
+    show_node_status(&node_status);
+
+    let mut fiat_costs = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Show estimated deploy costs in USD and EUR"));
+    fiat_costs.set_tooltip(tr(
+        "Fetches the current CSPR price from the price feed below when building a deploy, and \
+        shows the estimated cost in US dollars and euros next to the amount in motes.",
+    ));
+    fiat_costs.set_checked(casper_multisig_tool::price_feed_url().is_some());
+    let mut price_feed = new_option_input(
+        "Price feed URL",
+        &casper_multisig_tool::price_feed_url()
+            .unwrap_or_else(|| casper_multisig_tool::default_price_feed_url().to_string()),
+        "The endpoint the CSPR price is fetched from, CoinGecko by default.\n\n\
+        It must return JSON in the form of CoinGecko's, i.e. {\"casper-network\": {\"usd\": ..., \
+        \"eur\": ...}}, or just the inner object.",
+    );
+    set_active(&mut price_feed, fiat_costs.is_checked());
+    let mut price_feed_clone = price_feed.clone();
+    fiat_costs.set_callback(move |check_button| {
+        set_active(&mut price_feed_clone, check_button.is_checked());
+        casper_multisig_tool::set_price_feed_url(
+            check_button
+                .is_checked()
+                .then(|| price_feed_clone.value())
+                .as_deref(),
+        );
+    });
+    let fiat_costs_clone = fiat_costs.clone();
+    price_feed.set_callback(move |input| {
+        if fiat_costs_clone.is_checked() {
+            casper_multisig_tool::set_price_feed_url(Some(&input.value()));
+        }
+    });
+
+    let max_deploy_size_clone = max_deploy_size.clone();
+    let mut protocol_profile_clone = protocol_profile.clone();
+    let node_status_clone = node_status.clone();
+    network.set_callback(move |choice| {
+        let networks = casper_multisig_tool::network_profiles();
+        let name = match choice.value() {
+            0 => None,
+            value if value as usize <= networks.len() => {
+                Some(networks[value as usize - 1].name.clone())
+            }
+            _ => match add_network_profile() {
+                Some(name) => {
+                    choice.clear();
+                    choice.add_choice(&network_choices());
+                    Some(name)
+                }
+                None => {
+                    choice.set_value(selected_network_index());
+                    return;
+                }
+            },
+        };
+        if let Err(error) = casper_multisig_tool::select_network(name.as_deref()) {
+            dialog::alert_default(&error.to_string());
+        }
+        choice.set_value(selected_network_index());
+        show_node_status(&node_status_clone);
+        max_deploy_size_clone.set_value(&casper_multisig_tool::max_deploy_size().to_string());
+        let profile = casper_multisig_tool::selected_network()
+            .and_then(|network| network.protocol_version)
+            .and_then(|version| ProtocolProfile::for_protocol_version(&version).ok());
+        if let Some(index) = profile.and_then(|profile| {
+            ProtocolProfile::ALL
+                .iter()
+                .position(|&item| item == profile)
+        }) {
+            // Updates the SDK versions, toolchain and target network inputs to match.
+            protocol_profile_clone.set_value(index as i32 + 1);
+            protocol_profile_clone.do_callback();
+        }
+    });
+
+    let mut target_dir_location = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Cargo target dir"));
+    target_dir_location.set_align(Align::Left);
+    target_dir_location.add_choice(
+        &["Inside each project", "Shared dir", "Shared temporary dir"]
+            .iter()
+            .map(|location| tr(location))
+            .collect::<Vec<_>>()
+            .join("|"),
+    );
+
+    target_dir_location.set_tooltip(tr("Where the build output goes.\n\n\
+        A shared dir avoids recompiling the contract's dependencies from scratch for every \
+        generated project.  A temporary one is eventually cleaned up by the OS."));
+    let current_target_dir_location = casper_multisig_tool::target_dir_location();
+    let shared_target_dir_value = match &current_target_dir_location {
+        TargetDirLocation::Shared { path } => Some(path.clone()),
+        TargetDirLocation::Project | TargetDirLocation::Temp => None,
+    };
+    target_dir_location.set_value(match current_target_dir_location {
+        TargetDirLocation::Project => 0,
+        TargetDirLocation::Shared { .. } => 1,
+        TargetDirLocation::Temp => 2,
+    });
+    let mut shared_target_dir_path = new_option_input(
+        "Shared target dir",
+        &shared_target_dir_value
+            .or_else(casper_multisig_tool::default_shared_target_dir)
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default(),
+        "The directory used as CARGO_TARGET_DIR for every build",
+    );
+    set_active(
+        &mut shared_target_dir_path,
+        target_dir_location.value() == 1,
+    );
+    let mut shared_target_dir_path_clone = shared_target_dir_path.clone();
+    target_dir_location.set_callback(move |choice| {
+        set_active(&mut shared_target_dir_path_clone, choice.value() == 1)
+    });
+
+    let mut sccache = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Use sccache to cache compilation results"));
+    if !casper_multisig_tool::sccache_available() {
+        sccache.set_label(&casper_multisig_tool::translate_with(
+            "{} (sccache not found)",
+            &[&sccache.label()],
+        ));
+    }
+    sccache.set_tooltip(tr(
+        "Sets RUSTC_WRAPPER=sccache.  Not used with the Docker backend.",
+    ));
+    sccache.set_checked(casper_multisig_tool::sccache_enabled());
+    sccache.set_callback(|check_button| {
+        casper_multisig_tool::set_sccache_enabled(check_button.is_checked())
+    });
+
+    let mut docker = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr(
+            "Compile inside Docker rather than with the host toolchain",
+        ));
+    docker.set_tooltip(tr(
+        "Runs the build via docker run, so only Docker needs to be installed, and every signer \
+        builds with an identical toolchain.",
+    ));
+    let docker_image_value = match casper_multisig_tool::compile_backend() {
+        CompileBackend::Host => {
+            docker.set_checked(false);
+            String::new()
+        }
+        CompileBackend::Docker { image } => {
+            docker.set_checked(true);
+            image.unwrap_or_default()
+        }
+    };
+    let mut docker_image = new_option_input(
+        "Docker image",
+        &docker_image_value,
+        "The image to compile in, which must contain the toolchain and wasm32 target.\n\n\
+        Leave empty to build an image from a Dockerfile generated in the project.",
+    );
+    if !docker.is_checked() {
+        docker_image.deactivate();
+    }
+    let mut docker_image_clone = docker_image.clone();
+    docker.set_callback(move |check_button| {
+        if check_button.is_checked() {
+            docker_image_clone.activate();
+        } else {
+            docker_image_clone.deactivate();
+        }
+    });
+
+    pack.end();
+    let rows = pack.children();
+    pack.set_size(
+        pack.width(),
+        (rows * BUTTON_HEIGHT) + ((rows - 1) * pack.spacing()),
+    );
+
+    let clean_button_width = 160;
+    let mut clean_button = Button::new(
+        PADDING,
+        pack.y() + pack.height() + PADDING,
+        clean_button_width,
+        BUTTON_HEIGHT,
+        tr("Clean build files"),
+    );
+    clean_button.set_color(Color::from_u32(palette().button));
+    clean_button.set_tooltip(tr(
+        "Removes the build output of the current project, including the compiled contract, and \
+        the shared target dir if one is used.",
+    ));
+    clean_button.set_callback(|_| match casper_multisig_tool::clean_build_artifacts() {
+        Ok(removed) if removed.is_empty() => {
+            dialog::message_default(tr("No build files to remove"))
+        }
+        Ok(removed) => dialog::message_default(&casper_multisig_tool::translate_with(
+            "Removed:\n{}",
+            &[&removed
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join("\n")],
+        )),
+        Err(error) => dialog::alert_default(&error.to_string()),
+    });
+
+    let button_width = 100;
+    let mut done_button = Button::new(
+        width - PADDING - button_width,
+        pack.y() + pack.height() + PADDING,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Done"),
+    );
+    done_button.set_color(Color::from_u32(palette().button));
+    done_button.set_callback(move |button| {
+        let value = compile_timeout.value();
+        match value.trim() {
+            "" => casper_multisig_tool::set_compile_timeout(None),
+            minutes => match minutes.parse::<u64>() {
+                Ok(minutes) if minutes > 0 => casper_multisig_tool::set_compile_timeout(Some(
+                    Duration::from_secs(minutes * 60),
+                )),
+                _ => {
+                    dialog::alert_default(&casper_multisig_tool::translate_with(
+                        "failed to parse '{}' as a compile timeout: expected a positive number \
+                        of minutes",
+                        &[minutes],
+                    ));
+                    return;
+                }
+            },
+        }
+        let value = max_deploy_size.value();
+        match value.trim().parse() {
+            Ok(size) => casper_multisig_tool::set_max_deploy_size(size),
+            Err(error) => {
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "failed to parse '{}' as a max deploy size: {}",
+                    &[value.trim(), &error.to_string()],
+                ));
+                return;
+            }
+        }
+        let result = casper_contract
+            .value()
+            .parse()
+            .and_then(|casper_contract| Ok((casper_contract, casper_types.value().parse()?)))
+            .and_then(|(casper_contract, casper_types)| {
+                casper_multisig_tool::set_dependencies_and_toolchain(
+                    casper_contract,
+                    casper_types,
+                    &toolchain.value(),
+                )
+            })
+            .and_then(|_| {
+                casper_multisig_tool::set_casper_engine_test_support_dependency(
+                    casper_engine_test_support.value().parse()?,
+                )
+            })
+            .and_then(|_| {
+                let compile_backend = if docker.is_checked() {
+                    let image = docker_image.value().trim().to_string();
+                    CompileBackend::Docker {
+                        image: if image.is_empty() { None } else { Some(image) },
+                    }
+                } else {
+                    CompileBackend::Host
+                };
+                casper_multisig_tool::set_compile_backend(compile_backend)
+            });
+        if result.is_ok() {
+            let target_dir = shared_target_dir_path.value();
+            let target_dir = target_dir.trim();
+            let location = match target_dir_location.value() {
+                1 if !target_dir.is_empty() => TargetDirLocation::Shared {
+                    path: PathBuf::from(target_dir),
+                },
+                2 => TargetDirLocation::Temp,
+                _ => TargetDirLocation::Project,
+            };
+            casper_multisig_tool::set_target_dir_location(location);
+            casper_multisig_tool::set_codegen_target(choice_codegen_target(&codegen_target));
+        }
+        if let Err(error) = result {
+            dialog::alert_default(error.to_string().as_str());
+            return;
+        }
+        start_prewarm();
+        let mut window = button.window().unwrap();
+        window.hide();
+    });
+
+    window.end();
+    window.set_size(width, done_button.y() + done_button.height() + PADDING);
+    window.show();
+}
+
+/// Shows the modal window for choosing the preferences, which are saved and applied at startup and
+/// to every new tab.
+fn show_preferences_window() {
+    let width = 1000;
+    let label_width = 250;
+    let mut window = Window::default()
+        .with_size(width, 0)
+        .with_label(tr("Preferences"));
+    window.make_modal(true);
+
+    let mut pack = Pack::new(
+        PADDING + label_width,
+        PADDING,
+        width - label_width - (2 * PADDING),
+        0,
+        None,
+    );
+    pack.set_spacing(PADDING);
+
+    let preferences = casper_multisig_tool::preferences();
+    let project_dir = new_option_input(
+        "Default project directory",
+        &preferences.project_dir.display().to_string(),
+        "The directory offered for saving a new smart contract.  Leave empty for the home \
+        directory.",
+    );
+    let contract_name = new_option_input(
+        "Default contract name",
+        &preferences.contract_name,
+        "The name offered for a new smart contract.  Leave empty for multisig_setup_contract.",
+    );
+    let mut authors = MultilineInput::default()
+        .with_size(0, 3 * BUTTON_HEIGHT)
+        .with_label(tr("Authors\n(one per line)"));
+    authors.set_align(Align::Left);
+    authors.set_tooltip(tr(
+        "The authors written to the generated Cargo.toml, e.g. Jane Doe <jane@example.com>",
+    ));
+    authors.set_text_font(Font::Courier);
+    authors.set_text_size(text_size());
+    authors.set_value(&preferences.authors.join("\n"));
+    let toolchain = new_option_input(
+        "Rust toolchain",
+        &preferences.toolchain,
+        "The rustup toolchain written to the project's rust-toolchain file.  Leave empty for the \
+        target network's default.",
+    );
+
+    let networks = casper_multisig_tool::network_profiles();
+    let mut network = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Network"));
+    network.set_align(Align::Left);
+    let mut network_choices = vec![tr("Last selected").to_string()];
+    network_choices.extend(
+        networks
+            .iter()
+            // Unescaped, these would separate entries or start submenus.
+            .map(|network| network.name.replace('/', "\\/").replace('|', "\\|")),
+    );
+    network.add_choice(&network_choices.join("|"));
+    network.set_tooltip(tr(
+        "The network selected at startup and in new tabs.  'Last selected' keeps the one last \
+        chosen in the Network menu or under \"Build options\".",
+    ));
+    network.set_value(
+        preferences
+            .network
+            .as_ref()
+            .and_then(|name| networks.iter().position(|network| &network.name == name))
+            .map_or(0, |index| index as i32 + 1),
+    );
+
+    let mut theme = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Theme"));
+    theme.set_align(Align::Left);
+    theme.set_tooltip(tr(
+        "Whether the widgets, generated code and compile output are light or dark.  High contrast \
+        is white on black with bright highlighting, and System follows the OS's appearance where \
+        it can be detected, and is light otherwise.",
+    ));
+    theme.add_choice(
+        &Theme::ALL
+            .iter()
+            .map(|theme| tr(&theme.to_string()).to_string())
+            .collect::<Vec<_>>()
+            .join("|"),
+    );
+    theme.set_value(
+        Theme::ALL
+            .iter()
+            .position(|&item| item == preferences.theme)
+            .unwrap_or_default() as i32,
+    );
+    let mut language = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Language"));
+    language.set_align(Align::Left);
+    language.set_tooltip(tr(
+        "The language the tool is shown in.  System follows the OS's locale where it's one of the \
+        others, and is English otherwise.\n\nApplies after a restart.",
+    ));
+    language.add_choice(
+        &Language::ALL
+            .iter()
+            .map(|language| match language {
+                Language::System => tr("System").to_string(),
+                language => language.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("|"),
+    );
+    language.set_value(
+        Language::ALL
+            .iter()
+            .position(|&item| item == preferences.language)
+            .unwrap_or_default() as i32,
+    );
+    let font_size = new_option_input(
+        "Font size",
+        &preferences.font_size.to_string(),
+        "The size of the text in the keys, labels, weights and other values, from 8 to 48.  The \
+        generated code and command output are a little smaller.\n\n\
+        Applies to what's shown afterwards, such as new keys, tabs and windows, and to everything \
+        after a restart.",
+    );
+    let ui_scale = new_option_input(
+        "UI scale (%)",
+        &preferences
+            .ui_scale
+            .map(|ui_scale| ui_scale.to_string())
+            .unwrap_or_default(),
+        "The scale of the whole UI, from 50% to 400%, e.g. 200 on a 4K display.  Leave empty for \
+        the system's scale.",
+    );
+
+    let mut wasm_strip = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Strip the compiled Wasm using wasm-strip"));
+    wasm_strip.set_tooltip(tr("Requires wasm-strip from wabt to be installed."));
+    wasm_strip.set_checked(preferences.run_wasm_strip);
+    let mut wasm_opt = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Optimize the compiled Wasm for size using wasm-opt -Oz"));
+    wasm_opt.set_tooltip(tr("Requires wasm-opt from binaryen to be installed."));
+    wasm_opt.set_checked(preferences.run_wasm_opt);
+
+    pack.end();
+    let rows = pack.children();
+    let rows_height: i32 = (0..rows)
+        .filter_map(|index| pack.child(index))
+        .map(|child| child.height())
+        .sum();
+    pack.set_size(pack.width(), rows_height + ((rows - 1) * pack.spacing()));
+
+    let mut note = Frame::new(
+        PADDING,
+        pack.y() + pack.height() + PADDING,
+        width - (2 * PADDING),
+        BUTTON_HEIGHT,
+        None,
+    );
+    note.set_align(Align::Left | Align::Inside | Align::Clip);
+    let saved_in = casper_multisig_tool::preferences_file()
+        .map(|file| file.display().to_string())
+        .unwrap_or_else(|| tr("memory only, as there's no config dir").to_string());
+    note.set_label(&casper_multisig_tool::translate_with(
+        "Saved in {}",
+        &[&saved_in],
+    ));
+
+    let button_width = 100;
+    let mut cancel_button = Button::new(
+        width - PADDING - button_width,
+        note.y() + note.height() + PADDING,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Cancel"),
+    );
+    cancel_button.set_color(Color::from_u32(palette().button));
+    cancel_button.set_callback(|button| button.window().unwrap().hide());
+    let mut save_button = Button::new(
+        cancel_button.x() - PADDING - button_width,
+        cancel_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Save"),
+    );
+    save_button.set_color(Color::from_u32(palette().button));
+    save_button.set_callback(move |button| {
+        let font_size = match font_size.value().trim().parse::<u8>() {
+            Ok(font_size) => font_size,
+            Err(_) => {
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "failed to parse '{}' as a font size: expected a number of pixels",
+                    &[font_size.value().trim()],
+                ));
+                return;
+            }
+        };
+        let ui_scale = match ui_scale.value().trim() {
+            "" => None,
+            percent => match percent.trim_end_matches('%').trim().parse::<u16>() {
+                Ok(percent) => Some(percent),
+                Err(_) => {
+                    dialog::alert_default(&casper_multisig_tool::translate_with(
+                        "failed to parse '{}' as a UI scale: expected a percentage",
+                        &[percent],
+                    ));
+                    return;
+                }
+            },
+        };
+        let theme = Theme::ALL
+            .get(theme.value() as usize)
+            .copied()
+            .unwrap_or_default();
+        let language = Language::ALL
+            .get(language.value() as usize)
+            .copied()
+            .unwrap_or_default();
+        let preferences = Preferences {
+            project_dir: PathBuf::from(project_dir.value().trim()),
+            contract_name: contract_name.value().trim().to_string(),
+            authors: authors
+                .value()
+                .lines()
+                .map(str::trim)
+                .filter(|author| !author.is_empty())
+                .map(str::to_string)
+                .collect(),
+            toolchain: toolchain.value().trim().to_string(),
+            network: match network.value() {
+                value if value >= 1 => networks
+                    .get(value as usize - 1)
+                    .map(|network| network.name.clone()),
+                _ => None,
+            },
+            theme,
+            language,
+            font_size,
+            ui_scale,
+            run_wasm_strip: wasm_strip.is_checked(),
+            run_wasm_opt: wasm_opt.is_checked(),
+        };
+        if let Err(error) = casper_multisig_tool::set_preferences(preferences) {
+            dialog::alert_default(&error.to_string());
+            return;
+        }
+        apply_theme();
+        apply_ui_scale();
+        button.window().unwrap().hide();
+    });
+
+    window.end();
+    window.set_size(width, save_button.y() + save_button.height() + PADDING);
+    window.show();
+}
+
+/// Returns the size of the text in value inputs and outputs.
+fn text_size() -> i32 {
+    i32::from(casper_multisig_tool::preferences().font_size)
+}
+
+/// Returns the size of the text in the generated code and command output, a little smaller than
+/// the values'.
+fn code_text_size() -> i32 {
+    text_size() - 2
+}
+
+/// Applies the preferred UI scale to every screen, or restores the system's if none is preferred.
+fn apply_ui_scale() {
+    static SYSTEM_SCALES: OnceCell<Vec<f32>> = OnceCell::new();
+    let system_scales =
+        SYSTEM_SCALES.get_or_init(|| (0..app::screen_count()).map(app::screen_scale).collect());
+    let ui_scale = casper_multisig_tool::preferences().ui_scale;
+    for (screen, system_scale) in system_scales.iter().enumerate() {
+        let scale = ui_scale.map_or(*system_scale, |percent| f32::from(percent) / 100.0);
+        app::set_screen_scale(screen as i32, scale);
+    }
+}
+
+/// Returns the colours of the current theme.
+fn palette() -> &'static Palette {
+    if HIGH_CONTRAST_THEME.load(Ordering::Relaxed) {
+        &HIGH_CONTRAST_PALETTE
+    } else if DARK_THEME.load(Ordering::Relaxed) {
+        &DARK_PALETTE
+    } else {
+        &LIGHT_PALETTE
+    }
+}
+
+/// Applies the preferred theme's colours to the default colours, and to every widget of the shown
+/// windows which was given one of the previous theme's colours.
+fn apply_theme() {
+    let previous = palette();
+    DARK_THEME.store(casper_multisig_tool::dark_theme(), Ordering::Relaxed);
+    HIGH_CONTRAST_THEME.store(
+        casper_multisig_tool::high_contrast_theme(),
+        Ordering::Relaxed,
+    );
+    let current = palette();
+
+    let rgb = |color: u32| ((color >> 16) as u8, (color >> 8) as u8, color as u8);
+    let (red, green, blue) = rgb(current.background);
+    app::background(red, green, blue);
+    let (red, green, blue) = rgb(current.input_background);
+    app::background2(red, green, blue);
+    let (red, green, blue) = rgb(current.text);
+    app::foreground(red, green, blue);
+
+    if !ptr::eq(previous, current) {
+        for mut window in app::windows().unwrap_or_default() {
+            recolor_widget(&mut window, previous, current);
+        }
+    }
+    app::redraw();
+}
+
+/// Returns the styles of the Rust output's plain text, keywords, comments and literals, selected by
+/// the style chars 'A' to 'D' respectively.
+fn rust_style_table() -> Vec<StyleTableEntry> {
+    let entry = |color: u32, font: Font| StyleTableEntry {
+        color: Color::from_u32(color),
+        font,
+        size: code_text_size(),
+    };
+    vec![
+        entry(palette().text, Font::Courier),
+        entry(palette().code_keyword, Font::CourierBold),
+        entry(palette().code_comment, Font::CourierItalic),
+        entry(palette().code_literal, Font::Courier),
+    ]
+}
+
+/// Returns the styles of the compile output, selected by `PLAIN_STYLE`, `STDERR_STYLE` and
+/// `STATUS_STYLE`.
+fn compile_output_style_table() -> Vec<StyleTableEntry> {
+    let entry = |color: u32, font: Font| StyleTableEntry {
+        color: Color::from_u32(color),
+        font,
+        size: code_text_size(),
+    };
+    vec![
+        entry(palette().text, Font::Courier),
+        entry(palette().stderr_text, Font::Courier),
+        entry(palette().text, Font::CourierBold),
+    ]
+}
+
+/// Swaps any of `previous`'s colours given to `widget` and its descendants for `current`'s.
+fn recolor_widget(widget: &mut dyn WidgetExt, previous: &Palette, current: &Palette) {
+    let colors = [
+        (previous.button, current.button),
+        (previous.passed, current.passed),
+        (previous.failed, current.failed),
+        (previous.code_background, current.code_background),
+        (previous.main_key_background, current.main_key_background),
+    ];
+    if let Some(&(_, color)) = colors
+        .iter()
+        .find(|(previous_color, _)| widget.color() == Color::from_u32(*previous_color))
+    {
+        widget.set_color(Color::from_u32(color));
+        // Only the Rust output has the code background, and its highlighting is coloured too.
+        if color == current.code_background {
+            let mut rust_output = unsafe { TextDisplay::from_widget_ptr(widget.as_widget_ptr()) };
+            let style_buffer = rust_output.style_buffer();
+            rust_output.set_highlight_data(style_buffer, rust_style_table());
+        }
+    }
+    if widget.label_color() == Color::from_u32(previous.warning_text) {
+        widget.set_label_color(Color::from_u32(current.warning_text));
+    }
+    if let Some(group) = widget.as_group() {
+        for index in 0..group.children() {
+            if let Some(mut child) = group.child(index) {
+                recolor_widget(&mut *child, previous, current);
+            }
+        }
+    }
+}
+
+/// Shows the modal window for choosing the options applied to the generated contract and its
+/// deployment, then updates `main_output_pack` with them, e.g. the keys' labels.
+fn show_contract_options_window(main_output_pack: MainOutputPack) {
+    let width = 1000;
+    let label_width = 250;
+    let mut window = Window::default()
+        .with_size(width, 0)
+        .with_label(tr("Contract options"));
+    window.make_modal(true);
+
+    let mut pack = Pack::new(
+        PADDING + label_width,
+        PADDING,
+        width - label_width - (2 * PADDING),
+        0,
+        None,
+    );
+    pack.set_spacing(PADDING);
+
+    let mut contract_mode = Choice::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Contract mode"));
+    contract_mode.set_align(Align::Left);
+    contract_mode.add_choice(
+        &[
+            "Multisig account setup",
+            "Remove keys only",
+            "Update thresholds only",
+            "Revert to single key",
+        ]
+        .iter()
+        .map(|mode| tr(mode))
+        .collect::<Vec<_>>()
+        .join("|"),
+    );
+    contract_mode.set_tooltip(tr(
+        "What the generated contract does to the main account.\n\n\
+        'Remove keys only' removes the listed associated keys, leaving the other keys and the \
+        action thresholds untouched.\n\n\
+        'Update thresholds only' sets the given action thresholds, leaving the associated keys \
+        untouched.\n\n\
+        'Revert to single key' returns the configured multisig account to the control of the \
+        recovery key alone.  Generate and keep it before sending the setup deploy.",
+    ));
+    let mut keys_to_remove = MultilineInput::default()
+        .with_size(0, 3 * BUTTON_HEIGHT)
+        .with_label(tr("Keys to remove\n(one account hash per line)"));
+    keys_to_remove.set_align(Align::Left);
+    keys_to_remove.set_tooltip(tr(
+        "The formatted account hashes of the associated keys to remove.",
+    ));
+    keys_to_remove.set_text_font(Font::Courier);
+    keys_to_remove.set_text_size(text_size());
+    let new_key_management_threshold = new_option_input(
+        "New key-management threshold",
+        "",
+        "The new key-management threshold.  Leave blank to keep the current one.",
+    );
+    let new_deployment_threshold = new_option_input(
+        "New deploy-execution threshold",
+        "",
+        "The new deploy-execution threshold.  Leave blank to keep the current one.",
+    );
+    let recovery_key = new_option_input(
+        "Recovery key",
+        "",
+        "The formatted account hash of the key left in control of the account.  Every other \
+        configured associated key is removed and both action thresholds are set to 1.",
+    );
+    let mut idempotent = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Make the setup contract idempotent"));
+    idempotent.set_tooltip(tr(
+        "Update keys which are already associated rather than failing, and skip keys which are \
+        already removed, so the setup deploy can be sent again to a partially configured account.",
+    ));
+    idempotent.set_checked(casper_multisig_tool::idempotent_enabled());
+    let mut runtime_args_template = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr(
+            "Read the keys, weights and thresholds from runtime args",
+        ));
+    runtime_args_template.set_tooltip(tr(
+        "Generate the generic setup contract, which reads its configuration from the deploy's \
+        runtime args rather than having it baked in, so one audited Wasm can be reused for many \
+        accounts.\n\n\
+        The runtime args matching the configuration are written to session_args.txt.",
+    ));
+    runtime_args_template.set_checked(casper_multisig_tool::runtime_args_template_enabled());
+    let mode_index = match casper_multisig_tool::contract_mode() {
+        ContractMode::Setup => 0,
+        ContractMode::RemoveKeys { account_hashes } => {
+            keys_to_remove.set_value(&account_hashes.join("\n"));
+            1
+        }
+        ContractMode::UpdateThresholds {
+            key_management,
+            deployment,
+        } => {
+            let to_string = |threshold: Option<u8>| threshold.map(|t| t.to_string());
+            new_key_management_threshold.set_value(&to_string(key_management).unwrap_or_default());
+            new_deployment_threshold.set_value(&to_string(deployment).unwrap_or_default());
+            2
+        }
+        ContractMode::RevertToSingleKey {
+            recovery_key: account_hash,
+        } => {
+            recovery_key.set_value(&account_hash);
+            3
+        }
+    };
+    contract_mode.set_value(mode_index);
+    let mut mode_inputs = (
+        keys_to_remove.clone(),
+        new_key_management_threshold.clone(),
+        new_deployment_threshold.clone(),
+        recovery_key.clone(),
+        idempotent.clone(),
+        runtime_args_template.clone(),
+    );
+    let mut activate_mode_inputs = move |mode_index: i32| {
+        set_active(&mut mode_inputs.0, mode_index == 1);
+        set_active(&mut mode_inputs.1, mode_index == 2);
+        set_active(&mut mode_inputs.2, mode_index == 2);
+        set_active(&mut mode_inputs.3, mode_index == 3);
+        set_active(&mut mode_inputs.4, mode_index == 0);
+        set_active(&mut mode_inputs.5, mode_index == 0);
+    };
+    activate_mode_inputs(mode_index);
+    contract_mode.set_callback(move |choice| activate_mode_inputs(choice.value()));
+
+    let mut named_keys = MultilineInput::default()
+        .with_size(0, 4 * BUTTON_HEIGHT)
+        .with_label(tr("Named keys\n(one 'name = key' per line)"));
+    named_keys.set_align(Align::Left);
+    named_keys.set_value(
+        &casper_multisig_tool::named_keys()
+            .iter()
+            .map(|(name, key)| format!("{} = {}", name, key))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    named_keys.set_tooltip(tr(
+        "Named keys to be stored in the main account by the contract, e.g.\n\n\
+        my-contract = hash-<hex>\n\n\
+        Keys may be formatted account hashes, hashes or URefs.  A URef must already be accessible \
+        to the main account, otherwise the deploy will fail.",
+    ));
+    named_keys.set_text_font(Font::Courier);
+    named_keys.set_text_size(text_size());
+
+    let mut key_labels = MultilineInput::default()
+        .with_size(0, 3 * BUTTON_HEIGHT)
+        .with_label(tr("Key labels\n(one 'account hash = label' per line)"));
+    key_labels.set_align(Align::Left);
+    key_labels.set_value(
+        &casper_multisig_tool::key_labels()
+            .iter()
+            .map(|(account_hash, label)| format!("{} = {}", account_hash, label))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    key_labels.set_tooltip(tr("Labels identifying who holds each key, e.g.\n\n\
+        account-hash-<hex> = Alice's hardware wallet\n\n\
+        They are recorded with the keys in the provenance header at the top of the generated \
+        sources, and don't affect the contract."));
+    key_labels.set_text_font(Font::Courier);
+    key_labels.set_text_size(text_size());
+
+    let current_initial_transfer = casper_multisig_tool::initial_transfer();
+    let mut initial_transfer = CheckButton::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr("Fund the main account with a transfer before deploying"));
+    initial_transfer.set_tooltip(tr(
+        "The setup deploy is paid for from the main account, so the account must be funded \
+        first.\n\nThe transfer is described in the generated README and Makefile, and can be sent \
+        once the contract has been generated.",
+    ));
+    initial_transfer.set_checked(current_initial_transfer.is_some());
+    let (amount, source_secret_key, target_public_key) =
+        current_initial_transfer.unwrap_or_default();
+    let transfer_amount = new_option_input(
+        "Transfer amount (motes)",
+        &amount,
+        "The amount to transfer to the main account, in motes.  Must be at least 2.5 CSPR.",
+    );
+    let transfer_source_secret_key = new_option_input(
+        "Source secret key",
+        &source_secret_key,
+        "The path to the secret key file of the account sending the funds.",
+    );
+    let transfer_target_public_key = new_option_input(
+        "Main account public key",
+        &target_public_key,
+        "The hex-encoded public key of the main account, which receives the funds.",
+    );
+    let mut transfer_inputs = [
+        transfer_amount.clone(),
+        transfer_source_secret_key.clone(),
+        transfer_target_public_key.clone(),
+    ];
+    if !initial_transfer.is_checked() {
+        transfer_inputs
+            .iter_mut()
+            .for_each(|input| input.deactivate());
+    }
+    initial_transfer.set_callback(move |check_button| {
+        for input in transfer_inputs.iter_mut() {
+            if check_button.is_checked() {
+                input.activate();
+            } else {
+                input.deactivate();
+            }
+        }
+    });
+
+    pack.end();
+    let rows = pack.children();
+    let rows_height: i32 = (0..rows)
+        .filter_map(|index| pack.child(index))
+        .map(|child| child.height())
+        .sum();
+    pack.set_size(pack.width(), rows_height + ((rows - 1) * pack.spacing()));
+
+    let button_width = 100;
+    let mut done_button = Button::new(
+        width - PADDING - button_width,
+        pack.y() + pack.height() + PADDING,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Done"),
+    );
+    done_button.set_color(Color::from_u32(palette().button));
+    done_button.set_callback(move |button| {
+        let mode = match contract_mode.value() {
+            1 => Ok(ContractMode::RemoveKeys {
+                account_hashes: keys_to_remove
+                    .value()
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            }),
+            2 => parse_optional_threshold(&new_key_management_threshold.value()).and_then(
+                |key_management| {
+                    Ok(ContractMode::UpdateThresholds {
+                        key_management,
+                        deployment: parse_optional_threshold(&new_deployment_threshold.value())?,
+                    })
+                },
+            ),
+            3 => Ok(ContractMode::RevertToSingleKey {
+                recovery_key: recovery_key.value().trim().to_string(),
+            }),
+            _ => Ok(ContractMode::Setup),
+        };
+        casper_multisig_tool::set_idempotent_enabled(idempotent.is_checked());
+        casper_multisig_tool::set_runtime_args_template_enabled(runtime_args_template.is_checked());
+        let result = mode
+            .and_then(|mode| {
+                casper_multisig_tool::set_contract_mode(mode).map_err(|error| error.to_string())
+            })
+            .and_then(|_| parse_assignments(&named_keys.value(), "name = key"))
+            .and_then(|named_keys| {
+                casper_multisig_tool::set_named_keys(named_keys).map_err(|error| error.to_string())
+            })
+            .and_then(|_| parse_assignments(&key_labels.value(), "account hash = label"))
+            .and_then(|key_labels| {
+                casper_multisig_tool::set_key_labels(key_labels).map_err(|error| error.to_string())
+            })
+            .and_then(|_| {
+                let amount = transfer_amount.value();
+                let source_secret_key = transfer_source_secret_key.value();
+                let target_public_key = transfer_target_public_key.value();
+                let transfer = if initial_transfer.is_checked() {
+                    Some((
+                        amount.as_str(),
+                        source_secret_key.as_str(),
+                        target_public_key.as_str(),
+                    ))
+                } else {
+                    None
+                };
+                casper_multisig_tool::set_initial_transfer(transfer)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            dialog::alert_default(error.as_str());
+            return;
+        }
+        main_output_pack.redraw_window();
+        let mut window = button.window().unwrap();
+        window.hide();
+    });
+
+    window.end();
+    window.set_size(width, done_button.y() + done_button.height() + PADDING);
+    window.show();
+}
+
+/// Shows a dialog for choosing a common scheme, e.g. 2-of-3, which sets the weights of the keys
+/// already added and the action thresholds, describing the policy it gives them.
+fn show_scheme_template_window(main_output_pack: MainOutputPack) {
+    let width = 800;
+    let label_width = 120;
+    let mut window = Window::default()
+        .with_size(width, 0)
+        .with_label(tr("Apply scheme template"));
+    window.make_modal(true);
+
+    let mut template_choice = Choice::new(
+        PADDING + label_width,
+        PADDING,
+        width - label_width - (2 * PADDING),
+        BUTTON_HEIGHT,
+        "Template",
+    );
+    template_choice.set_align(Align::Left);
+    template_choice.add_choice(
+        &SchemeTemplate::ALL
+            .iter()
+            .map(SchemeTemplate::to_string)
+            .collect::<Vec<_>>()
+            .join("|"),
+    );
+    template_choice.set_value(0);
+
+    let mut policy = Frame::new(
+        PADDING,
+        template_choice.y() + template_choice.height() + PADDING,
+        width - (2 * PADDING),
+        3 * BUTTON_HEIGHT,
+        None,
+    );
+    policy.set_align(Align::Left | Align::Inside | Align::Wrap);
+
+    let button_width = 100;
+    let mut cancel_button = Button::new(
+        width - PADDING - button_width,
+        policy.y() + policy.height() + PADDING,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Cancel"),
+    );
+    cancel_button.set_color(Color::from_u32(palette().button));
+    cancel_button.set_callback(|button| button.window().unwrap().hide());
+    let mut apply_button = Button::new(
+        cancel_button.x() - PADDING - button_width,
+        cancel_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Apply"),
+    );
+    apply_button.set_color(Color::from_u32(palette().button));
+
+    // The policy is described for the keys as they are, or why the template doesn't suit them.
+    let configuration = main_output_pack.key_configuration();
+    let mut apply_button_clone = apply_button.clone();
+    let mut describe = move |template: SchemeTemplate| {
+        match casper_multisig_tool::describe_scheme_template(template, &configuration) {
+            Ok(description) => {
+                policy.set_label(&description);
+                policy.set_label_color(Color::ForeGround);
+                apply_button_clone.activate();
+            }
+            Err(error) => {
+                policy.set_label(&casper_multisig_tool::translate_with(
+                    "{}.  Add or delete keys first.",
+                    &[&error.to_string()],
+                ));
+                policy.set_label_color(Color::from_u32(palette().warning_text));
+                apply_button_clone.deactivate();
+            }
+        }
+        policy.redraw();
+    };
+    describe(SchemeTemplate::ALL[0]);
+    template_choice.set_callback(move |choice| {
+        if let Some(template) = SchemeTemplate::ALL.get(choice.value() as usize) {
+            describe(*template);
+        }
+    });
+
+    apply_button.set_callback(move |button| {
+        let template = match SchemeTemplate::ALL.get(template_choice.value() as usize) {
+            Some(template) => *template,
+            None => return,
+        };
+        match casper_multisig_tool::apply_scheme_template(
+            template,
+            &main_output_pack.key_configuration(),
+        ) {
+            // Every key was added earlier, so keeps its tooltip.
+            Ok(configuration) => {
+                main_output_pack.apply_key_configuration(&configuration, "", false)
+            }
+            Err(error) => {
+                dialog::alert_default(&error.to_string());
+                return;
+            }
+        }
+        button.window().unwrap().hide();
+    });
+
+    window.end();
+    window.set_size(width, cancel_button.y() + cancel_button.height() + PADDING);
+    window.show();
+}
+
+/// Shows a dialog with a checkbox per key left on the account once set up, saying whether the keys
+/// ticked could together send deploys and change the associated keys and thresholds.
+fn show_signer_combinations_window(main_output_pack: MainOutputPack) {
+    if main_output_pack.associated_keys().is_empty() {
+        dialog::message_default(tr(
+            "Add keys to explore which of them can sign for the account",
+        ));
+        return;
+    }
+    let account = match casper_multisig_tool::resulting_account() {
+        Some(account) => account,
+        None => {
+            dialog::message_default(tr(
+                "Which keys can sign depends on the account's keys before the deploy, as the \
+                contract mode changes an existing account",
+            ));
+            return;
+        }
+    };
+
+    let width = 800;
+    let visible_rows = cmp::min(account.associated_keys.len() as i32, 10);
+    let mut window = Window::default()
+        .with_size(width, 0)
+        .with_label(tr("Explore signer combinations"));
+    window.make_modal(true);
+
+    let intro = Frame::new(
+        PADDING,
+        PADDING,
+        width - (2 * PADDING),
+        BUTTON_HEIGHT,
+        tr("Tick the keys which sign a deploy to see what they could do together"),
+    )
+    .with_align(Align::Left | Align::Inside);
+
+    let mut scroll = Scroll::new(
+        PADDING,
+        intro.y() + intro.height() + PADDING,
+        width - (2 * PADDING),
+        visible_rows * BUTTON_HEIGHT,
+        None,
+    );
+    scroll.set_type(ScrollType::Vertical);
+    let mut pack = Pack::new(
+        scroll.x(),
+        scroll.y(),
+        scroll.width() - app::scrollbar_size(),
+        0,
+        None,
+    );
+    let check_buttons = account
+        .associated_keys
+        .iter()
+        .map(|(account_hash, weight)| {
+            let account_hash = account_hash.to_formatted_string();
+            let label = match key_label(&account_hash) {
+                Some(label) => casper_multisig_tool::translate_with(
+                    "{} ({}), weight {}",
+                    &[
+                        &label,
+                        &abbreviated_account_hash(&account_hash),
+                        &weight.to_string(),
+                    ],
+                ),
+                None => casper_multisig_tool::translate_with(
+                    "{}, weight {}",
+                    &[&account_hash, &weight.to_string()],
+                ),
+            };
+            let mut check_button = CheckButton::default().with_size(0, BUTTON_HEIGHT);
+            // The "@." stops a label starting with '@' being taken as a symbol.
+            check_button.set_label(&format!("@.{}", label));
+            check_button
+        })
+        .collect::<Vec<_>>();
+    pack.end();
+    pack.set_size(pack.width(), BUTTON_HEIGHT * check_buttons.len() as i32);
+    scroll.end();
+
+    let mut outcome = Frame::new(
+        PADDING,
+        scroll.y() + scroll.height() + PADDING,
+        width - (2 * PADDING),
+        3 * BUTTON_HEIGHT,
+        None,
+    );
+    outcome.set_align(Align::Left | Align::Inside);
+    outcome.set_frame(FrameType::FlatBox);
+
+    let button_width = 100;
+    let mut close_button = Button::new(
+        width - PADDING - button_width,
+        outcome.y() + outcome.height() + PADDING,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Close"),
+    );
+    close_button.set_color(Color::from_u32(palette().button));
+    close_button.set_callback(|button| button.window().unwrap().hide());
+
+    show_signer_combination_outcome(&account, &check_buttons, &mut outcome);
+    for check_button in &check_buttons {
+        let account = account.clone();
+        let check_buttons = check_buttons.clone();
+        let mut outcome = outcome.clone();
+        check_button.clone().set_callback(move |_| {
+            show_signer_combination_outcome(&account, &check_buttons, &mut outcome)
+        });
+    }
+
+    window.end();
+    window.set_size(width, close_button.y() + close_button.height() + PADDING);
+    window.show();
+}
+
+/// Shows in `outcome` what the keys of `account` whose `check_buttons` are ticked could do together.
+fn show_signer_combination_outcome(
+    account: &AccountState,
+    check_buttons: &[CheckButton],
+    outcome: &mut Frame,
+) {
+    let signers = account
+        .associated_keys
+        .iter()
+        .zip(check_buttons)
+        .filter(|(_, check_button)| check_button.is_checked())
+        .map(|((account_hash, _), _)| *account_hash)
+        .collect::<Vec<_>>();
+    let yes_or_no = |allowed: bool| if allowed { tr("Yes") } else { tr("No") };
+    let can_deploy = account.can_deploy(&signers);
+    let can_manage_keys = account.can_manage_keys(&signers);
+    outcome.set_label(&casper_multisig_tool::translate_with(
+        "Combined weight: {}\nCan send deploys: {} (deploy-execution threshold {})\nCan change the \
+        associated keys and thresholds: {} (key-management threshold {})",
+        &[
+            &account.signing_weight(&signers).to_string(),
+            yes_or_no(can_deploy),
+            &account.deployment_threshold.to_string(),
+            yes_or_no(can_manage_keys),
+            &account.key_management_threshold.to_string(),
+        ],
+    ));
+    let color = if can_manage_keys {
+        palette().passed
+    } else if can_deploy {
+        palette().input_background
+    } else {
+        palette().failed
+    };
+    outcome.set_color(Color::from_u32(color));
+    outcome.redraw();
+}
+
+/// Parses a threshold weight, treating a blank value as `None`.
+fn parse_optional_threshold(value: &str) -> Result<Option<u8>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value.parse::<u8>().map(Some).map_err(|error| {
+        casper_multisig_tool::translate_with(
+            "failed to parse '{}' as a threshold: {}",
+            &[value, &error.to_string()],
+        )
+    })
+}
+
+/// Parses lines of the form `left = right` into `(left, right)` pairs, ignoring blank lines.
+///
+/// `form` describes the expected form of a line for the error message, e.g. `name = key`.
+fn parse_assignments(text: &str, form: &str) -> Result<Vec<(String, String)>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once('=') {
+            Some((left, right)) => Ok((left.trim().to_string(), right.trim().to_string())),
+            None => Err(casper_multisig_tool::translate_with(
+                "expected '{}' but got '{}'",
+                &[form, line],
+            )),
+        })
+        .collect()
+}
+
+/// Lists the missing prerequisites if any of the tools needed to compile the contract aren't
+/// installed, so that a missing target doesn't only surface as a cargo error mid-compile.
+fn check_prerequisites_on_startup() {
+    let missing = casper_multisig_tool::missing_prerequisites();
+    if !missing.is_empty() {
+        show_prerequisites_window(missing, false);
+    }
+}
+
+/// Shows a window listing the `missing` prerequisites, each with how to install it and, where the
+/// tool can install it, a "Fix" button doing so.
+///
+/// If `before_compiling`, returns whether the user chose to continue compiling; otherwise always
+/// returns `false`.
+fn show_prerequisites_window(missing: Vec<Prerequisite>, before_compiling: bool) -> bool {
+    let width = 1100;
+    let row_height = BUTTON_HEIGHT + PADDING;
+    let header_height = 60;
+    let height =
+        header_height + (missing.len() as i32 * row_height) + BUTTON_HEIGHT + (2 * PADDING);
+    let mut window = Window::default()
+        .with_size(width, height)
+        .center_screen()
+        .with_label(tr("Missing prerequisites"));
+    window.make_modal(true);
+
+    let mut header = Frame::new(
+        PADDING,
+        PADDING,
+        width - (2 * PADDING),
+        header_height - PADDING,
+        tr("Compiling the contract needs these, which aren't installed:"),
+    );
+    header.set_align(Align::Left | Align::Inside);
+
+    let fix_button_width = 120;
+    let (sender, receiver) = mpsc::channel::<(usize, Result<(), String>)>();
+    let mut rows = vec![];
+    let mut fix_buttons = vec![];
+    for (index, prerequisite) in missing.iter().enumerate() {
+        let y = header_height + (index as i32 * row_height);
+        let mut row = Frame::new(
+            PADDING,
+            y,
+            width - (3 * PADDING) - fix_button_width,
+            BUTTON_HEIGHT,
+            None,
+        );
+        row.set_label(&format!(
+            "{}: {}",
+            prerequisite,
+            prerequisite.install_hint()
+        ));
+        row.set_align(Align::Left | Align::Inside | Align::Wrap);
+        rows.push(row);
+
+        if prerequisite.fix_command().is_none() {
+            continue;
+        }
+        let mut fix_button = Button::new(
+            width - PADDING - fix_button_width,
+            y,
+            fix_button_width,
+            BUTTON_HEIGHT,
+            tr("Fix"),
+        );
+        fix_button.set_color(Color::from_u32(palette().button));
+        fix_button.set_tooltip(&casper_multisig_tool::translate_with(
+            "Run `{}`",
+            &[&prerequisite.fix_command().unwrap_or_default().join(" ")],
+        ));
+        fix_buttons.push((index, fix_button));
+    }
+
+    let button_width = 150;
+    let mut continue_button = Button::new(
+        width - PADDING - button_width,
+        height - PADDING - BUTTON_HEIGHT,
+        button_width,
+        BUTTON_HEIGHT,
+        if before_compiling {
+            "Compile anyway"
+        } else {
+            "Close"
+        },
+    );
+    continue_button.set_color(Color::from_u32(palette().button));
+    let mut cancel_button = Button::new(
+        continue_button.x() - PADDING - button_width,
+        continue_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Cancel"),
+    );
+    cancel_button.set_color(Color::from_u32(palette().button));
+    if !before_compiling {
+        cancel_button.hide();
+    }
+    window.end();
+    window.show();
+
+    // The fixes run on other threads, as installing a toolchain can take minutes.  Only one runs
+    // at a time, since rustup locks its installation.
+    let all_fix_buttons = fix_buttons
+        .iter()
+        .map(|(_, button)| button.clone())
+        .collect::<Vec<_>>();
+    for (index, fix_button) in fix_buttons.iter_mut() {
+        let index = *index;
+        let prerequisite = missing[index].clone();
+        let mut row = rows[index].clone();
+        let all_fix_buttons = all_fix_buttons.clone();
+        let sender = sender.clone();
+        fix_button.set_callback(move |_| {
+            for mut button in all_fix_buttons.iter().cloned() {
+                button.deactivate();
+            }
+            row.set_label(&casper_multisig_tool::translate_with(
+                "{}: installing...",
+                &[&prerequisite.to_string()],
+            ));
+            row.redraw();
+            let sender = sender.clone();
+            let prerequisite = prerequisite.clone();
+            thread::spawn(move || {
+                let result = casper_multisig_tool::fix_prerequisite(&prerequisite)
+                    .map_err(|error| error.to_string());
+                let _ = sender.send((index, result));
+            });
+        });
+    }
+    let (button_sender, button_receiver) = app::channel::<bool>();
+    continue_button.emit(button_sender, true);
+    cancel_button.emit(button_sender, false);
+
+    let mut fixed = vec![false; missing.len()];
+    let mut proceed = false;
+    while window.shown() {
+        let _ = app::wait_for(0.1);
+        if let Some(value) = button_receiver.recv() {
+            proceed = value;
+            window.hide();
+        }
+        let (index, result) = match receiver.try_recv() {
+            Ok(fix) => fix,
+            Err(_) => continue,
+        };
+        match result {
+            Ok(()) => {
+                fixed[index] = true;
+                rows[index].set_label(&casper_multisig_tool::translate_with(
+                    "{}: installed",
+                    &[&missing[index].to_string()],
+                ));
+            }
+            Err(error) => {
+                rows[index].set_label(&format!(
+                    "{}: {}",
+                    missing[index],
+                    missing[index].install_hint()
+                ));
+                dialog::alert_default(&error);
+            }
+        }
+        for (index, fix_button) in fix_buttons.iter_mut() {
+            set_active(fix_button, !fixed[*index]);
+        }
+        if before_compiling && fixed.iter().all(|fixed| *fixed) {
+            continue_button.set_label(tr("Compile"));
+        }
+        window.redraw();
+    }
+    before_compiling && proceed
+}
+
+/// Starts pre-warming the shared target dir in the background, if one is configured.
+///
+/// Neither its output nor its failure is shown, as it's only an optimisation: if it can't start or
+/// fails, the real build fails the same way and shows why.
+fn start_prewarm() {
+    let receiver = match casper_multisig_tool::prewarm_dependencies() {
+        Ok(Some(receiver)) => receiver,
+        Ok(None) | Err(_) => return,
+    };
+    // The events are drained until the build ends, as it stalls once its output stops being read.
+    thread::spawn(move || while receiver.recv().is_ok() {});
+}
+
+/// Activates or deactivates `widget`.
+fn set_active<W: WidgetExt>(widget: &mut W, active: bool) {
+    if active {
+        widget.activate();
+    } else {
+        widget.deactivate();
+    }
+}
+
+/// Asks the user for a node address and returns the protocol profile for the node's network, or
+/// `None` if the user cancelled or the version couldn't be detected.
+fn detect_protocol_profile() -> Option<ProtocolProfile> {
+    let node_address = input_node_address()?;
+    let result = casper_multisig_tool::detect_protocol_version(&node_address).and_then(|version| {
+        let profile = ProtocolProfile::for_protocol_version(&version)?;
+        Ok((version, profile))
+    });
+    match result {
+        Ok((version, profile)) => {
+            dialog::message_default(&casper_multisig_tool::translate_with(
+                "The network's protocol version is {}",
+                &[&version.to_string()],
+            ));
+            Some(profile)
+        }
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            None
+        }
+    }
+}
+
+/// Asks the user for one or more comma-separated node addresses, defaulting to those of the
+/// selected network.
+fn input_node_address() -> Option<String> {
+    let default = casper_multisig_tool::selected_network()
+        .map(|network| network.node_addresses.join(", "))
+        .unwrap_or_default();
+    dialog::input_default(
+        tr("Enter node address, or several separated by commas to fail over between them"),
+        &default,
+    )
+}
+
+/// Asks the user for a chain name, defaulting to that of the selected network.
+fn input_chain_name() -> Option<String> {
+    let default = casper_multisig_tool::selected_network()
+        .map(|network| network.chain_name)
+        .unwrap_or_default();
+    dialog::input_default(tr("Enter chain name"), &default)
+}
+
+/// Returns the event stream URL of the selected network if `node_address` lists its nodes, else
+/// the URL derived from the first node address, or an empty string if neither is known.
+fn default_event_stream_url(node_address: &str) -> String {
+    casper_multisig_tool::selected_network()
+        .filter(|network| network.node_addresses.join(", ") == node_address.trim())
+        .and_then(|network| network.event_stream_url)
+        .or_else(|| casper_multisig_tool::default_event_stream_url(node_address))
+        .unwrap_or_default()
+}
+
+/// Fetches the status of the selected network's nodes in the background and shows it in `frame`,
+/// warning if the network's chain name doesn't match the node's.
+fn show_node_status(frame: &Frame) {
+    let mut frame = frame.clone();
+    let network = match casper_multisig_tool::selected_network() {
+        Some(network) => network,
+        None => {
+            frame.set_label("");
+            return;
+        }
+    };
+    frame.set_label_color(Color::ForeGround);
+    frame.set_label(&casper_multisig_tool::translate_with(
+        "Checking {}...",
+        &[&network.name],
+    ));
+    thread::spawn(move || {
+        let node_address = network.node_addresses.join(", ");
+        match casper_multisig_tool::node_status(&node_address) {
+            Ok(status) => match status.chain_name_warning(&network.chain_name) {
+                Some(warning) => {
+                    frame.set_label_color(Color::from_u32(palette().warning_text));
+                    frame.set_label(&casper_multisig_tool::translate_with(
+                        "Warning: {}",
+                        &[&warning],
+                    ));
+                }
+                None => frame.set_label(&status.to_string()),
+            },
+            Err(error) => {
+                frame.set_label_color(Color::from_u32(palette().warning_text));
+                frame.set_label(&error.to_string());
+            }
+        }
+        frame.redraw();
+    });
+}
+
+/// Returns the entries of the "Network" choice: none, each network profile, then an entry for
+/// adding a profile.
+fn network_choices() -> String {
+    let mut choices = vec![tr("None").to_string()];
+    choices.extend(
+        casper_multisig_tool::network_profiles()
+            .into_iter()
+            // Unescaped, these would separate entries or start submenus.
+            .map(|network| network.name.replace('/', "\\/").replace('|', "\\|")),
+    );
+    choices.push(tr("Add network...").to_string());
+    choices.join("|")
+}
+
+/// Returns the index of the selected network in the "Network" choice.
+fn selected_network_index() -> i32 {
+    let selected = match casper_multisig_tool::selected_network() {
+        Some(network) => network.name,
+        None => return 0,
+    };
+    casper_multisig_tool::network_profiles()
+        .iter()
+        .position(|network| network.name == selected)
+        .map_or(0, |index| index as i32 + 1)
+}
+
+/// Asks for the proxy, CA certificate and request headers used to reach a network's nodes.
+fn get_connection_settings() -> Option<ConnectionSettings> {
+    let optional = |value: String| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+    let proxy = dialog::input_default(
+        tr(
+            "Enter the proxy URL, e.g. http://proxy.example.com:3128, or leave empty to use the \
+        HTTPS_PROXY and HTTP_PROXY environment variables",
+        ),
+        "",
+    )?;
+    let ca_certificate = dialog::input_default(
+        tr(
+            "Enter the path of a PEM file of CA certificates to trust instead of the system's, or \
+        leave empty to use the system's",
+        ),
+        "",
+    )?;
+    let headers = dialog::input_default(
+        tr(
+            "Enter headers to send with every request as 'Name: value', separated by semicolons, \
+            or leave empty to send none",
+        ),
+        "",
+    )?;
+    let mut parsed_headers = vec![];
+    for header in headers
+        .split(';')
+        .filter(|header| !header.trim().is_empty())
+    {
+        match header.split_once(':') {
+            Some((name, value)) => {
+                parsed_headers.push((name.trim().to_string(), value.trim().to_string()))
+            }
+            None => {
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "'{}' isn't a header of the form 'Name: value'",
+                    &[header.trim()],
+                ));
+                return None;
+            }
+        }
+    }
+    Some(ConnectionSettings {
+        ca_certificate: optional(ca_certificate).map(PathBuf::from),
+        proxy: optional(proxy),
+        headers: parsed_headers,
+    })
+}
+
+/// Asks the user for the details of a new network profile and saves it, returning its name, or
+/// `None` if the user cancelled or the profile is invalid.
+fn add_network_profile() -> Option<String> {
+    let name = dialog::input_default(tr("Enter a name for the network"), "")?;
+    let node_addresses = dialog::input_default(
+        tr(
+            "Enter the addresses of nodes on the network, separated by commas, in the order they \
+        should be tried",
+        ),
+        "",
+    )?;
+    let chain_name = dialog::input_default(tr("Enter the network's chain name"), "")?;
+    let event_stream_url = dialog::input_default(
+        tr(
+            "Enter a node's event stream URL, or leave empty to derive it from the first node \
+            address",
+        ),
+        &casper_multisig_tool::default_event_stream_url(&node_addresses).unwrap_or_default(),
+    )?;
+    let protocol_version = dialog::input_default(
+        tr(
+            "Enter the network's protocol version, e.g. 1.5.2, or leave empty to leave the \
+            protocol version unchanged when it's selected",
+        ),
+        "",
+    )?;
+    let explorer_url = dialog::input_default(
+        tr(
+            "Enter the URL of the network's block explorer, e.g. https://cspr.live, or leave empty \
+            if it has none",
+        ),
+        "",
+    )?;
+    let max_deploy_size = dialog::input_default(
+        tr("Enter the network's max deploy size (bytes)"),
+        &casper_multisig_tool::max_deploy_size().to_string(),
+    )?;
+    let max_deploy_size = match max_deploy_size.trim().parse::<u32>() {
+        Ok(size) => size,
+        Err(error) => {
+            dialog::alert_default(&casper_multisig_tool::translate_with(
+                "failed to parse '{}' as a max deploy size: {}",
+                &[max_deploy_size.trim(), &error.to_string()],
+            ));
+            return None;
+        }
+    };
+    let optional = |value: String| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+    let connection = if dialog::choice_default(
+        tr("Does the network need a proxy, custom CA certificate or request headers?"),
+        tr("No"),
+        tr("Yes"),
+        "",
+    ) == 1
+    {
+        get_connection_settings()?
+    } else {
+        ConnectionSettings::default()
+    };
+    let profile = NetworkProfile {
+        name: name.trim().to_string(),
+        node_addresses: node_addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect(),
+        event_stream_url: optional(event_stream_url),
+        chain_name: chain_name.trim().to_string(),
+        protocol_version: optional(protocol_version),
+        max_deploy_size,
+        explorer_url: optional(explorer_url),
+        connection,
+    };
+    let name = profile.name.clone();
+    match casper_multisig_tool::save_network_profile(profile) {
+        Ok(()) => Some(name),
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            None
+        }
+    }
+}
+
+/// Returns the codegen target selected in the "Target network" choice.
+fn choice_codegen_target(choice: &Choice) -> CodegenTarget {
+    match choice.value() {
+        1 => CodegenTarget::Casper2,
+        _ => CodegenTarget::Casper1,
+    }
+}
+
+/// Returns a new labelled input row for the build or contract options windows.
+fn new_option_input(label: &str, value: &str, tooltip: &str) -> Input {
+    let mut input = Input::default()
+        .with_size(0, BUTTON_HEIGHT)
+        .with_label(tr(label));
+    input.set_align(Align::Left);
+    input.set_value(value);
+    input.set_tooltip(tr(tooltip));
+    input.set_text_font(Font::Courier);
+    input.set_text_size(text_size());
+    input
+}
+
+/// Returns the path to a prebuilt session Wasm chosen by the user, or `None` if the user cancelled
+/// the operation.
+fn get_prebuilt_session_wasm_file() -> Option<String> {
+    let mut file_dialog = FileDialog::new(FileDialogType::BrowseFile);
+    if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
+        let _ = file_dialog.set_directory(start_dir);
+    }
+    file_dialog.set_option(FileDialogOptions::UseFilterExt);
+    file_dialog.set_title(tr("Choose Prebuilt Session Wasm"));
+    file_dialog.set_filter("Wasm Files \t*.wasm");
+    file_dialog.show();
+
+    if file_dialog.filename() == PathBuf::default() {
+        return None;
+    }
+
+    Some(file_dialog.filename().to_string_lossy().to_string())
+}
+
+/// Asks for the Blake2b hash published with the audited build of the prebuilt session Wasm at
+/// `wasm_path`, showing the file's own hash.  Returns `Some(None)` if the user leaves it blank to
+/// use the Wasm unverified, or `None` if they cancelled.
+fn get_expected_wasm_hash(wasm_path: &str) -> Option<Option<String>> {
+    let actual_hash = match casper_multisig_tool::wasm_file_hash(wasm_path) {
+        Ok(hash) => hash,
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            return None;
+        }
+    };
+    let expected_hash = dialog::input_default(
+        &casper_multisig_tool::translate_with(
+            "The Blake2b hash of this Wasm is\n{}\n\nEnter the hash published with the audited \
+            build to check it's that build, or leave this blank to use it unverified.",
+            &[&actual_hash],
+        ),
+        "",
+    )?;
+    if expected_hash.trim().is_empty() {
+        Some(None)
+    } else {
+        Some(Some(expected_hash))
+    }
+}
+
+/// Returns the path of a secret key file chosen by the user, or `None` if they cancelled.
+fn get_secret_key_file(title: &str) -> Option<String> {
+    let mut file_dialog = FileDialog::new(FileDialogType::BrowseFile);
+    if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
+        let _ = file_dialog.set_directory(start_dir);
+    }
+    file_dialog.set_option(FileDialogOptions::UseFilterExt);
+    file_dialog.set_title(title);
+    file_dialog.set_filter(
+        "PEM-encoded Secret Key Files \t*secret_key*.pem\nEncrypted Keystore Files \t*.json",
+    );
+    file_dialog.show();
+
+    if file_dialog.filename() == PathBuf::default() {
+        return None;
+    }
+
+    let file_path = file_dialog.filename().to_string_lossy().to_string();
+    if !unlock_keystore(&file_path) {
+        return None;
+    }
+    Some(file_path)
+}
+
+/// Asks for the passphrase of the file at `file_path` and unlocks it if it's an encrypted keystore
+/// which hasn't been unlocked yet.  Returns `false` if the user cancelled or it couldn't be
+/// unlocked.
+fn unlock_keystore(file_path: &str) -> bool {
+    if !casper_multisig_tool::is_encrypted_keystore(file_path)
+        || casper_multisig_tool::keystore_unlocked(file_path)
+    {
+        return true;
+    }
+    let passphrase = match dialog::password_default(
+        &casper_multisig_tool::translate_with(
+            "Enter the passphrase of the keystore {}",
+            &[file_path],
+        ),
+        "",
+    ) {
+        Some(passphrase) => passphrase,
+        None => return false,
+    };
+    match casper_multisig_tool::unlock_keystore(file_path, &passphrase) {
+        Ok(_) => true,
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            false
+        }
+    }
+}
+
+/// Opens the testnet faucet for the main account, whose public key is entered by the user.
+fn request_testnet_funds() {
+    let default_public_key = casper_multisig_tool::initial_transfer()
+        .map(|(_, _, target_public_key)| target_public_key)
+        .unwrap_or_default();
+    let public_key = match dialog::input_default(
+        tr("Enter the main account's hex-encoded public key"),
+        &default_public_key,
+    ) {
+        Some(value) => value,
+        None => return,
+    };
+    match casper_multisig_tool::open_testnet_faucet(&public_key) {
+        Ok(public_key) => {
+            // The faucet's form can't be pre-filled, so offer the key for copying.
+            let _ = dialog::input_default(
+                tr(
+                    "The faucet has been opened in your browser.  Copy this public key into it and \
+                complete its check to request test CSPR.",
+                ),
+                &public_key,
+            );
+        }
+        Err(error) => dialog::alert_default(&error.to_string()),
+    }
+}
+
+/// Starts rehearsing the setup deploy of the session Wasm at `wasm_path` on the NCTL network,
+/// funding key and main account key chosen by the user, or returns `None` if they cancelled or it
+/// couldn't be started.
+fn start_local_network_test(wasm_path: &Path) -> Option<Receiver<LocalNetworkEvent>> {
+    let default_assets_dir = casper_multisig_tool::default_nctl_assets_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    let assets_dir = dialog::input_default(
+        tr("Enter the NCTL network's assets dir, e.g. $NCTL/assets/net-1"),
+        &default_assets_dir,
+    )?;
+    let funding_user = dialog::input_default(
+        tr("Enter the NCTL user whose key funds the main account, or 0 for the faucet"),
+        "0",
+    )?;
+    let funding_user = match funding_user.trim().parse::<u32>() {
+        Ok(user) => user,
+        Err(error) => {
+            dialog::alert_default(&casper_multisig_tool::translate_with(
+                "failed to parse '{}' as a user number: {}",
+                &[&funding_user, &error.to_string()],
+            ));
+            return None;
+        }
+    };
+    let main_secret_key = get_secret_key_file(tr("Choose the Main Account's Secret Key File"))?;
+
+    match casper_multisig_tool::test_on_local_network(
+        wasm_path,
+        assets_dir.trim(),
+        funding_user,
+        &main_secret_key,
+    ) {
+        Ok(receiver) => Some(receiver),
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            None
+        }
+    }
+}
+
+/// Shows whether each approval in `approvals_file` is a valid signature of the deploy's hash,
+/// returning `true` if they all are and the user chooses to import them.
+fn confirm_approvals(deploy: &Deploy, approvals_file: &str) -> bool {
+    let checks = match casper_multisig_tool::check_approvals(deploy, approvals_file) {
+        Ok(checks) => checks,
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            return false;
+        }
+    };
+    if checks.is_empty() {
+        dialog::message_default(&casper_multisig_tool::translate_with(
+            "{} holds no approvals",
+            &[approvals_file],
+        ));
+        return false;
+    }
+
+    let mut report = casper_multisig_tool::translate_with(
+        "Approvals checked against deploy hash {}:\n",
+        &[&deploy.id().to_string()],
+    );
+    for check in &checks {
+        match &check.error {
+            None => report.push_str(&casper_multisig_tool::translate_with(
+                "\nVALID: {}",
+                &[&check.signer.to_hex()],
+            )),
+            Some(error) => report.push_str(&casper_multisig_tool::translate_with(
+                "\nINVALID: {}\n    {}",
+                &[&check.signer.to_hex(), &error.to_string()],
+            )),
+        }
+    }
+    if checks.iter().any(|check| check.error.is_some()) {
+        report.push_str(tr(
+            "\n\nNone can be imported until the co-signers sign this exact deploy.",
+        ));
+        dialog::alert_default(&report);
+        return false;
+    }
+    dialog::choice_default(&report, tr("Cancel"), tr("Import"), "") == 1
+}
+
+/// Returns the path of a dir chosen by the user, or `None` if they cancelled.
+fn get_dir(title: &str) -> Option<PathBuf> {
+    let mut file_dialog = FileDialog::new(FileDialogType::BrowseDir);
+    if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
+        let _ = file_dialog.set_directory(start_dir);
+    }
+    file_dialog.set_option(FileDialogOptions::NewFolder);
+    file_dialog.set_title(title);
+    file_dialog.show();
+
+    if file_dialog.filename() == PathBuf::default() {
+        return None;
+    }
+
+    Some(file_dialog.filename())
+}
+
+/// Returns the path of a deploy JSON file chosen by the user, or `None` if they cancelled.
+fn get_deploy_file() -> Option<PathBuf> {
+    let mut file_dialog = FileDialog::new(FileDialogType::BrowseFile);
+    if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
+        let _ = file_dialog.set_directory(start_dir);
+    }
+    file_dialog.set_option(FileDialogOptions::UseFilterExt);
+    file_dialog.set_title(tr("Choose Deploy File"));
+    file_dialog.set_filter("JSON Files \t*.json");
+    file_dialog.show();
+
+    if file_dialog.filename() == PathBuf::default() {
+        return None;
+    }
+
+    Some(file_dialog.filename())
+}
+
+/// Returns the path of a file of approvals chosen by the user, or `None` if they cancelled.
+fn get_approvals_file() -> Option<String> {
+    let mut file_dialog = FileDialog::new(FileDialogType::BrowseFile);
+    if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
+        let _ = file_dialog.set_directory(start_dir);
+    }
+    file_dialog.set_option(FileDialogOptions::UseFilterExt);
+    file_dialog.set_title(tr("Choose Signed Deploy or Approvals File"));
+    file_dialog.set_filter("JSON Files \t*.json");
+    file_dialog.show();
+
+    if file_dialog.filename() == PathBuf::default() {
+        return None;
+    }
+
+    Some(file_dialog.filename().to_string_lossy().to_string())
+}
+
+/// Asks for an account, a node and optionally a past block, then shows the account's associated
+/// keys and action thresholds in the global state after that block.
+fn show_account_state() {
+    let account = match dialog::input_default(
+        tr("Enter the account's hex-encoded public key or formatted account hash"),
+        "",
+    ) {
+        Some(value) => value,
+        None => return,
+    };
+    let node_address = match input_node_address() {
+        Some(value) => value,
+        None => return,
+    };
+    let block = match dialog::input_default(
+        tr(
+            "Enter a block hash or height to see the account as it was after that block, or leave \
+        empty for its current state",
+        ),
+        "",
+    ) {
+        Some(value) => value,
+        None => return,
+    };
+    let block = if block.trim().is_empty() {
+        None
+    } else {
+        match block.parse::<BlockIdentifier>() {
+            Ok(block) => Some(block),
+            Err(error) => {
+                dialog::alert_default(&error.to_string());
+                return;
+            }
+        }
+    };
+
+    match casper_multisig_tool::query_account_state(&node_address, &account, block.as_ref()) {
+        Ok(account_state) => {
+            let when = match &block {
+                Some(block) => {
+                    casper_multisig_tool::translate_with("after block {}", &[&block.to_string()])
+                }
+                None => tr("now").to_string(),
+            };
+            dialog::message_default(&casper_multisig_tool::translate_with(
+                "{} {}:\n\n{}",
+                &[account.trim(), &when, &account_state.to_string()],
+            ));
+        }
+        Err(error) => dialog::alert_default(&error.to_string()),
+    }
+}
+
+/// Returns `true` if `deploy` has expired or expires within `EXPIRY_WARNING`.
+fn expires_soon(deploy: &Deploy) -> bool {
+    casper_multisig_tool::time_to_expiry(deploy).is_none_or(|remaining| remaining < EXPIRY_WARNING)
+}
+
+/// Returns a line saying how long until `deploy` expires, or that it has.
+fn expiry_status(deploy: &Deploy) -> String {
+    match casper_multisig_tool::time_to_expiry(deploy) {
+        None => tr("The deploy has EXPIRED, so the network will no longer execute it").to_string(),
+        Some(remaining) if remaining.as_secs() < 60 => {
+            tr("The deploy expires in less than a minute").to_string()
+        }
+        Some(remaining) => casper_multisig_tool::translate_with(
+            "The deploy expires in {} minutes",
+            &[&remaining.as_secs().div_ceil(60).to_string()],
+        ),
+    }
+}
+
+/// Warns that `deploy` has expired or is about to, and offers to replace it with a copy with a
+/// fresh timestamp, signed by the secret key of its account.
+///
+/// Returns `true` if the deploy was rebuilt, discarding its other approvals.
+fn offer_rebuild(deploy: &mut Deploy) -> bool {
+    let prompt = casper_multisig_tool::translate_with(
+        "{}.\n\nRebuild it with a fresh timestamp?  The rebuilt deploy has a new hash, so its {} \
+        approval(s) will be discarded and every signer must sign it again.",
+        &[
+            &expiry_status(deploy),
+            &deploy.approvals().len().to_string(),
+        ],
+    );
+    if dialog::choice_default(&prompt, tr("Not now"), tr("Rebuild"), "") != 1 {
+        return false;
+    }
+    let secret_key = match get_secret_key_file(tr("Choose Secret Key File of the Deploy's Account"))
+    {
+        Some(file) => file,
+        None => return false,
+    };
+    match casper_multisig_tool::rebuild_deploy(deploy, &secret_key) {
+        Ok(rebuilt) => {
+            *deploy = rebuilt;
+            true
+        }
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            false
+        }
+    }
+}
+
+/// Asks whether to track the approvals against the deploy's account on the network, and if so
+/// fetches the account's associated keys and thresholds.
+///
+/// Returns `None` if the user declines or the account can't be fetched.
+fn fetch_signing_account(deploy: &Deploy) -> Option<AccountState> {
+    if dialog::choice_default(
+        tr(
+            "Track the collected weight against the deployment threshold of the deploy's account \
+            on the network?",
+        ),
+        tr("No"),
+        tr("Yes"),
+        "",
+    ) != 1
+    {
+        return None;
+    }
+    let node_address = input_node_address()?;
+    match casper_multisig_tool::fetch_account_state(&node_address, deploy.header().account()) {
+        Ok(account_state) => Some(account_state),
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            None
+        }
+    }
+}
+
+/// Builds a deploy of the session Wasm at `wasm_path`, or continues with the one previously saved
+/// in the project, and has the user add approvals to it by signing it or importing other signers'
+/// approvals.
+///
+/// The deploy is saved in the project along with an unsigned copy for the other signers, and the
+/// outcome reported in `text_display`.
+fn build_and_sign_deploy(wasm_path: &Path, text_display: &mut TextDisplay) {
+    let project_dir =
+        casper_multisig_tool::project_path().join(casper_multisig_tool::contract_name());
+    let deploy_path = project_dir.join("deploy.json");
+    let unsigned_deploy_path = project_dir.join("unsigned_deploy.json");
+
+    let existing_deploy = deploy_path.exists()
+        && dialog::choice_default(
+            tr("Add approvals to the deploy previously saved in the project?"),
+            tr("No, build a new one"),
+            tr("Yes"),
+            "",
+        ) == 1;
+    let deploy = if existing_deploy {
+        match casper_multisig_tool::load_deploy(&deploy_path) {
+            Ok(deploy) => Some(deploy),
+            Err(error) => {
+                dialog::alert_default(&error.to_string());
+                None
+            }
+        }
+    } else {
+        build_deploy(wasm_path)
+    };
+    let mut deploy = match deploy {
+        Some(deploy) => deploy,
+        None => return,
+    };
+    let account_state = fetch_signing_account(&deploy);
+    let mut expiry_warned = false;
+
+    loop {
+        if !expiry_warned && expires_soon(&deploy) {
+            expiry_warned = true;
+            if offer_rebuild(&mut deploy) {
+                expiry_warned = false;
+            }
+        }
+        let mut prompt = expiry_status(&deploy);
+        if let Some(account_state) = &account_state {
+            prompt = format!(
+                "{}\n{}",
+                prompt,
+                casper_multisig_tool::approval_progress(&deploy, account_state)
+            );
+        }
+        prompt.push_str(tr("\n\nAdd another approval?"));
+        match dialog::choice_default(
+            &prompt,
+            tr("Done"),
+            tr("Sign with a key"),
+            tr("Import approvals"),
+        ) {
+            1 => {
+                let device_label = if casper_multisig_tool::ledger_supported()
+                    || casper_multisig_tool::yubikey_supported()
+                {
+                    tr("Hardware device")
+                } else {
+                    ""
+                };
+                let result = match dialog::choice_default(
+                    tr("Where is the key held?"),
+                    tr("Secret key file"),
+                    tr("AWS KMS"),
+                    device_label,
+                ) {
+                    1 => {
+                        let signer = match get_aws_kms_signer() {
+                            Some(signer) => signer,
+                            None => continue,
+                        };
+                        casper_multisig_tool::sign_deploy_remotely(&mut deploy, &signer)
+                    }
+                    2 => match choose_device() {
+                        Some(Device::Ledger) => {
+                            let account_index = match get_ledger_account_index() {
+                                Some(account_index) => account_index,
+                                None => continue,
+                            };
+                            dialog::message_default(tr(
+                                "Open the Casper app on the Ledger device, then review and sign \
+                                the deploy on it",
+                            ));
+                            casper_multisig_tool::sign_deploy_with_ledger(
+                                &mut deploy,
+                                account_index,
+                            )
+                        }
+                        Some(Device::YubiKey) => {
+                            let signer = match get_yubikey_signer() {
+                                Some(signer) => signer,
+                                None => continue,
+                            };
+                            dialog::message_default(tr(
+                                "Touch the YubiKey if it flashes while signing",
+                            ));
+                            casper_multisig_tool::sign_deploy_remotely(&mut deploy, &signer)
+                        }
+                        None => continue,
+                    },
+                    _ => {
+                        let secret_key = match get_secret_key_file(tr("Choose Secret Key File")) {
+                            Some(file) => file,
+                            None => continue,
+                        };
+                        casper_multisig_tool::sign_deploy(&mut deploy, &secret_key)
+                    }
+                };
+                if let Err(error) = result {
+                    dialog::alert_default(&error.to_string());
+                }
+            }
+            2 => {
+                let from_bundle = dialog::choice_default(
+                    tr("Import approvals from where?"),
+                    tr("Approvals file"),
+                    tr("Offline bundle"),
+                    "",
+                ) == 1;
+                let (source, result) = if from_bundle {
+                    let bundle_dir = match get_dir(tr("Choose Offline Signing Bundle")) {
+                        Some(dir) => dir,
+                        None => continue,
+                    };
+                    (
+                        bundle_dir.display().to_string(),
+                        casper_multisig_tool::import_offline_bundle(&mut deploy, &bundle_dir),
+                    )
+                } else {
+                    let approvals_file = match get_approvals_file() {
+                        Some(file) => file,
+                        None => continue,
+                    };
+                    if !confirm_approvals(&deploy, &approvals_file) {
+                        continue;
+                    }
+                    let result =
+                        casper_multisig_tool::import_approvals(&mut deploy, &approvals_file);
+                    (approvals_file, result)
+                };
+                match result {
+                    Ok(added) if added.is_empty() => {
+                        dialog::message_default(&casper_multisig_tool::translate_with(
+                            "{} holds no approvals which the deploy doesn't already have",
+                            &[&source.to_string()],
+                        ))
+                    }
+                    Ok(_) => (),
+                    Err(error) => dialog::alert_default(&error.to_string()),
+                }
+            }
+            _ => break,
+        }
+    }
+
+    if let Err(error) = casper_multisig_tool::save_deploy(&deploy, &deploy_path)
+        .and_then(|_| casper_multisig_tool::save_unsigned_deploy(&deploy, &unsigned_deploy_path))
+    {
+        dialog::alert_default(&error.to_string());
+        return;
+    }
+    append_line(text_display, "");
+    append_line(
+        text_display,
+        &casper_multisig_tool::translate_with(
+            "Saved deploy {} to {}",
+            &[&deploy.id().to_string(), &deploy_path.display().to_string()],
+        ),
+    );
+    for approval in deploy.approvals() {
+        append_line(
+            text_display,
+            &casper_multisig_tool::translate_with("  signed by {}", &[&approval.signer().to_hex()]),
+        );
+    }
+    if let Some(account_state) = &account_state {
+        let progress = casper_multisig_tool::approval_progress(&deploy, account_state);
+        for line in progress.to_string().lines() {
+            append_line(text_display, line);
+        }
+    }
+    append_line(
+        text_display,
+        &casper_multisig_tool::translate_with(
+            "Other signers can sign {} with casper-client sign-deploy",
+            &[&unsigned_deploy_path.display().to_string()],
+        ),
+    );
+
+    if dialog::choice_default(
+        tr("Export an offline signing bundle for signers whose machines are never online?"),
+        tr("No"),
+        tr("Yes"),
+        "",
+    ) != 1
+    {
+        return;
+    }
+    let parent_dir = match get_dir(tr("Choose Where to Save the Offline Signing Bundle")) {
+        Some(dir) => dir,
+        None => return,
+    };
+    match casper_multisig_tool::export_offline_bundle(&parent_dir, wasm_path, &deploy) {
+        Ok(bundle_dir) => append_line(
+            text_display,
+            &casper_multisig_tool::translate_with(
+                "Exported an offline signing bundle to {}.  Import it once signers have added \
+                their signed copies to its approvals folder.",
+                &[&bundle_dir.display().to_string()],
+            ),
+        ),
+        Err(error) => dialog::alert_default(&error.to_string()),
+    }
+}
+
+/// The index of "Ledger" among the deploy screen's signing key choices, after the secret key file
+/// and the encrypted keystore.
+const LEDGER_KEY_SOURCE: i32 = 2;
+
+/// Shows the deploy screen, where a deploy of the session Wasm at `wasm_path` is built for the
+/// chosen network with the entered payment and time-to-live, signed with a secret key file,
+/// encrypted keystore or Ledger device, and sent, with its status shown as it progresses.
+///
+/// As with "Sign deploy", the deploy is saved to the project after every change, so the compile
+/// window's other actions, e.g. "Dry run", act on it too.
+fn show_deploy_window(wasm_path: PathBuf) {
+    let width = 1200;
+    let height = 800;
+    let label_width = 180;
+    let mut window = Window::default()
+        .with_size(width, height)
+        .center_screen()
+        .with_label(tr("Deploy"));
+    window.make_modal(true);
+
+    let field_x = PADDING + label_width;
+    let field_width = width - field_x - PADDING;
+    let short_field_width = 300;
+    let row_y = |row: i32| PADDING + (row * (BUTTON_HEIGHT + PADDING));
+
+    let profiles = casper_multisig_tool::network_profiles();
+    let mut network = Choice::new(
+        field_x,
+        row_y(0),
+        short_field_width,
+        BUTTON_HEIGHT,
+        "Network",
+    );
+    for profile in &profiles {
+        network.add_choice(&escape_menu_label(&profile.name));
+    }
+    network.add_choice("Other");
+    let selected = casper_multisig_tool::selected_network()
+        .and_then(|selected| {
+            profiles
+                .iter()
+                .position(|profile| profile.name == selected.name)
+        })
+        .unwrap_or(profiles.len());
+    network.set_value(selected as i32);
+
+    let mut node_address = Input::new(
+        field_x,
+        row_y(1),
+        field_width,
+        BUTTON_HEIGHT,
+        "Node address",
+    );
+    node_address.set_tooltip(tr(
+        "The node the deploy is sent to, or several separated by commas to fail over between them",
+    ));
+    let chain_name = Input::new(
+        field_x,
+        row_y(2),
+        short_field_width,
+        BUTTON_HEIGHT,
+        "Chain name",
+    );
+    if let Some(profile) = profiles.get(selected) {
+        node_address.set_value(&profile.node_addresses.join(", "));
+        chain_name.set_value(&profile.chain_name);
+    }
+    let network_node_address = node_address.clone();
+    let network_chain_name = chain_name.clone();
+    network.set_callback(move |network| {
+        let profile = Some(network.value())
+            .filter(|index| *index >= 0)
+            .and_then(|index| profiles.get(index as usize));
+        if let Some(profile) = profile {
+            network_node_address.set_value(&profile.node_addresses.join(", "));
+            network_chain_name.set_value(&profile.chain_name);
+        }
+    });
+
+    let payment = Input::new(
+        field_x,
+        row_y(3),
+        short_field_width,
+        BUTTON_HEIGHT,
+        "Payment (motes)",
+    );
+    let mut cost_estimate = Frame::new(
+        payment.x() + short_field_width + PADDING,
+        row_y(3),
+        field_width - short_field_width - PADDING,
+        BUTTON_HEIGHT,
+        None,
+    );
+    cost_estimate.set_align(Align::Left | Align::Inside | Align::Clip);
+    match casper_multisig_tool::estimate_cost(&wasm_path) {
+        Ok(estimate) => {
+            payment.set_value(&estimate.suggested_payment.to_string());
+            cost_estimate.set_label(&casper_multisig_tool::translate_with(
+                "Estimated to cost {} motes{}, by {}",
+                &[
+                    &estimate.cost.to_string(),
+                    &fiat_cost(estimate.cost),
+                    &estimate.method.to_string(),
+                ],
+            ));
+        }
+        Err(_) => payment.set_value(DEFAULT_PAYMENT_AMOUNT),
+    }
+
+    let mut ttl = Input::new(
+        field_x,
+        row_y(4),
+        short_field_width,
+        BUTTON_HEIGHT,
+        "Time to live",
+    );
+    ttl.set_value(
+        &DeployParams::new("", U512::zero(), PathBuf::new())
+            .ttl
+            .to_string(),
+    );
+    ttl.set_tooltip(tr(
+        "How long after it's built the deploy can be executed, e.g. 30m or 2h, and at most a day.  \
+        Every approval must be collected and the deploy sent within it.",
+    ));
+
+    let mut key_source = Choice::new(
+        field_x,
+        row_y(5),
+        short_field_width,
+        BUTTON_HEIGHT,
+        "Signing key",
+    );
+    key_source.add_choice("Secret key file|Encrypted keystore");
+    if casper_multisig_tool::ledger_supported() {
+        key_source.add_choice("Ledger");
+    }
+    key_source.set_value(0);
+
+    let browse_button_width = 120;
+    let mut key_file = Input::new(
+        field_x,
+        row_y(6),
+        field_width - browse_button_width - PADDING,
+        BUTTON_HEIGHT,
+        "Key file",
+    );
+    key_file.set_tooltip(tr(
+        "The deploy is built with the main account's key, and other keys add their approvals to it",
+    ));
+    let mut browse_button = Button::new(
+        key_file.x() + key_file.width() + PADDING,
+        row_y(6),
+        browse_button_width,
+        BUTTON_HEIGHT,
+        tr("Browse..."),
+    );
+    browse_button.set_color(Color::from_u32(palette().button));
+    let mut ledger_index = Input::new(
+        field_x,
+        row_y(6),
+        short_field_width,
+        BUTTON_HEIGHT,
+        "Ledger key index",
+    );
+    ledger_index.set_value("0");
+    ledger_index.hide();
+
+    let browse_key_file = key_file.clone();
+    browse_button.set_callback(move |_| {
+        if let Some(file) = get_secret_key_file(tr("Choose Secret Key File or Keystore")) {
+            browse_key_file.set_value(&file);
+        }
+    });
+    let mut source_key_file = key_file.clone();
+    let mut source_browse_button = browse_button.clone();
+    let mut source_ledger_index = ledger_index.clone();
+    key_source.set_callback(move |key_source| {
+        let ledger = key_source.value() == LEDGER_KEY_SOURCE;
+        if ledger {
+            source_key_file.hide();
+            source_browse_button.hide();
+            source_ledger_index.show();
+        } else {
+            source_ledger_index.hide();
+            source_key_file.show();
+            source_browse_button.show();
+        }
+        source_key_file.window().unwrap().redraw();
+    });
+
+    let button_width = 150;
+    let mut build_button = Button::new(
+        field_x,
+        row_y(7),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Build and sign"),
+    );
+    build_button.set_color(Color::from_u32(palette().button));
+    build_button.set_tooltip(tr(
+        "Build a new deploy for the network with the payment and time to live above, signed by the \
+        main account's key",
+    ));
+    let mut approve_button = Button::new(
+        build_button.x() + button_width + PADDING,
+        row_y(7),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Add approval"),
+    );
+    approve_button.set_color(Color::from_u32(palette().button));
+    approve_button.set_tooltip(tr(
+        "Sign the deploy with the key above, adding its approval",
+    ));
+    let mut send_button = Button::new(
+        approve_button.x() + button_width + PADDING,
+        row_y(7),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Send"),
+    );
+    send_button.set_color(Color::from_u32(palette().button));
+    send_button.set_tooltip(tr(
+        "Send the deploy to the node, and wait for it to be executed",
+    ));
+    let mut qr_code_button = Button::new(
+        send_button.x() + button_width + PADDING,
+        row_y(7),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Show QR code"),
+    );
+    qr_code_button.set_color(Color::from_u32(palette().button));
+    qr_code_button.set_tooltip(tr(
+        "Show the deploy hash as a QR code, e.g. to track the deploy on a phone",
+    ));
+
+    let mut deploy_status = Frame::new(
+        PADDING,
+        row_y(8),
+        width - (2 * PADDING),
+        BUTTON_HEIGHT,
+        tr("No deploy built yet"),
+    );
+    deploy_status.set_align(Align::Left | Align::Inside | Align::Clip);
+
+    let close_button_width = 100;
+    let mut close_button = Button::new(
+        width - PADDING - close_button_width,
+        height - PADDING - BUTTON_HEIGHT,
+        close_button_width,
+        BUTTON_HEIGHT,
+        tr("Close"),
+    );
+    close_button.set_color(Color::from_u32(palette().button));
+    close_button.set_callback(|button| button.window().unwrap().hide());
+
+    let mut text_display = TextDisplay::new(
+        PADDING,
+        row_y(9),
+        width - (2 * PADDING),
+        close_button.y() - row_y(9) - PADDING,
+        None,
+    );
+    text_display.set_buffer(Some(TextBuffer::default()));
+    text_display.set_text_font(Font::Courier);
+    text_display.set_text_size(code_text_size());
+
+    // A button only so that a sent deploy's result can be clicked to view it in the explorer.
+    let mut result_pane = Button::new(
+        PADDING,
+        close_button.y(),
+        close_button.x() - (2 * PADDING),
+        BUTTON_HEIGHT,
+        None,
+    );
+    result_pane.set_frame(FrameType::FlatBox);
+    result_pane.set_down_frame(FrameType::FlatBox);
+    result_pane.set_align(Align::Inside | Align::Left | Align::Clip);
+    result_pane.hide();
+
+    window.end();
+    window.show();
+
+    let project_dir =
+        casper_multisig_tool::project_path().join(casper_multisig_tool::contract_name());
+    let deploy_path = project_dir.join("deploy.json");
+    let deploy = Arc::new(Mutex::new(
+        casper_multisig_tool::load_deploy(&deploy_path).ok(),
+    ));
+    if let Some(deploy) = deploy.lock().unwrap().as_ref() {
+        show_deploy_status(deploy, &mut deploy_status);
+    }
+
+    let build_deploy = Arc::clone(&deploy);
+    let build_key_source = key_source.clone();
+    let build_key_file = key_file.clone();
+    let mut build_deploy_status = deploy_status.clone();
+    let mut build_text_display = text_display.clone();
+    build_button.set_callback(move |_| {
+        if build_key_source.value() == LEDGER_KEY_SOURCE {
+            dialog::alert_default(tr(
+                "The deploy is built and first signed by the main account's key, which must be a \
+                secret key file or keystore.  A Ledger key can then add its approval.",
+            ));
+            return;
+        }
+        let secret_key = match chosen_secret_key(&build_key_file) {
+            Some(secret_key) => secret_key,
+            None => return,
+        };
+        let payment_amount = match U512::from_dec_str(payment.value().trim()) {
+            Ok(amount) => amount,
+            Err(error) => {
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "failed to parse '{}' as a payment amount: {}",
+                    &[&payment.value(), &format!("{:?}", error)],
+                ));
+                return;
+            }
+        };
+        let mut params = DeployParams::new(
+            &chain_name.value(),
+            payment_amount,
+            PathBuf::from(secret_key),
+        );
+        params.ttl = match ttl.value().trim().parse() {
+            Ok(ttl) => ttl,
+            Err(error) => {
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "failed to parse '{}' as a time to live: {}",
+                    &[&ttl.value(), &error.to_string()],
+                ));
+                return;
+            }
+        };
+        let deploy = match build_deploy_from_params(&wasm_path, params) {
+            Some(deploy) => deploy,
+            None => return,
+        };
+        append_line(
+            &mut build_text_display,
+            &casper_multisig_tool::translate_with("Built deploy {}", &[&deploy.id().to_string()]),
+        );
+        save_deploy_in_project(&deploy, &mut build_deploy_status, &mut build_text_display);
+        *build_deploy.lock().unwrap() = Some(deploy);
+    });
+
+    let approve_deploy = Arc::clone(&deploy);
+    let mut approve_deploy_status = deploy_status.clone();
+    let mut approve_text_display = text_display.clone();
+    approve_button.set_callback(move |_| {
+        let mut deploy = approve_deploy.lock().unwrap();
+        let deploy = match deploy.as_mut() {
+            Some(deploy) => deploy,
+            None => return dialog::alert_default(tr("Build the deploy before adding approvals")),
+        };
+        let result = if key_source.value() == LEDGER_KEY_SOURCE {
+            let account_index = match ledger_index.value().trim().parse::<u32>() {
+                Ok(account_index) => account_index,
+                Err(error) => {
+                    return dialog::alert_default(&casper_multisig_tool::translate_with(
+                        "failed to parse '{}' as a key index: {}",
+                        &[&ledger_index.value(), &error.to_string()],
+                    ))
+                }
+            };
+            dialog::message_default(tr(
+                "Open the Casper app on the Ledger device, then review and sign the deploy on it",
+            ));
+            casper_multisig_tool::sign_deploy_with_ledger(deploy, account_index)
+        } else {
+            let secret_key = match chosen_secret_key(&key_file) {
+                Some(secret_key) => secret_key,
+                None => return,
+            };
+            casper_multisig_tool::sign_deploy(deploy, &secret_key)
+        };
+        if let Err(error) = result {
+            return dialog::alert_default(&error.to_string());
+        }
+        save_deploy_in_project(
+            deploy,
+            &mut approve_deploy_status,
+            &mut approve_text_display,
+        );
+    });
+
+    let qr_code_deploy = Arc::clone(&deploy);
+    qr_code_button.set_callback(move |_| {
+        let deploy_hash = match qr_code_deploy.lock().unwrap().as_ref() {
+            Some(deploy) => format!("{:x}", deploy.id().inner()),
+            None => return dialog::alert_default(tr("Build the deploy before showing its hash")),
+        };
+        show_qr_code_window(vec![(tr("Deploy hash").to_string(), deploy_hash)]);
+    });
+
+    send_button.set_callback(move |button| {
+        let expired = match deploy.lock().unwrap().as_ref() {
+            Some(deploy) => casper_multisig_tool::time_to_expiry(deploy).is_none(),
+            None => {
+                return dialog::alert_default(tr("Build and sign the deploy before sending it"))
+            }
+        };
+        if expired {
+            return dialog::alert_default(tr(
+                "The deploy has expired, so the network won't execute it.  Choose \"Build and \
+                sign\" to build it again and collect the approvals again.",
+            ));
+        }
+        let node_address = node_address.value();
+        let receiver = match casper_multisig_tool::send_deploy(
+            &deploy_path,
+            &node_address,
+            Some(default_event_stream_url(&node_address).as_str()).filter(|url| !url.is_empty()),
+        ) {
+            Ok(receiver) => receiver,
+            Err(error) => return dialog::alert_default(&error.to_string()),
+        };
+        button.deactivate();
+
+        let mut button = button.clone();
+        let mut text_display = text_display.clone();
+        let mut result_pane = result_pane.clone();
+        let deploy_path = deploy_path.clone();
+        let explorer_url = deploy
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(casper_multisig_tool::deploy_explorer_url);
+        thread::spawn(move || {
+            if show_deploy_events(
+                receiver,
+                &deploy_path,
+                &node_address,
+                explorer_url.as_deref(),
+                &mut text_display,
+                &mut result_pane,
+            ) {
+                button.activate();
+            }
+        });
+    });
+}
+
+/// Returns the secret key file or keystore entered in `key_file`, unlocking it if it's a keystore,
+/// or `None` if none was entered or it couldn't be unlocked.
+fn chosen_secret_key(key_file: &Input) -> Option<String> {
+    let file = key_file.value().trim().to_string();
+    if file.is_empty() {
+        dialog::alert_default(tr("Choose the secret key file or keystore first"));
+        return None;
+    }
+    if !unlock_keystore(&file) {
+        return None;
+    }
+    Some(file)
+}
+
+/// Saves `deploy` to the project as the deploy to be sent, along with an unsigned copy for other
+/// signers, and shows its approvals and expiry.
+fn save_deploy_in_project(
+    deploy: &Deploy,
+    deploy_status: &mut Frame,
+    text_display: &mut TextDisplay,
+) {
+    let project_dir =
+        casper_multisig_tool::project_path().join(casper_multisig_tool::contract_name());
+    let deploy_path = project_dir.join("deploy.json");
+    if let Err(error) = casper_multisig_tool::save_deploy(deploy, &deploy_path).and_then(|_| {
+        casper_multisig_tool::save_unsigned_deploy(deploy, project_dir.join("unsigned_deploy.json"))
+    }) {
+        return dialog::alert_default(&error.to_string());
+    }
+    append_line(
+        text_display,
+        &casper_multisig_tool::translate_with(
+            "Saved deploy {} to {}",
+            &[&deploy.id().to_string(), &deploy_path.display().to_string()],
+        ),
+    );
+    for approval in deploy.approvals() {
+        append_line(
+            text_display,
+            &casper_multisig_tool::translate_with("  signed by {}", &[&approval.signer().to_hex()]),
+        );
+    }
+    show_deploy_status(deploy, deploy_status);
+}
+
+/// Shows the number of approvals `deploy` has and how long until it expires in `deploy_status`.
+fn show_deploy_status(deploy: &Deploy, deploy_status: &mut Frame) {
+    deploy_status.set_label(&casper_multisig_tool::translate_with(
+        "Deploy {} has {} approval(s).  {}.",
+        &[
+            &deploy.id().to_string(),
+            &deploy.approvals().len().to_string(),
+            &expiry_status(deploy),
+        ],
+    ));
+    deploy_status.redraw();
+}
+
+/// Returns the value of `motes` in fiat to show after an amount of motes, e.g.
+/// ` (12.34 USD / 11.20 EUR)`, or an empty string if no price feed is set.
+fn fiat_cost(motes: U512) -> String {
+    match casper_multisig_tool::cspr_price() {
+        Ok(Some(price)) => format!(" ({})", price.fiat_value(motes)),
+        Ok(None) => String::new(),
+        Err(error) => {
+            casper_multisig_tool::translate_with(" (no fiat estimate: {})", &[&error.to_string()])
+        }
+    }
+}
+
+/// Builds a deploy of the session Wasm at `wasm_path` from the chain name, payment amount and
+/// secret key entered by the user, or returns `None` if they cancelled or it couldn't be built.
+fn build_deploy(wasm_path: &Path) -> Option<Deploy> {
+    let chain_name = input_chain_name()?;
+    let (prompt, default_payment) = match casper_multisig_tool::estimate_cost(wasm_path) {
+        Ok(estimate) => (
+            casper_multisig_tool::translate_with(
+                "Enter payment amount (motes).  The deploy is estimated to cost {} motes{}, by {}.",
+                &[
+                    &estimate.cost.to_string(),
+                    &fiat_cost(estimate.cost),
+                    &estimate.method.to_string(),
+                ],
+            ),
+            estimate.suggested_payment.to_string(),
+        ),
+        Err(_) => (
+            tr("Enter payment amount (motes)").to_string(),
+            DEFAULT_PAYMENT_AMOUNT.to_string(),
+        ),
+    };
+    let payment_amount = dialog::input_default(&prompt, &default_payment)?;
+    let payment_amount = match U512::from_dec_str(payment_amount.trim()) {
+        Ok(amount) => amount,
+        Err(error) => {
+            dialog::alert_default(&casper_multisig_tool::translate_with(
+                "failed to parse '{}' as a payment amount: {}",
+                &[&payment_amount, &format!("{:?}", error)],
+            ));
+
+            return None;
+        }
+    };
+    let secret_key = get_secret_key_file(tr("Choose the Main Account's Secret Key File"))?;
+    build_deploy_from_params(
+        wasm_path,
+        DeployParams::new(&chain_name, payment_amount, PathBuf::from(secret_key)),
+    )
+}
+
+/// Builds a deploy of the session Wasm at `wasm_path` from `params`, adding the runtime args if the
+/// session is the generic contract, or returns `None` if it couldn't be built.
+fn build_deploy_from_params(wasm_path: &Path, mut params: DeployParams) -> Option<Deploy> {
+    if casper_multisig_tool::prebuilt_session_wasm().is_some() {
+        params.session_args = match casper_multisig_tool::session_runtime_args() {
+            Ok(args) => args,
+            Err(error) => {
+                dialog::alert_default(&error.to_string());
+                return None;
+            }
+        };
+    }
+    match casper_multisig_tool::build_deploy(wasm_path, &params) {
+        Ok(deploy) => Some(deploy),
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            None
+        }
+    }
+}
+
+/// Returns the account hashes as formatted strings, each with a tooltip indicating the origin of
+/// the account hash, or `None` if the user didn't enter a valid path or cancelled the operation.
+///
+/// A Casper Wallet export can hold several accounts, whose names are set as their key labels.
+fn get_account_hashes_from_key_file() -> Option<Vec<(String, String)>> {
+    let mut file_dialog = FileDialog::new(FileDialogType::BrowseFile);
+    if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
+        let _ = file_dialog.set_directory(start_dir);
+    }
+    file_dialog.set_option(FileDialogOptions::UseFilterExt);
+    file_dialog.set_title(tr("Choose Public Key File"));
+    file_dialog.set_filter(
+        "PEM-encoded Public Key Files \t*public_key*.pem\nHex-encoded Public Key Files \
+        \t*public_key*_hex*\nEncrypted Keystore Files \t*.json\nCasper Wallet Exports \
+        \t*.{json,pem}",
+    );
+    file_dialog.show();
+
+    if file_dialog.filename() == PathBuf::default() {
+        return None;
+    }
+
+    let file_path = file_dialog.filename().to_string_lossy().to_string();
+    get_account_hashes_from_file(&file_path)
+}
+
+/// Returns the account hashes of the public key file, encrypted keystore or Casper Wallet export at
+/// `file_path` as formatted strings, each with a tooltip indicating the origin of the account
+/// hash, or `None` if the file isn't valid or the user cancelled unlocking it.
+fn get_account_hashes_from_file(file_path: &str) -> Option<Vec<(String, String)>> {
+    if casper_multisig_tool::is_wallet_export(file_path) {
+        return match casper_multisig_tool::import_wallet_export(file_path) {
+            Ok(accounts) => Some(
+                accounts
+                    .into_iter()
+                    .map(|(account_hash, public_key, label)| {
+                        let tooltip = match label {
+                            Some(label) => casper_multisig_tool::translate_with(
+                                "'{}' with public key {}, from Casper Wallet export {}",
+                                &[&label, &public_key, file_path],
+                            ),
+                            None => casper_multisig_tool::translate_with(
+                                "Public key {}, from Casper Wallet export {}",
+                                &[&public_key, file_path],
+                            ),
+                        };
+                        (account_hash, tooltip)
+                    })
+                    .collect(),
+            ),
+            Err(error) => {
+                dialog::alert_default(&error.to_string());
+                None
+            }
+        };
+    }
+    // A keystore which doesn't record its public key must be unlocked to derive it.
+    if casper_multisig_tool::is_encrypted_keystore(file_path)
+        && casper_multisig_tool::get_account_hash_from_file(file_path).is_err()
+        && !unlock_keystore(file_path)
+    {
+        return None;
+    }
+
+    match casper_multisig_tool::get_account_hash_from_file(file_path) {
+        Ok(account_hash) => {
+            let tooltip =
+                casper_multisig_tool::translate_with("Derived from contents of {}", &[file_path]);
+            Some(vec![(account_hash, tooltip)])
+        }
+        Err(error) => {
+            dialog::alert_default(error.to_string().as_str());
+            None
+        }
+    }
+}
+
+/// Returns the paths of the files dropped onto the window, given the dropped `text`.
+///
+/// Some desktops drop files as `file://` URIs rather than plain paths, one per line.
+fn dropped_file_paths(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.strip_prefix("file://") {
+            Some(path) => percent_decode(path),
+            None => line.to_string(),
+        })
+        .collect()
+}
+
+/// Decodes the `%XX` escapes in the path of a `file://` URI, e.g. `%20` for a space.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            if let Some(byte) = path
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the account hash as a formatted string and a tooltip indicating the origin of the
+/// account hash, or `None` if the user didn't enter a valid public key or cancelled the operation.
+fn get_account_hash_from_hex_public_key() -> Option<(String, String)> {
+    // let mut window = Window::default().with_size(1000, 60).center_screen();
+    // window.make_modal(true);
+    //
+    // let mut input = Input::new(10, 10, 800, 40, "Enter formatted public key");
+    // input.set_trigger(CallbackTrigger::EnterKey);
+    //
+    // window.end();
+    // window.show();
+    //
+    // let (sender, receiver) = app::channel::<()>();
+    //
+    // input.emit(sender, ());
+    //
+    // let _ = receiver.recv();
+    // let hex_public_key = input.value();
+
+    let hex_public_key = dialog::input_default(tr("Enter formatted public key"), "")?;
+
+    match casper_multisig_tool::get_account_hash_from_hex_encoded_public_key(&hex_public_key) {
+        Ok(account_hash) => {
+            let tooltip = casper_multisig_tool::translate_with(
+                "Derived from public key {}",
+                &[&hex_public_key],
+            );
+            Some((account_hash, tooltip))
+        }
+        Err(error) => {
+            dialog::alert_default(error.to_string().as_str());
+            None
+        }
+    }
+}
+
+/// Returns the account hash as a formatted string and a tooltip indicating the origin of the
+/// account hash, or `None` if the user cancelled the operation or the Ledger device failed to
+/// provide a key.
+fn get_account_hash_from_ledger() -> Option<(String, String)> {
+    let account_index = get_ledger_account_index()?;
+    dialog::message_default(tr(
+        "Open the Casper app on the Ledger device, then confirm the address it shows",
+    ));
+    let result = casper_multisig_tool::ledger_public_key(account_index).and_then(|public_key| {
+        casper_multisig_tool::get_account_hash_from_hex_encoded_public_key(&public_key)
+            .map(|account_hash| (account_hash, public_key))
+    });
+    match result {
+        Ok((account_hash, public_key)) => {
+            let tooltip = casper_multisig_tool::translate_with(
+                "Derived from Ledger key m/44'/506'/0'/0/{} ({})",
+                &[&account_index.to_string(), &public_key],
+            );
+            Some((account_hash, tooltip))
+        }
+        Err(error) => {
+            dialog::alert_default(error.to_string().as_str());
+            None
+        }
+    }
+}
+
+/// Returns the account hash as a formatted string and a tooltip indicating the origin of the
+/// account hash, or `None` if the user cancelled the operation or the YubiKey failed to provide a
+/// key.
+fn get_account_hash_from_yubikey() -> Option<(String, String)> {
+    let signer = YubiKeySigner::new(get_yubikey_slot()?, None);
+    let result = casper_multisig_tool::remote_signer_public_key(&signer).and_then(|public_key| {
+        casper_multisig_tool::get_account_hash_from_hex_encoded_public_key(&public_key)
+            .map(|account_hash| (account_hash, public_key))
+    });
+    match result {
+        Ok((account_hash, public_key)) => {
+            let tooltip = casper_multisig_tool::translate_with(
+                "Derived from YubiKey PIV slot {} ({})",
+                &[&format!("{:02x}", signer.slot), &public_key],
+            );
+            Some((account_hash, tooltip))
+        }
+        Err(error) => {
+            dialog::alert_default(error.to_string().as_str());
+            None
+        }
+    }
+}
+
+/// Generates a key pair whose account hash starts with the prefix entered by the user, showing
+/// the search's progress until it finishes or the user stops it.
+///
+/// Returns the account hash as a formatted string and a tooltip indicating where the key pair was
+/// saved, or `None` if the user cancelled or no key pair was found.
+fn generate_vanity_key() -> Option<(String, String)> {
+    let prefix = dialog::input_default(
+        &casper_multisig_tool::translate_with(
+            "Enter the hex prefix for the account hash, up to {} digits.  Each digit makes the \
+            search 16 times longer.",
+            &[&casper_multisig_tool::max_vanity_prefix_length().to_string()],
+        ),
+        "",
+    )?;
+    let expected_attempts = match casper_multisig_tool::vanity_expected_attempts(&prefix) {
+        Ok(attempts) => attempts,
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            return None;
+        }
+    };
+    let algorithm = match dialog::choice_default(
+        &casper_multisig_tool::translate_with(
+            "Around {} key pairs must be generated on average to find a match.\n\nWhich kind of \
+            key?",
+            &[&expected_attempts.to_string()],
+        ),
+        tr("Cancel"),
+        tr("Ed25519"),
+        tr("secp256k1"),
+    ) {
+        1 => KeyAlgorithm::Ed25519,
+        2 => KeyAlgorithm::Secp256k1,
+        _ => return None,
+    };
+    let max_threads = casper_multisig_tool::max_vanity_threads();
+    let threads = dialog::input_default(
+        &casper_multisig_tool::translate_with(
+            "Enter the number of threads to search with, up to {}",
+            &[&max_threads.to_string()],
+        ),
+        &max_threads.to_string(),
+    )?;
+    let threads = match threads.trim().parse::<usize>() {
+        Ok(threads) if (1..=max_threads).contains(&threads) => threads,
+        _ => {
+            dialog::alert_default(&casper_multisig_tool::translate_with(
+                "the number of threads must be from 1 to {}",
+                &[&max_threads.to_string()],
+            ));
+            return None;
+        }
+    };
+    let max_minutes = casper_multisig_tool::max_vanity_duration().as_secs() / 60;
+    let minutes = dialog::input_default(
+        &casper_multisig_tool::translate_with(
+            "Enter the number of minutes to search for before giving up, up to {}",
+            &[&max_minutes.to_string()],
+        ),
+        "10",
+    )?;
+    let minutes = match minutes.trim().parse::<u64>() {
+        Ok(minutes) if (1..=max_minutes).contains(&minutes) => minutes,
+        _ => {
+            dialog::alert_default(&casper_multisig_tool::translate_with(
+                "the number of minutes must be from 1 to {}",
+                &[&max_minutes.to_string()],
+            ));
+            return None;
+        }
+    };
+    let output_dir = get_dir(tr("Choose Dir to Save the Key Pair"))?;
+
+    let receiver = match casper_multisig_tool::generate_vanity_key(
+        &prefix,
+        algorithm,
+        threads,
+        Duration::from_secs(minutes * 60),
+        &output_dir,
+    ) {
+        Ok(receiver) => receiver,
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            return None;
+        }
+    };
+
+    let mut window = Window::default()
+        .with_size(600, 130)
+        .center_screen()
+        .with_label(tr("Generating vanity key"));
+    window.make_modal(true);
+    let mut progress = Frame::new(PADDING, PADDING, 580, 60, tr("Starting the search"));
+    progress.set_align(Align::Left | Align::Inside | Align::Wrap);
+    let mut stop_button = Button::new(600 - PADDING - 150, 80, 150, BUTTON_HEIGHT, tr("Stop"));
+    stop_button.set_color(Color::from_u32(palette().button));
+    window.end();
+    window.show();
+    stop_button.set_callback(move |button| button.window().unwrap().hide());
+
+    // Polled on this thread, so that the key can be added once it's found.  Closing the window
+    // drops the receiver, which stops the search.
+    while window.shown() {
+        let _ = app::wait_for(0.1);
+        let event = match receiver.try_recv() {
+            Ok(event) => event,
+            Err(mpsc::TryRecvError::Empty) => continue,
+            Err(mpsc::TryRecvError::Disconnected) => break,
+        };
+        match event {
+            VanityEvent::Progress {
+                attempts,
+                keys_per_second,
+            } => {
+                progress.set_label(&casper_multisig_tool::translate_with(
+                    "Searching for an account hash starting with {}: {} of around {} attempts, at \
+                    {} keys per second",
+                    &[
+                        prefix.trim(),
+                        &attempts.to_string(),
+                        &expected_attempts.to_string(),
+                        &keys_per_second.to_string(),
+                    ],
+                ));
+                window.redraw();
+            }
+            VanityEvent::Found {
+                account_hash,
+                public_key,
+                dir,
+            } => {
+                window.hide();
+                dialog::message_default(&casper_multisig_tool::translate_with(
+                    "Found {}\n\nThe key pair is saved in {}",
+                    &[&account_hash.to_string(), &dir.display().to_string()],
+                ));
+
+                let tooltip = casper_multisig_tool::translate_with(
+                    "Vanity key with public key {}, saved in {}",
+                    &[&public_key, &dir.display().to_string()],
+                );
+                return Some((account_hash, tooltip));
+            }
+            VanityEvent::Stopped { reason } => {
+                window.hide();
+                dialog::alert_default(&reason);
+                return None;
+            }
+        }
+    }
+    None
+}
+
+/// Splits `text` into lines of at most `max_line_length` characters, breaking between words unless
+/// a single word is longer.
+fn wrap_words(text: &str, max_line_length: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_line_length {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Returns the hex digits of the given formatted account hash with the middle elided, e.g.
+/// `1a2b3c4d…5e6f7a8b`, to fit a narrow column.
+fn abbreviated_account_hash(account_hash: &str) -> String {
+    let hex = account_hash.trim_start_matches("account-hash-");
+    match (hex.get(..8), hex.get(hex.len().saturating_sub(8)..)) {
+        (Some(start), Some(end)) if hex.len() > 16 => format!("{}…{}", start, end),
+        _ => hex.to_string(),
+    }
+}
+
+/// Returns the label of the given formatted account hash, if it has one.
+fn key_label(account_hash: &str) -> Option<String> {
+    casper_multisig_tool::key_labels()
+        .into_iter()
+        .find(|(labelled, _)| labelled == account_hash)
+        .map(|(_, label)| label)
+}
+
+/// Returns the keys entered by the user one per line, or `None` if they cancelled.
+///
+/// Each line is checked as it's typed, including against the `existing_account_hashes`, and the
+/// keys can only be added once every line is valid.
+fn get_bulk_keys(existing_account_hashes: Vec<String>) -> Option<Vec<BulkKey>> {
+    let width = 1200;
+    let height = 700;
+    let mut window = Window::default()
+        .with_size(width, height)
+        .center_screen()
+        .with_label(tr("Add keys in bulk"));
+    window.make_modal(true);
+
+    let mut input = MultilineInput::new(
+        PADDING,
+        PADDING + 40,
+        width - (2 * PADDING),
+        320,
+        "Paste one key per line, as a hex-encoded public key or an account hash, optionally as\n\
+        'label,key' or 'label,key,weight'.  Blank lines and lines starting with '#' are skipped.",
+    );
+    input.set_align(Align::TopLeft);
+    input.set_text_font(Font::Courier);
+    input.set_text_size(code_text_size());
+    input.set_trigger(CallbackTrigger::Changed);
+
+    let mut results = TextDisplay::new(
+        PADDING,
+        input.y() + input.height() + PADDING,
+        width - (2 * PADDING),
+        height - input.y() - input.height() - BUTTON_HEIGHT - (3 * PADDING),
+        None,
+    );
+    results.set_buffer(Some(TextBuffer::default()));
+    results.set_text_font(Font::Courier);
+    results.set_text_size(code_text_size());
+
+    let button_width = 150;
+    let mut add_button = Button::new(
+        width - PADDING - button_width,
+        height - PADDING - BUTTON_HEIGHT,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Add"),
+    );
+    add_button.set_color(Color::from_u32(palette().button));
+    add_button.deactivate();
+    let mut cancel_button = Button::new(
+        add_button.x() - PADDING - button_width,
+        add_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Cancel"),
+    );
+    cancel_button.set_color(Color::from_u32(palette().button));
+    window.end();
+    window.show();
+
+    let bulk_keys = Arc::new(Mutex::new(vec![]));
+    let bulk_keys_clone = Arc::clone(&bulk_keys);
+    let mut add_button_clone = add_button.clone();
+    input.set_callback(move |input| {
+        let mut bulk_keys = bulk_keys_clone.lock().unwrap();
+        bulk_keys.clear();
+        let mut lines = vec![];
+        let mut all_valid = true;
+        for result in casper_multisig_tool::parse_bulk_keys(&input.value()) {
+            match result {
+                Ok(bulk_key) if existing_account_hashes.contains(&bulk_key.account_hash) => {
+                    lines.push(casper_multisig_tool::translate_with(
+                        "line {}: {} is already added",
+                        &[&bulk_key.line.to_string(), &bulk_key.account_hash],
+                    ));
+                    all_valid = false;
+                }
+                Ok(bulk_key) => {
+                    let line = bulk_key.line.to_string();
+                    let weight = bulk_key.weight.unwrap_or(1).to_string();
+                    lines.push(match &bulk_key.label {
+                        Some(label) => casper_multisig_tool::translate_with(
+                            "line {}: {} ({}), weight {}",
+                            &[&line, &bulk_key.account_hash, label, &weight],
+                        ),
+                        None => casper_multisig_tool::translate_with(
+                            "line {}: {}, weight {}",
+                            &[&line, &bulk_key.account_hash, &weight],
+                        ),
+                    });
+                    bulk_keys.push(bulk_key);
+                }
+                Err(error) => {
+                    lines.push(error.to_string());
+                    all_valid = false;
+                }
+            }
+        }
+        let key_count = existing_account_hashes.len() + bulk_keys.len();
+        if key_count > MAX_ASSOCIATED_KEYS {
+            lines.push(casper_multisig_tool::translate_with(
+                "Too many keys: there can be at most {} associated keys, but this would make {}",
+                &[&MAX_ASSOCIATED_KEYS.to_string(), &key_count.to_string()],
+            ));
+            all_valid = false;
+        }
+        results.buffer().unwrap().set_text(&lines.join("\n"));
+        if all_valid && !bulk_keys.is_empty() {
+            add_button_clone.activate();
+        } else {
+            add_button_clone.deactivate();
+        }
+    });
+    let (sender, receiver) = app::channel::<bool>();
+    add_button.emit(sender, true);
+    cancel_button.emit(sender, false);
+
+    let mut add = false;
+    while window.shown() {
+        let _ = app::wait();
+        if let Some(value) = receiver.recv() {
+            add = value;
+            window.hide();
+        }
+    }
+    if !add {
+        return None;
+    }
+    let bulk_keys = bulk_keys.lock().unwrap().clone();
+    Some(bulk_keys)
+}
+
+/// Returns the account hashes chosen by the user from the address book, each with a tooltip
+/// indicating its origin, or `None` if they cancelled.  Their labels are set as the keys' labels.
+///
+/// Entries can also be removed from the address book here.
+fn get_account_hashes_from_address_book() -> Option<Vec<(String, String)>> {
+    let entries = match casper_multisig_tool::address_book() {
+        Ok(entries) => entries,
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            return None;
+        }
+    };
+    if entries.is_empty() {
+        dialog::message_default(tr(
+            "The address book is empty.  Save a key to it with the \"Save\" button beside the key.",
+        ));
+        return None;
+    }
+
+    let mut window = Window::default()
+        .with_size(1200, 500)
+        .center_screen()
+        .with_label(tr("Add from address book"));
+    window.make_modal(true);
+    let mut browser = MultiBrowser::new(
+        PADDING,
+        PADDING,
+        1200 - (2 * PADDING),
+        500 - BUTTON_HEIGHT - (3 * PADDING),
+        None,
+    );
+    browser.set_column_widths(&[300]);
+    browser.set_text_size(text_size());
+    for entry in &entries {
+        // The "@." stops a label starting with '@' being taken as formatting.
+        browser.add(&format!("@.{}\t@.{}", entry.label, entry.account_hash));
+    }
+    let button_width = 150;
+    let mut add_button = Button::new(
+        1200 - PADDING - button_width,
+        500 - PADDING - BUTTON_HEIGHT,
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Add selected"),
+    );
+    add_button.set_color(Color::from_u32(palette().button));
+    let mut remove_button = Button::new(
+        add_button.x() - PADDING - button_width,
+        add_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Remove selected"),
+    );
+    remove_button.set_color(Color::from_u32(palette().button));
+    remove_button.set_tooltip(tr("Remove the selected entries from the address book"));
+    let mut cancel_button = Button::new(
+        remove_button.x() - PADDING - button_width,
+        add_button.y(),
+        button_width,
+        BUTTON_HEIGHT,
+        tr("Cancel"),
+    );
+    cancel_button.set_color(Color::from_u32(palette().button));
+    window.end();
+    window.show();
+
+    // The entries still listed, in the same order as the browser's lines.
+    let entries = Arc::new(Mutex::new(entries));
+    let entries_clone = Arc::clone(&entries);
+    let mut browser_clone = browser.clone();
+    remove_button.set_callback(move |_| {
+        let mut entries = entries_clone.lock().unwrap();
+        // Removed from the bottom, so the remaining lines keep their numbers.
+        for line in (1..=browser_clone.size()).rev() {
+            if !browser_clone.selected(line) {
+                continue;
+            }
+            let index = line as usize - 1;
+            if let Err(error) =
+                casper_multisig_tool::remove_from_address_book(&entries[index].account_hash)
+            {
+                dialog::alert_default(&error.to_string());
+                return;
+            }
+            entries.remove(index);
+            browser_clone.remove(line);
+        }
+    });
+    let (sender, receiver) = app::channel::<bool>();
+    add_button.emit(sender, true);
+    cancel_button.emit(sender, false);
+
+    let mut add = false;
+    while window.shown() {
+        let _ = app::wait();
+        if let Some(value) = receiver.recv() {
+            add = value;
+            window.hide();
+        }
+    }
+    if !add {
+        return None;
+    }
+
+    let entries = entries.lock().unwrap();
+    let mut key_labels = casper_multisig_tool::key_labels();
+    let mut chosen = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        if !browser.selected(index as i32 + 1) {
+            continue;
+        }
+        key_labels.retain(|(labelled, _)| *labelled != entry.account_hash);
+        key_labels.push((entry.account_hash.clone(), entry.label.clone()));
+        let tooltip = match &entry.public_key {
+            Some(public_key) => casper_multisig_tool::translate_with(
+                "From address book entry '{}', derived from public key {}",
+                &[&entry.label, public_key],
+            ),
+            None => casper_multisig_tool::translate_with(
+                "From address book entry '{}'",
+                &[&entry.label],
+            ),
+        };
+        chosen.push((entry.account_hash.clone(), tooltip));
+    }
+    if let Err(error) = casper_multisig_tool::set_key_labels(key_labels) {
+        dialog::alert_default(&error.to_string());
+    }
+    Some(chosen)
+}
+
+/// Saves the given formatted account hash to the address book under a label entered by the user,
+/// which defaults to the key's existing label.
+fn save_to_address_book(account_hash: &str) {
+    let existing_label = key_label(account_hash)
+        .or_else(|| {
+            casper_multisig_tool::address_book()
+                .ok()?
+                .into_iter()
+                .find(|entry| entry.account_hash == account_hash)
+                .map(|entry| entry.label)
+        })
+        .unwrap_or_default();
+    let label = match dialog::input_default(
+        &casper_multisig_tool::translate_with(
+            "Enter a label for {} in the address book",
+            &[account_hash],
+        ),
+        &existing_label,
+    ) {
+        Some(label) => label,
+        None => return,
+    };
+    if let Err(error) = casper_multisig_tool::save_to_address_book(&label, account_hash) {
+        dialog::alert_default(&error.to_string());
+    }
+}
+
+/// The hardware devices which can hold keys.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Device {
+    Ledger,
+    YubiKey,
+}
+
+/// Returns the device chosen by the user from those this build supports, or `None` if they
+/// cancelled.
+fn choose_device() -> Option<Device> {
+    match (
+        casper_multisig_tool::ledger_supported(),
+        casper_multisig_tool::yubikey_supported(),
+    ) {
+        (true, true) => {
+            match dialog::choice_default(
+                tr("Which device?"),
+                tr("Cancel"),
+                tr("Ledger"),
+                tr("YubiKey"),
+            ) {
+                1 => Some(Device::Ledger),
+                2 => Some(Device::YubiKey),
+                _ => None,
+            }
+        }
+        (true, false) => Some(Device::Ledger),
+        (false, true) => Some(Device::YubiKey),
+        (false, false) => None,
+    }
+}
+
+/// Returns a signer for the YubiKey PIV slot and PIN entered by the user, or `None` if they
+/// cancelled.
+fn get_yubikey_signer() -> Option<YubiKeySigner> {
+    let slot = get_yubikey_slot()?;
+    let pin = dialog::password_default(tr("Enter the YubiKey's PIV PIN"), "")?;
+
+    Some(YubiKeySigner::new(slot, Some(&pin)))
+}
+
+/// Returns the PIV slot entered by the user, or `None` if they cancelled.
+fn get_yubikey_slot() -> Option<u8> {
+    let value = dialog::input_default(
+        tr("Enter the YubiKey's PIV slot holding the Ed25519 key, e.g. 9c"),
+        &format!("{:02x}", YubiKeySigner::SIGNATURE_SLOT),
+    )?;
+    match u8::from_str_radix(value.trim(), 16) {
+        Ok(slot) => Some(slot),
+        Err(error) => {
+            dialog::alert_default(&casper_multisig_tool::translate_with(
+                "failed to parse '{}' as a hex-encoded slot: {}",
+                &[&value, &error.to_string()],
+            ));
+            None
+        }
+    }
+}
+
+/// Returns a signer for the AWS KMS key whose ID and region are entered by the user, or `None` if
+/// they cancelled.
+fn get_aws_kms_signer() -> Option<AwsKmsSigner> {
+    let key_id = dialog::input_default(
+        tr("Enter the ID, ARN or alias of the AWS KMS key, which must be an ECC_SECG_P256K1 key"),
+        "",
+    )?;
+    if key_id.trim().is_empty() {
+        return None;
+    }
+    let region = dialog::input_default(
+        tr("Enter the key's AWS region, or leave empty to use the AWS CLI's default"),
+        "",
+    )?;
+    Some(AwsKmsSigner::new(&key_id, Some(&region)))
+}
+
+/// Returns the index of the Ledger key entered by the user, or `None` if they cancelled.
+fn get_ledger_account_index() -> Option<u32> {
+    let value = dialog::input_default(tr("Enter the index of the key on the Ledger device"), "0")?;
+    match value.trim().parse::<u32>() {
+        Ok(account_index) => Some(account_index),
+        Err(error) => {
+            dialog::alert_default(&casper_multisig_tool::translate_with(
+                "failed to parse '{}' as a key index: {}",
+                &[&value, &error.to_string()],
+            ));
+
+            None
+        }
+    }
+}
+
+/// Returns the account hash as a formatted string and a tooltip indicating the origin of the
+/// account hash, or `None` if the user didn't enter a valid account hash or cancelled the
+/// operation.
+fn get_account_hash_from_formatted_account_hash() -> Option<(String, String)> {
+    let hex_account_hash = dialog::input_default(tr("Enter formatted account hash"), "")?;
+
+    match casper_multisig_tool::validate_account_hash(&hex_account_hash) {
+        Ok(_) => {
+            let tooltip = casper_multisig_tool::translate_with(
+                "Derived from account hash {}",
+                &[&hex_account_hash],
+            );
+            Some((hex_account_hash, tooltip))
+        }
+        Err(error) => {
+            dialog::alert_default(error.to_string().as_str());
+            None
+        }
+    }
+}
+
+/// Returns the account hash of the key in `text`, pasted from the clipboard, as a formatted string
+/// and a tooltip indicating the origin of the account hash, or `None` if it isn't a key.
+fn get_account_hash_from_pasted_key(text: &str) -> Option<(String, String)> {
+    match casper_multisig_tool::parse_pasted_key(text) {
+        Ok((account_hash, Some(public_key))) => {
+            let tooltip = casper_multisig_tool::translate_with(
+                "Derived from pasted public key {}",
+                &[&public_key],
+            );
+            Some((account_hash, tooltip))
+        }
+        Ok((account_hash, None)) => {
+            let tooltip = casper_multisig_tool::translate_with(
+                "Derived from pasted account hash {}",
+                &[&account_hash],
+            );
+            Some((account_hash, tooltip))
+        }
+        Err(error) => {
+            dialog::alert_default(error.to_string().as_str());
+            None
+        }
+    }
+}
+
+/// Returns the account hash of the key in a QR code scanned with the webcam as a formatted string
+/// and a tooltip indicating the origin of the account hash, or `None` if the user cancelled or the
+/// code isn't a key.
+fn get_account_hash_from_scanned_qr_code() -> Option<(String, String)> {
+    let scan = match casper_multisig_tool::scan_qr_code() {
+        Ok(scan) => scan,
+        Err(error) => {
+            dialog::alert_default(&error.to_string());
+            return None;
+        }
+    };
+
+    let mut window = Window::default()
+        .with_size(600, 130)
+        .center_screen()
+        .with_label(tr("Scan QR code"));
+    window.make_modal(true);
+    let mut status = Frame::new(
+        PADDING,
+        PADDING,
+        580,
+        60,
+        tr("Hold the QR code of the public key or account hash up to the webcam"),
+    );
+    status.set_align(Align::Left | Align::Inside | Align::Wrap);
+    let mut cancel_button = Button::new(600 - PADDING - 150, 80, 150, BUTTON_HEIGHT, tr("Cancel"));
+    cancel_button.set_color(Color::from_u32(palette().button));
+    window.end();
+    window.show();
+    cancel_button.set_callback(move |button| button.window().unwrap().hide());
+
+    // Polled on this thread, so that the key can be added once it's scanned.  Dropping the scan
+    // when the window is closed stops it.
+    let mut result = None;
+    while window.shown() {
+        let _ = app::wait_for(0.1);
+        if let Some(scanned) = scan.try_result() {
+            result = Some(scanned);
+            window.hide();
+        }
+    }
+    drop(scan);
+
+    match result? {
+        Ok(text) => match casper_multisig_tool::parse_pasted_key(&text) {
+            Ok((account_hash, Some(public_key))) => {
+                let tooltip = casper_multisig_tool::translate_with(
+                    "Derived from scanned public key {}",
+                    &[&public_key],
+                );
+                Some((account_hash, tooltip))
+            }
+            Ok((account_hash, None)) => {
+                let tooltip = casper_multisig_tool::translate_with(
+                    "Derived from scanned account hash {}",
+                    &[&account_hash],
+                );
+                Some((account_hash, tooltip))
+            }
+            Err(error) => {
+                dialog::alert_default(error.to_string().as_str());
+                None
+            }
+        },
+        Err(error) => {
+            dialog::alert_default(&error);
+            None
+        }
+    }
+}
+
+fn get_current_or_default_project_path() -> Option<PathBuf> {
+    let current_project_path = casper_multisig_tool::project_path();
+    if current_project_path != PathBuf::default() {
+        return Some(current_project_path);
+    }
+    dirs::home_dir().or_else(|| env::current_dir().ok())
+}
+
+fn get_current_or_default_contract_name() -> String {
+    let current_contract_name = casper_multisig_tool::contract_name();
+    if !current_contract_name.is_empty() {
+        return current_contract_name;
+    }
+    "multisig_setup_contract".to_string()
+}
+
+/// Returns the main window's menu bar.
+fn new_menu_bar(configuration_tabs: &ConfigurationTabs) -> MenuBar {
+    let mut menu_bar = MenuBar::new(0, 0, WINDOW_WIDTH, MENU_BAR_HEIGHT, None);
+    menu_bar.set_color(Color::from_u32(palette().button));
+
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/New tab"),
+        Shortcut::Command | 't',
+        MenuFlag::Normal,
+        move |_| configuration_tabs_clone.add_tab(),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/Rename tab..."),
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| configuration_tabs_clone.rename_current(),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/Close tab"),
+        Shortcut::Command | 'w',
+        MenuFlag::MenuDivider,
+        move |_| configuration_tabs_clone.close_current(),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/New configuration"),
+        Shortcut::Command | 'n',
+        MenuFlag::Normal,
+        move |_| {
+            if !configuration_tabs_clone
+                .current()
+                .associated_keys()
+                .is_empty()
+                && dialog::choice_default(
+                    tr("Discard the current associated keys and thresholds?  This can be undone."),
+                    tr("Cancel"),
+                    tr("Discard"),
+                    "",
+                ) != 1
+            {
+                return;
+            }
+            configuration_tabs_clone.current().apply_key_configuration(
+                &KeyConfiguration::default(),
+                "",
+                false,
+            );
+        },
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/Open configuration..."),
+        // Ctrl+O imports a key, the commonest way of adding one, so "load" opens a configuration.
+        Shortcut::Command | 'l',
+        MenuFlag::Normal,
+        move |_| {
+            let file = match get_configuration_file(FileDialogType::BrowseFile) {
+                Some(file) => file,
+                None => return,
+            };
+            open_configuration(&configuration_tabs_clone.current(), &file);
+        },
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/Open existing project..."),
+        Shortcut::Command | Shortcut::Shift | 'o',
+        MenuFlag::Normal,
+        move |_| {
+            let dir = match get_dir(tr("Choose a previously generated project's folder")) {
+                Some(dir) => dir,
+                None => return,
+            };
+            match casper_multisig_tool::open_project(&dir) {
+                Ok(configuration) => reopen_recent_item(
+                    &configuration_tabs_clone.current(),
+                    &RecentItem::Project { dir, configuration },
+                ),
+                Err(error) => dialog::alert_default(&error.to_string()),
+            }
+        },
+    );
+    populate_recent_menu(&mut menu_bar, configuration_tabs);
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/Save configuration..."),
+        Shortcut::Command | 's',
+        MenuFlag::Normal,
+        move |_| {
+            let file = match get_configuration_file(FileDialogType::BrowseSaveFile) {
+                Some(file) => file,
+                None => return,
+            };
+            if let Err(error) = casper_multisig_tool::save_key_configuration(
+                &configuration_tabs_clone.current().key_configuration(),
+                &file,
+            ) {
+                return dialog::alert_default(&error.to_string());
+            }
+            if let Err(error) = casper_multisig_tool::add_recent_configuration(&file) {
+                alert_not_added_to_recent_items(&error);
+            }
+        },
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("File/Generate smart contract"),
+        Shortcut::Command | 'g',
+        MenuFlag::MenuDivider,
+        move |_| {
+            click(
+                &mut *configuration_tabs_clone
+                    .current()
+                    .generate_smart_contract_button(),
+            )
+        },
+    );
+    menu_bar.add(
+        &menu_path("File/Quit"),
+        Shortcut::Command | 'q',
+        MenuFlag::Normal,
+        |_| app::quit(),
+    );
+
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Edit/Undo"),
+        Shortcut::Command | 'z',
+        MenuFlag::Normal,
+        move |_| configuration_tabs_clone.current().undo(),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Edit/Redo"),
+        Shortcut::Command | Shortcut::Shift | 'z',
+        MenuFlag::MenuDivider,
+        move |_| configuration_tabs_clone.current().redo(),
+    );
+    // Ctrl+Y is the other common shortcut for redo, so is bound too without a second visible entry.
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Edit/Redo (Ctrl+Y)"),
+        Shortcut::Command | 'y',
+        MenuFlag::Invisible,
+        move |_| configuration_tabs_clone.current().redo(),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Edit/Add keys in bulk..."),
+        Shortcut::Command | Shortcut::Shift | 'v',
+        MenuFlag::MenuDivider,
+        move |_| {
+            let main_output_pack = configuration_tabs_clone.current();
+            let existing_account_hashes = main_output_pack
+                .associated_keys()
+                .into_iter()
+                .map(|(account_hash, _)| account_hash)
+                .collect();
+            if let Some(bulk_keys) = get_bulk_keys(existing_account_hashes) {
+                main_output_pack.add_bulk_keys(&bulk_keys);
+            }
+        },
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Edit/Apply scheme template..."),
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| show_scheme_template_window(configuration_tabs_clone.current()),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Edit/Explore signer combinations..."),
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| show_signer_combinations_window(configuration_tabs_clone.current()),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Edit/Contract options..."),
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| show_contract_options_window(configuration_tabs_clone.current()),
+    );
+    menu_bar.add(
+        &menu_path("Edit/Build options..."),
+        Shortcut::Command | 'b',
+        MenuFlag::Normal,
+        |_| show_build_options_window(),
+    );
+    menu_bar.add(
+        &menu_path("Edit/Preferences..."),
+        Shortcut::Command | ',',
+        MenuFlag::Normal,
+        |_| show_preferences_window(),
+    );
+
+    // Each of these clicks the matching button of the current tab, so does nothing while the button
+    // is inactive, e.g. once the maximum number of keys is reached.
+    type AddKeyItem = (
+        &'static str,
+        Shortcut,
+        fn(&MainOutputPack) -> Box<dyn WidgetExt>,
+    );
+    let add_key_items: [AddKeyItem; 8] = [
+        (
+            "Keys/Import from file...",
+            Shortcut::Command | 'o',
+            |main_output_pack| Box::new(main_output_pack.add_public_key_from_file_button.clone()),
+        ),
+        (
+            "Keys/Enter public key...",
+            Shortcut::Command | 'k',
+            |main_output_pack| Box::new(main_output_pack.add_public_key_from_hex_button.clone()),
+        ),
+        (
+            "Keys/Enter account hash...",
+            Shortcut::Command | Shortcut::Shift | 'k',
+            |main_output_pack| Box::new(main_output_pack.add_account_hash_button.clone()),
+        ),
+        (
+            "Keys/Import from device...",
+            Shortcut::Command | 'd',
+            |main_output_pack| Box::new(main_output_pack.add_device_key_button.clone()),
+        ),
+        (
+            "Keys/Generate vanity key...",
+            Shortcut::None,
+            |main_output_pack| Box::new(main_output_pack.add_vanity_key_button.clone()),
+        ),
+        (
+            "Keys/Add from address book...",
+            Shortcut::Command | Shortcut::Shift | 'a',
+            |main_output_pack| Box::new(main_output_pack.add_address_book_key_button.clone()),
+        ),
+        (
+            "Keys/Scan QR code...",
+            Shortcut::Command | Shortcut::Shift | 'r',
+            |main_output_pack| main_output_pack.add_scanned_key_button(),
+        ),
+        (
+            "Keys/Paste key",
+            Shortcut::Command | 'v',
+            |main_output_pack| main_output_pack.add_pasted_key_button(),
+        ),
+    ];
+    let last_index = add_key_items.len() - 1;
+    for (index, (path, shortcut, button)) in add_key_items.iter().copied().enumerate() {
+        let flag = if index == last_index {
+            MenuFlag::MenuDivider
+        } else {
+            MenuFlag::Normal
+        };
+        let configuration_tabs_clone = configuration_tabs.clone();
+        menu_bar.add(&menu_path(path), shortcut, flag, move |_| {
+            click(&mut *button(&configuration_tabs_clone.current()))
+        });
+    }
+    // Text inputs keep these keys for editing, so they only act on the selected key while another
+    // of its widgets, such as its account hash or a button, has the focus.
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Keys/Remove selected key"),
+        Shortcut::None | Key::Delete,
+        MenuFlag::Normal,
+        move |_| configuration_tabs_clone.current().remove_selected_key(),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Keys/Move selected key up"),
+        Shortcut::Alt | Key::Up,
+        MenuFlag::Normal,
+        move |_| configuration_tabs_clone.current().move_selected_key(false),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Keys/Move selected key down"),
+        Shortcut::Alt | Key::Down,
+        MenuFlag::Normal,
+        move |_| configuration_tabs_clone.current().move_selected_key(true),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Keys/Make selected key main"),
+        Shortcut::Command | 'm',
+        MenuFlag::Normal,
+        move |_| configuration_tabs_clone.current().make_selected_key_main(),
+    );
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.add(
+        &menu_path("Keys/Show QR code of selected key..."),
+        Shortcut::Command | 'r',
+        MenuFlag::Normal,
+        move |_| {
+            configuration_tabs_clone
+                .current()
+                .show_selected_key_qr_code()
+        },
+    );
+
+    populate_network_menu(&mut menu_bar);
+    // Rebuilt whenever the menu bar is clicked, as networks can also be added or selected under
+    // "Build options", and recent items are added by other tabs.
+    let configuration_tabs_clone = configuration_tabs.clone();
+    menu_bar.handle(move |menu_bar, event| {
+        if event == Event::Push {
+            populate_recent_menu(menu_bar, &configuration_tabs_clone);
+            populate_network_menu(menu_bar);
+        }
+        false
+    });
+
+    menu_bar.add(
+        &menu_path("Help/Guide..."),
+        // F1, which fltk has no constant for.
+        Shortcut::None | Key::from_i32(0xffbe),
+        MenuFlag::Normal,
+        |_| show_help_window(None),
+    );
+    menu_bar.add(
+        &menu_path("Help/Documentation"),
+        Shortcut::None,
+        MenuFlag::Normal,
+        |_| {
+            if let Err(error) = casper_multisig_tool::open_in_explorer(DOCUMENTATION_URL) {
+                dialog::alert_default(&error.to_string());
+            }
+        },
+    );
+    menu_bar.add(
+        &menu_path("Help/About"),
+        Shortcut::None,
+        MenuFlag::Normal,
+        |_| {
+            dialog::message_default(&casper_multisig_tool::translate_with(
+                "{} {}\n\nGenerates, builds and signs the session code which sets up a multisig \
+                account on a Casper network.",
+                &[TOOL_NAME, env!("CARGO_PKG_VERSION")],
+            ))
+        },
+    );
+
+    menu_bar
+}
+
+/// Returns `text` translated into the preferred language, or `text` itself if it has no
+/// translation.
+fn tr(text: &str) -> &str {
+    casper_multisig_tool::translate(text)
+}
+
+/// Runs the callback of `widget` as a click would, unless it's inactive or hidden, e.g. for a
+/// keyboard shortcut of a button.
+fn click(widget: &mut dyn WidgetExt) {
+    if widget.takes_events() {
+        widget.do_callback();
+    }
+}
+
+/// Returns the menu item `path` with each of its parts translated into the preferred language and
+/// escaped, e.g. "Archivo/Abrir configuración..." for "File/Open configuration...".
+///
+/// The menu bar's menu is opened by Alt and its first letter, e.g. Alt+F for "File", so the
+/// menus can be reached from the keyboard.
+fn menu_path(path: &str) -> String {
+    path.split('/')
+        .enumerate()
+        .map(|(index, part)| {
+            let label = escape_menu_label(tr(part));
+            if index == 0 {
+                format!("&{}", label)
+            } else {
+                label
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns `label` escaped for use as a menu item's label.  Unescaped, `/` would start a submenu
+/// and `&` or `_` would be taken as shortcuts.
+fn escape_menu_label(label: &str) -> String {
+    label
+        .replace('/', "\\/")
+        .replace('&', "&&")
+        .replace('_', "\\_")
+}
+
+/// Fills the "File/Recent" submenu with the configuration files and generated projects used
+/// recently, most recent first.
+fn populate_recent_menu(menu_bar: &mut MenuBar, configuration_tabs: &ConfigurationTabs) {
+    let index = menu_bar.find_index(&menu_path("File/Recent"));
+    if index >= 0 {
+        let _ = menu_bar.clear_submenu(index);
+    }
+
+    // The menu is rebuilt on every click, so an unreadable list is shown in it rather than in a
+    // dialog.
+    let items = match casper_multisig_tool::recent_items() {
+        Ok(items) => items,
+        Err(error) => {
+            menu_bar.add(
+                &format!(
+                    "{}/{}",
+                    menu_path("File/Recent"),
+                    escape_menu_label(&casper_multisig_tool::translate_with(
+                        "Couldn't read the recent items: {}",
+                        &[&error.to_string()],
+                    ))
+                ),
+                Shortcut::None,
+                MenuFlag::Inactive,
+                |_| (),
+            );
+            vec![]
+        }
+    };
+    if items.is_empty() {
+        menu_bar.add(
+            &menu_path("File/Recent/No recent items"),
+            Shortcut::None,
+            MenuFlag::Inactive,
+            |_| (),
+        );
+        return;
+    }
+    let last_index = items.len() - 1;
+    for (index, item) in items.into_iter().enumerate() {
+        let kind = match item {
+            RecentItem::Configuration { .. } => "configuration",
+            RecentItem::Project { .. } => "project",
+        };
+        let label = format!(
+            "{}/{} ({})",
+            menu_path("File/Recent"),
+            escape_menu_label(&item.path().display().to_string()),
+            tr(kind)
+        );
+        let flag = if index == last_index {
+            MenuFlag::MenuDivider
+        } else {
+            MenuFlag::Normal
+        };
+        let configuration_tabs_clone = configuration_tabs.clone();
+        menu_bar.add(&label, Shortcut::None, flag, move |_| {
+            reopen_recent_item(&configuration_tabs_clone.current(), &item)
+        });
+    }
+    menu_bar.add(
+        &menu_path("File/Recent/Clear recent items"),
+        Shortcut::None,
+        MenuFlag::Normal,
+        |_| {
+            if let Err(error) = casper_multisig_tool::clear_recent_items() {
+                dialog::alert_default(&error.to_string());
+            }
+        },
+    );
+}
+
+/// Opens the configuration `file` in the tab of `main_output_pack`, recording it as recently used.
+fn open_configuration(main_output_pack: &MainOutputPack, file: &Path) {
+    match casper_multisig_tool::open_key_configuration(file) {
+        Ok(configuration) => main_output_pack.apply_key_configuration(
+            &configuration,
+            &casper_multisig_tool::translate_with(
+                "Opened from configuration {}",
+                &[&file.display().to_string()],
+            ),
+            true,
+        ),
+        Err(error) => return dialog::alert_default(&error.to_string()),
+    }
+    if let Err(error) = casper_multisig_tool::add_recent_configuration(file) {
+        alert_not_added_to_recent_items(&error);
+    }
+}
+
+/// Tells the user that the configuration file or project just saved or opened couldn't be added
+/// to the recent items, though it was still saved or opened.
+fn alert_not_added_to_recent_items(error: &casper_multisig_tool::Error) {
+    dialog::alert_default(&casper_multisig_tool::translate_with(
+        "Couldn't add this to the recent items: {}",
+        &[&error.to_string()],
+    ));
+}
+
+/// Reopens a recent item in the tab of `main_output_pack`: a configuration file, or the
+/// configuration a project was generated from along with its project path and contract name, so
+/// it can be tweaked and regenerated.
+fn reopen_recent_item(main_output_pack: &MainOutputPack, item: &RecentItem) {
+    let (dir, configuration) = match item {
+        RecentItem::Configuration { file } => return open_configuration(main_output_pack, file),
+        RecentItem::Project { dir, configuration } => (dir, configuration),
+    };
+    if let Some(root_dir) = dir.parent() {
+        casper_multisig_tool::set_project_path(&root_dir.display().to_string());
+    }
+    if let Some(contract_name) = dir.file_name() {
+        if let Err(error) =
+            casper_multisig_tool::set_contract_name(&contract_name.to_string_lossy())
+        {
+            dialog::alert_default(&error.to_string());
+        }
+    }
+    main_output_pack.apply_key_configuration(
+        configuration,
+        &casper_multisig_tool::translate_with(
+            "Reopened from project {}",
+            &[&dir.display().to_string()],
+        ),
+        true,
+    );
+    if let Err(error) = casper_multisig_tool::add_recent_project(configuration) {
+        alert_not_added_to_recent_items(&error);
+    }
+}
+
+/// Fills the "Network" menu with the network actions, followed by the network profiles with the
+/// selected one checked.
+fn populate_network_menu(menu_bar: &mut MenuBar) {
+    let index = menu_bar.find_index(&menu_path("Network"));
+    if index >= 0 {
+        let _ = menu_bar.clear_submenu(index);
+    }
+
+    menu_bar.add(
+        &menu_path("Network/Query account state..."),
+        Shortcut::None,
+        MenuFlag::Normal,
+        |_| show_account_state(),
+    );
+    menu_bar.add(
+        &menu_path("Network/Request testnet funds..."),
+        Shortcut::None,
+        MenuFlag::Normal,
+        |_| request_testnet_funds(),
+    );
+    menu_bar.add(
+        &menu_path("Network/Add network..."),
+        Shortcut::None,
+        MenuFlag::MenuDivider,
+        |_| {
+            if let Some(name) = add_network_profile() {
+                if let Err(error) = casper_multisig_tool::select_network(Some(&name)) {
+                    dialog::alert_default(&error.to_string());
+                }
+            }
+        },
+    );
+
+    let select = |name: Option<String>| {
+        move |_: &mut MenuBar| {
+            if let Err(error) = casper_multisig_tool::select_network(name.as_deref()) {
+                dialog::alert_default(&error.to_string());
+            }
+        }
+    };
+    menu_bar.add(
+        &menu_path("Network/None"),
+        Shortcut::None,
+        MenuFlag::Radio,
+        select(None),
+    );
+    let selected = casper_multisig_tool::selected_network().map(|network| network.name);
+    for network in casper_multisig_tool::network_profiles() {
+        let label = format!(
+            "{}/{}",
+            menu_path("Network"),
+            escape_menu_label(&network.name)
+        );
+        let is_selected = selected.as_ref() == Some(&network.name);
+        menu_bar.add(
+            &label,
+            Shortcut::None,
+            MenuFlag::Radio,
+            select(Some(network.name)),
+        );
+        if is_selected {
+            if let Some(mut item) = menu_bar.find_item(&label) {
+                item.set();
+            }
+        }
+    }
+    if selected.is_none() {
+        if let Some(mut item) = menu_bar.find_item(&menu_path("Network/None")) {
+            item.set();
+        }
+    }
+}
+
+/// Returns the path of a key configuration file chosen by the user, to open or save depending on
+/// `dialog_type`, or `None` if they cancelled.
+fn get_configuration_file(dialog_type: FileDialogType) -> Option<PathBuf> {
+    let mut file_dialog = FileDialog::new(dialog_type);
     if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
         let _ = file_dialog.set_directory(start_dir);
     }
-    file_dialog.set_option(FileDialogOptions::UseFilterExt);
-    file_dialog.set_title("Choose Public Key File");
-    file_dialog.set_filter(
-        "PEM-encoded Public Key Files \t*public_key*.pem\nHex-encoded Public Key Files \
-        \t*public_key*_hex*",
-    );
+    file_dialog.set_option(FileDialogOptions::SaveAsConfirm);
+    file_dialog.set_title(tr("Choose Configuration File"));
+    file_dialog.set_filter("Configuration Files \t*.json");
     file_dialog.show();
 
     if file_dialog.filename() == PathBuf::default() {
         return None;
     }
 
-    let file_path = file_dialog.filename().to_string_lossy().to_string();
+    Some(file_dialog.filename())
+}
 
-    match casper_multisig_tool::get_account_hash_from_file(&file_path) {
-        Ok(account_hash) => {
-            let tooltip = format!("Derived from contents of {}", file_path);
-            Some((account_hash, tooltip))
-        }
-        Err(error) => {
-            dialog::alert_default(error.to_string().as_str());
-            None
-        }
+/// Returns the file the generated code should be saved to, or `None` if none was chosen.
+fn get_rust_source_file() -> Option<PathBuf> {
+    let mut file_dialog = FileDialog::new(FileDialogType::BrowseSaveFile);
+    if let Some(start_dir) = dirs::home_dir().or_else(|| env::current_dir().ok()) {
+        let _ = file_dialog.set_directory(start_dir);
+    }
+    file_dialog.set_option(FileDialogOptions::SaveAsConfirm);
+    file_dialog.set_title(tr("Save Generated Code"));
+    file_dialog.set_filter("Rust Files \t*.rs");
+    file_dialog.set_preset_file("main.rs");
+    file_dialog.show();
+
+    if file_dialog.filename() == PathBuf::default() {
+        return None;
     }
+
+    Some(file_dialog.filename())
 }
 
-/// Returns the account hash as a formatted string and a tooltip indicating the origin of the
-/// account hash, or `None` if the user didn't enter a valid public key or cancelled the operation.
-fn get_account_hash_from_hex_public_key() -> Option<(String, String)> {
-    // let mut window = Window::default().with_size(1000, 60).center_screen();
-    // window.make_modal(true);
-    //
-    // let mut input = Input::new(10, 10, 800, 40, "Enter formatted public key");
-    // input.set_trigger(CallbackTrigger::EnterKey);
-    //
-    // window.end();
-    // window.show();
-    //
-    // let (sender, receiver) = app::channel::<()>();
-    //
-    // input.emit(sender, ());
-    //
-    // let _ = receiver.recv();
-    // let hex_public_key = input.value();
+fn new_button(label: &str) -> Button {
+    let mut button = Button::default()
+        .with_size(BUTTON_WIDTH, BUTTON_HEIGHT)
+        .with_label(tr(label));
+    button.set_color(Color::from_u32(palette().button));
+    button
+}
 
-    let hex_public_key = dialog::input_default("Enter formatted public key", "")?;
+/// A configuration tab in the main window, backed by its own library session.
+#[derive(Clone)]
+struct ConfigurationTab {
+    group: Group,
+    main_output_pack: MainOutputPack,
+    session: SessionId,
+}
 
-    match casper_multisig_tool::get_account_hash_from_hex_encoded_public_key(&hex_public_key) {
-        Ok(account_hash) => {
-            let tooltip = format!("Derived from public key {}", hex_public_key);
-            Some((account_hash, tooltip))
-        }
-        Err(error) => {
-            dialog::alert_default(error.to_string().as_str());
-            None
-        }
-    }
+/// A wrapper for the `Tabs` widget holding a tab per configuration being worked on, e.g. one each
+/// for a treasury and an operations account.
+#[derive(Clone)]
+struct ConfigurationTabs {
+    tabs: Tabs,
+    entries: Arc<Mutex<Vec<ConfigurationTab>>>,
+    /// The number in the default label of the last tab added.
+    last_number: Arc<Mutex<usize>>,
 }
 
-/// Returns the account hash as a formatted string and a tooltip indicating the origin of the
-/// account hash, or `None` if the user didn't enter a valid account hash or cancelled the
-/// operation.
-fn get_account_hash_from_formatted_account_hash() -> Option<(String, String)> {
-    let hex_account_hash = dialog::input_default("Enter formatted account hash", "")?;
+impl ConfigurationTabs {
+    fn new() -> Self {
+        let mut tabs = Tabs::new(0, MENU_BAR_HEIGHT, WINDOW_WIDTH, 10, None);
+        tabs.make_resizable(false);
+        tabs.end();
+        let configuration_tabs = ConfigurationTabs {
+            tabs: tabs.clone(),
+            entries: Arc::new(Mutex::new(vec![])),
+            last_number: Arc::new(Mutex::new(0)),
+        };
 
-    match casper_multisig_tool::validate_account_hash(&hex_account_hash) {
-        Ok(_) => {
-            let tooltip = format!("Derived from account hash {}", hex_account_hash);
-            Some((hex_account_hash, tooltip))
+        let configuration_tabs_clone = configuration_tabs.clone();
+        tabs.set_callback(move |_| configuration_tabs_clone.select_current());
+        configuration_tabs
+    }
+
+    /// Returns the tab of the current library session.
+    fn current_tab(&self) -> ConfigurationTab {
+        let session = casper_multisig_tool::current_session();
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|tab| tab.session == session)
+            .cloned()
+            .unwrap()
+    }
+
+    /// Returns the wrapper for the current tab's associated keys.
+    fn current(&self) -> MainOutputPack {
+        self.current_tab().main_output_pack
+    }
+
+    /// Adds a tab for a new, empty configuration and selects it.
+    fn add_tab(&self) {
+        // The first tab takes the session which exists from the start.
+        let session = if self.entries.lock().unwrap().is_empty() {
+            casper_multisig_tool::current_session()
+        } else {
+            match casper_multisig_tool::new_session() {
+                Ok(session) => session,
+                Err(error) => return dialog::alert_default(&error.to_string()),
+            }
+        };
+
+        let (mut group, main_output_pack) = new_configuration_tab();
+        let number = {
+            let mut last_number = self.last_number.lock().unwrap();
+            *last_number += 1;
+            *last_number
+        };
+        group.set_label(&casper_multisig_tool::translate_with(
+            "Configuration {}",
+            &[&number.to_string()],
+        ));
+        let mut tabs = self.tabs.clone();
+        tabs.add(&group);
+        self.entries.lock().unwrap().push(ConfigurationTab {
+            group: group.clone(),
+            main_output_pack,
+            session,
+        });
+
+        let _ = tabs.set_value(&group);
+        self.select_current();
+    }
+
+    /// Switches to the library session of the tab shown, and fits the window to the tab.
+    fn select_current(&self) {
+        let mut tabs = self.tabs.clone();
+        let index = match tabs.value() {
+            Some(group) => tabs.find(&group),
+            None => return,
+        };
+        // The entries are in the same order as the tabs.
+        let tab = match self.entries.lock().unwrap().get(index as usize).cloned() {
+            Some(tab) => tab,
+            None => return,
+        };
+        if let Err(error) = casper_multisig_tool::select_session(tab.session) {
+            return dialog::alert_default(&error.to_string());
         }
-        Err(error) => {
-            dialog::alert_default(error.to_string().as_str());
-            None
+        tab.main_output_pack.redraw_window();
+        self.tabs.clone().redraw();
+    }
+
+    /// Autosaves every tab's configuration, so it can be restored if the tool is closed by accident.
+    ///
+    /// Only the first failure of the session is shown, as this happens every `AUTOSAVE_INTERVAL`.
+    fn autosave(&self) {
+        let tabs = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|tab| {
+                (
+                    tab.session,
+                    tab.group.label(),
+                    tab.main_output_pack.key_configuration(),
+                )
+            })
+            .collect();
+        if let Err(error) = casper_multisig_tool::autosave(tabs) {
+            if !AUTOSAVE_FAILURE_SHOWN.swap(true, Ordering::Relaxed) {
+                dialog::alert_default(&casper_multisig_tool::translate_with(
+                    "Failed to autosave the configurations, so they can't be restored if the \
+                    tool is closed by accident: {}\n\nFurther autosave failures won't be shown.",
+                    &[&error.to_string()],
+                ));
+            }
         }
     }
-}
 
-fn get_current_or_default_project_path() -> Option<PathBuf> {
-    let current_project_path = casper_multisig_tool::project_path();
-    if current_project_path != PathBuf::default() {
-        return Some(current_project_path);
+    /// Offers to restore the configurations autosaved when the tool was last closed, each in its
+    /// own tab, starting with the current empty one.  The autosave is discarded if the user
+    /// declines.
+    fn offer_autosave_restore(&self) {
+        let autosaved_tabs = match casper_multisig_tool::autosaved_tabs() {
+            Ok(autosaved_tabs) => autosaved_tabs,
+            Err(error) => return dialog::alert_default(&error.to_string()),
+        };
+        if autosaved_tabs.is_empty() {
+            return;
+        }
+        let summary = autosaved_tabs
+            .iter()
+            .map(|autosaved_tab| {
+                casper_multisig_tool::translate_with(
+                    "  {} ({} keys)",
+                    &[
+                        &autosaved_tab.name,
+                        &autosaved_tab
+                            .configuration
+                            .associated_keys
+                            .len()
+                            .to_string(),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if dialog::choice_default(
+            &casper_multisig_tool::translate_with(
+                "Restore the configurations from when the tool was last closed?\n\n{}",
+                &[&summary],
+            ),
+            tr("Discard"),
+            tr("Restore"),
+            "",
+        ) != 1
+        {
+            if let Err(error) = casper_multisig_tool::discard_autosave() {
+                dialog::alert_default(&error.to_string());
+            }
+            return;
+        }
+
+        for (index, autosaved_tab) in autosaved_tabs.iter().enumerate() {
+            if index > 0 {
+                self.add_tab();
+            }
+            let tab = self.current_tab();
+            tab.group.clone().set_label(&autosaved_tab.name);
+            if autosaved_tab.project_path != PathBuf::default() {
+                casper_multisig_tool::set_project_path(
+                    &autosaved_tab.project_path.display().to_string(),
+                );
+            }
+            if !autosaved_tab.contract_name.is_empty() {
+                let _ = casper_multisig_tool::set_contract_name(&autosaved_tab.contract_name);
+            }
+            tab.main_output_pack.apply_key_configuration(
+                &autosaved_tab.configuration,
+                "Restored from the autosave",
+                true,
+            );
+        }
+        self.tabs.clone().redraw();
     }
-    dirs::home_dir().or_else(|| env::current_dir().ok())
-}
 
-fn get_current_or_default_contract_name() -> String {
-    let current_contract_name = casper_multisig_tool::contract_name();
-    if !current_contract_name.is_empty() {
-        return current_contract_name;
+    /// Asks the user for a new label for the current tab.
+    fn rename_current(&self) {
+        let mut group = self.current_tab().group;
+        let label = match dialog::input_default(tr("Enter the tab's name"), &group.label()) {
+            Some(label) if !label.trim().is_empty() => label.trim().to_string(),
+            _ => return,
+        };
+        group.set_label(&label);
+        self.tabs.clone().redraw();
     }
-    "multisig_setup_contract".to_string()
-}
 
-fn new_button(label: &str) -> Button {
-    let mut button = Button::default()
-        .with_size(BUTTON_WIDTH, BUTTON_HEIGHT)
-        .with_label(label);
-    button.set_color(Color::from_u32(BUTTON_COLOR));
-    button
-}
+    /// Closes the current tab, discarding its configuration, after selecting a neighbouring one.
+    /// The last tab can't be closed.
+    fn close_current(&self) {
+        let tab = self.current_tab();
+        let neighbour = {
+            let entries = self.entries.lock().unwrap();
+            let index = entries
+                .iter()
+                .position(|entry| entry.session == tab.session)
+                .unwrap();
+            match entries
+                .get(index + 1)
+                .or_else(|| entries.get(index.wrapping_sub(1)))
+            {
+                Some(neighbour) => neighbour.clone(),
+                None => return dialog::alert_default(tr("The last tab can't be closed")),
+            }
+        };
+        if !tab.main_output_pack.associated_keys().is_empty()
+            && dialog::choice_default(
+                &casper_multisig_tool::translate_with(
+                    "Close \"{}\" and discard its configuration?",
+                    &[&tab.group.label()],
+                ),
+                tr("Cancel"),
+                tr("Close"),
+                "",
+            ) != 1
+        {
+            return;
+        }
 
-fn main() {
-    set_panic_handler();
+        let mut tabs = self.tabs.clone();
+        let _ = tabs.set_value(&neighbour.group);
+        self.select_current();
+        if let Err(error) = casper_multisig_tool::close_session(tab.session) {
+            return dialog::alert_default(&error.to_string());
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|entry| entry.session != tab.session);
+        tabs.remove(&tab.group);
+        app::delete_widget(tab.group);
+        tabs.redraw();
+    }
+}
 
-    let app = App::default().with_scheme(Scheme::Gtk);
+/// Returns a new configuration tab's `Group` widget, holding its own associated keys, thresholds
+/// and Rust output, and the wrapper for its associated keys.
+fn new_configuration_tab() -> (Group, MainOutputPack) {
+    let mut group = Group::new(0, HEADER_HEIGHT, WINDOW_WIDTH, 10, None);
+    // The tab's widgets are laid out by `MainOutputPack::layout` rather than scaled.
+    group.make_resizable(false);
 
-    let mut top_frame = Frame::new(PADDING, PADDING, 980, 80, "Add public key")
-        .with_align(Align::TopLeft | Align::Inside);
+    let mut top_frame = Frame::new(
+        PADDING,
+        PADDING + HEADER_HEIGHT,
+        WINDOW_WIDTH - (2 * PADDING),
+        80,
+        tr("Add public key"),
+    )
+    .with_align(Align::TopLeft | Align::Inside);
     top_frame.set_frame(FrameType::PlasticDownFrame);
 
     let mut add_key_button_pack = Pack::new(
         2 * PADDING,
-        40,
+        40 + HEADER_HEIGHT,
         WINDOW_WIDTH - (2 * PADDING),
         BUTTON_HEIGHT,
         "",
     );
-    add_key_button_pack.set_spacing(30);
+    add_key_button_pack.set_spacing(10);
     add_key_button_pack.set_type(PackType::Horizontal);
     top_frame.set_size(
         top_frame.width(),
-        add_key_button_pack.height() + add_key_button_pack.y(),
+        add_key_button_pack.height() + add_key_button_pack.y() - HEADER_HEIGHT,
     );
 
-    let mut add_public_key_from_file_button = new_button("Import from file");
-    let mut add_public_key_from_hex_button = new_button("Enter hex-encoded public key");
-    let mut add_account_hash_button = new_button("Enter hex-encoded account hash");
+    let mut add_public_key_from_file_button =
+        new_button("Import from file").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    let mut add_public_key_from_hex_button =
+        new_button("Enter hex-encoded public key").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    let mut add_account_hash_button =
+        new_button("Enter hex-encoded account hash").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    let mut add_device_key_button =
+        new_button("Import from device").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    if casper_multisig_tool::ledger_supported() || casper_multisig_tool::yubikey_supported() {
+        add_device_key_button.set_tooltip(tr(
+            "Use a key held by the Casper app on a Ledger device, or in a YubiKey's PIV applet",
+        ));
+    } else {
+        add_device_key_button.set_tooltip(tr(
+            "Rebuild the tool with `--features ledger` or `--features yubikey` to enable",
+        ));
+        add_device_key_button.deactivate();
+    }
+    let mut add_vanity_key_button =
+        new_button("Generate vanity key").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    add_vanity_key_button.set_tooltip(tr(
+        "Generate a new key pair whose account hash starts with a chosen hex prefix, e.g. for a \
+        recognisable treasury account",
+    ));
+    let mut add_address_book_key_button =
+        new_button("Add from address book").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    add_address_book_key_button.set_tooltip(tr(
+        "Add keys of known signers saved with the \"Save\" button of a key",
+    ));
+    let mut add_scanned_key_button =
+        new_button("Scan QR code").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    add_scanned_key_button.set_tooltip(tr(
+        "Scan a QR code of a public key or account hash with the webcam, e.g. shown on a signer's \
+        phone or wallet",
+    ));
+    let mut add_pasted_key_button =
+        new_button("Paste key").with_size(ADD_KEY_BUTTON_WIDTH, BUTTON_HEIGHT);
+    add_pasted_key_button.set_tooltip(tr(
+        "Add the key on the clipboard, whether a hex-encoded public key or an account hash",
+    ));
 
     add_key_button_pack.end();
+    // The buttons narrow to fit small windows, so their labels wrap rather than being cut off.
+    for index in 0..add_key_button_pack.children() {
+        if let Some(mut button) = add_key_button_pack.child(index) {
+            button.set_align(Align::Center | Align::Inside | Align::Wrap);
+        }
+    }
+
+    // FLTK only delivers the clipboard's contents to a text widget, so a hidden one receives them.
+    let mut paste_target = Input::new(0, 0, 0, 0, None);
+    paste_target.hide();
 
     let mut middle_frame = Frame::new(
         PADDING,
-        120,
+        120 + HEADER_HEIGHT,
         WINDOW_WIDTH - (2 * PADDING),
         40,
-        "Current associated keys",
+        tr("Current associated keys"),
     )
     .with_align(Align::TopLeft | Align::Inside);
     middle_frame.set_frame(FrameType::PlasticDownFrame);
 
+    let mut rust_output = TextDisplay::new(PADDING, 0, WINDOW_WIDTH - (2 * PADDING), 800, None);
+    let buffer = TextBuffer::default();
+    rust_output.set_buffer(Some(buffer.clone()));
+    rust_output.set_text_font(Font::Courier);
+    rust_output.set_text_size(code_text_size());
+    rust_output.set_color(Color::from_u32(palette().code_background));
+    rust_output.set_tooltip(tr(
+        "The session code generated from the configuration, updated with every change",
+    ));
+
+    let mut copy_rust_button = Button::new(0, 0, 0, 0, tr("Copy Rust to clipboard"));
+    copy_rust_button.set_color(Color::from_u32(palette().button));
+    copy_rust_button.set_tooltip(tr(
+        "Copy the generated code to the clipboard, e.g. to share it for review before generating \
+        and compiling the project",
+    ));
+    copy_rust_button.deactivate();
+    let buffer_clone = buffer.clone();
+    copy_rust_button.set_callback(move |_| copy_to_clipboard(&buffer_clone.text()));
+
+    let mut save_rust_button = Button::new(0, 0, 0, 0, tr("Save main.rs as..."));
+    save_rust_button.set_color(Color::from_u32(palette().button));
+    save_rust_button.set_tooltip(tr(
+        "Save the generated code to a file, without generating or compiling the project",
+    ));
+    save_rust_button.deactivate();
+    let buffer_clone = buffer.clone();
+    save_rust_button.set_callback(move |_| {
+        let file = match get_rust_source_file() {
+            Some(file) => file,
+            None => return,
+        };
+        if let Err(error) = casper_multisig_tool::save_source(&buffer_clone.text(), &file) {
+            dialog::alert_default(&error.to_string());
+        }
+    });
+
+    let mut issues = Browser::new(0, 0, 0, 0, None);
+    issues.set_text_size(code_text_size());
+    issues.set_color(Color::from_u32(palette().input_background));
+    issues.set_tooltip(tr(
+        "Problems with the configuration, updated with every change.  Errors must be fixed before \
+        the contract can be generated, while warnings flag what's probably unintended",
+    ));
+
+    let mut resulting_account = Browser::new(0, 0, 0, 0, None);
+    resulting_account.set_column_widths(&[220, 200]);
+    resulting_account.set_text_size(code_text_size());
+    resulting_account.set_color(Color::from_u32(palette().input_background));
+    resulting_account.set_tooltip(tr(
+        "The associated keys, weights and action thresholds the account is left with once the \
+        setup deploy has executed, as they'll be held on chain",
+    ));
+
+    let mut associated_keys_scroll =
+        Scroll::new(15, 175 + HEADER_HEIGHT, WINDOW_WIDTH - 30, 0, None);
+    associated_keys_scroll.set_type(ScrollType::Vertical);
+
     let mut main_key_frame = Frame::new(
         15,
-        175,
-        WINDOW_WIDTH - 35,
+        175 + HEADER_HEIGHT,
+        WINDOW_WIDTH - 30 - app::scrollbar_size(),
         OUTPUT_ROW_HEIGHT + PADDING,
-        "Main account  ",
+        tr("Main account  "),
     );
     main_key_frame.set_align(Align::Right | Align::Inside);
-    main_key_frame.set_color(Color::from_u32(0xaed6f1));
+    main_key_frame.set_color(Color::from_u32(palette().main_key_background));
     main_key_frame.set_frame(FrameType::FlatBox);
     main_key_frame.hide();
 
-    let mut rust_output = TextDisplay::new(PADDING, 0, WINDOW_WIDTH - (2 * PADDING), 800, None);
-    let buffer = TextBuffer::default();
-    rust_output.set_buffer(Some(buffer.clone()));
-    rust_output.set_text_font(Font::Courier);
-    rust_output.set_text_size(14);
-    rust_output.set_color(Color::from_u32(0xe0e8ee));
-
     let main_output_pack = MainOutputPack::new(
         add_public_key_from_file_button.clone(),
         add_public_key_from_hex_button.clone(),
         add_account_hash_button.clone(),
+        add_device_key_button.clone(),
+        add_vanity_key_button.clone(),
+        add_address_book_key_button.clone(),
         buffer,
     );
 
-    let mut bottom_frame =
-        Frame::new(10, 10, 650, 90, "Action thresholds").with_align(Align::TopLeft | Align::Inside);
+    associated_keys_scroll.end();
+    associated_keys_scroll.insert(
+        &main_key_frame,
+        AssociatedKeysScrollIndices::MainKeyFrame as i32,
+    );
+    associated_keys_scroll.insert(
+        &main_output_pack.pack,
+        AssociatedKeysScrollIndices::MainOutputPack as i32,
+    );
+
+    let mut bottom_frame = Frame::new(10, 10, 650, 90, tr("Action thresholds"))
+        .with_align(Align::TopLeft | Align::Inside);
     bottom_frame.set_frame(FrameType::PlasticDownFrame);
 
     let action_thresholds_pack = ActionThresholdsPack::new(main_output_pack.clone());
@@ -841,18 +8316,35 @@ fn main() {
         PADDING,
         BUTTON_WIDTH,
         BUTTON_HEIGHT,
-        "Generate smart contract",
+        tr("Generate smart contract"),
     );
-    generate_smart_contract_button.set_color(Color::from_u32(0xc3fdb8));
+    generate_smart_contract_button.set_color(Color::from_u32(palette().passed));
     generate_smart_contract_button.deactivate();
 
+    let mut prebuilt_wasm_check_button = CheckButton::new(
+        WINDOW_WIDTH - (2 * PADDING) - (2 * BUTTON_WIDTH),
+        PADDING,
+        BUTTON_WIDTH,
+        BUTTON_HEIGHT,
+        tr("Use prebuilt session Wasm"),
+    );
+    prebuilt_wasm_check_button.set_tooltip(tr(
+        "Use a prebuilt session Wasm of the generic contract, which reads the keys, weights and \
+        thresholds from its runtime args, so no Rust toolchain is needed to generate the \
+        project.\n\nThe tool doesn't ship this Wasm: choose one built by someone with the \
+        toolchain, and enter the Blake2b hash published with the audited build to check it's \
+        that build.  A Wasm whose hash isn't checked is marked unverified.",
+    ));
+
     let main_output_pack_clone = main_output_pack.clone();
     add_public_key_from_file_button.set_callback(move |_| {
-        let (account_hash, tooltip) = match get_account_hash_from_public_key_file() {
+        let account_hashes = match get_account_hashes_from_key_file() {
             Some(value) => value,
             None => return,
         };
-        main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+        for (account_hash, tooltip) in account_hashes {
+            main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+        }
     });
 
     let main_output_pack_clone = main_output_pack.clone();
@@ -873,39 +8365,220 @@ fn main() {
         main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
     });
 
+    let main_output_pack_clone = main_output_pack.clone();
+    add_device_key_button.set_callback(move |_| {
+        let account_hash_and_tooltip = match choose_device() {
+            Some(Device::Ledger) => get_account_hash_from_ledger(),
+            Some(Device::YubiKey) => get_account_hash_from_yubikey(),
+            None => return,
+        };
+        let (account_hash, tooltip) = match account_hash_and_tooltip {
+            Some(value) => value,
+            None => return,
+        };
+        main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+    });
+
+    let main_output_pack_clone = main_output_pack.clone();
+    add_address_book_key_button.set_callback(move |_| {
+        let account_hashes = match get_account_hashes_from_address_book() {
+            Some(value) => value,
+            None => return,
+        };
+        for (account_hash, tooltip) in account_hashes {
+            main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+        }
+    });
+
+    // Public key files dropped anywhere on the tab are added as associated keys.
+    let main_output_pack_clone = main_output_pack.clone();
+    group.handle(move |_, event| match event {
+        Event::DndEnter | Event::DndDrag | Event::DndLeave | Event::DndRelease => true,
+        Event::Paste => {
+            for file_path in dropped_file_paths(&app::event_text()) {
+                let account_hashes = get_account_hashes_from_file(&file_path).unwrap_or_default();
+                for (account_hash, tooltip) in account_hashes {
+                    main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+                }
+            }
+            true
+        }
+        _ => false,
+    });
+
+    let main_output_pack_clone = main_output_pack.clone();
+    paste_target.handle(move |_, event| {
+        if event != Event::Paste {
+            return false;
+        }
+        if let Some((account_hash, tooltip)) = get_account_hash_from_pasted_key(&app::event_text())
+        {
+            main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+        }
+        true
+    });
+    add_pasted_key_button.set_callback(move |_| app::paste(&paste_target));
+
+    let main_output_pack_clone = main_output_pack.clone();
+    add_scanned_key_button.set_callback(move |_| {
+        if let Some((account_hash, tooltip)) = get_account_hash_from_scanned_qr_code() {
+            main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+        }
+    });
+
+    let main_output_pack_clone = main_output_pack.clone();
+    add_vanity_key_button.set_callback(move |_| {
+        let (account_hash, tooltip) = match generate_vanity_key() {
+            Some(value) => value,
+            None => return,
+        };
+        main_output_pack_clone.add_associated_key(&account_hash, &tooltip);
+    });
+
+    let mut build_options_button = Button::new(
+        WINDOW_WIDTH - (3 * PADDING) - (3 * BUTTON_WIDTH),
+        PADDING,
+        BUTTON_WIDTH,
+        BUTTON_HEIGHT,
+        tr("Build options"),
+    );
+    build_options_button.set_color(Color::from_u32(palette().button));
+    build_options_button.set_callback(|_| show_build_options_window());
+
+    let main_output_pack_clone = main_output_pack.clone();
+    prebuilt_wasm_check_button.set_callback(move |check_button| {
+        if check_button.is_checked() {
+            let wasm_path = match get_prebuilt_session_wasm_file() {
+                Some(value) => value,
+                None => {
+                    check_button.set_checked(false);
+                    return;
+                }
+            };
+            let expected_hash = match get_expected_wasm_hash(&wasm_path) {
+                Some(value) => value,
+                None => {
+                    check_button.set_checked(false);
+                    return;
+                }
+            };
+            if let Err(error) = casper_multisig_tool::set_prebuilt_session_wasm(
+                Some(&wasm_path),
+                expected_hash.as_deref(),
+            ) {
+                dialog::alert_default(error.to_string().as_str());
+                check_button.set_checked(false);
+                return;
+            }
+            let template = if expected_hash.is_some() {
+                "Use prebuilt session Wasm (verified)\n{}"
+            } else {
+                "Use prebuilt session Wasm (UNVERIFIED)\n{}"
+            };
+            check_button.set_label(&casper_multisig_tool::translate_with(
+                template,
+                &[&wasm_path],
+            ));
+        } else {
+            let _ = casper_multisig_tool::set_prebuilt_session_wasm(None, None);
+            check_button.set_label(tr("Use prebuilt session Wasm"));
+        }
+        main_output_pack_clone.redraw_window();
+    });
+
     let main_output_pack_clone = main_output_pack.clone();
     let mut _child_output_worker = None;
     generate_smart_contract_button.set_callback(move |_| {
         _child_output_worker = main_output_pack_clone.generate_smart_contract();
     });
 
-    let mut window = Window::default()
-        .with_size(WINDOW_WIDTH, 10)
-        .with_label(TOOL_NAME);
-    window.insert(&top_frame, WindowIndices::TopFrame as i32);
-    window.insert(&add_key_button_pack, WindowIndices::AddKeyButtonPack as i32);
-    window.insert(&middle_frame, WindowIndices::MiddleFrame as i32);
-    window.insert(&main_key_frame, WindowIndices::MainKeyFrame as i32);
-    window.insert(&main_output_pack.pack, WindowIndices::MainOutputPack as i32);
-    window.insert(&bottom_frame, WindowIndices::BottomFrame as i32);
-    window.insert(
+    group.end();
+    group.insert(&top_frame, TabIndices::TopFrame as i32);
+    group.insert(&add_key_button_pack, TabIndices::AddKeyButtonPack as i32);
+    group.insert(&middle_frame, TabIndices::MiddleFrame as i32);
+    group.insert(
+        &associated_keys_scroll,
+        TabIndices::AssociatedKeysScroll as i32,
+    );
+    group.insert(&bottom_frame, TabIndices::BottomFrame as i32);
+    group.insert(
         &action_thresholds_pack.pack,
-        WindowIndices::ActionThresholdsPack as i32,
+        TabIndices::ActionThresholdsPack as i32,
     );
-    window.insert(&rust_output, WindowIndices::RustOutput as i32);
-    window.insert(
+    group.insert(&build_options_button, TabIndices::BuildOptionsButton as i32);
+    group.insert(
+        &prebuilt_wasm_check_button,
+        TabIndices::PrebuiltWasmCheckButton as i32,
+    );
+    group.insert(
         &generate_smart_contract_button,
-        WindowIndices::GenerateButton as i32,
+        TabIndices::GenerateButton as i32,
     );
+    group.insert(&rust_output, TabIndices::RustOutput as i32);
+    group.insert(&copy_rust_button, TabIndices::CopyRustButton as i32);
+    group.insert(&save_rust_button, TabIndices::SaveRustButton as i32);
+    group.insert(&issues, TabIndices::Issues as i32);
+    group.insert(&resulting_account, TabIndices::ResultingAccount as i32);
+
+    (group, main_output_pack)
+}
+
+fn main() {
+    set_panic_handler();
+    if let Err(error) = casper_multisig_tool::load_network_profiles() {
+        dialog::alert_default(&error.to_string());
+    }
+    if let Err(error) = casper_multisig_tool::load_preferences() {
+        dialog::alert_default(&error.to_string());
+    }
+
+    let app = App::default().with_scheme(Scheme::Gtk);
+    apply_theme();
+    apply_ui_scale();
+
+    // The window opens at its preferred size where the screen allows, and can be resized down to
+    // a size which still fits a small laptop's screen.
+    let (_x, _y, work_area_width, work_area_height) = app::screen_work_area(0);
+    let mut window = Window::default()
+        .with_size(
+            work_area_width.clamp(MIN_WINDOW_WIDTH, WINDOW_WIDTH),
+            work_area_height.clamp(MIN_WINDOW_HEIGHT, WINDOW_HEIGHT),
+        )
+        .center_screen()
+        .with_label(TOOL_NAME);
+    let configuration_tabs = ConfigurationTabs::new();
+    configuration_tabs.add_tab();
+    let mut menu_bar = new_menu_bar(&configuration_tabs);
+    window.end();
+    // With no resizable child, FLTK leaves the widgets alone as the window is resized, and
+    // `MainOutputPack::layout` fits them to the window's new size instead.
+    window.make_resizable(false);
+    window.size_range(MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT, 0, 0);
 
     let icon_contents = include_bytes!("../casperlabs_logo.png");
     let maybe_image = PngImage::from_data(icon_contents.as_ref()).ok();
     window.set_icon(maybe_image);
 
-    main_output_pack.redraw_window();
     window.show_with_args(&["-name", TOOL_NAME]);
 
+    configuration_tabs.offer_autosave_restore();
+    check_prerequisites_on_startup();
+    start_prewarm();
+
+    let mut last_autosave = Instant::now();
+    let mut laid_out_size = (0, 0);
     while app.wait() && !app::should_program_quit() {
+        // FLTK 1.0 has no resize callback, so the window's size is polled instead.
+        if (window.width(), window.height()) != laid_out_size {
+            laid_out_size = (window.width(), window.height());
+            menu_bar.set_size(window.width(), MENU_BAR_HEIGHT);
+            configuration_tabs.current_tab().main_output_pack.layout();
+        }
+        if last_autosave.elapsed() >= AUTOSAVE_INTERVAL {
+            configuration_tabs.autosave();
+            last_autosave = Instant::now();
+        }
         thread::sleep(Duration::from_millis(1));
     }
+    configuration_tabs.autosave();
 }
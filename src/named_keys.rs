@@ -0,0 +1,112 @@
+use casper_types::{Key, URef};
+
+use super::Error;
+
+/// A named key to be stored in the account's named keys by the generated contract.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct NamedKey {
+    pub(super) name: String,
+    pub(super) key: Key,
+}
+
+impl NamedKey {
+    /// Parses a named key from its name and formatted key, e.g. `hash-<hex>`, `uref-<hex>-<rights>`
+    /// or `account-hash-<hex>`.
+    pub(super) fn new(name: &str, formatted_key: &str) -> Result<Self, Error> {
+        let make_error = |inner: String| Error::InvalidNamedKey {
+            name: name.to_string(),
+            inner,
+        };
+
+        if name.is_empty() {
+            return Err(make_error("the name must not be empty".to_string()));
+        }
+        let key = Key::from_formatted_str(formatted_key.trim())
+            .map_err(|error| make_error(format!("failed to parse key: {:?}", error)))?;
+        match key {
+            Key::Account(_) | Key::Hash(_) | Key::URef(_) => (),
+            _ => {
+                return Err(make_error(
+                    "only account-hash, hash and uref keys are supported".to_string(),
+                ))
+            }
+        }
+        Ok(NamedKey {
+            name: name.to_string(),
+            key,
+        })
+    }
+}
+
+/// Returns `Ok` if no two named keys share a name, else `Err`.
+pub(super) fn validate_unique(named_keys: &[NamedKey]) -> Result<(), Error> {
+    for (index, named_key) in named_keys.iter().enumerate() {
+        if named_keys[..index]
+            .iter()
+            .any(|other| other.name == named_key.name)
+        {
+            return Err(Error::InvalidNamedKey {
+                name: named_key.name.clone(),
+                inner: "the name is used more than once".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the constant definitions for the named key at `index`, for the top of `main.rs`.
+pub(super) fn constants(index: usize, named_key: &NamedKey) -> String {
+    let addr = match named_key.key {
+        Key::Account(account_hash) => account_hash.value(),
+        Key::Hash(addr) => addr,
+        Key::URef(uref) => uref.addr(),
+        _ => unreachable!("unsupported named keys are rejected when parsed"),
+    };
+    format!(
+        r#"const NAMED_KEY_{index}_NAME: &str = {name:?};
+// {formatted_key}
+#[rustfmt::skip]
+const NAMED_KEY_{index}_ADDR: [u8; 32] = {addr:?};
+
+"#,
+        index = index,
+        name = named_key.name,
+        formatted_key = named_key.key.to_formatted_string(),
+        addr = addr,
+    )
+}
+
+/// Returns the statement storing the named key at `index`, for the body of `call()`, laid out as
+/// rustfmt would.
+pub(super) fn put_key_statement(index: usize, named_key: &NamedKey) -> String {
+    let key = match named_key.key {
+        Key::Account(_) => format!("Key::Account(AccountHash::new(NAMED_KEY_{}_ADDR))", index),
+        Key::Hash(_) => format!("Key::Hash(NAMED_KEY_{}_ADDR)", index),
+        Key::URef(uref) => return uref_put_key_statement(index, uref),
+        _ => unreachable!("unsupported named keys are rejected when parsed"),
+    };
+    format!("    runtime::put_key(NAMED_KEY_{}_NAME, {});\n", index, key)
+}
+
+fn uref_put_key_statement(index: usize, uref: URef) -> String {
+    format!(
+        r#"    runtime::put_key(
+        NAMED_KEY_{index}_NAME,
+        Key::URef(URef::new(
+            NAMED_KEY_{index}_ADDR,
+            AccessRights::from_bits_truncate({bits}),
+        )),
+    );
+"#,
+        index = index,
+        bits = uref.access_rights().bits()
+    )
+}
+
+/// Returns `true` if any of the named keys is a URef, and hence the generated code needs to import
+/// `URef` and `AccessRights`.
+pub(super) fn any_urefs(named_keys: &[NamedKey]) -> bool {
+    named_keys
+        .iter()
+        .any(|named_key| matches!(named_key.key, Key::URef(_)))
+}
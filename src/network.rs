@@ -0,0 +1,244 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use super::{
+    deploy_limits, explorer, protocol_profile::ProtocolProfile, rpc::ConnectionSettings, Error,
+};
+
+/// The name of the file holding the network profiles, under the tool's config dir.
+const CONFIG_FILE_NAME: &str = "networks.json";
+
+/// A Casper network the tool can talk to, along with the settings deploys for it must use.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NetworkProfile {
+    /// The name the profile is selected by, e.g. `testnet`.
+    pub name: String,
+    /// The JSON-RPC addresses of nodes on the network, e.g. `http://1.2.3.4:7777`, in the order
+    /// they're tried.
+    pub node_addresses: Vec<String>,
+    /// The URL of a node's main event stream, or `None` to derive it from the first node address.
+    pub event_stream_url: Option<String>,
+    /// The chain name deploys for the network must specify, e.g. `casper-test`.
+    pub chain_name: String,
+    /// The protocol version the network is expected to run, e.g. `1.5.2`, used to choose the
+    /// protocol profile when the network is selected.
+    pub protocol_version: Option<String>,
+    /// The largest deploy, in bytes, accepted by the network.
+    pub max_deploy_size: u32,
+    /// The base URL of a block explorer for the network, e.g. `https://cspr.live`, used to link to
+    /// accounts and deploys.
+    pub explorer_url: Option<String>,
+    /// The trusted CAs, proxy and headers used to reach the network's nodes.
+    pub connection: ConnectionSettings,
+}
+
+impl NetworkProfile {
+    /// Returns an error if the profile is missing a required field or names an unsupported
+    /// protocol version.
+    pub(super) fn validate(&self) -> Result<(), Error> {
+        let make_error = |inner: &str| Error::InvalidNetworkProfile {
+            name: self.name.clone(),
+            inner: inner.to_string(),
+        };
+
+        if self.name.trim().is_empty() {
+            return Err(make_error("the name must not be empty"));
+        }
+        if self.node_addresses.is_empty() {
+            return Err(make_error("at least one node address must be given"));
+        }
+        if self
+            .node_addresses
+            .iter()
+            .any(|address| address.trim().is_empty() || address.contains(','))
+        {
+            return Err(make_error(
+                "node addresses must not be empty or contain commas",
+            ));
+        }
+        if self.chain_name.trim().is_empty() {
+            return Err(make_error("the chain name must not be empty"));
+        }
+        if self.max_deploy_size == 0 {
+            return Err(make_error("the max deploy size must be greater than zero"));
+        }
+        if let Some(version) = &self.protocol_version {
+            ProtocolProfile::for_protocol_version(version)?;
+        }
+        if self
+            .connection
+            .headers
+            .iter()
+            .any(|(name, _)| name.trim().is_empty() || name.contains(':'))
+        {
+            return Err(make_error(
+                "header names must not be empty or contain colons",
+            ));
+        }
+        Ok(())
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "node_addresses": self.node_addresses,
+            "event_stream_url": self.event_stream_url,
+            "chain_name": self.chain_name,
+            "protocol_version": self.protocol_version,
+            "max_deploy_size": self.max_deploy_size,
+            "explorer_url": self.explorer_url,
+            "ca_certificate": self.connection.ca_certificate,
+            "proxy": self.connection.proxy,
+            "headers": self
+                .connection
+                .headers
+                .iter()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let string = |field: &str| {
+            value[field]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("a network has no '{}'", field))
+        };
+        let optional_string = |field: &str| value[field].as_str().map(str::to_string);
+
+        Ok(NetworkProfile {
+            name: string("name")?,
+            node_addresses: value["node_addresses"]
+                .as_array()
+                .ok_or("a network has no 'node_addresses'")?
+                .iter()
+                .filter_map(|address| address.as_str().map(str::to_string))
+                .collect(),
+            event_stream_url: optional_string("event_stream_url"),
+            chain_name: string("chain_name")?,
+            protocol_version: optional_string("protocol_version"),
+            max_deploy_size: value["max_deploy_size"]
+                .as_u64()
+                .map(|size| size as u32)
+                .unwrap_or(deploy_limits::DEFAULT_MAX_DEPLOY_SIZE),
+            explorer_url: optional_string("explorer_url"),
+            connection: ConnectionSettings {
+                ca_certificate: optional_string("ca_certificate").map(PathBuf::from),
+                proxy: optional_string("proxy"),
+                headers: value["headers"]
+                    .as_array()
+                    .map(|headers| {
+                        headers
+                            .iter()
+                            .filter_map(|header| {
+                                Some((
+                                    header["name"].as_str()?.to_string(),
+                                    header["value"].as_str()?.to_string(),
+                                ))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            },
+        })
+    }
+}
+
+/// The profiles available before any have been saved: Casper mainnet and testnet via their public
+/// nodes, and the first network started by NCTL.
+pub(super) fn builtin_profiles() -> Vec<NetworkProfile> {
+    vec![
+        NetworkProfile {
+            name: "mainnet".to_string(),
+            node_addresses: vec!["https://node.mainnet.casper.network".to_string()],
+            event_stream_url: None,
+            chain_name: "casper".to_string(),
+            protocol_version: None,
+            max_deploy_size: deploy_limits::DEFAULT_MAX_DEPLOY_SIZE,
+            explorer_url: Some(explorer::MAINNET_EXPLORER_URL.to_string()),
+            connection: ConnectionSettings::default(),
+        },
+        NetworkProfile {
+            name: "testnet".to_string(),
+            node_addresses: vec!["https://node.testnet.casper.network".to_string()],
+            event_stream_url: None,
+            chain_name: "casper-test".to_string(),
+            protocol_version: None,
+            max_deploy_size: deploy_limits::DEFAULT_MAX_DEPLOY_SIZE,
+            explorer_url: Some(explorer::TESTNET_EXPLORER_URL.to_string()),
+            connection: ConnectionSettings::default(),
+        },
+        NetworkProfile {
+            name: "nctl".to_string(),
+            node_addresses: vec![
+                "http://localhost:11101".to_string(),
+                "http://localhost:11102".to_string(),
+                "http://localhost:11103".to_string(),
+            ],
+            event_stream_url: Some("http://localhost:18101/events/main".to_string()),
+            chain_name: "casper-net-1".to_string(),
+            protocol_version: None,
+            max_deploy_size: deploy_limits::DEFAULT_MAX_DEPLOY_SIZE,
+            explorer_url: None,
+            connection: ConnectionSettings::default(),
+        },
+    ]
+}
+
+/// The contents of the networks config file.
+pub(super) struct Config {
+    pub(super) networks: Vec<NetworkProfile>,
+    /// The name of the selected network, if any.
+    pub(super) selected: Option<String>,
+}
+
+/// Returns the path of the file the network profiles are persisted in, under the user's config
+/// dir.
+pub(super) fn config_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| {
+        config_dir
+            .join("casper-multisig-tool")
+            .join(CONFIG_FILE_NAME)
+    })
+}
+
+/// Reads the profiles and the name of the selected one from `file`.
+///
+/// Returns `Ok(None)` if the file doesn't exist yet.
+pub(super) fn load(file: &Path) -> Result<Option<Config>, String> {
+    if !file.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(file).map_err(|error| error.to_string())?;
+    let config = serde_json::from_str::<Value>(&contents).map_err(|error| error.to_string())?;
+    let networks = config["networks"]
+        .as_array()
+        .ok_or("it has no 'networks' list")?
+        .iter()
+        .map(NetworkProfile::from_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    let selected = config["selected"].as_str().map(str::to_string);
+    Ok(Some(Config { networks, selected }))
+}
+
+/// Writes the profiles and the name of the selected one to `file`, creating its dir if needed.
+pub(super) fn save(
+    file: &Path,
+    profiles: &[NetworkProfile],
+    selected: Option<&str>,
+) -> Result<(), String> {
+    let config = json!({
+        "selected": selected,
+        "networks": profiles.iter().map(NetworkProfile::to_json).collect::<Vec<_>>(),
+    });
+    let contents = serde_json::to_string_pretty(&config).map_err(|error| error.to_string())?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    fs::write(file, contents).map_err(|error| error.to_string())
+}
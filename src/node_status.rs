@@ -0,0 +1,67 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::rpc;
+
+/// The parts of a node's `info_get_status` response relevant to building and sending deploys.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NodeStatus {
+    /// The chain name deploys sent to the node must specify, i.e. its chainspec name.
+    pub chain_name: String,
+    /// The protocol version the node runs, e.g. `1.5.2`.
+    pub protocol_version: String,
+    /// The height of the last block the node added, or `None` if it hasn't added one yet.
+    pub last_block_height: Option<u64>,
+    /// When the last block the node added was created.
+    pub last_block_timestamp: Option<String>,
+}
+
+impl NodeStatus {
+    /// Returns a warning if `chain_name` isn't the node's, as deploys specifying it would never be
+    /// executed.
+    pub fn chain_name_warning(&self, chain_name: &str) -> Option<String> {
+        if chain_name.trim() == self.chain_name {
+            return None;
+        }
+        Some(format!(
+            "the chain name '{}' doesn't match the node's '{}', so deploys for it would never be \
+            executed",
+            chain_name.trim(),
+            self.chain_name
+        ))
+    }
+}
+
+impl Display for NodeStatus {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "Chain {}, protocol {}",
+            self.chain_name, self.protocol_version
+        )?;
+        match (&self.last_block_height, &self.last_block_timestamp) {
+            (Some(height), Some(timestamp)) => {
+                write!(formatter, ", last block {} at {}", height, timestamp)
+            }
+            (Some(height), None) => write!(formatter, ", last block {}", height),
+            _ => write!(formatter, ", no blocks yet"),
+        }
+    }
+}
+
+/// Returns the status of the first node in the comma-separated `node_address` which responds.
+pub(super) fn fetch(node_address: &str) -> Result<NodeStatus, String> {
+    let status = rpc::call(node_address, &["get-node-status"])?;
+    let field = |name: &str| {
+        status[name]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("no {} in the node's status", name))
+    };
+    let last_block = &status["last_added_block_info"];
+    Ok(NodeStatus {
+        chain_name: field("chainspec_name")?,
+        protocol_version: field("api_version")?,
+        last_block_height: last_block["height"].as_u64(),
+        last_block_timestamp: last_block["timestamp"].as_str().map(str::to_string),
+    })
+}
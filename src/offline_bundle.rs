@@ -0,0 +1,248 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use casper_node::{
+    crypto::hash,
+    types::{Approval, Deploy},
+};
+use casper_types::crypto::AsymmetricType;
+use serde_json::{json, Map, Value};
+
+use super::{cost_estimate, deploy, Error};
+
+/// The dir of the bundle holding a copy of the project's sources.
+const SOURCE_DIR: &str = "source";
+
+/// The bundle's copy of the session Wasm the deploy carries.
+const WASM_FILE: &str = "session.wasm";
+
+/// The bundle's copy of the deploy without approvals, for signers to sign.
+const UNSIGNED_DEPLOY_FILE: &str = "unsigned_deploy.json";
+
+/// The bundle's human-readable summary of the configuration and signing instructions.
+const SUMMARY_FILE: &str = "SUMMARY.txt";
+
+/// The bundle's record of the deploy hash, the Wasm hash and the hash of every other file.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// The dir of the bundle into which signers write their signed copies of the deploy.
+const APPROVALS_DIR: &str = "approvals";
+
+/// Dirs of the project which aren't copied into the bundle.
+const SKIPPED_DIRS: [&str; 2] = ["target", ".git"];
+
+/// Writes an offline signing bundle for `deploy` to `bundle_dir`, which must not already exist.
+///
+/// The bundle holds a copy of the sources in `project_dir`, the session Wasm at `wasm`, the
+/// unsigned deploy, `summary` and a manifest of their hashes, so that signers on machines which are
+/// never online can check what they're signing.
+pub(super) fn export(
+    bundle_dir: &Path,
+    project_dir: &Path,
+    wasm: &Path,
+    deploy: &Deploy,
+    summary: &str,
+) -> Result<(), Error> {
+    let make_error = |inner: String| Error::ExportBundle {
+        dir: bundle_dir.display().to_string(),
+        inner,
+    };
+
+    if bundle_dir.exists() {
+        return Err(make_error("it already exists".to_string()));
+    }
+    let wasm_bytes = fs::read(wasm).map_err(|error| Error::ReadWasmFile {
+        file: wasm.display().to_string(),
+        inner: error.to_string(),
+    })?;
+    let wasm_hash = cost_estimate::wasm_hash(&wasm_bytes);
+    if cost_estimate::session_wasm_hash(deploy).as_ref() != Some(&wasm_hash) {
+        return Err(make_error(format!(
+            "the deploy's session code isn't {}",
+            wasm.display()
+        )));
+    }
+
+    fs::create_dir_all(bundle_dir.join(APPROVALS_DIR))
+        .map_err(|error| make_error(error.to_string()))?;
+    copy_sources(project_dir, &bundle_dir.join(SOURCE_DIR))
+        .map_err(|error| make_error(format!("failed to copy the project: {}", error)))?;
+    fs::write(bundle_dir.join(WASM_FILE), &wasm_bytes)
+        .map_err(|error| make_error(error.to_string()))?;
+    deploy::save_unsigned(deploy, &bundle_dir.join(UNSIGNED_DEPLOY_FILE))?;
+    fs::write(
+        bundle_dir.join(SUMMARY_FILE),
+        summary_contents(deploy, &wasm_hash, summary),
+    )
+    .map_err(|error| make_error(error.to_string()))?;
+
+    let mut files = Map::new();
+    for path in files_under(bundle_dir).map_err(|error| make_error(error.to_string()))? {
+        let contents =
+            fs::read(bundle_dir.join(&path)).map_err(|error| make_error(error.to_string()))?;
+        files.insert(path, Value::String(format!("{:x}", hash::hash(&contents))));
+    }
+    let manifest = json!({
+        "deploy_hash": format!("{:x}", deploy.id().inner()),
+        "wasm_hash": wasm_hash,
+        "files": files,
+    });
+    let contents =
+        serde_json::to_string_pretty(&manifest).map_err(|error| make_error(error.to_string()))?;
+    fs::write(bundle_dir.join(MANIFEST_FILE), contents)
+        .map_err(|error| make_error(error.to_string()))
+}
+
+/// Checks the offline signing bundle in `bundle_dir` is for `deploy` and unaltered, then merges
+/// the approvals in the signed copies of the deploy in its approvals dir into `deploy`, returning
+/// those added.
+pub(super) fn import(deploy: &mut Deploy, bundle_dir: &Path) -> Result<Vec<Approval>, Error> {
+    let make_error = |inner: String| Error::ImportBundle {
+        dir: bundle_dir.display().to_string(),
+        inner,
+    };
+
+    verify(deploy, bundle_dir).map_err(make_error)?;
+
+    let mut approval_files = fs::read_dir(bundle_dir.join(APPROVALS_DIR))
+        .map_err(|error| make_error(error.to_string()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect::<Vec<_>>();
+    approval_files.sort();
+    let mut added = vec![];
+    for approval_file in approval_files {
+        added.extend(deploy::import_approvals(deploy, &approval_file)?);
+    }
+    Ok(added)
+}
+
+/// Checks every file listed in the bundle's manifest still has the recorded hash, and that the
+/// bundle's deploy and Wasm are those of `deploy`.
+fn verify(deploy: &Deploy, bundle_dir: &Path) -> Result<(), String> {
+    let contents = fs::read_to_string(bundle_dir.join(MANIFEST_FILE))
+        .map_err(|error| format!("failed to read {}: {}", MANIFEST_FILE, error))?;
+    let manifest = serde_json::from_str::<Value>(&contents)
+        .map_err(|error| format!("failed to parse {}: {}", MANIFEST_FILE, error))?;
+
+    let deploy_hash = format!("{:x}", deploy.id().inner());
+    if manifest["deploy_hash"].as_str() != Some(deploy_hash.as_str()) {
+        return Err(format!(
+            "it's for deploy {} rather than {}",
+            manifest["deploy_hash"].as_str().unwrap_or("<none>"),
+            deploy_hash
+        ));
+    }
+    let files = manifest["files"]
+        .as_object()
+        .ok_or_else(|| format!("{} lists no files", MANIFEST_FILE))?;
+    for (path, expected_hash) in files {
+        let contents = fs::read(bundle_dir.join(path))
+            .map_err(|error| format!("failed to read {}: {}", path, error))?;
+        if expected_hash.as_str() != Some(format!("{:x}", hash::hash(&contents)).as_str()) {
+            return Err(format!("{} has been modified", path));
+        }
+    }
+    for required in &[WASM_FILE, UNSIGNED_DEPLOY_FILE] {
+        if !files.contains_key(*required) {
+            return Err(format!("{} doesn't list {}", MANIFEST_FILE, required));
+        }
+    }
+
+    let wasm_bytes = fs::read(bundle_dir.join(WASM_FILE)).map_err(|error| error.to_string())?;
+    let wasm_hash = cost_estimate::wasm_hash(&wasm_bytes);
+    if manifest["wasm_hash"].as_str() != Some(wasm_hash.as_str())
+        || cost_estimate::session_wasm_hash(deploy).as_ref() != Some(&wasm_hash)
+    {
+        return Err(format!("{} isn't the deploy's session code", WASM_FILE));
+    }
+    let unsigned_deploy =
+        deploy::load(&bundle_dir.join(UNSIGNED_DEPLOY_FILE)).map_err(|error| error.to_string())?;
+    if unsigned_deploy.id() != deploy.id() {
+        return Err(format!("{} is a different deploy", UNSIGNED_DEPLOY_FILE));
+    }
+    Ok(())
+}
+
+/// Returns the contents of the bundle's summary: the deploy and Wasm hashes for signers to
+/// compare, how to sign, and the configuration.
+fn summary_contents(deploy: &Deploy, wasm_hash: &str, summary: &str) -> String {
+    format!(
+        "Offline signing bundle\n\
+        \n\
+        Deploy hash: {deploy_hash:x}\n\
+        Account: {account}\n\
+        Chain name: {chain_name}\n\
+        Session Wasm Blake2b hash: {wasm_hash}\n\
+        \n\
+        Every file's Blake2b hash is listed in {manifest}.  To sign, check the configuration below \
+        and the Wasm built from {source}/ match what was agreed, then run:\n\
+        \n    \
+        casper-client sign-deploy -i {unsigned_deploy} -k <secret key file> -o \
+        {approvals}/<your name>.json\n\
+        \n\
+        Return the bundle with your signed copy in {approvals}/.\n\
+        \n\
+        Configuration\n\
+        \n\
+        {summary}",
+        deploy_hash = deploy.id().inner(),
+        account = deploy.header().account().to_hex(),
+        chain_name = deploy.header().chain_name(),
+        wasm_hash = wasm_hash,
+        manifest = MANIFEST_FILE,
+        source = SOURCE_DIR,
+        unsigned_deploy = UNSIGNED_DEPLOY_FILE,
+        approvals = APPROVALS_DIR,
+        summary = summary,
+    )
+}
+
+/// Recursively copies the files under `source` to `destination`, skipping build output and any
+/// git repository.
+fn copy_sources(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let destination_path = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            if !SKIPPED_DIRS
+                .iter()
+                .any(|skipped| entry.file_name() == *skipped)
+            {
+                copy_sources(&entry.path(), &destination_path)?;
+            }
+        } else {
+            fs::copy(entry.path(), &destination_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the paths, relative to `dir` and `/`-separated, of all files under it.
+fn files_under(dir: &Path) -> io::Result<Vec<String>> {
+    let mut files = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative_dir) = dirs.pop() {
+        for entry in fs::read_dir(dir.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(relative_path);
+            } else {
+                let components = relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>();
+                files.push(components.join("/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
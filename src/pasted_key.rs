@@ -0,0 +1,219 @@
+use casper_types::{
+    account::{self, AccountHash},
+    crypto::AsymmetricType,
+    PublicKey,
+};
+
+/// The prefix of a formatted account hash.
+const ACCOUNT_HASH_PREFIX: &str = "account-hash-";
+
+/// The number of hex chars in an account hash.
+const ACCOUNT_HASH_HEX_LENGTH: usize = 64;
+
+/// A key recognised in text pasted by the user.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) enum PastedKey {
+    /// A hex-encoded public key, prefixed with its algorithm tag.
+    PublicKey(PublicKey),
+    /// An account hash, either formatted or as bare hex.
+    AccountHash(AccountHash),
+}
+
+impl PastedKey {
+    /// Recognises `text` as a hex-encoded public key, a formatted account hash or an account hash
+    /// as bare hex.
+    ///
+    /// Surrounding whitespace and quotes are ignored.  Hex in mixed case must match the checksummed
+    /// form produced by newer versions of the casper-client.
+    pub(super) fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().trim_matches(|c| c == '"' || c == '\'').trim();
+        let (hex, formatted) = match text.strip_prefix(ACCOUNT_HASH_PREFIX) {
+            Some(hex) => (hex, true),
+            None => (text, false),
+        };
+        if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "'{}' isn't a hex-encoded public key or an account hash",
+                abbreviate(text)
+            ));
+        }
+
+        if formatted || hex.len() == ACCOUNT_HASH_HEX_LENGTH {
+            if hex.len() != ACCOUNT_HASH_HEX_LENGTH {
+                return Err(format!(
+                    "an account hash must have {} hex chars, but '{}' has {}",
+                    ACCOUNT_HASH_HEX_LENGTH,
+                    abbreviate(text),
+                    hex.len()
+                ));
+            }
+            verify_checksum(hex)?;
+            let account_hash = AccountHash::from_formatted_str(&format!(
+                "{}{}",
+                ACCOUNT_HASH_PREFIX,
+                hex.to_lowercase()
+            ))
+            .map_err(|error| error.to_string())?;
+            return Ok(PastedKey::AccountHash(account_hash));
+        }
+
+        // The algorithm tag is all digits, so only the key itself can carry a checksum.
+        if hex.len() > 2 {
+            verify_checksum(&hex[2..])?;
+        }
+        PublicKey::from_hex(hex.to_lowercase())
+            .map(PastedKey::PublicKey)
+            .map_err(|error| {
+                format!(
+                    "'{}' isn't a valid hex-encoded public key: {}",
+                    abbreviate(text),
+                    error
+                )
+            })
+    }
+
+    /// Returns the account hash of the key.
+    pub(super) fn account_hash(&self) -> AccountHash {
+        match self {
+            PastedKey::PublicKey(public_key) => public_key.to_account_hash(),
+            PastedKey::AccountHash(account_hash) => *account_hash,
+        }
+    }
+}
+
+/// Returns an error if `hex` is in mixed case but doesn't match the checksummed encoding of its
+/// bytes.  All lowercase or all uppercase hex carries no checksum, so is always accepted.
+///
+/// The checksum is like Ethereum's EIP-55: each letter is uppercase if the next bit of the Blake2b
+/// hash of the bytes is set, taking the hash's bits least significant first and cycling through
+/// them.
+fn verify_checksum(hex: &str) -> Result<(), String> {
+    let has_lowercase = hex.chars().any(|c| c.is_ascii_lowercase());
+    let has_uppercase = hex.chars().any(|c| c.is_ascii_uppercase());
+    if !has_lowercase || !has_uppercase {
+        return Ok(());
+    }
+
+    let bytes = hex::decode(hex).map_err(|error| error.to_string())?;
+    let hash = account::blake2b(&bytes);
+    let mut hash_bits = hash
+        .iter()
+        .cycle()
+        .flat_map(|byte| (0..8).map(move |offset| (byte >> offset) & 1 == 1));
+    let expected = hex::encode(&bytes)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() && hash_bits.next().unwrap_or(true) {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    if expected != hex {
+        return Err(format!(
+            "the mixed-case hex '{}' has an invalid checksum, so may have been mistyped",
+            abbreviate(hex)
+        ));
+    }
+    Ok(())
+}
+
+/// Returns `text` shortened for an error message if it's long, e.g. if something other than a key
+/// was on the clipboard.
+fn abbreviate(text: &str) -> String {
+    const MAX_CHARS: usize = 80;
+    if text.chars().count() <= MAX_CHARS {
+        return text.to_string();
+    }
+    format!("{}...", text.chars().take(MAX_CHARS).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT_HASH_HEX: &str =
+        "2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb";
+    /// `ACCOUNT_HASH_HEX` checksummed, as accepted by the checksummed hex decoding of
+    /// casper-types 3.0.
+    const CHECKSUMMED_ACCOUNT_HASH_HEX: &str =
+        "2c4a11c062A8A337Bfc97E27fd66291cAEB2C65865Dcb5d3eF3759c4c97efeCB";
+    const PUBLIC_KEY_HEX: &str =
+        "01197f6b23e16c8532c6abc838facd5ea789be0c76b2920334039bfa8b3d368d61";
+    const CHECKSUMMED_PUBLIC_KEY_HEX: &str =
+        "01197f6b23e16C8532c6ABC838fAcD5eA789be0c76b2920334039bfa8b3d368D61";
+
+    fn expected_account_hash() -> PastedKey {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(&hex::decode(ACCOUNT_HASH_HEX).unwrap());
+        PastedKey::AccountHash(AccountHash::new(bytes))
+    }
+
+    fn expected_public_key() -> PastedKey {
+        PastedKey::PublicKey(PublicKey::from_hex(PUBLIC_KEY_HEX).unwrap())
+    }
+
+    #[test]
+    fn parses_all_lowercase() {
+        assert_eq!(
+            PastedKey::parse(ACCOUNT_HASH_HEX).unwrap(),
+            expected_account_hash()
+        );
+        assert_eq!(
+            PastedKey::parse(PUBLIC_KEY_HEX).unwrap(),
+            expected_public_key()
+        );
+    }
+
+    #[test]
+    fn parses_correctly_checksummed() {
+        assert_eq!(
+            PastedKey::parse(CHECKSUMMED_ACCOUNT_HASH_HEX).unwrap(),
+            expected_account_hash()
+        );
+        assert_eq!(
+            PastedKey::parse(CHECKSUMMED_PUBLIC_KEY_HEX).unwrap(),
+            expected_public_key()
+        );
+    }
+
+    #[test]
+    fn rejects_wrongly_checksummed() {
+        // The first letter's case is swapped.
+        let account_hash_hex = CHECKSUMMED_ACCOUNT_HASH_HEX.replacen("2c4a", "2C4a", 1);
+        let error = PastedKey::parse(&account_hash_hex).unwrap_err();
+        assert!(error.contains("invalid checksum"), "{}", error);
+
+        let public_key_hex = CHECKSUMMED_PUBLIC_KEY_HEX.replacen("7f6b", "7F6b", 1);
+        let error = PastedKey::parse(&public_key_hex).unwrap_err();
+        assert!(error.contains("invalid checksum"), "{}", error);
+    }
+
+    #[test]
+    fn parses_quoted() {
+        assert_eq!(
+            PastedKey::parse(&format!(" \"{}\"\n", ACCOUNT_HASH_HEX)).unwrap(),
+            expected_account_hash()
+        );
+        assert_eq!(
+            PastedKey::parse(&format!("'{}'", PUBLIC_KEY_HEX)).unwrap(),
+            expected_public_key()
+        );
+    }
+
+    #[test]
+    fn parses_account_hash_prefixed() {
+        assert_eq!(
+            PastedKey::parse(&format!("account-hash-{}", ACCOUNT_HASH_HEX)).unwrap(),
+            expected_account_hash()
+        );
+        assert_eq!(
+            PastedKey::parse(&format!("account-hash-{}", CHECKSUMMED_ACCOUNT_HASH_HEX)).unwrap(),
+            expected_account_hash()
+        );
+
+        let error = PastedKey::parse(&format!("account-hash-{}", PUBLIC_KEY_HEX)).unwrap_err();
+        assert!(error.contains("must have 64 hex chars"), "{}", error);
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+
+use super::Error;
+
+/// A change made to the account by the generated session code.
+///
+/// Keys are identified by their index in the configuration, with index 0 being the main account's
+/// key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum Operation {
+    UpdateMainKey,
+    AddKey(usize),
+    SetKeyManagementThreshold,
+    SetDeploymentThreshold,
+    RemoveMainKey,
+}
+
+/// A single deploy of the plan: the operations its session code performs, in order, and the keys
+/// which must sign it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct Step {
+    pub(super) signers: Vec<usize>,
+    pub(super) operations: Vec<Operation>,
+}
+
+/// The target configuration of the account.
+pub(super) struct Target<'a> {
+    /// The weights of all associated keys, main key first.
+    pub(super) weights: &'a [u8],
+    pub(super) remove_main_key: bool,
+    pub(super) key_management_threshold: u8,
+    pub(super) deployment_threshold: u8,
+}
+
+/// Why an operation couldn't be applied.
+enum Failure {
+    /// The deploy's signers don't meet the key-management threshold in force.
+    Unauthorized,
+    /// The operation would be rejected regardless of who signs the deploy.
+    Invalid(String),
+}
+
+/// The associated keys and thresholds of the account, as tracked while planning.
+#[derive(Clone)]
+struct AccountState {
+    weights: BTreeMap<usize, u32>,
+    key_management_threshold: u32,
+    deployment_threshold: u32,
+}
+
+impl AccountState {
+    /// A newly-created account: only the main key, with weight 1, and both thresholds at 1.
+    fn new() -> Self {
+        AccountState {
+            weights: vec![(0, 1)].into_iter().collect(),
+            key_management_threshold: 1,
+            deployment_threshold: 1,
+        }
+    }
+
+    fn total_weight(&self) -> u32 {
+        self.weights.values().sum()
+    }
+
+    fn signing_weight(&self, signers: &[usize]) -> u32 {
+        signers
+            .iter()
+            .filter_map(|signer| self.weights.get(signer))
+            .sum()
+    }
+
+    /// Applies `operation` as the execution engine would, given the deploy's signers.
+    fn apply(
+        &mut self,
+        operation: Operation,
+        signers: &[usize],
+        target: &Target,
+    ) -> Result<(), Failure> {
+        if self.signing_weight(signers) < self.key_management_threshold {
+            return Err(Failure::Unauthorized);
+        }
+        let key_management_threshold = u32::from(target.key_management_threshold);
+        let deployment_threshold = u32::from(target.deployment_threshold);
+        match operation {
+            Operation::UpdateMainKey => {
+                self.weights.insert(0, u32::from(target.weights[0]));
+            }
+            Operation::AddKey(index) => {
+                self.weights.insert(index, u32::from(target.weights[index]));
+            }
+            Operation::SetKeyManagementThreshold => {
+                if key_management_threshold < self.deployment_threshold {
+                    return Err(Failure::Invalid(
+                        "the key-management threshold must not be lower than the \
+                        deploy-execution threshold"
+                            .to_string(),
+                    ));
+                }
+                self.key_management_threshold = key_management_threshold;
+            }
+            Operation::SetDeploymentThreshold => {
+                if deployment_threshold > self.key_management_threshold {
+                    return Err(Failure::Invalid(
+                        "the deploy-execution threshold must not be higher than the \
+                        key-management threshold"
+                            .to_string(),
+                    ));
+                }
+                self.deployment_threshold = deployment_threshold;
+            }
+            Operation::RemoveMainKey => {
+                self.weights.remove(&0);
+            }
+        }
+        if self.total_weight() < self.key_management_threshold.max(self.deployment_threshold) {
+            return Err(Failure::Invalid(
+                "the total weight of the keys must be at least each of the thresholds".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the operations in the order the session code performs them when there's no reason to
+/// deviate.
+pub(super) fn default_operations(key_count: usize, remove_main_key: bool) -> Vec<Operation> {
+    let mut operations = vec![Operation::UpdateMainKey];
+    operations.extend((1..key_count).map(Operation::AddKey));
+    operations.push(Operation::SetKeyManagementThreshold);
+    operations.push(Operation::SetDeploymentThreshold);
+    if remove_main_key {
+        operations.push(Operation::RemoveMainKey);
+    }
+    operations
+}
+
+/// Splits the changes needed to reach `target` from a newly-created account into an ordered
+/// sequence of deploys.
+///
+/// Every operation requires the deploy's signers to meet the key-management threshold in force at
+/// that point, so a single deploy signed by the main key alone can't always make every change,
+/// e.g. once the key-management threshold has been raised above the main key's weight.  Each
+/// further deploy is signed by enough of the keys added by the earlier ones.
+pub(super) fn plan(target: &Target) -> Result<Vec<Step>, Error> {
+    let mut state = AccountState::new();
+    let mut remaining = default_operations(target.weights.len(), target.remove_main_key);
+    let mut signers = vec![0];
+    let mut steps = vec![];
+
+    while !remaining.is_empty() {
+        let mut best = apply_while_authorized(&state, &remaining, &signers, target)?;
+        // Setting the deploy-execution threshold first can let the main key make more changes
+        // before it loses the authority to do so.
+        if let Some(reordered) = with_thresholds_swapped(&remaining) {
+            if let Ok(candidate) = apply_while_authorized(&state, &reordered, &signers, target) {
+                if candidate.applied > best.applied {
+                    best = candidate;
+                    remaining = reordered;
+                }
+            }
+        }
+        if best.applied == 0 {
+            return Err(unreachable_error(
+                "the keys can't meet the key-management threshold".to_string(),
+            ));
+        }
+
+        steps.push(Step {
+            signers: signers.clone(),
+            operations: remaining.drain(..best.applied).collect(),
+        });
+        state = best.state;
+
+        if !remaining.is_empty() {
+            let required_weight = state
+                .key_management_threshold
+                .max(state.deployment_threshold)
+                .max(u32::from(target.key_management_threshold));
+            signers = choose_signers(&state, required_weight)?;
+        }
+    }
+
+    Ok(steps)
+}
+
+struct Applied {
+    state: AccountState,
+    applied: usize,
+}
+
+/// Applies as many of `operations` as the signers are authorized to, stopping at the first they
+/// aren't.
+fn apply_while_authorized(
+    state: &AccountState,
+    operations: &[Operation],
+    signers: &[usize],
+    target: &Target,
+) -> Result<Applied, Error> {
+    let mut state = state.clone();
+    for (applied, operation) in operations.iter().enumerate() {
+        match state.apply(*operation, signers, target) {
+            Ok(()) => (),
+            Err(Failure::Unauthorized) => return Ok(Applied { state, applied }),
+            Err(Failure::Invalid(reason)) => return Err(unreachable_error(reason)),
+        }
+    }
+    Ok(Applied {
+        state,
+        applied: operations.len(),
+    })
+}
+
+/// Returns `operations` with the key-management threshold being set after the deploy-execution
+/// one, or `None` if they aren't both pending.
+fn with_thresholds_swapped(operations: &[Operation]) -> Option<Vec<Operation>> {
+    let key_management = operations
+        .iter()
+        .position(|operation| *operation == Operation::SetKeyManagementThreshold)?;
+    let deployment = operations
+        .iter()
+        .position(|operation| *operation == Operation::SetDeploymentThreshold)?;
+    let mut reordered = operations.to_vec();
+    reordered.swap(key_management, deployment);
+    Some(reordered)
+}
+
+/// Returns the keys to sign a deploy, whose combined weight meets `required_weight`.
+///
+/// While it is still associated, the main key is always included, since the deploy is sent from
+/// the main account.  The other keys are added heaviest first.
+fn choose_signers(state: &AccountState, required_weight: u32) -> Result<Vec<usize>, Error> {
+    let mut candidates = state.weights.iter().collect::<Vec<_>>();
+    candidates.sort_by(|(index1, weight1), (index2, weight2)| {
+        (**index1 != 0)
+            .cmp(&(**index2 != 0))
+            .then(weight2.cmp(weight1))
+            .then(index1.cmp(index2))
+    });
+
+    let mut signers = vec![];
+    let mut weight = 0;
+    for (index, key_weight) in candidates {
+        if weight >= required_weight && !signers.is_empty() {
+            break;
+        }
+        signers.push(*index);
+        weight += key_weight;
+    }
+    if weight < required_weight {
+        return Err(unreachable_error(
+            "the keys can't meet the key-management threshold".to_string(),
+        ));
+    }
+    signers.sort_unstable();
+    Ok(signers)
+}
+
+fn unreachable_error(inner: String) -> Error {
+    Error::UnreachableConfiguration { inner }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_for(
+        weights: &[u8],
+        remove_main_key: bool,
+        key_management_threshold: u8,
+        deployment_threshold: u8,
+    ) -> Result<Vec<Step>, Error> {
+        plan(&Target {
+            weights,
+            remove_main_key,
+            key_management_threshold,
+            deployment_threshold,
+        })
+    }
+
+    #[test]
+    fn two_of_three_with_main_key_removed() {
+        let steps = plan_for(&[1, 1, 1, 1], true, 2, 2).unwrap();
+
+        // The main key can add the keys and raise the key-management threshold, but then needs a
+        // second signer to make the remaining changes.
+        assert_eq!(
+            steps,
+            vec![
+                Step {
+                    signers: vec![0],
+                    operations: vec![
+                        Operation::UpdateMainKey,
+                        Operation::AddKey(1),
+                        Operation::AddKey(2),
+                        Operation::AddKey(3),
+                        Operation::SetKeyManagementThreshold,
+                    ],
+                },
+                Step {
+                    signers: vec![0, 1],
+                    operations: vec![Operation::SetDeploymentThreshold, Operation::RemoveMainKey,],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn thresholds_above_initial_weight_are_set_after_the_keys() {
+        // Both thresholds exceed the new account's total weight of 1, so can only be set once the
+        // other keys have been added.
+        let steps = plan_for(&[1, 1, 1], false, 3, 2).unwrap();
+
+        assert_eq!(
+            steps,
+            vec![
+                Step {
+                    signers: vec![0],
+                    operations: vec![
+                        Operation::UpdateMainKey,
+                        Operation::AddKey(1),
+                        Operation::AddKey(2),
+                        Operation::SetKeyManagementThreshold,
+                    ],
+                },
+                Step {
+                    signers: vec![0, 1, 2],
+                    operations: vec![Operation::SetDeploymentThreshold],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn main_key_weight_raised_first_makes_every_change() {
+        let steps = plan_for(&[3, 1, 1], false, 3, 3).unwrap();
+
+        assert_eq!(
+            steps,
+            vec![Step {
+                signers: vec![0],
+                operations: default_operations(3, false),
+            }]
+        );
+    }
+
+    #[test]
+    fn deployment_threshold_set_first_if_it_saves_a_deploy() {
+        let steps = plan_for(&[1, 1, 1], false, 2, 1).unwrap();
+
+        assert_eq!(
+            steps,
+            vec![Step {
+                signers: vec![0],
+                operations: vec![
+                    Operation::UpdateMainKey,
+                    Operation::AddKey(1),
+                    Operation::AddKey(2),
+                    Operation::SetDeploymentThreshold,
+                    Operation::SetKeyManagementThreshold,
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn threshold_above_total_weight_is_unreachable() {
+        let result = plan_for(&[1, 1], false, 5, 1);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnreachableConfiguration { .. })
+        ));
+    }
+
+    #[test]
+    fn removing_main_key_below_thresholds_is_unreachable() {
+        // Without the main key, the remaining keys only have a total weight of 2.
+        let result = plan_for(&[1, 1, 1], true, 3, 3);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnreachableConfiguration { .. })
+        ));
+    }
+}
@@ -0,0 +1,189 @@
+use casper_node::crypto::hash;
+use casper_types::{account::AccountHash, RuntimeArgs};
+
+use super::Error;
+
+pub(super) const ARG_KEY_COUNT: &str = "key_count";
+pub(super) const ARG_ACCOUNT_PREFIX: &str = "account_";
+pub(super) const ARG_WEIGHT_PREFIX: &str = "weight_";
+pub(super) const ARG_REMOVE_MAIN_ACCOUNT: &str = "remove_main_account";
+pub(super) const ARG_KEY_MANAGEMENT_THRESHOLD: &str = "key_management_threshold";
+pub(super) const ARG_DEPLOYMENT_THRESHOLD: &str = "deployment_threshold";
+
+/// The magic bytes with which every Wasm module starts.
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Returns the source of the generic session contract.
+///
+/// Rather than having the keys, weights and thresholds baked in as constants, this contract reads
+/// them all from the deploy's runtime args, meaning a single audited build can be reused for any
+/// configuration.  Key 0 is always the main account's key.
+pub(super) fn main_rs_contents() -> String {
+    format!(
+        r#"#![cfg_attr(
+    not(target_arch = "wasm32"),
+    crate_type = "target arch should be wasm32"
+)]
+#![no_main]
+
+use casper_contract::{{
+    contract_api::{{account, runtime}},
+    unwrap_or_revert::UnwrapOrRevert,
+}};
+use casper_types::account::{{AccountHash, ActionType, Weight}};
+
+const ARG_KEY_COUNT: &str = "{key_count}";
+const ARG_ACCOUNT_PREFIX: &str = "{account_prefix}";
+const ARG_WEIGHT_PREFIX: &str = "{weight_prefix}";
+const ARG_REMOVE_MAIN_ACCOUNT: &str = "{remove_main_account}";
+const ARG_KEY_MANAGEMENT_THRESHOLD: &str = "{key_management_threshold}";
+const ARG_DEPLOYMENT_THRESHOLD: &str = "{deployment_threshold}";
+
+fn account_hash_arg(index: u8) -> AccountHash {{
+    runtime::get_named_arg(&format!("{{}}{{}}", ARG_ACCOUNT_PREFIX, index))
+}}
+
+fn weight_arg(index: u8) -> Weight {{
+    Weight::new(runtime::get_named_arg(&format!("{{}}{{}}", ARG_WEIGHT_PREFIX, index)))
+}}
+
+#[no_mangle]
+pub extern "C" fn call() {{
+    let key_count: u8 = runtime::get_named_arg(ARG_KEY_COUNT);
+
+    // Update the main account key's weight.
+    let main_account_hash = account_hash_arg(0);
+    account::update_associated_key(main_account_hash, weight_arg(0)).unwrap_or_revert();
+
+    // Add the remaining associated keys.
+    for index in 1..key_count {{
+        account::add_associated_key(account_hash_arg(index), weight_arg(index)).unwrap_or_revert();
+    }}
+
+    // Set the action thresholds.
+    let key_management_threshold: u8 = runtime::get_named_arg(ARG_KEY_MANAGEMENT_THRESHOLD);
+    account::set_action_threshold(
+        ActionType::KeyManagement,
+        Weight::new(key_management_threshold),
+    )
+    .unwrap_or_revert();
+    let deployment_threshold: u8 = runtime::get_named_arg(ARG_DEPLOYMENT_THRESHOLD);
+    account::set_action_threshold(ActionType::Deployment, Weight::new(deployment_threshold))
+        .unwrap_or_revert();
+
+    // Remove the main account's key if required.
+    let remove_main_account: bool = runtime::get_named_arg(ARG_REMOVE_MAIN_ACCOUNT);
+    if remove_main_account {{
+        account::remove_associated_key(main_account_hash).unwrap_or_revert();
+    }}
+}}
+"#,
+        key_count = ARG_KEY_COUNT,
+        account_prefix = ARG_ACCOUNT_PREFIX,
+        weight_prefix = ARG_WEIGHT_PREFIX,
+        remove_main_account = ARG_REMOVE_MAIN_ACCOUNT,
+        key_management_threshold = ARG_KEY_MANAGEMENT_THRESHOLD,
+        deployment_threshold = ARG_DEPLOYMENT_THRESHOLD,
+    )
+}
+
+/// Returns `Ok` if the provided bytes look like a Wasm module and, if `expected_hash` is given,
+/// their Blake2b hash matches it, else `Err`.
+///
+/// The tool neither ships nor downloads a build of the generic contract, so the expected hash is
+/// the one published with the audited build, which pins the module to exactly that build.
+pub(super) fn validate_wasm(
+    file: &str,
+    bytes: &[u8],
+    expected_hash: Option<&str>,
+) -> Result<(), Error> {
+    if !bytes.starts_with(WASM_MAGIC) {
+        return Err(Error::ReadWasmFile {
+            file: file.to_string(),
+            inner: "not a Wasm module".to_string(),
+        });
+    }
+    if let Some(expected_hash) = expected_hash {
+        let actual_hash = wasm_hash(bytes);
+        if !actual_hash.eq_ignore_ascii_case(expected_hash.trim()) {
+            return Err(Error::ReadWasmFile {
+                file: file.to_string(),
+                inner: format!(
+                    "its Blake2b hash {} doesn't match the expected {}, so it isn't the audited \
+                    build",
+                    actual_hash,
+                    expected_hash.trim()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the hex-encoded Blake2b hash of the Wasm module `bytes`.
+pub(super) fn wasm_hash(bytes: &[u8]) -> String {
+    format!("{:x}", hash::hash(bytes))
+}
+
+/// Returns the runtime args to be passed to the generic session contract.
+///
+/// `keys` must have the main account's key as its first entry.
+pub(super) fn runtime_args(
+    keys: &[(AccountHash, u8)],
+    remove_main_account: bool,
+    key_management_weight: u8,
+    deployment_weight: u8,
+) -> Result<RuntimeArgs, Error> {
+    let make_error = |error: casper_types::CLValueError| Error::BuildRuntimeArgs {
+        inner: format!("{:?}", error),
+    };
+
+    let mut args = RuntimeArgs::new();
+    args.insert(ARG_KEY_COUNT, keys.len() as u8)
+        .map_err(make_error)?;
+    for (index, (account_hash, weight)) in keys.iter().enumerate() {
+        args.insert(format!("{}{}", ARG_ACCOUNT_PREFIX, index), *account_hash)
+            .map_err(make_error)?;
+        args.insert(format!("{}{}", ARG_WEIGHT_PREFIX, index), *weight)
+            .map_err(make_error)?;
+    }
+    args.insert(ARG_REMOVE_MAIN_ACCOUNT, remove_main_account)
+        .map_err(make_error)?;
+    args.insert(ARG_KEY_MANAGEMENT_THRESHOLD, key_management_weight)
+        .map_err(make_error)?;
+    args.insert(ARG_DEPLOYMENT_THRESHOLD, deployment_weight)
+        .map_err(make_error)?;
+    Ok(args)
+}
+
+/// Returns the runtime args formatted as `--session-arg` values for the casper-client.
+pub(super) fn casper_client_session_args(
+    keys: &[(AccountHash, u8)],
+    remove_main_account: bool,
+    key_management_weight: u8,
+    deployment_weight: u8,
+) -> Vec<String> {
+    let mut args = vec![format!("{}:u8='{}'", ARG_KEY_COUNT, keys.len())];
+    for (index, (account_hash, weight)) in keys.iter().enumerate() {
+        args.push(format!(
+            "{}{}:account_hash='{}'",
+            ARG_ACCOUNT_PREFIX,
+            index,
+            account_hash.to_formatted_string()
+        ));
+        args.push(format!("{}{}:u8='{}'", ARG_WEIGHT_PREFIX, index, weight));
+    }
+    args.push(format!(
+        "{}:bool='{}'",
+        ARG_REMOVE_MAIN_ACCOUNT, remove_main_account
+    ));
+    args.push(format!(
+        "{}:u8='{}'",
+        ARG_KEY_MANAGEMENT_THRESHOLD, key_management_weight
+    ));
+    args.push(format!(
+        "{}:u8='{}'",
+        ARG_DEPLOYMENT_THRESHOLD, deployment_weight
+    ));
+    args
+}
@@ -0,0 +1,303 @@
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde_json::{json, Value};
+
+use super::i18n::Language;
+
+/// The name of the file holding the preferences, under the tool's config dir.
+const FILE_NAME: &str = "preferences.json";
+
+/// The author written to the generated Cargo.toml unless others are preferred.
+pub(super) const DEFAULT_AUTHOR: &str = "Fraser Hutchison <fraser@casperlabs.io>";
+
+/// The size of the text in inputs, outputs and the generated code unless another is preferred.
+const DEFAULT_FONT_SIZE: u8 = 16;
+
+/// The smallest and largest font sizes which can be preferred.
+const FONT_SIZES: (u8, u8) = (8, 48);
+
+/// The smallest and largest UI scales which can be preferred, as percentages.
+const UI_SCALES: (u16, u16) = (50, 400);
+
+/// Whether the tool's widgets are light or dark.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Theme {
+    Light,
+    Dark,
+    /// White text on black, with yellow for warnings and bright highlighting of the generated code.
+    HighContrast,
+    /// Follows the OS's appearance where it can be detected, and is light otherwise.
+    #[default]
+    System,
+}
+
+impl Theme {
+    /// All the themes.
+    pub const ALL: [Theme; 4] = [
+        Theme::Light,
+        Theme::Dark,
+        Theme::HighContrast,
+        Theme::System,
+    ];
+
+    /// Returns `true` if the theme is dark, detecting the OS's appearance for `System`.
+    pub(super) fn is_dark(&self) -> bool {
+        match self {
+            Theme::Light => false,
+            Theme::Dark | Theme::HighContrast => true,
+            Theme::System => system_prefers_dark(),
+        }
+    }
+
+    /// Returns the name the theme is stored under in the preferences file.
+    fn id(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high_contrast",
+            Theme::System => "system",
+        }
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Theme::Light => write!(formatter, "Light"),
+            Theme::Dark => write!(formatter, "Dark"),
+            Theme::HighContrast => write!(formatter, "High contrast"),
+            Theme::System => write!(formatter, "System"),
+        }
+    }
+}
+
+/// The user's defaults, applied at startup and to every new tab.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Preferences {
+    /// The dir new projects are saved under, or empty for the home dir.
+    pub project_dir: PathBuf,
+    /// The name new contracts are given, or empty for the built-in default.
+    pub contract_name: String,
+    /// The authors written to the generated Cargo.toml.
+    pub authors: Vec<String>,
+    /// The Rust toolchain the contract is compiled with, or empty for the target network's
+    /// default.
+    pub toolchain: String,
+    /// The network selected at startup and in new tabs, or `None` to keep the last one selected.
+    pub network: Option<String>,
+    pub theme: Theme,
+    /// The language the UI is shown in.  Takes effect after a restart.
+    pub language: Language,
+    /// The size of the text in inputs, outputs and the generated code, before any scaling.
+    pub font_size: u8,
+    /// The scale of the whole UI as a percentage, or `None` for the system's.
+    pub ui_scale: Option<u16>,
+    /// Whether to run `wasm-strip` on the compiled contract.
+    pub run_wasm_strip: bool,
+    /// Whether to run `wasm-opt -Oz` on the compiled contract.
+    pub run_wasm_opt: bool,
+}
+
+impl Preferences {
+    /// Returns an error if an author is empty or spans several lines, or the font size or UI scale
+    /// is out of range.
+    pub(super) fn validate(&self) -> Result<(), String> {
+        if self.font_size < FONT_SIZES.0 || self.font_size > FONT_SIZES.1 {
+            return Err(format!(
+                "the font size must be from {} to {}",
+                FONT_SIZES.0, FONT_SIZES.1
+            ));
+        }
+        if let Some(ui_scale) = self.ui_scale {
+            if ui_scale < UI_SCALES.0 || ui_scale > UI_SCALES.1 {
+                return Err(format!(
+                    "the UI scale must be from {}% to {}%",
+                    UI_SCALES.0, UI_SCALES.1
+                ));
+            }
+        }
+        for author in &self.authors {
+            if author.trim().is_empty() {
+                return Err("an author must not be empty".to_string());
+            }
+            if author.contains(char::is_control) {
+                return Err(format!(
+                    "the author '{}' must not contain control characters",
+                    author.escape_default()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "project_dir": self.project_dir.display().to_string(),
+            "contract_name": self.contract_name,
+            "authors": self.authors,
+            "toolchain": self.toolchain,
+            "network": self.network,
+            "theme": self.theme.id(),
+            "language": self.language.id(),
+            "font_size": self.font_size,
+            "ui_scale": self.ui_scale,
+            "run_wasm_strip": self.run_wasm_strip,
+            "run_wasm_opt": self.run_wasm_opt,
+        })
+    }
+
+    /// Parses the preferences, taking the default for any which are missing, e.g. as they were
+    /// added in a later version of the tool.
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let defaults = Preferences::default();
+        let string = |name: &str| value[name].as_str().map(str::to_string);
+        let authors = match value.get("authors") {
+            Some(authors) => authors
+                .as_array()
+                .ok_or("'authors' isn't a list")?
+                .iter()
+                .map(|author| {
+                    author
+                        .as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| format!("the author {} isn't a string", author))
+                })
+                .collect::<Result<_, _>>()?,
+            None => defaults.authors,
+        };
+        let theme = match value["theme"].as_str() {
+            Some(id) => Theme::ALL
+                .iter()
+                .copied()
+                .find(|theme| theme.id() == id)
+                .ok_or_else(|| format!("there is no '{}' theme", id))?,
+            None => defaults.theme,
+        };
+        let language = match value["language"].as_str() {
+            Some(id) => Language::ALL
+                .iter()
+                .copied()
+                .find(|language| language.id() == id)
+                .ok_or_else(|| format!("there is no '{}' language", id))?,
+            None => defaults.language,
+        };
+        Ok(Preferences {
+            project_dir: string("project_dir")
+                .map(PathBuf::from)
+                .unwrap_or(defaults.project_dir),
+            contract_name: string("contract_name").unwrap_or(defaults.contract_name),
+            authors,
+            toolchain: string("toolchain").unwrap_or(defaults.toolchain),
+            network: string("network"),
+            theme,
+            language,
+            font_size: value["font_size"]
+                .as_u64()
+                .map(|font_size| font_size.min(u64::from(u8::MAX)) as u8)
+                .unwrap_or(defaults.font_size),
+            ui_scale: value["ui_scale"]
+                .as_u64()
+                .map(|ui_scale| ui_scale.min(u64::from(u16::MAX)) as u16),
+            run_wasm_strip: value["run_wasm_strip"]
+                .as_bool()
+                .unwrap_or(defaults.run_wasm_strip),
+            run_wasm_opt: value["run_wasm_opt"]
+                .as_bool()
+                .unwrap_or(defaults.run_wasm_opt),
+        })
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            project_dir: PathBuf::new(),
+            contract_name: String::new(),
+            authors: vec![DEFAULT_AUTHOR.to_string()],
+            toolchain: String::new(),
+            network: None,
+            theme: Theme::default(),
+            language: Language::default(),
+            font_size: DEFAULT_FONT_SIZE,
+            ui_scale: None,
+            run_wasm_strip: false,
+            run_wasm_opt: false,
+        }
+    }
+}
+
+/// Returns the path of the preferences file, under the user's config dir, e.g.
+/// `~/.config/casper-multisig-tool/preferences.json` on Linux.
+pub(super) fn file() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join("casper-multisig-tool").join(FILE_NAME))
+}
+
+/// Reads the preferences in `file`, or returns the defaults if it doesn't exist yet.
+pub(super) fn load(file: &Path) -> Result<Preferences, String> {
+    if !file.exists() {
+        return Ok(Preferences::default());
+    }
+    let contents = fs::read_to_string(file).map_err(|error| error.to_string())?;
+    let preferences =
+        serde_json::from_str::<Value>(&contents).map_err(|error| error.to_string())?;
+    let preferences = Preferences::from_json(&preferences)?;
+    preferences.validate()?;
+    Ok(preferences)
+}
+
+/// Writes the preferences to `file`, creating its dir if needed.
+pub(super) fn save(file: &Path, preferences: &Preferences) -> Result<(), String> {
+    let contents =
+        serde_json::to_string_pretty(&preferences.to_json()).map_err(|error| error.to_string())?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    fs::write(file, contents).map_err(|error| error.to_string())
+}
+
+/// Returns `true` if the OS is set to a dark appearance, or `false` if that can't be told.
+fn system_prefers_dark() -> bool {
+    let output = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_lowercase())
+    };
+    if cfg!(target_os = "macos") {
+        // Only set while dark mode is on.
+        return output("defaults", &["read", "-g", "AppleInterfaceStyle"])
+            .is_some_and(|style| style.trim() == "dark");
+    }
+    if cfg!(target_os = "windows") {
+        return output(
+            "reg",
+            &[
+                "query",
+                r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+                "/v",
+                "AppsUseLightTheme",
+            ],
+        )
+        .is_some_and(|value| {
+            value
+                .lines()
+                .any(|line| line.contains("appsuselighttheme") && line.trim_end().ends_with("0x0"))
+        });
+    }
+    // Otherwise a desktop with GTK settings, as on most Linux distros, is assumed.
+    if env::var("GTK_THEME").is_ok_and(|theme| theme.to_lowercase().contains("dark")) {
+        return true;
+    }
+    ["color-scheme", "gtk-theme"].iter().any(|key| {
+        output("gsettings", &["get", "org.gnome.desktop.interface", key])
+            .is_some_and(|value| value.contains("dark"))
+    })
+}
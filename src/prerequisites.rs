@@ -0,0 +1,158 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    process::Command,
+};
+
+use super::smart_contract;
+
+/// The target the contract is compiled for.
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+/// A tool needed to compile the contract which isn't installed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Prerequisite {
+    Cargo,
+    /// rustup, which installs the toolchain named in the project's `rust-toolchain` file.
+    Rustup,
+    Toolchain {
+        toolchain: String,
+    },
+    Wasm32Target {
+        toolchain: String,
+    },
+    /// Only needed if stripping the compiled Wasm is enabled.
+    WasmStrip,
+}
+
+impl Prerequisite {
+    /// Returns the command which installs the prerequisite, if the tool can run it.
+    pub fn fix_command(&self) -> Option<Vec<String>> {
+        let command: &[&str] = match self {
+            Prerequisite::Toolchain { toolchain } => &[
+                "rustup",
+                "toolchain",
+                "install",
+                toolchain,
+                "--profile",
+                "minimal",
+            ],
+            Prerequisite::Wasm32Target { toolchain } => &[
+                "rustup",
+                "target",
+                "add",
+                WASM_TARGET,
+                "--toolchain",
+                toolchain,
+            ],
+            Prerequisite::Cargo | Prerequisite::Rustup | Prerequisite::WasmStrip => return None,
+        };
+        Some(command.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    /// Returns how to install the prerequisite by hand.
+    pub fn install_hint(&self) -> String {
+        match self {
+            Prerequisite::Cargo | Prerequisite::Rustup => {
+                "install Rust with rustup, following https://rustup.rs".to_string()
+            }
+            Prerequisite::Toolchain { .. } | Prerequisite::Wasm32Target { .. } => {
+                format!("run `{}`", self.fix_command().unwrap_or_default().join(" "))
+            }
+            Prerequisite::WasmStrip => "install wabt, e.g. with `sudo apt install wabt` or \
+                `brew install wabt`, or disable stripping under \"Build options\""
+                .to_string(),
+        }
+    }
+}
+
+impl Display for Prerequisite {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Prerequisite::Cargo => write!(formatter, "cargo"),
+            Prerequisite::Rustup => write!(formatter, "rustup"),
+            Prerequisite::Toolchain { toolchain } => {
+                write!(formatter, "the {} toolchain", toolchain)
+            }
+            Prerequisite::Wasm32Target { toolchain } => {
+                write!(formatter, "the {} target for {}", WASM_TARGET, toolchain)
+            }
+            Prerequisite::WasmStrip => write!(formatter, "wasm-strip"),
+        }
+    }
+}
+
+/// Returns the prerequisites which are missing for compiling with `host_toolchain` on the host, if
+/// compiling there, along with `wasm-strip` if `wasm_strip_needed`.
+///
+/// The toolchain and its target can only be checked once rustup is found, so aren't listed while
+/// it's missing.
+pub(super) fn missing(host_toolchain: Option<&str>, wasm_strip_needed: bool) -> Vec<Prerequisite> {
+    let mut missing = vec![];
+    if let Some(toolchain) = host_toolchain {
+        if !succeeds(&["cargo", "--version"]) {
+            missing.push(Prerequisite::Cargo);
+        }
+        let toolchain_string = toolchain.to_string();
+        if !succeeds(&["rustup", "--version"]) {
+            missing.push(Prerequisite::Rustup);
+        } else if !succeeds(&["rustup", "run", toolchain, "rustc", "--version"]) {
+            // Installing the toolchain doesn't add the target.
+            missing.push(Prerequisite::Toolchain {
+                toolchain: toolchain_string.clone(),
+            });
+            missing.push(Prerequisite::Wasm32Target {
+                toolchain: toolchain_string,
+            });
+        } else if !target_installed(toolchain) {
+            missing.push(Prerequisite::Wasm32Target {
+                toolchain: toolchain_string,
+            });
+        }
+    }
+    if wasm_strip_needed && !smart_contract::wasm_strip_available() {
+        missing.push(Prerequisite::WasmStrip);
+    }
+    missing
+}
+
+/// Runs the command installing `prerequisite`, returning its stderr if it fails.
+pub(super) fn fix(prerequisite: &Prerequisite) -> Result<(), String> {
+    let command = prerequisite.fix_command().ok_or_else(|| {
+        format!(
+            "it can't be installed automatically: {}",
+            prerequisite.install_hint()
+        )
+    })?;
+    let output = Command::new(&command[0])
+        .args(&command[1..])
+        .output()
+        .map_err(|error| format!("failed to run {}: {}", command[0], error))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Returns `true` if the Wasm target is installed for `toolchain`.
+fn target_installed(toolchain: &str) -> bool {
+    Command::new("rustup")
+        .args(["target", "list", "--installed", "--toolchain", toolchain])
+        .output()
+        .map(|output| {
+            output.status.success()
+                && String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .any(|line| line.trim() == WASM_TARGET)
+        })
+        .unwrap_or(false)
+}
+
+/// Returns `true` if `command` can be run and exits successfully.
+fn succeeds(command: &[&str]) -> bool {
+    Command::new(command[0])
+        .args(&command[1..])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
@@ -0,0 +1,72 @@
+use std::process::Command;
+
+use casper_types::U512;
+use serde_json::Value;
+
+/// CoinGecko's public endpoint for the CSPR price in US dollars and euros.
+pub(super) const COINGECKO_PRICE_URL: &str =
+    "https://api.coingecko.com/api/v3/simple/price?ids=casper-network&vs_currencies=usd,eur";
+
+/// CoinGecko's id for CSPR, under which the prices are nested in its response.
+const COINGECKO_ID: &str = "casper-network";
+
+/// The number of motes in one CSPR.
+const MOTES_PER_CSPR: f64 = 1_000_000_000.0;
+
+/// How long, in seconds, the price feed may take to respond.
+const TIMEOUT_SECS: &str = "10";
+
+/// The price of one CSPR in US dollars and euros.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CsprPrice {
+    pub usd: f64,
+    pub eur: f64,
+}
+
+impl CsprPrice {
+    /// Returns the value of the given amount of motes in both currencies, e.g.
+    /// `12.34 USD / 11.20 EUR`.
+    pub fn fiat_value(&self, motes: U512) -> String {
+        let cspr = motes.to_string().parse::<f64>().unwrap_or(f64::MAX) / MOTES_PER_CSPR;
+        format!("{:.2} USD / {:.2} EUR", cspr * self.usd, cspr * self.eur)
+    }
+}
+
+/// Fetches the CSPR price from `url` using `curl`.
+///
+/// The response must be JSON in the form of CoinGecko's, i.e. `{"casper-network": {"usd": 0.03,
+/// "eur": 0.028}}`, or just the inner object, so a configurable endpoint can serve either.
+pub(super) fn fetch(url: &str) -> Result<CsprPrice, String> {
+    let output = Command::new("curl")
+        .args(&[
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            TIMEOUT_SECS,
+        ])
+        .arg(url)
+        .output()
+        .map_err(|error| format!("failed to run curl: {}", error))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let response = serde_json::from_slice::<Value>(&output.stdout)
+        .map_err(|error| format!("failed to parse the response: {}", error))?;
+
+    let prices = if response[COINGECKO_ID].is_object() {
+        &response[COINGECKO_ID]
+    } else {
+        &response
+    };
+    let price = |currency: &str| {
+        prices[currency]
+            .as_f64()
+            .filter(|price| price.is_finite() && *price >= 0.0)
+            .ok_or_else(|| format!("the response has no valid {} price", currency))
+    };
+    Ok(CsprPrice {
+        usd: price("usd")?,
+        eur: price("eur")?,
+    })
+}
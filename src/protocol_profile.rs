@@ -0,0 +1,165 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::{
+    codegen_target::CodegenTarget,
+    dependencies::{DependencySource, DEFAULT_TOOLCHAIN},
+    rpc,
+    smart_contract::SmartContract,
+    Error,
+};
+
+/// The field of a node's `info_get_status` response holding its protocol version.
+const API_VERSION_FIELD: &str = "api_version";
+
+/// The SDK versions, toolchain and code generation target suited to a range of Casper protocol
+/// versions.
+///
+/// Contracts built with a newer SDK than the network supports can call host functions it doesn't
+/// provide, which only shows up as an on-chain failure, so the SDK is pinned to the network's
+/// release.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProtocolProfile {
+    /// Protocol versions before 1.4.
+    V1_2,
+    /// Protocol version 1.4.
+    V1_4,
+    /// Protocol version 1.5.
+    V1_5,
+    /// Protocol version 2.0 (Condor) and later 2.x versions.
+    V2_0,
+}
+
+impl ProtocolProfile {
+    /// All the profiles, oldest first.
+    pub const ALL: [ProtocolProfile; 4] = [
+        ProtocolProfile::V1_2,
+        ProtocolProfile::V1_4,
+        ProtocolProfile::V1_5,
+        ProtocolProfile::V2_0,
+    ];
+
+    /// Returns the profile for the given protocol version, e.g. `1.4.15`.
+    pub fn for_protocol_version(version: &str) -> Result<Self, Error> {
+        let make_error = |inner: &str| Error::UnsupportedProtocolVersion {
+            version: version.to_string(),
+            inner: inner.to_string(),
+        };
+
+        let mut parts = version.trim().split('.').map(str::parse::<u32>);
+        let (major, minor) = match (parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor))) => (major, minor),
+            _ => return Err(make_error("expected a version like 1.5.2")),
+        };
+        match (major, minor) {
+            (1, 0..=3) => Ok(ProtocolProfile::V1_2),
+            (1, 4) => Ok(ProtocolProfile::V1_4),
+            (1, _) => Ok(ProtocolProfile::V1_5),
+            (2, _) => Ok(ProtocolProfile::V2_0),
+            _ => Err(make_error("only versions 1.x and 2.x are supported")),
+        }
+    }
+
+    /// Returns the version requirement for the `casper-contract` dependency.
+    pub fn casper_contract_dependency(&self) -> DependencySource {
+        match self {
+            ProtocolProfile::V1_2 => DependencySource::Version("~1.2".to_string()),
+            ProtocolProfile::V1_4 => DependencySource::Version("~1.4".to_string()),
+            ProtocolProfile::V1_5 => DependencySource::Version("3".to_string()),
+            ProtocolProfile::V2_0 => self.codegen_target().default_dependency(),
+        }
+    }
+
+    /// Returns the version requirement for the `casper-types` dependency.
+    pub fn casper_types_dependency(&self) -> DependencySource {
+        // The two crates are released in lockstep.
+        self.casper_contract_dependency()
+    }
+
+    /// Returns the version requirement for the `casper-engine-test-support` dependency of the test
+    /// crate, or `None` if no test crate is generated for this profile.
+    pub fn casper_engine_test_support_dependency(&self) -> Option<DependencySource> {
+        let version = match self {
+            ProtocolProfile::V1_2 => "~1.2",
+            ProtocolProfile::V1_4 => "2",
+            ProtocolProfile::V1_5 => "5",
+            ProtocolProfile::V2_0 => return None,
+        };
+        Some(DependencySource::Version(version.to_string()))
+    }
+
+    /// Returns the toolchain.
+    pub fn toolchain(&self) -> &'static str {
+        match self {
+            ProtocolProfile::V1_2 => DEFAULT_TOOLCHAIN,
+            ProtocolProfile::V1_4 => "nightly-2021-06-17",
+            ProtocolProfile::V1_5 => "nightly-2022-08-03",
+            ProtocolProfile::V2_0 => self.codegen_target().default_toolchain(),
+        }
+    }
+
+    /// Returns the code generation target.
+    pub fn codegen_target(&self) -> CodegenTarget {
+        match self {
+            ProtocolProfile::V1_2 | ProtocolProfile::V1_4 | ProtocolProfile::V1_5 => {
+                CodegenTarget::Casper1
+            }
+            ProtocolProfile::V2_0 => CodegenTarget::Casper2,
+        }
+    }
+
+    /// Sets the SDK versions, toolchain and code generation target of `smart_contract`.
+    pub(super) fn apply(&self, smart_contract: &mut SmartContract) {
+        smart_contract.casper_contract_dependency = self.casper_contract_dependency();
+        smart_contract.casper_types_dependency = self.casper_types_dependency();
+        if let Some(test_support) = self.casper_engine_test_support_dependency() {
+            smart_contract.casper_engine_test_support_dependency = test_support;
+        }
+        smart_contract.toolchain = self.toolchain().to_string();
+        smart_contract.codegen_target = self.codegen_target();
+    }
+
+    /// Returns `true` if `smart_contract` uses this profile's settings.
+    pub(super) fn is_applied_to(&self, smart_contract: &SmartContract) -> bool {
+        smart_contract.casper_contract_dependency == self.casper_contract_dependency()
+            && smart_contract.casper_types_dependency == self.casper_types_dependency()
+            && self
+                .casper_engine_test_support_dependency()
+                .is_none_or(|test_support| {
+                    smart_contract.casper_engine_test_support_dependency == test_support
+                })
+            && smart_contract.toolchain == self.toolchain()
+            && smart_contract.codegen_target == self.codegen_target()
+    }
+}
+
+impl Display for ProtocolProfile {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            ProtocolProfile::V1_2 => write!(formatter, "1.3 or earlier"),
+            ProtocolProfile::V1_4 => write!(formatter, "1.4"),
+            ProtocolProfile::V1_5 => write!(formatter, "1.5"),
+            ProtocolProfile::V2_0 => write!(formatter, "2.x"),
+        }
+    }
+}
+
+/// Returns the protocol version of the network the given node is on, as reported by its
+/// `info_get_status` JSON-RPC endpoint.
+///
+/// Uses `casper-client get-node-status`, so `casper-client` must be installed.
+pub(super) fn detect_protocol_version(node_address: &str) -> Result<String, Error> {
+    let make_error = |inner: String| Error::DetectProtocolVersion {
+        node_address: node_address.to_string(),
+        inner,
+    };
+
+    let status = rpc::call(
+        rpc::primary_node_address(node_address),
+        &["get-node-status"],
+    )
+    .map_err(make_error)?;
+    status[API_VERSION_FIELD]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| make_error(format!("no {} in the node's status", API_VERSION_FIELD)))
+}
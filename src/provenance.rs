@@ -0,0 +1,331 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use casper_node::crypto::hash;
+use casper_types::account::AccountHash;
+
+use super::{cargo_casper, Error};
+
+/// The first line of the provenance header.
+const HEADER_START: &str =
+    "// Generated by the Casper Multisig Account Creation Tool.  Do not edit.";
+
+const TOOL_VERSION_FIELD: &str = "tool-version";
+const GENERATED_AT_FIELD: &str = "generated-at";
+const CONFIG_HASH_FIELD: &str = "config-hash";
+const SOURCE_HASH_FIELD: &str = "source-hash";
+const KEY_FIELD: &str = "key";
+
+/// The provenance recorded in the header of a generated session code source.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Provenance {
+    /// The version of the tool which generated the source.
+    pub tool_version: String,
+    /// When the source was generated, as a UTC timestamp in RFC 3339 format.
+    pub generated_at: String,
+    /// The hex-encoded Blake2b hash of the configuration from which the source was generated.
+    pub config_hash: String,
+    /// The hex-encoded Blake2b hash of the source following the header.
+    pub source_hash: String,
+    /// The formatted account hashes referenced by the configuration, each with its label if it
+    /// was given one.
+    pub keys: Vec<(String, Option<String>)>,
+}
+
+/// Returns `body` prefixed with a provenance header recording the tool version, the current time,
+/// the hash of `config_summary` and of `body`, and the given keys with their labels.
+pub(super) fn with_header(
+    body: &str,
+    config_summary: &str,
+    keys: &[(String, Option<String>)],
+) -> String {
+    let mut header = format!(
+        "{}\n// {}: {}\n// {}: {}\n// {}: {:x}\n// {}: {:x}\n",
+        HEADER_START,
+        TOOL_VERSION_FIELD,
+        env!("CARGO_PKG_VERSION"),
+        GENERATED_AT_FIELD,
+        utc_timestamp(SystemTime::now()),
+        CONFIG_HASH_FIELD,
+        hash::hash(config_summary.as_bytes()),
+        SOURCE_HASH_FIELD,
+        hash::hash(body.as_bytes()),
+    );
+    for (account_hash, label) in keys {
+        header = match label {
+            Some(label) => format!("{}// {}: {} {}\n", header, KEY_FIELD, account_hash, label),
+            None => format!("{}// {}: {}\n", header, KEY_FIELD, account_hash),
+        };
+    }
+    format!("{}\n{}", header, body)
+}
+
+/// Returns `true` if `old` and `new` differ only in their generation timestamps.
+///
+/// Used to avoid rewriting an unchanged source just to update the timestamp, which would force
+/// cargo to rebuild it.
+pub(super) fn same_apart_from_timestamp(old: &str, new: &str) -> bool {
+    let generated_at_prefix = format!("// {}: ", GENERATED_AT_FIELD);
+    let strip = |contents: &str| -> Vec<String> {
+        contents
+            .lines()
+            .filter(|line| !line.starts_with(&generated_at_prefix))
+            .map(str::to_string)
+            .collect()
+    };
+    strip(old) == strip(new)
+}
+
+/// Parses the provenance header of the given source, checking the recorded source hash matches
+/// the rest of the file.
+///
+/// `file` is only used in error messages.
+pub(super) fn parse_and_verify(file: &str, contents: &str) -> Result<Provenance, Error> {
+    let make_error = |inner: String| Error::InvalidProvenance {
+        file: file.to_string(),
+        inner,
+    };
+
+    // A checkout with CRLF line endings, e.g. with git's `core.autocrlf`, must still verify.
+    let contents = contents.replace("\r\n", "\n");
+    let (header, body) = match contents.split_once("\n\n") {
+        Some((header, body)) if header.starts_with(HEADER_START) => (header, body),
+        _ => return Err(make_error("no provenance header found".to_string())),
+    };
+
+    let mut tool_version = None;
+    let mut generated_at = None;
+    let mut config_hash = None;
+    let mut source_hash = None;
+    let mut keys = vec![];
+    for line in header.lines().skip(1) {
+        let (field, value) = line
+            .strip_prefix("// ")
+            .and_then(|line| line.split_once(": "))
+            .ok_or_else(|| make_error(format!("malformed header line '{}'", line)))?;
+        let value = value.to_string();
+        match field {
+            TOOL_VERSION_FIELD => tool_version = Some(value),
+            GENERATED_AT_FIELD => generated_at = Some(value),
+            CONFIG_HASH_FIELD => config_hash = Some(value),
+            SOURCE_HASH_FIELD => source_hash = Some(value),
+            KEY_FIELD => keys.push(match value.split_once(' ') {
+                Some((account_hash, label)) => (account_hash.to_string(), Some(label.to_string())),
+                None => (value, None),
+            }),
+            _ => return Err(make_error(format!("unknown header field '{}'", field))),
+        }
+    }
+    let missing = |field: &str| make_error(format!("the header has no {}", field));
+    let provenance = Provenance {
+        tool_version: tool_version.ok_or_else(|| missing(TOOL_VERSION_FIELD))?,
+        generated_at: generated_at.ok_or_else(|| missing(GENERATED_AT_FIELD))?,
+        config_hash: config_hash.ok_or_else(|| missing(CONFIG_HASH_FIELD))?,
+        source_hash: source_hash.ok_or_else(|| missing(SOURCE_HASH_FIELD))?,
+        keys,
+    };
+
+    let actual_source_hash = format!("{:x}", hash::hash(body.as_bytes()));
+    if provenance.source_hash != actual_source_hash {
+        return Err(make_error(format!(
+            "the source has been modified: its hash is {} but the header records {}",
+            actual_source_hash, provenance.source_hash
+        )));
+    }
+    Ok(provenance)
+}
+
+/// Parses and verifies the provenance headers of every session code source in the project at
+/// `project_dir`, i.e. `src/main.rs` and any in `src/bin`, checking they were all generated from
+/// the same configuration.
+///
+/// Returns the provenance of `src/main.rs`.
+pub(super) fn verify_project(project_dir: &Path) -> Result<Provenance, Error> {
+    let paths = session_source_paths(project_dir);
+    let mut provenances = vec![];
+    for path in &paths {
+        let file = path.display().to_string();
+        let contents = fs::read_to_string(path).map_err(|error| Error::InvalidProvenance {
+            file: file.clone(),
+            inner: error.to_string(),
+        })?;
+        provenances.push((file.clone(), parse_and_verify(&file, &contents)?));
+    }
+
+    let (_, main_provenance) = &provenances[0];
+    for (file, provenance) in &provenances[1..] {
+        if provenance.config_hash != main_provenance.config_hash {
+            return Err(Error::InvalidProvenance {
+                file: file.clone(),
+                inner: "generated from a different configuration than src/main.rs".to_string(),
+            });
+        }
+    }
+    Ok(provenances.swap_remove(0).1)
+}
+
+/// Returns the dir of the contract crate in the project at `project_dir`.
+pub(super) fn contract_dir(project_dir: &Path) -> PathBuf {
+    // A project scaffolded by cargo-casper has the contract crate in a subdirectory.
+    let scaffolded_contract_dir = project_dir.join(cargo_casper::CONTRACT_DIR);
+    if scaffolded_contract_dir.join("Cargo.toml").exists() {
+        scaffolded_contract_dir
+    } else {
+        project_dir.to_path_buf()
+    }
+}
+
+/// Returns the paths of the session code sources in the project at `project_dir`: `src/main.rs`
+/// followed by any in `src/bin`, sorted.
+pub(super) fn session_source_paths(project_dir: &Path) -> Vec<PathBuf> {
+    let src_dir = contract_dir(project_dir).join("src");
+    let mut paths = vec![src_dir.join("main.rs")];
+    if let Ok(entries) = fs::read_dir(src_dir.join("bin")) {
+        let mut bin_paths = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+            .collect::<Vec<_>>();
+        bin_paths.sort();
+        paths.extend(bin_paths);
+    }
+    paths
+}
+
+/// Returns `time` as a UTC timestamp in RFC 3339 format, e.g. `2021-06-01T12:34:56Z`.
+fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let days = (seconds / 86_400) as i64;
+    let seconds_of_day = seconds % 86_400;
+
+    // Converts days since the Unix epoch to a civil date in the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Parses the given `(formatted account hash, label)` pairs.
+///
+/// Labels must be non-empty and on a single line, and each account hash may only be labelled once.
+pub(super) fn parse_key_labels(
+    key_labels: &[(String, String)],
+) -> Result<Vec<(AccountHash, String)>, Error> {
+    let mut parsed: Vec<(AccountHash, String)> = vec![];
+    for (formatted_account_hash, label) in key_labels {
+        let make_error = |inner: String| Error::InvalidKeyLabel {
+            account_hash: formatted_account_hash.clone(),
+            inner,
+        };
+        let account_hash = AccountHash::from_formatted_str(formatted_account_hash.trim())
+            .map_err(|error| make_error(format!("failed to parse account hash: {}", error)))?;
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(make_error("the label must not be empty".to_string()));
+        }
+        if label.contains('\n') {
+            return Err(make_error("the label must be a single line".to_string()));
+        }
+        if parsed.iter().any(|(labelled, _)| *labelled == account_hash) {
+            return Err(make_error("the key is labelled more than once".to_string()));
+        }
+        parsed.push((account_hash, label.to_string()));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const BODY: &str = "#![no_std]\n\nfn call() {}\n";
+
+    fn keys() -> Vec<(String, Option<String>)> {
+        vec![
+            (
+                "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb"
+                    .to_string(),
+                Some("Alice's laptop".to_string()),
+            ),
+            (
+                "account-hash-a9efd010c7cee2245b5bad77e70d9beb73c8776cbe4698b2d8fdf6c8433d5ba0"
+                    .to_string(),
+                None,
+            ),
+        ]
+    }
+
+    fn timestamp_at(seconds: u64) -> String {
+        utc_timestamp(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn header_round_trips() {
+        let contents = with_header(BODY, "config summary", &keys());
+        let provenance = parse_and_verify("main.rs", &contents).unwrap();
+
+        assert_eq!(provenance.tool_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            provenance.config_hash,
+            format!("{:x}", hash::hash("config summary".as_bytes()))
+        );
+        assert_eq!(
+            provenance.source_hash,
+            format!("{:x}", hash::hash(BODY.as_bytes()))
+        );
+        assert_eq!(provenance.keys, keys());
+        assert!(contents.ends_with(BODY));
+    }
+
+    #[test]
+    fn modified_body_is_rejected() {
+        let contents = with_header(BODY, "config summary", &keys()).replace("call", "cal");
+        let error = parse_and_verify("main.rs", &contents).unwrap_err();
+        assert!(error.to_string().contains("the source has been modified"));
+    }
+
+    #[test]
+    fn crlf_line_endings_verify() {
+        let contents = with_header(BODY, "config summary", &keys()).replace('\n', "\r\n");
+        let provenance = parse_and_verify("main.rs", &contents).unwrap();
+        assert_eq!(provenance.keys, keys());
+    }
+
+    #[test]
+    fn utc_timestamp_known_answers() {
+        assert_eq!(timestamp_at(0), "1970-01-01T00:00:00Z");
+        // A leap day in a year divisible by 400.
+        assert_eq!(timestamp_at(951_827_696), "2000-02-29T12:34:56Z");
+        // 2100 isn't a leap year, so the day after 28 February is 1 March.
+        assert_eq!(timestamp_at(4_107_456_000), "2100-02-28T00:00:00Z");
+        assert_eq!(timestamp_at(4_107_628_799), "2100-03-01T23:59:59Z");
+    }
+}
@@ -0,0 +1,626 @@
+/// The highest version, i.e. size, of QR code which can be encoded.  Version 10 holds 213 bytes,
+/// plenty for a formatted account hash, public key or deploy hash.
+const MAX_VERSION: usize = 10;
+
+/// The number of error correction codewords in each block, by version, at the medium level.
+const ECC_CODEWORDS_PER_BLOCK: [usize; MAX_VERSION + 1] =
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26];
+
+/// The number of error correction blocks, by version, at the medium level.
+const ECC_BLOCKS: [usize; MAX_VERSION + 1] = [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5];
+
+/// The bits of the format information identifying the medium error correction level.
+const MEDIUM_LEVEL_BITS: u32 = 0b00;
+
+/// The width of the blank margin around the code, in modules, as required by the spec.
+const QUIET_ZONE: usize = 4;
+
+const DARK: [u8; 3] = [0x00, 0x00, 0x00];
+const LIGHT: [u8; 3] = [0xff, 0xff, 0xff];
+
+/// A QR code encoding a short text, so that it can be scanned by a phone or hardware wallet rather
+/// than retyped.
+///
+/// The text is encoded in byte mode at the medium error correction level, which recovers from
+/// about 15% of the code being damaged or obscured, in the smallest version it fits.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct QrCode {
+    /// Whether each module is dark, by row then column.
+    pub modules: Vec<Vec<bool>>,
+}
+
+impl QrCode {
+    pub(super) fn new(text: &str) -> Result<Self, String> {
+        let data = text.as_bytes();
+        let version = (1..=MAX_VERSION)
+            .find(|&version| {
+                4 + count_bits(version) + data.len() * 8 <= data_codewords(version) * 8
+            })
+            .ok_or_else(|| {
+                format!(
+                    "{} bytes is too long for a QR code of version {} or lower",
+                    data.len(),
+                    MAX_VERSION
+                )
+            })?;
+
+        let mut builder = Builder::new(version);
+        builder.draw_function_patterns();
+        let codewords = add_error_correction(version, &encode_data(version, data));
+        builder.draw_codewords(&codewords);
+
+        // Use the mask which leaves the fewest patterns which are hard to scan.
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                builder.apply_mask(mask);
+                builder.draw_format_bits(mask);
+                let penalty = builder.penalty();
+                builder.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or_default();
+        builder.apply_mask(mask);
+        builder.draw_format_bits(mask);
+
+        Ok(QrCode {
+            modules: builder.modules,
+        })
+    }
+
+    /// Returns the width and height in pixels of the code drawn with `module_size` pixel modules,
+    /// including its quiet zone.
+    pub fn size(&self, module_size: usize) -> usize {
+        (self.modules.len() + 2 * QUIET_ZONE) * module_size
+    }
+
+    /// Returns the code as RGB pixel data, row by row, with `module_size` pixel modules.
+    ///
+    /// It's always dark on light whatever the theme, as not every scanner reads inverted codes.
+    pub fn to_rgb(&self, module_size: usize) -> Vec<u8> {
+        let size = self.size(module_size);
+        let mut pixels = Vec::with_capacity(size * size * 3);
+        for y in 0..size {
+            for x in 0..size {
+                let row = (y / module_size).checked_sub(QUIET_ZONE);
+                let column = (x / module_size).checked_sub(QUIET_ZONE);
+                let dark = match (row, column) {
+                    (Some(row), Some(column)) => self
+                        .modules
+                        .get(row)
+                        .and_then(|modules| modules.get(column))
+                        .copied()
+                        .unwrap_or(false),
+                    _ => false,
+                };
+                pixels.extend_from_slice(if dark { &DARK } else { &LIGHT });
+            }
+        }
+        pixels
+    }
+}
+
+/// The modules of a code being drawn, along with which of them belong to the function patterns,
+/// i.e. the finders, timing, alignment, format and version patterns, so aren't masked.
+struct Builder {
+    version: usize,
+    modules: Vec<Vec<bool>>,
+    is_function: Vec<Vec<bool>>,
+}
+
+impl Builder {
+    fn new(version: usize) -> Self {
+        let size = version * 4 + 17;
+        Builder {
+            version,
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.modules.len()
+    }
+
+    fn set_function_module(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.is_function[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size();
+        for index in 0..size {
+            self.set_function_module(6, index, index % 2 == 0);
+            self.set_function_module(index, 6, index % 2 == 0);
+        }
+
+        self.draw_finder_pattern(3, 3);
+        self.draw_finder_pattern(size - 4, 3);
+        self.draw_finder_pattern(3, size - 4);
+
+        let positions = alignment_pattern_positions(self.version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // Those at the corners would overlap the finder patterns.
+                if (i == 0 && j == 0) || (i == 0 && j == last) || (i == last && j == 0) {
+                    continue;
+                }
+                self.draw_alignment_pattern(x, y);
+            }
+        }
+
+        // Reserve the format bits' modules, which are drawn once the mask is chosen.
+        self.draw_format_bits(0);
+        self.draw_version_bits();
+    }
+
+    /// Draws a finder pattern, along with its separator, centred on `x` and `y`.
+    fn draw_finder_pattern(&mut self, x: usize, y: usize) {
+        let size = self.size() as isize;
+        for dy in -4_isize..=4 {
+            for dx in -4_isize..=4 {
+                let (module_x, module_y) = (x as isize + dx, y as isize + dy);
+                if module_x < 0 || module_x >= size || module_y < 0 || module_y >= size {
+                    continue;
+                }
+                let distance = dx.abs().max(dy.abs());
+                self.set_function_module(
+                    module_x as usize,
+                    module_y as usize,
+                    distance != 2 && distance != 4,
+                );
+            }
+        }
+    }
+
+    /// Draws an alignment pattern centred on `x` and `y`.
+    fn draw_alignment_pattern(&mut self, x: usize, y: usize) {
+        for dy in -2_isize..=2 {
+            for dx in -2_isize..=2 {
+                self.set_function_module(
+                    (x as isize + dx) as usize,
+                    (y as isize + dy) as usize,
+                    dx.abs().max(dy.abs()) != 1,
+                );
+            }
+        }
+    }
+
+    /// Draws both copies of the format information for `mask`, along with the dark module.
+    fn draw_format_bits(&mut self, mask: u32) {
+        let bits = format_bits(mask);
+        let bit = |index: usize| (bits >> index) & 1 != 0;
+
+        let size = self.size();
+        for index in 0..6 {
+            self.set_function_module(8, index, bit(index));
+        }
+        self.set_function_module(8, 7, bit(6));
+        self.set_function_module(8, 8, bit(7));
+        self.set_function_module(7, 8, bit(8));
+        for index in 9..15 {
+            self.set_function_module(14 - index, 8, bit(index));
+        }
+
+        for index in 0..8 {
+            self.set_function_module(size - 1 - index, 8, bit(index));
+        }
+        for index in 8..15 {
+            self.set_function_module(8, size - 15 + index, bit(index));
+        }
+        self.set_function_module(8, size - 8, true);
+    }
+
+    /// Draws both copies of the version information, which only versions 7 and up have.
+    fn draw_version_bits(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let bits = version_bits(self.version);
+        let size = self.size();
+        for index in 0..18 {
+            let dark = (bits >> index) & 1 != 0;
+            let (a, b) = (size - 11 + index % 3, index / 3);
+            self.set_function_module(a, b, dark);
+            self.set_function_module(b, a, dark);
+        }
+    }
+
+    /// Draws the codewords in the zigzag order of the spec: up and down pairs of columns from the
+    /// right, skipping the function patterns.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size();
+        let total_bits = codewords.len() * 8;
+        let mut bit_index = 0;
+        let mut right = size - 1;
+        loop {
+            // The vertical timing pattern's column is skipped.
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                for offset in 0..2 {
+                    let x = right - offset;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.is_function[y][x] && bit_index < total_bits {
+                        self.modules[y][x] =
+                            (codewords[bit_index / 8] >> (7 - bit_index % 8)) & 1 != 0;
+                        bit_index += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Inverts the modules outside the function patterns selected by `mask`.  Applying the same
+    /// mask again undoes it.
+    fn apply_mask(&mut self, mask: u32) {
+        let size = self.size();
+        for y in 0..size {
+            for x in 0..size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.is_function[y][x] {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    /// Returns the spec's penalty score for the modules: the higher it is, the more they contain
+    /// long runs, blocks or finder-like patterns, or are unbalanced between dark and light.
+    fn penalty(&self) -> usize {
+        let size = self.size();
+        let columns: Vec<Vec<bool>> = (0..size)
+            .map(|x| (0..size).map(|y| self.modules[y][x]).collect())
+            .collect();
+        let finder_like: [&[bool]; 2] = [
+            &[
+                true, false, true, true, true, false, true, false, false, false, false,
+            ],
+            &[
+                false, false, false, false, true, false, true, true, true, false, true,
+            ],
+        ];
+
+        let mut penalty = 0;
+        for line in self.modules.iter().chain(columns.iter()) {
+            let mut run_length = 1;
+            for index in 1..=size {
+                if index < size && line[index] == line[index - 1] {
+                    run_length += 1;
+                    continue;
+                }
+                if run_length >= 5 {
+                    penalty += run_length - 2;
+                }
+                run_length = 1;
+            }
+            penalty += line
+                .windows(11)
+                .filter(|window| finder_like.contains(window))
+                .count()
+                * 40;
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.modules[y][x];
+                if self.modules[y][x + 1] == color
+                    && self.modules[y + 1][x] == color
+                    && self.modules[y + 1][x + 1] == color
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.modules.iter().flatten().filter(|dark| **dark).count();
+        let imbalance = (dark * 20).abs_diff(total * 10);
+        penalty + imbalance.div_ceil(total).saturating_sub(1) * 10
+    }
+}
+
+/// Returns the 15 bits of the format information for `mask` at the medium level, i.e. the BCH
+/// code of the level and mask, masked so they're never all light.
+fn format_bits(mask: u32) -> u32 {
+    let data = (MEDIUM_LEVEL_BITS << 3) | mask;
+    let mut remainder = data;
+    for _ in 0..10 {
+        remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+    }
+    ((data << 10) | remainder) ^ 0x5412
+}
+
+/// Returns the 18 bits of the version information for `version`, i.e. its Golay code.
+fn version_bits(version: usize) -> u32 {
+    let mut remainder = version as u32;
+    for _ in 0..12 {
+        remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+    }
+    ((version as u32) << 12) | remainder
+}
+
+/// Returns the number of bits of the byte count at the start of the data for `version`.
+fn count_bits(version: usize) -> usize {
+    if version < 10 {
+        8
+    } else {
+        16
+    }
+}
+
+/// Returns the number of modules available for the codewords in `version`, i.e. those outside the
+/// function patterns.
+fn raw_data_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignment_count = version / 7 + 2;
+        modules -= (25 * alignment_count - 10) * alignment_count - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+/// Returns the number of data codewords, i.e. those other than for error correction, in `version`.
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8 - ECC_CODEWORDS_PER_BLOCK[version] * ECC_BLOCKS[version]
+}
+
+/// Returns the centres of the alignment patterns along each axis for `version`.
+fn alignment_pattern_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return vec![];
+    }
+    let count = version / 7 + 2;
+    let size = version * 4 + 17;
+    let step = (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2;
+    let mut positions: Vec<usize> = (0..count - 1)
+        .map(|index| size - 7 - index * step)
+        .collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// Returns `data` encoded as the byte mode segment, followed by the terminator and padding which
+/// fill the data codewords of `version`.
+fn encode_data(version: usize, data: &[u8]) -> Vec<u8> {
+    let capacity_bits = data_codewords(version) * 8;
+    let mut bits = Vec::with_capacity(capacity_bits);
+    let mut push_bits = |value: usize, count: usize| {
+        for index in (0..count).rev() {
+            bits.push((value >> index) & 1 != 0);
+        }
+    };
+    push_bits(0b0100, 4);
+    push_bits(data.len(), count_bits(version));
+    for byte in data {
+        push_bits(usize::from(*byte), 8);
+    }
+
+    let terminator_length = (capacity_bits - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator_length));
+    while bits.len() % 8 != 0 {
+        bits.push(false);
+    }
+
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, bit| (acc << 1) | u8::from(*bit)))
+        .collect();
+    for pad in [0xec, 0x11].iter().cycle() {
+        if codewords.len() >= capacity_bits / 8 {
+            break;
+        }
+        codewords.push(*pad);
+    }
+    codewords
+}
+
+/// Returns the data codewords split into blocks, each followed by its Reed-Solomon error
+/// correction codewords, then interleaved as the spec requires.
+fn add_error_correction(version: usize, data: &[u8]) -> Vec<u8> {
+    let block_count = ECC_BLOCKS[version];
+    let ecc_length = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_block_count = block_count - raw_codewords % block_count;
+    let short_block_length = raw_codewords / block_count;
+    let divisor = reed_solomon_divisor(ecc_length);
+
+    let mut blocks = vec![];
+    let mut start = 0;
+    for index in 0..block_count {
+        let data_length = short_block_length - ecc_length + usize::from(index >= short_block_count);
+        let mut block = data[start..start + data_length].to_vec();
+        start += data_length;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        // Short blocks are padded so every block's codewords line up when interleaved.
+        if index < short_block_count {
+            block.push(0);
+        }
+        block.extend(ecc);
+        blocks.push(block);
+    }
+
+    let mut codewords = Vec::with_capacity(raw_codewords);
+    for index in 0..=short_block_length {
+        for (block_index, block) in blocks.iter().enumerate() {
+            if index != short_block_length - ecc_length || block_index >= short_block_count {
+                codewords.push(block[index]);
+            }
+        }
+    }
+    codewords
+}
+
+/// Returns the coefficients of the Reed-Solomon generator polynomial of `degree`, highest power
+/// first, excluding the leading 1.
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut divisor = vec![0; degree];
+    divisor[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for index in 0..degree {
+            divisor[index] = gf_multiply(divisor[index], root);
+            if index + 1 < degree {
+                divisor[index] ^= divisor[index + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    divisor
+}
+
+/// Returns the error correction codewords of `data`, i.e. the remainder of dividing it by
+/// `divisor`.
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0; divisor.len()];
+    for byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (value, coefficient) in remainder.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(*coefficient, factor);
+        }
+    }
+    remainder
+}
+
+/// Multiplies two elements of GF(2^8) modulo the QR code polynomial x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut product: u16 = 0;
+    for index in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11d);
+        product ^= u16::from((y >> index) & 1) * u16::from(x);
+    }
+    product as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the rows of `code`, with '#' for dark modules and '.' for light.
+    fn rows(code: &QrCode) -> Vec<String> {
+        code.modules
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|dark| if *dark { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn known_answer() {
+        // A version 1 code at the medium level, drawn with mask 0.
+        let expected = [
+            "#######...#.#.#######",
+            "#.....#.#.#...#.....#",
+            "#.###.#..#.##.#.###.#",
+            "#.###.#..#.#..#.###.#",
+            "#.###.#.#####.#.###.#",
+            "#.....#..###..#.....#",
+            "#######.#.#.#.#######",
+            ".........#...........",
+            "#.#.#.#.....#...#..#.",
+            ".#.#...#####.#.#.#.##",
+            "##..#.###..#.###.####",
+            "##.#...#######.##...#",
+            "..#..##..###.###.#..#",
+            "........##....##.#.##",
+            "#######...#.#...##.##",
+            "#.....#..#....###..##",
+            "#.###.#.#...#.#.##.##",
+            "#.###.#..###.#..##.#.",
+            "#.###.#.#..#.####.#.#",
+            "#.....#...####.....#.",
+            "#######.#.##.####..##",
+        ];
+        assert_eq!(rows(&QrCode::new("casper").unwrap()), expected);
+    }
+
+    #[test]
+    fn published_error_correction() {
+        // The spec's "01234567" and the widely used "HELLO WORLD" examples, both version 1 at the
+        // medium level.
+        let numeric = [
+            0x10, 0x20, 0x0c, 0x56, 0x61, 0x80, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11,
+            0xec, 0x11,
+        ];
+        assert_eq!(
+            add_error_correction(1, &numeric)[16..],
+            [0xa5, 0x24, 0xd4, 0xc1, 0xed, 0x36, 0xc7, 0x87, 0x2c, 0x55]
+        );
+
+        let alphanumeric = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        assert_eq!(
+            add_error_correction(1, &alphanumeric)[16..],
+            [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+        );
+    }
+
+    #[test]
+    fn published_format_and_version_bits() {
+        let format = [
+            0b101010000010010,
+            0b101000100100101,
+            0b101111001111100,
+            0b101101101001011,
+            0b100010111111001,
+            0b100000011001110,
+            0b100111110010111,
+            0b100101010100000,
+        ];
+        for (mask, expected) in format.iter().enumerate() {
+            assert_eq!(format_bits(mask as u32), *expected, "mask {}", mask);
+        }
+
+        let version = [
+            (7, 0b000111110010010100),
+            (8, 0b001000010110111100),
+            (9, 0b001001101010011001),
+            (10, 0b001010010011010011),
+        ];
+        for (version, expected) in version.iter() {
+            assert_eq!(version_bits(*version), *expected, "version {}", version);
+        }
+    }
+
+    #[test]
+    fn smallest_version_at_capacity_boundary() {
+        // The byte mode capacities at the medium level of versions 1, 2 and 10.
+        for (length, size) in [(14, 21), (15, 25), (26, 25), (27, 29), (213, 57)].iter() {
+            let code = QrCode::new(&"a".repeat(*length)).unwrap();
+            assert_eq!(code.modules.len(), *size, "{} bytes", length);
+        }
+    }
+
+    #[test]
+    fn oversized_input_is_rejected() {
+        assert!(QrCode::new(&"a".repeat(214)).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    io::Read,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// The zbar tool which shows the webcam's video and decodes the first QR code seen in it.
+const ZBARCAM: &str = "zbarcam";
+
+/// A running scan of the webcam for a QR code, e.g. of a public key shown on a signer's phone.
+///
+/// The scan is done by zbarcam, which shows the video in its own window so the code can be held
+/// in view.  Dropping the scan closes that window.
+pub struct QrScan {
+    child: Child,
+    receiver: Receiver<Result<String, String>>,
+}
+
+impl QrScan {
+    /// Starts zbarcam, scanning only for QR codes and exiting after the first.
+    pub(super) fn start() -> Result<Self, String> {
+        let mut child = Command::new(ZBARCAM)
+            .args(["--raw", "--oneshot", "-Sdisable", "-Sqrcode.enable"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                format!(
+                    "failed to run {}: {}.  Install zbar, e.g. with `sudo apt install \
+                    zbar-tools`, to scan QR codes with the webcam.",
+                    ZBARCAM, error
+                )
+            })?;
+
+        let mut stdout = child.stdout.take();
+        let mut stderr = child.stderr.take();
+        // zbarcam can log a lot to stderr, e.g. about the camera's driver, so it's drained on its
+        // own thread in case it fills the pipe and blocks zbarcam before it writes the result.
+        let stderr_reader = thread::spawn(move || {
+            let mut error = String::new();
+            if let Some(stderr) = stderr.as_mut() {
+                let _ = stderr.read_to_string(&mut error);
+            }
+            error
+        });
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            // zbarcam only writes the decoded text, once it's found a code, before exiting.
+            let mut text = String::new();
+            if let Some(stdout) = stdout.as_mut() {
+                let _ = stdout.read_to_string(&mut text);
+            }
+            let error = stderr_reader.join().unwrap_or_default();
+            let result = if text.trim().is_empty() {
+                Err(match error.trim() {
+                    "" => format!("{} exited without scanning a QR code", ZBARCAM),
+                    error => format!("{} failed: {}", ZBARCAM, error),
+                })
+            } else {
+                Ok(text.trim().to_string())
+            };
+            let _ = sender.send(result);
+        });
+
+        Ok(QrScan { child, receiver })
+    }
+
+    /// Returns the text of the scanned QR code, or why no code was scanned, once the scan has
+    /// finished, or `None` while it's still running.
+    pub fn try_result(&self) -> Option<Result<String, String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(format!("{} stopped unexpectedly", ZBARCAM)))
+            }
+        }
+    }
+}
+
+impl Drop for QrScan {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
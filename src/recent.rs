@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use super::key_configuration::KeyConfiguration;
+
+/// The name of the file holding the recent items, under the tool's config dir.
+const FILE_NAME: &str = "recent.json";
+
+/// The most recent items remembered.
+const MAX_ITEMS: usize = 10;
+
+/// A configuration file or generated project recently used, which can be reopened.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RecentItem {
+    /// A configuration file which was saved or opened.
+    Configuration { file: PathBuf },
+    /// The dir of a generated project, along with the configuration it was generated from.
+    Project {
+        dir: PathBuf,
+        configuration: KeyConfiguration,
+    },
+}
+
+impl RecentItem {
+    /// Returns the configuration file or project dir.
+    pub fn path(&self) -> &Path {
+        match self {
+            RecentItem::Configuration { file } => file,
+            RecentItem::Project { dir, .. } => dir,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            RecentItem::Configuration { file } => json!({
+                "configuration_file": file.display().to_string(),
+            }),
+            RecentItem::Project { dir, configuration } => json!({
+                "project_dir": dir.display().to_string(),
+                "configuration": configuration.to_json(),
+            }),
+        }
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        if let Some(file) = value["configuration_file"].as_str() {
+            return Ok(RecentItem::Configuration {
+                file: PathBuf::from(file),
+            });
+        }
+        let dir = value["project_dir"]
+            .as_str()
+            .ok_or("an item has neither a 'configuration_file' nor a 'project_dir' string")?;
+        let configuration = KeyConfiguration::from_json(&value["configuration"])
+            .map_err(|error| format!("project {}: {}", dir, error))?;
+        Ok(RecentItem::Project {
+            dir: PathBuf::from(dir),
+            configuration,
+        })
+    }
+}
+
+/// Returns the path of the recent items file, under the user's config dir, e.g.
+/// `~/.config/casper-multisig-tool/recent.json` on Linux.
+pub(super) fn file() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join("casper-multisig-tool").join(FILE_NAME))
+}
+
+/// Reads the items in `file`, most recent first, or returns none if it doesn't exist yet.
+pub(super) fn load(file: &Path) -> Result<Vec<RecentItem>, String> {
+    if !file.exists() {
+        return Ok(vec![]);
+    }
+    let contents = fs::read_to_string(file).map_err(|error| error.to_string())?;
+    let recent = serde_json::from_str::<Value>(&contents).map_err(|error| error.to_string())?;
+    recent["items"]
+        .as_array()
+        .ok_or("it has no 'items' list")?
+        .iter()
+        .map(RecentItem::from_json)
+        .collect()
+}
+
+/// Writes the items to `file`, creating its dir if needed.
+pub(super) fn save(file: &Path, items: &[RecentItem]) -> Result<(), String> {
+    let recent = json!({
+        "items": items.iter().map(RecentItem::to_json).collect::<Vec<_>>(),
+    });
+    let contents = serde_json::to_string_pretty(&recent).map_err(|error| error.to_string())?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    fs::write(file, contents).map_err(|error| error.to_string())
+}
+
+/// Adds `item` as the most recent, replacing any earlier item of the same kind for the same path,
+/// and forgets the oldest beyond the most remembered.
+pub(super) fn insert(items: &mut Vec<RecentItem>, item: RecentItem) {
+    items.retain(|existing| {
+        std::mem::discriminant(existing) != std::mem::discriminant(&item)
+            || existing.path() != item.path()
+    });
+    items.insert(0, item);
+    items.truncate(MAX_ITEMS);
+}
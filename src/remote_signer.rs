@@ -0,0 +1,37 @@
+//! Signing with keys held by a remote service, e.g. a cloud KMS or an HSM, which never leave it.
+//!
+//! Each backend implements [`RemoteSigner`]; [`AwsKmsSigner`](super::AwsKmsSigner) is provided,
+//! and others such as HashiCorp Vault's transit engine or Google Cloud KMS can be added alongside
+//! it.
+
+use casper_node::types::{Approval, Deploy};
+use casper_types::{PublicKey, Signature};
+
+use super::{deploy, Error};
+
+/// A key held by a remote service, which signs messages without revealing the secret key.
+pub trait RemoteSigner {
+    /// Returns a short description of the key for messages, e.g. its ID.
+    fn description(&self) -> String;
+
+    /// Returns the key's public key.
+    fn public_key(&self) -> Result<PublicKey, String>;
+
+    /// Returns the key's signature of `message`, as the Casper node expects it: for secp256k1, an
+    /// ECDSA signature of the SHA-256 digest of `message`, normalized to low-S.
+    fn sign(&self, message: &[u8]) -> Result<Signature, String>;
+}
+
+/// Signs the hash of `deploy` with `signer`, appending and returning the approval.
+///
+/// The signature is checked against the deploy hash before it's added.
+pub(super) fn sign(deploy: &mut Deploy, signer: &dyn RemoteSigner) -> Result<Approval, Error> {
+    let make_error = |inner: String| Error::RemoteSigner {
+        signer: signer.description(),
+        inner,
+    };
+
+    let public_key = signer.public_key().map_err(make_error)?;
+    let signature = signer.sign(deploy.id().as_ref()).map_err(make_error)?;
+    deploy::add_signature(deploy, public_key, signature).map_err(make_error)
+}
@@ -0,0 +1,253 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use casper_node::crypto::hash;
+
+use super::test_project;
+
+/// The name of the file recording the inputs and output of a reproducible build.
+pub(super) const BUILD_MANIFEST: &str = "build-manifest.toml";
+
+/// The directory, relative to the project dir, into which dependencies are vendored.
+pub(super) const VENDOR_DIR: &str = "vendor";
+
+/// The extra args passed to `cargo build` in reproducible-build mode.
+pub(super) const BUILD_ARGS: &[&str] = &["--locked", "--offline"];
+
+/// A package entry from a `Cargo.lock`.
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+}
+
+/// Parses the `[[package]]` entries of a `Cargo.lock`.
+fn locked_packages(lockfile: &str) -> Vec<LockedPackage> {
+    let mut packages = vec![];
+    let mut current: Option<LockedPackage> = None;
+    for line in lockfile.lines().map(str::trim) {
+        if line == "[[package]]" {
+            packages.extend(current.take());
+            current = Some(LockedPackage {
+                name: String::new(),
+                version: String::new(),
+                source: None,
+                checksum: None,
+            });
+            continue;
+        }
+        if line.starts_with('[') {
+            packages.extend(current.take());
+            continue;
+        }
+        let package = match current.as_mut() {
+            Some(package) => package,
+            None => continue,
+        };
+        let mut parts = line.splitn(2, " = ");
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key, value.trim_matches('"').to_string()),
+            _ => continue,
+        };
+        match key {
+            "name" => package.name = value,
+            "version" => package.version = value,
+            "source" => package.source = Some(value),
+            "checksum" => package.checksum = Some(value),
+            _ => (),
+        }
+    }
+    packages.extend(current);
+    packages
+}
+
+/// Returns the first line of `<program> --version` run in `project_dir`, so that any
+/// `rust-toolchain` file there is honoured.
+fn version_of(program: &str, project_dir: &Path) -> String {
+    Command::new(program)
+        .arg("--version")
+        .current_dir(project_dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn file_hash(path: &Path) -> String {
+    match fs::read(path) {
+        Ok(bytes) => format!("{:x}", hash::hash(&bytes)),
+        Err(_) => "missing".to_string(),
+    }
+}
+
+/// Pins every dependency of the project at `project_dir` in its `Cargo.lock`, so the project can
+/// be committed with the exact versions it builds against.
+///
+/// If there's no `Cargo.lock`, all dependencies are resolved afresh.  Otherwise only those needed
+/// by changes to `Cargo.toml` are, and the existing pins are kept.  `cargo` is run in `project_dir`
+/// so that its `rust-toolchain` file is honoured.
+///
+/// Returns an error message if `cargo` failed.
+pub(super) fn pin_dependencies(project_dir: &Path) -> Result<(), String> {
+    let args: &[&str] = if project_dir.join("Cargo.lock").is_file() {
+        &["update", "--workspace"]
+    } else {
+        &["generate-lockfile"]
+    };
+    let output = Command::new("cargo")
+        .args(args)
+        .current_dir(project_dir)
+        .output()
+        .map_err(|error| format!("failed to run cargo: {}", error))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(format!(
+        "'cargo {}' failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// Returns the args to `cargo` which vendor the dependencies.
+///
+/// If `has_tests` is `true`, the test crate's dependencies are vendored too, since it inherits the
+/// source replacement.
+pub(super) fn vendor_args(has_tests: bool) -> Vec<String> {
+    let mut args = vec!["vendor".to_string(), "--versioned-dirs".to_string()];
+    if has_tests {
+        args.push("--sync".to_string());
+        args.push(format!("{}/Cargo.toml", test_project::TESTS_DIR));
+    }
+    args.push(VENDOR_DIR.to_string());
+    args
+}
+
+/// Runs the given `cargo vendor` command and appends the resulting source replacement to the
+/// project's `.cargo/config.toml`.
+///
+/// Returns the lines of output to be shown to the user, or an error message.
+pub(super) fn vendor_dependencies(
+    project_dir: &Path,
+    mut command: Command,
+) -> Result<Vec<String>, String> {
+    let output = command
+        .output()
+        .map_err(|error| format!("failed to run cargo vendor: {}", error))?;
+    let stderr_lines = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if !output.status.success() {
+        return Err(format!(
+            "cargo vendor failed: {}\n{}",
+            output.status,
+            stderr_lines.join("\n")
+        ));
+    }
+
+    // `cargo vendor` prints the config required to use the vendored sources to stdout.
+    let mut config = OpenOptions::new()
+        .append(true)
+        .open(project_dir.join(".cargo").join("config.toml"))
+        .map_err(|error| format!("failed to open .cargo/config.toml: {}", error))?;
+    config
+        .write_all(b"\n")
+        .and_then(|_| config.write_all(&output.stdout))
+        .map_err(|error| format!("failed to write .cargo/config.toml: {}", error))?;
+    Ok(stderr_lines)
+}
+
+/// Returns the contents of the build manifest, recording the toolchain, the hashes of the inputs
+/// and of each compiled session Wasm, and every locked dependency.
+pub(super) fn build_manifest_contents(
+    project_dir: &Path,
+    toolchain: &str,
+    source_paths: &[PathBuf],
+    wasm_relative_paths: &[PathBuf],
+    wasm_opt_used: bool,
+    wasm_strip_used: bool,
+    vendored: bool,
+) -> String {
+    let mut contents = format!(
+        r#"# Generated by the Casper Multisig Account Creation Tool.
+#
+# To verify, rebuild from this directory with `cargo build --release {build_args}` using the
+# toolchain below and check the Blake2b hash of the Wasm matches.
+
+[toolchain]
+rust-toolchain = "{toolchain}"
+rustc = "{rustc}"
+cargo = "{cargo}"
+"#,
+        build_args = BUILD_ARGS.join(" "),
+        toolchain = toolchain,
+        rustc = version_of("rustc", project_dir),
+        cargo = version_of("cargo", project_dir),
+    );
+    if wasm_opt_used {
+        contents = format!(
+            "{}wasm-opt = \"{}\"\n",
+            contents,
+            version_of("wasm-opt", project_dir)
+        );
+    }
+    if wasm_strip_used {
+        contents = format!(
+            "{}wasm-strip = \"{}\"\n",
+            contents,
+            version_of("wasm-strip", project_dir)
+        );
+    }
+
+    contents = format!("{}\n[inputs]\n", contents);
+    let manifests = [PathBuf::from("Cargo.toml"), PathBuf::from("Cargo.lock")];
+    for file in manifests.iter().chain(source_paths) {
+        contents = format!(
+            "{}\"{}\" = \"{}\"\n",
+            contents,
+            file.display(),
+            file_hash(&project_dir.join(file))
+        );
+    }
+
+    // One output per deploy of the plan, in the order they're sent.
+    for wasm_relative_path in wasm_relative_paths {
+        contents = format!(
+            "{}\n[[output]]\npath = \"{}\"\nblake2b = \"{}\"\n",
+            contents,
+            wasm_relative_path.display(),
+            file_hash(&project_dir.join(wasm_relative_path))
+        );
+    }
+
+    let lockfile = fs::read_to_string(project_dir.join("Cargo.lock")).unwrap_or_default();
+    for package in locked_packages(&lockfile) {
+        contents = format!(
+            "{}\n[[dependency]]\nname = \"{}\"\nversion = \"{}\"\n",
+            contents, package.name, package.version
+        );
+        if let Some(checksum) = package.checksum {
+            contents = format!("{}checksum = \"{}\"\n", contents, checksum);
+        }
+        // Only packages with a source are fetched and hence vendored; the rest are local.
+        if let Some(source) = package.source {
+            contents = format!("{}source = \"{}\"\n", contents, source);
+            if vendored {
+                contents = format!("{}vendored = true\n", contents);
+            }
+        }
+    }
+    contents
+}
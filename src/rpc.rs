@@ -0,0 +1,357 @@
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+
+use casper_types::{crypto::AsymmetricType, PublicKey};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+/// The connection settings of the selected network, applied to every request.
+static CONNECTION: Lazy<Mutex<ConnectionSettings>> =
+    Lazy::new(|| Mutex::new(ConnectionSettings::default()));
+
+/// The path of a node's JSON-RPC endpoint, appended to its address as casper-client does.
+const RPC_PATH: &str = "/rpc";
+
+/// Separates the addresses of several nodes in a single node address string, e.g.
+/// `http://1.2.3.4:7777, http://5.6.7.8:7777`.
+const NODE_ADDRESS_SEPARATOR: char = ',';
+
+/// How many times each node is tried before giving up.
+const ROUNDS: u32 = 3;
+
+/// How long to wait after every node has failed before trying them all again, doubled each round.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// How long a request made with `curl` may take before it's abandoned, so a hung node counts as
+/// timed out and the next node is tried.
+const CURL_MAX_TIME: Duration = Duration::from_secs(60);
+
+/// Fragments of casper-client's error output which mean the node couldn't be reached, timed out or
+/// failed with a 5xx status, so another node or a later attempt may succeed.
+const RETRYABLE_ERRORS: [&str; 11] = [
+    "timed out",
+    "timeout",
+    "error sending request",
+    "connection refused",
+    "connection reset",
+    "dns error",
+    "could not resolve",
+    "code: 5",
+    "status: 5",
+    "status code 5",
+    "returned error: 5",
+];
+
+/// How to reach a network's nodes, e.g. from behind a corporate proxy or TLS-intercepting
+/// firewall, or via a hosted RPC provider which requires an API key.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct ConnectionSettings {
+    /// A PEM file of the CA certificates to trust for https nodes, instead of the system's.
+    pub ca_certificate: Option<PathBuf>,
+    /// The HTTP(S) proxy to connect through, e.g. `http://proxy.example.com:3128`.  If `None`, the
+    /// `HTTPS_PROXY` and `HTTP_PROXY` environment variables are honored.
+    pub proxy: Option<String>,
+    /// Headers added to every request, e.g. `("Authorization", "Bearer <API key>")`.
+    ///
+    /// casper-client can't send custom headers, so when any are given, requests are made with
+    /// `curl` instead.
+    pub headers: Vec<(String, String)>,
+}
+
+/// Sets the connection settings applied to every subsequent request.
+pub(super) fn set_connection(connection: ConnectionSettings) {
+    *CONNECTION.lock().unwrap() = connection;
+}
+
+/// Applies the trusted CAs and proxy of the connection settings to a `casper-client` command, whose
+/// HTTP client reads them from the environment.
+///
+/// Custom headers can't be applied, so should be checked for via [`has_custom_headers`] where a
+/// command can't be replaced by a request made with `curl`.
+pub(super) fn apply_connection_settings(command: &mut Command) {
+    apply_connection(command, &CONNECTION.lock().unwrap());
+}
+
+/// Returns `true` if the connection settings include custom headers.
+pub(super) fn has_custom_headers() -> bool {
+    !CONNECTION.lock().unwrap().headers.is_empty()
+}
+
+fn apply_connection(command: &mut Command, connection: &ConnectionSettings) {
+    if let Some(ca_certificate) = &connection.ca_certificate {
+        command.env("SSL_CERT_FILE", ca_certificate);
+    }
+    if let Some(proxy) = &connection.proxy {
+        command.env("HTTPS_PROXY", proxy).env("HTTP_PROXY", proxy);
+    }
+}
+
+/// Returns the `curl` config which applies the connection settings, for requests made with `curl`.
+///
+/// It's to be passed on stdin with `--config -` rather than as args, since the headers usually
+/// hold API keys, and any local user can read a process's args, e.g. with `ps`.
+pub(super) fn curl_config() -> String {
+    let connection = CONNECTION.lock().unwrap();
+    let mut config = String::new();
+    if let Some(ca_certificate) = &connection.ca_certificate {
+        config.push_str(&curl_config_line(
+            "cacert",
+            &ca_certificate.display().to_string(),
+        ));
+    }
+    if let Some(proxy) = &connection.proxy {
+        config.push_str(&curl_config_line("proxy", proxy));
+    }
+    for (name, value) in &connection.headers {
+        config.push_str(&curl_config_line("header", &format!("{}: {}", name, value)));
+    }
+    config
+}
+
+/// Returns a line of a `curl` config setting `option` to `value`, quoted and escaped.
+fn curl_config_line(option: &str, value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("{} = \"{}\"\n", option, escaped)
+}
+
+/// Returns the individual addresses in a comma-separated list of node addresses.
+pub(super) fn node_addresses(node_address: &str) -> Vec<&str> {
+    node_address
+        .split(NODE_ADDRESS_SEPARATOR)
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .collect()
+}
+
+/// Returns the first address in a comma-separated list of node addresses, for requests which
+/// mustn't be repeated against another node.
+pub(super) fn primary_node_address(node_address: &str) -> &str {
+    node_addresses(node_address)
+        .first()
+        .copied()
+        .unwrap_or_else(|| node_address.trim())
+}
+
+/// Runs `casper-client` with the given args against the nodes in the comma-separated
+/// `node_address`, returning the `result` of the JSON-RPC response.
+///
+/// `args` start with the subcommand, and `--node-address` is added after it.  See
+/// [`call_reporting`] for how failed attempts are retried.
+pub(super) fn call(node_address: &str, args: &[&str]) -> Result<Value, String> {
+    call_reporting(node_address, args, |_| ())
+}
+
+/// Like [`call`], but describes each failed attempt via `report`.
+///
+/// If a node can't be reached, times out or responds with a 5xx status, the next node is tried.
+/// Once all have failed, they're tried again after an exponentially increasing delay, up to
+/// `ROUNDS` times.  Any other error, e.g. the node rejecting the request, is returned immediately.
+pub(super) fn call_reporting<F: Fn(String)>(
+    node_address: &str,
+    args: &[&str],
+    report: F,
+) -> Result<Value, String> {
+    let nodes = node_addresses(node_address);
+    if nodes.is_empty() {
+        return Err("no node address given".to_string());
+    }
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut last_error = String::new();
+    for round in 1..=ROUNDS {
+        for node in &nodes {
+            match run(node, args) {
+                Ok(result) => return Ok(result),
+                Err(error) if is_retryable(&error) => {
+                    report(format!("{} failed: {}", node, error));
+                    last_error = error;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        if round < ROUNDS {
+            report(format!("Retrying in {} seconds", backoff.as_secs()));
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+    Err(if nodes.len() == 1 {
+        last_error
+    } else {
+        format!(
+            "all {} nodes failed, the last with: {}",
+            nodes.len(),
+            last_error
+        )
+    })
+}
+
+/// Runs `casper-client` with the given args against a single node, or makes the equivalent request
+/// with `curl` if custom headers are needed.
+fn run(node_address: &str, args: &[&str]) -> Result<Value, String> {
+    let connection = CONNECTION.lock().unwrap().clone();
+    if !connection.headers.is_empty() {
+        return run_with_curl(node_address, args);
+    }
+
+    let (subcommand, args) = args.split_first().ok_or("no casper-client subcommand")?;
+    let mut command = Command::new("casper-client");
+    command
+        .arg(subcommand)
+        .args(&["--node-address", node_address])
+        .args(args);
+    apply_connection(&mut command, &connection);
+    let output = command
+        .output()
+        .map_err(|error| format!("failed to run casper-client: {}", error))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let mut response = serde_json::from_slice::<Value>(&output.stdout)
+        .map_err(|error| format!("failed to parse the response: {}", error))?;
+    Ok(response["result"].take())
+}
+
+/// Makes the JSON-RPC request equivalent to the given casper-client args against a single node
+/// using `curl`, applying the connection settings.
+fn run_with_curl(node_address: &str, args: &[&str]) -> Result<Value, String> {
+    let (method, params) = json_rpc_request(args)?;
+    let mut request = json!({ "jsonrpc": "2.0", "id": 1, "method": method });
+    if let Some(params) = params {
+        request["params"] = params;
+    }
+
+    // The request is sent in the config on stdin along with the connection settings.
+    let config = format!(
+        "{}{}",
+        curl_config(),
+        curl_config_line("data-binary", &request.to_string())
+    );
+    let mut curl = Command::new("curl")
+        .args(&["--silent", "--show-error", "--fail"])
+        .arg("--max-time")
+        .arg(CURL_MAX_TIME.as_secs().to_string())
+        .args(&["--header", "Content-Type: application/json"])
+        .args(["--config", "-"])
+        .arg(format!(
+            "{}{}",
+            node_address.trim_end_matches('/'),
+            RPC_PATH
+        ))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("failed to run curl: {}", error))?;
+    curl.stdin
+        .take()
+        .unwrap()
+        .write_all(config.as_bytes())
+        .map_err(|error| format!("failed to send the request to curl: {}", error))?;
+    let output = curl
+        .wait_with_output()
+        .map_err(|error| format!("failed to run curl: {}", error))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let mut response = serde_json::from_slice::<Value>(&output.stdout)
+        .map_err(|error| format!("failed to parse the response: {}", error))?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("the node returned an error: {}", error));
+    }
+    Ok(response["result"].take())
+}
+
+/// Returns the JSON-RPC method and params of the request made by casper-client for the given
+/// args, for the subcommands the tool uses.
+fn json_rpc_request(args: &[&str]) -> Result<(&'static str, Option<Value>), String> {
+    let (subcommand, args) = args.split_first().ok_or("no casper-client subcommand")?;
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| *arg == name)
+            .and_then(|index| args.get(index + 1))
+            .copied()
+    };
+    let required = |name: &str| option(name).ok_or_else(|| format!("no {} given", name));
+
+    match *subcommand {
+        "get-node-status" => Ok(("info_get_status", None)),
+        "get-state-root-hash" => Ok((
+            "chain_get_state_root_hash",
+            option("--block-identifier").map(|block| {
+                let block_identifier = match block.parse::<u64>() {
+                    Ok(height) => json!({ "Height": height }),
+                    Err(_) => json!({ "Hash": block }),
+                };
+                json!({ "block_identifier": block_identifier })
+            }),
+        )),
+        "query-global-state" => {
+            // casper-client accepts an account's public key, querying its account hash.
+            let key = required("--key")?;
+            let key = match PublicKey::from_hex(key) {
+                Ok(public_key) => public_key.to_account_hash().to_formatted_string(),
+                Err(_) => key.to_string(),
+            };
+            Ok((
+                "state_get_item",
+                Some(json!({
+                    "state_root_hash": required("--state-root-hash")?,
+                    "key": key,
+                    "path": [],
+                })),
+            ))
+        }
+        "get-balance" => Ok((
+            "state_get_balance",
+            Some(json!({
+                "state_root_hash": required("--state-root-hash")?,
+                "purse_uref": required("--purse-uref")?,
+            })),
+        )),
+        "get-deploy" => {
+            let deploy_hash = args.first().ok_or("no deploy hash given")?;
+            Ok((
+                "info_get_deploy",
+                Some(json!({ "deploy_hash": deploy_hash })),
+            ))
+        }
+        "send-deploy" => {
+            let input = required("--input")?;
+            let contents = fs::read_to_string(input)
+                .map_err(|error| format!("failed to read {}: {}", input, error))?;
+            let deploy = serde_json::from_str::<Value>(&contents)
+                .map_err(|error| format!("failed to parse {}: {}", input, error))?;
+            let method = if args.contains(&"--speculative-exec") {
+                "speculative_exec"
+            } else {
+                "account_put_deploy"
+            };
+            Ok((method, Some(json!({ "deploy": deploy }))))
+        }
+        _ => Err(format!(
+            "casper-client {} can't be sent with custom headers",
+            subcommand
+        )),
+    }
+}
+
+/// Returns `true` if the given error means the request may succeed against another node or later.
+fn is_retryable(error: &str) -> bool {
+    let error = error.to_lowercase();
+    RETRYABLE_ERRORS
+        .iter()
+        .any(|fragment| error.contains(fragment))
+}
@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+/// The keywords highlighted, including the reserved ones never generated.
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "Self", "self", "static", "struct", "super", "trait", "type", "union", "unsafe",
+    "use", "where", "while", "yield",
+];
+
+/// The kind of a span of Rust source which is highlighted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RustToken {
+    Keyword,
+    /// A line or block comment, including doc comments.
+    Comment,
+    /// A string, byte string, char, number or boolean literal.
+    Literal,
+}
+
+/// Returns the byte ranges of `source` which are keywords, comments or literals, in order.
+///
+/// This is a lexer for highlighting rather than a parser: it's only as precise as the generated
+/// sources need, and never fails, leaving anything unrecognised unhighlighted.
+pub(super) fn highlight(source: &str) -> Vec<(Range<usize>, RustToken)> {
+    let bytes = source.as_bytes();
+    let mut spans = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        let byte = bytes[index];
+        let next = bytes.get(index + 1).copied();
+        let token = if byte == b'/' && next == Some(b'/') {
+            index = find_from(bytes, index, b"\n").unwrap_or(bytes.len());
+            Some(RustToken::Comment)
+        } else if byte == b'/' && next == Some(b'*') {
+            index = find_from(bytes, index + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+            Some(RustToken::Comment)
+        } else if byte == b'"' {
+            index = string_end(bytes, index + 1);
+            Some(RustToken::Literal)
+        } else if let Some(end) = raw_or_byte_string_end(bytes, index) {
+            index = end;
+            Some(RustToken::Literal)
+        } else if byte == b'\'' {
+            match char_literal_end(source, index + 1) {
+                Some(end) => {
+                    index = end;
+                    Some(RustToken::Literal)
+                }
+                // A lifetime, which is left unhighlighted.
+                None => {
+                    index += 1;
+                    None
+                }
+            }
+        } else if byte.is_ascii_digit() {
+            index = number_end(bytes, index);
+            Some(RustToken::Literal)
+        } else if is_identifier_start(byte) {
+            while index < bytes.len() && is_identifier_continue(bytes[index]) {
+                index += 1;
+            }
+            match &source[start..index] {
+                "true" | "false" => Some(RustToken::Literal),
+                identifier if KEYWORDS.contains(&identifier) => Some(RustToken::Keyword),
+                _ => None,
+            }
+        } else {
+            // Skips a whole char, so `index` stays on a char boundary.
+            index += source[index..].chars().next().map_or(1, char::len_utf8);
+            None
+        };
+        if let Some(token) = token {
+            spans.push((start..index, token));
+        }
+    }
+    spans
+}
+
+/// Returns the index of the first occurrence of `pattern` in `bytes` at or after `from`.
+fn find_from(bytes: &[u8], from: usize, pattern: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+        .map(|position| from + position)
+}
+
+/// Returns the index just past the closing quote of a string whose contents start at `from`.
+fn string_end(bytes: &[u8], mut from: usize) -> usize {
+    while from < bytes.len() {
+        match bytes[from] {
+            b'\\' => from += 2,
+            b'"' => return from + 1,
+            _ => from += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the index just past a raw string, byte string or raw byte string starting at `from`,
+/// e.g. `r#"..."#` or `b"..."`, or `None` if there isn't one.
+fn raw_or_byte_string_end(bytes: &[u8], from: usize) -> Option<usize> {
+    // Part of a longer identifier, e.g. `for`, can't start a string.
+    if from > 0 && is_identifier_continue(bytes[from - 1]) {
+        return None;
+    }
+    let mut index = from;
+    if bytes.get(index) == Some(&b'b') {
+        index += 1;
+    }
+    if bytes.get(index) != Some(&b'r') {
+        return match (index > from, bytes.get(index)) {
+            (true, Some(b'"')) => Some(string_end(bytes, index + 1)),
+            _ => None,
+        };
+    }
+    index += 1;
+    let hashes = bytes[index..]
+        .iter()
+        .take_while(|byte| **byte == b'#')
+        .count();
+    index += hashes;
+    if bytes.get(index) != Some(&b'"') {
+        return None;
+    }
+    let mut terminator = vec![b'"'];
+    terminator.resize(hashes + 1, b'#');
+    Some(find_from(bytes, index + 1, &terminator).map_or(bytes.len(), |end| end + hashes + 1))
+}
+
+/// Returns the index just past the closing quote of a char literal whose contents start at `from`,
+/// or `None` if the quote starts a lifetime instead.
+fn char_literal_end(source: &str, from: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    if bytes.get(from) == Some(&b'\\') {
+        let close = find_from(bytes, from + 2, b"'")?;
+        return Some(close + 1);
+    }
+    // A single char, which may be several bytes long.
+    let char_length = source.get(from..)?.chars().next()?.len_utf8();
+    match bytes.get(from + char_length) {
+        Some(b'\'') => Some(from + char_length + 1),
+        _ => None,
+    }
+}
+
+/// Returns the index just past a number starting at `from`, including any suffix, e.g. `1_000u64`
+/// or `2.5`.
+fn number_end(bytes: &[u8], mut from: usize) -> usize {
+    while from < bytes.len() {
+        let byte = bytes[from];
+        let is_decimal_point = byte == b'.'
+            && bytes
+                .get(from + 1)
+                .is_some_and(|next| next.is_ascii_digit());
+        if !is_identifier_continue(byte) && !is_decimal_point {
+            break;
+        }
+        from += 1;
+    }
+    from
+}
+
+fn is_identifier_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_'
+}
+
+fn is_identifier_continue(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
@@ -0,0 +1,200 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+use super::key_configuration::KeyConfiguration;
+
+/// A common multisig scheme, setting the weights and action thresholds for the keys already added.
+///
+/// The keys which sign are all the associated keys, or all but the main account's key if it's set
+/// to be deleted after account creation, in which case the main key's weight is left as it is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SchemeTemplate {
+    /// Any `required` of exactly `keys` keys.
+    MOfN { required: usize, keys: usize },
+    /// Any majority of the keys, however many there are.
+    Majority,
+    /// Every one of the keys.
+    All,
+    /// Any one key can deploy, but changing the keys or thresholds needs a majority.
+    AnyDeploysMajorityManages,
+    /// The first key alone, or else a majority of the others.
+    ManagerWithBackups,
+}
+
+/// The weights of the signing keys, in order, and the key-management and deployment thresholds.
+struct Scheme {
+    weights: Vec<u8>,
+    key_management_threshold: u8,
+    deployment_threshold: u8,
+}
+
+impl SchemeTemplate {
+    /// All the templates offered.
+    pub const ALL: [SchemeTemplate; 6] = [
+        SchemeTemplate::MOfN {
+            required: 2,
+            keys: 3,
+        },
+        SchemeTemplate::MOfN {
+            required: 3,
+            keys: 5,
+        },
+        SchemeTemplate::Majority,
+        SchemeTemplate::All,
+        SchemeTemplate::AnyDeploysMajorityManages,
+        SchemeTemplate::ManagerWithBackups,
+    ];
+
+    /// Returns the weights and thresholds for `key_count` signing keys, or an error if the template
+    /// needs a different number of keys.
+    fn scheme(&self, key_count: usize) -> Result<Scheme, String> {
+        let min_key_count = match self {
+            SchemeTemplate::MOfN { keys, .. } => {
+                if key_count != *keys {
+                    return Err(format!(
+                        "it needs exactly {} signing keys rather than {}",
+                        keys, key_count
+                    ));
+                }
+                *keys
+            }
+            SchemeTemplate::Majority
+            | SchemeTemplate::All
+            | SchemeTemplate::AnyDeploysMajorityManages => 2,
+            SchemeTemplate::ManagerWithBackups => 3,
+        };
+        if key_count < min_key_count {
+            return Err(format!(
+                "it needs at least {} signing keys rather than {}",
+                min_key_count, key_count
+            ));
+        }
+
+        let majority = key_count / 2 + 1;
+        let (weights, key_management_threshold, deployment_threshold) = match self {
+            SchemeTemplate::MOfN { required, .. } => (vec![1; key_count], *required, *required),
+            SchemeTemplate::Majority => (vec![1; key_count], majority, majority),
+            SchemeTemplate::All => (vec![1; key_count], key_count, key_count),
+            SchemeTemplate::AnyDeploysMajorityManages => (vec![1; key_count], majority, 1),
+            SchemeTemplate::ManagerWithBackups => {
+                // The manager's weight alone meets the thresholds, as does a majority of backups.
+                let backups_majority = (key_count - 1) / 2 + 1;
+                let mut weights = vec![1; key_count];
+                weights[0] = backups_majority;
+                (weights, backups_majority, backups_majority)
+            }
+        };
+        // With at most `MAX_ASSOCIATED_KEYS` keys, every weight and threshold fits in a `u8`.
+        let to_weight = |value: usize| u8::try_from(value).map_err(|error| error.to_string());
+        Ok(Scheme {
+            weights: weights
+                .into_iter()
+                .map(to_weight)
+                .collect::<Result<_, _>>()?,
+            key_management_threshold: to_weight(key_management_threshold)?,
+            deployment_threshold: to_weight(deployment_threshold)?,
+        })
+    }
+
+    /// Returns a description of the policy the template gives `key_count` signing keys.
+    fn policy(&self, key_count: usize) -> String {
+        let majority = key_count / 2 + 1;
+        match self {
+            SchemeTemplate::MOfN { required, keys } => format!(
+                "Any {} of the {} keys must sign both to deploy and to change the associated keys \
+                or thresholds.  Losing up to {} of them doesn't lock the account.",
+                required,
+                keys,
+                keys - required
+            ),
+            SchemeTemplate::Majority => format!(
+                "Any {} of the {} keys must sign both to deploy and to change the associated keys \
+                or thresholds.  Losing up to {} of them doesn't lock the account.",
+                majority,
+                key_count,
+                key_count - majority
+            ),
+            SchemeTemplate::All => format!(
+                "All {} keys must sign both to deploy and to change the associated keys or \
+                thresholds.  Losing any one key locks the account.",
+                key_count
+            ),
+            SchemeTemplate::AnyDeploysMajorityManages => format!(
+                "Any one of the {} keys can deploy alone, but changing the associated keys or \
+                thresholds needs any {} of them.",
+                key_count, majority
+            ),
+            SchemeTemplate::ManagerWithBackups => {
+                let backups = key_count - 1;
+                let backups_majority = backups / 2 + 1;
+                format!(
+                    "The first signing key can deploy and change the associated keys or \
+                    thresholds alone.  Without it, any {} of the other {} keys can do the same, \
+                    e.g. to replace it if it's lost.",
+                    backups_majority, backups
+                )
+            }
+        }
+    }
+}
+
+impl Display for SchemeTemplate {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            SchemeTemplate::MOfN { required, keys } => {
+                write!(formatter, "{}-of-{}", required, keys)
+            }
+            SchemeTemplate::Majority => write!(formatter, "Majority of the keys"),
+            SchemeTemplate::All => write!(formatter, "All of the keys"),
+            SchemeTemplate::AnyDeploysMajorityManages => {
+                write!(formatter, "Any key deploys, a majority manages")
+            }
+            SchemeTemplate::ManagerWithBackups => write!(formatter, "Manager with backups"),
+        }
+    }
+}
+
+/// Returns the number of keys which sign for the account in `configuration`.
+fn signing_key_count(configuration: &KeyConfiguration) -> usize {
+    let key_count = configuration.associated_keys.len();
+    if configuration.primary_key_should_be_deleted {
+        key_count.saturating_sub(1)
+    } else {
+        key_count
+    }
+}
+
+/// Returns the policy `template` would give the keys of `configuration`, or an error if it doesn't
+/// suit their number.
+pub(super) fn describe(
+    template: SchemeTemplate,
+    configuration: &KeyConfiguration,
+) -> Result<String, String> {
+    let key_count = signing_key_count(configuration);
+    template.scheme(key_count)?;
+    Ok(template.policy(key_count))
+}
+
+/// Returns `configuration` with the weights and thresholds of `template`, or an error if it doesn't
+/// suit the number of keys.
+pub(super) fn apply(
+    template: SchemeTemplate,
+    configuration: &KeyConfiguration,
+) -> Result<KeyConfiguration, String> {
+    let scheme = template.scheme(signing_key_count(configuration))?;
+    let mut configuration = configuration.clone();
+    let skipped = configuration.associated_keys.len() - scheme.weights.len();
+    for ((_, weight), scheme_weight) in configuration
+        .associated_keys
+        .iter_mut()
+        .skip(skipped)
+        .zip(scheme.weights)
+    {
+        *weight = scheme_weight;
+    }
+    configuration.key_management_weight = scheme.key_management_threshold;
+    configuration.deployment_weight = scheme.deployment_threshold;
+    Ok(configuration)
+}
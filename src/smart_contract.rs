@@ -1,15 +1,81 @@
 use std::{
-    fs::{self, File},
-    io::{BufRead, BufReader, BufWriter, Write},
-    path::PathBuf,
-    process::{Command, Stdio},
-    sync::mpsc::{self, Receiver},
+    cmp, fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-use casper_types::account::{AccountHash, Weight};
+use casper_node::crypto::hash;
+use casper_types::{
+    account::{AccountHash, Weight},
+    RuntimeArgs,
+};
+
+use super::{
+    build_cache::{self, TargetDirLocation},
+    cargo_casper,
+    ci_workflow::{self, CiProvider, WorkflowConfig},
+    codegen_target::CodegenTarget,
+    compile_backend::{self, CompileBackend},
+    compile_output::OutputStream,
+    compile_progress::{self, CompilePhase},
+    contract_mode::{self, ContractMode},
+    contract_name,
+    cost_estimate::CostEstimate,
+    dependencies::{DependencySource, DEFAULT_TOOLCHAIN},
+    deploy_limits, explorer, git_repo,
+    initial_transfer::{self, InitialTransfer},
+    local_network::Rehearsal,
+    named_keys::{self, NamedKey},
+    network::{self, NetworkProfile},
+    plan::{self, Operation, Step, Target},
+    prebuilt_session, preferences,
+    protocol_profile::ProtocolProfile,
+    provenance, reproducible_build,
+    rpc::{self, ConnectionSettings},
+    test_project::{self, TestConfig},
+    validation::{self, Severity, ValidationIssue},
+    verification::ExpectedAccount,
+    write_if_changed, CompileEvent, Error, TestEvent, TransferEvent,
+};
+
+/// The file listing the runtime args for the generic session contract.
+pub(super) const SESSION_ARGS_FILE: &str = "session_args.txt";
+
+/// The default compile timeout, long enough for a cold build of the dependencies on a slow machine.
+pub(super) const DEFAULT_COMPILE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often a command with a deadline is checked for having finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The values substituted for the node and account specific options of a put-deploy command.
+struct DeployPlaceholders {
+    node_address: &'static str,
+    chain_name: &'static str,
+    secret_key: &'static str,
+    payment_amount: &'static str,
+}
 
-use super::Error;
+const EXAMPLE_PLACEHOLDERS: DeployPlaceholders = DeployPlaceholders {
+    node_address: "<NODE_ADDRESS>",
+    chain_name: "<CHAIN_NAME>",
+    secret_key: "<MAIN_ACCOUNT_SECRET_KEY_PATH>",
+    payment_amount: "<PAYMENT_AMOUNT>",
+};
+
+const MAKEFILE_PLACEHOLDERS: DeployPlaceholders = DeployPlaceholders {
+    node_address: "$(NODE)",
+    chain_name: "$(CHAIN_NAME)",
+    secret_key: "$(SECRET_KEY)",
+    payment_amount: "$(PAYMENT_AMOUNT)",
+};
 
 #[derive(Clone, PartialOrd, Ord, PartialEq, Eq, Debug)]
 enum AssociatedKeyKind {
@@ -78,7 +144,69 @@ pub(super) struct SmartContract {
     pub(super) associated_keys: Vec<AssociatedKey>,
     pub(super) key_management_weight: Weight,
     pub(super) deployment_weight: Weight,
+    pub(super) contract_mode: ContractMode,
+    /// Whether the setup contract tolerates the failures caused by an earlier attempt having
+    /// already made a change, so that a retried deploy converges rather than reverting.
+    pub(super) idempotent: bool,
+    /// Whether the setup contract is the generic one which reads the keys, weights and thresholds
+    /// from its runtime args rather than having them baked in as constants.
+    pub(super) runtime_args_template: bool,
+    /// Human-readable labels for account hashes, recorded in the provenance header of the
+    /// generated sources.
+    pub(super) key_labels: Vec<(AccountHash, String)>,
+    /// The named keys stored in the account by the generated contract.
+    pub(super) named_keys: Vec<NamedKey>,
+    /// The transfer funding the main account before the setup deploy is sent, if any.
+    pub(super) initial_transfer: Option<InitialTransfer>,
+    /// If set, this Wasm module is used as the session code rather than compiling a contract.
+    pub(super) prebuilt_session_wasm: Option<PathBuf>,
+    /// The Blake2b hash the prebuilt session Wasm was pinned to when chosen, or `None` if it's
+    /// unverified.
+    pub(super) prebuilt_session_wasm_hash: Option<String>,
+    /// Whether to only write the project, without compiling it.
+    pub(super) source_only: bool,
+    /// Whether to run `wasm-opt -Oz` on the compiled contract.
+    pub(super) run_wasm_opt: bool,
+    /// Whether to run `wasm-strip` on the compiled contract.
+    pub(super) run_wasm_strip: bool,
+    /// The authors written to the generated Cargo.toml.
+    pub(super) authors: Vec<String>,
+    /// The largest deploy, in bytes, accepted by the target network.
+    pub(super) max_deploy_size: u32,
+    /// How long the cargo commands of a compilation may run in total before being killed.
+    pub(super) compile_timeout: Option<Duration>,
+    /// The version of the Casper network the session code is generated for.
+    pub(super) codegen_target: CodegenTarget,
+    pub(super) casper_contract_dependency: DependencySource,
+    pub(super) casper_types_dependency: DependencySource,
+    pub(super) casper_engine_test_support_dependency: DependencySource,
+    pub(super) toolchain: String,
+    pub(super) init_git_repo: bool,
+    /// Whether to scaffold the project with cargo-casper, if it's installed.
+    pub(super) use_cargo_casper: bool,
+    /// The CI service for which a workflow building the contract is generated, if any.
+    pub(super) ci_provider: Option<CiProvider>,
+    pub(super) reproducible_build: bool,
+    pub(super) vendor_dependencies: bool,
+    pub(super) compile_backend: CompileBackend,
+    pub(super) target_dir_location: TargetDirLocation,
+    pub(super) use_sccache: bool,
+    /// The networks the tool can talk to, as loaded from the networks config file.
+    pub(super) networks: Vec<NetworkProfile>,
+    /// The name of the network RPC-facing features talk to by default, if one is selected.
+    pub(super) selected_network: Option<String>,
+    /// The cost estimate from the last successful dry run, with the hash of its session Wasm.
+    pub(super) speculative_estimate: Option<(String, CostEstimate)>,
+    /// The endpoint the CSPR price is fetched from to show costs in fiat, or `None` if they aren't.
+    pub(super) price_feed_url: Option<String>,
+    /// Identifies the most recently started pre-warm build, to avoid repeating it.
+    prewarm_key: Option<String>,
+    /// Whether the most recently generated project has cargo-casper's layout, with the contract
+    /// crate in a subdirectory.
+    cargo_casper_layout: bool,
     compile_worker: Option<JoinHandle<()>>,
+    /// Set to cancel the compilation most recently started.
+    compile_cancelled: Arc<AtomicBool>,
 }
 
 impl Default for SmartContract {
@@ -89,7 +217,41 @@ impl Default for SmartContract {
             associated_keys: Vec::new(),
             key_management_weight: Weight::new(0),
             deployment_weight: Weight::new(0),
+            contract_mode: ContractMode::default(),
+            idempotent: false,
+            runtime_args_template: false,
+            key_labels: vec![],
+            named_keys: vec![],
+            initial_transfer: None,
+            prebuilt_session_wasm: None,
+            prebuilt_session_wasm_hash: None,
+            source_only: false,
+            run_wasm_opt: false,
+            run_wasm_strip: false,
+            authors: vec![preferences::DEFAULT_AUTHOR.to_string()],
+            max_deploy_size: deploy_limits::DEFAULT_MAX_DEPLOY_SIZE,
+            compile_timeout: Some(DEFAULT_COMPILE_TIMEOUT),
+            codegen_target: CodegenTarget::default(),
+            casper_contract_dependency: DependencySource::default(),
+            casper_types_dependency: DependencySource::default(),
+            casper_engine_test_support_dependency: DependencySource::default(),
+            toolchain: DEFAULT_TOOLCHAIN.to_string(),
+            init_git_repo: false,
+            use_cargo_casper: false,
+            ci_provider: None,
+            reproducible_build: false,
+            vendor_dependencies: false,
+            compile_backend: CompileBackend::default(),
+            target_dir_location: TargetDirLocation::default(),
+            use_sccache: false,
+            networks: network::builtin_profiles(),
+            selected_network: None,
+            speculative_estimate: None,
+            price_feed_url: None,
+            prewarm_key: None,
+            cargo_casper_layout: false,
             compile_worker: None,
+            compile_cancelled: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -126,35 +288,110 @@ impl SmartContract {
         Ok(())
     }
 
-    pub(super) fn create_and_compile(&mut self) -> Result<Receiver<String>, Error> {
+    pub(super) fn create_and_compile(&mut self) -> Result<Receiver<CompileEvent>, Error> {
+        // The name is sanitized when set, but may never have been set at all.
+        contract_name::sanitize(&self.contract_name)?;
+        self.validate_initial_transfer()?;
+        self.validate_contract_mode()?;
+        self.contract_sources()?;
         let project_dir = self.project_dir();
         fs::create_dir_all(&project_dir).unwrap();
 
+        if let Some(wasm_path) = self.prebuilt_session_wasm.clone() {
+            self.cargo_casper_layout = false;
+            return self.create_prebuilt_session_project(&wasm_path);
+        }
+        self.codegen_target.validate_dependencies(
+            &self.casper_contract_dependency,
+            &self.casper_types_dependency,
+        )?;
+
+        // Without cargo-casper, fall back to generating the whole project.
+        self.cargo_casper_layout = self.use_cargo_casper && cargo_casper::available();
+        if self.cargo_casper_layout {
+            cargo_casper::scaffold(&project_dir)
+                .map_err(|inner| Error::ScaffoldProject { inner })?;
+        }
+        fs::create_dir_all(self.contract_dir()).unwrap();
+
         self.create_cargo_config()?;
-        self.create_main_rs()?;
+        self.create_contract_sources()?;
+        if self.uses_runtime_args() {
+            self.create_session_args();
+        } else {
+            // Remove any session args left by a previous generation using the generic contract.
+            let _ = fs::remove_file(self.contract_dir().join(SESSION_ARGS_FILE));
+        }
         self.create_cargo_toml()?;
         self.create_rust_toolchain()?;
+        // Resolving the dependencies needs cargo, and usually network access.
+        if !self.source_only {
+            self.create_cargo_lock()?;
+        }
+        self.create_dockerfile()?;
+        self.create_readme()?;
+        self.create_makefile()?;
+        self.create_ci_workflow()?;
+        self.create_test_project()?;
+        self.create_git_repo(None)?;
 
+        if self.source_only {
+            return self.source_generated();
+        }
         self.compile_contract()
     }
 
-    fn create_cargo_config(&self) -> Result<(), Error> {
+    /// Reports the generated project without compiling it.
+    fn source_generated(&self) -> Result<Receiver<CompileEvent>, Error> {
         let project_dir = self.project_dir();
-        let cargo_config_dir = project_dir.join(".cargo");
-        fs::create_dir_all(&cargo_config_dir).unwrap();
-
-        fs::write(
-            cargo_config_dir.join("config.toml"),
-            br#"[build]
-target = "wasm32-unknown-unknown"
-"#,
-        )
-        .unwrap();
+        let (sender, receiver) = mpsc::channel();
+        send_output(&sender, "Generated the project without compiling it:");
+        send_output(&sender, project_dir.display());
+        send_output(&sender, "");
+        send_output(&sender, "Smart contract source code:");
+        let contract_dir = self.contract_dir();
+        for (relative_path, _) in self.contract_sources()? {
+            send_output(&sender, contract_dir.join(relative_path).display());
+        }
+        send_output(&sender, "");
+        send_output(&sender, "See README.md for how to build it.");
+        let _ = sender.send(CompileEvent::SourceGenerated { project_dir });
+        Ok(receiver)
+    }
 
+    fn create_cargo_config(&self) -> Result<(), Error> {
+        write_cargo_config(&self.contract_dir());
         Ok(())
     }
 
+    /// Returns the deploys needed to reach the configuration from a newly-created account, in
+    /// order.
+    pub(super) fn plan(&self) -> Result<Vec<Step>, Error> {
+        if self.associated_keys.is_empty() {
+            return Err(Error::NoKeys);
+        }
+        let weights = self
+            .associated_keys
+            .iter()
+            .map(|key| key.weight.value())
+            .collect::<Vec<_>>();
+        plan::plan(&Target {
+            weights: &weights,
+            remove_main_key: self.main_key_should_be_deleted(),
+            key_management_threshold: self.key_management_weight.value(),
+            deployment_threshold: self.deployment_weight.value(),
+        })
+    }
+
+    /// Returns the source of the session code for the first deploy.
     pub(super) fn main_rs_contents(&self) -> String {
+        if self.contract_mode != ContractMode::Setup {
+            return match self.contract_sources() {
+                Ok(mut sources) => sources.remove(0).1,
+                Err(error) => format!("// {}\n", error),
+            };
+        }
+
         if self.associated_keys.is_empty()
             || self.key_management_weight.value() == 0
             || self.deployment_weight.value() == 0
@@ -162,8 +399,65 @@ target = "wasm32-unknown-unknown"
             return String::new();
         }
 
-        let mut iter = self.associated_keys.iter().enumerate();
-        let (_, primary_key) = iter.next().unwrap();
+        if self.runtime_args_template {
+            return match self.validate_runtime_args_session() {
+                Ok(()) => self
+                    .codegen_target
+                    .adapt_source(&prebuilt_session::main_rs_contents()),
+                Err(error) => format!("// {}\n", error),
+            };
+        }
+
+        match self.plan() {
+            Ok(steps) => self
+                .codegen_target
+                .adapt_source(&self.step_source(0, &steps)),
+            Err(error) => format!("// {}\n", error),
+        }
+    }
+
+    /// Returns the path relative to the project dir and the contents of the session code source for
+    /// each deploy, in order, adapted for the codegen target.
+    pub(super) fn contract_sources(&self) -> Result<Vec<(PathBuf, String)>, Error> {
+        Ok(self
+            .casper1_contract_sources()?
+            .into_iter()
+            .map(|(path, contents)| (path, self.codegen_target.adapt_source(&contents)))
+            .collect())
+    }
+
+    /// Returns the session code sources as generated for Casper 1.x.
+    fn casper1_contract_sources(&self) -> Result<Vec<(PathBuf, String)>, Error> {
+        let configured_keys = self
+            .associated_keys
+            .iter()
+            .map(|key| key.account_hash)
+            .collect::<Vec<_>>();
+        if let Some(contents) = self.contract_mode.main_rs_contents(&configured_keys)? {
+            if self.associated_keys.is_empty() {
+                return Err(Error::NoKeys);
+            }
+            return Ok(vec![(step_source_path(0), contents)]);
+        }
+
+        if self.runtime_args_template {
+            self.validate_runtime_args_session()?;
+            return Ok(vec![(
+                step_source_path(0),
+                prebuilt_session::main_rs_contents(),
+            )]);
+        }
+
+        let steps = self.plan()?;
+        Ok((0..steps.len())
+            .map(|index| (step_source_path(index), self.step_source(index, &steps)))
+            .collect())
+    }
+
+    /// Returns the source of the session code for the deploy at `index` of `steps`.
+    fn step_source(&self, index: usize, steps: &[Step]) -> String {
+        let step = &steps[index];
+        let store_named_keys = index == 0 && !self.named_keys.is_empty();
         let mut contents = format!(
             r#"#![cfg_attr(
     not(target_arch = "wasm32"),
@@ -171,116 +465,351 @@ target = "wasm32-unknown-unknown"
 )]
 #![no_main]
 
-use casper_contract::{{contract_api::account, unwrap_or_revert::UnwrapOrRevert}};
-use casper_types::account::{{AccountHash, ActionType, Weight}};
+{}
+"#,
+            self.step_imports(step, store_named_keys)
+        );
+
+        if steps.len() > 1 {
+            contents = format!(
+                "{}// Deploy {} of {}.  The deploy must be signed by:\n",
+                contents,
+                index + 1,
+                steps.len()
+            );
+            for signer in &step.signers {
+                contents = format!(
+                    "{}//   {}\n",
+                    contents,
+                    self.associated_keys[*signer]
+                        .account_hash
+                        .to_formatted_string()
+                );
+            }
+            contents.push('\n');
+        }
 
-// {}
+        let primary_key = &self.associated_keys[0];
+        if step.operations.contains(&Operation::UpdateMainKey)
+            || step.operations.contains(&Operation::RemoveMainKey)
+        {
+            contents = format!(
+                r#"{}// {}
 #[rustfmt::skip]
 const MAIN_ACCOUNT_HASH: AccountHash = AccountHash::new({:?});
-const MAIN_ACCOUNT_WEIGHT: u8 = {};
-
 "#,
-            primary_key.account_hash.to_formatted_string(),
-            primary_key.account_hash.value(),
-            primary_key.weight.value(),
-        );
+                contents,
+                self.key_comment(&primary_key.account_hash),
+                primary_key.account_hash.value(),
+            );
+            if step.operations.contains(&Operation::UpdateMainKey) {
+                contents = format!(
+                    "{}const MAIN_ACCOUNT_WEIGHT: u8 = {};\n",
+                    contents,
+                    primary_key.weight.value()
+                );
+            }
+            contents.push('\n');
+        }
 
-        for (index, secondary_key) in iter {
-            contents = format!(
-                r#"{contents}// {hex_hash}
+        for operation in &step.operations {
+            if let Operation::AddKey(index) = operation {
+                let secondary_key = &self.associated_keys[*index];
+                contents = format!(
+                    r#"{contents}// {comment}
 #[rustfmt::skip]
 const ACCOUNT_{index}_HASH: AccountHash = AccountHash::new({hash:?});
 const ACCOUNT_{index}_WEIGHT: u8 = {weight};
 
 "#,
-                contents = contents,
-                hex_hash = secondary_key.account_hash.to_formatted_string(),
-                index = index,
-                hash = secondary_key.account_hash.value(),
-                weight = secondary_key.weight.value(),
-            );
+                    contents = contents,
+                    comment = self.key_comment(&secondary_key.account_hash),
+                    index = index,
+                    hash = secondary_key.account_hash.value(),
+                    weight = secondary_key.weight.value(),
+                );
+            }
         }
 
-        contents = format!(
-            r#"{contents}const KEY_MANAGEMENT_WEIGHT: u8 = {km_weight};
-const DEPLOYMENT_WEIGHT: u8 = {dp_weight};
+        let mut put_keys = String::new();
+        if store_named_keys {
+            for (index, named_key) in self.named_keys.iter().enumerate() {
+                contents += &named_keys::constants(index, named_key);
+            }
+            put_keys = "    // Store the named keys.\n".to_string();
+            for (index, named_key) in self.named_keys.iter().enumerate() {
+                put_keys += &named_keys::put_key_statement(index, named_key);
+            }
+        }
 
-#[no_mangle]
-pub extern "C" fn call() {{
-    // Update the main account key's weight.
+        let mut thresholds = String::new();
+        if step
+            .operations
+            .contains(&Operation::SetKeyManagementThreshold)
+        {
+            thresholds = format!(
+                "const KEY_MANAGEMENT_WEIGHT: u8 = {};\n",
+                self.key_management_weight.value()
+            );
+        }
+        if step.operations.contains(&Operation::SetDeploymentThreshold) {
+            thresholds = format!(
+                "{}const DEPLOYMENT_WEIGHT: u8 = {};\n",
+                thresholds,
+                self.deployment_weight.value()
+            );
+        }
+        if !thresholds.is_empty() {
+            contents = format!("{}{}\n", contents, thresholds);
+        }
+
+        let mut statements = vec![];
+        if !put_keys.is_empty() {
+            statements.push(put_keys);
+        }
+        statements.extend(step.operations.iter().map(|operation| match operation {
+            Operation::UpdateMainKey if self.idempotent && self.main_key_should_be_deleted() => {
+                r#"    // Update the main account key's weight, unless an earlier attempt already removed it.
+    match account::update_associated_key(MAIN_ACCOUNT_HASH, Weight::new(MAIN_ACCOUNT_WEIGHT)) {
+        Ok(()) | Err(UpdateKeyFailure::MissingKey) => (),
+        Err(error) => runtime::revert(error),
+    }
+"#
+                .to_string()
+            }
+            Operation::UpdateMainKey => r#"    // Update the main account key's weight.
     account::update_associated_key(MAIN_ACCOUNT_HASH, Weight::new(MAIN_ACCOUNT_WEIGHT))
         .unwrap_or_revert();
-
+"#
+            .to_string(),
+            Operation::AddKey(index) if self.idempotent => format!(
+                r#"    // Add associated key {index}, or update its weight if an earlier attempt already added it.
+    match account::add_associated_key(ACCOUNT_{index}_HASH, Weight::new(ACCOUNT_{index}_WEIGHT)) {{
+        Ok(()) => (),
+        Err(AddKeyFailure::DuplicateKey) => {{
+            account::update_associated_key(ACCOUNT_{index}_HASH, Weight::new(ACCOUNT_{index}_WEIGHT))
+                .unwrap_or_revert()
+        }}
+        Err(error) => runtime::revert(error),
+    }}
 "#,
-            contents = contents,
-            km_weight = self.key_management_weight.value(),
-            dp_weight = self.deployment_weight.value()
-        );
-
-        for index in 1..self.associated_keys.len() {
-            contents = format!(
-                r#"{contents}    // Add associated key {index}.
+                index = index
+            ),
+            Operation::AddKey(index) => format!(
+                r#"    // Add associated key {index}.
     account::add_associated_key(ACCOUNT_{index}_HASH, Weight::new(ACCOUNT_{index}_WEIGHT)).unwrap_or_revert();
-
 "#,
-                contents = contents,
                 index = index
-            );
-        }
-
-        let remove_main_account = if primary_key.remove_after_creation() {
-            r#"
-    // Remove the main account's key.
-    account::remove_associated_key(MAIN_ACCOUNT_HASH).unwrap_or_revert();
-"#
-        } else {
-            ""
-        };
-
-        contents = format!(
-            r#"{contents}    // Set the action thresholds.
+            ),
+            Operation::SetKeyManagementThreshold => r#"    // Set the key-management threshold.
     account::set_action_threshold(
         ActionType::KeyManagement,
         Weight::new(KEY_MANAGEMENT_WEIGHT),
     )
     .unwrap_or_revert();
+"#
+            .to_string(),
+            Operation::SetDeploymentThreshold => r#"    // Set the deploy-execution threshold.
     account::set_action_threshold(ActionType::Deployment, Weight::new(DEPLOYMENT_WEIGHT))
         .unwrap_or_revert();
-{remove_main_account}}}
+"#
+            .to_string(),
+            Operation::RemoveMainKey if self.idempotent => r#"    // Remove the main account's key, unless an earlier attempt already did.
+    match account::remove_associated_key(MAIN_ACCOUNT_HASH) {
+        Ok(()) | Err(RemoveKeyFailure::MissingKey) => (),
+        Err(error) => runtime::revert(error),
+    }
+"#
+            .to_string(),
+            Operation::RemoveMainKey => r#"    // Remove the main account's key.
+    account::remove_associated_key(MAIN_ACCOUNT_HASH).unwrap_or_revert();
+"#
+            .to_string(),
+        }));
+
+        format!(
+            r#"{}#[no_mangle]
+pub extern "C" fn call() {{
+{}}}
 "#,
-            contents = contents,
-            remove_main_account = remove_main_account
-        );
+            contents,
+            statements.join("\n")
+        )
+    }
+
+    /// Returns the `use` declarations for the session code performing `step`.
+    fn step_imports(&self, step: &Step, store_named_keys: bool) -> String {
+        let mut account_types = vec!["AccountHash"];
+        if step.operations.iter().any(|operation| {
+            matches!(
+                operation,
+                Operation::SetKeyManagementThreshold | Operation::SetDeploymentThreshold
+            )
+        }) {
+            account_types.push("ActionType");
+        }
+        let tolerated_failures = self.tolerated_failures(step);
+        account_types.extend(&tolerated_failures);
+        if step
+            .operations
+            .iter()
+            .any(|operation| *operation != Operation::RemoveMainKey)
+        {
+            account_types.push("Weight");
+        }
+        let account_types = |prefix: &str, indent: usize| {
+            if account_types.len() == 1 {
+                account_types[0].to_string()
+            } else {
+                braced_list(&account_types, prefix, indent)
+            }
+        };
+
+        if !store_named_keys && tolerated_failures.is_empty() {
+            return format!(
+                r#"use casper_contract::{{contract_api::account, unwrap_or_revert::UnwrapOrRevert}};
+use casper_types::account::{};
+"#,
+                account_types("use casper_types::account::", 0)
+            );
+        }
+
+        // Reverting with a failure which isn't tolerated needs `runtime`, as does storing named
+        // keys.
+        let casper_contract_imports = r#"use casper_contract::{
+    contract_api::{account, runtime},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+"#;
+        if !store_named_keys {
+            return format!(
+                "{}use casper_types::account::{};\n",
+                casper_contract_imports,
+                account_types("use casper_types::account::", 0)
+            );
+        }
+
+        let (uref_imports, uref_type) = if named_keys::any_urefs(&self.named_keys) {
+            ("AccessRights, ", ", URef")
+        } else {
+            ("", "")
+        };
+        format!(
+            r#"{}use casper_types::{{
+    account::{},
+    {}Key{},
+}};
+"#,
+            casper_contract_imports,
+            account_types("account::", 4),
+            uref_imports,
+            uref_type
+        )
+    }
 
-        contents
+    /// Returns the names of the failure types which the session code performing `step` tolerates
+    /// in idempotent mode, in the order they're imported.
+    ///
+    /// These are the failures caused only by an earlier attempt of the same deploy having already
+    /// made the change.
+    fn tolerated_failures(&self, step: &Step) -> Vec<&'static str> {
+        if !self.idempotent {
+            return vec![];
+        }
+        let mut failures = vec![];
+        if step
+            .operations
+            .iter()
+            .any(|operation| matches!(operation, Operation::AddKey(_)))
+        {
+            failures.push("AddKeyFailure");
+        }
+        if step.operations.contains(&Operation::RemoveMainKey) {
+            failures.push("RemoveKeyFailure");
+        }
+        if step.operations.contains(&Operation::UpdateMainKey) && self.main_key_should_be_deleted()
+        {
+            failures.push("UpdateKeyFailure");
+        }
+        failures
     }
 
-    fn create_main_rs(&self) -> Result<(), Error> {
-        let project_dir = self.project_dir();
-        let src_dir = project_dir.join("src");
-        fs::create_dir_all(&src_dir).unwrap();
+    fn create_contract_sources(&self) -> Result<(), Error> {
+        let contract_dir = self.contract_dir();
+        let sources = self.contract_sources()?;
+        let config_summary = self.configuration_summary(None);
+        let keys = self.provenance_keys();
+        for (relative_path, body) in &sources {
+            let path = contract_dir.join(relative_path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let contents = provenance::with_header(body, &config_summary, &keys);
+            // Only the timestamp differs if the configuration is unchanged, so keep the existing
+            // file to avoid a needless rebuild.
+            let unchanged = fs::read_to_string(&path)
+                .is_ok_and(|old| provenance::same_apart_from_timestamp(&old, &contents));
+            if !unchanged {
+                write_if_changed(path, contents.as_bytes()).unwrap();
+            }
+        }
 
-        let contents = self.main_rs_contents();
-        fs::write(src_dir.join("main.rs"), contents.as_bytes()).unwrap();
+        // Cargo builds every file in `src/bin`, so remove those left by a previous plan with more
+        // deploys.
+        let bin_dir = contract_dir.join("src").join("bin");
+        if let Ok(entries) = fs::read_dir(&bin_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_current = sources
+                    .iter()
+                    .any(|(relative_path, _)| contract_dir.join(relative_path) == path);
+                if !is_current {
+                    fs::remove_file(&path).unwrap();
+                }
+            }
+        }
         Ok(())
     }
 
     fn create_cargo_toml(&self) -> Result<(), Error> {
-        let project_dir = self.project_dir();
+        write_if_changed(
+            self.contract_dir().join("Cargo.toml"),
+            self.cargo_toml_contents(&self.contract_name, self.contract_sources()?.len()),
+        )
+        .unwrap();
+        Ok(())
+    }
 
-        let mut cargo_toml = BufWriter::new(File::create(project_dir.join("Cargo.toml")).unwrap());
-        cargo_toml
-            .write_all(
-                format!(
-                    r#"[package]
+    /// Returns the contents of the contract's `Cargo.toml`, using `package_name` as the name of
+    /// both the package and the main binary, with a further binary for each deploy of the plan
+    /// after the first.
+    fn cargo_toml_contents(&self, package_name: &str, step_count: usize) -> String {
+        let mut step_bins = String::new();
+        for index in 1..step_count {
+            step_bins = format!(
+                r#"{}
+[[bin]]
+name = "{}_step_{}"
+path = "{}"
+bench = false
+doctest = false
+test = false
+"#,
+                step_bins,
+                package_name,
+                index + 1,
+                step_source_path(index).display()
+            );
+        }
+
+        format!(
+            r#"[package]
 name = "{0}"
 version = "0.1.0"
-authors = ["Fraser Hutchison <fraser@casperlabs.io>"]
+authors = [{4}]
 edition = "2018"
 
 [dependencies]
-casper-contract = "1"
-casper-types = "1"
+casper-contract = {1}
+casper-types = {2}
 
 [[bin]]
 name = "{0}"
@@ -288,7 +817,7 @@ path = "src/main.rs"
 bench = false
 doctest = false
 test = false
-
+{3}
 [features]
 default = ["casper-contract/std", "casper-types/std"]
 
@@ -296,107 +825,2400 @@ default = ["casper-contract/std", "casper-types/std"]
 lto = true
 codegen-units = 1
 "#,
-                    self.contract_name
-                )
-                .as_bytes(),
-            )
-            .unwrap();
-        Ok(())
+            package_name,
+            self.casper_contract_dependency.to_manifest_value(),
+            self.casper_types_dependency.to_manifest_value(),
+            step_bins,
+            self.authors
+                .iter()
+                .map(|author| format!("{:?}", author))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
     }
 
     fn create_rust_toolchain(&self) -> Result<(), Error> {
-        let project_dir = self.project_dir();
+        let contents = format!("{}\n", self.toolchain);
+        write_if_changed(self.contract_dir().join("rust-toolchain"), &contents).unwrap();
+        // The test crate is outside the contract crate in cargo-casper's layout, so needs its own.
+        if self.cargo_casper_layout {
+            write_if_changed(self.project_dir().join("rust-toolchain"), &contents).unwrap();
+        }
+        Ok(())
+    }
 
-        let mut rust_toolchain =
-            BufWriter::new(File::create(project_dir.join("rust-toolchain")).unwrap());
-        rust_toolchain
-            .write_all(
-                br#"nightly-2020-12-16
-"#,
+    /// Pins the dependencies in `Cargo.lock`, so that the committed project doesn't pick up newer
+    /// releases of them when rebuilt later.
+    fn create_cargo_lock(&self) -> Result<(), Error> {
+        reproducible_build::pin_dependencies(&self.contract_dir())
+            .map_err(|inner| Error::PinDependencies { inner })
+    }
+
+    /// Writes a skeleton contract with the same dependencies, toolchain and profile as the real one
+    /// and starts compiling it into the shared target dir, so that the dependencies are already
+    /// built by the time the real contract is generated.
+    ///
+    /// Returns `None` without doing anything if there's no shared target dir, or if an identical
+    /// pre-warm build has already been started.
+    pub(super) fn prewarm(&mut self) -> Result<Option<Receiver<CompileEvent>>, Error> {
+        let target_dir = match self.target_dir_location.shared_target_dir() {
+            Some(target_dir) => target_dir,
+            None => return Ok(None),
+        };
+        let prewarm_key = self.cargo_toml_contents(build_cache::PREWARM_PACKAGE_NAME, 1)
+            + &self.toolchain
+            + &self.compile_backend.to_string()
+            + &target_dir.display().to_string();
+        if self.prewarm_key.as_ref() == Some(&prewarm_key) {
+            return Ok(None);
+        }
+        self.prewarm_key = Some(prewarm_key);
+
+        let project_dir = build_cache::prewarm_project_dir(&target_dir);
+        let src_dir = project_dir.join("src");
+        fs::create_dir_all(&src_dir).unwrap();
+        write_cargo_config(&project_dir);
+        write_if_changed(
+            project_dir.join("Cargo.toml"),
+            self.cargo_toml_contents(build_cache::PREWARM_PACKAGE_NAME, 1),
+        )
+        .unwrap();
+        write_if_changed(
+            project_dir.join("rust-toolchain"),
+            format!("{}\n", self.toolchain),
+        )
+        .unwrap();
+        write_if_changed(src_dir.join("main.rs"), build_cache::PREWARM_MAIN_RS).unwrap();
+        if self.compile_backend == (CompileBackend::Docker { image: None }) {
+            write_if_changed(
+                project_dir.join(compile_backend::DOCKERFILE),
+                compile_backend::dockerfile_contents(&self.toolchain),
             )
             .unwrap();
+        }
+
+        let job = CompileJob {
+            project_dir,
+            bin_names: vec![build_cache::PREWARM_PACKAGE_NAME.to_string()],
+            source_paths: vec![],
+            toolchain: self.toolchain.clone(),
+            compile_backend: self.compile_backend.clone(),
+            run_wasm_opt: false,
+            run_wasm_strip: false,
+            max_deploy_size: self.max_deploy_size,
+            timeout: self.compile_timeout,
+            reproducible_build: false,
+            vendor_dependencies: false,
+            has_tests: false,
+            shared_target_dir: Some(target_dir),
+            use_sccache: self.use_sccache,
+            // Pre-warm builds run in the background, so aren't cancelled along with compilations.
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || job.run_and_report(&sender));
+        Ok(Some(receiver))
+    }
+
+    /// Returns the README's instructions for building inside Docker, or an empty string if the host
+    /// toolchain is used.
+    fn docker_readme_note(&self) -> String {
+        let (image, build_image) = match &self.compile_backend {
+            CompileBackend::Host => return String::new(),
+            CompileBackend::Docker { image: Some(image) } => (image.clone(), String::new()),
+            CompileBackend::Docker { image: None } => {
+                let image = compile_backend::default_image_tag(&self.toolchain);
+                let build_image = format!(
+                    "docker build --tag {} - < {}\n",
+                    image,
+                    compile_backend::DOCKERFILE
+                );
+                (image, build_image)
+            }
+        };
+        format!(
+            r#"
+Alternatively, with only Docker installed, build inside a container:
+
+```console
+{build_image}docker run --rm --volume "$PWD":/project --workdir /project --user "$(id -u):$(id -g)" \
+    {image} cargo build --release
+```
+"#,
+            build_image = build_image,
+            image = image
+        )
+    }
+
+    fn create_dockerfile(&self) -> Result<(), Error> {
+        if self.compile_backend != (CompileBackend::Docker { image: None }) {
+            return Ok(());
+        }
+        write_if_changed(
+            self.contract_dir().join(compile_backend::DOCKERFILE),
+            compile_backend::dockerfile_contents(&self.toolchain).as_bytes(),
+        )
+        .unwrap();
         Ok(())
     }
 
-    fn compile_contract(&mut self) -> Result<Receiver<String>, Error> {
+    fn compile_contract(&mut self) -> Result<Receiver<CompileEvent>, Error> {
+        let step_count = self.contract_sources()?.len();
         let (sender, receiver) = mpsc::channel();
-        let project_dir = self.project_dir();
-        let contract_name = self.contract_name.clone();
+        // A new flag, so that cancelling this compilation can't affect an earlier one still running.
+        self.compile_cancelled = Arc::new(AtomicBool::new(false));
+        let job = CompileJob {
+            project_dir: self.contract_dir(),
+            bin_names: (0..step_count)
+                .map(|index| self.step_bin_name(index))
+                .collect(),
+            source_paths: (0..step_count).map(step_source_path).collect(),
+            toolchain: self.toolchain.clone(),
+            compile_backend: self.compile_backend.clone(),
+            run_wasm_opt: self.run_wasm_opt,
+            run_wasm_strip: self.run_wasm_strip,
+            max_deploy_size: self.max_deploy_size,
+            timeout: self.compile_timeout,
+            reproducible_build: self.reproducible_build,
+            vendor_dependencies: self.vendor_dependencies,
+            has_tests: self.has_tests(),
+            shared_target_dir: self.target_dir_location.shared_target_dir(),
+            use_sccache: self.use_sccache,
+            cancelled: Arc::clone(&self.compile_cancelled),
+        };
+
+        let compile_worker = thread::spawn(move || job.run_and_report(&sender));
+
+        self.compile_worker = Some(compile_worker);
+
+        Ok(receiver)
+    }
 
-        let compile_worker = thread::spawn(move || {
+    /// Cancels the compilation most recently started, if it's still running.
+    pub(super) fn cancel_compilation(&self) {
+        self.compile_cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub(super) fn run_tests(&mut self) -> Result<Receiver<TestEvent>, Error> {
+        // The tests need the compiled contract, so wait for any in-progress compilation to finish.
+        if let Some(compile_worker) = self.compile_worker.take() {
+            let _ = compile_worker.join();
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let tests_dir = self.project_dir().join(test_project::TESTS_DIR);
+
+        thread::spawn(move || {
             let mut command = Command::new("cargo");
-            command.args(&["build", "--release"]);
-            command.current_dir(&project_dir);
+            command.arg("test");
+            command.current_dir(&tests_dir);
 
-            let _ = sender.send(format!(
+            let _ = sender.send(TestEvent::Output(format!(
                 "Running {:?} in {}",
                 command,
-                project_dir.display()
-            ));
-            let _ = sender.send(String::new());
-
-            let mut child = command
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .unwrap();
-            let stdout = child.stdout.take().unwrap();
-            let stdout_reader = BufReader::new(stdout);
-            let stdout_lines = stdout_reader.lines();
-
-            let stderr = child.stderr.take().unwrap();
-            let stderr_reader = BufReader::new(stderr);
-            let stderr_lines = stderr_reader.lines();
-
-            let sender_clone = sender.clone();
-            let stderr_thread = thread::spawn(move || {
-                for line in stderr_lines {
-                    let send_res = sender_clone.send(line.unwrap());
-                    if let Err(error) = send_res {
-                        println!("stopping sending stderr: {}", error);
-                        break;
-                    };
+                tests_dir.display()
+            )));
+            let _ = sender.send(TestEvent::Output(String::new()));
+
+            let passed = match run_streaming_output(&mut command, &sender, TestEvent::Output) {
+                Ok(status) => status.success(),
+                Err(error) => {
+                    let _ = sender.send(TestEvent::Output(format!(
+                        "Failed to run cargo test: {}",
+                        error
+                    )));
+                    false
                 }
-            });
+            };
+            let _ = sender.send(TestEvent::Finished { passed });
+        });
 
-            for line in stdout_lines {
-                if sender.send(line.unwrap()).is_err() {
-                    println!("stopping sending stdout");
-                    break;
-                };
-            }
+        Ok(receiver)
+    }
 
-            stderr_thread.join().unwrap();
-            child.wait().unwrap();
+    /// Removes the build output of the generated project and its test crate, and the shared
+    /// target dir if one is used.
+    ///
+    /// Waits for any in-progress compilation to finish first.  Returns the dirs removed.
+    pub(super) fn clean_build_artifacts(&mut self) -> Result<Vec<PathBuf>, Error> {
+        if let Some(compile_worker) = self.compile_worker.take() {
+            let _ = compile_worker.join();
+        }
 
-            let _ = sender.send(String::new());
-            let _ = sender.send("Smart contract source code:".to_string());
-            let _ = sender.send(
-                project_dir
-                    .join("src")
-                    .join("main.rs")
-                    .display()
-                    .to_string(),
-            );
-            let _ = sender.send(String::new());
-            let _ = sender.send("Compiled smart contract:".to_string());
-            let _ = sender.send(
-                project_dir
-                    .join("target")
-                    .join("wasm32-unknown-unknown")
-                    .join("release")
-                    .join(format!("{}.wasm", contract_name))
-                    .display()
-                    .to_string(),
-            );
-        });
+        let make_error = |path: &Path, inner: String| Error::CleanBuildArtifacts {
+            path: path.display().to_string(),
+            inner,
+        };
+        let mut removed = vec![];
+        // Without a contract name, the project dir would be the root dir itself.
+        if !self.contract_name.is_empty() {
+            let target_dirs = [
+                self.contract_dir().join("target"),
+                self.project_dir()
+                    .join(test_project::TESTS_DIR)
+                    .join("target"),
+            ];
+            for target_dir in target_dirs.iter().filter(|dir| dir.exists()) {
+                fs::remove_dir_all(target_dir)
+                    .map_err(|error| make_error(target_dir, error.to_string()))?;
+                removed.push(target_dir.clone());
+            }
+        }
+        if let Some(target_dir) = self.target_dir_location.shared_target_dir() {
+            if build_cache::remove_shared_target_dir(&target_dir)
+                .map_err(|inner| make_error(&target_dir, inner))?
+            {
+                removed.push(target_dir);
+            }
+            // The pre-warmed dependencies have gone with it.
+            self.prewarm_key = None;
+        }
+        Ok(removed)
+    }
 
-        self.compile_worker = Some(compile_worker);
+    /// Returns `Ok` if no initial transfer is configured or it funds the main account, else `Err`.
+    fn validate_initial_transfer(&self) -> Result<(), Error> {
+        match (&self.initial_transfer, self.associated_keys.first()) {
+            (Some(initial_transfer), Some(main_key)) => {
+                initial_transfer.validate_target(main_key.account_hash)
+            }
+            (Some(_), None) => Err(Error::NoKeys),
+            (None, _) => Ok(()),
+        }
+    }
 
-        Ok(receiver)
+    /// Returns `Ok` if the other settings can be used with the contract mode, else `Err`.
+    fn validate_contract_mode(&self) -> Result<(), Error> {
+        self.contract_mode.validate()?;
+        if self.contract_mode == ContractMode::Setup {
+            return Ok(());
+        }
+        let inner = if !self.named_keys.is_empty() {
+            "named keys can only be stored by the multisig account setup contract"
+        } else if self.prebuilt_session_wasm.is_some() {
+            "the prebuilt session Wasm can only perform the multisig account setup"
+        } else {
+            return Ok(());
+        };
+        Err(Error::InvalidContractMode {
+            inner: inner.to_string(),
+        })
     }
 
-    fn project_dir(&self) -> PathBuf {
-        self.root_dir.join(&self.contract_name)
+    /// Returns the configured associated keys removed by the single-key recovery contract, or
+    /// none in any other mode.
+    fn keys_removed_on_revert(&self) -> Vec<AccountHash> {
+        let recovery_key = match &self.contract_mode {
+            ContractMode::RevertToSingleKey { recovery_key } => recovery_key,
+            _ => return vec![],
+        };
+        let recovery_key = match AccountHash::from_formatted_str(recovery_key.trim()) {
+            Ok(recovery_key) => recovery_key,
+            Err(_) => return vec![],
+        };
+        let configured_keys = self
+            .associated_keys
+            .iter()
+            .map(|key| key.account_hash)
+            .collect::<Vec<_>>();
+        contract_mode::keys_removed_on_revert(recovery_key, &configured_keys)
+    }
+
+    /// Returns whether a test crate is generated, which is only the case for the multisig account
+    /// setup since the tests execute against a newly-created account, and only when targeting
+    /// Casper 1.x.
+    pub(super) fn has_tests(&self) -> bool {
+        self.contract_mode == ContractMode::Setup && self.codegen_target == CodegenTarget::Casper1
     }
+
+    pub(super) fn submit_initial_transfer(
+        &self,
+        node_address: &str,
+        chain_name: &str,
+    ) -> Result<Receiver<TransferEvent>, Error> {
+        self.validate_initial_transfer()?;
+        if rpc::has_custom_headers() {
+            return Err(Error::InvalidInitialTransfer {
+                inner: "casper-client can't send the transfer with the network's custom headers"
+                    .to_string(),
+            });
+        }
+        let mut command = match &self.initial_transfer {
+            // Not failed over to other nodes, as a retried transfer would be a second transfer.
+            Some(initial_transfer) => {
+                initial_transfer.command(rpc::primary_node_address(node_address), chain_name)
+            }
+            None => {
+                return Err(Error::InvalidInitialTransfer {
+                    inner: "no initial transfer has been configured".to_string(),
+                })
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(TransferEvent::Output(format!("Running {:?}", command)));
+            let _ = sender.send(TransferEvent::Output(String::new()));
+
+            let succeeded = match run_streaming_output(&mut command, &sender, TransferEvent::Output)
+            {
+                Ok(status) => status.success(),
+                Err(error) => {
+                    let _ = sender.send(TransferEvent::Output(format!(
+                        "Failed to run casper-client: {}",
+                        error
+                    )));
+                    false
+                }
+            };
+            let _ = sender.send(TransferEvent::Finished { succeeded });
+        });
+
+        Ok(receiver)
+    }
+
+    /// Returns the account hashes and weights of all associated keys, main key first.
+    fn keys_and_weights(&self) -> Vec<(AccountHash, u8)> {
+        self.associated_keys
+            .iter()
+            .map(|key| (key.account_hash, key.weight.value()))
+            .collect()
+    }
+
+    fn main_key_should_be_deleted(&self) -> bool {
+        self.associated_keys
+            .first()
+            .map(AssociatedKey::remove_after_creation)
+            .unwrap_or_default()
+    }
+
+    /// Returns a rehearsal of the setup deploy of `wasm` on the NCTL network with the given assets
+    /// dir.
+    pub(super) fn local_network_rehearsal(
+        &self,
+        wasm: &Path,
+        assets_dir: &Path,
+        funding_user: u32,
+        main_secret_key: &str,
+    ) -> Result<Rehearsal, Error> {
+        let make_error = |inner: &str| Error::LocalNetwork {
+            inner: inner.to_string(),
+        };
+
+        if self.contract_mode != ContractMode::Setup {
+            return Err(make_error("only the setup contract can be tested"));
+        }
+        if self.contract_sources()?.len() > 1 {
+            return Err(make_error(
+                "the setup needs more than one deploy, so can't be tested automatically",
+            ));
+        }
+        let session_args = self.session_args()?;
+
+        Ok(Rehearsal {
+            assets_dir: assets_dir.to_path_buf(),
+            funding_user,
+            main_secret_key: PathBuf::from(main_secret_key),
+            wasm: wasm.to_path_buf(),
+            session_args,
+            max_deploy_size: self.max_deploy_size,
+            expected: self.expected_account(),
+        })
+    }
+
+    /// Returns every problem with the configuration, from the weights and thresholds through to
+    /// the contract mode and other settings, or none if there are no keys yet.
+    pub(super) fn validate(&self) -> Vec<ValidationIssue> {
+        if self.associated_keys.is_empty() {
+            return vec![];
+        }
+        let mut issues = vec![];
+        if self.contract_mode == ContractMode::Setup {
+            let weights = self
+                .associated_keys
+                .iter()
+                .map(|key| key.weight.value())
+                .collect::<Vec<_>>();
+            issues = validation::check_weights(&Target {
+                weights: &weights,
+                remove_main_key: self.main_key_should_be_deleted(),
+                key_management_threshold: self.key_management_weight.value(),
+                deployment_threshold: self.deployment_weight.value(),
+            });
+            // The planner's error would only repeat those already found with the weights.
+            if !issues.iter().any(|issue| issue.severity == Severity::Error) {
+                let result = if self.uses_runtime_args() {
+                    self.validate_runtime_args_session()
+                } else {
+                    self.plan().map(|_| ())
+                };
+                if let Err(error) = result {
+                    issues.push(ValidationIssue::error(error));
+                }
+            }
+        }
+        for result in [
+            self.validate_contract_mode(),
+            self.validate_initial_transfer(),
+        ] {
+            if let Err(error) = result {
+                issues.push(ValidationIssue::error(error));
+            }
+        }
+        // Errors first, as they have to be fixed before the contract can be generated.
+        issues.sort_by_key(|issue| issue.severity != Severity::Error);
+        issues
+    }
+
+    /// Returns the associated keys and thresholds the setup contract leaves the account with.
+    pub(super) fn expected_account(&self) -> ExpectedAccount {
+        let associated_keys = self
+            .keys_and_weights()
+            .into_iter()
+            .skip(if self.main_key_should_be_deleted() {
+                1
+            } else {
+                0
+            })
+            .collect();
+        ExpectedAccount {
+            associated_keys,
+            key_management_threshold: self.key_management_weight.value(),
+            deployment_threshold: self.deployment_weight.value(),
+        }
+    }
+
+    /// Returns the selected network's profile, if one is selected.
+    pub(super) fn selected_network(&self) -> Option<&NetworkProfile> {
+        let name = self.selected_network.as_ref()?;
+        self.networks.iter().find(|profile| profile.name == *name)
+    }
+
+    /// Selects the network with the given name, or none, applying its protocol profile and max
+    /// deploy size.
+    pub(super) fn select_network(&mut self, name: Option<&str>) -> Result<(), Error> {
+        let profile = match name {
+            Some(name) => self
+                .networks
+                .iter()
+                .find(|profile| profile.name == name)
+                .cloned()
+                .ok_or_else(|| Error::InvalidNetworkProfile {
+                    name: name.to_string(),
+                    inner: "no network has this name".to_string(),
+                })?,
+            None => {
+                rpc::set_connection(ConnectionSettings::default());
+                self.selected_network = None;
+                return Ok(());
+            }
+        };
+        if let Some(version) = &profile.protocol_version {
+            ProtocolProfile::for_protocol_version(version)?.apply(self);
+        }
+        self.max_deploy_size = profile.max_deploy_size;
+        rpc::set_connection(profile.connection);
+        self.selected_network = Some(profile.name);
+        Ok(())
+    }
+
+    /// Adds the given network profile, replacing any existing one with the same name, and
+    /// re-applies it if it's selected.
+    pub(super) fn save_network(&mut self, profile: NetworkProfile) -> Result<(), Error> {
+        profile.validate()?;
+        let name = profile.name.clone();
+        match self
+            .networks
+            .iter_mut()
+            .find(|existing| existing.name == name)
+        {
+            Some(existing) => *existing = profile,
+            None => self.networks.push(profile),
+        }
+        if self.selected_network.as_ref() == Some(&name) {
+            self.select_network(Some(&name))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the runtime args to be passed to the prebuilt session Wasm.
+    pub(super) fn runtime_args(&self) -> Result<RuntimeArgs, Error> {
+        if self.associated_keys.is_empty() {
+            return Err(Error::NoKeys);
+        }
+        prebuilt_session::runtime_args(
+            &self.keys_and_weights(),
+            self.main_key_should_be_deleted(),
+            self.key_management_weight.value(),
+            self.deployment_weight.value(),
+        )
+    }
+
+    /// Returns the runtime args a deploy of the session Wasm should pass: those of the prebuilt
+    /// session Wasm if used, else none.
+    pub(super) fn session_args(&self) -> Result<RuntimeArgs, Error> {
+        if self.prebuilt_session_wasm.is_some() {
+            self.runtime_args()
+        } else {
+            Ok(RuntimeArgs::new())
+        }
+    }
+
+    /// Returns the runtime args to be passed to the prebuilt session Wasm, formatted as
+    /// casper-client `--session-arg` values.
+    pub(super) fn casper_client_session_args(&self) -> Vec<String> {
+        if self.associated_keys.is_empty() {
+            return Vec::new();
+        }
+        prebuilt_session::casper_client_session_args(
+            &self.keys_and_weights(),
+            self.main_key_should_be_deleted(),
+            self.key_management_weight.value(),
+            self.deployment_weight.value(),
+        )
+    }
+
+    /// Returns `Ok` if the configuration can be reached by the generic session contract, else
+    /// `Err`.
+    fn validate_runtime_args_session(&self) -> Result<(), Error> {
+        let steps = self.plan()?;
+        let default_operations = plan::default_operations(
+            self.associated_keys.len(),
+            self.main_key_should_be_deleted(),
+        );
+        if steps.len() > 1 || steps[0].operations != default_operations {
+            return Err(Error::UnreachableConfiguration {
+                inner: "the generic session contract makes every change in a single deploy signed \
+                    by the main key, in a fixed order"
+                    .to_string(),
+            });
+        }
+        if let Some(named_key) = self.named_keys.first() {
+            return Err(Error::InvalidNamedKey {
+                name: named_key.name.clone(),
+                inner: "named keys are not supported by the generic session contract".to_string(),
+            });
+        }
+        if self.idempotent {
+            return Err(Error::InvalidContractMode {
+                inner: "the generic session contract can't be made idempotent".to_string(),
+            });
+        }
+        // Ensure the args can be built.
+        self.runtime_args().map(|_| ())
+    }
+
+    /// Writes the runtime args for the generic session contract as casper-client `--session-arg`
+    /// values, one per line, returning the path of the file.
+    fn create_session_args(&self) -> PathBuf {
+        let session_args_path = self.contract_dir().join(SESSION_ARGS_FILE);
+        write_if_changed(
+            &session_args_path,
+            format!("{}\n", self.casper_client_session_args().join("\n")),
+        )
+        .unwrap();
+        session_args_path
+    }
+
+    /// Returns whether the session code is the generic contract, and hence needs runtime args.
+    fn uses_runtime_args(&self) -> bool {
+        self.prebuilt_session_wasm.is_some()
+            || (self.runtime_args_template && self.contract_mode == ContractMode::Setup)
+    }
+
+    /// Copies the prebuilt session Wasm into the project and writes the matching session args
+    /// alongside it.  No compilation is performed.
+    fn create_prebuilt_session_project(
+        &mut self,
+        wasm_path: &Path,
+    ) -> Result<Receiver<CompileEvent>, Error> {
+        let file = wasm_path.display().to_string();
+        let wasm_bytes = fs::read(wasm_path).map_err(|error| Error::ReadWasmFile {
+            file: file.clone(),
+            inner: error.to_string(),
+        })?;
+        // Checked again, as the file may have been replaced since it was chosen.
+        prebuilt_session::validate_wasm(
+            &file,
+            &wasm_bytes,
+            self.prebuilt_session_wasm_hash.as_deref(),
+        )?;
+        self.validate_runtime_args_session()?;
+
+        let project_dir = self.project_dir();
+        let wasm_copy = project_dir.join(format!("{}.wasm", self.contract_name));
+        write_if_changed(&wasm_copy, &wasm_bytes).unwrap();
+
+        let session_args_path = self.create_session_args();
+
+        self.create_readme()?;
+        self.create_makefile()?;
+        self.create_test_project()?;
+        self.create_git_repo(Some(&wasm_bytes))?;
+
+        let (sender, receiver) = mpsc::channel();
+        send_output(&sender, format!("Using prebuilt session Wasm {}", file));
+        send_output(
+            &sender,
+            format!("Blake2b hash: {}", prebuilt_session::wasm_hash(&wasm_bytes)),
+        );
+        if self.prebuilt_session_wasm_hash.is_none() {
+            let _ = sender.send(CompileEvent::Warning(
+                "the prebuilt session Wasm is unverified: its hash wasn't checked against that of \
+                an audited build, so check the hash above before signing a deploy of it"
+                    .to_string(),
+            ));
+        }
+        send_output(&sender, "");
+        send_output(&sender, "Session Wasm:");
+        send_output(&sender, wasm_copy.display().to_string());
+        send_output(&sender, "");
+        send_output(&sender, "Session args:");
+        send_output(&sender, session_args_path.display().to_string());
+        send_output(&sender, "");
+        send_output(&sender, "Example deploy command:");
+        send_output(&sender, self.put_deploy_command(&wasm_copy));
+        if let Some(warning) = deploy_limits::size_warning(&wasm_copy, self.max_deploy_size) {
+            let _ = sender.send(CompileEvent::Warning(warning));
+        }
+
+        let _ = sender.send(CompileEvent::Succeeded {
+            wasm_path: wasm_copy,
+        });
+
+        Ok(receiver)
+    }
+
+    /// Returns an example casper-client command for deploying the given session Wasm.
+    fn put_deploy_command(&self, session_path: &Path) -> String {
+        self.put_deploy_args(session_path, &EXAMPLE_PLACEHOLDERS)
+            .join(" \\\n    ")
+    }
+
+    /// Returns the casper-client command for deploying the given session Wasm, split into one
+    /// entry per option.
+    fn put_deploy_args(
+        &self,
+        session_path: &Path,
+        placeholders: &DeployPlaceholders,
+    ) -> Vec<String> {
+        let mut put_deploy_args = vec![
+            "casper-client put-deploy".to_string(),
+            format!("--node-address {}", placeholders.node_address),
+            format!("--chain-name {}", placeholders.chain_name),
+            format!("--secret-key {}", placeholders.secret_key),
+            format!("--payment-amount {}", placeholders.payment_amount),
+            format!("--session-path {}", session_path.display()),
+        ];
+        if self.uses_runtime_args() {
+            put_deploy_args.extend(
+                self.casper_client_session_args()
+                    .iter()
+                    .map(|arg| format!("--session-arg \"{}\"", arg)),
+            );
+        }
+        put_deploy_args
+    }
+
+    /// Returns the contents of the project's Makefile, with `build`, `strip`, `test` and `deploy`
+    /// targets.
+    fn makefile_contents(&self) -> String {
+        let session_path = self.relative_session_path();
+        let (build_recipe, strip_recipe) = if self.prebuilt_session_wasm.is_some() {
+            (
+                "\t@echo \"Using the prebuilt session Wasm; nothing to build.\"\n".to_string(),
+                "\t@echo \"Not stripping the prebuilt session Wasm as that would change its hash.\"\n"
+                    .to_string(),
+            )
+        } else {
+            let cd = if self.cargo_casper_layout {
+                format!("cd {} && ", cargo_casper::CONTRACT_DIR)
+            } else {
+                String::new()
+            };
+            let mut build_recipe = if self.reproducible_build {
+                format!(
+                    "\t{}cargo build --release {}\n",
+                    cd,
+                    reproducible_build::BUILD_ARGS.join(" ")
+                )
+            } else {
+                format!("\t{}cargo build --release\n", cd)
+            };
+            if self.run_wasm_opt {
+                build_recipe.push_str("\twasm-opt -Oz -o $(WASM) $(WASM)\n");
+            }
+            (build_recipe, "\twasm-strip $(WASM)\n".to_string())
+        };
+
+        let mut deploy_recipe = makefile_variable_checks(
+            "deploy",
+            &[
+                ("NODE", "http://localhost:7777"),
+                ("CHAIN_NAME", "casper-test"),
+                ("SECRET_KEY", "secret_key.pem"),
+                ("PAYMENT_AMOUNT", "10000000000"),
+            ],
+        );
+        deploy_recipe = format!(
+            "{}\t{}\n",
+            deploy_recipe,
+            self.put_deploy_args(Path::new("$(WASM)"), &MAKEFILE_PLACEHOLDERS)
+                .join(" \\\n\t    ")
+        );
+
+        let (fund_usage, fund_target) = match &self.initial_transfer {
+            Some(initial_transfer) => {
+                let mut fund_recipe = makefile_variable_checks(
+                    "fund",
+                    &[
+                        ("NODE", "http://localhost:7777"),
+                        ("CHAIN_NAME", "casper-test"),
+                    ],
+                );
+                fund_recipe = format!(
+                    "{}\t{}\n",
+                    fund_recipe,
+                    initial_transfer
+                        .transfer_args(&initial_transfer::MAKEFILE_PLACEHOLDERS)
+                        .join(" \\\n\t    ")
+                );
+                (
+                    "#   make fund NODE=<node address> CHAIN_NAME=<chain name>\n",
+                    format!(
+                        "\nTRANSFER_ID ?= $(shell date +%s)\n\n.PHONY: fund\n\nfund:\n{}",
+                        fund_recipe
+                    ),
+                )
+            }
+            None => ("", String::new()),
+        };
+
+        let (test_usage, test_phony, test_target) = if self.has_tests() {
+            (
+                "#   make test\n",
+                " test",
+                format!(
+                    "test: build\n\tcd {} && cargo test\n\n",
+                    test_project::TESTS_DIR
+                ),
+            )
+        } else {
+            ("", "", String::new())
+        };
+
+        format!(
+            r#"# Generated by the Casper Multisig Account Creation Tool.
+#
+# Usage:
+#   make build
+#   make strip
+{test_usage}{fund_usage}#   make deploy NODE=<node address> CHAIN_NAME=<chain name> SECRET_KEY=<path> PAYMENT_AMOUNT=<motes>
+
+WASM := {session_path}
+
+.PHONY: build strip{test_phony} deploy
+
+build:
+{build_recipe}
+strip: build
+{strip_recipe}
+{test_target}deploy: build
+{deploy_recipe}{fund_target}"#,
+            test_usage = test_usage,
+            test_phony = test_phony,
+            test_target = test_target,
+            fund_usage = fund_usage,
+            fund_target = fund_target,
+            session_path = session_path.display(),
+            build_recipe = build_recipe,
+            strip_recipe = strip_recipe,
+            deploy_recipe = deploy_recipe,
+        )
+    }
+
+    fn create_makefile(&self) -> Result<(), Error> {
+        write_if_changed(
+            self.project_dir().join("Makefile"),
+            self.makefile_contents().as_bytes(),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    /// Writes the CI workflow building the contract, if enabled, and removes any generated earlier
+    /// for another provider.
+    fn create_ci_workflow(&self) -> Result<(), Error> {
+        let project_dir = self.project_dir();
+        for provider in CiProvider::ALL.iter() {
+            let path = project_dir.join(provider.workflow_path());
+            // Only remove workflows generated by this tool, not any written by the user.
+            let stale = Some(*provider) != self.ci_provider
+                && fs::read_to_string(&path)
+                    .is_ok_and(|contents| contents.starts_with(ci_workflow::HEADER));
+            if stale {
+                fs::remove_file(&path).unwrap();
+            }
+        }
+        let provider = match self.ci_provider {
+            Some(provider) => provider,
+            None => return Ok(()),
+        };
+
+        let wasm_paths = (0..self.contract_sources()?.len())
+            .map(|index| {
+                // Used in the workflow's shell commands, so always uses forward slashes.
+                format!(
+                    "target/wasm32-unknown-unknown/release/{}.wasm",
+                    self.step_bin_name(index)
+                )
+            })
+            .collect();
+        let contract_dir_prefix = self.contract_dir_prefix();
+        let config = WorkflowConfig {
+            contract_dir_prefix: &contract_dir_prefix,
+            toolchain: &self.toolchain,
+            build_args: if self.reproducible_build {
+                reproducible_build::BUILD_ARGS
+            } else {
+                &[]
+            },
+            wasm_paths,
+            run_wasm_opt: self.run_wasm_opt,
+            artifact_name: &format!("{}-wasm", self.contract_name),
+        };
+        let path = project_dir.join(provider.workflow_path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        write_if_changed(path, ci_workflow::workflow_contents(provider, &config)).unwrap();
+        Ok(())
+    }
+
+    /// Returns the contents of the project's README, describing the configuration and how to build,
+    /// deploy and verify the contract.
+    fn readme_contents(&self) -> String {
+        let main_account_hash = self
+            .associated_keys
+            .first()
+            .map(|key| key.account_hash.to_formatted_string())
+            .unwrap_or_default();
+        let (summary, configuration, signing_note, verify_note) =
+            self.readme_mode_sections(&main_account_hash);
+
+        let build_section = if self.prebuilt_session_wasm.is_some() {
+            let verification = match &self.prebuilt_session_wasm_hash {
+                Some(hash) => format!(
+                    "Its Blake2b hash, `{}`, was checked against the one published with the \
+                    audited build.",
+                    hash
+                ),
+                None => format!(
+                    "**It's unverified**: its hash wasn't checked against the one published with \
+                    an audited build, so check the Blake2b hash of `{}.wasm` before signing.",
+                    self.contract_name
+                ),
+            };
+            format!(
+                r#"This project uses a prebuilt session Wasm, `{name}.wasm`, compiled from the generic session
+contract which reads the keys, weights and thresholds from its runtime args.  No compilation is
+required.  The runtime args matching the configuration above are listed in `session_args.txt`.
+
+{verification}
+"#,
+                name = self.contract_name,
+                verification = verification
+            )
+        } else {
+            format!(
+                r#"Building requires the `{toolchain}` Rust toolchain with the `wasm32-unknown-unknown` target:
+
+```console
+rustup toolchain install {toolchain}
+rustup target add wasm32-unknown-unknown --toolchain {toolchain}
+```
+
+Then from {build_dir} run:
+
+```console
+cargo build --release{build_args}
+```
+
+The compiled contract is written to
+`{wasm_path}`.
+
+The dependencies are pinned in `Cargo.lock`, so rebuilding later uses the same versions rather than
+any released since the project was generated.
+{runtime_args_note}{docker_note}{reproducible_note}"#,
+                toolchain = self.toolchain,
+                build_dir = if self.cargo_casper_layout {
+                    format!("the `{}` directory", cargo_casper::CONTRACT_DIR)
+                } else {
+                    "this directory".to_string()
+                },
+                wasm_path = self.relative_session_path().display(),
+                build_args = if self.reproducible_build {
+                    format!(" {}", reproducible_build::BUILD_ARGS.join(" "))
+                } else {
+                    String::new()
+                },
+                runtime_args_note = if self.uses_runtime_args() {
+                    r#"
+This is the generic session contract, which reads the keys, weights and thresholds from its runtime
+args rather than having them baked in, so the same audited Wasm can be reused for other accounts.
+The runtime args matching the configuration above are listed in `session_args.txt`, and are passed
+by the deploy command below.
+"#
+                } else {
+                    ""
+                },
+                docker_note = self.docker_readme_note(),
+                reproducible_note = if self.reproducible_build {
+                    format!(
+                        r#"
+{vendored}`{manifest}` records the toolchain, the hashes of the inputs and the Blake2b hash of the
+compiled contract; rebuilding as above should produce a bit-identical Wasm.
+"#,
+                        vendored = if self.vendor_dependencies {
+                            "The dependencies are vendored in `vendor`.  "
+                        } else {
+                            ""
+                        },
+                        manifest = reproducible_build::BUILD_MANIFEST
+                    )
+                } else {
+                    String::new()
+                }
+            )
+        };
+
+        format!(
+            r#"# {name}
+
+{summary}  Generated by the Casper Multisig Account Creation Tool.
+
+## Configuration
+
+{configuration}{named_keys_section}
+## Building
+
+{build_section}{funding_section}
+## Deploying
+
+{signing_note}
+
+```console
+{put_deploy_command}
+```
+
+Note the deploy hash which is printed, then check it executed successfully:
+
+```console
+casper-client get-deploy --node-address <NODE_ADDRESS> <DEPLOY_HASH>
+```
+{later_deploys_section}{testing_section}{ci_section}
+## Makefile
+
+The `Makefile` wraps the steps above:
+
+```console
+make build
+make strip
+{make_test}{make_fund}make deploy NODE=<NODE_ADDRESS> CHAIN_NAME=<CHAIN_NAME> SECRET_KEY=<MAIN_ACCOUNT_SECRET_KEY_PATH> \
+    PAYMENT_AMOUNT=<PAYMENT_AMOUNT>
+```
+
+`make strip` requires `wasm-strip` from [wabt](https://github.com/WebAssembly/wabt).
+
+## Verifying
+
+{verify_note}
+
+```console
+casper-client get-state-root-hash --node-address <NODE_ADDRESS>
+casper-client query-state --node-address <NODE_ADDRESS> --state-root-hash <STATE_ROOT_HASH> \
+    --key {main_account_hash}
+```
+"#,
+            name = self.contract_name,
+            main_account_hash = main_account_hash,
+            summary = summary,
+            configuration = configuration,
+            signing_note = signing_note,
+            verify_note = verify_note,
+            named_keys_section = self.readme_named_keys_section(),
+            funding_section = self.readme_funding_section(),
+            later_deploys_section = self.readme_later_deploys_section(),
+            make_fund = if self.initial_transfer.is_some() {
+                "make fund NODE=<NODE_ADDRESS> CHAIN_NAME=<CHAIN_NAME>\n"
+            } else {
+                ""
+            },
+            build_section = build_section,
+            put_deploy_command = self.put_deploy_command(&self.relative_session_path()),
+            testing_section = self.readme_testing_section(),
+            ci_section = self.readme_ci_section(),
+            make_test = if self.has_tests() { "make test\n" } else { "" },
+        )
+    }
+
+    /// Returns the README's description of the contract, its configuration table(s), the
+    /// requirements for signing the deploy and what to check once it has executed.
+    fn readme_mode_sections(&self, main_account_hash: &str) -> (String, String, String, String) {
+        let (summary, configuration, constraint, verify_note) = match &self.contract_mode {
+            ContractMode::Setup => {
+                return (
+                    format!(
+                        "Session code which converts the account `{}` into a multisig account.",
+                        main_account_hash
+                    ),
+                    self.readme_setup_configuration(),
+                    format!(
+                        r#"The deploy must be sent from the main account `{}` and signed by its secret key.
+The account must already exist on chain and hold enough CSPR to pay for the deploy.{}"#,
+                        main_account_hash,
+                        if self.idempotent {
+                            r#"
+
+The session code is idempotent: keys already associated are updated rather than added, and keys
+already removed are skipped.  It can therefore be sent again against a partially configured
+account, e.g. if it's unclear whether an earlier attempt executed, provided it's signed by keys
+meeting the account's key-management threshold at that point."#
+                        } else {
+                            ""
+                        }
+                    ),
+                    r#"Once the deploy has executed, query the account and check its `associated_keys` and
+`action_thresholds` match the configuration above:"#
+                        .to_string(),
+                )
+            }
+            ContractMode::RemoveKeys { account_hashes } => {
+                let mut keys_table =
+                    "| Index | Associated key to remove |\n|-------|--------------------------|\n"
+                        .to_string();
+                for (index, account_hash) in account_hashes.iter().enumerate() {
+                    keys_table =
+                        format!("{}| {} | `{}` |\n", keys_table, index, account_hash.trim());
+                }
+                (
+                    format!(
+                        "Session code which removes {} associated key(s) from the account `{}`, \
+                        leaving its other associated keys and action thresholds unchanged.",
+                        account_hashes.len(),
+                        main_account_hash
+                    ),
+                    keys_table,
+                    "The remaining keys must still meet both action thresholds, otherwise the deploy \
+                    will fail.",
+                    r#"Once the deploy has executed, query the account and check none of the keys above remain in
+its `associated_keys`:"#,
+                )
+            }
+            ContractMode::UpdateThresholds {
+                key_management,
+                deployment,
+            } => {
+                let mut thresholds_table =
+                    "| Action threshold | Weight |\n|------------------|--------|\n".to_string();
+                if let Some(weight) = key_management {
+                    thresholds_table = format!("{}| Key management | {} |\n", thresholds_table, weight);
+                }
+                if let Some(weight) = deployment {
+                    thresholds_table =
+                        format!("{}| Deploy execution | {} |\n", thresholds_table, weight);
+                }
+                (
+                    format!(
+                        "Session code which sets the action thresholds of the account `{}`, leaving \
+                        its associated keys{} unchanged.",
+                        main_account_hash,
+                        if key_management.is_none() || deployment.is_none() {
+                            " and the other threshold"
+                        } else {
+                            ""
+                        }
+                    ),
+                    thresholds_table,
+                    "The total weight of the associated keys must meet the new thresholds, otherwise \
+                    the deploy will fail.",
+                    r#"Once the deploy has executed, query the account and check its `action_thresholds` match the
+configuration above:"#,
+                )
+            }
+            ContractMode::RevertToSingleKey { recovery_key } => {
+                let mut keys_table = format!(
+                    "| Associated key | Weight after recovery |\n|----------------|-----------------------|\n\
+                    | `{}` | 1 |\n",
+                    recovery_key.trim()
+                );
+                for account_hash in self.keys_removed_on_revert() {
+                    keys_table = format!(
+                        "{}| `{}` | Removed |\n",
+                        keys_table,
+                        account_hash.to_formatted_string()
+                    );
+                }
+                (
+                    format!(
+                        "Session code which returns the multisig account `{}` to the control of a \
+                        single key: both action thresholds are set to 1, the recovery key's weight \
+                        is set to 1 and every other configured associated key is removed.",
+                        main_account_hash
+                    ),
+                    format!(
+                        "{}\n| Action threshold | Weight after recovery |\n\
+                        |------------------|-----------------------|\n\
+                        | Key management | 1 |\n| Deploy execution | 1 |\n",
+                        keys_table
+                    ),
+                    "Keys which are already absent are skipped, so the deploy can be sent whatever \
+                    state the account has been left in.  Keep this project with the setup contract so \
+                    it is available if keys are lost.",
+                    r#"Once the deploy has executed, query the account and check the recovery key is its only
+associated key and both `action_thresholds` are 1:"#,
+                )
+            }
+        };
+
+        let signing_note = format!(
+            r#"The deploy must be sent from the account `{}` and signed by keys whose combined weight
+meets its key-management threshold.  If the main account's key alone doesn't, create the deploy with
+`casper-client make-deploy`, add the other signatures with `casper-client sign-deploy` and send it
+with `casper-client send-deploy`.  {}"#,
+            main_account_hash, constraint
+        );
+        (
+            summary,
+            configuration,
+            signing_note,
+            verify_note.to_string(),
+        )
+    }
+
+    /// Returns the README's tables of associated keys and action thresholds for the multisig
+    /// account setup.
+    fn readme_setup_configuration(&self) -> String {
+        let mut keys_table = String::new();
+        for (index, key) in self.associated_keys.iter().enumerate() {
+            let notes = match key.kind {
+                AssociatedKeyKind::Primary {
+                    remove_after_creation: true,
+                } => "Main account key; removed once the other keys are added",
+                AssociatedKeyKind::Primary {
+                    remove_after_creation: false,
+                } => "Main account key",
+                AssociatedKeyKind::Secondary => "",
+            };
+            keys_table = format!(
+                "{}| {} | {} | {} | {} | {} |\n",
+                keys_table,
+                index,
+                self.readme_account_hash(&key.account_hash),
+                self.key_label(&key.account_hash)
+                    .unwrap_or_default()
+                    .replace('|', "\\|"),
+                key.weight.value(),
+                notes
+            );
+        }
+
+        format!(
+            r#"| Index | Associated key | Label | Weight | Notes |
+|-------|----------------|-------|--------|-------|
+{keys_table}
+| Action threshold | Weight |
+|------------------|--------|
+| Key management | {km_weight} |
+| Deploy execution | {dp_weight} |
+"#,
+            keys_table = keys_table,
+            km_weight = self.key_management_weight.value(),
+            dp_weight = self.deployment_weight.value(),
+        )
+    }
+
+    /// Returns the account hash formatted for the README, linked to its page on the selected
+    /// network's block explorer if it has one.
+    fn readme_account_hash(&self, account_hash: &AccountHash) -> String {
+        let formatted = format!("`{}`", account_hash.to_formatted_string());
+        match self
+            .selected_network()
+            .and_then(|network| network.explorer_url.as_deref())
+        {
+            Some(explorer_url) => format!(
+                "[{}]({})",
+                formatted,
+                explorer::account_url(explorer_url, account_hash)
+            ),
+            None => formatted,
+        }
+    }
+
+    fn readme_testing_section(&self) -> String {
+        if !self.has_tests() {
+            return String::new();
+        }
+        format!(
+            r#"
+## Testing
+
+The `tests` crate executes the session code against the main account in an in-memory execution
+engine and checks the resulting associated keys and action thresholds match the configuration
+above.  {test_build_step}Then run:
+
+```console
+cd tests
+cargo test
+```
+"#,
+            test_build_step = if self.prebuilt_session_wasm.is_some() {
+                ""
+            } else {
+                "The contract must be built first as described above.  "
+            },
+        )
+    }
+
+    fn readme_ci_section(&self) -> String {
+        let provider = match self.ci_provider {
+            Some(provider) => provider,
+            None => return String::new(),
+        };
+        format!(
+            r#"
+## Continuous integration
+
+The {provider} workflow in `{path}` builds, strips and hashes
+the session Wasm on every push, and uploads it along with its Blake2b hashes.  Signers can compare
+those hashes with the Wasm they're asked to sign.
+"#,
+            provider = provider,
+            path = provider.workflow_path().display(),
+        )
+    }
+
+    fn readme_funding_section(&self) -> String {
+        let initial_transfer = match &self.initial_transfer {
+            Some(initial_transfer) => initial_transfer,
+            None => return String::new(),
+        };
+        format!(
+            r#"
+## Funding
+
+The main account must hold enough CSPR to pay for the setup deploy before it is sent.  Fund it with
+a transfer of {amount} motes from the account of `{source_secret_key}`, using any unique transfer
+ID:
+
+```console
+{transfer_command}
+```
+
+Check the transfer executed successfully before deploying.
+"#,
+            amount = initial_transfer.amount,
+            source_secret_key = initial_transfer.source_secret_key.display(),
+            transfer_command = initial_transfer
+                .transfer_args(&initial_transfer::EXAMPLE_PLACEHOLDERS)
+                .join(" \\\n    "),
+        )
+    }
+
+    fn readme_later_deploys_section(&self) -> String {
+        if self.contract_mode != ContractMode::Setup {
+            return String::new();
+        }
+        let steps = match self.plan() {
+            Ok(steps) if steps.len() > 1 && self.prebuilt_session_wasm.is_none() => steps,
+            _ => return String::new(),
+        };
+
+        let mut deploys_table = String::new();
+        for (index, step) in steps.iter().enumerate().skip(1) {
+            let signers = step
+                .signers
+                .iter()
+                .map(|signer| {
+                    format!(
+                        "`{}`",
+                        self.associated_keys[*signer]
+                            .account_hash
+                            .to_formatted_string()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("<br>");
+            deploys_table = format!(
+                "{}| {} | `{}{}` | `{}{}` | {} |\n",
+                deploys_table,
+                index + 1,
+                self.contract_dir_prefix(),
+                step_source_path(index).display(),
+                self.contract_dir_prefix(),
+                relative_wasm_path(&self.step_bin_name(index)).display(),
+                signers
+            );
+        }
+
+        format!(
+            r#"
+### Later deploys
+
+This configuration can't be reached in a single deploy signed by the main account's key alone,
+since every change to the keys or thresholds must be authorized by keys meeting the key-management
+threshold in force at that point.  Once the deploy above has executed, send each of the following
+deploys from the main account in order, checking each executed successfully before sending the
+next.
+
+| Deploy | Source | Session Wasm | Signed by |
+|--------|--------|--------------|-----------|
+{deploys_table}
+Each deploy must be signed by every listed key.  Create it signed by the main account's key, add
+the signature of each other key in turn, then send it:
+
+```console
+casper-client make-deploy --chain-name <CHAIN_NAME> --secret-key <MAIN_ACCOUNT_SECRET_KEY_PATH> \
+    --payment-amount <PAYMENT_AMOUNT> --session-path <SESSION_WASM> --output deploy.json
+casper-client sign-deploy --input deploy.json --secret-key <OTHER_SECRET_KEY_PATH> \
+    --output signed_deploy.json
+casper-client send-deploy --node-address <NODE_ADDRESS> --input signed_deploy.json
+```
+"#,
+            deploys_table = deploys_table
+        )
+    }
+
+    fn readme_named_keys_section(&self) -> String {
+        if self.named_keys.is_empty() {
+            return String::new();
+        }
+        let mut section = "\n| Named key | Key |\n|-----------|-----|\n".to_string();
+        for named_key in &self.named_keys {
+            section = format!(
+                "{}| {} | `{}` |\n",
+                section,
+                named_key.name,
+                named_key.key.to_formatted_string()
+            );
+        }
+        if named_keys::any_urefs(&self.named_keys) {
+            section += "\nEach URef must already be accessible to the main account, otherwise the deploy will fail \
+                with a forged reference error.\n";
+        }
+        section
+    }
+
+    fn create_test_project(&self) -> Result<(), Error> {
+        let tests_dir = self.project_dir().join(test_project::TESTS_DIR);
+        if !self.has_tests() {
+            // Remove any test crate left by a previous generation, as it would test a different
+            // contract.
+            if tests_dir.exists() {
+                fs::remove_dir_all(&tests_dir).unwrap();
+            }
+            return Ok(());
+        }
+        let src_dir = tests_dir.join("src");
+        fs::create_dir_all(&src_dir).unwrap();
+
+        if let Some(host_target) = test_project::host_target() {
+            let cargo_config_dir = tests_dir.join(".cargo");
+            fs::create_dir_all(&cargo_config_dir).unwrap();
+            write_if_changed(
+                cargo_config_dir.join("config.toml"),
+                test_project::cargo_config_contents(&host_target).as_bytes(),
+            )
+            .unwrap();
+        }
+
+        write_if_changed(
+            tests_dir.join("Cargo.toml"),
+            test_project::cargo_toml_contents(
+                &self.casper_engine_test_support_dependency,
+                &self.casper_types_dependency,
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let deploys = self
+            .plan()?
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let wasm_path = if self.prebuilt_session_wasm.is_some() {
+                    format!("../{}.wasm", self.contract_name)
+                } else {
+                    format!(
+                        "../{}target/wasm32-unknown-unknown/release/{}.wasm",
+                        self.contract_dir_prefix(),
+                        self.step_bin_name(index)
+                    )
+                };
+                let signers = step
+                    .signers
+                    .iter()
+                    .map(|signer| self.associated_keys[*signer].account_hash)
+                    .collect();
+                (wasm_path, signers)
+            })
+            .collect();
+        let keys = self.keys_and_weights();
+        let config = TestConfig {
+            deploys,
+            keys: &keys,
+            remove_main_account: self.main_key_should_be_deleted(),
+            key_management_weight: self.key_management_weight.value(),
+            deployment_weight: self.deployment_weight.value(),
+            runtime_args: self.uses_runtime_args(),
+        };
+        write_if_changed(
+            src_dir.join("integration_tests.rs"),
+            test_project::integration_tests_rs_contents(&config).as_bytes(),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    /// Initializes a git repository in the project dir and commits the generated files, if enabled.
+    ///
+    /// `prebuilt_wasm` should be the bytes of the prebuilt session Wasm if one is being used.
+    fn create_git_repo(&self, prebuilt_wasm: Option<&[u8]>) -> Result<(), Error> {
+        if !self.init_git_repo {
+            return Ok(());
+        }
+        git_repo::init_and_commit(&self.project_dir(), &self.commit_message(prebuilt_wasm))
+    }
+
+    /// Returns the message for the project's initial commit, summarizing the configuration.
+    fn commit_message(&self, prebuilt_wasm: Option<&[u8]>) -> String {
+        format!(
+            "Generate {} for {}\n\n{}",
+            self.contract_mode,
+            self.contract_name,
+            self.configuration_summary(prebuilt_wasm)
+        )
+    }
+
+    /// Returns a summary of the configuration from which the session code is generated, as used
+    /// in the commit message and hashed into the provenance header.
+    fn configuration_summary(&self, prebuilt_wasm: Option<&[u8]>) -> String {
+        let mut summary = String::new();
+        match &self.contract_mode {
+            ContractMode::Setup => {
+                summary += "Associated keys:\n";
+                for key in &self.associated_keys {
+                    let notes = match key.kind {
+                        AssociatedKeyKind::Primary {
+                            remove_after_creation: true,
+                        } => " (main account key, removed after setup)",
+                        AssociatedKeyKind::Primary {
+                            remove_after_creation: false,
+                        } => " (main account key)",
+                        AssociatedKeyKind::Secondary => "",
+                    };
+                    summary = format!(
+                        "{}  {} weight {}{}\n",
+                        summary,
+                        key.account_hash.to_formatted_string(),
+                        key.weight.value(),
+                        notes
+                    );
+                }
+                summary = format!(
+                    "{}\nKey management threshold: {}\nDeployment threshold: {}\n\n",
+                    summary,
+                    self.key_management_weight.value(),
+                    self.deployment_weight.value()
+                );
+                if self.idempotent {
+                    summary += "Error handling: idempotent\n\n";
+                }
+                if self.runtime_args_template {
+                    summary += "Session code: generic, configured via runtime args\n\n";
+                }
+            }
+            ContractMode::RemoveKeys { account_hashes } => {
+                summary = format!(
+                    "{}Account: {}\n\nKeys to remove:\n",
+                    summary,
+                    self.associated_keys[0].account_hash.to_formatted_string()
+                );
+                for account_hash in account_hashes {
+                    summary = format!("{}  {}\n", summary, account_hash.trim());
+                }
+                summary.push('\n');
+            }
+            ContractMode::RevertToSingleKey { recovery_key } => {
+                summary = format!(
+                    "{}Account: {}\n\nRecovery key: {}\n\nKeys to remove:\n",
+                    summary,
+                    self.associated_keys[0].account_hash.to_formatted_string(),
+                    recovery_key.trim()
+                );
+                for account_hash in self.keys_removed_on_revert() {
+                    summary = format!("{}  {}\n", summary, account_hash.to_formatted_string());
+                }
+                summary.push('\n');
+            }
+            ContractMode::UpdateThresholds {
+                key_management,
+                deployment,
+            } => {
+                summary = format!(
+                    "{}Account: {}\n\n",
+                    summary,
+                    self.associated_keys[0].account_hash.to_formatted_string()
+                );
+                if let Some(weight) = key_management {
+                    summary = format!("{}Key management threshold: {}\n", summary, weight);
+                }
+                if let Some(weight) = deployment {
+                    summary = format!("{}Deployment threshold: {}\n", summary, weight);
+                }
+                summary.push('\n');
+            }
+        }
+        if !self.named_keys.is_empty() {
+            summary += "Named keys:\n";
+            for named_key in &self.named_keys {
+                summary = format!(
+                    "{}  {}: {}\n",
+                    summary,
+                    named_key.name,
+                    named_key.key.to_formatted_string()
+                );
+            }
+            summary.push('\n');
+        }
+        match prebuilt_wasm {
+            Some(wasm_bytes) => format!(
+                "{}Session Wasm: prebuilt, Blake2b hash {:x}\n",
+                summary,
+                hash::hash(wasm_bytes)
+            ),
+            None => format!(
+                "{}Target: {}\nToolchain: {}\ncasper-contract: {}\ncasper-types: {}\n",
+                summary,
+                self.codegen_target,
+                self.toolchain,
+                self.casper_contract_dependency,
+                self.casper_types_dependency
+            ),
+        }
+    }
+
+    /// Returns the summary of the configuration included in an offline signing bundle, given the
+    /// bytes of the session Wasm being deployed.
+    pub(super) fn bundle_summary(&self, wasm_bytes: &[u8]) -> String {
+        self.configuration_summary(self.prebuilt_session_wasm.as_ref().map(|_| wasm_bytes))
+    }
+
+    /// Returns the hex-encoded Blake2b hash of the configuration, as recorded in the provenance
+    /// header of the generated sources.
+    pub(super) fn config_hash(&self) -> Result<String, Error> {
+        if self.associated_keys.is_empty() {
+            return Err(Error::NoKeys);
+        }
+        Ok(format!(
+            "{:x}",
+            hash::hash(self.configuration_summary(None).as_bytes())
+        ))
+    }
+
+    /// Returns the formatted account hashes referenced by the configuration with their labels, for
+    /// the provenance header: the associated keys, followed by any others the contract mode names.
+    fn provenance_keys(&self) -> Vec<(String, Option<String>)> {
+        let mut account_hashes = self
+            .associated_keys
+            .iter()
+            .map(|key| key.account_hash)
+            .collect::<Vec<_>>();
+        let mode_keys = match &self.contract_mode {
+            ContractMode::RemoveKeys { account_hashes } => account_hashes.clone(),
+            ContractMode::RevertToSingleKey { recovery_key } => vec![recovery_key.clone()],
+            ContractMode::Setup | ContractMode::UpdateThresholds { .. } => vec![],
+        };
+        for formatted_account_hash in mode_keys {
+            if let Ok(account_hash) = AccountHash::from_formatted_str(formatted_account_hash.trim())
+            {
+                if !account_hashes.contains(&account_hash) {
+                    account_hashes.push(account_hash);
+                }
+            }
+        }
+        account_hashes
+            .into_iter()
+            .map(|account_hash| {
+                let label = self.key_label(&account_hash).map(str::to_string);
+                (account_hash.to_formatted_string(), label)
+            })
+            .collect()
+    }
+
+    /// Returns the label of `account_hash`, if it has one.
+    fn key_label(&self, account_hash: &AccountHash) -> Option<&str> {
+        self.key_labels
+            .iter()
+            .find(|(labelled, _)| labelled == account_hash)
+            .map(|(_, label)| label.as_str())
+    }
+
+    /// Returns the formatted account hash followed by its label, if it has one, for the comment
+    /// above the key's constants in the session code.
+    fn key_comment(&self, account_hash: &AccountHash) -> String {
+        match self.key_label(account_hash) {
+            Some(label) => format!("{} ({})", account_hash.to_formatted_string(), label),
+            None => account_hash.to_formatted_string(),
+        }
+    }
+
+    fn create_readme(&self) -> Result<(), Error> {
+        let project_dir = self.project_dir();
+        write_if_changed(
+            project_dir.join("README.md"),
+            self.readme_contents().as_bytes(),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn project_dir(&self) -> PathBuf {
+        self.root_dir.join(&self.contract_name)
+    }
+
+    /// Returns the dir of the contract crate, which is the project dir unless the project was
+    /// scaffolded by cargo-casper.
+    fn contract_dir(&self) -> PathBuf {
+        self.project_dir().join(self.contract_dir_prefix())
+    }
+
+    /// Returns the path of the contract crate's dir relative to the project dir, followed by a `/`,
+    /// or an empty string if they're the same.
+    fn contract_dir_prefix(&self) -> String {
+        if self.cargo_casper_layout {
+            format!("{}/", cargo_casper::CONTRACT_DIR)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Returns the name of the binary holding the session code for the deploy at `index` of the
+    /// plan.
+    fn step_bin_name(&self, index: usize) -> String {
+        if index == 0 {
+            self.contract_name.clone()
+        } else {
+            format!("{}_step_{}", self.contract_name, index + 1)
+        }
+    }
+
+    /// Returns the path of the session Wasm to be deployed, relative to the project dir.
+    fn relative_session_path(&self) -> PathBuf {
+        if self.prebuilt_session_wasm.is_some() {
+            PathBuf::from(format!("{}.wasm", self.contract_name))
+        } else {
+            PathBuf::from(self.contract_dir_prefix()).join(relative_wasm_path(&self.contract_name))
+        }
+    }
+}
+
+/// Returns the path of the given binary's compiled Wasm, relative to the project dir.
+fn relative_wasm_path(bin_name: &str) -> PathBuf {
+    PathBuf::from("target")
+        .join("wasm32-unknown-unknown")
+        .join("release")
+        .join(format!("{}.wasm", bin_name))
+}
+
+/// Returns the path of the source of the session code for the deploy at `index` of the plan,
+/// relative to the project dir.
+fn step_source_path(index: usize) -> PathBuf {
+    // Used in `Cargo.toml` too, so always uses forward slashes.
+    if index == 0 {
+        PathBuf::from("src/main.rs")
+    } else {
+        PathBuf::from(format!("src/bin/step_{}.rs", index + 1))
+    }
+}
+
+/// Returns `{a, b, ...}` as formatted by rustfmt, where the list follows `prefix` at the given
+/// indent and is followed by a single `,` or `;`.
+pub(super) fn braced_list(items: &[&str], prefix: &str, indent: usize) -> String {
+    let single_line = format!("{{{}}}", items.join(", "));
+    if indent + prefix.len() + single_line.len() < 100 {
+        return single_line;
+    }
+    format!(
+        "{{\n{inner}{items},\n{outer}}}",
+        inner = " ".repeat(indent + 4),
+        items = items.join(", "),
+        outer = " ".repeat(indent)
+    )
+}
+
+/// Returns `true` if `wasm-opt` can be run.
+pub(super) fn wasm_opt_available() -> bool {
+    Command::new("wasm-opt")
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Returns `true` if `wasm-strip` can be run.
+pub(super) fn wasm_strip_available() -> bool {
+    Command::new("wasm-strip")
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Writes the `.cargo/config.toml` making `wasm32-unknown-unknown` the default build target.
+fn write_cargo_config(project_dir: &Path) {
+    let cargo_config_dir = project_dir.join(".cargo");
+    fs::create_dir_all(&cargo_config_dir).unwrap();
+
+    write_if_changed(
+        cargo_config_dir.join("config.toml"),
+        br#"[build]
+target = "wasm32-unknown-unknown"
+"#,
+    )
+    .unwrap();
+}
+
+/// The settings for a single compilation of the contract, owned by the compile worker thread.
+struct CompileJob {
+    project_dir: PathBuf,
+    /// The names of the binaries built, one per deploy of the plan, in order.
+    bin_names: Vec<String>,
+    /// The paths of the binaries' sources, relative to the project dir.
+    source_paths: Vec<PathBuf>,
+    toolchain: String,
+    compile_backend: CompileBackend,
+    run_wasm_opt: bool,
+    run_wasm_strip: bool,
+    max_deploy_size: u32,
+    /// How long the cargo commands may run in total before being killed.
+    timeout: Option<Duration>,
+    reproducible_build: bool,
+    vendor_dependencies: bool,
+    /// Whether the project has a test crate, whose dependencies are vendored too.
+    has_tests: bool,
+    shared_target_dir: Option<PathBuf>,
+    use_sccache: bool,
+    /// Set to kill the cargo commands and stop before the next step.
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Why a compile job stopped without producing the Wasm.
+enum CompileFailure {
+    /// A step failed for the given reason.
+    Failed(String),
+    /// The cargo commands were still running when the timeout expired, and were killed.
+    TimedOut(Duration),
+    /// The job was cancelled, and any cargo command running was killed.
+    Cancelled,
+}
+
+impl From<String> for CompileFailure {
+    fn from(reason: String) -> Self {
+        CompileFailure::Failed(reason)
+    }
+}
+
+impl CompileJob {
+    /// Compiles the contract, streaming output via `sender`, followed by the final event.
+    fn run_and_report(&self, sender: &Sender<CompileEvent>) {
+        let event = match self.run(sender) {
+            Ok(()) => CompileEvent::Succeeded {
+                wasm_path: self.wasm_paths().remove(0),
+            },
+            Err(CompileFailure::Failed(reason)) => CompileEvent::Failed { reason },
+            Err(CompileFailure::TimedOut(timeout)) => CompileEvent::TimedOut { timeout },
+            Err(CompileFailure::Cancelled) => CompileEvent::Cancelled,
+        };
+        let _ = sender.send(event);
+    }
+
+    /// Compiles the contract, streaming output via `sender`.
+    ///
+    /// Returns `Err` if any step failed, the Wasm wasn't produced, the timeout expired or the job
+    /// was cancelled.
+    fn run(&self, sender: &Sender<CompileEvent>) -> Result<(), CompileFailure> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let docker_image = match &self.compile_backend {
+            CompileBackend::Host => None,
+            CompileBackend::Docker { image: Some(image) } => Some(image.clone()),
+            CompileBackend::Docker { image: None } => {
+                let image = compile_backend::default_image_tag(&self.toolchain);
+                build_docker_image_if_missing(&self.project_dir, &image, sender)?;
+                Some(image)
+            }
+        };
+        self.check_cancelled(sender)?;
+        if let Some(target_dir) = &self.shared_target_dir {
+            fs::create_dir_all(target_dir)
+                .map_err(|error| format!("failed to create {}: {}", target_dir.display(), error))?;
+        }
+        let use_sccache = self.use_sccache && docker_image.is_none() && {
+            let available = build_cache::sccache_available();
+            if !available {
+                send_output(
+                    sender,
+                    "Not using sccache: failed to run it.  Is it installed?",
+                );
+            }
+            available
+        };
+        if self.use_sccache && docker_image.is_some() {
+            send_output(
+                sender,
+                "Not using sccache: unsupported with the Docker backend.",
+            );
+        }
+
+        let cargo_command = |args: &[&str]| match &docker_image {
+            Some(image) => compile_backend::cargo_command(
+                &self.project_dir,
+                image,
+                self.shared_target_dir.as_deref(),
+                &compile_progress::CARGO_PROGRESS_ENV,
+                args,
+            ),
+            None => {
+                let mut command = Command::new("cargo");
+                command
+                    .args(args)
+                    .envs(compile_progress::CARGO_PROGRESS_ENV.iter().copied())
+                    .current_dir(&self.project_dir);
+                build_cache::apply_to_host_command(
+                    &mut command,
+                    self.shared_target_dir.as_deref(),
+                    use_sccache,
+                );
+                command
+            }
+        };
+
+        if self.reproducible_build {
+            let _ = sender.send(CompileEvent::Progress(CompilePhase::FetchingDependencies));
+            let prepared = prepare_reproducible_build(
+                &self.project_dir,
+                self.vendor_dependencies,
+                self.has_tests,
+                &cargo_command,
+                deadline,
+                &self.cancelled,
+                sender,
+            )?;
+            if !prepared {
+                return Err(self.stopped(sender));
+            }
+            self.check_cancelled(sender)?;
+        }
+
+        let mut build_args = vec!["build", "--release"];
+        if self.reproducible_build {
+            build_args.extend(reproducible_build::BUILD_ARGS);
+        }
+        let mut command = cargo_command(&build_args);
+
+        send_output(
+            sender,
+            format!("Running {:?} in {}", command, self.project_dir.display()),
+        );
+        send_output(sender, "");
+
+        let status = run_streaming_output_until(
+            &mut command,
+            sender,
+            compile_progress::output_events,
+            deadline,
+            Some(&self.cancelled),
+        )
+        .map_err(|error| format!("failed to run cargo: {}", error))?;
+        let status = match status {
+            Some(status) => status,
+            None => return Err(self.stopped(sender)),
+        };
+        if !status.success() {
+            return Err(format!("cargo build failed: {}", status).into());
+        }
+        let wasm_paths = self.wasm_paths();
+        for (bin_name, wasm_path) in self.bin_names.iter().zip(&wasm_paths) {
+            // Copy the Wasm from the shared target dir to where it would be without one, so that
+            // the paths used by the README, Makefile and tests hold either way.
+            if let Some(target_dir) = &self.shared_target_dir {
+                let built_wasm_path = build_cache::wasm_path_in(target_dir, bin_name);
+                fs::create_dir_all(wasm_path.parent().unwrap()).unwrap();
+                fs::copy(&built_wasm_path, wasm_path).map_err(|error| {
+                    format!(
+                        "failed to copy {} to {}: {}",
+                        built_wasm_path.display(),
+                        wasm_path.display(),
+                        error
+                    )
+                })?;
+            }
+            // Cargo can exit successfully without producing the Wasm, e.g. if the crate is built
+            // as a library or for the wrong target.
+            if !wasm_path.is_file() {
+                return Err(format!(
+                    "cargo build succeeded, but {} was not produced",
+                    wasm_path.display()
+                )
+                .into());
+            }
+
+            if self.run_wasm_opt || self.run_wasm_strip {
+                self.check_cancelled(sender)?;
+                let _ = sender.send(CompileEvent::Progress(CompilePhase::Stripping));
+            }
+            if self.run_wasm_opt {
+                run_wasm_opt_in_place(wasm_path, sender);
+            }
+            if self.run_wasm_strip {
+                run_wasm_strip_in_place(wasm_path, sender);
+            }
+            if let Some(warning) = deploy_limits::size_warning(wasm_path, self.max_deploy_size) {
+                let _ = sender.send(CompileEvent::Warning(warning));
+            }
+        }
+
+        if self.reproducible_build {
+            let manifest_path = self.project_dir.join(reproducible_build::BUILD_MANIFEST);
+            let manifest = reproducible_build::build_manifest_contents(
+                &self.project_dir,
+                &self.toolchain,
+                &self.source_paths,
+                &self
+                    .bin_names
+                    .iter()
+                    .map(|bin_name| relative_wasm_path(bin_name))
+                    .collect::<Vec<_>>(),
+                self.run_wasm_opt,
+                self.run_wasm_strip,
+                self.vendor_dependencies,
+            );
+            write_if_changed(&manifest_path, manifest).unwrap();
+            send_output(sender, "");
+            send_output(sender, "Build manifest:");
+            send_output(sender, manifest_path.display());
+        }
+
+        send_output(sender, "");
+        send_output(sender, "Smart contract source code:");
+        for source_path in &self.source_paths {
+            send_output(sender, self.project_dir.join(source_path).display());
+        }
+        send_output(sender, "");
+        send_output(sender, "Compiled smart contract:");
+        for wasm_path in &wasm_paths {
+            send_output(sender, wasm_path.display());
+        }
+        Ok(())
+    }
+
+    /// Reports why a cargo command was killed, whether cancelled or timed out, returning the
+    /// corresponding failure.
+    fn stopped(&self, sender: &Sender<CompileEvent>) -> CompileFailure {
+        if self.cancelled.load(Ordering::SeqCst) {
+            send_output(sender, "");
+            send_output(sender, "Killed cargo as compilation was cancelled");
+            return CompileFailure::Cancelled;
+        }
+        let timeout = self.timeout.unwrap_or_default();
+        send_output(sender, "");
+        send_output(
+            sender,
+            format!(
+                "Killed cargo as compilation was still running after {}",
+                format_duration(timeout)
+            ),
+        );
+        CompileFailure::TimedOut(timeout)
+    }
+
+    /// Returns `Err` if the job has been cancelled, in which case the next step shouldn't start.
+    fn check_cancelled(&self, sender: &Sender<CompileEvent>) -> Result<(), CompileFailure> {
+        if !self.cancelled.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        send_output(sender, "");
+        send_output(sender, "Stopped as compilation was cancelled");
+        Err(CompileFailure::Cancelled)
+    }
+
+    /// Returns the paths of the compiled binaries, in the order they're to be deployed.
+    fn wasm_paths(&self) -> Vec<PathBuf> {
+        self.bin_names
+            .iter()
+            .map(|bin_name| build_cache::wasm_path_in(&self.project_dir.join("target"), bin_name))
+            .collect()
+    }
+}
+
+fn send_output<T: ToString>(sender: &Sender<CompileEvent>, line: T) {
+    let _ = sender.send(CompileEvent::Output(line.to_string()));
+}
+
+/// Sends a line which a command wrote to its stderr, but which was captured rather than streamed.
+fn send_stderr<T: ToString>(sender: &Sender<CompileEvent>, line: T) {
+    let _ = sender.send(CompileEvent::CommandOutput {
+        line: line.to_string(),
+        stream: OutputStream::Stderr,
+    });
+}
+
+/// Pins the dependencies in `Cargo.lock`, fetching them so that the build can run offline, and
+/// optionally vendors them.
+///
+/// Returns `Ok(false)` if `cargo fetch` was killed for still running at `deadline` or being
+/// `cancelled`, or `Err` with the reason if any step failed.  In either case the build should not
+/// proceed.
+fn prepare_reproducible_build(
+    project_dir: &Path,
+    vendor_dependencies: bool,
+    has_tests: bool,
+    cargo_command: &dyn Fn(&[&str]) -> Command,
+    deadline: Option<Instant>,
+    cancelled: &AtomicBool,
+    sender: &Sender<CompileEvent>,
+) -> Result<bool, String> {
+    // `cargo fetch` creates `Cargo.lock` if missing, but leaves an existing one's pins unchanged.
+    let mut command = cargo_command(&["fetch"]);
+    send_output(
+        sender,
+        format!("Running {:?} in {}", command, project_dir.display()),
+    );
+    let status = run_streaming_output_until(
+        &mut command,
+        sender,
+        compile_progress::output_events,
+        deadline,
+        Some(cancelled),
+    )
+    .map_err(|error| format!("failed to run cargo fetch: {}", error))?;
+    let status = match status {
+        Some(status) => status,
+        None => return Ok(false),
+    };
+    if !status.success() {
+        return Err(format!("cargo fetch failed: {}", status));
+    }
+
+    if vendor_dependencies {
+        send_output(sender, "");
+        send_output(
+            sender,
+            format!(
+                "Vendoring dependencies into {}",
+                project_dir.join(reproducible_build::VENDOR_DIR).display()
+            ),
+        );
+        let vendor_args = reproducible_build::vendor_args(has_tests);
+        let vendor_args = vendor_args.iter().map(String::as_str).collect::<Vec<_>>();
+        let lines =
+            reproducible_build::vendor_dependencies(project_dir, cargo_command(&vendor_args))?;
+        for line in lines {
+            send_stderr(sender, line);
+        }
+    }
+
+    send_output(sender, "");
+    Ok(true)
+}
+
+/// Builds the Docker image from the project's Dockerfile unless it already exists locally.
+///
+/// Returns `Err` with the reason if the build failed, in which case compilation should not
+/// proceed.
+fn build_docker_image_if_missing(
+    project_dir: &Path,
+    image: &str,
+    sender: &Sender<CompileEvent>,
+) -> Result<(), String> {
+    if compile_backend::image_exists(image) {
+        return Ok(());
+    }
+
+    let mut command = compile_backend::build_image_command(project_dir, image)?;
+    send_output(
+        sender,
+        format!("Building Docker image {}: {:?}", image, command),
+    );
+    send_output(sender, "");
+    let to_events = |line, stream| vec![CompileEvent::CommandOutput { line, stream }];
+    let status = run_streaming_output_until(&mut command, sender, to_events, None, None)
+        .map_err(|error| format!("failed to run docker: {}.  Is Docker installed?", error))?
+        .unwrap();
+    if !status.success() {
+        return Err(format!("docker build failed: {}", status));
+    }
+    send_output(sender, "");
+    Ok(())
+}
+
+/// Returns Makefile recipe lines which fail `target` with an example if any of the given variables
+/// is unset.
+fn makefile_variable_checks(target: &str, variables: &[(&str, &str)]) -> String {
+    let mut checks = String::new();
+    for (variable, example) in variables {
+        checks = format!(
+            "{}\t@test -n \"$({var})\" || {{ echo \"{var} must be set, e.g. make {target} \
+            {var}={example}\"; exit 1; }}\n",
+            checks,
+            var = variable,
+            target = target,
+            example = example
+        );
+    }
+    checks
+}
+
+/// Runs `command`, sending each line of its stdout and stderr as it arrives, and returns its exit
+/// status once it has finished.
+fn run_streaming_output<T: Send + 'static>(
+    command: &mut Command,
+    sender: &Sender<T>,
+    to_event: fn(String) -> T,
+) -> io::Result<ExitStatus> {
+    // Without a deadline or cancellation, the command always runs to completion.
+    run_streaming_output_until(
+        command,
+        sender,
+        move |line, _| vec![to_event(line)],
+        None,
+        None,
+    )
+    .map(Option::unwrap)
+}
+
+/// Runs `command`, sending the events `to_events` returns for each line of its stdout and stderr,
+/// and the stream it was written to, as it arrives, and returns its exit status once it has finished, or `None` if it was still
+/// running at `deadline` or once `cancelled` was set, and was killed.
+///
+/// A carriage return also ends a line, so that each redraw of a progress bar is a line of its own.
+fn run_streaming_output_until<T, F>(
+    command: &mut Command,
+    sender: &Sender<T>,
+    to_events: F,
+    deadline: Option<Instant>,
+    cancelled: Option<&AtomicBool>,
+) -> io::Result<Option<ExitStatus>>
+where
+    T: Send + 'static,
+    F: Fn(String, OutputStream) -> Vec<T> + Clone + Send + 'static,
+{
+    if deadline.is_some() || cancelled.is_some() {
+        make_process_group_leader(command);
+    }
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().unwrap();
+    let stdout_reader = BufReader::new(stdout);
+
+    let stderr = child.stderr.take().unwrap();
+    let stderr_reader = BufReader::new(stderr);
+
+    let sender_clone = sender.clone();
+    let to_events_clone = to_events.clone();
+    let stderr_thread = thread::spawn(move || {
+        for_each_line(stderr_reader, |line| {
+            for event in to_events_clone(line, OutputStream::Stderr) {
+                if let Err(error) = sender_clone.send(event) {
+                    println!("stopping sending stderr: {}", error);
+                    return false;
+                }
+            }
+            true
+        });
+    });
+
+    let sender_clone = sender.clone();
+    let stdout_thread = thread::spawn(move || {
+        for_each_line(stdout_reader, |line| {
+            for event in to_events(line, OutputStream::Stdout) {
+                if sender_clone.send(event).is_err() {
+                    println!("stopping sending stdout");
+                    return false;
+                }
+            }
+            true
+        });
+    });
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            || cancelled.is_some_and(|cancelled| cancelled.load(Ordering::SeqCst))
+        {
+            kill_process_tree(&mut child);
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    stdout_thread.join().unwrap();
+    stderr_thread.join().unwrap();
+    Ok(status)
+}
+
+/// Calls `on_line` with each line read from `reader`, ended by a newline, a carriage return or
+/// both, until it returns `false` or the reader is exhausted.
+///
+/// Only a newline ends an empty line, so e.g. a carriage return followed by a newline ends one.
+fn for_each_line<R: BufRead>(mut reader: R, mut on_line: impl FnMut(String) -> bool) {
+    let mut bytes = vec![];
+    loop {
+        bytes.clear();
+        match reader.read_until(b'\n', &mut bytes) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => (),
+        }
+        if bytes.last() == Some(&b'\n') {
+            bytes.pop();
+        }
+        for segment in bytes.split(|byte| *byte == b'\r') {
+            if segment.is_empty() && !bytes.is_empty() {
+                continue;
+            }
+            if !on_line(String::from_utf8_lossy(segment).into_owned()) {
+                return;
+            }
+        }
+    }
+}
+
+/// Makes `command` start a new process group, so that it can be killed along with any processes
+/// it spawns, e.g. cargo's rustc processes.
+#[cfg(unix)]
+fn make_process_group_leader(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(not(unix))]
+fn make_process_group_leader(_command: &mut Command) {}
+
+/// Kills `child` and all its descendants.
+fn kill_process_tree(child: &mut Child) {
+    let pid = child.id().to_string();
+    // Killing only the child would leave its descendants holding its output pipes open.
+    let killed_tree = if cfg!(windows) {
+        Command::new("taskkill")
+            .args(&["/T", "/F", "/PID", &pid])
+            .output()
+    } else {
+        Command::new("kill")
+            .args(&["-KILL", "--", &format!("-{}", pid)])
+            .output()
+    };
+    if !killed_tree.is_ok_and(|output| output.status.success()) {
+        let _ = child.kill();
+    }
+}
+
+/// Returns `duration` in whole minutes if possible, else in seconds, e.g. `30 minutes`.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        60 => "1 minute".to_string(),
+        _ if seconds.is_multiple_of(60) => format!("{} minutes", seconds / 60),
+        1 => "1 second".to_string(),
+        _ => format!("{} seconds", seconds),
+    }
+}
+
+/// Runs `wasm-opt -Oz` on the given Wasm file, overwriting it with the optimized version and
+/// reporting the change in size via `sender`.
+///
+/// A failure here isn't fatal; the unoptimized Wasm is left in place.
+fn run_wasm_opt_in_place(wasm_path: &Path, sender: &Sender<CompileEvent>) {
+    let mut command = Command::new("wasm-opt");
+    command.arg("-Oz").arg("-o").arg(wasm_path).arg(wasm_path);
+    shrink_in_place(command, "wasm-opt", "binaryen", wasm_path, sender);
+}
+
+/// Runs `wasm-strip` on the given Wasm file, removing its custom sections such as debug info, and
+/// reports the change in size via `sender`.
+///
+/// A failure here isn't fatal; the unstripped Wasm is left in place.
+fn run_wasm_strip_in_place(wasm_path: &Path, sender: &Sender<CompileEvent>) {
+    let mut command = Command::new("wasm-strip");
+    command.arg(wasm_path);
+    shrink_in_place(command, "wasm-strip", "wabt", wasm_path, sender);
+}
+
+/// Runs `command`, which rewrites the given Wasm file in place, reporting the change in size via
+/// `sender`.  `tool` is the command's name and `package` the package providing it.
+fn shrink_in_place(
+    mut command: Command,
+    tool: &str,
+    package: &str,
+    wasm_path: &Path,
+    sender: &Sender<CompileEvent>,
+) {
+    send_output(sender, "");
+    let size_before = match fs::metadata(wasm_path) {
+        Ok(metadata) => metadata.len(),
+        Err(error) => {
+            send_output(
+                sender,
+                format!(
+                    "Not running {}: failed to read {}: {}",
+                    tool,
+                    wasm_path.display(),
+                    error
+                ),
+            );
+            return;
+        }
+    };
+
+    send_output(sender, format!("Running {:?}", command));
+
+    match command.output() {
+        Ok(output) if output.status.success() => (),
+        Ok(output) => {
+            for line in String::from_utf8_lossy(&output.stderr).lines() {
+                send_stderr(sender, line);
+            }
+            send_output(sender, format!("{} failed: {}", tool, output.status));
+            return;
+        }
+        Err(error) => {
+            send_output(
+                sender,
+                format!(
+                    "Failed to run {}: {}.  Is {} installed?",
+                    tool, error, package
+                ),
+            );
+            return;
+        }
+    }
+
+    let size_after = fs::metadata(wasm_path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(size_before);
+    let saved = size_before.saturating_sub(size_after);
+    send_output(
+        sender,
+        format!(
+            "{} reduced the Wasm size from {} bytes to {} bytes (saved {} bytes, {:.1}%)",
+            tool,
+            size_before,
+            size_after,
+            saved,
+            saved as f64 * 100.0 / cmp::max(size_before, 1) as f64
+        ),
+    );
 }
@@ -0,0 +1,251 @@
+use std::process::Command;
+
+use casper_types::account::AccountHash;
+
+use super::{dependencies::DependencySource, prebuilt_session};
+
+/// The name of the directory holding the test crate, relative to the contract's project dir.
+pub(super) const TESTS_DIR: &str = "tests";
+
+/// The configuration which the generated integration test asserts against.
+pub(super) struct TestConfig<'a> {
+    /// The path of each deploy's session Wasm relative to the tests crate's manifest dir, and the
+    /// keys which sign the deploy, in the order they're sent.
+    pub(super) deploys: Vec<(String, Vec<AccountHash>)>,
+    /// The account hashes and weights of all associated keys, main key first.
+    pub(super) keys: &'a [(AccountHash, u8)],
+    pub(super) remove_main_account: bool,
+    pub(super) key_management_weight: u8,
+    pub(super) deployment_weight: u8,
+    /// Whether the session Wasm is the generic contract, and hence needs runtime args.
+    pub(super) runtime_args: bool,
+}
+
+/// Returns the host's target triple as reported by `rustc`, or `None` if that fails.
+pub(super) fn host_target() -> Option<String> {
+    let output = Command::new("rustc").arg("-vV").output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(str::to_string)
+}
+
+/// Returns the contents of the test crate's `.cargo/config.toml`.
+///
+/// The contract's project dir sets the default build target to `wasm32-unknown-unknown`, which
+/// would otherwise apply to the test crate too.
+pub(super) fn cargo_config_contents(host_target: &str) -> String {
+    format!(
+        r#"[build]
+target = "{}"
+"#,
+        host_target
+    )
+}
+
+/// Returns the contents of the test crate's `Cargo.toml`.
+pub(super) fn cargo_toml_contents(
+    casper_engine_test_support: &DependencySource,
+    casper_types: &DependencySource,
+) -> String {
+    format!(
+        r#"[package]
+name = "tests"
+version = "0.1.0"
+edition = "2018"
+
+[dev-dependencies]
+casper-engine-test-support = {}
+casper-types = {}
+
+[lib]
+path = "src/integration_tests.rs"
+bench = false
+doctest = false
+
+[features]
+default = ["casper-engine-test-support/test-support", "casper-types/std"]
+"#,
+        casper_engine_test_support.to_manifest_value(),
+        casper_types.to_manifest_value(),
+    )
+}
+
+/// Returns the contents of the test crate's `src/integration_tests.rs`.
+pub(super) fn integration_tests_rs_contents(config: &TestConfig) -> String {
+    let (main_account_hash, _) = config.keys[0];
+
+    let mut expected_keys = String::new();
+    for (index, (account_hash, weight)) in config.keys.iter().enumerate() {
+        if index == 0 && config.remove_main_account {
+            continue;
+        }
+        expected_keys = format!(
+            "{}            // {}\n            (AccountHash::new({:?}), {}),\n",
+            expected_keys,
+            account_hash.to_formatted_string(),
+            account_hash.value(),
+            weight
+        );
+    }
+
+    let mut deploys = String::new();
+    for (wasm_path, signers) in &config.deploys {
+        let signers = signers
+            .iter()
+            .map(|signer| format!("AccountHash::new({:?})", signer.value()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        deploys = format!(
+            "{}            ({:?}, vec![{}]),\n",
+            deploys, wasm_path, signers
+        );
+    }
+
+    let mut session_args = String::new();
+    if config.runtime_args {
+        let mut insert_arg = |name: String, value: String| {
+            session_args = format!(
+                "{}        args.insert({:?}, {}).unwrap();\n",
+                session_args, name, value
+            );
+        };
+        insert_arg(
+            prebuilt_session::ARG_KEY_COUNT.to_string(),
+            format!("{}u8", config.keys.len()),
+        );
+        for (index, (account_hash, weight)) in config.keys.iter().enumerate() {
+            insert_arg(
+                format!("{}{}", prebuilt_session::ARG_ACCOUNT_PREFIX, index),
+                format!("AccountHash::new({:?})", account_hash.value()),
+            );
+            insert_arg(
+                format!("{}{}", prebuilt_session::ARG_WEIGHT_PREFIX, index),
+                format!("{}u8", weight),
+            );
+        }
+        insert_arg(
+            prebuilt_session::ARG_REMOVE_MAIN_ACCOUNT.to_string(),
+            config.remove_main_account.to_string(),
+        );
+        insert_arg(
+            prebuilt_session::ARG_KEY_MANAGEMENT_THRESHOLD.to_string(),
+            format!("{}u8", config.key_management_weight),
+        );
+        insert_arg(
+            prebuilt_session::ARG_DEPLOYMENT_THRESHOLD.to_string(),
+            format!("{}u8", config.deployment_weight),
+        );
+    }
+
+    format!(
+        r#"#[cfg(test)]
+mod tests {{
+    use std::{{collections::BTreeMap, fs, path::PathBuf}};
+
+    use casper_engine_test_support::{{
+        internal::{{
+            DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, ARG_AMOUNT,
+            DEFAULT_ACCOUNT_ADDR, DEFAULT_PAYMENT, DEFAULT_RUN_GENESIS_REQUEST,
+        }},
+        MINIMUM_ACCOUNT_CREATION_BALANCE,
+    }};
+    use casper_types::{{
+        account::AccountHash, runtime_args, system::mint, RuntimeArgs, U512,
+    }};
+
+    // {main_account_formatted_hash}
+    #[rustfmt::skip]
+    const MAIN_ACCOUNT_HASH: AccountHash = AccountHash::new({main_account_hash:?});
+
+    const EXPECTED_KEY_MANAGEMENT_THRESHOLD: u8 = {km_weight};
+    const EXPECTED_DEPLOYMENT_THRESHOLD: u8 = {dp_weight};
+
+    /// Each deploy's session Wasm, relative to this crate, and the keys which sign it, in order.
+    #[rustfmt::skip]
+    fn deploys() -> Vec<(&'static str, Vec<AccountHash>)> {{
+        vec![
+{deploys}        ]
+    }}
+
+    #[rustfmt::skip]
+    fn expected_associated_keys() -> BTreeMap<AccountHash, u8> {{
+        vec![
+{expected_keys}        ]
+        .into_iter()
+        .collect()
+    }}
+
+    fn session_args() -> RuntimeArgs {{
+        #[allow(unused_mut)]
+        let mut args = RuntimeArgs::new();
+{session_args}        args
+    }}
+
+    #[test]
+    fn should_set_up_multisig_account() {{
+        let mut builder = InMemoryWasmTestBuilder::default();
+        builder.run_genesis(&*DEFAULT_RUN_GENESIS_REQUEST);
+
+        // Create the main account by funding it from the genesis account.
+        let transfer_request = ExecuteRequestBuilder::transfer(
+            *DEFAULT_ACCOUNT_ADDR,
+            runtime_args! {{
+                mint::ARG_TARGET => MAIN_ACCOUNT_HASH,
+                mint::ARG_AMOUNT => U512::from(MINIMUM_ACCOUNT_CREATION_BALANCE),
+                mint::ARG_ID => Option::<u64>::None,
+            }},
+        )
+        .build();
+        builder.exec(transfer_request).expect_success().commit();
+
+        // Execute each deploy's setup session code as the main account, in order.
+        for (index, (wasm_path, signers)) in deploys().into_iter().enumerate() {{
+            let wasm_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(wasm_path);
+            let module_bytes = fs::read(&wasm_path).unwrap_or_else(|error| {{
+                panic!(
+                    "failed to read {{}}: {{}}.  Has the contract been built?",
+                    wasm_path.display(),
+                    error
+                )
+            }});
+            let deploy = DeployItemBuilder::new()
+                .with_address(MAIN_ACCOUNT_HASH)
+                .with_session_bytes(module_bytes, session_args())
+                .with_empty_payment_bytes(runtime_args! {{ ARG_AMOUNT => *DEFAULT_PAYMENT }})
+                .with_authorization_keys(&signers)
+                .with_deploy_hash([index as u8 + 1; 32])
+                .build();
+            let setup_request = ExecuteRequestBuilder::new().push_deploy(deploy).build();
+            builder.exec(setup_request).expect_success().commit();
+        }}
+
+        // Check the account now has exactly the configured associated keys and thresholds.
+        let account = builder
+            .get_account(MAIN_ACCOUNT_HASH)
+            .expect("should have main account");
+        let actual_associated_keys = account
+            .associated_keys()
+            .map(|(account_hash, weight)| (*account_hash, weight.value()))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(actual_associated_keys, expected_associated_keys());
+        assert_eq!(
+            account.action_thresholds().key_management().value(),
+            EXPECTED_KEY_MANAGEMENT_THRESHOLD
+        );
+        assert_eq!(
+            account.action_thresholds().deployment().value(),
+            EXPECTED_DEPLOYMENT_THRESHOLD
+        );
+    }}
+}}
+"#,
+        deploys = deploys,
+        main_account_formatted_hash = main_account_hash.to_formatted_string(),
+        main_account_hash = main_account_hash.value(),
+        km_weight = config.key_management_weight,
+        dp_weight = config.deployment_weight,
+        expected_keys = expected_keys,
+        session_args = session_args,
+    )
+}
@@ -0,0 +1,163 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::plan::Target;
+
+/// How serious a problem found in the configuration is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    /// The contract can't be generated, or the account would be locked.
+    Error,
+    /// The contract works, but probably not as intended.
+    Warning,
+}
+
+/// A problem found in the configuration by [`validate_configuration`](super::validate_configuration).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(super) fn error<T: Display>(message: T) -> Self {
+        ValidationIssue {
+            severity: Severity::Error,
+            message: message.to_string(),
+        }
+    }
+
+    fn warning<T: Display>(message: T) -> Self {
+        ValidationIssue {
+            severity: Severity::Warning,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(formatter, "Error: {}", self.message),
+            Severity::Warning => write!(formatter, "Warning: {}", self.message),
+        }
+    }
+}
+
+/// Returns the problems with the weights and thresholds of `target`, judged by the keys left on the
+/// account once it's set up.
+pub(super) fn check_weights(target: &Target) -> Vec<ValidationIssue> {
+    let key_management_threshold = u32::from(target.key_management_threshold);
+    let deployment_threshold = u32::from(target.deployment_threshold);
+    // The keys left on the account as `(index, weight)` pairs, the main key being index 0.
+    let remaining = target
+        .weights
+        .iter()
+        .map(|weight| u32::from(*weight))
+        .enumerate()
+        .skip(if target.remove_main_key { 1 } else { 0 })
+        .collect::<Vec<_>>();
+    let total_weight: u32 = remaining.iter().map(|(_, weight)| weight).sum();
+    let whose = if target.remove_main_key {
+        "the keys left once the main key is deleted"
+    } else {
+        "all the keys"
+    };
+
+    let mut issues = vec![];
+    if remaining.is_empty() {
+        issues.push(ValidationIssue::error(
+            "deleting the main key would leave the account with no keys at all",
+        ));
+        return issues;
+    }
+    if key_management_threshold > total_weight {
+        issues.push(ValidationIssue::error(format!(
+            "the key-management threshold of {} is unreachable, as the total weight of {} is {}, \
+            so the account's keys could never be changed",
+            key_management_threshold, whose, total_weight
+        )));
+    }
+    if deployment_threshold > total_weight {
+        issues.push(ValidationIssue::error(format!(
+            "the deploy-execution threshold of {} is unreachable, as the total weight of {} is {}, \
+            so the account could never send a deploy",
+            deployment_threshold, whose, total_weight
+        )));
+    }
+    if deployment_threshold > key_management_threshold {
+        issues.push(ValidationIssue::error(format!(
+            "the deploy-execution threshold of {} is higher than the key-management threshold of \
+            {}, which the network doesn't allow",
+            deployment_threshold, key_management_threshold
+        )));
+    }
+
+    for (index, _) in remaining.iter().filter(|(_, weight)| *weight == 0) {
+        issues.push(ValidationIssue::warning(format!(
+            "{} has a weight of 0, so counts towards neither threshold",
+            key_name(*index)
+        )));
+    }
+    if remaining.len() == 1 {
+        issues.push(ValidationIssue::warning(
+            "only one key is left on the account, so it isn't a multisig account",
+        ));
+        return issues;
+    }
+    let alone_meeting = |threshold: u32| {
+        key_names(
+            remaining
+                .iter()
+                .filter(|(_, weight)| *weight > 0 && *weight >= threshold)
+                .map(|(index, _)| *index),
+        )
+    };
+    if let Some(names) = alone_meeting(key_management_threshold) {
+        issues.push(ValidationIssue::warning(format!(
+            "the key-management threshold can be met by a single key, as {} alone can change the \
+            associated keys and thresholds",
+            names
+        )));
+    } else if let Some(names) = alone_meeting(deployment_threshold) {
+        issues.push(ValidationIssue::warning(format!(
+            "the deploy-execution threshold can be met by a single key, as {} alone can send \
+            deploys",
+            names
+        )));
+    }
+    if key_management_threshold <= total_weight {
+        let indispensable = key_names(
+            remaining
+                .iter()
+                .filter(|(_, weight)| total_weight - weight < key_management_threshold)
+                .map(|(index, _)| *index),
+        );
+        if let Some(names) = indispensable {
+            issues.push(ValidationIssue::warning(format!(
+                "losing {} would leave the key-management threshold unreachable, so the account's \
+                keys could never be changed again",
+                names
+            )));
+        }
+    }
+    issues
+}
+
+/// Returns the name the key at `index` is referred to by, matching its position in the list.
+fn key_name(index: usize) -> String {
+    match index {
+        0 => "the main key".to_string(),
+        _ => format!("key {}", index + 1),
+    }
+}
+
+/// Returns the names of the keys at `indices` as a list, e.g. "the main key, key 2 or key 3", or
+/// `None` if there are none.
+fn key_names<I: Iterator<Item = usize>>(indices: I) -> Option<String> {
+    let mut names = indices.map(key_name).collect::<Vec<_>>();
+    let last = names.pop()?;
+    if names.is_empty() {
+        return Some(last);
+    }
+    Some(format!("{} or {}", names.join(", "), last))
+}
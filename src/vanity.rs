@@ -0,0 +1,240 @@
+//! Generating key pairs whose account hash starts with a chosen hex prefix, by brute force.
+//!
+//! Each extra hex digit multiplies the expected number of attempts by 16, so the prefix length,
+//! the number of worker threads and the search's duration are all capped.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use casper_node::crypto::AsymmetricKeyExt;
+use casper_types::{crypto::AsymmetricType, PublicKey, SecretKey};
+
+use super::VanityEvent;
+
+/// The longest prefix which can be searched for.  Eight hex digits take around 4 billion attempts
+/// on average.
+pub(super) const MAX_PREFIX_LENGTH: usize = 8;
+
+/// The longest a search may run before giving up.
+pub(super) const MAX_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// How often progress is reported.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The number of keys each worker generates between checks of whether to stop.
+const BATCH_SIZE: u64 = 256;
+
+/// The names of the files the key pair is saved in, as written by `casper-client keygen`.
+const SECRET_KEY_FILE: &str = "secret_key.pem";
+const PUBLIC_KEY_FILE: &str = "public_key.pem";
+const PUBLIC_KEY_HEX_FILE: &str = "public_key_hex";
+
+/// The algorithm of the generated key pair.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyAlgorithm {
+    Ed25519,
+    Secp256k1,
+}
+
+impl KeyAlgorithm {
+    fn generate(&self) -> Result<SecretKey, String> {
+        match self {
+            KeyAlgorithm::Ed25519 => SecretKey::generate_ed25519(),
+            KeyAlgorithm::Secp256k1 => SecretKey::generate_secp256k1(),
+        }
+        .map_err(|error| format!("failed to generate a key: {}", error))
+    }
+}
+
+impl Display for KeyAlgorithm {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            KeyAlgorithm::Ed25519 => write!(formatter, "Ed25519"),
+            KeyAlgorithm::Secp256k1 => write!(formatter, "secp256k1"),
+        }
+    }
+}
+
+/// Returns the lowercase hex prefix to search for, given with or without the `account-hash-`
+/// prefix.
+pub(super) fn parse_prefix(prefix: &str) -> Result<String, String> {
+    let prefix = prefix.trim().to_lowercase();
+    let prefix = prefix
+        .strip_prefix("account-hash-")
+        .unwrap_or(&prefix)
+        .to_string();
+    if prefix.is_empty() {
+        return Err("the prefix must not be empty".to_string());
+    }
+    if !prefix.chars().all(|char| char.is_ascii_hexdigit()) {
+        return Err("the prefix must only contain hex digits".to_string());
+    }
+    if prefix.len() > MAX_PREFIX_LENGTH {
+        return Err(format!(
+            "the prefix must be at most {} hex digits long",
+            MAX_PREFIX_LENGTH
+        ));
+    }
+    Ok(prefix)
+}
+
+/// Returns the number of keys which must be generated on average to find `prefix`.
+pub(super) fn expected_attempts(prefix: &str) -> u64 {
+    16_u64.pow(prefix.len() as u32)
+}
+
+/// Returns the most worker threads a search may use: all but one of the available cores, leaving
+/// one for the UI.
+pub(super) fn max_threads() -> usize {
+    thread::available_parallelism()
+        .map(|cores| cores.get().saturating_sub(1).max(1))
+        .unwrap_or(1)
+}
+
+/// Searches for a key pair whose account hash starts with `prefix`, using `threads` workers for at
+/// most `time_limit`, and saves the first found in `output_dir`.
+///
+/// The progress is streamed through the returned channel, with [`VanityEvent::Found`] or
+/// [`VanityEvent::Stopped`] sent last.  Dropping the receiver stops the search.
+pub(super) fn search(
+    prefix: String,
+    algorithm: KeyAlgorithm,
+    threads: usize,
+    time_limit: Duration,
+    output_dir: PathBuf,
+) -> Receiver<VanityEvent> {
+    let (sender, receiver) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let attempts = Arc::new(AtomicU64::new(0));
+    let (found_sender, found_receiver) = mpsc::channel();
+
+    for _ in 0..threads {
+        let prefix = prefix.clone();
+        let stop = Arc::clone(&stop);
+        let attempts = Arc::clone(&attempts);
+        let found_sender = found_sender.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                for _ in 0..BATCH_SIZE {
+                    let result = algorithm.generate().map(|secret_key| {
+                        let public_key = PublicKey::from(&secret_key);
+                        let matches =
+                            hex::encode(public_key.to_account_hash().value()).starts_with(&prefix);
+                        (secret_key, public_key, matches)
+                    });
+                    match result {
+                        Ok((secret_key, public_key, true)) => {
+                            stop.store(true, Ordering::Relaxed);
+                            let _ = found_sender.send(Ok((secret_key, public_key)));
+                            return;
+                        }
+                        Ok(_) => (),
+                        Err(error) => {
+                            stop.store(true, Ordering::Relaxed);
+                            let _ = found_sender.send(Err(error));
+                            return;
+                        }
+                    }
+                }
+                attempts.fetch_add(BATCH_SIZE, Ordering::Relaxed);
+            }
+        });
+    }
+    drop(found_sender);
+
+    thread::spawn(move || {
+        let start = Instant::now();
+        let final_event = loop {
+            match found_receiver.recv_timeout(PROGRESS_INTERVAL) {
+                Ok(Ok((secret_key, public_key))) => {
+                    break match save(&secret_key, &public_key, &output_dir) {
+                        Ok(()) => VanityEvent::Found {
+                            account_hash: public_key.to_account_hash().to_formatted_string(),
+                            public_key: public_key.to_hex(),
+                            dir: output_dir,
+                        },
+                        Err(reason) => VanityEvent::Stopped { reason },
+                    };
+                }
+                Ok(Err(reason)) => break VanityEvent::Stopped { reason },
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    break VanityEvent::Stopped {
+                        reason: "the workers stopped unexpectedly".to_string(),
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => (),
+            }
+
+            let elapsed = start.elapsed();
+            let attempts = attempts.load(Ordering::Relaxed);
+            let progress = VanityEvent::Progress {
+                attempts,
+                keys_per_second: (attempts as f64 / elapsed.as_secs_f64()) as u64,
+            };
+            if sender.send(progress).is_err() {
+                stop.store(true, Ordering::Relaxed);
+                return;
+            }
+            if elapsed >= time_limit {
+                break VanityEvent::Stopped {
+                    reason: format!(
+                        "no match was found within the time limit of {} seconds, after {} attempts",
+                        time_limit.as_secs(),
+                        attempts
+                    ),
+                };
+            }
+        };
+        stop.store(true, Ordering::Relaxed);
+        let _ = sender.send(final_event);
+    });
+
+    receiver
+}
+
+/// Returns the path of the first of the key files which already exists in `dir`, if any.
+pub(super) fn existing_key_file(dir: &Path) -> Option<PathBuf> {
+    [SECRET_KEY_FILE, PUBLIC_KEY_FILE, PUBLIC_KEY_HEX_FILE]
+        .iter()
+        .map(|file_name| dir.join(file_name))
+        .find(|path| path.exists())
+}
+
+/// Writes the key pair to `dir`, refusing to overwrite existing files.
+fn save(secret_key: &SecretKey, public_key: &PublicKey, dir: &Path) -> Result<(), String> {
+    if let Some(existing) = existing_key_file(dir) {
+        return Err(format!(
+            "a matching key was found, but wasn't saved as {} already exists",
+            existing.display()
+        ));
+    }
+
+    let secret_key_path = dir.join(SECRET_KEY_FILE);
+    let public_key_path = dir.join(PUBLIC_KEY_FILE);
+    let public_key_hex_path = dir.join(PUBLIC_KEY_HEX_FILE);
+    fs::create_dir_all(dir)
+        .map_err(|error| format!("failed to create {}: {}", dir.display(), error))?;
+    secret_key
+        .to_file(&secret_key_path)
+        .map_err(|error| format!("failed to write {}: {}", secret_key_path.display(), error))?;
+    public_key
+        .to_file(&public_key_path)
+        .map_err(|error| format!("failed to write {}: {}", public_key_path.display(), error))?;
+    fs::write(&public_key_hex_path, public_key.to_hex()).map_err(|error| {
+        format!(
+            "failed to write {}: {}",
+            public_key_hex_path.display(),
+            error
+        )
+    })
+}
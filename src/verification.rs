@@ -0,0 +1,108 @@
+use std::fmt::{self, Display, Formatter};
+
+use casper_types::{account::AccountHash, PublicKey};
+
+use super::account_state;
+
+/// The outcome of comparing an account on the network against the configuration.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Verification {
+    /// The account's associated keys, their weights and its action thresholds all match.
+    Verified,
+    /// The account differs from the configuration in the given ways, e.g. `the deployment
+    /// threshold is 1 rather than 2`.
+    Mismatch { mismatches: Vec<String> },
+}
+
+impl Verification {
+    pub(super) fn new(mismatches: Vec<String>) -> Self {
+        if mismatches.is_empty() {
+            Verification::Verified
+        } else {
+            Verification::Mismatch { mismatches }
+        }
+    }
+}
+
+impl Display for Verification {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Verification::Verified => write!(
+                formatter,
+                "VERIFIED: the account's associated keys and thresholds match the configuration"
+            ),
+            Verification::Mismatch { mismatches } => {
+                write!(formatter, "MISMATCH: {}", mismatches.join("; "))
+            }
+        }
+    }
+}
+
+/// The associated keys and action thresholds the setup deploy should leave the account with.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct ExpectedAccount {
+    pub(super) associated_keys: Vec<(AccountHash, u8)>,
+    pub(super) key_management_threshold: u8,
+    pub(super) deployment_threshold: u8,
+}
+
+/// Returns the differences between the associated keys and thresholds of the given account on
+/// the network and those expected, as reported by the node at `node_address`.
+pub(super) fn verify(
+    node_address: &str,
+    account: &PublicKey,
+    expected: &ExpectedAccount,
+) -> Result<Vec<String>, String> {
+    let account = account_state::fetch(node_address, account)?;
+    let mut mismatches = vec![];
+
+    for (account_hash, weight) in &expected.associated_keys {
+        match account.weight_of(account_hash) {
+            Some(actual_weight) if actual_weight == *weight => (),
+            Some(actual_weight) => mismatches.push(format!(
+                "{} has weight {} rather than {}",
+                account_hash.to_formatted_string(),
+                actual_weight,
+                weight
+            )),
+            None => mismatches.push(format!(
+                "{} isn't an associated key",
+                account_hash.to_formatted_string()
+            )),
+        }
+    }
+    for (account_hash, _) in &account.associated_keys {
+        if expected
+            .associated_keys
+            .iter()
+            .all(|(expected, _)| expected != account_hash)
+        {
+            mismatches.push(format!(
+                "{} is unexpectedly an associated key",
+                account_hash.to_formatted_string()
+            ));
+        }
+    }
+
+    for (name, actual, expected_threshold) in &[
+        (
+            "key-management",
+            account.key_management_threshold,
+            expected.key_management_threshold,
+        ),
+        (
+            "deployment",
+            account.deployment_threshold,
+            expected.deployment_threshold,
+        ),
+    ] {
+        if actual != expected_threshold {
+            mismatches.push(format!(
+                "the {} threshold is {} rather than {}",
+                name, actual, expected_threshold
+            ));
+        }
+    }
+
+    Ok(mismatches)
+}
@@ -0,0 +1,97 @@
+use std::{fs, path::Path};
+
+use casper_node::crypto::AsymmetricKeyExt;
+use casper_types::{crypto::AsymmetricType, PublicKey, SecretKey};
+use serde_json::Value;
+
+/// The suffix of the file stem of a secret key downloaded from Casper Wallet, which is preceded by
+/// the account's name, e.g. `Account 1_secret_key.pem`.
+const SECRET_KEY_SUFFIX: &str = "secret_key";
+
+/// An account read from a Casper Wallet export.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct WalletAccount {
+    pub(super) public_key: PublicKey,
+    /// The account's name in the wallet, if the export records one.
+    pub(super) label: Option<String>,
+}
+
+/// Returns `true` if the file at `path` is a Casper Wallet export: a list of accounts, or a
+/// downloaded secret key.
+pub(super) fn is_wallet_export(path: &Path) -> bool {
+    match read_json(path) {
+        Some(export) => accounts_json(&export).is_some(),
+        None => SecretKey::from_file(path).is_ok(),
+    }
+}
+
+/// Returns the accounts in the Casper Wallet export at `path`.
+///
+/// An account list export is JSON, holding an array of accounts or an object with an `accounts`
+/// array, where each account has a hex-encoded `publicKey` and a `name`.  A downloaded secret key
+/// is PEM-encoded, and named after its account, e.g. `Account 1_secret_key.pem`; only its public
+/// key is kept.
+pub(super) fn read(path: &Path) -> Result<Vec<WalletAccount>, String> {
+    let export = match read_json(path) {
+        Some(export) => export,
+        None => {
+            let secret_key = SecretKey::from_file(path).map_err(|error| {
+                format!("it's neither an account list nor a secret key: {}", error)
+            })?;
+            return Ok(vec![WalletAccount {
+                public_key: PublicKey::from(&secret_key),
+                label: secret_key_label(path),
+            }]);
+        }
+    };
+
+    let accounts = accounts_json(&export).ok_or("it holds no list of accounts")?;
+    accounts
+        .iter()
+        .enumerate()
+        .map(|(index, account)| {
+            let public_key = account["publicKey"]
+                .as_str()
+                .or_else(|| account["public_key"].as_str())
+                .ok_or_else(|| format!("account {} has no public key", index + 1))?;
+            let public_key = PublicKey::from_hex(public_key.trim()).map_err(|error| {
+                format!(
+                    "failed to parse the public key of account {}: {}",
+                    index + 1,
+                    error
+                )
+            })?;
+            let label = account["name"]
+                .as_str()
+                .or_else(|| account["label"].as_str())
+                .map(str::trim)
+                .filter(|label| !label.is_empty() && !label.contains('\n'))
+                .map(str::to_string);
+            Ok(WalletAccount { public_key, label })
+        })
+        .collect()
+}
+
+/// Returns the array of accounts in an account list export.
+fn accounts_json(export: &Value) -> Option<&Vec<Value>> {
+    export
+        .as_array()
+        .or_else(|| export["accounts"].as_array())
+        .filter(|accounts| accounts.iter().all(Value::is_object))
+}
+
+/// Parses the file at `path` as JSON, or returns `None` if it isn't.
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Returns the account name which precedes `_secret_key` in the file stem of a downloaded secret
+/// key, if there is one.
+fn secret_key_label(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let label = stem
+        .strip_suffix(SECRET_KEY_SUFFIX)?
+        .trim_end_matches(['_', '-', ' '])
+        .trim();
+    Some(label.to_string()).filter(|label| !label.is_empty())
+}
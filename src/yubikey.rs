@@ -0,0 +1,260 @@
+//! Signing with Ed25519 keys held in a YubiKey's PIV applet.
+//!
+//! Ed25519 keys need YubiKey firmware 5.7 or later.  PIV offers no secp256k1 keys, so those can't
+//! be held on a YubiKey.  The applet is driven by APDU commands sent via PC/SC, which needs the
+//! `yubikey` feature; without it, opening the card fails with an error.
+
+use casper_types::{crypto::AsymmetricType, PublicKey, Signature};
+
+use super::remote_signer::RemoteSigner;
+
+/// Returns a key's algorithm, origin and public key.  A YubiKey extension, from firmware 5.3.
+const INS_GET_METADATA: u8 = 0xf7;
+const INS_VERIFY: u8 = 0x20;
+const INS_GENERAL_AUTHENTICATE: u8 = 0x87;
+
+/// The reference of the PIV application PIN.
+const PIN_REFERENCE: u8 = 0x80;
+/// The length the PIN is padded to with `0xff`.
+const PIN_LENGTH: usize = 8;
+
+/// The PIV algorithm ID of Ed25519.
+const ALGORITHM_ED25519: u8 = 0xe0;
+
+/// The metadata tags of the algorithm and the public key, and the public key's inner tag.
+const TAG_ALGORITHM: u8 = 0x01;
+const TAG_PUBLIC_KEY: u8 = 0x04;
+const TAG_POINT: u8 = 0x86;
+
+/// The tags of GENERAL AUTHENTICATE's dynamic authentication template and its fields.
+const TAG_TEMPLATE: u8 = 0x7c;
+const TAG_RESPONSE: u8 = 0x82;
+const TAG_CHALLENGE: u8 = 0x81;
+
+/// A key in a slot of a YubiKey's PIV applet.
+#[derive(Clone)]
+pub struct YubiKeySigner {
+    /// The slot holding the key, e.g. `0x9c` for the digital signature slot.
+    pub slot: u8,
+    /// The PIV PIN, needed only to sign.
+    pin: Option<String>,
+}
+
+impl YubiKeySigner {
+    /// The PIV slot intended for signing keys, used by default.
+    pub const SIGNATURE_SLOT: u8 = 0x9c;
+
+    pub fn new(slot: u8, pin: Option<&str>) -> Self {
+        YubiKeySigner {
+            slot,
+            pin: pin.map(str::to_string),
+        }
+    }
+
+    /// Returns the Ed25519 public key in the slot, as read from its metadata.
+    fn read_public_key(&self, card: &Card) -> Result<PublicKey, String> {
+        let metadata = card.transmit(INS_GET_METADATA, 0, self.slot, &[])?;
+        let algorithm = find_tlv(&metadata, TAG_ALGORITHM)
+            .and_then(|value| value.first().copied())
+            .ok_or_else(|| format!("slot {:02x} holds no key", self.slot))?;
+        if algorithm != ALGORITHM_ED25519 {
+            return Err(format!(
+                "slot {:02x} holds a key with PIV algorithm {:#04x} rather than Ed25519",
+                self.slot, algorithm
+            ));
+        }
+        let point = find_tlv(&metadata, TAG_PUBLIC_KEY)
+            .and_then(|public_key| find_tlv(public_key, TAG_POINT))
+            .ok_or("the key's metadata holds no public key")?;
+        PublicKey::ed25519_from_bytes(point)
+            .map_err(|error| format!("invalid public key: {}", error))
+    }
+}
+
+impl RemoteSigner for YubiKeySigner {
+    fn description(&self) -> String {
+        format!("YubiKey PIV slot {:02x}", self.slot)
+    }
+
+    fn public_key(&self) -> Result<PublicKey, String> {
+        self.read_public_key(&Card::open()?)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, String> {
+        let card = Card::open()?;
+        // Checks the slot holds an Ed25519 key before the PIN is tried.
+        self.read_public_key(&card)?;
+
+        let pin = self.pin.as_deref().ok_or("the PIN is needed to sign")?;
+        if pin.is_empty() || pin.len() > PIN_LENGTH {
+            return Err(format!("the PIN must be 1 to {} bytes long", PIN_LENGTH));
+        }
+        let mut padded_pin = pin.as_bytes().to_vec();
+        padded_pin.resize(PIN_LENGTH, 0xff);
+        card.transmit(INS_VERIFY, 0, PIN_REFERENCE, &padded_pin)?;
+
+        let mut fields = vec![TAG_RESPONSE, 0];
+        fields.extend(tlv(TAG_CHALLENGE, message));
+        let response = card.transmit(
+            INS_GENERAL_AUTHENTICATE,
+            ALGORITHM_ED25519,
+            self.slot,
+            &tlv(TAG_TEMPLATE, &fields),
+        )?;
+        let signature = find_tlv(&response, TAG_TEMPLATE)
+            .and_then(|template| find_tlv(template, TAG_RESPONSE))
+            .ok_or("the YubiKey returned no signature")?;
+        Signature::ed25519_from_bytes(signature)
+            .map_err(|error| format!("invalid signature: {}", error))
+    }
+}
+
+/// Returns the BER-TLV encoding of `value` with the given tag.
+fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    match value.len() {
+        length if length < 0x80 => encoded.push(length as u8),
+        length if length <= 0xff => encoded.extend_from_slice(&[0x81, length as u8]),
+        length => {
+            encoded.push(0x82);
+            encoded.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+    }
+    encoded.extend_from_slice(value);
+    encoded
+}
+
+/// Returns the value of the first BER-TLV with the given single-byte tag in `data`.
+fn find_tlv(mut data: &[u8], tag: u8) -> Option<&[u8]> {
+    while data.len() >= 2 {
+        let (length, header_length) = match data[1] {
+            0x81 => (usize::from(*data.get(2)?), 3),
+            0x82 => (
+                usize::from(u16::from_be_bytes([*data.get(2)?, *data.get(3)?])),
+                4,
+            ),
+            length => (usize::from(length), 2),
+        };
+        let value = data.get(header_length..header_length + length)?;
+        if data[0] == tag {
+            return Some(value);
+        }
+        data = &data[header_length + length..];
+    }
+    None
+}
+
+#[cfg(feature = "yubikey")]
+use transport::Card;
+
+#[cfg(feature = "yubikey")]
+mod transport {
+    use pcsc::{Context, Protocols, Scope, ShareMode, MAX_BUFFER_SIZE};
+
+    const INS_SELECT: u8 = 0xa4;
+    const INS_GET_RESPONSE: u8 = 0xc0;
+
+    /// The application ID of the PIV applet.
+    const PIV_AID: [u8; 5] = [0xa0, 0x00, 0x00, 0x03, 0x08];
+
+    const SW_OK: u16 = 0x9000;
+    /// The first byte of the status word when more response data is available.
+    const SW1_MORE_DATA: u8 = 0x61;
+    /// The first byte of the status word when the PIN is wrong, with the retries left in the
+    /// second byte's lower nibble.
+    const SW1_WRONG_PIN: u8 = 0x63;
+    const SW_PIN_BLOCKED: u16 = 0x6983;
+    const SW_SECURITY_STATUS_NOT_SATISFIED: u16 = 0x6982;
+    const SW_INS_NOT_SUPPORTED: u16 = 0x6d00;
+
+    /// The PIV applet of a YubiKey connected via PC/SC.
+    pub(super) struct Card(pcsc::Card);
+
+    impl Card {
+        /// Connects to the first YubiKey and selects its PIV applet.
+        pub(super) fn open() -> Result<Self, String> {
+            let context = Context::establish(Scope::User)
+                .map_err(|error| format!("failed to connect to the PC/SC service: {}", error))?;
+            let readers = context
+                .list_readers_owned()
+                .map_err(|error| format!("failed to list smart card readers: {}", error))?;
+            let reader = readers
+                .iter()
+                .find(|reader| reader.to_string_lossy().to_lowercase().contains("yubi"))
+                .ok_or("no YubiKey is connected")?;
+            let card = context
+                .connect(reader, ShareMode::Shared, Protocols::ANY)
+                .map_err(|error| format!("failed to connect to the YubiKey: {}", error))?;
+            let card = Card(card);
+            card.transmit(INS_SELECT, 0x04, 0x00, &PIV_AID)?;
+            Ok(card)
+        }
+
+        /// Sends the command APDU for the given instruction and returns the response data,
+        /// fetching any remainder with GET RESPONSE.
+        pub(super) fn transmit(
+            &self,
+            ins: u8,
+            p1: u8,
+            p2: u8,
+            data: &[u8],
+        ) -> Result<Vec<u8>, String> {
+            let mut apdu = vec![0, ins, p1, p2, data.len() as u8];
+            apdu.extend_from_slice(data);
+            let mut response_data = vec![];
+            loop {
+                let mut buffer = [0; MAX_BUFFER_SIZE];
+                let response = self
+                    .0
+                    .transmit(&apdu, &mut buffer)
+                    .map_err(|error| format!("failed to talk to the YubiKey: {}", error))?;
+                if response.len() < 2 {
+                    return Err("the YubiKey returned a truncated response".to_string());
+                }
+                let (data, status) = response.split_at(response.len() - 2);
+                response_data.extend_from_slice(data);
+                match (status[0], status[1]) {
+                    (SW1_MORE_DATA, remaining) => {
+                        apdu = vec![0, INS_GET_RESPONSE, 0, 0, remaining];
+                    }
+                    (SW1_WRONG_PIN, retries) => {
+                        return Err(format!(
+                            "the PIN is wrong, with {} tries left",
+                            retries & 0x0f
+                        ))
+                    }
+                    (sw1, sw2) => {
+                        return match u16::from_be_bytes([sw1, sw2]) {
+                            SW_OK => Ok(response_data),
+                            SW_PIN_BLOCKED => Err("the PIN is blocked".to_string()),
+                            SW_SECURITY_STATUS_NOT_SATISFIED => {
+                                Err("the YubiKey refused, e.g. as it wasn't touched in time"
+                                    .to_string())
+                            }
+                            SW_INS_NOT_SUPPORTED => Err(
+                                "the YubiKey's firmware is too old; 5.7 or later is needed for \
+                                Ed25519 keys"
+                                    .to_string(),
+                            ),
+                            status => Err(format!("the YubiKey returned status {:#06x}", status)),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "yubikey"))]
+struct Card;
+
+#[cfg(not(feature = "yubikey"))]
+impl Card {
+    fn open() -> Result<Self, String> {
+        Err("this build doesn't support YubiKeys; rebuild with `--features yubikey`".to_string())
+    }
+
+    fn transmit(&self, _ins: u8, _p1: u8, _p2: u8, _data: &[u8]) -> Result<Vec<u8>, String> {
+        unreachable!("a card can't be opened without the yubikey feature")
+    }
+}